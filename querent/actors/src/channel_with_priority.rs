@@ -196,6 +196,13 @@ impl<T> Drop for Receiver<T> {
 }
 
 impl<T> Receiver<T> {
+	/// Number of messages waiting in the channel, regardless of their priority.
+	pub fn len(&self) -> usize {
+		self.low_priority_rx.len() +
+			self.high_priority_rx.len() +
+			usize::from(self.pending_low_priority_message.is_some())
+	}
+
 	pub fn is_empty(&self) -> bool {
 		self.low_priority_rx.is_empty() &&
 			self.pending_low_priority_message.is_none() &&
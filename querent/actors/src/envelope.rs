@@ -20,6 +20,7 @@ use std::{any::Any, fmt};
 
 use async_trait::async_trait;
 use tokio::sync::oneshot;
+use tracing::{Instrument, Span};
 
 use crate::{
 	actor::DeferableReplyHandler, scheduler::NoAdvanceTimeGuard, Actor, ActorContext,
@@ -37,6 +38,8 @@ use crate::{
 pub struct Envelope<A> {
 	handler_envelope: Box<dyn EnvelopeT<A>>,
 	_no_advance_time_guard: Option<NoAdvanceTimeGuard>,
+	/// Span the message is handled in. Disabled unless the sender waits for the reply.
+	span: Span,
 }
 
 impl<A: Actor> Envelope<A> {
@@ -61,7 +64,8 @@ impl<A: Actor> Envelope<A> {
 		actor: &mut A,
 		ctx: &ActorContext<A>,
 	) -> Result<(), ActorExitStatus> {
-		self.handler_envelope.handle_message(actor, ctx).await?;
+		let span = self.span.clone();
+		self.handler_envelope.handle_message(actor, ctx).instrument(span).await?;
 		Ok(())
 	}
 }
@@ -137,6 +141,7 @@ where
 pub(crate) fn wrap_in_envelope<A, M>(
 	msg: M,
 	no_advance_time_guard: Option<NoAdvanceTimeGuard>,
+	span: Span,
 ) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
 where
 	A: DeferableReplyHandler<M>,
@@ -147,6 +152,7 @@ where
 	let envelope = Envelope {
 		handler_envelope: Box::new(handler_envelope),
 		_no_advance_time_guard: no_advance_time_guard,
+		span,
 	};
 	(envelope, response_rx)
 }
@@ -39,6 +39,7 @@ pub mod channel_with_priority;
 mod command;
 mod envelope;
 mod messagebus;
+pub mod metrics;
 mod observation;
 mod registry;
 pub(crate) mod scheduler;
@@ -30,6 +30,7 @@ use std::{
 use async_trait::async_trait;
 use common::metrics::IntCounter;
 use tokio::sync::oneshot;
+use tracing::Span;
 
 use crate::{
 	channel_with_priority::{Receiver, Sender, TrySendError},
@@ -188,7 +189,7 @@ impl<A: Actor> MessageBus<A> {
 		A: DeferableReplyHandler<M>,
 		M: fmt::Debug + Send + 'static,
 	{
-		let (envelope, response_rx) = self.wrap_in_envelope(message);
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::none());
 		self.inner.tx.try_send_low_priority(envelope).map_err(|err| {
 			match err {
 				TrySendError::Disconnected => TrySendError::Disconnected,
@@ -202,7 +203,11 @@ impl<A: Actor> MessageBus<A> {
 		Ok(response_rx)
 	}
 
-	fn wrap_in_envelope<M>(&self, message: M) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
+	fn wrap_in_envelope<M>(
+		&self,
+		message: M,
+		span: Span,
+	) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
 	where
 		A: DeferableReplyHandler<M>,
 		M: fmt::Debug + Send + 'static,
@@ -212,7 +217,7 @@ impl<A: Actor> MessageBus<A> {
 			.scheduler_client_opt
 			.as_ref()
 			.map(|scheduler_client| scheduler_client.no_advance_time_guard());
-		wrap_in_envelope(message, guard)
+		wrap_in_envelope(message, guard, span)
 	}
 
 	/// Sends a message to the actor owning the associated inbox.
@@ -229,9 +234,19 @@ impl<A: Actor> MessageBus<A> {
 		A: DeferableReplyHandler<M>,
 		M: fmt::Debug + Send + 'static,
 	{
-		let (envelope, response_rx) = self.wrap_in_envelope(message);
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::none());
+		self.send_envelope_with_backpressure_counter(envelope, backpressure_micros_counter_opt)
+			.await?;
+		Ok(response_rx)
+	}
+
+	async fn send_envelope_with_backpressure_counter(
+		&self,
+		envelope: Envelope<A>,
+		backpressure_micros_counter_opt: Option<&IntCounter>,
+	) -> Result<(), SendError> {
 		match self.inner.tx.try_send_low_priority(envelope) {
-			Ok(()) => Ok(response_rx),
+			Ok(()) => Ok(()),
 			Err(TrySendError::Full(envelope)) => {
 				if let Some(backpressure_micros_counter) = backpressure_micros_counter_opt {
 					let now = Instant::now();
@@ -241,7 +256,7 @@ impl<A: Actor> MessageBus<A> {
 				} else {
 					self.inner.tx.send_low_priority(envelope).await?;
 				}
-				Ok(response_rx)
+				Ok(())
 			},
 			Err(TrySendError::Disconnected) => Err(SendError::Disconnected),
 		}
@@ -255,7 +270,7 @@ impl<A: Actor> MessageBus<A> {
 		A: DeferableReplyHandler<M>,
 		M: fmt::Debug + Send + 'static,
 	{
-		let (envelope, response_rx) = self.wrap_in_envelope(message);
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::none());
 		self.inner.tx.send_high_priority(envelope)?;
 		Ok(response_rx)
 	}
@@ -269,7 +284,7 @@ impl<A: Actor> MessageBus<A> {
 		A: DeferableReplyHandler<M>,
 		M: fmt::Debug + Send + 'static,
 	{
-		let (envelope, response_rx) = self.wrap_in_envelope(message);
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::none());
 		match priority {
 			Priority::High => self.inner.tx.send_high_priority(envelope)?,
 			Priority::Low => {
@@ -301,6 +316,9 @@ impl<A: Actor> MessageBus<A> {
 	/// - the amount spent waiting in the queue,
 	/// - the amount spent processing the message.
 	///
+	/// The message is handled within the caller's current span, so that the work done on behalf
+	/// of a request shows up in the request's trace.
+	///
 	/// From an actor context, use the `ActorContext::ask` method instead.
 	pub async fn ask_with_backpressure_counter<M, T>(
 		&self,
@@ -311,12 +329,11 @@ impl<A: Actor> MessageBus<A> {
 		A: DeferableReplyHandler<M, Reply = T>,
 		M: fmt::Debug + Send + 'static,
 	{
-		let resp = self
-			.send_message_with_backpressure_counter(message, backpressure_micros_counter_opt)
-			.await;
-		resp.map_err(|_send_error| AskError::MessageNotDelivered)?
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::current());
+		self.send_envelope_with_backpressure_counter(envelope, backpressure_micros_counter_opt)
 			.await
-			.map_err(|_| AskError::ProcessMessageError)
+			.map_err(|_send_error| AskError::MessageNotDelivered)?;
+		response_rx.await.map_err(|_| AskError::ProcessMessageError)
 	}
 
 	/// Similar to `send_message`, except this method
@@ -329,9 +346,11 @@ impl<A: Actor> MessageBus<A> {
 		M: fmt::Debug + Send + 'static,
 		E: fmt::Debug,
 	{
-		self.send_message(message)
+		let (envelope, response_rx) = self.wrap_in_envelope(message, Span::current());
+		self.send_envelope_with_backpressure_counter(envelope, None)
 			.await
-			.map_err(|_send_error| AskError::MessageNotDelivered)?
+			.map_err(|_send_error| AskError::MessageNotDelivered)?;
+		response_rx
 			.await
 			.map_err(|_| AskError::ProcessMessageError)?
 			.map_err(AskError::from)
@@ -353,6 +372,10 @@ impl<A: Actor> Inbox<A> {
 		self.rx.is_empty()
 	}
 
+	pub(crate) fn len(&self) -> usize {
+		self.rx.len()
+	}
+
 	pub(crate) async fn recv(&self) -> Result<Envelope<A>, RecvError> {
 		self.rx.recv().await
 	}
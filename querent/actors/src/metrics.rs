@@ -0,0 +1,77 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use common::metrics::{gauge_vec, IntGaugeVec};
+use once_cell::sync::Lazy;
+
+pub struct ActorMetrics {
+	pub mailbox_depth: IntGaugeVec<1>,
+}
+
+impl Default for ActorMetrics {
+	fn default() -> Self {
+		ActorMetrics {
+			mailbox_depth: gauge_vec(
+				"mailbox_depth",
+				"Number of messages waiting in the mailboxes of the actors of each type.",
+				"actors",
+				&[],
+				["actor"],
+			),
+		}
+	}
+}
+
+pub static ACTOR_METRICS: Lazy<ActorMetrics> = Lazy::new(ActorMetrics::default);
+
+/// Strips module paths from a type name, e.g.
+/// `actors::supervisor::Supervisor<rian_core::indexer::Indexer>` becomes `Supervisor<Indexer>`.
+pub(crate) fn short_type_name(type_name: &str) -> String {
+	let mut short_name = String::with_capacity(type_name.len());
+	let mut segment = String::new();
+	let mut chars = type_name.chars().peekable();
+	while let Some(c) = chars.next() {
+		if c == ':' && chars.peek() == Some(&':') {
+			chars.next();
+			segment.clear();
+		} else if c.is_alphanumeric() || c == '_' {
+			segment.push(c);
+		} else {
+			short_name.push_str(&segment);
+			segment.clear();
+			short_name.push(c);
+		}
+	}
+	short_name.push_str(&segment);
+	short_name
+}
+
+#[cfg(test)]
+mod tests {
+	use super::short_type_name;
+
+	#[test]
+	fn test_short_type_name() {
+		assert_eq!(short_type_name("rian_core::indexer::Indexer"), "Indexer");
+		assert_eq!(
+			short_type_name("actors::supervisor::Supervisor<rian_core::indexer::Indexer>"),
+			"Supervisor<Indexer>"
+		);
+		assert_eq!(short_type_name("IngestorService"), "IngestorService");
+	}
+}
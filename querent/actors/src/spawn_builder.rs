@@ -19,7 +19,7 @@
 use std::time::Duration;
 
 use anyhow::Context;
-use common::metrics::IntCounter;
+use common::metrics::{IntCounter, IntGauge};
 use sync_wrapper::SyncWrapper;
 use tokio::sync::watch;
 use tracing::{debug, error, info};
@@ -27,6 +27,7 @@ use tracing::{debug, error, info};
 use crate::{
 	envelope::Envelope,
 	messagebus::{create_messagebus, Inbox},
+	metrics::{short_type_name, ACTOR_METRICS},
 	registry::{ActorJoinHandle, ActorRegistry},
 	scheduler::{NoAdvanceTimeGuard, SchedulerClient},
 	supervisor::Supervisor,
@@ -234,6 +235,9 @@ struct ActorExecutionEnv<A: Actor> {
 	actor: SyncWrapper<A>,
 	inbox: Inbox<A>,
 	ctx: ActorContext<A>,
+	mailbox_depth: IntGauge,
+	/// Share of this actor in the mailbox depth of its type.
+	reported_depth: i64,
 }
 
 impl<A: Actor> ActorExecutionEnv<A> {
@@ -253,6 +257,9 @@ impl<A: Actor> ActorExecutionEnv<A> {
 		&mut self,
 		mut envelope: Envelope<A>,
 	) -> Result<(), ActorExitStatus> {
+		let depth = self.inbox.len() as i64;
+		self.mailbox_depth.add(depth - self.reported_depth);
+		self.reported_depth = depth;
 		self.yield_and_check_if_killed().await?;
 		envelope.handle_message(self.actor.get_mut(), &self.ctx).await?;
 		Ok(())
@@ -345,6 +352,7 @@ impl<A: Actor> Drop for ActorExecutionEnv<A> {
 	// We rely on this object internally to fetch a post-mortem state,
 	// even in case of a panic.
 	fn drop(&mut self) {
+		self.mailbox_depth.sub(self.reported_depth);
 		self.ctx.observe(self.actor.get_mut());
 	}
 }
@@ -355,7 +363,16 @@ async fn actor_loop<A: Actor>(
 	no_advance_time_guard: NoAdvanceTimeGuard,
 	ctx: ActorContext<A>,
 ) -> ActorExitStatus {
-	let mut actor_env = ActorExecutionEnv { actor: SyncWrapper::new(actor), inbox, ctx };
+	let mailbox_depth = ACTOR_METRICS
+		.mailbox_depth
+		.with_label_values([&short_type_name(std::any::type_name::<A>())]);
+	let mut actor_env = ActorExecutionEnv {
+		actor: SyncWrapper::new(actor),
+		inbox,
+		ctx,
+		mailbox_depth,
+		reported_depth: 0,
+	};
 
 	let initialize_exit_status_res: Result<(), ActorExitStatus> = actor_env.initialize().await;
 	drop(no_advance_time_guard);
@@ -46,9 +46,11 @@ use crate::{
 	change::{compute_cluster_change_events, ClusterChange},
 	member::{
 		build_cluster_member, ClusterMember, NodeStateExt, GRPC_ADVERTISE_ADDR_KEY, READINESS_KEY,
-		READINESS_VALUE_NOT_READY, READINESS_VALUE_READY, SEMANTIC_METRICS_PREFIX,
-		SEMANTIC_PIPE_PREFIX,
+		READINESS_VALUE_NOT_READY, READINESS_VALUE_READY, SEMANTIC_DEFINITION_PREFIX,
+		SEMANTIC_METRICS_PREFIX, SEMANTIC_PIPE_PREFIX, SEMANTIC_PLACEMENT_PREFIX,
 	},
+	placement::{build_placement_node, Placement, PlacementNode},
+	session::{session_key, session_state, SessionKind, SessionOwner, SESSION_STATE_RUNNING},
 	types::NodeId,
 	ClusterNode,
 };
@@ -59,6 +61,9 @@ const GOSSIP_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
 	Duration::from_secs(1)
 };
 
+/// Number of gossip rounds to wait for the owner of a session no live node is known to run.
+const SESSION_OWNER_GOSSIP_ROUNDS: u32 = 5;
+
 const MARKED_FOR_DELETION_GRACE_PERIOD: usize = if cfg!(any(test, feature = "testsuite")) {
 	100 // ~ HEARTBEAT * 100 = 2.5 seconds.
 } else {
@@ -337,6 +342,156 @@ impl Cluster {
 		Ok(())
 	}
 
+	/// Returns the live nodes with their capacities, the pipelines they run and the placements
+	/// they published.
+	pub async fn placement_nodes(&self) -> Vec<PlacementNode> {
+		let chitchat = self.chitchat().await;
+		let chitchat_guard = chitchat.lock().await;
+		chitchat_guard
+			.live_nodes()
+			.filter_map(|chitchat_id| {
+				let node_state = chitchat_guard.node_state(chitchat_id)?;
+				Some(build_placement_node(chitchat_id, node_state))
+			})
+			.collect()
+	}
+
+	/// Publishes the placement of pipelines decided by this node, replacing the previous one.
+	pub async fn publish_placements(&self, placements: &BTreeMap<String, Placement>) {
+		let chitchat = self.chitchat().await;
+		let mut chitchat_guard = chitchat.lock().await;
+		let node_state = chitchat_guard.self_node_state();
+		let mut current_placement_keys: HashSet<String> = node_state
+			.iter_prefix(SEMANTIC_PLACEMENT_PREFIX)
+			.map(|(key, _)| key.to_string())
+			.collect();
+		for (task_id, placement) in placements {
+			let key = format!("{SEMANTIC_PLACEMENT_PREFIX}{task_id}");
+			current_placement_keys.remove(&key);
+			match serde_json::to_string(placement) {
+				Ok(value) => node_state.set(key, value),
+				Err(error) =>
+					warn!(task_id=%task_id, error=?error, "Failed to serialize placement."),
+			}
+		}
+		for obsolete_placement_key in current_placement_keys {
+			node_state.mark_for_deletion(&obsolete_placement_key);
+		}
+	}
+
+	/// Publishes the serialized definitions of the pipelines stored by this node, by pipeline id,
+	/// so that the other nodes can store them too.
+	pub async fn publish_pipeline_definitions(&self, definitions: &BTreeMap<String, String>) {
+		self.publish_replicated_values(SEMANTIC_DEFINITION_PREFIX, definitions).await
+	}
+
+	/// Returns the pipeline definitions published by the nodes of the cluster state, the dead
+	/// nodes it still holds included, by pipeline id.
+	pub async fn pipeline_definitions(&self) -> BTreeMap<String, String> {
+		self.replicated_values(SEMANTIC_DEFINITION_PREFIX).await
+	}
+
+	/// Publishes the values stored by this node under `{prefix}{id}`, keeping the ones published
+	/// before.
+	pub(crate) async fn publish_replicated_values(
+		&self,
+		prefix: &str,
+		values: &BTreeMap<String, String>,
+	) {
+		let chitchat = self.chitchat().await;
+		let mut chitchat_guard = chitchat.lock().await;
+		let node_state = chitchat_guard.self_node_state();
+		for (id, value) in values {
+			node_state.set(format!("{prefix}{id}"), value);
+		}
+	}
+
+	/// Returns the values published under `prefix` by the nodes of the cluster state, the dead
+	/// nodes it still holds included, by id.
+	pub(crate) async fn replicated_values(&self, prefix: &str) -> BTreeMap<String, String> {
+		let chitchat = self.chitchat().await;
+		let chitchat_guard = chitchat.lock().await;
+		let mut values = BTreeMap::new();
+		for chitchat_id in chitchat_guard.live_nodes().chain(chitchat_guard.dead_nodes()) {
+			let Some(node_state) = chitchat_guard.node_state(chitchat_id) else {
+				continue;
+			};
+			for (key, versioned_value) in node_state.iter_prefix(prefix) {
+				if versioned_value.tombstone.is_none() {
+					values
+						.entry(key[prefix.len()..].to_string())
+						.or_insert_with(|| versioned_value.value.clone());
+				}
+			}
+		}
+		values
+	}
+
+	/// Publishes the state of a session run by this node, so that the other nodes forward its
+	/// requests here.
+	pub async fn update_session_state(&self, kind: SessionKind, session_id: &str, state: &str) {
+		self.set_self_key_value(session_key(kind, session_id), state).await
+	}
+
+	/// Returns the node running a session. A live node running the session wins over the nodes
+	/// which stopped it, and this node wins over the others.
+	pub async fn session_owner(&self, kind: SessionKind, session_id: &str) -> SessionOwner {
+		let key = session_key(kind, session_id);
+		let chitchat = self.chitchat().await;
+		let chitchat_guard = chitchat.lock().await;
+		let self_state = chitchat_guard
+			.node_state(&self.self_chitchat_id)
+			.and_then(|node_state| session_state(node_state, &key));
+		if self_state == Some(SESSION_STATE_RUNNING) {
+			return SessionOwner::SelfNode;
+		}
+		let mut owner =
+			if self_state.is_some() { SessionOwner::Stopped } else { SessionOwner::Unknown };
+		for chitchat_id in chitchat_guard.live_nodes() {
+			if *chitchat_id == self.self_chitchat_id {
+				continue;
+			}
+			let Some(node_state) = chitchat_guard.node_state(chitchat_id) else {
+				continue;
+			};
+			match session_state(node_state, &key) {
+				Some(SESSION_STATE_RUNNING) => match node_state.grpc_advertise_addr() {
+					Ok(grpc_advertise_addr) =>
+						return SessionOwner::Node {
+							node_id: chitchat_id.node_id.clone().into(),
+							grpc_advertise_addr,
+						},
+					Err(error) => warn!(
+						node_id=%chitchat_id.node_id,
+						error=?error,
+						"Failed to read gRPC address of session owner."
+					),
+				},
+				Some(_) => owner = SessionOwner::Stopped,
+				None => {},
+			}
+		}
+		owner
+	}
+
+	/// Returns the node running a session like [`Cluster::session_owner`], waiting a few gossip
+	/// rounds when no live node is known to run it: a session started on another node a moment
+	/// ago may not have reached the cluster state of this node yet.
+	pub async fn wait_for_session_owner(
+		&self,
+		kind: SessionKind,
+		session_id: &str,
+	) -> SessionOwner {
+		for _ in 0..SESSION_OWNER_GOSSIP_ROUNDS {
+			let owner = self.session_owner(kind, session_id).await;
+			if owner != SessionOwner::Unknown {
+				return owner;
+			}
+			tokio::time::sleep(GOSSIP_INTERVAL).await;
+		}
+		self.session_owner(kind, session_id).await
+	}
+
 	pub async fn chitchat(&self) -> Arc<Mutex<Chitchat>> {
 		self.inner.read().await.chitchat_handle.chitchat()
 	}
@@ -477,3 +632,62 @@ pub struct ClusterSnapshot {
 	/// A complete snapshot of the Chitchat cluster state.
 	pub chitchat_state_snapshot: ClusterStateSnapshot,
 }
+
+#[cfg(test)]
+pub(crate) const TEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Joins a ready node with 4 cpus and 8GB of memory to a test cluster gossiping over `transport`.
+#[cfg(test)]
+pub(crate) async fn create_cluster_for_test(
+	node_id: &str,
+	port: u16,
+	peer_seed_addrs: Vec<String>,
+	transport: &chitchat::transport::ChannelTransport,
+) -> Cluster {
+	use crate::{
+		types::CpuCapacity, GenerationId, SEMANTIC_CPU_CAPACITY_KEY, SEMANTIC_MEMORY_CAPACITY_KEY,
+	};
+
+	let gossip_advertise_addr: SocketAddr = ([127, 0, 0, 1], port).into();
+	let self_node = ClusterMember {
+		node_id: node_id.into(),
+		generation_id: GenerationId::from(1),
+		is_ready: true,
+		gossip_advertise_addr,
+		grpc_advertise_addr: ([127, 0, 0, 1], port + 1_000).into(),
+		indexing_cpu_capacity: CpuCapacity::from_cpu_millis(4_000),
+		memory_capacity_mb: 8_192,
+	};
+	let failure_detector_config = FailureDetectorConfig {
+		phi_threshold: 5.0,
+		initial_interval: Duration::from_millis(25),
+		..Default::default()
+	};
+	let cluster = Cluster::join(
+		"test-cluster".to_string(),
+		self_node,
+		gossip_advertise_addr,
+		peer_seed_addrs,
+		failure_detector_config,
+		transport,
+	)
+	.await
+	.unwrap();
+	cluster
+		.set_self_key_value(SEMANTIC_CPU_CAPACITY_KEY, CpuCapacity::from_cpu_millis(4_000))
+		.await;
+	cluster.set_self_key_value(SEMANTIC_MEMORY_CAPACITY_KEY, 8_192).await;
+	cluster.set_self_node_readiness(true).await;
+	cluster
+}
+
+#[cfg(test)]
+pub(crate) async fn wait_until(mut condition: impl FnMut() -> bool) {
+	tokio::time::timeout(TEST_TIMEOUT, async {
+		while !condition() {
+			tokio::time::sleep(Duration::from_millis(25)).await;
+		}
+	})
+	.await
+	.expect("deadline has passed before the condition held true");
+}
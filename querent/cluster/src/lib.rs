@@ -23,6 +23,8 @@ pub mod cluster;
 pub mod grpc_service;
 pub mod member;
 pub mod node;
+pub mod placement;
+pub mod session;
 pub mod types;
 pub use grpc_service::*;
 use proto::NodeConfig;
@@ -40,8 +42,16 @@ use time::OffsetDateTime;
 pub use crate::{
 	change::ClusterChange,
 	cluster::{Cluster, ClusterSnapshot, NodeIdSchema},
-	member::{ClusterMember, SEMANTIC_CPU_CAPACITY_KEY},
+	member::{ClusterMember, SEMANTIC_CPU_CAPACITY_KEY, SEMANTIC_MEMORY_CAPACITY_KEY},
 	node::ClusterNode,
+	placement::{
+		KeyRange, PipelineCatalog, PipelineLauncher, Placement, PlacementCost, PlacementScheduler,
+		PlacementTask,
+	},
+	session::{
+		SessionCatalog, SessionKind, SessionOwner, SessionReplicator, SESSION_STATE_RUNNING,
+		SESSION_STATE_STOPPED,
+	},
 };
 
 use self::types::{CpuCapacity, NodeId};
@@ -85,6 +95,7 @@ pub async fn start_cluster_service(node_config: &NodeConfig) -> anyhow::Result<C
 		gossip_advertise_addr: gossip_listen_addr,
 		grpc_advertise_addr: grpc_listen_addr,
 		indexing_cpu_capacity: cpu_capacity,
+		memory_capacity_mb: node_config.memory_capacity as u64,
 	};
 	let cluster: Cluster = Cluster::join(
 		cluster_id,
@@ -96,6 +107,9 @@ pub async fn start_cluster_service(node_config: &NodeConfig) -> anyhow::Result<C
 	)
 	.await?;
 	cluster.set_self_key_value(SEMANTIC_CPU_CAPACITY_KEY, cpu_capacity).await;
+	cluster
+		.set_self_key_value(SEMANTIC_MEMORY_CAPACITY_KEY, node_config.memory_capacity)
+		.await;
 
 	Ok(cluster)
 }
@@ -34,8 +34,13 @@ pub(crate) const READINESS_VALUE_READY: &str = "READY";
 pub(crate) const READINESS_VALUE_NOT_READY: &str = "NOT_READY";
 pub(crate) const SEMANTIC_METRICS_PREFIX: &str = "semantic_metrics:";
 pub(crate) const SEMANTIC_PIPE_PREFIX: &str = "semantic_pipelines:";
+pub(crate) const SEMANTIC_PLACEMENT_PREFIX: &str = "semantic_placements:";
+pub(crate) const SEMANTIC_DEFINITION_PREFIX: &str = "semantic_definitions:";
+pub(crate) const SESSION_PREFIX: &str = "sessions:";
+pub(crate) const SESSION_REQUEST_PREFIX: &str = "session_requests:";
 
 pub const SEMANTIC_CPU_CAPACITY_KEY: &str = "semantic_cpu_capacity";
+pub const SEMANTIC_MEMORY_CAPACITY_KEY: &str = "semantic_memory_capacity";
 
 pub(crate) trait NodeStateExt {
 	fn grpc_advertise_addr(&self) -> anyhow::Result<SocketAddr>;
@@ -80,6 +85,8 @@ pub struct ClusterMember {
 	pub grpc_advertise_addr: SocketAddr,
 	/// Indexing cpu capacity of the node expressed in milli cpu.
 	pub indexing_cpu_capacity: CpuCapacity,
+	/// Memory the node gives to pipelines, in megabytes.
+	pub memory_capacity_mb: u64,
 	pub is_ready: bool,
 }
 
@@ -99,7 +106,7 @@ impl From<ClusterMember> for ChitchatId {
 	}
 }
 
-fn parse_indexing_cpu_capacity(node_state: &NodeState) -> CpuCapacity {
+pub(crate) fn parse_indexing_cpu_capacity(node_state: &NodeState) -> CpuCapacity {
 	let Some(indexing_capacity_str) = node_state.get(SEMANTIC_CPU_CAPACITY_KEY) else {
 		return CpuCapacity::zero();
 	};
@@ -111,6 +118,18 @@ fn parse_indexing_cpu_capacity(node_state: &NodeState) -> CpuCapacity {
 	}
 }
 
+pub(crate) fn parse_memory_capacity_mb(node_state: &NodeState) -> u64 {
+	let Some(memory_capacity_str) = node_state.get(SEMANTIC_MEMORY_CAPACITY_KEY) else {
+		return 0;
+	};
+	if let Ok(memory_capacity_mb) = memory_capacity_str.parse() {
+		memory_capacity_mb
+	} else {
+		error!(memory_capacity=?memory_capacity_str, "received an unparseable memory capacity from node");
+		0
+	}
+}
+
 // Builds a cluster member from a [`NodeState`].
 pub(crate) fn build_cluster_member(
 	chitchat_id: ChitchatId,
@@ -119,6 +138,7 @@ pub(crate) fn build_cluster_member(
 	let is_ready = node_state.is_ready();
 	let grpc_advertise_addr = node_state.grpc_advertise_addr()?;
 	let indexing_cpu_capacity = parse_indexing_cpu_capacity(node_state);
+	let memory_capacity_mb = parse_memory_capacity_mb(node_state);
 	let member = ClusterMember {
 		node_id: chitchat_id.node_id.into(),
 		generation_id: chitchat_id.generation_id.into(),
@@ -126,6 +146,7 @@ pub(crate) fn build_cluster_member(
 		gossip_advertise_addr: chitchat_id.gossip_advertise_addr,
 		grpc_advertise_addr,
 		indexing_cpu_capacity,
+		memory_capacity_mb,
 	};
 	Ok(member)
 }
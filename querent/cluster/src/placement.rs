@@ -0,0 +1,733 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{
+	collections::{BTreeMap, BTreeSet, HashSet},
+	sync::Arc,
+	time::Duration,
+};
+
+use async_trait::async_trait;
+use chitchat::{ChitchatId, NodeState};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+use tracing::{error, info, warn};
+
+use crate::{
+	member::{
+		parse_indexing_cpu_capacity, parse_memory_capacity_mb, NodeStateExt, SEMANTIC_PIPE_PREFIX,
+		SEMANTIC_PLACEMENT_PREFIX,
+	},
+	types::{CpuCapacity, NodeId},
+	Cluster,
+};
+
+const PLACEMENT_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
+	Duration::from_millis(50)
+} else {
+	Duration::from_secs(5)
+};
+
+/// State gossiped by the node running a pipeline, under `semantic_pipelines:{task_id}`.
+pub const PIPELINE_STATE_RUNNING: &str = "running";
+/// State gossiped by the node that ran a pipeline to its end.
+pub const PIPELINE_STATE_FINISHED: &str = "finished";
+
+const SHARD_SEPARATOR: &str = "-shard-";
+
+/// Range of the hash space of document keys collected by one shard of a pipeline. Both bounds
+/// are inclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyRange {
+	pub start: u64,
+	pub end: u64,
+}
+
+impl KeyRange {
+	/// The `shard`-th of `num_shards` equal ranges covering the whole hash space.
+	pub fn shard(shard: u32, num_shards: u32) -> Self {
+		let num_shards = num_shards.max(1) as u128;
+		let shard = (shard as u128).min(num_shards - 1);
+		let width = (u64::MAX as u128 + 1) / num_shards;
+		let start = shard * width;
+		let end = if shard == num_shards - 1 { u64::MAX as u128 } else { start + width - 1 };
+		KeyRange { start: start as u64, end: end as u64 }
+	}
+
+	/// Whether the document with this key belongs to the range.
+	pub fn contains(&self, key: &str) -> bool {
+		(self.start..=self.end).contains(&key_hash(key))
+	}
+}
+
+/// Hashes a document key, the same way on every node and release: FNV-1a, finalized so that
+/// similar keys spread over the ranges.
+pub fn key_hash(key: &str) -> u64 {
+	let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+	for byte in key.bytes() {
+		hash ^= byte as u64;
+		hash = hash.wrapping_mul(0x0100_0000_01b3);
+	}
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+	hash ^= hash >> 33;
+	hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+	hash ^ (hash >> 33)
+}
+
+/// A pipeline, or one shard of a pipeline split by key range, to run on a node of the cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacementTask {
+	pub pipeline_id: String,
+	pub shard: u32,
+	pub num_shards: u32,
+}
+
+impl PlacementTask {
+	/// The tasks running a pipeline split into `num_shards` key ranges, or whole when there are
+	/// less than two.
+	pub fn for_pipeline(pipeline_id: &str, num_shards: u32) -> Vec<Self> {
+		let num_shards = num_shards.max(1);
+		(0..num_shards)
+			.map(|shard| PlacementTask { pipeline_id: pipeline_id.to_string(), shard, num_shards })
+			.collect()
+	}
+
+	/// Id of the pipeline running the task: the pipeline id, suffixed with the shard when split.
+	pub fn task_id(&self) -> String {
+		if self.num_shards > 1 {
+			format!("{}{SHARD_SEPARATOR}{}", self.pipeline_id, self.shard)
+		} else {
+			self.pipeline_id.clone()
+		}
+	}
+
+	/// Keys of the documents the task collects, all of them when the pipeline is not split.
+	pub fn key_range(&self) -> Option<KeyRange> {
+		(self.num_shards > 1).then(|| KeyRange::shard(self.shard, self.num_shards))
+	}
+}
+
+fn pipeline_of_task(task_id: &str) -> &str {
+	task_id
+		.rsplit_once(SHARD_SEPARATOR)
+		.map(|(pipeline_id, _)| pipeline_id)
+		.unwrap_or(task_id)
+}
+
+/// The node a task is placed on, as published by the scheduling node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Placement {
+	pub node_id: NodeId,
+	pub task: PlacementTask,
+}
+
+/// Resources a task takes on the node it runs on.
+#[derive(Debug, Clone, Copy)]
+pub struct PlacementCost {
+	pub cpu: CpuCapacity,
+	pub memory_mb: u64,
+}
+
+impl Default for PlacementCost {
+	fn default() -> Self {
+		PlacementCost { cpu: CpuCapacity::one_cpu_thread(), memory_mb: 512 }
+	}
+}
+
+/// What the scheduler knows of a live node, from its gossiped state.
+#[derive(Debug, Clone)]
+pub struct PlacementNode {
+	pub node_id: NodeId,
+	pub is_ready: bool,
+	pub cpu_capacity: CpuCapacity,
+	pub memory_capacity_mb: u64,
+	/// State of the tasks the node runs or ran, by task id.
+	pub pipelines: BTreeMap<String, String>,
+	/// Placements the node published while it was the scheduling node, by task id.
+	pub placements: BTreeMap<String, Placement>,
+}
+
+impl PlacementNode {
+	fn running_tasks(&self) -> impl Iterator<Item = &String> {
+		self.pipelines
+			.iter()
+			.filter(|(_, state)| state.as_str() == PIPELINE_STATE_RUNNING)
+			.map(|(task_id, _)| task_id)
+	}
+}
+
+pub(crate) fn build_placement_node(
+	chitchat_id: &ChitchatId,
+	node_state: &NodeState,
+) -> PlacementNode {
+	let live_values = |prefix: &'static str| {
+		node_state
+			.iter_prefix(prefix)
+			.filter(|(_, versioned_value)| versioned_value.tombstone.is_none())
+			.map(move |(key, versioned_value)| {
+				(key[prefix.len()..].to_string(), versioned_value.value.clone())
+			})
+	};
+	let placements = live_values(SEMANTIC_PLACEMENT_PREFIX)
+		.filter_map(|(task_id, value)| match serde_json::from_str(&value) {
+			Ok(placement) => Some((task_id, placement)),
+			Err(error) => {
+				warn!(task_id=%task_id, error=?error, "Received an unparseable placement.");
+				None
+			},
+		})
+		.collect();
+	PlacementNode {
+		node_id: chitchat_id.node_id.clone().into(),
+		is_ready: node_state.is_ready(),
+		cpu_capacity: parse_indexing_cpu_capacity(node_state),
+		memory_capacity_mb: parse_memory_capacity_mb(node_state),
+		pipelines: live_values(SEMANTIC_PIPE_PREFIX).collect(),
+		placements,
+	}
+}
+
+/// Places every task on a node. A task stays on the live node running it, or on the ready node
+/// it was placed on before. The others go, one by one, to the ready node the least used once the
+/// task is added, preferring nodes with room left for it and without a shard of the same
+/// pipeline. Tasks are left out when no node is ready.
+pub fn schedule(
+	tasks: &[PlacementTask],
+	nodes: &[PlacementNode],
+	previous: &BTreeMap<String, Placement>,
+	cost: PlacementCost,
+) -> BTreeMap<String, Placement> {
+	let mut hosted: BTreeMap<&NodeId, BTreeSet<String>> = nodes
+		.iter()
+		.map(|node| (&node.node_id, node.running_tasks().cloned().collect()))
+		.collect();
+	let mut placements = BTreeMap::new();
+	let mut unplaced = Vec::new();
+	for task in tasks {
+		let task_id = task.task_id();
+		let running_node = nodes.iter().find(|node| node.pipelines.contains_key(&task_id));
+		let previous_node = || {
+			let previous_node_id = &previous.get(&task_id)?.node_id;
+			nodes.iter().find(|node| node.is_ready && &node.node_id == previous_node_id)
+		};
+		match running_node.or_else(previous_node) {
+			Some(node) => {
+				if let Some(node_tasks) = hosted.get_mut(&node.node_id) {
+					node_tasks.insert(task_id.clone());
+				}
+				placements.insert(
+					task_id,
+					Placement { node_id: node.node_id.clone(), task: task.clone() },
+				);
+			},
+			None => unplaced.push(task),
+		}
+	}
+	for task in unplaced {
+		let task_id = task.task_id();
+		let best_node = nodes.iter().filter(|node| node.is_ready).min_by_key(|node| {
+			let node_tasks = &hosted[&node.node_id];
+			let num_tasks = node_tasks.len() as u64 + 1;
+			let cpu = cost.cpu.cpu_millis() as u64 * num_tasks;
+			let memory_mb = cost.memory_mb * num_tasks;
+			let cpu_capacity = node.cpu_capacity.cpu_millis() as u64;
+			let fits = cpu <= cpu_capacity && memory_mb <= node.memory_capacity_mb;
+			let hosts_sibling = node_tasks
+				.iter()
+				.any(|hosted_task_id| pipeline_of_task(hosted_task_id) == task.pipeline_id);
+			// per mille of the most used resource
+			let usage = (cpu * 1000 / cpu_capacity.max(1))
+				.max(memory_mb * 1000 / node.memory_capacity_mb.max(1));
+			(!fits, hosts_sibling, usage, &node.node_id)
+		});
+		let Some(node) = best_node else {
+			warn!(task_id=%task_id, "No ready node to place the pipeline on.");
+			continue;
+		};
+		if let Some(node_tasks) = hosted.get_mut(&node.node_id) {
+			node_tasks.insert(task_id.clone());
+		}
+		placements.insert(task_id, Placement { node_id: node.node_id.clone(), task: task.clone() });
+	}
+	placements
+}
+
+/// Lists the pipelines the cluster runs from the definitions stored on this node. The scheduler
+/// replicates the definitions: every node publishes the ones it stores and stores the ones the
+/// other nodes publish, so that a survivor can run the pipelines of a dead node.
+#[async_trait]
+pub trait PipelineCatalog: Send + Sync {
+	async fn list_tasks(&self) -> anyhow::Result<Vec<PlacementTask>>;
+
+	/// The serialized definitions of the pipelines stored on this node, by pipeline id.
+	async fn definitions(&self) -> anyhow::Result<BTreeMap<String, String>> {
+		Ok(BTreeMap::new())
+	}
+
+	/// Stores the definition of a pipeline published by another node.
+	async fn store_definition(&self, _pipeline_id: &str, _definition: &str) -> anyhow::Result<()> {
+		Ok(())
+	}
+}
+
+/// Starts, on this node, the tasks placed on it.
+#[async_trait]
+pub trait PipelineLauncher: Send + Sync {
+	async fn launch(&self, task: &PlacementTask) -> anyhow::Result<()>;
+}
+
+/// Runs the pipelines of the cluster on its nodes, and moves the pipelines of a dead node to the
+/// surviving ones.
+///
+/// Every node runs a scheduler. The ready node with the lowest id places the tasks listed by the
+/// catalog and publishes the placements in its gossiped state; every node then launches the
+/// tasks placed on it. When the scheduling node dies, the next one takes over from the tasks the
+/// nodes gossip as running.
+///
+/// Only the pipelines seen in the cluster, started by a request on one of its nodes or placed
+/// before, are placed: the pipelines stored by past runs are not started again, and the tasks
+/// gossiped as finished are not placed again.
+pub struct PlacementScheduler {
+	cluster: Cluster,
+	catalog: Arc<dyn PipelineCatalog>,
+	launcher: Arc<dyn PipelineLauncher>,
+	cost: PlacementCost,
+	seen_pipelines: HashSet<String>,
+	finished_tasks: HashSet<String>,
+	// Tasks launched by this node, never launched again even once finished.
+	launched: HashSet<String>,
+}
+
+impl PlacementScheduler {
+	pub fn new(
+		cluster: Cluster,
+		catalog: Arc<dyn PipelineCatalog>,
+		launcher: Arc<dyn PipelineLauncher>,
+		cost: PlacementCost,
+	) -> Self {
+		Self {
+			cluster,
+			catalog,
+			launcher,
+			cost,
+			seen_pipelines: HashSet::new(),
+			finished_tasks: HashSet::new(),
+			launched: HashSet::new(),
+		}
+	}
+
+	/// Spawns the scheduling loop. Aborting the returned handle stops it.
+	pub fn spawn(mut self) -> JoinHandle<()> {
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(PLACEMENT_INTERVAL);
+			loop {
+				interval.tick().await;
+				if let Err(error) = self.run_once().await {
+					warn!(error=?error, "Failed to place the pipelines of the cluster.");
+				}
+			}
+		})
+	}
+
+	/// Places the tasks when this node schedules, then launches the tasks placed on it.
+	pub async fn run_once(&mut self) -> anyhow::Result<()> {
+		if let Err(error) = self.replicate_definitions().await {
+			warn!(error=?error, "Failed to replicate the pipeline definitions.");
+		}
+		let nodes = self.cluster.placement_nodes().await;
+		let self_node_id = self.cluster.self_node_id().to_string();
+		self.observe(&nodes);
+		let Some(scheduling_node) = nodes
+			.iter()
+			.filter(|node| node.is_ready)
+			.min_by(|a, b| a.node_id.cmp(&b.node_id))
+		else {
+			return Ok(());
+		};
+		let placements = if scheduling_node.node_id == self_node_id {
+			let tasks: Vec<PlacementTask> = self
+				.catalog
+				.list_tasks()
+				.await?
+				.into_iter()
+				.filter(|task| {
+					self.seen_pipelines.contains(&task.pipeline_id) &&
+						!self.finished_tasks.contains(&task.task_id())
+				})
+				.collect();
+			// placements published by a previous scheduling node still alive, then ours
+			let mut previous = BTreeMap::new();
+			for node in nodes.iter().filter(|node| node.node_id != self_node_id) {
+				previous.extend(node.placements.clone());
+			}
+			previous.extend(scheduling_node.placements.clone());
+			let placements = schedule(&tasks, &nodes, &previous, self.cost);
+			self.cluster.publish_placements(&placements).await;
+			placements
+		} else {
+			scheduling_node.placements.clone()
+		};
+		let self_pipelines = nodes
+			.iter()
+			.find(|node| node.node_id == self_node_id)
+			.map(|node| &node.pipelines);
+		for (task_id, placement) in placements {
+			if placement.node_id != self_node_id ||
+				self.launched.contains(&task_id) ||
+				self_pipelines.is_some_and(|pipelines| pipelines.contains_key(&task_id))
+			{
+				continue;
+			}
+			info!(task_id=%task_id, "Launching the pipeline placed on this node.");
+			match self.launcher.launch(&placement.task).await {
+				Ok(()) => {
+					self.launched.insert(task_id);
+				},
+				// launched again on the next round
+				Err(error) => error!(task_id=%task_id, error=?error, "Failed to launch pipeline."),
+			}
+		}
+		Ok(())
+	}
+
+	/// Stores the pipeline definitions published by the other nodes, then publishes the ones
+	/// stored here, so that they outlive the node which started the pipelines.
+	async fn replicate_definitions(&self) -> anyhow::Result<()> {
+		let mut definitions = self.catalog.definitions().await?;
+		for (pipeline_id, definition) in self.cluster.pipeline_definitions().await {
+			if definitions.contains_key(&pipeline_id) {
+				continue;
+			}
+			self.catalog.store_definition(&pipeline_id, &definition).await?;
+			definitions.insert(pipeline_id, definition);
+		}
+		self.cluster.publish_pipeline_definitions(&definitions).await;
+		Ok(())
+	}
+
+	fn observe(&mut self, nodes: &[PlacementNode]) {
+		for node in nodes {
+			for (task_id, state) in &node.pipelines {
+				self.seen_pipelines.insert(pipeline_of_task(task_id).to_string());
+				if state == PIPELINE_STATE_FINISHED {
+					self.finished_tasks.insert(task_id.clone());
+				} else {
+					self.finished_tasks.remove(task_id);
+				}
+			}
+			for placement in node.placements.values() {
+				self.seen_pipelines.insert(placement.task.pipeline_id.clone());
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::Mutex;
+
+	use chitchat::transport::ChannelTransport;
+
+	use super::*;
+	use crate::cluster::{create_cluster_for_test, wait_until, TEST_TIMEOUT};
+
+	fn placement_node(node_id: &str, cpu_millis: u32, running: &[&str]) -> PlacementNode {
+		PlacementNode {
+			node_id: node_id.into(),
+			is_ready: true,
+			cpu_capacity: CpuCapacity::from_cpu_millis(cpu_millis),
+			memory_capacity_mb: 8_192,
+			pipelines: running
+				.iter()
+				.map(|task_id| (task_id.to_string(), PIPELINE_STATE_RUNNING.to_string()))
+				.collect(),
+			placements: BTreeMap::new(),
+		}
+	}
+
+	fn placed_on<'a>(placements: &'a BTreeMap<String, Placement>, node_id: &str) -> Vec<&'a str> {
+		placements
+			.iter()
+			.filter(|(_, placement)| placement.node_id == node_id)
+			.map(|(task_id, _)| task_id.as_str())
+			.collect()
+	}
+
+	#[test]
+	fn test_key_ranges_split_the_documents() {
+		let ranges: Vec<KeyRange> = (0..3).map(|shard| KeyRange::shard(shard, 3)).collect();
+		assert_eq!(ranges[0].start, 0);
+		assert_eq!(ranges[0].end + 1, ranges[1].start);
+		assert_eq!(ranges[1].end + 1, ranges[2].start);
+		assert_eq!(ranges[2].end, u64::MAX);
+		assert_eq!(KeyRange::shard(0, 1), KeyRange { start: 0, end: u64::MAX });
+
+		let mut documents_per_shard = [0; 3];
+		for document in 0..3_000 {
+			let key = format!("reports/well-{document}.pdf");
+			let shards: Vec<usize> = (0..3).filter(|&shard| ranges[shard].contains(&key)).collect();
+			assert_eq!(shards.len(), 1);
+			documents_per_shard[shards[0]] += 1;
+		}
+		assert!(documents_per_shard.iter().all(|&count| count > 800), "{documents_per_shard:?}");
+	}
+
+	#[test]
+	fn test_schedule_by_capacity_and_fail_over() {
+		let mut tasks = PlacementTask::for_pipeline("running", 1);
+		for pipeline_id in ["p1", "p2", "p3", "p4"] {
+			tasks.extend(PlacementTask::for_pipeline(pipeline_id, 1));
+		}
+		tasks.extend(PlacementTask::for_pipeline("big", 2));
+		assert_eq!(tasks[5].task_id(), "big-shard-0");
+		assert_eq!(tasks[5].key_range(), Some(KeyRange::shard(0, 2)));
+		assert_eq!(tasks[0].key_range(), None);
+
+		let nodes = [placement_node("a", 4_000, &[]), placement_node("b", 1_000, &["running"])];
+		let placements = schedule(&tasks, &nodes, &BTreeMap::new(), PlacementCost::default());
+		// the running task stays, the others fill the largest node, and the last shard goes to
+		// the node without the first one once both are full
+		assert_eq!(placed_on(&placements, "a"), ["big-shard-0", "p1", "p2", "p3", "p4"]);
+		assert_eq!(placed_on(&placements, "b"), ["big-shard-1", "running"]);
+
+		// b dies: its tasks move to the new node c, the others stay where they were placed
+		let nodes = [placement_node("a", 4_000, &[]), placement_node("c", 4_000, &[])];
+		let placements = schedule(&tasks, &nodes, &placements, PlacementCost::default());
+		assert_eq!(placed_on(&placements, "a"), ["big-shard-0", "p1", "p2", "p3", "p4"]);
+		assert_eq!(placed_on(&placements, "c"), ["big-shard-1", "running"]);
+
+		assert!(schedule(&tasks, &[], &placements, PlacementCost::default()).is_empty());
+	}
+
+	struct StaticCatalog(Vec<PlacementTask>);
+
+	#[async_trait]
+	impl PipelineCatalog for StaticCatalog {
+		async fn list_tasks(&self) -> anyhow::Result<Vec<PlacementTask>> {
+			Ok(self.0.clone())
+		}
+	}
+
+	/// Records the launches, and gossips the launched tasks as running like the semantic service.
+	struct RecordingLauncher {
+		cluster: Cluster,
+		launches: Arc<Mutex<Vec<(String, String)>>>,
+	}
+
+	#[async_trait]
+	impl PipelineLauncher for RecordingLauncher {
+		async fn launch(&self, task: &PlacementTask) -> anyhow::Result<()> {
+			let task_id = task.task_id();
+			self.cluster
+				.update_semantic_pipeline_state(&task_id, PIPELINE_STATE_RUNNING)
+				.await?;
+			self.launches
+				.lock()
+				.unwrap()
+				.push((self.cluster.self_node_id().to_string(), task_id));
+			Ok(())
+		}
+	}
+
+	/// Stores the definitions of one node, a definition being the number of shards of a pipeline.
+	#[derive(Default)]
+	struct MemoryCatalog(Mutex<BTreeMap<String, String>>);
+
+	#[async_trait]
+	impl PipelineCatalog for MemoryCatalog {
+		async fn list_tasks(&self) -> anyhow::Result<Vec<PlacementTask>> {
+			let definitions = self.0.lock().unwrap().clone();
+			let mut tasks = Vec::new();
+			for (pipeline_id, num_shards) in definitions {
+				tasks.extend(PlacementTask::for_pipeline(&pipeline_id, num_shards.parse()?));
+			}
+			Ok(tasks)
+		}
+
+		async fn definitions(&self) -> anyhow::Result<BTreeMap<String, String>> {
+			Ok(self.0.lock().unwrap().clone())
+		}
+
+		async fn store_definition(
+			&self,
+			pipeline_id: &str,
+			definition: &str,
+		) -> anyhow::Result<()> {
+			self.0.lock().unwrap().insert(pipeline_id.to_string(), definition.to_string());
+			Ok(())
+		}
+	}
+
+	/// Launches the tasks of the pipelines stored in the catalog of its node only, like the node
+	/// reading the definitions from its metadata store.
+	struct StoredLauncher {
+		catalog: Arc<MemoryCatalog>,
+		launcher: RecordingLauncher,
+	}
+
+	#[async_trait]
+	impl PipelineLauncher for StoredLauncher {
+		async fn launch(&self, task: &PlacementTask) -> anyhow::Result<()> {
+			let is_stored = self.catalog.0.lock().unwrap().contains_key(&task.pipeline_id);
+			anyhow::ensure!(is_stored, "pipeline `{}` is not stored", task.pipeline_id);
+			self.launcher.launch(task).await
+		}
+	}
+
+	#[tokio::test]
+	async fn test_pipelines_fail_over_to_surviving_nodes() {
+		let transport = ChannelTransport::default();
+		let node1 = create_cluster_for_test("node-1", 11_001, Vec::new(), &transport).await;
+		let peer_seed_addrs = vec![node1.gossip_listen_addr().to_string()];
+		let node2 =
+			create_cluster_for_test("node-2", 11_002, peer_seed_addrs.clone(), &transport).await;
+		let node3 = create_cluster_for_test("node-3", 11_003, peer_seed_addrs, &transport).await;
+		for node in [&node1, &node2, &node3] {
+			node.wait_for_ready_members(|members| members.len() == 3, TEST_TIMEOUT)
+				.await
+				.unwrap();
+		}
+
+		// wells is split in three, its first shard started on node-1 by a request; reports runs on
+		// node-2; archived was stored by a past run
+		let mut tasks = PlacementTask::for_pipeline("wells", 3);
+		tasks.extend(PlacementTask::for_pipeline("reports", 1));
+		tasks.extend(PlacementTask::for_pipeline("archived", 1));
+		node1
+			.update_semantic_pipeline_state("wells-shard-0", PIPELINE_STATE_RUNNING)
+			.await
+			.unwrap();
+		node2
+			.update_semantic_pipeline_state("reports", PIPELINE_STATE_RUNNING)
+			.await
+			.unwrap();
+		let launches = Arc::new(Mutex::new(Vec::new()));
+		let mut schedulers: Vec<JoinHandle<()>> = [&node1, &node2, &node3]
+			.into_iter()
+			.map(|node| {
+				let launcher =
+					RecordingLauncher { cluster: node.clone(), launches: launches.clone() };
+				PlacementScheduler::new(
+					node.clone(),
+					Arc::new(StaticCatalog(tasks.clone())),
+					Arc::new(launcher),
+					PlacementCost::default(),
+				)
+				.spawn()
+			})
+			.collect();
+		let launched_on = |node_ids: &[&str]| -> BTreeSet<String> {
+			launches
+				.lock()
+				.unwrap()
+				.iter()
+				.filter(|(node_id, _)| node_ids.contains(&node_id.as_str()))
+				.map(|(_, task_id)| task_id.clone())
+				.collect()
+		};
+
+		// the other shards go to the nodes without one, each launched once
+		wait_until(|| launches.lock().unwrap().len() == 2).await;
+		assert!(launched_on(&["node-1"]).is_empty());
+		assert_eq!(launched_on(&["node-2"]).len(), 1);
+		assert_eq!(launched_on(&["node-3"]).len(), 1);
+		assert_eq!(
+			launched_on(&["node-2", "node-3"]),
+			BTreeSet::from(["wells-shard-1".to_string(), "wells-shard-2".to_string()])
+		);
+
+		// node-3 dies: its shard moves to a surviving node
+		let dead_task = launched_on(&["node-3"]).pop_first().unwrap();
+		let scheduler3 = schedulers.pop().unwrap();
+		scheduler3.abort();
+		let _ = scheduler3.await;
+		drop(node3);
+		wait_until(|| launches.lock().unwrap().len() == 3).await;
+		let (node_id, task_id) = launches.lock().unwrap()[2].clone();
+		assert_ne!(node_id, "node-3");
+		assert_eq!(task_id, dead_task);
+		assert!(!launched_on(&["node-1", "node-2"]).contains("archived"));
+		for scheduler in schedulers {
+			scheduler.abort();
+		}
+	}
+	#[tokio::test]
+	async fn test_pipelines_fail_over_to_nodes_which_did_not_start_them() {
+		let transport = ChannelTransport::default();
+		let node1 = create_cluster_for_test("node-1", 11_101, Vec::new(), &transport).await;
+		let peer_seed_addrs = vec![node1.gossip_listen_addr().to_string()];
+		let node2 =
+			create_cluster_for_test("node-2", 11_102, peer_seed_addrs.clone(), &transport).await;
+		let node3 = create_cluster_for_test("node-3", 11_103, peer_seed_addrs, &transport).await;
+		for node in [&node1, &node2, &node3] {
+			node.wait_for_ready_members(|members| members.len() == 3, TEST_TIMEOUT)
+				.await
+				.unwrap();
+		}
+
+		// reports was started by a request on node-1, the only node storing it
+		let catalogs: Vec<Arc<MemoryCatalog>> =
+			(0..3).map(|_| Arc::new(MemoryCatalog::default())).collect();
+		catalogs[0].0.lock().unwrap().insert("reports".to_string(), "1".to_string());
+		node1
+			.update_semantic_pipeline_state("reports", PIPELINE_STATE_RUNNING)
+			.await
+			.unwrap();
+		let launches = Arc::new(Mutex::new(Vec::new()));
+		let mut schedulers: Vec<JoinHandle<()>> = [&node1, &node2, &node3]
+			.into_iter()
+			.zip(&catalogs)
+			.map(|(node, catalog)| {
+				let launcher = StoredLauncher {
+					catalog: catalog.clone(),
+					launcher: RecordingLauncher {
+						cluster: node.clone(),
+						launches: launches.clone(),
+					},
+				};
+				PlacementScheduler::new(
+					node.clone(),
+					catalog.clone(),
+					Arc::new(launcher),
+					PlacementCost::default(),
+				)
+				.spawn()
+			})
+			.collect();
+
+		// the definition is replicated to the other nodes
+		wait_until(|| {
+			catalogs.iter().all(|catalog| catalog.0.lock().unwrap().contains_key("reports"))
+		})
+		.await;
+		assert!(launches.lock().unwrap().is_empty());
+
+		// node-1 dies: a survivor launches reports from the definition it replicated
+		let scheduler1 = schedulers.remove(0);
+		scheduler1.abort();
+		let _ = scheduler1.await;
+		drop(node1);
+		wait_until(|| launches.lock().unwrap().len() == 1).await;
+		let (node_id, task_id) = launches.lock().unwrap()[0].clone();
+		assert_ne!(node_id, "node-1");
+		assert_eq!(task_id, "reports");
+		for scheduler in schedulers {
+			scheduler.abort();
+		}
+	}
+}
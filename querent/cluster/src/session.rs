@@ -0,0 +1,317 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{collections::BTreeMap, fmt, net::SocketAddr, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chitchat::NodeState;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+use crate::{
+	member::{SESSION_PREFIX, SESSION_REQUEST_PREFIX},
+	types::NodeId,
+	Cluster,
+};
+
+const SESSION_REPLICATION_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
+	Duration::from_millis(50)
+} else {
+	Duration::from_secs(5)
+};
+
+/// State of a session run by the node, as published in the cluster state.
+pub const SESSION_STATE_RUNNING: &str = "running";
+/// State of a session stopped on the node. It is kept so that the other nodes do not re-create
+/// the session while the node is alive.
+pub const SESSION_STATE_STOPPED: &str = "stopped";
+
+/// Kind of the sessions whose owner is published in the cluster state.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum SessionKind {
+	Discovery,
+	Insight,
+	Layer,
+}
+
+impl SessionKind {
+	pub const ALL: [SessionKind; 3] =
+		[SessionKind::Discovery, SessionKind::Insight, SessionKind::Layer];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			SessionKind::Discovery => "discovery",
+			SessionKind::Insight => "insight",
+			SessionKind::Layer => "layer",
+		}
+	}
+}
+
+impl fmt::Display for SessionKind {
+	fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+		formatter.write_str(self.as_str())
+	}
+}
+
+/// Node running a session, as seen from the cluster state.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SessionOwner {
+	/// The session runs on this node.
+	SelfNode,
+	/// The session runs on another node, reachable at its gRPC advertise address.
+	Node { node_id: NodeId, grpc_advertise_addr: SocketAddr },
+	/// The session was stopped by a live node.
+	Stopped,
+	/// No live node runs the session: its node left the cluster, or it was never started.
+	Unknown,
+}
+
+pub(crate) fn session_key(kind: SessionKind, session_id: &str) -> String {
+	format!("{SESSION_PREFIX}{kind}:{session_id}")
+}
+
+/// The state of the session published by the node, if any.
+pub(crate) fn session_state<'a>(node_state: &'a NodeState, key: &str) -> Option<&'a str> {
+	node_state
+		.get_versioned(key)
+		.filter(|versioned_value| versioned_value.tombstone.is_none())
+		.map(|versioned_value| versioned_value.value.as_str())
+}
+
+/// The session requests stored on this node.
+#[async_trait]
+pub trait SessionCatalog: Send + Sync {
+	/// The serialized requests of the sessions of `kind` stored on this node, by session id.
+	async fn requests(&self, kind: SessionKind) -> anyhow::Result<BTreeMap<String, String>>;
+
+	/// Stores the request of a session started on another node.
+	async fn store_request(
+		&self,
+		kind: SessionKind,
+		session_id: &str,
+		request: &str,
+	) -> anyhow::Result<()>;
+}
+
+/// Replicates the session requests through the cluster state: every node publishes the requests
+/// it stores and stores the ones the other nodes publish, so that any node can re-create a
+/// session once the node running it has left.
+pub struct SessionReplicator {
+	cluster: Cluster,
+	catalog: Arc<dyn SessionCatalog>,
+}
+
+impl SessionReplicator {
+	pub fn new(cluster: Cluster, catalog: Arc<dyn SessionCatalog>) -> Self {
+		SessionReplicator { cluster, catalog }
+	}
+
+	/// Spawns the replication loop. Aborting the returned handle stops it.
+	pub fn spawn(self) -> JoinHandle<()> {
+		tokio::spawn(async move {
+			let mut interval = tokio::time::interval(SESSION_REPLICATION_INTERVAL);
+			loop {
+				interval.tick().await;
+				if let Err(error) = self.run_once().await {
+					warn!(error=?error, "Failed to replicate the session requests.");
+				}
+			}
+		})
+	}
+
+	/// Stores the session requests published by the other nodes, then publishes the ones stored
+	/// here.
+	pub async fn run_once(&self) -> anyhow::Result<()> {
+		for kind in SessionKind::ALL {
+			let prefix = format!("{SESSION_REQUEST_PREFIX}{kind}:");
+			let mut requests = self.catalog.requests(kind).await?;
+			for (session_id, request) in self.cluster.replicated_values(&prefix).await {
+				if requests.contains_key(&session_id) {
+					continue;
+				}
+				self.catalog.store_request(kind, &session_id, &request).await?;
+				requests.insert(session_id, request);
+			}
+			self.cluster.publish_replicated_values(&prefix, &requests).await;
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::{collections::HashMap, sync::Mutex};
+
+	use chitchat::transport::ChannelTransport;
+
+	use super::*;
+	use crate::cluster::{create_cluster_for_test, wait_until, TEST_TIMEOUT};
+
+	#[tokio::test]
+	async fn test_session_owner_follows_the_cluster_state() {
+		let transport = ChannelTransport::default();
+		let node1 = create_cluster_for_test("node-1", 12_001, Vec::new(), &transport).await;
+		let peer_seed_addrs = vec![node1.gossip_listen_addr().to_string()];
+		let node2 = create_cluster_for_test("node-2", 12_002, peer_seed_addrs, &transport).await;
+		for node in [&node1, &node2] {
+			node.wait_for_ready_members(|members| members.len() == 2, TEST_TIMEOUT)
+				.await
+				.unwrap();
+		}
+		assert_eq!(node2.session_owner(SessionKind::Discovery, "s1").await, SessionOwner::Unknown);
+
+		node1
+			.update_session_state(SessionKind::Discovery, "s1", SESSION_STATE_RUNNING)
+			.await;
+		assert_eq!(node1.session_owner(SessionKind::Discovery, "s1").await, SessionOwner::SelfNode);
+		let node1_owner = SessionOwner::Node {
+			node_id: "node-1".into(),
+			grpc_advertise_addr: ([127, 0, 0, 1], 13_001).into(),
+		};
+		wait_for_owner(&node2, SessionKind::Discovery, &node1_owner).await;
+		// the kinds of sessions do not share their ids
+		assert_eq!(node2.session_owner(SessionKind::Layer, "s1").await, SessionOwner::Unknown);
+
+		// the owner of a session started a moment ago is waited for
+		node1
+			.update_session_state(SessionKind::Layer, "s2", SESSION_STATE_RUNNING)
+			.await;
+		assert_eq!(node2.wait_for_session_owner(SessionKind::Layer, "s2").await, node1_owner);
+
+		node1
+			.update_session_state(SessionKind::Discovery, "s1", SESSION_STATE_STOPPED)
+			.await;
+		wait_for_owner(&node2, SessionKind::Discovery, &SessionOwner::Stopped).await;
+
+		// a node running the session wins over the node which stopped it
+		node2
+			.update_session_state(SessionKind::Discovery, "s1", SESSION_STATE_RUNNING)
+			.await;
+		assert_eq!(node2.session_owner(SessionKind::Discovery, "s1").await, SessionOwner::SelfNode);
+		let node2_owner = SessionOwner::Node {
+			node_id: "node-2".into(),
+			grpc_advertise_addr: ([127, 0, 0, 1], 13_002).into(),
+		};
+		wait_for_owner(&node1, SessionKind::Discovery, &node2_owner).await;
+	}
+
+	#[derive(Default)]
+	struct MemorySessions(Mutex<HashMap<(SessionKind, String), String>>);
+
+	impl MemorySessions {
+		fn request(&self, kind: SessionKind, session_id: &str) -> Option<String> {
+			self.0.lock().unwrap().get(&(kind, session_id.to_string())).cloned()
+		}
+	}
+
+	#[async_trait]
+	impl SessionCatalog for MemorySessions {
+		async fn requests(&self, kind: SessionKind) -> anyhow::Result<BTreeMap<String, String>> {
+			let requests = self.0.lock().unwrap();
+			Ok(requests
+				.iter()
+				.filter(|((request_kind, _), _)| *request_kind == kind)
+				.map(|((_, session_id), request)| (session_id.clone(), request.clone()))
+				.collect())
+		}
+
+		async fn store_request(
+			&self,
+			kind: SessionKind,
+			session_id: &str,
+			request: &str,
+		) -> anyhow::Result<()> {
+			self.0
+				.lock()
+				.unwrap()
+				.insert((kind, session_id.to_string()), request.to_string());
+			Ok(())
+		}
+	}
+
+	#[tokio::test]
+	async fn test_sessions_are_re_created_on_another_node_once_the_owner_leaves() {
+		let transport = ChannelTransport::default();
+		let node1 = create_cluster_for_test("node-1", 12_101, Vec::new(), &transport).await;
+		let peer_seed_addrs = vec![node1.gossip_listen_addr().to_string()];
+		let node2 =
+			create_cluster_for_test("node-2", 12_102, peer_seed_addrs.clone(), &transport).await;
+		let node3 = create_cluster_for_test("node-3", 12_103, peer_seed_addrs, &transport).await;
+		for node in [&node1, &node2, &node3] {
+			node.wait_for_ready_members(|members| members.len() == 3, TEST_TIMEOUT)
+				.await
+				.unwrap();
+		}
+
+		// s1 was started by a request on node-1, the only node storing it
+		let catalogs: Vec<Arc<MemorySessions>> =
+			(0..3).map(|_| Arc::new(MemorySessions::default())).collect();
+		catalogs[0]
+			.store_request(SessionKind::Discovery, "s1", "{\"agent_name\":\"a\"}")
+			.await
+			.unwrap();
+		node1
+			.update_session_state(SessionKind::Discovery, "s1", SESSION_STATE_RUNNING)
+			.await;
+		let mut replicators: Vec<JoinHandle<()>> = [&node1, &node2, &node3]
+			.into_iter()
+			.zip(&catalogs)
+			.map(|(node, catalog)| SessionReplicator::new(node.clone(), catalog.clone()).spawn())
+			.collect();
+
+		// the request is replicated to the other nodes, under its kind only
+		wait_until(|| {
+			catalogs
+				.iter()
+				.all(|catalog| catalog.request(SessionKind::Discovery, "s1").is_some())
+		})
+		.await;
+		assert!(catalogs[1].request(SessionKind::Layer, "s1").is_none());
+
+		// node-1 leaves: node-2 re-creates s1 from the request it replicated
+		let replicator1 = replicators.remove(0);
+		replicator1.abort();
+		let _ = replicator1.await;
+		drop(node1);
+		wait_for_owner(&node2, SessionKind::Discovery, &SessionOwner::Unknown).await;
+		assert_eq!(
+			catalogs[1].request(SessionKind::Discovery, "s1").as_deref(),
+			Some("{\"agent_name\":\"a\"}")
+		);
+		node2
+			.update_session_state(SessionKind::Discovery, "s1", SESSION_STATE_RUNNING)
+			.await;
+		let node2_owner = SessionOwner::Node {
+			node_id: "node-2".into(),
+			grpc_advertise_addr: ([127, 0, 0, 1], 13_102).into(),
+		};
+		wait_for_owner(&node3, SessionKind::Discovery, &node2_owner).await;
+		for replicator in replicators {
+			replicator.abort();
+		}
+	}
+
+	async fn wait_for_owner(node: &Cluster, kind: SessionKind, expected_owner: &SessionOwner) {
+		tokio::time::timeout(TEST_TIMEOUT, async {
+			while node.session_owner(kind, "s1").await != *expected_owner {
+				tokio::time::sleep(std::time::Duration::from_millis(25)).await;
+			}
+		})
+		.await
+		.expect("deadline has passed before the session owner was seen");
+	}
+}
@@ -41,6 +41,29 @@ pub struct VectorPayload {
 	pub score: f32,
 }
 
+/// Where a piece of semantic knowledge came from.
+#[derive(Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum KnowledgeOrigin {
+	/// Knowledge extracted from documents by an engine.
+	#[default]
+	Extracted,
+	/// Knowledge imported from a curated external source (RDF, CSV, ...).
+	Imported,
+	/// Knowledge read from the rows of tables in documents, in table mode.
+	Table,
+}
+
+impl KnowledgeOrigin {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			KnowledgeOrigin::Extracted => "extracted",
+			KnowledgeOrigin::Imported => "imported",
+			KnowledgeOrigin::Table => "table",
+		}
+	}
+}
+
 #[derive(Debug, Serialize, Deserialize, Default, Clone)]
 pub struct SemanticKnowledgePayload {
 	pub subject: String,
@@ -54,17 +77,107 @@ pub struct SemanticKnowledgePayload {
 	pub blob: Option<String>,
 	pub event_id: String,
 	pub source_id: String,
+	#[serde(default)]
+	pub origin: KnowledgeOrigin,
+	/// Canonical entity id of the subject, assigned by entity resolution.
+	#[serde(default)]
+	pub subject_id: Option<String>,
+	/// Canonical entity id of the object, assigned by entity resolution.
+	#[serde(default)]
+	pub object_id: Option<String>,
+	/// Headings leading to the section of the document the sentence was read from.
+	#[serde(default)]
+	pub section_path: Option<String>,
+	/// Byte offsets of the chunk holding the sentence in the ingested text it was cut from.
+	#[serde(default)]
+	pub chunk_start: Option<u32>,
+	#[serde(default)]
+	pub chunk_end: Option<u32>,
+	/// Pronoun or description the subject is written as in the sentence, when coreference
+	/// resolution found the entity it refers to.
+	#[serde(default)]
+	pub subject_coreference: Option<String>,
+	#[serde(default)]
+	pub object_coreference: Option<String>,
+	/// Document read before whose chunk the sentence repeats, when deduplication links
+	/// duplicates rather than skipping them.
+	#[serde(default)]
+	pub duplicate_of: Option<String>,
 }
 
 impl SemanticKnowledgePayload {
+	/// The chunk span is set rather than merged on, as Cypher cannot merge on null properties.
 	pub fn to_cypher_query(&self) -> String {
+		let subject_node = Self::node_merge_clause(
+			"n1",
+			&self.subject_type,
+			"entity1",
+			"subject_id",
+			self.subject_id.is_some(),
+		);
+		let object_node = Self::node_merge_clause(
+			"n2",
+			&self.object_type,
+			"entity2",
+			"object_id",
+			self.object_id.is_some(),
+		);
 		format!(
-			"MERGE (n1:`{entity_type1}` {{name: $entity1}}) \
-			MERGE (n2:`{entity_type2}` {{name: $entity2}}) \
-			MERGE (n1)-[:`{predicate}` {{sentence: $sentence, document_id: $document_id, document_source: $document_source, predicate_type: $predicate_type, image_id: $image_id}}]->(n2)",
-			entity_type1 = &self.subject_type,
+			"{subject_node} \
+			{object_node} \
+			MERGE (n1)-[relationship:`{predicate}` {{sentence: $sentence, document_id: $document_id, document_source: $document_source, predicate_type: $predicate_type, image_id: $image_id, origin: $origin, section_path: $section_path, subject_mention: $entity1, object_mention: $entity2, subject_coreference: $subject_coreference, object_coreference: $object_coreference, duplicate_of: $duplicate_of}}]->(n2) \
+			SET relationship.chunk_start = $chunk_start, relationship.chunk_end = $chunk_end",
 			predicate = &self.predicate,
-			entity_type2 = &self.object_type,
 		)
 	}
+
+	/// Nodes with a canonical entity id are merged on that id so that every surface form of an
+	/// entity ends up on the same node; the surface forms are kept in the `aliases` property.
+	fn node_merge_clause(
+		node: &str,
+		entity_type: &str,
+		name_param: &str,
+		id_param: &str,
+		has_canonical_id: bool,
+	) -> String {
+		if has_canonical_id {
+			format!(
+				"MERGE ({node}:`{entity_type}` {{canonical_id: ${id_param}}}) \
+				ON CREATE SET {node}.name = ${name_param}, {node}.aliases = [${name_param}] \
+				ON MATCH SET {node}.aliases = CASE WHEN ${name_param} IN coalesce({node}.aliases, []) \
+				THEN {node}.aliases ELSE coalesce({node}.aliases, []) + ${name_param} END"
+			)
+		} else {
+			format!("MERGE ({node}:`{entity_type}` {{name: ${name_param}}})")
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn cypher_query_merges_on_canonical_id_when_resolved() {
+		let mut payload = SemanticKnowledgePayload {
+			subject: "i.b.m.".to_string(),
+			subject_type: "org".to_string(),
+			object: "armonk".to_string(),
+			object_type: "location".to_string(),
+			predicate: "headquartered_in".to_string(),
+			subject_id: Some("ent_1".to_string()),
+			..Default::default()
+		};
+		let query = payload.to_cypher_query();
+		assert!(query.starts_with("MERGE (n1:`org` {canonical_id: $subject_id})"));
+		assert!(query.contains("MERGE (n2:`location` {name: $entity2})"));
+		assert!(query.contains("subject_mention: $entity1"));
+		assert!(query.contains("object_coreference: $object_coreference"));
+		assert!(query.ends_with(
+			"SET relationship.chunk_start = $chunk_start, relationship.chunk_end = $chunk_end"
+		));
+
+		payload.subject_id = None;
+		assert!(payload.to_cypher_query().starts_with("MERGE (n1:`org` {name: $entity1})"));
+	}
 }
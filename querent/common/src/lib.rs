@@ -53,6 +53,8 @@ pub mod schemas;
 pub use schemas::*;
 pub mod streaming;
 pub mod tools;
+pub mod trace_context;
+pub use trace_context::*;
 use std::path::PathBuf;
 pub use streaming::*;
 
@@ -0,0 +1,176 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use opentelemetry::{global, trace::TraceContextExt};
+use std::{
+	collections::{HashMap, VecDeque},
+	sync::{Arc, Mutex},
+};
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Number of documents whose trace context a pipeline remembers at once.
+const MAX_TRACKED_DOCUMENTS: usize = 1024;
+
+/// W3C trace context carried inside pipeline messages.
+///
+/// Actors run their handlers in their own tasks, so the current span does not follow a message
+/// from one actor to the next. Messages capture the context when they are built and the
+/// receiving actor parents its span to it, which stitches the path of a document into one trace.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraceContext(HashMap<String, String>);
+
+impl TraceContext {
+	/// Captures the context of the current span. Empty when tracing export is disabled.
+	pub fn current() -> Self {
+		Self::from_span(&Span::current())
+	}
+
+	pub fn from_span(span: &Span) -> Self {
+		let mut carrier = HashMap::new();
+		global::get_text_map_propagator(|propagator| {
+			propagator.inject_context(&span.context(), &mut carrier)
+		});
+		Self(carrier)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// Makes `span` a child of the captured context.
+	pub fn attach_to(&self, span: &Span) {
+		if self.is_empty() {
+			return;
+		}
+		let parent = global::get_text_map_propagator(|propagator| propagator.extract(&self.0));
+		span.set_parent(parent);
+	}
+
+	/// Links `span` to the captured context without making it a child, for spans that handle
+	/// several documents at once.
+	pub fn link_to(&self, span: &Span) {
+		if self.is_empty() {
+			return;
+		}
+		let context = global::get_text_map_propagator(|propagator| propagator.extract(&self.0));
+		let span_context = context.span().span_context().clone();
+		if span_context.is_valid() {
+			span.add_link(span_context);
+		}
+	}
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for TraceContext {
+	/// Builds a context from propagation headers such as `traceparent` and `tracestate`.
+	fn from_iter<I: IntoIterator<Item = (K, V)>>(headers: I) -> Self {
+		Self(headers.into_iter().map(|(key, value)| (key.into(), value.into())).collect())
+	}
+}
+
+/// Trace contexts of the documents flowing through a pipeline, keyed by file.
+///
+/// Ingested tokens and engine events only know the file they came from. The ingestor records
+/// the context of each document here and the engine runner looks it up when it batches events.
+/// Only the most recent documents are kept.
+#[derive(Clone, Debug, Default)]
+pub struct DocumentTraces {
+	inner: Arc<Mutex<DocumentTracesInner>>,
+}
+
+#[derive(Debug, Default)]
+struct DocumentTracesInner {
+	contexts: HashMap<String, TraceContext>,
+	order: VecDeque<String>,
+}
+
+impl DocumentTraces {
+	pub fn insert(&self, file: &str, trace_context: TraceContext) {
+		if trace_context.is_empty() {
+			return;
+		}
+		let mut inner = self.inner.lock().unwrap();
+		if inner.contexts.insert(file.to_string(), trace_context).is_none() {
+			inner.order.push_back(file.to_string());
+		}
+		while inner.order.len() > MAX_TRACKED_DOCUMENTS {
+			if let Some(evicted) = inner.order.pop_front() {
+				inner.contexts.remove(&evicted);
+			}
+		}
+	}
+
+	pub fn get(&self, file: &str) -> Option<TraceContext> {
+		self.inner.lock().unwrap().contexts.get(file).cloned()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use opentelemetry::{
+		sdk::{propagation::TraceContextPropagator, trace::TracerProvider},
+		trace::TracerProvider as _,
+	};
+	use tracing_subscriber::layer::SubscriberExt;
+
+	fn trace_id(span: &Span) -> opentelemetry::trace::TraceId {
+		span.context().span().span_context().trace_id()
+	}
+
+	#[test]
+	fn trace_context_round_trips_between_spans() {
+		global::set_text_map_propagator(TraceContextPropagator::new());
+		let tracer = TracerProvider::builder().build().tracer("test");
+		let subscriber =
+			tracing_subscriber::registry().with(tracing_opentelemetry::layer().with_tracer(tracer));
+
+		tracing::subscriber::with_default(subscriber, || {
+			let collect_span = tracing::info_span!("collect");
+			let trace_context = collect_span.in_scope(TraceContext::current);
+			assert!(trace_context.0.contains_key("traceparent"));
+
+			let ingest_span = tracing::info_span!("ingest");
+			trace_context.attach_to(&ingest_span);
+			assert_eq!(trace_id(&ingest_span), trace_id(&collect_span));
+
+			let unrelated_span = tracing::info_span!("unrelated");
+			TraceContext::default().attach_to(&unrelated_span);
+			assert_ne!(trace_id(&unrelated_span), trace_id(&collect_span));
+		});
+	}
+
+	#[test]
+	fn document_traces_evict_oldest_documents() {
+		let document_traces = DocumentTraces::default();
+		let trace_context: TraceContext =
+			[("traceparent", "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01")]
+				.into_iter()
+				.collect();
+		for i in 0..=MAX_TRACKED_DOCUMENTS {
+			document_traces.insert(&format!("file_{i}"), trace_context.clone());
+		}
+		document_traces.insert("empty", TraceContext::default());
+
+		assert!(document_traces.get("file_0").is_none());
+		assert!(document_traces.get("empty").is_none());
+		assert_eq!(
+			document_traces.get(&format!("file_{MAX_TRACKED_DOCUMENTS}")),
+			Some(trace_context)
+		);
+	}
+}
@@ -22,22 +22,24 @@ use std::{
 	sync::atomic::{AtomicU64, Ordering},
 };
 
-use crate::CollectedBytes;
+use crate::{CollectedBytes, TraceContext};
 
 #[derive(Debug, Default, Clone)]
 pub struct EventsBatch {
 	pub qflow_id: String,
 	pub events: HashMap<EventType, Vec<EventState>>,
 	pub timestamp: u64,
+	pub trace_context: TraceContext,
 }
 
 impl EventsBatch {
+	/// Creates a batch carrying the trace context of the current span.
 	pub fn new(
 		qflow_id: String,
 		events: HashMap<EventType, Vec<EventState>>,
 		timestamp: u64,
 	) -> Self {
-		Self { qflow_id, events, timestamp }
+		Self { qflow_id, events, timestamp, trace_context: TraceContext::current() }
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -138,16 +140,24 @@ pub struct CollectionBatch {
 	pub ext: String,
 	pub bytes: Vec<CollectedBytes>,
 	pub _permit: Option<tokio::sync::OwnedSemaphorePermit>,
+	pub trace_context: TraceContext,
 }
 
 impl CollectionBatch {
+	/// Creates a batch carrying the trace context of the current span.
 	pub fn new(
 		file: &String,
 		ext: &String,
 		bytes: Vec<CollectedBytes>,
 		_permit: Option<tokio::sync::OwnedSemaphorePermit>,
 	) -> Self {
-		Self { file: file.clone(), bytes, ext: ext.clone(), _permit }
+		Self {
+			file: file.clone(),
+			bytes,
+			ext: ext.clone(),
+			_permit,
+			trace_context: TraceContext::current(),
+		}
 	}
 
 	pub fn file(&self) -> String {
@@ -31,6 +31,8 @@ pub struct CollectedBytes {
 	pub source_id: String,
 	pub _owned_permit: Option<tokio::sync::OwnedSemaphorePermit>,
 	pub image_id: Option<String>,
+	/// MIME type of the document as reported by its source, if any.
+	pub content_type: Option<String>,
 }
 
 impl Debug for CollectedBytes {
@@ -42,6 +44,7 @@ impl Debug for CollectedBytes {
 			.field("extension", &self.extension)
 			.field("size", &self.size)
 			.field("source_id", &self.source_id)
+			.field("content_type", &self.content_type)
 			.finish()
 	}
 }
@@ -68,10 +71,17 @@ impl CollectedBytes {
 			size,
 			source_id,
 			_owned_permit: _permit,
+			content_type: None,
 			image_id: None,
 		}
 	}
 
+	/// Sets the MIME type the source reported for the document.
+	pub fn with_content_type(mut self, content_type: Option<String>) -> Self {
+		self.content_type = content_type;
+		self
+	}
+
 	pub fn is_eof(&self) -> bool {
 		self.eof
 	}
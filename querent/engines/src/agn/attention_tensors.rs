@@ -17,26 +17,30 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use crate::utils::{
-	add_attention_to_classified_sentences, calculate_biased_sentence_embedding,
+	add_attention_to_classified_sentences, calculate_biased_sentence_embedding, clean_chunk,
 	create_binary_pairs, extract_entities_and_types, generate_custom_comb_uuid,
 	label_entities_in_sentences, match_entities_with_tokens, merge_similar_relations,
-	remove_newlines, select_highest_score_relation, split_into_chunks, tokens_to_words,
-	ClassifiedSentence, ClassifiedSentenceWithRelations,
+	select_highest_score_relation, tokens_to_words, ClassifiedSentence,
+	ClassifiedSentenceWithRelations,
 };
 use async_stream::stream;
 use async_trait::async_trait;
-use common::{EventState, EventType, SemanticKnowledgePayload, VectorPayload};
+use common::{EventState, EventType, KnowledgeOrigin, SemanticKnowledgePayload, VectorPayload};
 use fastembed::TextEmbedding;
 use futures::Stream;
 use llms::llm::LLM;
 use proto::semantics::IngestedTokens;
-use std::{pin::Pin, sync::Arc};
+use std::{collections::HashMap, pin::Pin, sync::Arc};
 
 use crate::{
 	agn::{
 		attention_based_filter::{IndividualFilter, SearchBeam, Token},
 		attention_based_search::{perform_search, Entity, EntityPair},
+		coreference::CoreferenceResolver,
+		predicate_normalizer::PredicateNormalizer,
 	},
+	chunking::{Chunk, Chunker},
+	metrics::ENGINE_METRICS,
 	Engine, EngineError, EngineErrorKind, EngineResult,
 };
 use tokio::sync::mpsc::Receiver;
@@ -50,9 +54,15 @@ pub struct AttentionTensorsEngine {
 	/// Sample entity names for comparison and classification.
 	pub sample_entities: Vec<String>,
 	/// Optional text embedding model.
-	embedding_model: Option<TextEmbedding>,
+	embedding_model: Option<Arc<TextEmbedding>>,
 	/// Optional Named Entity Recognition (NER) model.
 	ner_llm: Option<Arc<dyn LLM>>,
+	/// Optional predicate schema normalizer.
+	predicate_normalizer: Option<Arc<PredicateNormalizer>>,
+	/// Cuts the ingested text into the chunks relations are searched in.
+	chunker: Arc<dyn Chunker>,
+	/// Optional resolver of pronouns and descriptions to the entities they refer to.
+	coreference_resolver: Option<Arc<CoreferenceResolver>>,
 }
 
 impl AttentionTensorsEngine {
@@ -65,6 +75,9 @@ impl AttentionTensorsEngine {
 	/// * `sample_entities` - Sample entity names for comparison and classification.
 	/// * `embedding_model` - Optional text embedding model.
 	/// * `ner_llm` - Optional Named Entity Recognition (NER) model.
+	/// * `predicate_normalizer` - Optional normalizer mapping predicates to a schema.
+	/// * `chunker` - Chunker cutting the ingested text into chunks.
+	/// * `coreference_resolver` - Optional resolver of pronouns and descriptions.
 	///
 	/// # Returns
 	///
@@ -73,10 +86,22 @@ impl AttentionTensorsEngine {
 		llm: Arc<dyn LLM>,
 		entities: Vec<String>,
 		sample_entities: Vec<String>,
-		embedding_model: Option<TextEmbedding>,
+		embedding_model: Option<Arc<TextEmbedding>>,
 		ner_llm: Option<Arc<dyn LLM>>, // Accept as optional
+		predicate_normalizer: Option<Arc<PredicateNormalizer>>,
+		chunker: Arc<dyn Chunker>,
+		coreference_resolver: Option<Arc<CoreferenceResolver>>,
 	) -> Self {
-		Self { llm, entities, sample_entities, embedding_model, ner_llm }
+		Self {
+			llm,
+			entities,
+			sample_entities,
+			embedding_model,
+			ner_llm,
+			predicate_normalizer,
+			chunker,
+			coreference_resolver,
+		}
 	}
 }
 
@@ -109,20 +134,31 @@ impl Engine for AttentionTensorsEngine {
 			let mut entities = self.entities.clone();
 			let mut sample_entities = self.sample_entities.clone();
 			let llm = &self.llm;
+			let model_name = llm.model_name();
 			let mut token_stream = token_stream;
 			while let Some(token) = token_stream.recv().await {
 				if token.data.is_empty() {
 					continue;
 				}
+				let inference_timer = ENGINE_METRICS
+					.inference_duration_seconds
+					.with_label_values([&model_name])
+					.start_timer();
 				let doc_source = &token.doc_source;
 				let file = &token.file;
-				let cleaned_data: Vec<String> =
-					token.data.into_iter().map(|s| remove_newlines(&s)).collect();
 				let source_id = &token.source_id;
 				let mut all_chunks = Vec::new();
-				for data in cleaned_data {
-					let split_chunks = split_into_chunks(max_tokens, &data);
-					all_chunks.extend(split_chunks);
+				// where each chunk was cut from the ingested text, for the provenance of its triples
+				let mut chunk_spans: HashMap<String, Chunk> = HashMap::new();
+				for data in &token.data {
+					for chunk in self.chunker.chunk(data, max_tokens) {
+						let text = clean_chunk(chunk.text(data));
+						if text.is_empty() {
+							continue;
+						}
+						chunk_spans.entry(text.clone()).or_insert(chunk);
+						all_chunks.push(text);
+					}
 				}
 				let mut tokenized_chunks = Vec::new();
 				for chunk in &all_chunks {
@@ -130,15 +166,19 @@ impl Engine for AttentionTensorsEngine {
 						llm.tokenize(chunk).await.map_err(|e| EngineError::from(e))?;
 					tokenized_chunks.push(tokenized_chunk);
 				}
-				let classified_sentences = if !entities.is_empty() {
+				let token_count: usize = tokenized_chunks.iter().map(|chunk| chunk.len()).sum();
+				ENGINE_METRICS
+					.tokens_processed_total
+					.with_label_values([&model_name])
+					.inc_by(token_count as u64);
+				let tokenized_words = tokens_to_words(llm.as_ref(), &tokenized_chunks).await;
+				let mut classified_sentences = if !entities.is_empty() {
 					let initial_classified_sentences =
 						label_entities_in_sentences(&entities, &all_chunks);
-					let tokenized_words = tokens_to_words(llm.as_ref(), &tokenized_chunks).await;
 					match_entities_with_tokens(&tokenized_words, &initial_classified_sentences)
 				} else {
 					let mut model_inputs = Vec::new();
 					let mut tokenized_chunks_ner = Vec::new();
-					let tokenized_words = tokens_to_words(llm.as_ref(), &tokenized_chunks).await;
 					let mut classified_sentences = Vec::new();
 					if let Some(ref ner_llm) = self.ner_llm {
 						for chunk in &all_chunks {
@@ -167,6 +207,19 @@ impl Engine for AttentionTensorsEngine {
 					}
 					match_entities_with_tokens(&tokenized_words, &classified_sentences)
 				};
+				// the text of the mentions resolved in each chunk, by their token span
+				let resolved_mentions = match &self.coreference_resolver {
+					Some(resolver) =>
+						resolver
+							.resolve(
+								llm.as_ref(),
+								&tokenized_words,
+								&tokenized_chunks,
+								&mut classified_sentences,
+							)
+							.await?,
+					None => Vec::new(),
+				};
 				let classified_sentences_with_pairs = create_binary_pairs(&classified_sentences);
 				let extended_classified_sentences_with_attention = add_attention_to_classified_sentences(
 					llm.as_ref(),
@@ -235,10 +288,24 @@ impl Engine for AttentionTensorsEngine {
 					});
 				}
 				merge_similar_relations(&mut all_sentences_with_relations);
+				if let Some(normalizer) = &self.predicate_normalizer {
+					normalizer.normalize(embedder, &mut all_sentences_with_relations)?;
+				}
+				inference_timer.observe_duration();
 				if !all_sentences_with_relations.is_empty()  && entities.is_empty(){
 					(entities, sample_entities) = extract_entities_and_types(all_sentences_with_relations.clone());
 				}
-				for sentence_with_relations in all_sentences_with_relations {
+				for (index, sentence_with_relations) in
+					all_sentences_with_relations.into_iter().enumerate()
+				{
+					let chunk_span =
+						chunk_spans.get(&sentence_with_relations.classified_sentence.sentence);
+					let coreference = |entity: &Entity| {
+						resolved_mentions
+							.get(index)
+							.and_then(|mentions| mentions.get(&(entity.start_idx, entity.end_idx)))
+							.cloned()
+					};
 					let mut event_ids = Vec::new();
 					for head_tail_relation in &sentence_with_relations.relations {
 						for (predicate, _score) in &head_tail_relation.relations {
@@ -264,6 +331,15 @@ impl Engine for AttentionTensorsEngine {
 								blob: Some("mock".to_string()),
 								source_id: source_id.to_string(),
 								event_id: event_id,
+								origin: KnowledgeOrigin::Extracted,
+								subject_id: None,
+								object_id: None,
+								section_path: token.section_path.clone(),
+								chunk_start: chunk_span.map(|chunk| chunk.start as u32),
+								chunk_end: chunk_span.map(|chunk| chunk.end as u32),
+								subject_coreference: coreference(&head_tail_relation.head),
+								object_coreference: coreference(&head_tail_relation.tail),
+								duplicate_of: token.duplicate_of.clone(),
 							};
 							let serialized_payload = match serde_json::to_string(&payload) {
 								Ok(json) => json,
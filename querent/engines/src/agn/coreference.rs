@@ -0,0 +1,494 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::collections::{HashMap, HashSet};
+
+use llms::LLM;
+use proto::semantics::{CoreferenceConfig, CoreferenceStrategy};
+
+use crate::{utils::ClassifiedSentence, EngineError, EngineResult};
+
+const DEFAULT_MAX_CHUNK_DISTANCE: usize = 1;
+
+const PERSON_PRONOUNS: &[&str] = &["he", "him", "his", "himself", "she", "her", "hers", "herself"];
+const THING_PRONOUNS: &[&str] = &["it", "its", "itself"];
+const GROUP_PRONOUNS: &[&str] = &["they", "them", "their", "theirs", "themselves"];
+const PERSON_NOUNS: &[&str] = &[
+	"man",
+	"woman",
+	"person",
+	"president",
+	"chairman",
+	"ceo",
+	"director",
+	"founder",
+	"author",
+	"scientist",
+	"researcher",
+	"engineer",
+	"geologist",
+];
+const ORGANIZATION_NOUNS: &[&str] = &[
+	"company",
+	"firm",
+	"corporation",
+	"organization",
+	"organisation",
+	"operator",
+	"group",
+	"agency",
+	"university",
+	"bank",
+	"government",
+	"ministry",
+	"startup",
+	"enterprise",
+];
+const LOCATION_NOUNS: &[&str] = &[
+	"city", "town", "village", "capital", "country", "nation", "state", "province", "county",
+	"region", "area", "island", "basin", "field", "site",
+];
+/// Words after "it is" / "it was" that make the pronoun refer to nothing ("it is likely that").
+const PLEONASTIC_WORDS: &[&str] = &[
+	"important",
+	"possible",
+	"likely",
+	"unlikely",
+	"clear",
+	"unclear",
+	"necessary",
+	"known",
+	"believed",
+	"expected",
+	"reported",
+	"thought",
+	"said",
+	"true",
+	"difficult",
+	"easy",
+	"worth",
+];
+
+/// Mentions resolved in a chunk: the pronoun or description written at a token span.
+pub type ResolvedMentions = HashMap<(usize, usize), String>;
+
+/// Kinds of entities, read from the labels of the NER model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Kind {
+	Person,
+	Organization,
+	Location,
+	Other,
+}
+
+/// The entities a mention can refer to.
+#[derive(Clone, Debug, PartialEq)]
+enum Agreement {
+	/// "he", "she": people.
+	Person,
+	/// "it": anything but people.
+	Thing,
+	/// "they": organizations.
+	Group,
+	/// "the company": entities labelled with the noun, or of the kind of the noun.
+	Described { noun: String, kind: Option<Kind> },
+}
+
+impl Agreement {
+	fn agrees_with(&self, label: &str) -> bool {
+		let kind = kind_of(label);
+		match self {
+			Agreement::Person => matches!(kind, Some(Kind::Person) | None),
+			Agreement::Thing => kind != Some(Kind::Person),
+			Agreement::Group => matches!(kind, Some(Kind::Organization) | None),
+			Agreement::Described { noun, kind: noun_kind } =>
+				label.eq_ignore_ascii_case(noun) || (noun_kind.is_some() && kind == *noun_kind),
+		}
+	}
+}
+
+/// A pronoun or definite description, by its token span in a chunk.
+#[derive(Clone, Debug, PartialEq)]
+struct Mention {
+	start: usize,
+	end: usize,
+	text: String,
+	agreement: Agreement,
+}
+
+/// An entity named, or resolved, in a chunk of the document.
+#[derive(Clone, Debug, PartialEq)]
+struct Antecedent {
+	chunk: usize,
+	start: usize,
+	end: usize,
+	name: String,
+	label: String,
+}
+
+/// Resolves pronouns and definite descriptions to the entities they refer to, so that
+/// relations are searched between the entities named in different sentences too.
+pub struct CoreferenceResolver {
+	strategy: CoreferenceStrategy,
+	max_chunk_distance: usize,
+}
+
+impl CoreferenceResolver {
+	/// Returns `None` when coreference resolution is disabled.
+	pub fn new(config: &CoreferenceConfig) -> Option<Self> {
+		if config.enabled == Some(false) {
+			return None;
+		}
+		Some(Self {
+			strategy: CoreferenceStrategy::from_i32(config.strategy)
+				.unwrap_or(CoreferenceStrategy::Rules),
+			max_chunk_distance: config
+				.max_chunk_distance
+				.map_or(DEFAULT_MAX_CHUNK_DISTANCE, |distance| distance as usize),
+		})
+	}
+
+	/// Adds the mentions of every chunk to its entities, under the name and label of the entity
+	/// they refer to. The sentences are the consecutive chunks of a document, with entities at
+	/// the positions of the words `llm` split them into.
+	pub async fn resolve(
+		&self,
+		llm: &dyn LLM,
+		tokenized_words: &[Vec<String>],
+		tokenized_chunks: &[Vec<i32>],
+		sentences: &mut [ClassifiedSentence],
+	) -> EngineResult<Vec<ResolvedMentions>> {
+		let labels: HashSet<String> = sentences
+			.iter()
+			.flat_map(|sentence| &sentence.entities)
+			.map(|(_, label, _, _)| label.to_lowercase())
+			.collect();
+		let mut antecedents = Vec::new();
+		let mut resolved = Vec::with_capacity(sentences.len());
+		for (chunk, sentence) in sentences.iter_mut().enumerate() {
+			let words = tokenized_words.get(chunk).map(Vec::as_slice).unwrap_or_default();
+			let mentions = find_mentions(words, &labels, &sentence.entities);
+			let attention = match tokenized_chunks.get(chunk) {
+				Some(tokens)
+					if self.strategy == CoreferenceStrategy::Attention &&
+						!mentions.is_empty() &&
+						sentence.entities.len() > 1 =>
+					Some(chunk_attention(llm, tokens).await?),
+				_ => None,
+			};
+			resolved.push(self.resolve_chunk(
+				chunk,
+				&mentions,
+				attention.as_deref(),
+				&mut sentence.entities,
+				&mut antecedents,
+			));
+		}
+		Ok(resolved)
+	}
+
+	/// Resolves the mentions of a chunk against the entities before them, adding the chunk's
+	/// entities and resolved mentions to the antecedents of the next chunks.
+	fn resolve_chunk(
+		&self,
+		chunk: usize,
+		mentions: &[Mention],
+		attention: Option<&[Vec<f32>]>,
+		entities: &mut Vec<(String, String, usize, usize)>,
+		antecedents: &mut Vec<Antecedent>,
+	) -> ResolvedMentions {
+		let mut named: Vec<Antecedent> = entities
+			.iter()
+			.map(|(name, label, start, end)| Antecedent {
+				chunk,
+				start: *start,
+				end: *end,
+				name: name.clone(),
+				label: label.clone(),
+			})
+			.collect();
+		named.sort_by_key(|antecedent| antecedent.start);
+		let mut named = named.into_iter().peekable();
+		let mut resolved = ResolvedMentions::new();
+		for mention in mentions {
+			while let Some(antecedent) = named.next_if(|antecedent| antecedent.end < mention.start)
+			{
+				antecedents.push(antecedent);
+			}
+			let Some(antecedent) = self.antecedent(chunk, mention, attention, antecedents) else {
+				continue;
+			};
+			entities.push((
+				antecedent.name.clone(),
+				antecedent.label.clone(),
+				mention.start,
+				mention.end,
+			));
+			resolved.insert((mention.start, mention.end), mention.text.clone());
+			antecedents.push(Antecedent {
+				chunk,
+				start: mention.start,
+				end: mention.end,
+				..antecedent
+			});
+		}
+		antecedents.extend(named);
+		resolved
+	}
+
+	/// The closest agreeing entity before the mention, or with attention, the agreeing entity
+	/// of its chunk the mention attends to most.
+	fn antecedent(
+		&self,
+		chunk: usize,
+		mention: &Mention,
+		attention: Option<&[Vec<f32>]>,
+		antecedents: &[Antecedent],
+	) -> Option<Antecedent> {
+		let mut candidates = antecedents
+			.iter()
+			.rev()
+			.take_while(|antecedent| antecedent.chunk + self.max_chunk_distance >= chunk)
+			.filter(|antecedent| mention.agreement.agrees_with(&antecedent.label));
+		if let Some(attention) = attention {
+			let attended = candidates
+				.clone()
+				.filter(|antecedent| antecedent.chunk == chunk)
+				.map(|antecedent| (antecedent, attention_score(attention, mention, antecedent)))
+				.max_by(|a, b| a.1.total_cmp(&b.1));
+			if let Some((antecedent, _)) = attended {
+				return Some(antecedent.clone());
+			}
+		}
+		candidates.next().cloned()
+	}
+}
+
+/// Pronouns and definite descriptions among the words of a chunk, outside of its entities.
+fn find_mentions(
+	words: &[String],
+	labels: &HashSet<String>,
+	entities: &[(String, String, usize, usize)],
+) -> Vec<Mention> {
+	let words: Vec<String> = words.iter().map(|word| word.trim().to_lowercase()).collect();
+	// words cut into pieces by the tokenizer are not pronouns
+	let whole = |i: usize| !words.get(i + 1).is_some_and(|next| next.starts_with("##"));
+	let in_entity =
+		|i: usize| entities.iter().any(|(_, _, start, end)| (*start..=*end).contains(&i));
+	let mut mentions = Vec::new();
+	let mut i = 0;
+	while i < words.len() {
+		let word = words[i].as_str();
+		if in_entity(i) || !whole(i) {
+			i += 1;
+			continue;
+		}
+		let agreement = if PERSON_PRONOUNS.contains(&word) {
+			Some(Agreement::Person)
+		} else if THING_PRONOUNS.contains(&word) {
+			(!is_pleonastic(&words[i + 1..])).then_some(Agreement::Thing)
+		} else if GROUP_PRONOUNS.contains(&word) {
+			Some(Agreement::Group)
+		} else {
+			None
+		};
+		let description = words
+			.get(i + 1)
+			.filter(|_| (word == "the" || word == "this") && whole(i + 1) && !in_entity(i + 1))
+			.and_then(|noun| Some((noun, noun_kind(noun, labels)?)));
+		if let Some(agreement) = agreement {
+			mentions.push(Mention { start: i, end: i, text: word.to_string(), agreement });
+		} else if let Some((noun, kind)) = description {
+			mentions.push(Mention {
+				start: i,
+				end: i + 1,
+				text: format!("{} {}", word, noun),
+				agreement: Agreement::Described { noun: noun.clone(), kind },
+			});
+			i += 1;
+		}
+		i += 1;
+	}
+	mentions
+}
+
+/// Kind of the entities a noun describes: `Some(None)` for nouns that are only entity labels
+/// of the document, `None` for nouns that describe no entity.
+fn noun_kind(noun: &str, labels: &HashSet<String>) -> Option<Option<Kind>> {
+	if PERSON_NOUNS.contains(&noun) {
+		Some(Some(Kind::Person))
+	} else if ORGANIZATION_NOUNS.contains(&noun) {
+		Some(Some(Kind::Organization))
+	} else if LOCATION_NOUNS.contains(&noun) {
+		Some(Some(Kind::Location))
+	} else if labels.contains(noun) {
+		Some(None)
+	} else {
+		None
+	}
+}
+
+/// Whether "it" is followed by words making it refer to nothing, as in "it seems" or "it is
+/// likely".
+fn is_pleonastic(next: &[String]) -> bool {
+	match next {
+		[verb, ..] if verb == "seems" || verb == "appears" => true,
+		[verb, word, ..] if verb == "is" || verb == "was" =>
+			PLEONASTIC_WORDS.contains(&word.as_str()),
+		_ => false,
+	}
+}
+
+/// Kind of entity of an NER label, `None` for entities without a label.
+fn kind_of(label: &str) -> Option<Kind> {
+	let label = label.to_lowercase();
+	let label = label.strip_prefix("b-").or_else(|| label.strip_prefix("i-")).unwrap_or(&label);
+	match label {
+		"" | "unlabelled" => None,
+		"per" | "person" => Some(Kind::Person),
+		"org" | "organization" | "organisation" | "company" => Some(Kind::Organization),
+		"loc" | "gpe" | "location" | "place" => Some(Kind::Location),
+		_ => Some(Kind::Other),
+	}
+}
+
+/// Mean attention from the words of the mention to the words of the entity.
+fn attention_score(attention: &[Vec<f32>], mention: &Mention, antecedent: &Antecedent) -> f32 {
+	let mut total = 0.0;
+	let mut count = 0;
+	for row in attention.get(mention.start..=mention.end).unwrap_or_default() {
+		for weight in row.get(antecedent.start..=antecedent.end).unwrap_or_default() {
+			total += weight;
+			count += 1;
+		}
+	}
+	if count == 0 {
+		0.0
+	} else {
+		total / count as f32
+	}
+}
+
+/// Attention between the words of a chunk, averaged over the heads of the last layer.
+async fn chunk_attention(llm: &dyn LLM, tokens: &[i32]) -> EngineResult<Vec<Vec<f32>>> {
+	let model_input = llm.model_input(tokens.to_vec()).await.map_err(EngineError::from)?;
+	let attention = llm.inference_attention(model_input).await.map_err(EngineError::from)?;
+	llm.attention_tensor_to_2d_vector(&attention).await.map_err(EngineError::from)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn words(text: &str) -> Vec<String> {
+		text.split_whitespace().map(str::to_string).collect()
+	}
+
+	fn entity(name: &str, label: &str, start: usize, end: usize) -> (String, String, usize, usize) {
+		(name.to_string(), label.to_string(), start, end)
+	}
+
+	fn resolver(strategy: CoreferenceStrategy) -> CoreferenceResolver {
+		CoreferenceResolver::new(&CoreferenceConfig {
+			strategy: strategy as i32,
+			..Default::default()
+		})
+		.unwrap()
+	}
+
+	/// Resolves chunks of words with the rules, or with the given attention for every chunk.
+	fn resolve(
+		resolver: &CoreferenceResolver,
+		chunks: &[&str],
+		entities: &mut [Vec<(String, String, usize, usize)>],
+		attention: Option<&[Vec<f32>]>,
+	) -> Vec<ResolvedMentions> {
+		let labels: HashSet<String> =
+			entities.iter().flatten().map(|(_, label, _, _)| label.to_lowercase()).collect();
+		let mut antecedents = Vec::new();
+		chunks
+			.iter()
+			.zip(entities.iter_mut())
+			.enumerate()
+			.map(|(chunk, (text, entities))| {
+				let mentions = find_mentions(&words(text), &labels, entities);
+				resolver.resolve_chunk(chunk, &mentions, attention, entities, &mut antecedents)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn resolves_pronouns_and_descriptions_across_chunks() {
+		let chunks = [
+			"Aramco hired Amin Nasser in Dhahran .",
+			"He said the company grew as it expanded .",
+			"It is likely that Ghawar flowed ; the well and its output rose .",
+		];
+		let mut entities = vec![
+			vec![
+				entity("aramco", "B-ORG", 0, 0),
+				entity("amin nasser", "B-PER", 2, 3),
+				entity("dhahran", "B-LOC", 5, 5),
+			],
+			vec![],
+			vec![entity("ghawar", "well", 4, 4)],
+		];
+		let resolved = resolve(&resolver(CoreferenceStrategy::Rules), &chunks, &mut entities, None);
+		assert_eq!(
+			entities[1],
+			vec![
+				entity("amin nasser", "B-PER", 0, 0),
+				entity("aramco", "B-ORG", 2, 3),
+				entity("aramco", "B-ORG", 6, 6),
+			]
+		);
+		assert_eq!(resolved[1].get(&(0, 0)).map(String::as_str), Some("he"));
+		assert_eq!(resolved[1].get(&(2, 3)).map(String::as_str), Some("the company"));
+		// "it is likely" refers to nothing
+		assert!(!resolved[2].contains_key(&(0, 0)));
+		assert_eq!(resolved[2].get(&(7, 8)).map(String::as_str), Some("the well"));
+		assert_eq!(entities[2].last(), Some(&entity("ghawar", "well", 10, 10)));
+	}
+
+	#[test]
+	fn picks_the_most_attended_entity_of_the_chunk() {
+		let mut entities =
+			vec![vec![entity("aramco", "B-ORG", 0, 0), entity("shell", "B-ORG", 2, 2)]];
+		let mut attention = vec![vec![0.0; 6]; 6];
+		attention[4][0] = 0.6;
+		attention[4][2] = 0.1;
+		let resolved = resolve(
+			&resolver(CoreferenceStrategy::Attention),
+			&["Aramco and Shell said it grew"],
+			&mut entities,
+			Some(&attention),
+		);
+		assert_eq!(entities[0][2], entity("aramco", "B-ORG", 4, 4));
+		assert_eq!(resolved[0].get(&(4, 4)).map(String::as_str), Some("it"));
+
+		let mut entities =
+			vec![vec![entity("aramco", "B-ORG", 0, 0), entity("shell", "B-ORG", 2, 2)]];
+		resolve(
+			&resolver(CoreferenceStrategy::Rules),
+			&["Aramco and Shell said it grew"],
+			&mut entities,
+			None,
+		);
+		assert_eq!(entities[0][2], entity("shell", "B-ORG", 4, 4));
+	}
+}
@@ -22,3 +22,7 @@ pub mod attention_based_search;
 pub use attention_based_search::*;
 pub mod attention_based_filter;
 pub use attention_based_filter::*;
+pub mod coreference;
+pub use coreference::*;
+pub mod predicate_normalizer;
+pub use predicate_normalizer::*;
@@ -0,0 +1,209 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{collections::HashMap, sync::Arc};
+
+use fastembed::TextEmbedding;
+use proto::semantics::{PredicateSchema, UnmatchedRelationPolicy};
+
+use crate::{utils::ClassifiedSentenceWithRelations, EngineError, EngineErrorKind, EngineResult};
+
+/// Relation type used for relations that match no schema type.
+pub const RELATED_TO: &str = "related_to";
+const DEFAULT_SIMILARITY_THRESHOLD: f32 = 0.5;
+
+/// Maps free-text predicates produced by the attention filter onto the relation types of a
+/// [`PredicateSchema`] by embedding similarity.
+pub struct PredicateNormalizer {
+	relation_types: Vec<String>,
+	/// Embedding of each relation type and its examples, tagged with the index of the type.
+	type_embeddings: Vec<(usize, Vec<f32>)>,
+	similarity_threshold: f32,
+	unmatched: UnmatchedRelationPolicy,
+}
+
+impl PredicateNormalizer {
+	/// Embeds the relation types of the schema. Returns `None` when the schema has no types.
+	pub fn new(schema: &PredicateSchema, embedder: &TextEmbedding) -> EngineResult<Option<Self>> {
+		let mut owners = Vec::new();
+		let mut texts = Vec::new();
+		let mut relation_types = Vec::new();
+		for relation_type in &schema.relation_types {
+			let name = relation_type.name.trim();
+			if name.is_empty() {
+				continue;
+			}
+			let index = relation_types.len();
+			relation_types.push(name.to_string());
+			let mut description = humanize(name);
+			if let Some(text) = relation_type.description.as_deref().filter(|d| !d.is_empty()) {
+				description = format!("{}: {}", description, text);
+			}
+			owners.push(index);
+			texts.push(description);
+			for example in relation_type.examples.iter().filter(|e| !e.trim().is_empty()) {
+				owners.push(index);
+				texts.push(example.trim().to_string());
+			}
+		}
+		if relation_types.is_empty() {
+			return Ok(None);
+		}
+		let embeddings = embedder.embed(texts, None).map_err(|e| {
+			EngineError::new(EngineErrorKind::ModelError, Arc::new(anyhow::anyhow!(e)))
+		})?;
+		Ok(Some(Self::from_embeddings(
+			relation_types,
+			owners.into_iter().zip(embeddings).collect(),
+			schema.similarity_threshold.unwrap_or(DEFAULT_SIMILARITY_THRESHOLD),
+			UnmatchedRelationPolicy::from_i32(schema.unmatched)
+				.unwrap_or(UnmatchedRelationPolicy::RelatedTo),
+		)))
+	}
+
+	fn from_embeddings(
+		relation_types: Vec<String>,
+		type_embeddings: Vec<(usize, Vec<f32>)>,
+		similarity_threshold: f32,
+		unmatched: UnmatchedRelationPolicy,
+	) -> Self {
+		Self { relation_types, type_embeddings, similarity_threshold, unmatched }
+	}
+
+	/// Names of the relation types of the schema.
+	pub fn relation_types(&self) -> &[String] {
+		&self.relation_types
+	}
+
+	/// Returns the schema type for a predicate embedding, or `None` if the relation is dropped.
+	pub fn closest_relation_type(&self, predicate: &str, embedding: &[f32]) -> Option<&str> {
+		let key = predicate.trim().to_lowercase().replace([' ', '-'], "_");
+		if let Some(name) = self.relation_types.iter().find(|name| name.to_lowercase() == key) {
+			return Some(name);
+		}
+		let best = self
+			.type_embeddings
+			.iter()
+			.map(|(index, type_embedding)| (*index, cosine_similarity(embedding, type_embedding)))
+			.max_by(|a, b| a.1.total_cmp(&b.1));
+		match best {
+			Some((index, similarity)) if similarity >= self.similarity_threshold =>
+				Some(&self.relation_types[index]),
+			_ => match self.unmatched {
+				UnmatchedRelationPolicy::RelatedTo => Some(RELATED_TO),
+				UnmatchedRelationPolicy::Drop => None,
+			},
+		}
+	}
+
+	/// Rewrites the predicates of every relation to schema types, dropping unmatched relations
+	/// when configured to. Predicates that collapse onto the same type keep the highest score.
+	pub fn normalize(
+		&self,
+		embedder: &TextEmbedding,
+		sentences: &mut [ClassifiedSentenceWithRelations],
+	) -> EngineResult<()> {
+		let mut predicates: Vec<String> = sentences
+			.iter()
+			.flat_map(|sentence| &sentence.relations)
+			.flat_map(|relation| relation.relations.iter().map(|(predicate, _)| predicate.clone()))
+			.collect();
+		predicates.sort();
+		predicates.dedup();
+		if predicates.is_empty() {
+			return Ok(());
+		}
+		let embeddings = embedder.embed(predicates.clone(), None).map_err(|e| {
+			EngineError::new(EngineErrorKind::ModelError, Arc::new(anyhow::anyhow!(e)))
+		})?;
+		let mapping: HashMap<String, Option<String>> = predicates
+			.into_iter()
+			.zip(embeddings)
+			.map(|(predicate, embedding)| {
+				let relation_type =
+					self.closest_relation_type(&predicate, &embedding).map(str::to_string);
+				(predicate, relation_type)
+			})
+			.collect();
+
+		for relation in sentences.iter_mut().flat_map(|sentence| sentence.relations.iter_mut()) {
+			let mut normalized: Vec<(String, f32)> = Vec::new();
+			for (predicate, score) in relation.relations.drain(..) {
+				let Some(Some(relation_type)) = mapping.get(&predicate) else { continue };
+				match normalized.iter_mut().find(|(existing, _)| existing == relation_type) {
+					Some(existing) => existing.1 = existing.1.max(score),
+					None => normalized.push((relation_type.clone(), score)),
+				}
+			}
+			relation.relations = normalized;
+		}
+		Ok(())
+	}
+}
+
+fn humanize(name: &str) -> String {
+	name.replace(['_', '-'], " ")
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+	let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+	let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+	let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+	if norm_a == 0.0 || norm_b == 0.0 {
+		0.0
+	} else {
+		dot / (norm_a * norm_b)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn normalizer(unmatched: UnmatchedRelationPolicy) -> PredicateNormalizer {
+		PredicateNormalizer::from_embeddings(
+			vec!["located_in".to_string(), "employs".to_string()],
+			vec![(0, vec![1.0, 0.0, 0.0]), (0, vec![0.8, 0.6, 0.0]), (1, vec![0.0, 1.0, 0.0])],
+			0.7,
+			unmatched,
+		)
+	}
+
+	#[test]
+	fn maps_predicates_to_closest_type() {
+		let normalizer = normalizer(UnmatchedRelationPolicy::RelatedTo);
+		assert_eq!(
+			normalizer.closest_relation_type("sits in", &[0.9, 0.1, 0.0]),
+			Some("located_in")
+		);
+		assert_eq!(normalizer.closest_relation_type("hires", &[0.1, 0.9, 0.0]), Some("employs"));
+		assert_eq!(
+			normalizer.closest_relation_type("Located In", &[0.0, 0.0, 1.0]),
+			Some("located_in")
+		);
+	}
+
+	#[test]
+	fn handles_unmatched_relations_per_policy() {
+		let embedding = [0.0, 0.0, 1.0];
+		let keep = normalizer(UnmatchedRelationPolicy::RelatedTo);
+		assert_eq!(keep.closest_relation_type("drilled", &embedding), Some(RELATED_TO));
+		let drop = normalizer(UnmatchedRelationPolicy::Drop);
+		assert_eq!(drop.closest_relation_type("drilled", &embedding), None);
+	}
+}
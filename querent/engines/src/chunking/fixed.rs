@@ -0,0 +1,55 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use super::{windows, Chunk, Chunker};
+
+/// Cuts text into windows of the maximum length, ending between words, whatever its structure.
+#[derive(Debug, Default)]
+pub struct FixedChunker {
+	overlap: usize,
+}
+
+impl FixedChunker {
+	/// Starts each window `overlap` characters before the end of the previous one, at a word.
+	pub fn new(overlap: usize) -> Self {
+		Self { overlap }
+	}
+}
+
+impl Chunker for FixedChunker {
+	fn chunk(&self, text: &str, max_len: usize) -> Vec<Chunk> {
+		// a window overlapping all of the previous one would never move on
+		let overlap = self.overlap.min(max_len / 2);
+		windows(text, 0, text.len(), max_len, overlap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn overlapping_windows() {
+		let text = "pressure was high at the first well and low at the second";
+		let chunks = FixedChunker::new(10).chunk(text, 25);
+		let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text(text)).collect();
+		assert_eq!(texts[0], "pressure was high at the");
+		assert!(texts[1].starts_with("at the first"));
+		assert!(texts.last().unwrap().ends_with("the second"));
+		assert!(texts.iter().all(|text| text.chars().count() <= 25));
+	}
+}
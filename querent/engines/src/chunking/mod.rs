@@ -0,0 +1,262 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+//! Chunkers cutting the text of ingested tokens into the chunks the engine extracts triples
+//! from. Chunks are byte ranges of the text, so that the knowledge extracted from them can point
+//! back to where it was read.
+
+pub mod fixed;
+pub mod paragraph;
+pub mod semantic;
+pub mod sentence;
+
+pub use fixed::*;
+pub use paragraph::*;
+pub use semantic::*;
+pub use sentence::*;
+
+use fastembed::TextEmbedding;
+use proto::semantics::{ChunkingConfig, ChunkingStrategy};
+use std::sync::Arc;
+use tracing::warn;
+use unicode_segmentation::UnicodeSegmentation;
+
+const DEFAULT_BREAKPOINT_PERCENTILE: f32 = 90.0;
+
+/// A chunk of text, by its byte offsets in the text it was cut from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Chunk {
+	pub start: usize,
+	pub end: usize,
+}
+
+impl Chunk {
+	pub fn text<'a>(&self, text: &'a str) -> &'a str {
+		&text[self.start..self.end]
+	}
+}
+
+/// Cuts text into chunks.
+pub trait Chunker: Send + Sync {
+	/// Cuts `text` into chunks of at most `max_len` characters, in reading order. Whitespace
+	/// between chunks belongs to none of them.
+	fn chunk(&self, text: &str, max_len: usize) -> Vec<Chunk>;
+}
+
+/// Creates the chunker of a pipeline, cutting fixed windows unless configured otherwise. Semantic
+/// chunking needs the embedder and falls back to sentences without it.
+pub fn create_chunker(
+	config: &ChunkingConfig,
+	embedder: Option<Arc<TextEmbedding>>,
+) -> Arc<dyn Chunker> {
+	let overlap = config.overlap.unwrap_or(0) as usize;
+	match (config.strategy(), embedder) {
+		(ChunkingStrategy::Fixed, _) => Arc::new(FixedChunker::new(overlap)),
+		(ChunkingStrategy::Sentence, _) => Arc::new(SentenceChunker::new(overlap)),
+		(ChunkingStrategy::Paragraph, _) => Arc::new(ParagraphChunker::new(overlap)),
+		(ChunkingStrategy::Semantic, Some(embedder)) => Arc::new(SemanticChunker::new(
+			embedder,
+			config.breakpoint_percentile.unwrap_or(DEFAULT_BREAKPOINT_PERCENTILE),
+			overlap,
+		)),
+		(ChunkingStrategy::Semantic, None) => {
+			warn!("Semantic chunking needs an embedder, chunking by sentences instead");
+			Arc::new(SentenceChunker::new(overlap))
+		},
+	}
+}
+
+/// A piece of text chunks are made of, such as a sentence or a paragraph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Unit {
+	pub start: usize,
+	pub end: usize,
+	/// Starts a new chunk, as headings do.
+	pub breaks: bool,
+}
+
+/// The sentences of `text[start..end]`, without surrounding whitespace.
+pub(crate) fn sentence_units(text: &str, start: usize, end: usize) -> Vec<Unit> {
+	text[start..end]
+		.split_sentence_bound_indices()
+		.filter_map(|(offset, sentence)| {
+			let trimmed = sentence.trim_start();
+			let sentence_start = start + offset + sentence.len() - trimmed.len();
+			let sentence_end = sentence_start + trimmed.trim_end().len();
+			(sentence_end > sentence_start).then_some(Unit {
+				start: sentence_start,
+				end: sentence_end,
+				breaks: false,
+			})
+		})
+		.collect()
+}
+
+/// Packs consecutive units into chunks of at most `max_len` characters. A chunk repeats the
+/// last units of the previous one, up to `overlap` characters, unless it starts at a break.
+/// Units longer than `max_len` are cut between words.
+pub(crate) fn pack(text: &str, units: &[Unit], max_len: usize, overlap: usize) -> Vec<Chunk> {
+	let max_len = max_len.max(1);
+	let len = |start: usize, end: usize| text[start..end].chars().count();
+	let mut chunks = Vec::new();
+	let mut current: Vec<Unit> = Vec::new();
+	let pieces = units.iter().flat_map(|unit| {
+		if len(unit.start, unit.end) <= max_len {
+			return vec![*unit];
+		}
+		windows(text, unit.start, unit.end, max_len, 0)
+			.into_iter()
+			.enumerate()
+			.map(|(i, window)| Unit {
+				start: window.start,
+				end: window.end,
+				breaks: unit.breaks && i == 0,
+			})
+			.collect()
+	});
+	for unit in pieces {
+		if let (Some(first), Some(last)) = (current.first(), current.last()) {
+			if unit.breaks || len(first.start, unit.end) > max_len {
+				chunks.push(Chunk { start: first.start, end: last.end });
+				let kept = if unit.breaks {
+					0
+				} else {
+					(1..current.len())
+						.take_while(|kept| {
+							len(current[current.len() - kept].start, last.end) <= overlap
+						})
+						.last()
+						.unwrap_or(0)
+				};
+				current.drain(..current.len() - kept);
+				while current.first().is_some_and(|first| len(first.start, unit.end) > max_len) {
+					current.remove(0);
+				}
+			}
+		}
+		current.push(unit);
+	}
+	if let (Some(first), Some(last)) = (current.first(), current.last()) {
+		chunks.push(Chunk { start: first.start, end: last.end });
+	}
+	chunks
+}
+
+/// Windows of at most `max_len` characters over `text[start..end]`, ending between words when
+/// possible, each starting `overlap` characters before the end of the previous one.
+pub(crate) fn windows(
+	text: &str,
+	start: usize,
+	end: usize,
+	max_len: usize,
+	overlap: usize,
+) -> Vec<Chunk> {
+	let max_len = max_len.max(1);
+	let mut chunks = Vec::new();
+	let mut position = start;
+	loop {
+		position = end - text[position..end].trim_start().len();
+		if position >= end {
+			break;
+		}
+		let mut window_end = text[position..end]
+			.char_indices()
+			.nth(max_len)
+			.map_or(end, |(offset, _)| position + offset);
+		if window_end < end && !text[window_end..].starts_with(char::is_whitespace) {
+			if let Some(space) = text[position..window_end].rfind(char::is_whitespace) {
+				if space > 0 {
+					window_end = position + space;
+				}
+			}
+		}
+		let chunk_end = position + text[position..window_end].trim_end().len();
+		chunks.push(Chunk { start: position, end: chunk_end });
+		if window_end >= end {
+			break;
+		}
+		position = overlap_start(text, position, window_end, overlap).unwrap_or(window_end);
+	}
+	chunks
+}
+
+/// Start of the first word within the last `overlap` characters of `text[start..end]`, when
+/// there is one after `start`.
+fn overlap_start(text: &str, start: usize, end: usize, overlap: usize) -> Option<usize> {
+	if overlap == 0 {
+		return None;
+	}
+	let (offset, _) = text[start..end].char_indices().rev().nth(overlap - 1)?;
+	let mut next = start + offset;
+	// within a word: move on to its end
+	if text[..next].ends_with(|c: char| !c.is_whitespace()) {
+		next += text[next..end].find(char::is_whitespace)?;
+	}
+	(next > start).then_some(next)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn texts<'a>(text: &'a str, chunks: &[Chunk]) -> Vec<&'a str> {
+		chunks.iter().map(|chunk| chunk.text(text)).collect()
+	}
+
+	#[test]
+	fn packs_sentences_with_overlap() {
+		let text = "Wells were drilled. Pumps were fitted. The field produced oil.";
+		let units = sentence_units(text, 0, text.len());
+		assert_eq!(units.len(), 3);
+		assert_eq!(
+			texts(text, &pack(text, &units, 45, 0)),
+			vec!["Wells were drilled. Pumps were fitted.", "The field produced oil."]
+		);
+		assert_eq!(
+			texts(text, &pack(text, &units, 45, 20)),
+			vec![
+				"Wells were drilled. Pumps were fitted.",
+				"Pumps were fitted. The field produced oil."
+			]
+		);
+	}
+
+	#[test]
+	fn cuts_long_units_between_words() {
+		let text = "a long sentence without any stop that goes on";
+		let chunks = windows(text, 0, text.len(), 16, 0);
+		assert_eq!(
+			texts(text, &chunks),
+			vec!["a long sentence", "without any stop", "that goes on"]
+		);
+		let chunks = windows(text, 0, text.len(), 16, 9);
+		assert_eq!(texts(text, &chunks)[1], "sentence without");
+		assert!(chunks.iter().all(|chunk| chunk.text(text).chars().count() <= 16));
+	}
+
+	#[test]
+	fn creates_the_configured_chunker() {
+		let text = "# Wells\nLine one.\n# Pumps\nLine two.";
+		assert_eq!(ChunkingConfig::default().strategy(), ChunkingStrategy::Fixed);
+		let config =
+			ChunkingConfig { strategy: ChunkingStrategy::Paragraph as i32, ..Default::default() };
+		assert_eq!(create_chunker(&config, None).chunk(text, 100).len(), 2);
+		let config =
+			ChunkingConfig { strategy: ChunkingStrategy::Semantic as i32, ..Default::default() };
+		assert_eq!(create_chunker(&config, None).chunk(text, 100).len(), 1);
+	}
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use super::{pack, sentence_units, Chunk, Chunker, Unit};
+
+/// Packs paragraphs into chunks, keeping list items and table rows whole and starting a new
+/// chunk at every Markdown heading. Paragraphs longer than a chunk are packed by sentences.
+///
+/// Ingestors that know the structure of a document (Markdown, DOCX, HTML, ...) send one token
+/// message per section, so chunks never span two sections either.
+#[derive(Debug, Default)]
+pub struct ParagraphChunker {
+	overlap: usize,
+}
+
+impl ParagraphChunker {
+	/// Repeats up to `overlap` characters of whole paragraphs from one chunk to the next.
+	pub fn new(overlap: usize) -> Self {
+		Self { overlap }
+	}
+}
+
+impl Chunker for ParagraphChunker {
+	fn chunk(&self, text: &str, max_len: usize) -> Vec<Chunk> {
+		let units: Vec<Unit> = paragraph_units(text)
+			.into_iter()
+			.flat_map(|unit| {
+				if text[unit.start..unit.end].chars().count() <= max_len {
+					return vec![unit];
+				}
+				let mut sentences = sentence_units(text, unit.start, unit.end);
+				if let Some(first) = sentences.first_mut() {
+					first.breaks = unit.breaks;
+				}
+				sentences
+			})
+			.collect();
+		pack(text, &units, max_len, self.overlap)
+	}
+}
+
+/// How a line takes part in the paragraphs of a text.
+#[derive(PartialEq)]
+enum Line {
+	Blank,
+	Heading,
+	/// A list item or a table row, a unit of its own.
+	Item,
+	Text,
+}
+
+fn classify(line: &str) -> Line {
+	let line = line.trim();
+	if line.is_empty() {
+		return Line::Blank;
+	}
+	if line.starts_with('#') {
+		return Line::Heading;
+	}
+	let numbered = line.split_once(['.', ')']).is_some_and(|(number, rest)| {
+		!number.is_empty() && number.chars().all(|c| c.is_ascii_digit()) && rest.starts_with(' ')
+	});
+	let bullet = ["- ", "* ", "+ ", "• "].iter().any(|bullet| line.starts_with(bullet));
+	if numbered || bullet || line.starts_with('|') || line.contains('\t') {
+		return Line::Item;
+	}
+	Line::Text
+}
+
+/// Paragraphs, headings, list items and table rows of `text`, by their lines.
+fn paragraph_units(text: &str) -> Vec<Unit> {
+	let mut units = Vec::new();
+	let mut paragraph: Option<Unit> = None;
+	let mut offset = 0;
+	for line in text.split_inclusive('\n') {
+		let start = offset + line.len() - line.trim_start().len();
+		let end = offset + line.trim_end().len();
+		offset += line.len();
+		let kind = classify(line);
+		if kind == Line::Text {
+			match paragraph.as_mut() {
+				Some(paragraph) => paragraph.end = end,
+				None => paragraph = Some(Unit { start, end, breaks: false }),
+			}
+			continue;
+		}
+		units.extend(paragraph.take());
+		if kind != Line::Blank {
+			units.push(Unit { start, end, breaks: kind == Line::Heading });
+		}
+	}
+	units.extend(paragraph);
+	units
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const REPORT: &str = "# Drilling\nThe rig reached the site.\nDrilling started on Monday.\n\n\
+		Steps:\n- casing\n- cementing\n\n# Production\n| well | rate |\n| A1 | 300 |\n";
+
+	#[test]
+	fn splits_paragraphs_items_and_rows() {
+		let texts: Vec<&str> = paragraph_units(REPORT)
+			.iter()
+			.map(|unit| &REPORT[unit.start..unit.end])
+			.collect();
+		assert_eq!(
+			texts,
+			vec![
+				"# Drilling",
+				"The rig reached the site.\nDrilling started on Monday.",
+				"Steps:",
+				"- casing",
+				"- cementing",
+				"# Production",
+				"| well | rate |",
+				"| A1 | 300 |",
+			]
+		);
+	}
+
+	#[test]
+	fn starts_chunks_at_headings() {
+		let chunks = ParagraphChunker::new(0).chunk(REPORT, 200);
+		let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text(REPORT)).collect();
+		assert_eq!(texts.len(), 2);
+		assert!(texts[0].starts_with("# Drilling") && texts[0].ends_with("- cementing"));
+		assert_eq!(texts[1], "# Production\n| well | rate |\n| A1 | 300 |");
+		// rows are never cut, even in small chunks
+		let chunks = ParagraphChunker::new(0).chunk(REPORT, 30);
+		let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text(REPORT)).collect();
+		assert!(texts.contains(&"# Production\n| well | rate |"));
+		assert!(texts.contains(&"| A1 | 300 |"));
+	}
+}
@@ -0,0 +1,90 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use super::{pack, sentence_units, Chunk, Chunker};
+use crate::agn::predicate_normalizer::cosine_similarity;
+use fastembed::TextEmbedding;
+use std::sync::Arc;
+use tracing::warn;
+
+/// Packs sentences into chunks like [`super::SentenceChunker`], and also starts a new chunk
+/// where the topic changes: where consecutive sentences are further apart, by the distance of
+/// their embeddings, than most consecutive sentences of the text.
+pub struct SemanticChunker {
+	embedder: Arc<TextEmbedding>,
+	breakpoint_percentile: f32,
+	overlap: usize,
+}
+
+impl SemanticChunker {
+	/// Breaks where the distance between two sentences is above `breakpoint_percentile` (0-100)
+	/// of the distances between consecutive sentences.
+	pub fn new(embedder: Arc<TextEmbedding>, breakpoint_percentile: f32, overlap: usize) -> Self {
+		Self { embedder, breakpoint_percentile: breakpoint_percentile.clamp(0.0, 100.0), overlap }
+	}
+}
+
+impl Chunker for SemanticChunker {
+	fn chunk(&self, text: &str, max_len: usize) -> Vec<Chunk> {
+		let mut units = sentence_units(text, 0, text.len());
+		if units.len() > 2 {
+			let sentences =
+				units.iter().map(|unit| text[unit.start..unit.end].to_string()).collect();
+			match self.embedder.embed(sentences, None) {
+				Ok(embeddings) => {
+					let breaks = breakpoints(&embeddings, self.breakpoint_percentile);
+					for (unit, breaks) in units.iter_mut().skip(1).zip(breaks) {
+						unit.breaks = breaks;
+					}
+				},
+				Err(e) => warn!("Failed to embed sentences, chunking by sentences only: {:?}", e),
+			}
+		}
+		pack(text, &units, max_len, self.overlap)
+	}
+}
+
+/// Whether a chunk starts at each sentence after the first: where the cosine distance to the
+/// previous sentence is above the percentile of the distances between consecutive sentences.
+fn breakpoints(embeddings: &[Vec<f32>], percentile: f32) -> Vec<bool> {
+	let distances: Vec<f32> = embeddings
+		.windows(2)
+		.map(|pair| 1.0 - cosine_similarity(&pair[0], &pair[1]))
+		.collect();
+	if distances.is_empty() {
+		return Vec::new();
+	}
+	let mut sorted = distances.clone();
+	sorted.sort_by(f32::total_cmp);
+	let rank = (percentile / 100.0 * (sorted.len() - 1) as f32).round() as usize;
+	let threshold = sorted[rank.min(sorted.len() - 1)];
+	distances.into_iter().map(|distance| distance > threshold).collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn breaks_where_the_topic_changes() {
+		// two sentences about wells, then two about finance
+		let embeddings = vec![vec![1.0, 0.1], vec![0.9, 0.2], vec![0.1, 1.0], vec![0.2, 0.9]];
+		assert_eq!(breakpoints(&embeddings, 50.0), vec![false, true, false]);
+		assert_eq!(breakpoints(&embeddings, 100.0), vec![false, false, false]);
+		assert!(breakpoints(&embeddings[..1], 90.0).is_empty());
+	}
+}
@@ -0,0 +1,53 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use super::{pack, sentence_units, Chunk, Chunker};
+
+/// Packs whole sentences into chunks, cutting only sentences longer than a chunk.
+#[derive(Debug, Default)]
+pub struct SentenceChunker {
+	overlap: usize,
+}
+
+impl SentenceChunker {
+	/// Repeats up to `overlap` characters of whole sentences from one chunk to the next.
+	pub fn new(overlap: usize) -> Self {
+		Self { overlap }
+	}
+}
+
+impl Chunker for SentenceChunker {
+	fn chunk(&self, text: &str, max_len: usize) -> Vec<Chunk> {
+		pack(text, &sentence_units(text, 0, text.len()), max_len, self.overlap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn never_cuts_sentences_that_fit() {
+		let text = "The rig moved north. It reached the second site on Monday. Drilling started.";
+		let chunks = SentenceChunker::new(0).chunk(text, 60);
+		let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text(text)).collect();
+		assert_eq!(
+			texts,
+			vec!["The rig moved north. It reached the second site on Monday.", "Drilling started."]
+		);
+	}
+}
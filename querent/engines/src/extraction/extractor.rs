@@ -0,0 +1,390 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	ops::Range,
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc,
+	},
+};
+
+use llms::{GenerateResult, Message, LLM};
+use proto::semantics::ExtractionConfig;
+use tracing::warn;
+
+use crate::{
+	extraction::{parse_response, validate_triples, ExtractedTriple, RESPONSE_SCHEMA},
+	metrics::ENGINE_METRICS,
+	EngineResult,
+};
+
+const DEFAULT_BATCH_SIZE: usize = 4;
+const DEFAULT_MAX_PROMPT_TOKENS: usize = 2000;
+const DEFAULT_MAX_REPAIRS: u32 = 1;
+/// Characters of text per token, to size prompts without the tokenizer of the model.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Prompts a generative model for the triples of batches of chunks, within a token budget.
+pub struct TripleExtractor {
+	llm: Arc<dyn LLM>,
+	/// Entities triples are restricted to, when fixed by the pipeline.
+	entities: Vec<String>,
+	/// Types of the fixed entities, by position.
+	entity_types: Vec<String>,
+	/// Relation types predicates are chosen from, when the pipeline has a predicate schema.
+	relation_types: Vec<String>,
+	batch_size: usize,
+	max_prompt_tokens: usize,
+	token_budget: Option<u64>,
+	max_repairs: u32,
+	tokens_spent: AtomicU64,
+}
+
+impl TripleExtractor {
+	pub fn new(
+		llm: Arc<dyn LLM>,
+		config: &ExtractionConfig,
+		entities: Vec<String>,
+		entity_types: Vec<String>,
+		relation_types: Vec<String>,
+	) -> Self {
+		Self {
+			llm,
+			entities,
+			entity_types,
+			relation_types,
+			batch_size: config.batch_size.map_or(DEFAULT_BATCH_SIZE, |size| size.max(1) as usize),
+			max_prompt_tokens: config
+				.max_prompt_tokens
+				.map_or(DEFAULT_MAX_PROMPT_TOKENS, |tokens| tokens.max(1) as usize),
+			token_budget: config.token_budget,
+			max_repairs: config.max_repairs.unwrap_or(DEFAULT_MAX_REPAIRS),
+			tokens_spent: AtomicU64::new(0),
+		}
+	}
+
+	pub fn model_name(&self) -> String {
+		self.llm.model_name()
+	}
+
+	/// Maximum length, in characters, of the chunks of a batch.
+	pub fn max_chunk_len(&self) -> usize {
+		(self.max_prompt_tokens * CHARS_PER_TOKEN / self.batch_size).max(1)
+	}
+
+	/// Tokens spent on prompts and completions so far.
+	pub fn tokens_spent(&self) -> u64 {
+		self.tokens_spent.load(Ordering::Relaxed)
+	}
+
+	/// Groups consecutive chunks into batches of at most `batch_size` chunks and
+	/// `max_prompt_tokens` estimated tokens. A chunk over the token limit is a batch of its own.
+	pub fn batches(&self, chunks: &[String]) -> Vec<Range<usize>> {
+		let mut batches = Vec::new();
+		let mut start = 0;
+		let mut tokens = 0;
+		for (i, chunk) in chunks.iter().enumerate() {
+			let chunk_tokens = estimate_tokens(chunk);
+			if i > start &&
+				(i - start == self.batch_size || tokens + chunk_tokens > self.max_prompt_tokens)
+			{
+				batches.push(start..i);
+				start = i;
+				tokens = 0;
+			}
+			tokens += chunk_tokens;
+		}
+		if start < chunks.len() {
+			batches.push(start..chunks.len());
+		}
+		batches
+	}
+
+	/// Extracts the triples of a batch of chunks. Answers that are not valid JSON are sent back
+	/// to the model with the error, up to `max_repairs` times. Returns `None` when the token
+	/// budget does not allow the prompt.
+	pub async fn extract(&self, chunks: &[String]) -> EngineResult<Option<Vec<ExtractedTriple>>> {
+		let mut messages = vec![
+			Message::new_system_message(format!(
+				"You extract knowledge graph triples from text. Answer with JSON only, valid \
+				 against this JSON schema:\n{}",
+				RESPONSE_SCHEMA
+			)),
+			Message::new_human_message(self.prompt(chunks)),
+		];
+		let mut repairs = 0;
+		loop {
+			let prompt_tokens: usize =
+				messages.iter().map(|message| estimate_tokens(&message.content)).sum();
+			let spent = self.tokens_spent() + prompt_tokens as u64;
+			if self.token_budget.is_some_and(|budget| spent > budget) {
+				return Ok(None);
+			}
+			let result = self.llm.generate(&messages).await?;
+			self.spend(prompt_tokens, &result);
+			match parse_response(&result.generation) {
+				Ok(triples) => return Ok(Some(self.filter(validate_triples(triples, chunks)))),
+				Err(e) if repairs < self.max_repairs => {
+					repairs += 1;
+					messages.push(Message::new_ai_message(result.generation));
+					messages.push(Message::new_human_message(format!(
+						"Your answer could not be read: {}. Answer again with the JSON only.",
+						e
+					)));
+				},
+				Err(e) => {
+					warn!("Dropping the answer of {} for invalid JSON: {}", self.model_name(), e);
+					return Ok(Some(Vec::new()));
+				},
+			}
+		}
+	}
+
+	/// The instructions and numbered chunks of a batch.
+	fn prompt(&self, chunks: &[String]) -> String {
+		let mut prompt = String::from(
+			"Extract the (subject, predicate, object) triples stated in the chunks below. \
+			 Subjects and objects are entities copied as written in the chunk, with a short type \
+			 such as person, organization or location. Predicates are short verb phrases. Give \
+			 a confidence between 0 and 1 and the number of the chunk of every triple.\n",
+		);
+		if !self.entities.is_empty() {
+			let entities: Vec<String> = self
+				.entities
+				.iter()
+				.enumerate()
+				.map(|(i, entity)| match self.entity_types.get(i) {
+					Some(entity_type) => format!("{} ({})", entity, entity_type),
+					None => entity.clone(),
+				})
+				.collect();
+			prompt.push_str(&format!(
+				"Only extract triples between these entities: {}.\n",
+				entities.join(", ")
+			));
+		}
+		if !self.relation_types.is_empty() {
+			prompt.push_str(&format!(
+				"Choose predicates among these relation types: {}.\n",
+				self.relation_types.join(", ")
+			));
+		}
+		for (i, chunk) in chunks.iter().enumerate() {
+			prompt.push_str(&format!("\nChunk {}:\n{}\n", i, chunk));
+		}
+		prompt
+	}
+
+	/// Keeps the triples between fixed entities, typed as the pipeline types them.
+	fn filter(&self, triples: Vec<ExtractedTriple>) -> Vec<ExtractedTriple> {
+		if self.entities.is_empty() {
+			return triples;
+		}
+		let entity_index =
+			|name: &str| self.entities.iter().position(|entity| entity.eq_ignore_ascii_case(name));
+		triples
+			.into_iter()
+			.filter_map(|mut triple| {
+				let subject = entity_index(&triple.subject)?;
+				let object = entity_index(&triple.object)?;
+				if let Some(subject_type) = self.entity_types.get(subject) {
+					triple.subject_type = subject_type.clone();
+				}
+				if let Some(object_type) = self.entity_types.get(object) {
+					triple.object_type = object_type.clone();
+				}
+				Some(triple)
+			})
+			.collect()
+	}
+
+	/// Counts the tokens of a call, as reported by the backend or else estimated.
+	fn spend(&self, prompt_tokens: usize, result: &GenerateResult) {
+		let tokens = match &result.tokens {
+			Some(usage) => usage.total_tokens as u64,
+			None => (prompt_tokens + estimate_tokens(&result.generation)) as u64,
+		};
+		self.tokens_spent.fetch_add(tokens, Ordering::Relaxed);
+		ENGINE_METRICS
+			.tokens_processed_total
+			.with_label_values([&self.model_name()])
+			.inc_by(tokens);
+	}
+}
+
+/// Estimated number of tokens of a text.
+fn estimate_tokens(text: &str) -> usize {
+	text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use async_trait::async_trait;
+	use candle_core::Tensor;
+	use llms::{LLMResult, TokenUsage};
+	use std::{collections::HashMap, sync::Mutex};
+
+	/// Answers generation requests with canned answers, recording the conversations.
+	#[derive(Default)]
+	struct ScriptedLLM {
+		answers: Mutex<Vec<String>>,
+		conversations: Mutex<Vec<Vec<Message>>>,
+	}
+
+	impl ScriptedLLM {
+		fn new(answers: &[&str]) -> Self {
+			Self {
+				answers: Mutex::new(answers.iter().rev().map(|a| a.to_string()).collect()),
+				conversations: Mutex::default(),
+			}
+		}
+	}
+
+	#[async_trait]
+	impl LLM for ScriptedLLM {
+		async fn init_token_idx_2_word_doc_idx(&self) -> Vec<(String, i32)> {
+			Vec::new()
+		}
+		async fn num_start_tokens(&self) -> usize {
+			0
+		}
+		async fn append_last_token(&self, _listing: &mut Vec<(String, i32)>) {}
+		async fn model_input(&self, _tokens: Vec<i32>) -> LLMResult<HashMap<String, Tensor>> {
+			Ok(HashMap::new())
+		}
+		async fn tokenize(&self, _word: &str) -> LLMResult<Vec<i32>> {
+			Ok(Vec::new())
+		}
+		async fn inference_attention(
+			&self,
+			_model_input: HashMap<String, Tensor>,
+		) -> LLMResult<Tensor> {
+			unimplemented!()
+		}
+		async fn maximum_tokens(&self) -> usize {
+			0
+		}
+		async fn tokens_to_words(&self, _tokens: &[i32]) -> Vec<String> {
+			Vec::new()
+		}
+		async fn attention_tensor_to_2d_vector(
+			&self,
+			_attention_weights: &Tensor,
+		) -> LLMResult<Vec<Vec<f32>>> {
+			Ok(Vec::new())
+		}
+		async fn token_classification(
+			&self,
+			_model_input: HashMap<String, Tensor>,
+			_labels: Option<&Tensor>,
+		) -> LLMResult<Vec<(String, String)>> {
+			Ok(Vec::new())
+		}
+		async fn generate(&self, messages: &[Message]) -> LLMResult<GenerateResult> {
+			self.conversations.lock().unwrap().push(messages.to_vec());
+			Ok(GenerateResult {
+				tokens: Some(TokenUsage {
+					prompt_tokens: 900,
+					completion_tokens: 100,
+					total_tokens: 1000,
+				}),
+				generation: self.answers.lock().unwrap().pop().unwrap_or_default(),
+			})
+		}
+	}
+
+	fn config() -> ExtractionConfig {
+		ExtractionConfig { batch_size: Some(2), max_prompt_tokens: Some(10), ..Default::default() }
+	}
+
+	fn chunks() -> Vec<String> {
+		vec![
+			"The Ghawar field is operated by Saudi Aramco.".to_string(),
+			"Ghawar lies in the Eastern Province.".to_string(),
+		]
+	}
+
+	#[test]
+	fn batches_chunks_by_count_and_tokens() {
+		let extractor = TripleExtractor::new(
+			Arc::new(ScriptedLLM::default()),
+			&config(),
+			vec![],
+			vec![],
+			vec![],
+		);
+		let chunks: Vec<String> = [
+			"a".repeat(12),
+			"b".repeat(12),
+			"c".repeat(40),
+			"d".repeat(4),
+			"e".repeat(4),
+			"f".repeat(4),
+		]
+		.into_iter()
+		.collect();
+		assert_eq!(extractor.batches(&chunks), vec![0..2, 2..3, 3..5, 5..6]);
+		assert_eq!(extractor.max_chunk_len(), 20);
+	}
+
+	#[tokio::test]
+	async fn repairs_invalid_answers() {
+		let llm = Arc::new(ScriptedLLM::new(&[
+			"Sure! Here are the triples.",
+			r#"{"triples": [{"chunk": 1, "subject": "Ghawar", "predicate": "lies in",
+				"object": "Eastern Province", "object_type": "location"}]}"#,
+		]));
+		let extractor = TripleExtractor::new(llm.clone(), &config(), vec![], vec![], vec![]);
+		let triples = extractor.extract(&chunks()).await.unwrap().unwrap();
+		assert_eq!(triples.len(), 1);
+		assert_eq!(triples[0].object_type, "location");
+		assert_eq!(extractor.tokens_spent(), 2000);
+		let conversations = llm.conversations.lock().unwrap();
+		assert_eq!(conversations[1].len(), 4);
+		assert!(conversations[1][3].content.contains("could not be read"));
+	}
+
+	#[tokio::test]
+	async fn keeps_fixed_entities_within_the_budget() {
+		let answer = r#"{"triples": [
+			{"chunk": 0, "subject": "Ghawar field", "predicate": "operated by", "object": "Saudi Aramco"},
+			{"chunk": 1, "subject": "Ghawar", "predicate": "lies in", "object": "Eastern Province"}
+		]}"#;
+		let llm = Arc::new(ScriptedLLM::new(&[answer, answer]));
+		let config = ExtractionConfig { token_budget: Some(1000), ..config() };
+		let extractor = TripleExtractor::new(
+			llm.clone(),
+			&config,
+			vec!["ghawar field".to_string(), "saudi aramco".to_string()],
+			vec!["field".to_string(), "company".to_string()],
+			vec!["operated_by".to_string()],
+		);
+		let triples = extractor.extract(&chunks()).await.unwrap().unwrap();
+		assert_eq!(triples.len(), 1);
+		assert_eq!(
+			(triples[0].subject_type.as_str(), triples[0].object_type.as_str()),
+			("field", "company")
+		);
+		let prompt = &llm.conversations.lock().unwrap()[0][1].content;
+		assert!(prompt.contains("ghawar field (field), saudi aramco (company)"));
+		assert!(prompt.contains("relation types: operated_by"));
+		assert!(extractor.extract(&chunks()).await.unwrap().is_none());
+	}
+}
@@ -0,0 +1,244 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::{EventState, EventType, KnowledgeOrigin, SemanticKnowledgePayload, VectorPayload};
+use fastembed::TextEmbedding;
+use futures::Stream;
+use llms::LLM;
+use proto::semantics::{ExtractionConfig, IngestedTokens};
+use tokio::sync::mpsc::Receiver;
+use tracing::warn;
+
+use crate::{
+	agn::PredicateNormalizer,
+	chunking::{Chunk, Chunker},
+	extraction::{ExtractedTriple, TripleExtractor},
+	metrics::ENGINE_METRICS,
+	utils::{calculate_triple_embeddings, clean_chunk, generate_custom_comb_uuid},
+	Engine, EngineError, EngineErrorKind, EngineResult,
+};
+
+const DEFAULT_LABEL: &str = "unlabelled";
+
+/// Engine extracting triples by prompting a generative model, for corpora where relations
+/// found in attention tensors are not precise enough.
+pub struct LlmExtractionEngine {
+	extractor: TripleExtractor,
+	embedding_model: Arc<TextEmbedding>,
+	/// Optional normalizer mapping predicates to a schema.
+	predicate_normalizer: Option<Arc<PredicateNormalizer>>,
+	/// Cuts the ingested text into the chunks sent to the model.
+	chunker: Arc<dyn Chunker>,
+}
+
+impl LlmExtractionEngine {
+	/// Creates an engine prompting `llm`. Fixed entities restrict the triples to them, and the
+	/// relation types of the predicate schema are suggested to the model.
+	pub fn new(
+		llm: Arc<dyn LLM>,
+		config: &ExtractionConfig,
+		entities: Vec<String>,
+		sample_entities: Vec<String>,
+		embedding_model: Arc<TextEmbedding>,
+		predicate_normalizer: Option<Arc<PredicateNormalizer>>,
+		chunker: Arc<dyn Chunker>,
+	) -> Self {
+		let relation_types = predicate_normalizer
+			.as_ref()
+			.map(|normalizer| normalizer.relation_types().to_vec())
+			.unwrap_or_default();
+		Self {
+			extractor: TripleExtractor::new(llm, config, entities, sample_entities, relation_types),
+			embedding_model,
+			predicate_normalizer,
+			chunker,
+		}
+	}
+
+	/// Rewrites predicates to the relation types of the schema, dropping unmatched triples
+	/// when the schema is configured to.
+	fn normalize(&self, triples: &mut Vec<ExtractedTriple>) -> EngineResult<()> {
+		let Some(normalizer) = &self.predicate_normalizer else {
+			return Ok(());
+		};
+		let mut predicates: Vec<String> =
+			triples.iter().map(|triple| triple.predicate.clone()).collect();
+		predicates.sort();
+		predicates.dedup();
+		if predicates.is_empty() {
+			return Ok(());
+		}
+		let embeddings = self.embedding_model.embed(predicates.clone(), None).map_err(|e| {
+			EngineError::new(EngineErrorKind::ModelError, Arc::new(anyhow::anyhow!(e)))
+		})?;
+		let mapping: HashMap<String, Option<String>> = predicates
+			.into_iter()
+			.zip(embeddings)
+			.map(|(predicate, embedding)| {
+				let relation_type =
+					normalizer.closest_relation_type(&predicate, &embedding).map(str::to_string);
+				(predicate, relation_type)
+			})
+			.collect();
+		triples.retain_mut(|triple| match mapping.get(&triple.predicate) {
+			Some(Some(relation_type)) => {
+				triple.predicate = relation_type.clone();
+				true
+			},
+			_ => false,
+		});
+		Ok(())
+	}
+
+	/// Graph events for the triples of a batch, followed by their vector events.
+	fn batch_events(
+		&self,
+		token: &IngestedTokens,
+		chunks: &[String],
+		spans: &[Chunk],
+		mut triples: Vec<ExtractedTriple>,
+	) -> EngineResult<Vec<EventState>> {
+		self.normalize(&mut triples)?;
+		let embedding_inputs: Vec<(String, String, String, String)> = triples
+			.iter()
+			.map(|triple| {
+				(
+					chunks[triple.chunk].clone(),
+					triple.subject.clone(),
+					triple.object.clone(),
+					triple.predicate.clone(),
+				)
+			})
+			.collect();
+		let embeddings = calculate_triple_embeddings(&self.embedding_model, &embedding_inputs)?;
+
+		let event = |event_type: EventType, payload: String| EventState {
+			event_type,
+			file: token.file.clone(),
+			doc_source: token.doc_source.clone(),
+			image_id: token.image_id.clone(),
+			timestamp: 0.0,
+			payload,
+		};
+		let label =
+			|label: String| if label.is_empty() { DEFAULT_LABEL.to_string() } else { label };
+		let mut graph_events = Vec::with_capacity(triples.len());
+		let mut vector_events = Vec::with_capacity(triples.len());
+		for (triple, embeddings) in triples.into_iter().zip(embeddings) {
+			let event_id = generate_custom_comb_uuid();
+			let span = spans[triple.chunk];
+			let payload = SemanticKnowledgePayload {
+				subject: triple.subject,
+				subject_type: label(triple.subject_type),
+				predicate: triple.predicate,
+				predicate_type: "relation".to_string(),
+				object: triple.object,
+				object_type: label(triple.object_type),
+				sentence: chunks[triple.chunk].clone(),
+				image_id: token.image_id.clone(),
+				blob: None,
+				event_id: event_id.clone(),
+				source_id: token.source_id.clone(),
+				origin: KnowledgeOrigin::Extracted,
+				subject_id: None,
+				object_id: None,
+				section_path: token.section_path.clone(),
+				chunk_start: Some(span.start as u32),
+				chunk_end: Some(span.end as u32),
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: token.duplicate_of.clone(),
+			};
+			graph_events.push(event(EventType::Graph, serde_json::to_string(&payload)?));
+			let score = triple.confidence.unwrap_or(1.0);
+			let payload = VectorPayload { event_id, embeddings, score };
+			vector_events.push(event(EventType::Vector, serde_json::to_string(&payload)?));
+		}
+		graph_events.extend(vector_events);
+		Ok(graph_events)
+	}
+}
+
+#[async_trait]
+impl Engine for LlmExtractionEngine {
+	async fn process_ingested_tokens<'life0>(
+		&'life0 self,
+		token_stream: Receiver<IngestedTokens>,
+	) -> EngineResult<Pin<Box<dyn Stream<Item = EngineResult<EventState>> + Send + 'life0>>> {
+		let stream = stream! {
+			let model_name = self.extractor.model_name();
+			let max_chunk_len = self.extractor.max_chunk_len();
+			let mut budget_spent = false;
+			let mut token_stream = token_stream;
+			while let Some(token) = token_stream.recv().await {
+				// tokens keep being received once the budget is spent, for ingestion to finish
+				if token.data.is_empty() || budget_spent {
+					continue;
+				}
+				let mut chunks = Vec::new();
+				let mut spans = Vec::new();
+				for data in &token.data {
+					for chunk in self.chunker.chunk(data, max_chunk_len) {
+						let text = clean_chunk(chunk.text(data));
+						if !text.is_empty() {
+							chunks.push(text);
+							spans.push(chunk);
+						}
+					}
+				}
+				for batch in self.extractor.batches(&chunks) {
+					let batch_chunks = &chunks[batch.clone()];
+					let inference_timer = ENGINE_METRICS
+						.inference_duration_seconds
+						.with_label_values([&model_name])
+						.start_timer();
+					let triples = match self.extractor.extract(batch_chunks).await {
+						Ok(Some(triples)) => triples,
+						Ok(None) => {
+							warn!(
+								"Token budget of {} spent after {} tokens, extraction stops at {}",
+								model_name,
+								self.extractor.tokens_spent(),
+								token.file
+							);
+							budget_spent = true;
+							break;
+						},
+						Err(e) => {
+							yield Err(e);
+							continue;
+						},
+					};
+					inference_timer.observe_duration();
+					match self.batch_events(&token, batch_chunks, &spans[batch], triples) {
+						Ok(events) =>
+							for event in events {
+								yield Ok(event);
+							},
+						Err(e) => yield Err(e),
+					}
+				}
+			}
+		};
+		Ok(Box::pin(stream))
+	}
+}
@@ -0,0 +1,224 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use serde::Deserialize;
+use serde_json::Value;
+
+/// JSON schema of the answers asked from the model.
+pub const RESPONSE_SCHEMA: &str = r#"{
+  "type": "object",
+  "properties": {
+    "triples": {
+      "type": "array",
+      "items": {
+        "type": "object",
+        "properties": {
+          "chunk": { "type": "integer", "description": "Number of the chunk the triple is stated in" },
+          "subject": { "type": "string" },
+          "subject_type": { "type": "string" },
+          "predicate": { "type": "string" },
+          "object": { "type": "string" },
+          "object_type": { "type": "string" },
+          "confidence": { "type": "number", "minimum": 0, "maximum": 1 }
+        },
+        "required": ["chunk", "subject", "predicate", "object"]
+      }
+    }
+  },
+  "required": ["triples"]
+}"#;
+
+/// A triple answered by the model.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ExtractedTriple {
+	/// Index, in the batch, of the chunk the triple was read from.
+	#[serde(default)]
+	pub chunk: usize,
+	pub subject: String,
+	#[serde(default)]
+	pub subject_type: String,
+	pub predicate: String,
+	pub object: String,
+	#[serde(default)]
+	pub object_type: String,
+	#[serde(default)]
+	pub confidence: Option<f32>,
+}
+
+/// Parses the answer of the model, repairing the usual defects of generated JSON: code fences
+/// and text around the JSON, trailing commas, and a bare array of triples. Malformed triples
+/// are skipped; an error means the answer holds no JSON at all.
+pub fn parse_response(answer: &str) -> Result<Vec<ExtractedTriple>, serde_json::Error> {
+	let json = remove_trailing_commas(json_span(answer));
+	let items = match serde_json::from_str(&json)? {
+		Value::Array(items) => items,
+		Value::Object(mut object) => match object.remove("triples") {
+			Some(Value::Array(items)) => items,
+			Some(Value::Null) | None => Vec::new(),
+			Some(_) => return Err(serde::de::Error::custom("`triples` is not an array")),
+		},
+		_ => return Err(serde::de::Error::custom("expected an object with `triples`")),
+	};
+	Ok(items.into_iter().filter_map(|item| serde_json::from_value(item).ok()).collect())
+}
+
+/// Keeps the triples whose subject, predicate and object are filled, and whose subject and
+/// object are written in the chunk they were read from, so that entities the model made up
+/// never reach the graph. Fields are trimmed and repeated triples dropped.
+pub fn validate_triples(triples: Vec<ExtractedTriple>, chunks: &[String]) -> Vec<ExtractedTriple> {
+	let lowercase_chunks: Vec<String> = chunks.iter().map(|chunk| chunk.to_lowercase()).collect();
+	let mut valid: Vec<ExtractedTriple> = Vec::new();
+	for mut triple in triples {
+		let Some(chunk) = lowercase_chunks.get(triple.chunk) else {
+			continue;
+		};
+		triple.subject = triple.subject.trim().to_string();
+		triple.predicate = triple.predicate.trim().to_lowercase();
+		triple.object = triple.object.trim().to_string();
+		triple.subject_type = triple.subject_type.trim().to_string();
+		triple.object_type = triple.object_type.trim().to_string();
+		triple.confidence = triple.confidence.map(|confidence| confidence.clamp(0.0, 1.0));
+		let subject = triple.subject.to_lowercase();
+		let object = triple.object.to_lowercase();
+		if subject.is_empty() ||
+			object.is_empty() ||
+			triple.predicate.is_empty() ||
+			subject == object ||
+			!chunk.contains(&subject) ||
+			!chunk.contains(&object)
+		{
+			continue;
+		}
+		let repeated = valid.iter().any(|other| {
+			other.chunk == triple.chunk &&
+				other.subject.to_lowercase() == subject &&
+				other.predicate == triple.predicate &&
+				other.object.to_lowercase() == object
+		});
+		if !repeated {
+			valid.push(triple);
+		}
+	}
+	valid
+}
+
+/// The JSON in an answer: from its first bracket to the last closing one.
+fn json_span(answer: &str) -> &str {
+	let start = answer.find(['{', '[']);
+	let end = answer.rfind(['}', ']']);
+	match (start, end) {
+		(Some(start), Some(end)) if start < end => &answer[start..=end],
+		_ => answer.trim(),
+	}
+}
+
+/// Removes the commas before closing brackets, outside of strings.
+fn remove_trailing_commas(json: &str) -> String {
+	let mut repaired = String::with_capacity(json.len());
+	let mut in_string = false;
+	let mut escaped = false;
+	let mut pending_comma: Option<usize> = None;
+	for c in json.chars() {
+		if in_string {
+			if escaped {
+				escaped = false;
+			} else if c == '\\' {
+				escaped = true;
+			} else if c == '"' {
+				in_string = false;
+			}
+		} else {
+			match c {
+				'"' => in_string = true,
+				',' => pending_comma = Some(repaired.len()),
+				'}' | ']' =>
+					if let Some(comma) = pending_comma {
+						repaired.remove(comma);
+					},
+				c if c.is_whitespace() => {
+					repaired.push(c);
+					continue;
+				},
+				_ => {},
+			}
+			if c != ',' {
+				pending_comma = None;
+			}
+		}
+		repaired.push(c);
+	}
+	repaired
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn repairs_generated_json() {
+		let answer = "Here are the triples:\n```json\n{\"triples\": [\n  {\"chunk\": 0, \
+			\"subject\": \"Ghawar\", \"predicate\": \"is located in\", \"object\": \"Saudi \
+			Arabia\", \"confidence\": 0.9,},\n  {\"chunk\": 1, \"subject\": \"a, ]\"},\n]}\n```";
+		let triples = parse_response(answer).unwrap();
+		assert_eq!(triples.len(), 1);
+		assert_eq!(triples[0].subject, "Ghawar");
+		assert_eq!(triples[0].object, "Saudi Arabia");
+		assert_eq!(triples[0].confidence, Some(0.9));
+
+		let bare = parse_response(r#"[{"subject": "a", "predicate": "b", "object": "c"}]"#);
+		assert_eq!(bare.unwrap()[0].chunk, 0);
+		assert!(parse_response("I could not find any triples.").is_err());
+		assert!(parse_response(r#"{"triples": "none"}"#).is_err());
+		assert!(serde_json::from_str::<Value>(RESPONSE_SCHEMA).is_ok());
+	}
+
+	#[test]
+	fn keeps_triples_stated_in_their_chunk() {
+		let triple = |chunk: usize, subject: &str, predicate: &str, object: &str| ExtractedTriple {
+			chunk,
+			subject: subject.to_string(),
+			subject_type: String::new(),
+			predicate: predicate.to_string(),
+			object: object.to_string(),
+			object_type: String::new(),
+			confidence: Some(1.5),
+		};
+		let chunks = vec![
+			"The Ghawar field is operated by Saudi Aramco.".to_string(),
+			"Ghawar lies in the Eastern Province.".to_string(),
+		];
+		let triples = validate_triples(
+			vec![
+				triple(0, " ghawar field ", "Operated By", "Saudi Aramco"),
+				triple(0, "Ghawar field", "operated by", "saudi aramco"),
+				triple(1, "Ghawar", "lies in", "Eastern Province"),
+				triple(1, "Ghawar", "produces", "natural gas"),
+				triple(0, "Saudi Aramco", "", "Ghawar"),
+				triple(2, "Ghawar", "lies in", "Eastern Province"),
+			],
+			&chunks,
+		);
+		assert_eq!(triples.len(), 2);
+		assert_eq!(
+			(triples[0].subject.as_str(), triples[0].predicate.as_str()),
+			("ghawar field", "operated by")
+		);
+		assert_eq!(triples[0].confidence, Some(1.0));
+		assert_eq!(triples[1].object, "Eastern Province");
+	}
+}
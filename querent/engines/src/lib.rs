@@ -19,5 +19,9 @@
 pub mod engines;
 pub use engines::*;
 pub mod agn;
+pub mod chunking;
+pub mod extraction;
+pub mod metrics;
 pub mod mock;
+pub mod table;
 pub mod utils;
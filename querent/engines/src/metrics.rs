@@ -0,0 +1,48 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use common::metrics::{counter_vec, histogram_vec, HistogramVec, IntCounterVec};
+use once_cell::sync::Lazy;
+
+pub struct EngineMetrics {
+	pub tokens_processed_total: IntCounterVec<1>,
+	pub inference_duration_seconds: HistogramVec<1>,
+}
+
+impl Default for EngineMetrics {
+	fn default() -> Self {
+		EngineMetrics {
+			tokens_processed_total: counter_vec(
+				"tokens_processed_total",
+				"Number of model tokens processed by the engine.",
+				"engine",
+				&[],
+				["model"],
+			),
+			inference_duration_seconds: histogram_vec(
+				"inference_duration_seconds",
+				"Time taken to extract knowledge from a batch of ingested tokens.",
+				"engine",
+				&[],
+				["model"],
+			),
+		}
+	}
+}
+
+pub static ENGINE_METRICS: Lazy<EngineMetrics> = Lazy::new(EngineMetrics::default);
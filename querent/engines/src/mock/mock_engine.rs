@@ -19,7 +19,7 @@
 use crate::{Engine, EngineResult};
 use async_stream::stream;
 use async_trait::async_trait;
-use common::{EventState, EventType, SemanticKnowledgePayload};
+use common::{EventState, EventType, KnowledgeOrigin, SemanticKnowledgePayload};
 use futures::Stream;
 use proto::semantics::IngestedTokens;
 use std::pin::Pin;
@@ -49,6 +49,15 @@ impl Engine for MockEngine {
 					blob: Some("mock".to_string()),
 					event_id: "mock".to_string(),
 					source_id: "mock".to_string(),
+					origin: KnowledgeOrigin::Extracted,
+					subject_id: None,
+					object_id: None,
+					section_path: None,
+					chunk_start: None,
+					chunk_end: None,
+					subject_coreference: None,
+					object_coreference: None,
+					duplicate_of: token.duplicate_of.clone(),
 				};
 
 				// create an event
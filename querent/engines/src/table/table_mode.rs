@@ -0,0 +1,325 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{collections::HashMap, pin::Pin, sync::Arc};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::{EventState, EventType, KnowledgeOrigin, SemanticKnowledgePayload, VectorPayload};
+use fastembed::TextEmbedding;
+use futures::Stream;
+use once_cell::sync::Lazy;
+use proto::semantics::{ExtractedTable, IngestedTokens, TableModeConfig};
+use regex::Regex;
+use tokio::sync::mpsc::{self, Receiver};
+
+use crate::{
+	utils::{calculate_triple_embeddings, generate_custom_comb_uuid},
+	Engine, EngineResult,
+};
+
+static NUMBER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^[-+]?(\d{1,3}(,\d{3})+|\d*)(\.\d+)?([eE][-+]?\d+)?%?$").unwrap());
+static DATE: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"^(\d{4}-\d{1,2}-\d{1,2}([T ][\d:.]+Z?)?|\d{1,2}[/.-]\d{1,2}[/.-]\d{2,4})$")
+		.unwrap()
+});
+
+/// A triple read from a table row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableTriple {
+	pub subject: String,
+	pub subject_type: String,
+	pub predicate: String,
+	pub object: String,
+	pub object_type: String,
+	pub sentence: String,
+}
+
+/// Maps table rows to triples: the primary key cell is the subject, every other header a
+/// predicate and its cell the object.
+#[derive(Debug, Clone, Default)]
+pub struct TableMapper {
+	primary_key: Option<String>,
+	/// Value types by lowercased header.
+	column_types: HashMap<String, String>,
+}
+
+impl TableMapper {
+	pub fn new(config: &TableModeConfig) -> Self {
+		Self {
+			primary_key: config
+				.primary_key
+				.as_deref()
+				.map(|header| header.trim().to_lowercase())
+				.filter(|header| !header.is_empty()),
+			column_types: config
+				.column_types
+				.iter()
+				.filter(|column| !column.entity_type.trim().is_empty())
+				.map(|column| {
+					(column.header.trim().to_lowercase(), column.entity_type.trim().to_string())
+				})
+				.collect(),
+		}
+	}
+
+	pub fn triples(&self, table: &ExtractedTable) -> Vec<TableTriple> {
+		let headers: Vec<String> = table
+			.headers
+			.iter()
+			.enumerate()
+			.map(|(i, header)| match header.trim() {
+				"" => format!("Column {}", i + 1),
+				header => header.to_string(),
+			})
+			.collect();
+		let key = self
+			.primary_key
+			.as_ref()
+			.and_then(|primary_key| {
+				headers.iter().position(|header| header.to_lowercase() == *primary_key)
+			})
+			.unwrap_or(0);
+		let Some(key_header) = headers.get(key) else {
+			return Vec::new();
+		};
+		let subject_type = self.column_type(key_header).unwrap_or(key_header);
+
+		let mut triples = Vec::new();
+		for row in &table.rows {
+			let Some(subject) =
+				row.cells.get(key).map(|cell| cell.trim()).filter(|s| !s.is_empty())
+			else {
+				continue;
+			};
+			for (i, cell) in row.cells.iter().enumerate() {
+				let object = cell.trim();
+				if i == key || object.is_empty() {
+					continue;
+				}
+				let predicate =
+					headers.get(i).cloned().unwrap_or_else(|| format!("Column {}", i + 1));
+				let object_type = match self.column_type(&predicate) {
+					Some(column_type) => column_type.to_string(),
+					None => infer_type(object).unwrap_or(&predicate).to_string(),
+				};
+				triples.push(TableTriple {
+					sentence: format!("The {} of {} is {}.", predicate, subject, object),
+					subject: subject.to_string(),
+					subject_type: subject_type.to_string(),
+					predicate,
+					object: object.to_string(),
+					object_type,
+				});
+			}
+		}
+		triples
+	}
+
+	fn column_type(&self, header: &str) -> Option<&str> {
+		self.column_types.get(&header.to_lowercase()).map(String::as_str)
+	}
+}
+
+/// Type of values that read as numbers or dates.
+fn infer_type(value: &str) -> Option<&'static str> {
+	if value.chars().any(|c| c.is_ascii_digit()) && NUMBER.is_match(value) {
+		Some("number")
+	} else if DATE.is_match(value) {
+		Some("date")
+	} else {
+		None
+	}
+}
+
+/// Wraps an engine so that tokens carrying a table are mapped to triples directly, without
+/// going through the engine. Other tokens are passed on to the wrapped engine.
+pub struct TableModeEngine {
+	engine: Arc<dyn Engine>,
+	mapper: TableMapper,
+	embedder: Arc<TextEmbedding>,
+}
+
+impl TableModeEngine {
+	pub fn new(
+		engine: Arc<dyn Engine>,
+		config: &TableModeConfig,
+		embedder: Arc<TextEmbedding>,
+	) -> Self {
+		Self { engine, mapper: TableMapper::new(config), embedder }
+	}
+
+	/// Graph events for the triples of a table, followed by their vector events.
+	fn table_events(
+		&self,
+		token: &IngestedTokens,
+		table: &ExtractedTable,
+	) -> EngineResult<Vec<EventState>> {
+		let triples = self.mapper.triples(table);
+		let embedding_inputs: Vec<(String, String, String, String)> = triples
+			.iter()
+			.map(|triple| {
+				(
+					triple.sentence.clone(),
+					triple.subject.clone(),
+					triple.object.clone(),
+					triple.predicate.clone(),
+				)
+			})
+			.collect();
+		let embeddings = calculate_triple_embeddings(&self.embedder, &embedding_inputs)?;
+
+		let event = |event_type: EventType, payload: String| EventState {
+			event_type,
+			file: token.file.clone(),
+			doc_source: token.doc_source.clone(),
+			image_id: None,
+			timestamp: 0.0,
+			payload,
+		};
+		let mut graph_events = Vec::with_capacity(triples.len());
+		let mut vector_events = Vec::with_capacity(triples.len());
+		for (triple, embeddings) in triples.into_iter().zip(embeddings) {
+			let event_id = generate_custom_comb_uuid();
+			let payload = SemanticKnowledgePayload {
+				subject: triple.subject,
+				subject_type: triple.subject_type,
+				predicate: triple.predicate,
+				predicate_type: "attribute".to_string(),
+				object: triple.object,
+				object_type: triple.object_type,
+				sentence: triple.sentence,
+				image_id: None,
+				blob: None,
+				event_id: event_id.clone(),
+				source_id: token.source_id.clone(),
+				origin: KnowledgeOrigin::Table,
+				subject_id: None,
+				object_id: None,
+				section_path: token.section_path.clone(),
+				chunk_start: None,
+				chunk_end: None,
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: token.duplicate_of.clone(),
+			};
+			graph_events.push(event(EventType::Graph, serde_json::to_string(&payload)?));
+			let payload = VectorPayload { event_id, embeddings, score: 1.0 };
+			vector_events.push(event(EventType::Vector, serde_json::to_string(&payload)?));
+		}
+		graph_events.extend(vector_events);
+		Ok(graph_events)
+	}
+}
+
+#[async_trait]
+impl Engine for TableModeEngine {
+	async fn process_ingested_tokens<'life0>(
+		&'life0 self,
+		token_stream: Receiver<IngestedTokens>,
+	) -> EngineResult<Pin<Box<dyn Stream<Item = EngineResult<EventState>> + Send + 'life0>>> {
+		let (text_sender, text_receiver) = mpsc::channel(10);
+		let engine_events = self.engine.process_ingested_tokens(text_receiver).await?;
+		let table_events = stream! {
+			let mut token_stream = token_stream;
+			while let Some(mut token) = token_stream.recv().await {
+				let Some(table) = token.table.take() else {
+					if text_sender.send(token).await.is_err() {
+						break;
+					}
+					continue;
+				};
+				match self.table_events(&token, &table) {
+					Ok(events) =>
+						for event in events {
+							yield Ok(event);
+						},
+					Err(e) => yield Err(e),
+				}
+			}
+			// the wrapped engine finishes once the sender is dropped here
+		};
+		Ok(Box::pin(futures::stream::select(table_events, engine_events)))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proto::semantics::{ColumnType, TableRow};
+
+	fn table() -> ExtractedTable {
+		let row =
+			|cells: &[&str]| TableRow { cells: cells.iter().map(|c| c.to_string()).collect() };
+		ExtractedTable {
+			headers: vec![
+				"Depth".to_string(),
+				"Well".to_string(),
+				"Spud date".to_string(),
+				"".to_string(),
+			],
+			rows: vec![
+				row(&["3,200", "A-7", "2021-05-14", "onshore"]),
+				row(&["1200.5", " ", "2020-01-02", ""]),
+				row(&["-", "B-2", "", ""]),
+			],
+		}
+	}
+
+	#[test]
+	fn maps_rows_to_triples() {
+		let config = TableModeConfig {
+			primary_key: Some("well".to_string()),
+			column_types: vec![ColumnType {
+				header: "WELL".to_string(),
+				entity_type: "Well".to_string(),
+			}],
+		};
+		let triples = TableMapper::new(&config).triples(&table());
+		assert_eq!(triples.len(), 4);
+		assert_eq!(
+			triples[0],
+			TableTriple {
+				subject: "A-7".to_string(),
+				subject_type: "Well".to_string(),
+				predicate: "Depth".to_string(),
+				object: "3,200".to_string(),
+				object_type: "number".to_string(),
+				sentence: "The Depth of A-7 is 3,200.".to_string(),
+			}
+		);
+		assert_eq!(triples[1].object_type, "date");
+		assert_eq!(triples[2].predicate, "Column 4");
+		assert_eq!(triples[2].object_type, "Column 4");
+		assert_eq!(
+			(triples[3].subject.as_str(), triples[3].object_type.as_str()),
+			("B-2", "Depth")
+		);
+	}
+
+	#[test]
+	fn defaults_to_the_first_column() {
+		let triples = TableMapper::new(&TableModeConfig::default()).triples(&table());
+		assert_eq!(triples[0].subject, "3,200");
+		assert_eq!(triples[0].subject_type, "Depth");
+		assert_eq!(triples[0].predicate, "Well");
+		assert_eq!(triples.len(), 5);
+		assert_eq!((triples[4].subject.as_str(), triples[4].object.as_str()), ("-", "B-2"));
+	}
+}
@@ -72,12 +72,19 @@ pub struct ClassifiedSentenceWithRelations {
 
 lazy_static! {
 	static ref NEWLINE_RE: Regex = Regex::new(r"\n+").unwrap();
+	static ref REPEATED_SYMBOLS_RE: Regex = Regex::new(r"[^a-zA-Z0-9]{5,}").unwrap();
 }
 pub fn remove_newlines(text: &str) -> String {
 	let sanitized_text = sanitize_text(text);
 	NEWLINE_RE.replace_all(&sanitized_text, " ").to_string()
 }
 
+/// The text of a chunk as the models read it: on one line, without runs of symbols such as
+/// dot leaders.
+pub fn clean_chunk(text: &str) -> String {
+	REPEATED_SYMBOLS_RE.replace_all(&remove_newlines(text), " ").trim().to_string()
+}
+
 /// Removes null bytes and any other invalid UTF-8 sequences from the given text.
 fn sanitize_text(input: &str) -> String {
 	input.chars().filter(|&c| c != '\0').collect()
@@ -491,6 +498,44 @@ pub async fn calculate_biased_sentence_embedding(
 	Ok(normalized_biased_sentence_embedding)
 }
 
+/// Utility function to calculate embeddings for triples that carry no attention information,
+/// e.g. triples imported from curated sources. Each item is (sentence, head, tail, predicate);
+/// the sentence embedding is shifted towards its head, tail and predicate and normalized.
+pub fn calculate_triple_embeddings(
+	embedder: &TextEmbedding,
+	triples: &[(String, String, String, String)],
+) -> Result<Vec<Vec<f32>>, EngineError> {
+	if triples.is_empty() {
+		return Ok(Vec::new());
+	}
+	let texts: Vec<String> = triples
+		.iter()
+		.flat_map(|(sentence, head, tail, predicate)| {
+			[sentence.clone(), head.clone(), tail.clone(), predicate.clone()]
+		})
+		.collect();
+	let embeddings = embedder.embed(texts, None).map_err(|e| {
+		EngineError::new(EngineErrorKind::ModelError, Arc::new(anyhow::anyhow!(e.to_string())))
+	})?;
+
+	Ok(embeddings
+		.chunks(4)
+		.map(|parts| {
+			let mut combined = parts[0].clone();
+			for part in &parts[1..] {
+				for (value, other) in combined.iter_mut().zip(part.iter()) {
+					*value += other;
+				}
+			}
+			let norm: f32 = combined.iter().map(|&x| x * x).sum::<f32>().sqrt();
+			if norm > 0.0 {
+				combined.iter_mut().for_each(|x| *x /= norm);
+			}
+			combined
+		})
+		.collect())
+}
+
 /// Generates a custom UUID based on the current time and a random number.
 pub fn generate_custom_comb_uuid() -> String {
 	let custom_epoch = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
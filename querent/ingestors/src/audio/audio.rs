@@ -82,6 +82,9 @@ impl BaseIngestor for AudioIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 			yield Ok(ingested_tokens);
 		};
@@ -26,7 +26,7 @@ use tokio::io::AsyncReadExt;
 
 use crate::{
 	process_ingested_tokens_stream, processors::text_processing::TextCleanupProcessor,
-	AsyncProcessor, BaseIngestor, IngestorResult,
+	AsyncProcessor, BaseIngestor, IngestorError, IngestorResult,
 };
 
 // Define the TxtIngestor
@@ -68,17 +68,10 @@ impl BaseIngestor for CodeIngestor {
 				}
 				if let Some(mut data) = collected_bytes.data {
 					let mut buf = Vec::new();
-					let read_res = data.read_to_end(&mut buf).await;
-					if read_res.is_err() {
-						yield Ok(IngestedTokens {
-							data: vec![],
-							file: file.clone(),
-							doc_source: doc_source.clone(),
-							is_token_stream: false,
-							source_id: source_id.clone(),
-							image_id: None,
-						})
-						}
+					if let Err(e) = data.read_to_end(&mut buf).await {
+						yield Err(IngestorError::from(e).add_context(format!("Failed to read {}", file)));
+						return;
+					}
 					buffer.extend_from_slice(&buf);
 				}
 				source_id = collected_bytes.source_id.clone();
@@ -86,17 +79,9 @@ impl BaseIngestor for CodeIngestor {
 
 				let mut content = String::new();
 				let mut cursor = Cursor::new(buffer);
-				let is_read = cursor.read_to_string(&mut content).await;
-				if is_read.is_err() {
-					yield Ok(IngestedTokens {
-						data: vec![],
-						file: file.clone(),
-						doc_source: doc_source.clone(),
-						is_token_stream: false,
-						source_id: source_id.clone(),
-						image_id: None,
-					})
-				}else {
+				if let Err(e) = cursor.read_to_string(&mut content).await {
+					yield Err(IngestorError::from(e).add_context(format!("{} is not valid UTF-8 text", file)));
+				} else {
 				let ingested_tokens = IngestedTokens {
 					data: vec![content.to_string()],
 					file: file.clone(),
@@ -104,6 +89,9 @@ impl BaseIngestor for CodeIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				};
 				yield Ok(ingested_tokens);
 
@@ -114,6 +102,9 @@ impl BaseIngestor for CodeIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				};
 
 				yield Ok(ingested_tokens);
@@ -146,6 +137,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -184,6 +176,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
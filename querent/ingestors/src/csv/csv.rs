@@ -21,13 +21,13 @@ use async_trait::async_trait;
 use common::CollectedBytes;
 use csv::StringRecord;
 use futures::Stream;
-use proto::semantics::IngestedTokens;
+use proto::semantics::{ExtractedTable, IngestedTokens, TableRow};
 use std::{io::Cursor, pin::Pin, sync::Arc};
 use tokio::io::AsyncReadExt;
 
 use crate::{
 	process_ingested_tokens_stream, processors::text_processing::TextCleanupProcessor,
-	AsyncProcessor, BaseIngestor, IngestorResult,
+	AsyncProcessor, BaseIngestor, IngestorError, IngestorResult,
 };
 
 // Define the TxtIngestor
@@ -69,16 +69,9 @@ impl BaseIngestor for CsvIngestor {
 				}
 				if let Some(mut data) = collected_bytes.data {
 					let mut buf = Vec::new();
-					let data_res = data.read_to_end(&mut buf).await;
-					if data_res.is_err() {
-						yield Ok(IngestedTokens {
-								data: vec![],
-								file: file.clone(),
-								doc_source: doc_source.clone(),
-								is_token_stream: false,
-								source_id: source_id.clone(),
-								image_id: None,
-							})
+					if let Err(e) = data.read_to_end(&mut buf).await {
+						yield Err(IngestorError::from(e).add_context(format!("Failed to read {}", file)));
+						return;
 					}
 					buffer.extend_from_slice(&buf);
 				}
@@ -87,20 +80,14 @@ impl BaseIngestor for CsvIngestor {
 			let cursor = Cursor::new(buffer);
 			let mut reader = csv::Reader::from_reader(cursor);
 			let headers = reader.headers()?.clone();
+			let table_headers: Vec<String> = headers.iter().map(str::to_string).collect();
 			for result in reader.records() {
 				let record: StringRecord;
 				match result {
 					Ok(res) => {
 						record = res;
-					}, Err(_e) => {
-						yield Ok(IngestedTokens {
-								data: vec![],
-								file: file.clone(),
-								doc_source: doc_source.clone(),
-								is_token_stream: false,
-								source_id: source_id.clone(),
-								image_id: None,
-							});
+					}, Err(e) => {
+						yield Err(IngestorError::from(e));
 						continue;
 					}
 				}
@@ -118,6 +105,12 @@ impl BaseIngestor for CsvIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: Some(ExtractedTable {
+						headers: table_headers.clone(),
+						rows: vec![TableRow { cells: record.iter().map(str::to_string).collect() }],
+					}),
+					duplicate_of: None,
 				};
 				yield Ok(ingested_tokens);
 			}
@@ -128,6 +121,9 @@ impl BaseIngestor for CsvIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 
 			yield Ok(ingested_tokens);
@@ -160,6 +156,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -172,6 +169,9 @@ mod tests {
 			match tokens {
 				Ok(tokens) =>
 					if !tokens.data.is_empty() {
+						let table = tokens.table.expect("every row carries its table");
+						assert_eq!(table.rows.len(), 1);
+						assert_eq!(table.rows[0].cells.len(), table.headers.len());
 						all_data.push(tokens.data);
 					},
 				Err(e) => {
@@ -196,6 +196,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
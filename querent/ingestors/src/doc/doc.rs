@@ -100,6 +100,9 @@ impl BaseIngestor for DocIngestor {
 						is_token_stream: false,
 						source_id: source_id.clone(),
 						image_id: None,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
 					});
 					return;
 				}
@@ -119,6 +122,9 @@ impl BaseIngestor for DocIngestor {
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
 						});
 						continue;
 					}
@@ -132,6 +138,9 @@ impl BaseIngestor for DocIngestor {
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
 						});
 						continue;
 					}
@@ -147,6 +156,9 @@ impl BaseIngestor for DocIngestor {
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
 						});
 						continue;
 					}
@@ -164,6 +176,9 @@ impl BaseIngestor for DocIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 				return;
 			}
@@ -196,6 +211,9 @@ impl BaseIngestor for DocIngestor {
 								is_token_stream: false,
 								source_id: source_id.clone(),
 								image_id: None,
+								section_path: None,
+								table: None,
+								duplicate_of: None,
 							});
 						return;
 					}
@@ -213,6 +231,9 @@ impl BaseIngestor for DocIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 			yield Ok(ingested_tokens);
 
@@ -233,6 +254,7 @@ impl BaseIngestor for DocIngestor {
 						size: Some(img_data.len()),
 						source_id: source_id.clone(),
 						_owned_permit: None,
+						content_type: None,
 						image_id: Some(name.to_string()),
 					};
 					let image_ingestor = ImageIngestor::new();
@@ -262,6 +284,9 @@ impl BaseIngestor for DocIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			});
 		};
 
@@ -297,6 +322,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -338,6 +364,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -24,7 +24,7 @@ use tokio::io::AsyncReadExt as _;
 use tracing::{error, info};
 
 use futures::{Stream, StreamExt};
-use proto::semantics::IngestedTokens;
+use proto::semantics::{ExtractedTable, IngestedTokens};
 use std::{
 	collections::HashMap,
 	io::{Cursor, Read},
@@ -34,9 +34,11 @@ use std::{
 };
 
 use crate::{
-	image::image::ImageIngestor, process_ingested_tokens_stream,
-	processors::text_processing::TextCleanupProcessor, AsyncProcessor, BaseIngestor,
-	IngestorResult,
+	image::image::ImageIngestor,
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	tables::{table_from_rows, table_text},
+	AsyncProcessor, BaseIngestor, IngestorResult,
 };
 use xml::{reader::XmlEvent, EventReader};
 use zip::ZipArchive;
@@ -133,20 +135,79 @@ impl BaseIngestor for DocxIngestor {
 			}
 
 			let parser = EventReader::from_str(&xml_data);
-			let mut txt: Vec<String> = Vec::new();
+			let mut txt: Vec<(String, Option<ExtractedTable>, Option<String>)> = Vec::new();
 			let mut in_text = false;
+			// open headings, and the level and text of the heading paragraph being read
+			let mut headings: Vec<(usize, String)> = Vec::new();
+			let mut heading: Option<(usize, String)> = None;
+			// rows of the outermost table being read; nested tables are flattened into its cells
+			let mut table_rows: Vec<Vec<String>> = Vec::new();
+			let mut table_depth = 0;
 
 			for event in parser {
 				match event {
-					Ok(XmlEvent::StartElement { name, .. }) => {
-						if name.local_name == "p" {
-							txt.push("\n".to_string());
-						} else if name.local_name == "t" {
-							in_text = true;
+					Ok(XmlEvent::StartElement { name, attributes, .. })
+						if name.local_name == "pStyle" && table_depth == 0 =>
+					{
+						heading = attributes
+							.iter()
+							.find(|attribute| attribute.name.local_name == "val")
+							.and_then(|style| heading_level(&style.value))
+							.map(|level| (level, String::new()));
+					},
+					Ok(XmlEvent::StartElement { name, .. }) => match name.local_name.as_str() {
+						"tbl" => table_depth += 1,
+						"tr" if table_depth == 1 => table_rows.push(Vec::new()),
+						"tc" if table_depth == 1 =>
+							if let Some(row) = table_rows.last_mut() {
+								row.push(String::new());
+							},
+						"p" if table_depth > 0 =>
+							if let Some(cell) =
+								table_rows.last_mut().and_then(|row| row.last_mut())
+							{
+								if !cell.is_empty() {
+									cell.push(' ');
+								}
+							},
+						"p" => txt.push(("\n".to_string(), None, section_path(&headings))),
+						"t" => in_text = true,
+						_ => {},
+					},
+					Ok(XmlEvent::EndElement { name }) if name.local_name == "tbl" => {
+						table_depth -= 1;
+						if table_depth == 0 {
+							let rows = std::mem::take(&mut table_rows);
+							let text =
+								rows.iter().map(|row| row.join(" ")).collect::<Vec<_>>().join("\n");
+							let path = section_path(&headings);
+							match table_from_rows(rows) {
+								Some(table) => txt.push((table_text(&table), Some(table), path)),
+								None => txt.push((text, None, path)),
+							}
 						}
-					}
+					},
+					Ok(XmlEvent::EndElement { name })
+						if name.local_name == "p" && table_depth == 0 =>
+					{
+						if let Some((level, title)) = heading.take() {
+							let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+							if !title.is_empty() {
+								headings.retain(|(open_level, _)| *open_level < level);
+								headings.push((level, title));
+							}
+						}
+					},
 					Ok(XmlEvent::Characters(content)) if in_text => {
-						txt.push(content);
+						match table_rows.last_mut().and_then(|row| row.last_mut()) {
+							Some(cell) if table_depth > 0 => cell.push_str(&content),
+							_ => {
+								if let Some((_, title)) = heading.as_mut() {
+									title.push_str(&content);
+								}
+								txt.push((content, None, section_path(&headings)));
+							},
+						}
 						in_text = false;
 					}
 					Ok(XmlEvent::EndDocument) => break,
@@ -158,7 +219,7 @@ impl BaseIngestor for DocxIngestor {
 				}
 			}
 
-			for text in txt {
+			for (text, table, section_path) in txt {
 				let ingested_tokens = IngestedTokens {
 					data: vec![text],
 					file: file.clone(),
@@ -166,6 +227,9 @@ impl BaseIngestor for DocxIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path,
+					table,
+					duplicate_of: None,
 				};
 				yield Ok(ingested_tokens);
 			}
@@ -187,6 +251,7 @@ impl BaseIngestor for DocxIngestor {
 						size: Some(img_data.len()),
 						source_id: source_id.clone(),
 						_owned_permit: None,
+						content_type: None,
 						image_id: Some(name.to_string()),
 					};
 					let image_ingestor = ImageIngestor::new();
@@ -217,6 +282,21 @@ impl BaseIngestor for DocxIngestor {
 	}
 }
 
+/// Level of a paragraph style of Word: `Title`, then `Heading1` to `Heading9`.
+fn heading_level(style: &str) -> Option<usize> {
+	if style.eq_ignore_ascii_case("title") {
+		return Some(0);
+	}
+	let level = style.get(..7)?.eq_ignore_ascii_case("heading").then(|| style[7..].trim())?;
+	level.parse().ok()
+}
+
+/// The titles of the open headings joined by " > ", or `None` before the first heading.
+fn section_path(headings: &[(usize, String)]) -> Option<String> {
+	(!headings.is_empty())
+		.then(|| headings.iter().map(|(_, title)| title.as_str()).collect::<Vec<_>>().join(" > "))
+}
+
 #[cfg(test)]
 mod tests {
 	use futures::StreamExt;
@@ -238,6 +318,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -279,6 +360,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -300,4 +382,70 @@ mod tests {
 		}
 		assert!(all_data.len() == 0, "Should not have found data in DOCX file");
 	}
+
+	#[tokio::test]
+	async fn test_docx_tables() {
+		let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:r><w:t>Well summary</w:t></w:r></w:p>
+<w:tbl>
+<w:tr><w:tc><w:p><w:r><w:t>Well</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>Depth</w:t></w:r></w:p></w:tc></w:tr>
+<w:tr><w:tc><w:p><w:r><w:t>A-7</w:t></w:r></w:p></w:tc><w:tc><w:p><w:r><w:t>3200</w:t></w:r></w:p><w:p><w:r><w:t>metres</w:t></w:r></w:p></w:tc></w:tr>
+</w:tbl>
+</w:body></w:document>"#;
+		let mut stream = DocxIngestor::new().ingest(vec![docx(document)]).await.unwrap();
+		let mut tables = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			if let Some(table) = tokens.unwrap().table {
+				tables.push(table);
+			}
+		}
+		assert_eq!(tables.len(), 1);
+		assert_eq!(tables[0].headers, vec!["Well", "Depth"]);
+		assert_eq!(tables[0].rows[0].cells, vec!["A-7", "3200 metres"]);
+	}
+	#[tokio::test]
+	async fn test_docx_section_paths() {
+		let document = r#"<?xml version="1.0" encoding="UTF-8"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main"><w:body>
+<w:p><w:pPr><w:pStyle w:val="Heading1"/></w:pPr><w:r><w:t>Wells</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Drill</w:t></w:r><w:r><w:t>ing</w:t></w:r></w:p>
+<w:p><w:r><w:t>The rig reached the site.</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="Heading2"/></w:pPr><w:r><w:t>Production</w:t></w:r></w:p>
+<w:p><w:pPr><w:pStyle w:val="Normal"/></w:pPr><w:r><w:t>300 barrels a day.</w:t></w:r></w:p>
+</w:body></w:document>"#;
+		let mut stream = DocxIngestor::new().ingest(vec![docx(document)]).await.unwrap();
+		let mut paths = HashMap::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if let Some(text) = tokens.data.first() {
+				paths.insert(text.trim().to_string(), tokens.section_path);
+			}
+		}
+		assert_eq!(paths["The rig reached the site."].as_deref(), Some("Wells > Drilling"));
+		assert_eq!(paths["300 barrels a day."].as_deref(), Some("Wells > Production"));
+		assert_eq!(heading_level("heading 3"), Some(3));
+		assert_eq!(heading_level("Normal"), None);
+	}
+
+	fn docx(document: &str) -> CollectedBytes {
+		let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+		writer
+			.start_file("word/document.xml", zip::write::SimpleFileOptions::default())
+			.unwrap();
+		std::io::Write::write_all(&mut writer, document.as_bytes()).unwrap();
+		let bytes = writer.finish().unwrap().into_inner();
+		CollectedBytes {
+			data: Some(Box::pin(Cursor::new(bytes))),
+			file: Some(Path::new("wells.docx").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("docx".to_string()),
+			size: Some(10),
+			source_id: "FileSystem1".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		}
+	}
 }
@@ -0,0 +1,133 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::{pin_mut, Stream, StreamExt};
+use proto::semantics::IngestedTokens;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	email::mime::{email_tokens, ParsedEmail},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes, AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+// Define the EmlIngestor
+pub struct EmlIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl EmlIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for EmlIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let email = match ParsedEmail::parse(&buffer) {
+				Ok(email) => email,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to parse email {}", file)));
+					return;
+				},
+			};
+			let tokens = email_tokens(vec![email], file, doc_source, source_id);
+			pin_mut!(tokens);
+			while let Some(tokens) = tokens.next().await {
+				yield tokens;
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{io::Cursor, path::Path};
+
+	#[tokio::test]
+	async fn test_eml_ingestor() {
+		let raw = concat!(
+			"From: alice@example.com\r\n",
+			"To: bob@example.com\r\n",
+			"Subject: Core samples\r\n",
+			"Message-ID: <core-1@example.com>\r\n",
+			"Content-Type: multipart/mixed; boundary=\"b\"\r\n",
+			"\r\n",
+			"--b\r\n",
+			"Content-Type: text/plain\r\n",
+			"\r\n",
+			"Samples from the north field are attached.\r\n",
+			"--b\r\n",
+			"Content-Type: text/plain\r\n",
+			"Content-Disposition: attachment; filename=\"samples.txt\"\r\n",
+			"\r\n",
+			"Sample one shows sandstone.\r\n",
+			"--b--\r\n",
+		);
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(raw.as_bytes().to_vec()))),
+			file: Some(Path::new("core-1.eml").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("eml".to_string()),
+			size: Some(raw.len()),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = EmlIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut texts = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			assert_eq!(tokens.file, "core-1.eml");
+			texts.extend(tokens.data);
+		}
+		assert_eq!(texts.len(), 2);
+		assert!(texts[0].contains("Core samples"));
+		assert!(texts[1].contains("sandstone"));
+	}
+}
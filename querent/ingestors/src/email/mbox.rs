@@ -0,0 +1,170 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::{pin_mut, Stream, StreamExt};
+use proto::semantics::IngestedTokens;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	email::mime::{email_tokens, ParsedEmail},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes, AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+// Define the MboxIngestor
+pub struct MboxIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl MboxIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+/// Splits an mbox archive into raw messages. A message starts at a `From ` line that
+/// opens the file or follows a blank line; `>From ` quoting is undone in the bodies.
+pub fn split_mbox(data: &[u8]) -> Vec<Vec<u8>> {
+	let mut messages = Vec::new();
+	let mut current: Option<Vec<u8>> = None;
+	let mut previous_blank = true;
+	for line in data.split_inclusive(|byte| *byte == b'\n') {
+		if previous_blank && line.starts_with(b"From ") {
+			if let Some(message) = current.take() {
+				messages.push(message);
+			}
+			current = Some(Vec::new());
+			previous_blank = false;
+			continue;
+		}
+		let content = line.strip_suffix(b"\n").unwrap_or(line);
+		previous_blank = content.strip_suffix(b"\r").unwrap_or(content).is_empty();
+		if let Some(message) = current.as_mut() {
+			let quoted = line.iter().take_while(|byte| **byte == b'>').count();
+			if quoted > 0 && line[quoted..].starts_with(b"From ") {
+				message.extend_from_slice(&line[1..]);
+			} else {
+				message.extend_from_slice(line);
+			}
+		}
+	}
+	if let Some(message) = current {
+		messages.push(message);
+	}
+	messages
+}
+
+#[async_trait]
+impl BaseIngestor for MboxIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let mut emails = Vec::new();
+			for (index, raw) in split_mbox(&buffer).iter().enumerate() {
+				match ParsedEmail::parse(raw) {
+					Ok(email) => emails.push(email),
+					Err(e) => tracing::warn!("Skipping message {} in {}: {:?}", index + 1, file, e),
+				}
+			}
+			let tokens = email_tokens(emails, file, doc_source, source_id);
+			pin_mut!(tokens);
+			while let Some(tokens) = tokens.next().await {
+				yield tokens;
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{io::Cursor, path::Path};
+
+	const MBOX: &str = concat!(
+		"From alice@example.com Mon Sep 30 09:00:00 2024\n",
+		"From: alice@example.com\n",
+		"Subject: First\n",
+		"\n",
+		"Drilling starts Monday.\n",
+		">From the field team.\n",
+		"\n",
+		"From bob@example.com Mon Sep 30 10:00:00 2024\n",
+		"From: bob@example.com\n",
+		"Subject: Second\n",
+		"\n",
+		"Noted.\n",
+	);
+
+	#[test]
+	fn test_split_mbox() {
+		let messages = split_mbox(MBOX.as_bytes());
+		assert_eq!(messages.len(), 2);
+		let first = String::from_utf8(messages[0].clone()).unwrap();
+		assert!(first.starts_with("From: alice@example.com\n"));
+		assert!(first.contains("\nFrom the field team.\n"));
+		assert!(String::from_utf8(messages[1].clone()).unwrap().contains("Subject: Second"));
+	}
+
+	#[tokio::test]
+	async fn test_mbox_ingestor() {
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(MBOX.as_bytes().to_vec()))),
+			file: Some(Path::new("archive.mbox").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("mbox".to_string()),
+			size: Some(MBOX.len()),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = MboxIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut count = 0;
+		while let Some(tokens) = stream.next().await {
+			if !tokens.unwrap().data.is_empty() {
+				count += 1;
+			}
+		}
+		assert_eq!(count, 2);
+	}
+}
@@ -0,0 +1,375 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_stream::stream;
+use common::CollectedBytes;
+use futures::{Stream, StreamExt};
+use mailparse::{parse_mail, DispositionType, MailHeaderMap, ParsedMail};
+use once_cell::sync::Lazy;
+use proto::semantics::IngestedTokens;
+use regex::Regex;
+use std::{io::Cursor, path::Path};
+
+use crate::{file_type::extension_for_content_type, resolve_ingestor, IngestorResult};
+
+static MESSAGE_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^<>\s]+)>").unwrap());
+static HTML_SKIPPED: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?is)<(script|style|head)[^>]*>.*?</(script|style|head)>").unwrap());
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<[^>]*>").unwrap());
+
+/// A file carried by an email, either attached or embedded inline.
+#[derive(Debug, Clone)]
+pub struct EmailAttachment {
+	pub filename: String,
+	pub content_type: String,
+	pub data: Vec<u8>,
+	pub inline: bool,
+}
+
+impl EmailAttachment {
+	/// Extension used to pick the ingestor, taken from the file name and falling back
+	/// to the content type.
+	pub fn extension(&self) -> String {
+		if let Some(ext) = Path::new(&self.filename).extension().and_then(|ext| ext.to_str()) {
+			return ext.to_lowercase();
+		}
+		extension_for_content_type(&self.content_type).unwrap_or_default().to_string()
+	}
+}
+
+/// An email decoded into its headers, readable body and attachments.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedEmail {
+	pub from: String,
+	pub to: String,
+	pub cc: String,
+	pub date: String,
+	pub subject: String,
+	pub message_id: String,
+	/// Message id of the first message in the conversation, taken from `References`
+	/// or `In-Reply-To`. Equal to `message_id` for messages that start a thread.
+	pub thread_id: String,
+	pub body: String,
+	pub attachments: Vec<EmailAttachment>,
+}
+
+impl ParsedEmail {
+	/// Parses an RFC 822 message, walking nested multiparts and decoding transfer
+	/// encodings and charsets along the way.
+	pub fn parse(raw: &[u8]) -> IngestorResult<Self> {
+		let mail = parse_mail(raw)?;
+		let headers = &mail.headers;
+		let header =
+			|name: &str| headers.get_first_value(name).unwrap_or_default().trim().to_string();
+
+		let message_id = first_message_id(&header("Message-ID")).unwrap_or_default();
+		let thread_id = first_message_id(&header("References"))
+			.or_else(|| first_message_id(&header("In-Reply-To")))
+			.unwrap_or_else(|| message_id.clone());
+		let mut email = ParsedEmail {
+			from: header("From"),
+			to: header("To"),
+			cc: header("Cc"),
+			date: header("Date"),
+			subject: header("Subject"),
+			message_id,
+			thread_id,
+			..Default::default()
+		};
+
+		let mut plain = Vec::new();
+		let mut html = Vec::new();
+		collect_parts(&mail, &mut email.attachments, &mut plain, &mut html);
+		email.body = if !plain.is_empty() {
+			plain.join("\n\n")
+		} else {
+			html.iter().map(|part| strip_html(part)).collect::<Vec<_>>().join("\n\n")
+		};
+		Ok(email)
+	}
+
+	/// The headers worth keeping as context, one `Name: value` per line.
+	pub fn header_text(&self) -> String {
+		[
+			("From", &self.from),
+			("To", &self.to),
+			("Cc", &self.cc),
+			("Date", &self.date),
+			("Subject", &self.subject),
+			("Message-ID", &self.message_id),
+			("Thread-ID", &self.thread_id),
+		]
+		.iter()
+		.filter(|(_, value)| !value.is_empty())
+		.map(|(name, value)| format!("{}: {}", name, value))
+		.collect::<Vec<_>>()
+		.join("\n")
+	}
+
+	/// Header block followed by the body.
+	pub fn text(&self) -> String {
+		let header = self.header_text();
+		if self.body.trim().is_empty() {
+			header
+		} else {
+			format!("{}\n\n{}", header, self.body.trim())
+		}
+	}
+}
+
+fn collect_parts(
+	part: &ParsedMail,
+	attachments: &mut Vec<EmailAttachment>,
+	plain: &mut Vec<String>,
+	html: &mut Vec<String>,
+) {
+	if !part.subparts.is_empty() {
+		for subpart in &part.subparts {
+			collect_parts(subpart, attachments, plain, html);
+		}
+		return;
+	}
+
+	let mimetype = part.ctype.mimetype.to_lowercase();
+	let disposition = part.get_content_disposition();
+	let filename = disposition
+		.params
+		.get("filename")
+		.or_else(|| part.ctype.params.get("name"))
+		.cloned();
+	let is_text = mimetype == "text/plain" || mimetype == "text/html";
+	let is_attachment =
+		disposition.disposition == DispositionType::Attachment || filename.is_some() || !is_text;
+
+	if is_attachment {
+		match part.get_body_raw() {
+			Ok(data) if !data.is_empty() => {
+				let mut attachment = EmailAttachment {
+					filename: filename.unwrap_or_default(),
+					content_type: mimetype,
+					data,
+					inline: disposition.disposition == DispositionType::Inline,
+				};
+				if attachment.filename.is_empty() {
+					attachment.filename =
+						format!("attachment-{}.{}", attachments.len() + 1, attachment.extension());
+				}
+				attachments.push(attachment);
+			},
+			Ok(_) => {},
+			Err(e) => tracing::warn!("Failed to decode email attachment: {:?}", e),
+		}
+		return;
+	}
+
+	match part.get_body() {
+		Ok(body) if mimetype == "text/plain" => plain.push(body),
+		Ok(body) => html.push(body),
+		Err(e) => tracing::warn!("Failed to decode email body: {:?}", e),
+	}
+}
+
+pub(crate) fn first_message_id(value: &str) -> Option<String> {
+	MESSAGE_ID.captures(value).map(|captures| captures[1].to_string())
+}
+
+pub(crate) fn strip_html(html: &str) -> String {
+	let html = HTML_SKIPPED.replace_all(html, " ");
+	let text = HTML_TAG.replace_all(&html, " ");
+	text.replace("&nbsp;", " ")
+		.replace("&amp;", "&")
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+/// Yields the text of each email followed by the tokens of its attachments. Attachments
+/// go through the ingestor detected for their content and are reported under the
+/// file of the email that carried them.
+pub(crate) fn email_tokens(
+	emails: Vec<ParsedEmail>,
+	file: String,
+	doc_source: String,
+	source_id: String,
+) -> impl Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static {
+	stream! {
+		for email in emails {
+			let text = email.text();
+			if !text.is_empty() {
+				yield Ok(IngestedTokens {
+					data: vec![text],
+					file: file.clone(),
+					doc_source: doc_source.clone(),
+					is_token_stream: false,
+					source_id: source_id.clone(),
+					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
+				});
+			}
+
+			for attachment in email.attachments {
+				let resolved = resolve_ingestor(
+					&attachment.data,
+					Some(&attachment.content_type),
+					&attachment.extension(),
+				)
+				.await;
+				let (extension, ingestor) = match resolved {
+					Ok((extension, ingestor)) if ingestor.is_supported() => (extension, ingestor),
+					_ => {
+						tracing::info!(
+							"Skipping unsupported attachment {} in {}",
+							attachment.filename,
+							file
+						);
+						continue;
+					},
+				};
+				let size = attachment.data.len();
+				let collected_bytes = CollectedBytes {
+					data: Some(Box::pin(Cursor::new(attachment.data))),
+					file: Some(Path::new(&attachment.filename).to_path_buf()),
+					doc_source: Some(doc_source.clone()),
+					eof: false,
+					extension: Some(extension),
+					size: Some(size),
+					source_id: source_id.clone(),
+					_owned_permit: None,
+					content_type: Some(attachment.content_type.clone()),
+					image_id: attachment.inline.then(|| attachment.filename.clone()),
+				};
+				let mut attachment_stream = match ingestor.ingest(vec![collected_bytes]).await {
+					Ok(attachment_stream) => attachment_stream,
+					Err(e) => {
+						tracing::error!(
+							"Failed to ingest attachment {}: {:?}",
+							attachment.filename,
+							e
+						);
+						continue;
+					},
+				};
+				while let Some(tokens) = attachment_stream.next().await {
+					match tokens {
+						Ok(mut tokens) => if !tokens.data.is_empty() {
+							// attribute attachment content to the email it came with
+							tokens.file = file.clone();
+							tokens.doc_source = doc_source.clone();
+							yield Ok(tokens);
+						},
+						Err(e) => tracing::error!(
+							"Failed to get tokens from attachment {}: {:?}",
+							attachment.filename,
+							e
+						),
+					}
+				}
+			}
+		}
+		yield Ok(IngestedTokens {
+			data: vec![],
+			file: file.clone(),
+			doc_source: doc_source.clone(),
+			is_token_stream: false,
+			source_id: source_id.clone(),
+			image_id: None,
+			section_path: None,
+			table: None,
+			duplicate_of: None,
+		});
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const NESTED: &str = concat!(
+		"From: =?ISO-8859-1?Q?Ren=E9_Dupont?= <rene@example.com>\r\n",
+		"To: team@example.com\r\n",
+		"Subject: Re: Well logs\r\n",
+		"Date: Tue, 1 Oct 2024 10:00:00 +0000\r\n",
+		"Message-ID: <reply-2@example.com>\r\n",
+		"In-Reply-To: <reply-1@example.com>\r\n",
+		"References: <root@example.com> <reply-1@example.com>\r\n",
+		"MIME-Version: 1.0\r\n",
+		"Content-Type: multipart/mixed; boundary=\"outer\"\r\n",
+		"\r\n",
+		"--outer\r\n",
+		"Content-Type: multipart/alternative; boundary=\"inner\"\r\n",
+		"\r\n",
+		"--inner\r\n",
+		"Content-Type: text/plain; charset=\"iso-8859-1\"\r\n",
+		"Content-Transfer-Encoding: quoted-printable\r\n",
+		"\r\n",
+		"The caf=E9 well reached 3000=\r\n",
+		" m depth.\r\n",
+		"--inner\r\n",
+		"Content-Type: text/html; charset=\"utf-8\"\r\n",
+		"\r\n",
+		"<p>The caf&eacute; well reached 3000 m depth.</p>\r\n",
+		"--inner--\r\n",
+		"--outer\r\n",
+		"Content-Type: image/png\r\n",
+		"Content-Disposition: inline\r\n",
+		"Content-Transfer-Encoding: base64\r\n",
+		"\r\n",
+		"iVBORw0KGgo=\r\n",
+		"--outer\r\n",
+		"Content-Type: text/plain\r\n",
+		"Content-Disposition: attachment; filename=\"notes.txt\"\r\n",
+		"Content-Transfer-Encoding: base64\r\n",
+		"\r\n",
+		"UG9yb3NpdHkgaXMgMTIlLg==\r\n",
+		"--outer--\r\n",
+	);
+
+	#[test]
+	fn test_parse_nested_multipart() {
+		let email = ParsedEmail::parse(NESTED.as_bytes()).unwrap();
+		assert_eq!(email.from, "René Dupont <rene@example.com>");
+		assert_eq!(email.subject, "Re: Well logs");
+		assert_eq!(email.message_id, "reply-2@example.com");
+		assert_eq!(email.thread_id, "root@example.com");
+		assert_eq!(email.body.trim(), "The café well reached 3000 m depth.");
+
+		assert_eq!(email.attachments.len(), 2);
+		assert!(email.attachments[0].inline);
+		assert_eq!(email.attachments[0].extension(), "png");
+		assert_eq!(email.attachments[1].filename, "notes.txt");
+		assert_eq!(email.attachments[1].data, b"Porosity is 12%.");
+		assert!(email.text().starts_with("From: René Dupont"));
+	}
+
+	#[test]
+	fn test_html_only_body() {
+		let raw = concat!(
+			"Subject: Status\r\n",
+			"Content-Type: text/html\r\n",
+			"\r\n",
+			"<html><head><title>x</title></head><body><p>All &amp; done</p></body></html>\r\n",
+		);
+		let email = ParsedEmail::parse(raw.as_bytes()).unwrap();
+		assert_eq!(email.body, "All & done");
+		assert!(email.attachments.is_empty());
+	}
+}
@@ -0,0 +1,299 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_stream::stream;
+use async_trait::async_trait;
+use cfb::CompoundFile;
+use common::CollectedBytes;
+use futures::{pin_mut, Stream, StreamExt};
+use mailparse::{parse_headers, MailHeaderMap};
+use proto::semantics::IngestedTokens;
+use std::{
+	io::{Cursor, Read, Seek},
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::Arc,
+};
+
+use crate::{
+	email::mime::{email_tokens, first_message_id, strip_html, EmailAttachment, ParsedEmail},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes, AsyncProcessor, BaseIngestor, IngestorError, IngestorErrorKind,
+	IngestorResult,
+};
+
+// MAPI property tags stored as `__substg1.0_<tag><type>` streams.
+const SUBJECT: &str = "0037";
+const TRANSPORT_HEADERS: &str = "007D";
+const SENDER_NAME: &str = "0C1A";
+const SENDER_EMAIL: &str = "0C1F";
+const DISPLAY_CC: &str = "0E03";
+const DISPLAY_TO: &str = "0E04";
+const BODY: &str = "1000";
+const HTML_BODY: &str = "1013";
+const INTERNET_MESSAGE_ID: &str = "1035";
+const ATTACH_DATA: &str = "3701";
+const ATTACH_FILENAME: &str = "3704";
+const ATTACH_LONG_FILENAME: &str = "3707";
+const ATTACH_MIME_TAG: &str = "370E";
+const ATTACHMENT_STORAGE_PREFIX: &str = "__attach_version1.0_#";
+
+// Define the MsgIngestor
+pub struct MsgIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl MsgIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+fn read_stream<F: Read + Seek>(
+	msg: &mut CompoundFile<F>,
+	storage: &Path,
+	tag: &str,
+	kind: &str,
+) -> Option<Vec<u8>> {
+	let path = storage.join(format!("__substg1.0_{}{}", tag, kind));
+	let mut stream = msg.open_stream(&path).ok()?;
+	let mut data = Vec::new();
+	stream.read_to_end(&mut data).ok()?;
+	Some(data)
+}
+
+/// Reads a string property, which Outlook stores either as UTF-16LE (`001F`) or as
+/// 8-bit text (`001E`).
+fn read_string<F: Read + Seek>(
+	msg: &mut CompoundFile<F>,
+	storage: &Path,
+	tag: &str,
+) -> Option<String> {
+	if let Some(data) = read_stream(msg, storage, tag, "001F") {
+		let units = data.chunks_exact(2).map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+		let value = String::from_utf16_lossy(&units.collect::<Vec<_>>());
+		return Some(value.trim_end_matches('\0').to_string());
+	}
+	read_stream(msg, storage, tag, "001E")
+		.map(|data| String::from_utf8_lossy(&data).trim_end_matches('\0').to_string())
+}
+
+/// Parses an Outlook `.msg` file, which is a compound file holding one stream per
+/// MAPI property.
+pub fn parse_msg(data: Vec<u8>) -> IngestorResult<ParsedEmail> {
+	let mut msg = CompoundFile::open(Cursor::new(data)).map_err(|err| {
+		IngestorError::new(
+			IngestorErrorKind::Email,
+			Arc::new(anyhow::anyhow!("Not an Outlook message: {:?}", err)),
+		)
+	})?;
+	let root = Path::new("/");
+
+	let mut email = ParsedEmail {
+		subject: read_string(&mut msg, root, SUBJECT).unwrap_or_default(),
+		to: read_string(&mut msg, root, DISPLAY_TO).unwrap_or_default(),
+		cc: read_string(&mut msg, root, DISPLAY_CC).unwrap_or_default(),
+		message_id: read_string(&mut msg, root, INTERNET_MESSAGE_ID)
+			.and_then(|id| first_message_id(&id))
+			.unwrap_or_default(),
+		..Default::default()
+	};
+	let sender_name = read_string(&mut msg, root, SENDER_NAME).unwrap_or_default();
+	let sender_email = read_string(&mut msg, root, SENDER_EMAIL).unwrap_or_default();
+	email.from = match (sender_name.is_empty(), sender_email.is_empty()) {
+		(false, false) => format!("{} <{}>", sender_name, sender_email),
+		(false, true) => sender_name,
+		_ => sender_email,
+	};
+
+	// The original internet headers, when kept, carry the date and threading details
+	// that have no dedicated property.
+	let mut references = String::new();
+	let mut in_reply_to = String::new();
+	if let Some(headers) = read_string(&mut msg, root, TRANSPORT_HEADERS) {
+		if let Ok((headers, _)) = parse_headers(headers.as_bytes()) {
+			let header = |name: &str| headers.get_first_value(name).unwrap_or_default();
+			email.date = header("Date").trim().to_string();
+			references = header("References");
+			in_reply_to = header("In-Reply-To");
+			if email.message_id.is_empty() {
+				email.message_id = first_message_id(&header("Message-ID")).unwrap_or_default();
+			}
+		}
+	}
+	email.thread_id = first_message_id(&references)
+		.or_else(|| first_message_id(&in_reply_to))
+		.unwrap_or_else(|| email.message_id.clone());
+
+	email.body = match read_string(&mut msg, root, BODY) {
+		Some(body) if !body.trim().is_empty() => body,
+		_ => read_stream(&mut msg, root, HTML_BODY, "0102")
+			.map(|html| strip_html(&String::from_utf8_lossy(&html)))
+			.unwrap_or_default(),
+	};
+
+	let attachment_storages: Vec<PathBuf> = match msg.read_storage(root) {
+		Ok(entries) => entries
+			.filter(|entry| {
+				entry.is_storage() && entry.name().starts_with(ATTACHMENT_STORAGE_PREFIX)
+			})
+			.map(|entry| entry.path().to_path_buf())
+			.collect(),
+		Err(_) => Vec::new(),
+	};
+	for storage in attachment_storages {
+		// Embedded messages keep their data in a sub-storage rather than a stream.
+		let data = match read_stream(&mut msg, &storage, ATTACH_DATA, "0102") {
+			Some(data) if !data.is_empty() => data,
+			_ => continue,
+		};
+		let filename = read_string(&mut msg, &storage, ATTACH_LONG_FILENAME)
+			.or_else(|| read_string(&mut msg, &storage, ATTACH_FILENAME))
+			.unwrap_or_default();
+		let content_type = read_string(&mut msg, &storage, ATTACH_MIME_TAG).unwrap_or_default();
+		let mut attachment = EmailAttachment { filename, content_type, data, inline: false };
+		if attachment.filename.is_empty() {
+			attachment.filename =
+				format!("attachment-{}.{}", email.attachments.len() + 1, attachment.extension());
+		}
+		email.attachments.push(attachment);
+	}
+	Ok(email)
+}
+
+#[async_trait]
+impl BaseIngestor for MsgIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let email = match parse_msg(buffer) {
+				Ok(email) => email,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to parse message {}", file)));
+					return;
+				},
+			};
+			let tokens = email_tokens(vec![email], file, doc_source, source_id);
+			pin_mut!(tokens);
+			while let Some(tokens) = tokens.next().await {
+				yield tokens;
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+
+	fn utf16(value: &str) -> Vec<u8> {
+		value.encode_utf16().flat_map(|unit| unit.to_le_bytes()).collect()
+	}
+
+	fn sample_msg() -> Vec<u8> {
+		let mut msg = CompoundFile::create(Cursor::new(Vec::new())).unwrap();
+		let streams: Vec<(&str, Vec<u8>)> =
+			vec![
+			("/__substg1.0_0037001F", utf16("Quarterly report")),
+			("/__substg1.0_0C1A001F", utf16("Carol")),
+			("/__substg1.0_0C1F001E", b"carol@example.com".to_vec()),
+			("/__substg1.0_0E04001F", utf16("Dave")),
+			("/__substg1.0_1000001F", utf16("Production rose by four percent.")),
+			(
+				"/__substg1.0_007D001F",
+				utf16("Date: Wed, 2 Oct 2024 08:00:00 +0000\r\nIn-Reply-To: <q3@example.com>\r\n\r\n"),
+			),
+		];
+		for (path, data) in streams {
+			msg.create_stream(path).unwrap().write_all(&data).unwrap();
+		}
+		msg.create_storage("/__attach_version1.0_#00000000").unwrap();
+		msg.create_stream("/__attach_version1.0_#00000000/__substg1.0_3707001F")
+			.unwrap()
+			.write_all(&utf16("figures.txt"))
+			.unwrap();
+		msg.create_stream("/__attach_version1.0_#00000000/__substg1.0_37010102")
+			.unwrap()
+			.write_all(b"Output was 1200 barrels per day.")
+			.unwrap();
+		msg.flush().unwrap();
+		msg.into_inner().into_inner()
+	}
+
+	#[test]
+	fn test_parse_msg() {
+		let email = parse_msg(sample_msg()).unwrap();
+		assert_eq!(email.subject, "Quarterly report");
+		assert_eq!(email.from, "Carol <carol@example.com>");
+		assert_eq!(email.to, "Dave");
+		assert_eq!(email.date, "Wed, 2 Oct 2024 08:00:00 +0000");
+		assert_eq!(email.thread_id, "q3@example.com");
+		assert_eq!(email.body, "Production rose by four percent.");
+		assert_eq!(email.attachments.len(), 1);
+		assert_eq!(email.attachments[0].filename, "figures.txt");
+	}
+
+	#[tokio::test]
+	async fn test_msg_ingestor() {
+		let bytes = sample_msg();
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(bytes.clone()))),
+			file: Some(Path::new("report.msg").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("msg".to_string()),
+			size: Some(bytes.len()),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = MsgIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut count = 0;
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				assert_eq!(tokens.file, "report.msg");
+				count += 1;
+			}
+		}
+		assert_eq!(count, 2);
+	}
+}
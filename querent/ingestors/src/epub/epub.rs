@@ -0,0 +1,406 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use proto::semantics::IngestedTokens;
+use std::{
+	collections::HashMap,
+	io::{Cursor, Read},
+	pin::Pin,
+	sync::Arc,
+};
+use xml::reader::{EventReader, XmlEvent};
+use zip::ZipArchive;
+
+use crate::{
+	odf::package::attribute,
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes,
+	sections::{section_tokens, Section, SectionBuilder},
+	AsyncProcessor, BaseIngestor, IngestorError, IngestorErrorKind, IngestorResult,
+};
+
+const BLOCK_TAGS: &[&str] = &[
+	"address",
+	"article",
+	"aside",
+	"blockquote",
+	"dd",
+	"div",
+	"dl",
+	"dt",
+	"figcaption",
+	"figure",
+	"footer",
+	"header",
+	"li",
+	"ol",
+	"p",
+	"pre",
+	"section",
+	"table",
+	"td",
+	"th",
+	"tr",
+	"ul",
+];
+
+/// Reads the chapters of a book in spine order and splits them into sections along
+/// their `h1` to `h6` headings.
+pub fn epub_sections(data: Vec<u8>) -> IngestorResult<Vec<Section>> {
+	let mut archive = ZipArchive::new(Cursor::new(data))?;
+	let container = read_entry(&mut archive, "META-INF/container.xml")?;
+	let mut package_path = None;
+	for event in EventReader::from_str(&container) {
+		if let XmlEvent::StartElement { name, attributes, .. } = event? {
+			if name.local_name == "rootfile" {
+				package_path = attribute(&attributes, "full-path").map(str::to_string);
+				break;
+			}
+		}
+	}
+	let Some(package_path) = package_path else {
+		return Err(IngestorError::new(
+			IngestorErrorKind::NotFound,
+			Arc::new(anyhow::anyhow!("No rootfile listed in META-INF/container.xml")),
+		));
+	};
+	let package = read_entry(&mut archive, &package_path)?;
+	let base = package_path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+
+	let mut manifest = HashMap::new();
+	let mut spine = Vec::new();
+	for event in EventReader::from_str(&package) {
+		if let XmlEvent::StartElement { name, attributes, .. } = event? {
+			match name.local_name.as_str() {
+				"item" =>
+					if let (Some(id), Some(href)) =
+						(attribute(&attributes, "id"), attribute(&attributes, "href"))
+					{
+						let media_type = attribute(&attributes, "media-type").unwrap_or_default();
+						manifest.insert(id.to_string(), (href.to_string(), media_type.to_string()));
+					},
+				"itemref" =>
+					if let Some(id) = attribute(&attributes, "idref") {
+						spine.push(id.to_string());
+					},
+				_ => {},
+			}
+		}
+	}
+
+	let mut builder = SectionBuilder::new();
+	for id in spine {
+		let Some((href, media_type)) = manifest.get(&id) else { continue };
+		if !media_type.contains("html") {
+			continue;
+		}
+		let path = resolve_href(base, &percent_decode(href));
+		match read_entry(&mut archive, &path) {
+			Ok(chapter) => chapter_sections(&chapter, &mut builder),
+			Err(e) => tracing::warn!("Skipping missing epub chapter {}: {:?}", path, e),
+		}
+	}
+	Ok(builder.finish())
+}
+
+fn read_entry(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> IngestorResult<String> {
+	let mut entry = archive.by_name(name)?;
+	let mut content = String::new();
+	entry.read_to_string(&mut content)?;
+	Ok(content)
+}
+
+/// Path of a manifest entry within the archive, `href` being relative to the directory
+/// of the package document.
+fn resolve_href(base: &str, href: &str) -> String {
+	let mut segments: Vec<&str> = base.split('/').filter(|segment| !segment.is_empty()).collect();
+	for segment in href.split('/') {
+		match segment {
+			"" | "." => {},
+			".." => {
+				segments.pop();
+			},
+			segment => segments.push(segment),
+		}
+	}
+	segments.join("/")
+}
+
+fn percent_decode(href: &str) -> String {
+	let bytes = href.as_bytes();
+	let mut decoded = Vec::with_capacity(bytes.len());
+	let mut i = 0;
+	while i < bytes.len() {
+		let escaped = (bytes[i] == b'%')
+			.then(|| href.get(i + 1..i + 3))
+			.flatten()
+			.and_then(|hex| u8::from_str_radix(hex, 16).ok());
+		match escaped {
+			Some(byte) => {
+				decoded.push(byte);
+				i += 3;
+			},
+			None => {
+				decoded.push(bytes[i]);
+				i += 1;
+			},
+		}
+	}
+	String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn chapter_sections(chapter: &str, builder: &mut SectionBuilder) {
+	let Ok(dom) = tl::parse(chapter, tl::ParserOptions::default()) else {
+		tracing::warn!("Failed to parse epub chapter");
+		return;
+	};
+	let mut line = String::new();
+	for handle in dom.children() {
+		walk(*handle, dom.parser(), builder, &mut line);
+	}
+	flush_line(builder, &mut line);
+}
+
+fn walk(
+	handle: tl::NodeHandle,
+	parser: &tl::Parser,
+	builder: &mut SectionBuilder,
+	line: &mut String,
+) {
+	let Some(node) = handle.get(parser) else { return };
+	match node {
+		tl::Node::Raw(text) => line.push_str(&decode_entities(&text.as_utf8_str())),
+		tl::Node::Tag(tag) => {
+			let name = tag.name().as_utf8_str().to_ascii_lowercase();
+			match name.as_str() {
+				"head" | "script" | "style" | "title" => {},
+				"br" => flush_line(builder, line),
+				"h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+					flush_line(builder, line);
+					let level = name[1..].parse().unwrap_or(1);
+					builder.heading(level, &decode_entities(&tag.inner_text(parser)));
+				},
+				name => {
+					let block = BLOCK_TAGS.contains(&name);
+					if block {
+						flush_line(builder, line);
+					}
+					for child in tag.children().top().iter() {
+						walk(*child, parser, builder, line);
+					}
+					if block {
+						flush_line(builder, line);
+					}
+				},
+			}
+		},
+		tl::Node::Comment(_) => {},
+	}
+}
+
+fn flush_line(builder: &mut SectionBuilder, line: &mut String) {
+	let text = line.split_whitespace().collect::<Vec<_>>().join(" ");
+	if !text.is_empty() {
+		builder.line(&text);
+	}
+	line.clear();
+}
+
+fn decode_entities(text: &str) -> String {
+	let mut decoded = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let entity = rest.find(';').filter(|end| *end <= 10).and_then(|end| {
+			let character = match &rest[1..end] {
+				"amp" => Some('&'),
+				"lt" => Some('<'),
+				"gt" => Some('>'),
+				"quot" => Some('"'),
+				"apos" => Some('\''),
+				"nbsp" => Some(' '),
+				name if name.starts_with("#x") || name.starts_with("#X") =>
+					u32::from_str_radix(&name[2..], 16).ok().and_then(char::from_u32),
+				name if name.starts_with('#') => name[1..].parse().ok().and_then(char::from_u32),
+				_ => None,
+			};
+			character.map(|character| (character, end))
+		});
+		match entity {
+			Some((character, end)) => {
+				decoded.push(character);
+				rest = &rest[end + 1..];
+			},
+			None => {
+				decoded.push('&');
+				rest = &rest[1..];
+			},
+		}
+	}
+	decoded.push_str(rest);
+	decoded
+}
+
+// Define the EpubIngestor
+pub struct EpubIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl EpubIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for EpubIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let sections = match epub_sections(buffer) {
+				Ok(sections) => sections,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to read epub {}", file)));
+					return;
+				},
+			};
+			for tokens in section_tokens(sections, &file, &doc_source, &source_id) {
+				yield Ok(tokens);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{io::Write, path::Path};
+	use zip::{write::SimpleFileOptions, ZipWriter};
+
+	fn book() -> Vec<u8> {
+		let entries = [
+			(
+				"META-INF/container.xml",
+				r#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+<rootfiles><rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/></rootfiles>
+</container>"#,
+			),
+			(
+				"OEBPS/content.opf",
+				r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0">
+<manifest>
+<item id="ch2" href="text/chapter%202.xhtml" media-type="application/xhtml+xml"/>
+<item id="ch1" href="text/chapter1.xhtml" media-type="application/xhtml+xml"/>
+<item id="css" href="style.css" media-type="text/css"/>
+</manifest>
+<spine><itemref idref="ch1"/><itemref idref="css"/><itemref idref="ch2"/></spine>
+</package>"#,
+			),
+			(
+				"OEBPS/text/chapter1.xhtml",
+				r#"<html><head><title>One</title><style>p { color: red }</style></head><body>
+<h1>Reservoirs</h1>
+<p>Porous rock holds <em>oil</em> &amp; gas.</p>
+<h2>Sandstone</h2>
+<p>Sandstone is common.<br/>It is&nbsp;permeable.</p>
+</body></html>"#,
+			),
+			(
+				"OEBPS/text/chapter 2.xhtml",
+				r#"<html><body><h1>Wells</h1><ul><li>Exploration</li><li>Production</li></ul></body></html>"#,
+			),
+		];
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		for (name, content) in entries {
+			writer.start_file(name, SimpleFileOptions::default()).unwrap();
+			writer.write_all(content.as_bytes()).unwrap();
+		}
+		writer.finish().unwrap().into_inner()
+	}
+
+	#[test]
+	fn test_epub_sections() {
+		let sections = epub_sections(book()).unwrap();
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![
+				Some("Reservoirs".to_string()),
+				Some("Reservoirs > Sandstone".to_string()),
+				Some("Wells".to_string())
+			]
+		);
+		assert_eq!(sections[0].text, "Porous rock holds oil & gas.");
+		assert_eq!(sections[1].text, "Sandstone is common.\nIt is permeable.");
+		assert_eq!(sections[2].text, "Exploration\nProduction");
+	}
+
+	#[tokio::test]
+	async fn test_epub_ingestor() {
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(book()))),
+			file: Some(Path::new("book.epub").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("epub".to_string()),
+			size: Some(10),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = EpubIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut sections = 0;
+		while let Some(tokens) = stream.next().await {
+			if !tokens.unwrap().data.is_empty() {
+				sections += 1;
+			}
+		}
+		assert_eq!(sections, 3);
+	}
+}
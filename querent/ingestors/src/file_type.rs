@@ -0,0 +1,289 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use common::CollectedBytes;
+use std::io::Cursor;
+use tokio::io::AsyncReadExt;
+
+/// Number of bytes read from the start of a document to detect its type.
+pub const SNIFF_LEN: usize = 8 * 1024;
+
+const ZIP_ENTRY: &[u8] = b"PK\x03\x04";
+const HEADER_NAMES: [&str; 9] = [
+	"received",
+	"return-path",
+	"delivered-to",
+	"from",
+	"to",
+	"subject",
+	"date",
+	"message-id",
+	"mime-version",
+];
+
+/// Reads the first [`SNIFF_LEN`] bytes of a document and puts them back in front of its
+/// data, so that the type can be detected without consuming the document.
+pub async fn peek_head(collected_bytes: &mut CollectedBytes) -> std::io::Result<Vec<u8>> {
+	let Some(mut data) = collected_bytes.data.take() else {
+		return Ok(Vec::new());
+	};
+	let mut head = Vec::with_capacity(SNIFF_LEN);
+	let read = (&mut data).take(SNIFF_LEN as u64).read_to_end(&mut head).await;
+	collected_bytes.data = Some(Box::pin(Cursor::new(head.clone()).chain(data)));
+	read.map(|_| head)
+}
+
+/// File type of a document from the signature at the start of its bytes, for formats
+/// that have one. Compound files (doc, msg) carry nothing telling in their first bytes
+/// and are left to the content type and extension.
+pub fn sniff_file_type(head: &[u8]) -> Option<&'static str> {
+	let file_type = match head {
+		[b'%', b'P', b'D', b'F', b'-', ..] => "pdf",
+		[0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n', ..] => "png",
+		[0xff, 0xd8, 0xff, ..] => "jpg",
+		[b'G', b'I', b'F', b'8', b'7' | b'9', b'a', ..] => "gif",
+		[b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => "webp",
+		[b'I', b'I', b'*', 0, ..] | [b'M', b'M', 0, b'*', ..] => "tiff",
+		[b'B', b'M', _, _, _, _, 0, 0, 0, 0, _, _, _, _, ..] => "bmp",
+		[0, 0, 1, 0, ..] => "ico",
+		[b'q', b'o', b'i', b'f', ..] => "qoi",
+		[b'D', b'D', b'S', b' ', ..] => "dds",
+		[0x76, 0x2f, 0x31, 0x01, ..] => "exr",
+		[b'#', b'?', b'R', b'A', b'D', b'I', b'A', b'N', b'C', b'E', ..] => "hdr",
+		[b'{', b'\\', b'r', b't', b'f', ..] => "rtf",
+		[b'P', b'K', 3, 4, ..] => return sniff_zip_type(head),
+		_ => return None,
+	};
+	Some(file_type)
+}
+
+/// Tells the zip based formats apart from the names of the entries found in the first
+/// bytes, or from the `mimetype` entry that EPUB and OpenDocument files start with.
+fn sniff_zip_type(head: &[u8]) -> Option<&'static str> {
+	let mut offset = 0;
+	while let Some(position) = find(&head[offset..], ZIP_ENTRY) {
+		let entry = &head[offset + position..];
+		offset += position + ZIP_ENTRY.len();
+		if entry.len() < 30 {
+			break;
+		}
+		let compressed_size = u32::from_le_bytes([entry[18], entry[19], entry[20], entry[21]]);
+		let name_len = u16::from_le_bytes([entry[26], entry[27]]) as usize;
+		let extra_len = u16::from_le_bytes([entry[28], entry[29]]) as usize;
+		let Some(name) = entry.get(30..30 + name_len) else {
+			break;
+		};
+		let file_type = match name {
+			b"mimetype" => {
+				let start = 30 + name_len + extra_len;
+				let content =
+					entry.get(start..start + compressed_size as usize).unwrap_or_default();
+				match content {
+					b"application/epub+zip" => Some("epub"),
+					b"application/vnd.oasis.opendocument.text" => Some("odt"),
+					b"application/vnd.oasis.opendocument.spreadsheet" => Some("ods"),
+					b"application/vnd.oasis.opendocument.presentation" => Some("odp"),
+					_ => None,
+				}
+			},
+			b"META-INF/container.xml" => Some("epub"),
+			_ if name.starts_with(b"word/") => Some("docx"),
+			_ if name.starts_with(b"xl/") => Some("xlsx"),
+			_ if name.starts_with(b"ppt/") => Some("pptx"),
+			_ => None,
+		};
+		if file_type.is_some() {
+			return file_type;
+		}
+	}
+	None
+}
+
+/// File type of a document made of text, from the way it starts: markup, JSON, email
+/// headers or else plain text. `None` when the bytes are not text.
+pub fn sniff_text_type(head: &[u8]) -> Option<&'static str> {
+	let head = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+	if head.is_empty() || head.contains(&0) {
+		return None;
+	}
+	let text = match std::str::from_utf8(head) {
+		Ok(text) => text,
+		// the head may end in the middle of a character
+		Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok()?,
+		Err(_) => return None,
+	};
+	let text = text.trim_start();
+	let start = text.chars().take(512).collect::<String>().to_lowercase();
+	if start.starts_with("<!doctype html") || start.starts_with("<html") {
+		return Some("html");
+	}
+	if start.starts_with('<') {
+		return Some(if start.contains("<html") { "html" } else { "xml" });
+	}
+	if start.starts_with('{') || start.starts_with('[') {
+		let next = start[1..].trim_start().chars().next();
+		if matches!(next, None | Some('"' | '{' | '[' | '}' | ']')) {
+			return Some("json");
+		}
+	}
+	let mut lines = start.lines();
+	let first_line = lines.next().unwrap_or_default();
+	let is_header = |line: &str| {
+		line.split_once(':')
+			.is_some_and(|(name, _)| HEADER_NAMES.contains(&name.trim_end()))
+	};
+	if first_line.starts_with("from ") && lines.clone().any(is_header) {
+		return Some("mbox");
+	}
+	if is_header(first_line) && lines.take_while(|line| !line.is_empty()).any(is_header) {
+		return Some("eml");
+	}
+	Some("txt")
+}
+
+/// Extension of the ingestor handling a MIME type, parameters and case aside. Generic
+/// types such as `application/octet-stream` have none.
+pub fn extension_for_content_type(content_type: &str) -> Option<&'static str> {
+	let mime_type = content_type.split(';').next().unwrap_or_default().trim().to_lowercase();
+	let extension = match mime_type.as_str() {
+		"message/rfc822" => "eml",
+		"text/plain" => "txt",
+		"text/html" | "application/xhtml+xml" => "html",
+		"text/csv" => "csv",
+		"text/markdown" => "md",
+		"text/xml" | "application/xml" => "xml",
+		"text/rtf" | "application/rtf" => "rtf",
+		"application/pdf" => "pdf",
+		"application/json" => "json",
+		"application/msword" => "doc",
+		"application/vnd.openxmlformats-officedocument.wordprocessingml.document" => "docx",
+		"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => "xlsx",
+		"application/vnd.openxmlformats-officedocument.presentationml.presentation" => "pptx",
+		"application/vnd.oasis.opendocument.text" => "odt",
+		"application/vnd.oasis.opendocument.spreadsheet" => "ods",
+		"application/vnd.oasis.opendocument.presentation" => "odp",
+		"application/epub+zip" => "epub",
+		"application/x-ipynb+json" => "ipynb",
+		"application/x-tex" | "text/x-tex" => "tex",
+		"application/vnd.ms-outlook" => "msg",
+		"application/mbox" => "mbox",
+		"image/jpeg" => "jpeg",
+		"image/png" => "png",
+		"image/gif" => "gif",
+		"image/bmp" => "bmp",
+		"image/tiff" => "tiff",
+		"image/webp" => "webp",
+		_ => return None,
+	};
+	Some(extension)
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+	haystack.windows(needle.len()).position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::io::Write;
+	use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+	fn zip(entries: &[(&str, &[u8])]) -> Vec<u8> {
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		let options = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+		for (name, data) in entries {
+			writer.start_file(*name, options).unwrap();
+			writer.write_all(data).unwrap();
+		}
+		writer.finish().unwrap().into_inner()
+	}
+
+	#[test]
+	fn sniffs_signatures() {
+		assert_eq!(sniff_file_type(b"%PDF-1.7\n%\xe2\xe3"), Some("pdf"));
+		assert_eq!(sniff_file_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("png"));
+		assert_eq!(sniff_file_type(b"{\\rtf1\\ansi"), Some("rtf"));
+		assert_eq!(sniff_file_type(b"hello"), None);
+		assert_eq!(sniff_file_type(b"\xd0\xcf\x11\xe0\xa1\xb1\x1a\xe1"), None);
+
+		let docx = zip(&[("[Content_Types].xml", b"<Types/>"), ("word/document.xml", b"<w/>")]);
+		assert_eq!(sniff_file_type(&docx), Some("docx"));
+		let odt = zip(&[("mimetype", b"application/vnd.oasis.opendocument.text")]);
+		assert_eq!(sniff_file_type(&odt), Some("odt"));
+		let archive = zip(&[("notes.txt", b"hello")]);
+		assert_eq!(sniff_file_type(&archive), None);
+	}
+
+	#[test]
+	fn sniffs_text() {
+		assert_eq!(sniff_text_type(b"\xef\xbb\xbf<!DOCTYPE html><html>"), Some("html"));
+		assert_eq!(sniff_text_type(b"<?xml version=\"1.0\"?><well/>"), Some("xml"));
+		assert_eq!(sniff_text_type(b"  {\"well\": \"A-7\"}"), Some("json"));
+		assert_eq!(sniff_text_type(b"[1] Drilling report"), Some("txt"));
+		assert_eq!(
+			sniff_text_type(b"From: ops@example.com\r\nSubject: Spud\r\n\r\nStarted."),
+			Some("eml")
+		);
+		assert_eq!(
+			sniff_text_type(
+				b"From ops@example.com Mon Jan  1 00:00:00 2024\nFrom: ops@example.com\n"
+			),
+			Some("mbox")
+		);
+		assert_eq!(sniff_text_type(b"Porosity: 12%\nDepth: 3200"), Some("txt"));
+		assert_eq!(sniff_text_type(b"caf\xc3"), Some("txt"));
+		assert_eq!(sniff_text_type(b"\x00\x01\x02"), None);
+	}
+
+	#[test]
+	fn maps_content_types() {
+		assert_eq!(extension_for_content_type("application/pdf"), Some("pdf"));
+		assert_eq!(extension_for_content_type("Text/HTML; charset=utf-8"), Some("html"));
+		assert_eq!(extension_for_content_type("application/octet-stream"), None);
+	}
+
+	#[tokio::test]
+	async fn resolves_from_content_before_extension() {
+		let resolved = |head: &'static [u8], content_type: Option<&'static str>, ext| async move {
+			crate::resolve_ingestor(head, content_type, ext).await.unwrap().0
+		};
+		assert_eq!(resolved(b"%PDF-1.5", None, "dat").await, "pdf");
+		assert_eq!(resolved(b"\x01\x02", Some("application/pdf"), "").await, "pdf");
+		assert_eq!(resolved(b"# Title", Some("text/plain"), "MD").await, "md");
+		assert_eq!(resolved(b"<html><body>", None, "").await, "html");
+		assert_eq!(resolved(b"\x01\x02", Some("application/octet-stream"), "bin").await, "bin");
+	}
+
+	#[tokio::test]
+	async fn peeking_keeps_the_data() {
+		let data = b"%PDF-1.4 rest of the document".to_vec();
+		let mut collected_bytes = CollectedBytes::new(
+			None,
+			Some(Box::pin(Cursor::new(data.clone()))),
+			true,
+			None,
+			Some(data.len()),
+			"source".to_string(),
+			None,
+		);
+		let head = peek_head(&mut collected_bytes).await.unwrap();
+		assert_eq!(head, data);
+		let mut read = Vec::new();
+		collected_bytes.unwrap().read_to_end(&mut read).await.unwrap();
+		assert_eq!(read, data);
+	}
+}
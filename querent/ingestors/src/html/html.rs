@@ -26,8 +26,8 @@ use tokio::io::AsyncReadExt;
 
 use crate::{
 	html::parser::HtmlParser, process_ingested_tokens_stream,
-	processors::text_processing::TextCleanupProcessor, AsyncProcessor, BaseIngestor, IngestorError,
-	IngestorErrorKind, IngestorResult,
+	processors::text_processing::TextCleanupProcessor, sections::section_tokens, AsyncProcessor,
+	BaseIngestor, IngestorError, IngestorErrorKind, IngestorResult,
 };
 
 // Define the HtmlIngestor
@@ -83,30 +83,10 @@ impl BaseIngestor for HtmlIngestor {
 			cursor.read_to_string(&mut content).await
 				.map_err(|err| IngestorError::new(IngestorErrorKind::Io, Arc::new(err.into())))?;
 			let mut parser = HtmlParser::new();
-			parser.parse(&content.clone());
-			for token in parser.get_body_elements() {
-				if token == "" {
-					continue;
-				} else {
-					let ingested_tokens = IngestedTokens {
-						data: vec![token.to_string()],
-						file: file.clone(),
-						doc_source: doc_source.clone(),
-						is_token_stream: false,
-						source_id: source_id.clone(),
-						image_id: None,
-					};
-					yield Ok(ingested_tokens);
-				}
+			parser.parse(&content);
+			for tokens in section_tokens(parser.sections(), &file, &doc_source, &source_id) {
+				yield Ok(tokens);
 			}
-			yield Ok(IngestedTokens {
-				data: vec![],
-				file: file.clone(),
-				doc_source: doc_source.clone(),
-				is_token_stream: false,
-				source_id: source_id.clone(),
-				image_id: None,
-			})
 		};
 
 		let processed_stream =
@@ -136,6 +116,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -173,6 +154,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -15,16 +15,18 @@
 // and non-infringement. See the Business Source License for more details.
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use crate::sections::{Section, SectionBuilder};
 
-use std::collections::HashSet;
-
+/// Reads the text of the body of an HTML page, in sections under its `h1` to `h6` headings.
 pub struct HtmlParser {
-	body_elements: HashSet<String>,
+	sections: SectionBuilder,
+	// level and text of the heading being read
+	heading: Option<(usize, String)>,
 }
 
 impl HtmlParser {
 	pub fn new() -> Self {
-		HtmlParser { body_elements: HashSet::new() }
+		HtmlParser { sections: SectionBuilder::new(), heading: None }
 	}
 
 	pub fn parse(&mut self, html: &str) {
@@ -37,7 +39,7 @@ impl HtmlParser {
 			if c == '<' {
 				if !current_element.is_empty() {
 					if in_body {
-						self.body_elements.insert(current_element.clone());
+						self.text(&current_element);
 					}
 					current_element.clear();
 				}
@@ -53,10 +55,18 @@ impl HtmlParser {
 					}
 				}
 
-				if current_element.trim().to_lowercase() == "</body>" {
+				let tag = current_element.trim().to_lowercase();
+				if tag == "</body>" {
 					in_body = false;
-				} else if current_element.trim().to_lowercase() == "<body>" {
+				} else if tag == "<body>" || tag.starts_with("<body ") {
+					// Real pages often carry attributes on the body tag.
 					in_body = true;
+				} else if let Some(level) = heading_level(&tag, "<h") {
+					self.heading = Some((level, String::new()));
+				} else if heading_level(&tag, "</h").is_some() {
+					if let Some((level, title)) = self.heading.take() {
+						self.sections.heading(level, &title);
+					}
 				}
 
 				current_element.clear();
@@ -68,7 +78,56 @@ impl HtmlParser {
 		}
 	}
 
-	pub fn get_body_elements(&self) -> &HashSet<String> {
-		&self.body_elements
+	/// The sections read so far, in the order of the page.
+	pub fn sections(self) -> Vec<Section> {
+		self.sections.finish()
+	}
+
+	fn text(&mut self, text: &str) {
+		let text = text.trim();
+		if text.is_empty() {
+			return;
+		}
+		match self.heading.as_mut() {
+			Some((_, title)) => {
+				title.push(' ');
+				title.push_str(text);
+			},
+			None => self.sections.line(text),
+		}
+	}
+}
+
+/// Level of a heading tag such as `<h2 class="title">`, opening or closing depending on `prefix`.
+fn heading_level(tag: &str, prefix: &str) -> Option<usize> {
+	let mut rest = tag.strip_prefix(prefix)?.chars();
+	let level = rest.next()?.to_digit(10).filter(|level| (1..=6).contains(level))?;
+	matches!(rest.next(), Some('>') | Some(' ')).then_some(level as usize)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_sections_under_headings() {
+		let mut parser = HtmlParser::new();
+		parser.parse(
+			"<html><head><title>Report</title></head><body><p>Summary</p>\
+			<h1 class=\"top\">Wells</h1><h2>Drilling</h2><p>The rig <b>moved</b>.</p>\
+			<h2>Production</h2><ul><li>300 barrels</li></ul></body></html>",
+		);
+		let sections = parser.sections();
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![
+				None,
+				Some("Wells > Drilling".to_string()),
+				Some("Wells > Production".to_string())
+			]
+		);
+		assert_eq!(sections[1].text, "The rig\nmoved\n.");
+		assert_eq!(heading_level("<hr>", "<h"), None);
 	}
 }
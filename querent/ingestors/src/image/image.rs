@@ -26,22 +26,34 @@ use std::{pin::Pin, sync::Arc};
 use tokio::{io::AsyncReadExt, sync::Semaphore};
 
 use crate::{
-	process_ingested_tokens_stream, processors::text_processing::TextCleanupProcessor,
+	ocr::{default_recognizer, TextRecognizer},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
 	AsyncProcessor, BaseIngestor, IngestorResult,
 };
 
 static REQUEST_SEMAPHORE: Lazy<Semaphore> = Lazy::new(|| Semaphore::new(10));
-static SUPPORTED_EXTENSIONS: Lazy<Vec<&'static str>> = Lazy::new(|| {
+pub(crate) static SUPPORTED_EXTENSIONS: Lazy<Vec<&'static str>> = Lazy::new(|| {
 	vec!["jpeg", "jpg", "png", "bmp", "gif", "pnm", "tiff", "webp", "dds", "ico", "ff"]
 });
 // Define the ImageIngestor
 pub struct ImageIngestor {
 	processors: Vec<Arc<dyn AsyncProcessor>>,
+	recognizer: Arc<dyn TextRecognizer>,
 }
 
 impl ImageIngestor {
 	pub fn new() -> Self {
-		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+		Self {
+			processors: vec![Arc::new(TextCleanupProcessor::new())],
+			recognizer: default_recognizer(),
+		}
+	}
+
+	/// Reads the text of images with `recognizer` rather than tesseract.
+	pub fn with_recognizer(mut self, recognizer: Arc<dyn TextRecognizer>) -> Self {
+		self.recognizer = recognizer;
+		self
 	}
 }
 
@@ -56,6 +68,7 @@ impl BaseIngestor for ImageIngestor {
 		all_collected_bytes: Vec<CollectedBytes>,
 	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
 	{
+		let recognizer = self.recognizer.clone();
 		let stream = stream! {
 			let mut buffer = Vec::new();
 			let mut file = String::new();
@@ -63,9 +76,11 @@ impl BaseIngestor for ImageIngestor {
 			let mut source_id = String::new();
 			let mut image_id: Option<String> = None;
 			let mut extension = String::new();
-			let tesseract_path = rusty_tesseract::tesseract::find_tesseract_path();
-			if tesseract_path.is_none() {
-				tracing::warn!("Tesseract not found in path: Skip image processing");
+			if !recognizer.is_available() {
+				tracing::warn!(
+					"OCR backend {} is not available: Skip image processing",
+					recognizer.name()
+				);
 				return yield Ok(IngestedTokens {
 					data: vec![],
 					file: file.clone(),
@@ -73,6 +88,9 @@ impl BaseIngestor for ImageIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
 			for collected_bytes in all_collected_bytes {
@@ -109,6 +127,9 @@ impl BaseIngestor for ImageIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
 			// Acquire semaphore for image processing
@@ -122,6 +143,9 @@ impl BaseIngestor for ImageIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
 			let _permit = permit_res.unwrap();
@@ -136,6 +160,9 @@ impl BaseIngestor for ImageIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
 			let img = img.unwrap();
@@ -148,36 +175,29 @@ impl BaseIngestor for ImageIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
-			let img = rusty_tesseract::Image::from_dynamic_image(&img);
-			if img.is_err() {
-				tracing::error!("Failed to convert image to tesseract image: {:?}", img.err());
-				return yield Ok(IngestedTokens {
-					data: vec![],
-					file: file.clone(),
-					doc_source: doc_source.clone(),
-					is_token_stream: false,
-					source_id: source_id.clone(),
-					image_id,
-				});
-			}
-
-			let img = img.unwrap();
-			let default_args = rusty_tesseract::Args::default();
-			let output = rusty_tesseract::image_to_string(&img, &default_args);
-			if output.is_err() {
-				tracing::error!("Failed to get text from image: {:?}", output.err());
-				return yield Ok(IngestedTokens {
-					data: vec![],
-					file: file.clone(),
-					doc_source: doc_source.clone(),
-					is_token_stream: false,
-					source_id: source_id.clone(),
-					image_id,
-				});
-			}
-			let output = output.unwrap();
+			let recognized = tokio::task::spawn_blocking(move || recognizer.recognize(&img)).await;
+			let output = match recognized {
+				Ok(Ok(output)) => output,
+				failed => {
+					tracing::error!("Failed to get text from image: {:?}", failed);
+					return yield Ok(IngestedTokens {
+						data: vec![],
+						file: file.clone(),
+						doc_source: doc_source.clone(),
+						is_token_stream: false,
+						source_id: source_id.clone(),
+						image_id,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
+					});
+				},
+			};
 			let ingested_tokens = IngestedTokens {
 				data: vec![output.to_string()],
 				file: file.clone(),
@@ -185,6 +205,9 @@ impl BaseIngestor for ImageIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 
 			yield Ok(ingested_tokens);
@@ -196,6 +219,9 @@ impl BaseIngestor for ImageIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			})
 		};
 
@@ -243,6 +269,7 @@ mod tests {
 				size: Some(10),
 				source_id: "FileSystem1".to_string(),
 				_owned_permit: None,
+				content_type: None,
 				image_id: None,
 			};
 			let ingestor = ImageIngestor::new();
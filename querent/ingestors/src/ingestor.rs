@@ -22,17 +22,38 @@ use common::CollectedBytes;
 use futures::{pin_mut, stream, Stream, StreamExt};
 use proto::semantics::IngestedTokens;
 use serde::{Deserialize, Serialize};
-use std::{fmt, io, pin::Pin, sync::Arc};
+use std::{collections::HashMap, fmt, io, pin::Pin, sync::Arc};
 use thiserror::Error;
+use tokio::io::AsyncReadExt;
 
 use crate::{
-	code::code::CodeIngestor, csv::csv::CsvIngestor, doc::doc::DocIngestor,
-	docx::docx::DocxIngestor, html::html::HtmlIngestor, image::image::ImageIngestor,
-	json::json::JsonIngestor, odp::odp::OdpIngestor, osdu::OSDURecordIngestor,
-	pdf::pdfv1::PdfIngestor, pptx::pptx::PptxIngestor, txt::txt::TxtIngestor,
-	xlsx::xlsx::XlsxIngestor, xml::xml::XmlIngestor,
+	code::code::CodeIngestor,
+	csv::csv::CsvIngestor,
+	doc::doc::DocIngestor,
+	docx::docx::DocxIngestor,
+	email::{eml::EmlIngestor, mbox::MboxIngestor, msg::MsgIngestor},
+	epub::epub::EpubIngestor,
+	file_type::{extension_for_content_type, sniff_file_type, sniff_text_type},
+	html::html::HtmlIngestor,
+	image::image::{ImageIngestor, SUPPORTED_EXTENSIONS},
+	json::json::JsonIngestor,
+	markdown::markdown::MarkdownIngestor,
+	notebook::notebook::NotebookIngestor,
+	ocr::TextRecognizer,
+	odp::odp::OdpIngestor,
+	ods::ods::OdsIngestor,
+	odt::odt::OdtIngestor,
+	osdu::OSDURecordIngestor,
+	pdf::pdfv1::PdfIngestor,
+	pptx::pptx::PptxIngestor,
+	processors::pii::RedactionAudit,
+	rtf::rtf::RtfIngestor,
+	tex::tex::TexIngestor,
+	txt::txt::TxtIngestor,
+	xlsx::xlsx::XlsxIngestor,
+	xml::xml::XmlIngestor,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 /// Ingestor error kind.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
@@ -55,6 +76,10 @@ pub enum IngestorErrorKind {
 	ZipError,
 	/// Xml error
 	Xml,
+	/// Email (MIME, mbox or msg) error
+	Email,
+	/// Invalid configuration, such as an unknown processor or a bad parameter
+	InvalidConfig,
 }
 
 /// Generic IngestorError.
@@ -126,6 +151,12 @@ impl From<xml::reader::Error> for IngestorError {
 	}
 }
 
+impl From<mailparse::MailParseError> for IngestorError {
+	fn from(error: mailparse::MailParseError) -> Self {
+		IngestorError::new(IngestorErrorKind::Email, Arc::new(error.into()))
+	}
+}
+
 // Define the trait for async processor
 #[async_trait]
 pub trait AsyncProcessor: Send + Sync {
@@ -141,6 +172,11 @@ pub trait BaseIngestor: Send + Sync {
 		&self,
 		all_collected_bytes: Vec<CollectedBytes>,
 	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>;
+
+	/// Whether the ingestor extracts anything from its documents.
+	fn is_supported(&self) -> bool {
+		true
+	}
 }
 
 pub struct UnsupportedIngestor {
@@ -171,6 +207,37 @@ impl BaseIngestor for UnsupportedIngestor {
 		info!("The following extension is unsupported at the moment: {:?}", extension.clone());
 		Ok(Box::pin(stream::empty()))
 	}
+
+	fn is_supported(&self) -> bool {
+		false
+	}
+}
+
+/// Reads every chunk of a document into one buffer, along with the file name,
+/// document source and source id of the first chunk.
+pub(crate) async fn read_collected_bytes(
+	all_collected_bytes: Vec<CollectedBytes>,
+) -> IngestorResult<(Vec<u8>, String, String, String)> {
+	let mut buffer = Vec::new();
+	let mut file = String::new();
+	let mut doc_source = String::new();
+	let mut source_id = String::new();
+	for collected_bytes in all_collected_bytes {
+		if collected_bytes.data.is_none() || collected_bytes.file.is_none() {
+			continue;
+		}
+		if file.is_empty() {
+			file = collected_bytes.file.as_ref().unwrap().to_string_lossy().to_string();
+		}
+		if doc_source.is_empty() {
+			doc_source = collected_bytes.doc_source.clone().unwrap_or_default();
+		}
+		if let Some(mut data) = collected_bytes.data {
+			data.read_to_end(&mut buffer).await?;
+		}
+		source_id = collected_bytes.source_id.clone();
+	}
+	Ok((buffer, file, doc_source, source_id))
 }
 
 // apply processors to stream of IngestedTokens and return a stream of IngestedTokens
@@ -206,15 +273,100 @@ pub async fn process_ingested_tokens_stream(
 	Box::pin(stream)
 }
 
+/// Picks the ingestor of a document from its first bytes, then from the content type its
+/// source reported and last from its extension. Returns the detected file type with it.
+///
+/// A plain text content type does not override a known extension, as sources report it for
+/// markdown, CSV or code as well. Text without a known extension is sniffed for markup,
+/// JSON and emails.
+pub async fn resolve_ingestor(
+	head: &[u8],
+	content_type: Option<&str>,
+	extension: &str,
+) -> IngestorResult<(String, Arc<dyn BaseIngestor>)> {
+	let extension = extension.to_lowercase();
+	let detected = sniff_file_type(head).or_else(|| {
+		content_type
+			.and_then(extension_for_content_type)
+			.filter(|file_type| *file_type != "txt")
+	});
+	let file_type = match detected {
+		Some(file_type) => file_type.to_string(),
+		None => {
+			let ingestor = resolve_ingestor_with_extension(&extension).await?;
+			if !extension.is_empty() && ingestor.is_supported() {
+				return Ok((extension, ingestor));
+			}
+			match sniff_text_type(head) {
+				Some(file_type) => file_type.to_string(),
+				None => extension,
+			}
+		},
+	};
+	let ingestor = resolve_ingestor_with_extension(&file_type).await?;
+	Ok((file_type, ingestor))
+}
+
+/// Settings of a pipeline that ingestors resolved by file type do not carry.
+#[derive(Clone, Default)]
+pub struct IngestorOptions {
+	/// Passwords of encrypted PDFs, by collector id.
+	pub pdf_passwords: HashMap<String, Vec<String>>,
+	/// Recognizer reading images and scanned pages, tesseract when unset.
+	pub recognizer: Option<Arc<dyn TextRecognizer>>,
+	/// Processor chain replacing the default processors of every ingestor.
+	pub processors: Option<Vec<Arc<dyn AsyncProcessor>>>,
+	/// Counts of the personal data the processors redacted from each document.
+	pub redaction_audit: RedactionAudit,
+}
+
+impl IngestorOptions {
+	/// Applies these options to `ingestor`, the ingestor resolved for `file_type`.
+	pub fn configure(
+		&self,
+		file_type: &str,
+		source_id: &str,
+		ingestor: Arc<dyn BaseIngestor>,
+	) -> Arc<dyn BaseIngestor> {
+		let mut ingestor = self.ingestor_for(file_type, source_id).unwrap_or(ingestor);
+		if let Some(processors) = &self.processors {
+			match Arc::get_mut(&mut ingestor) {
+				Some(ingestor) => ingestor.set_processors(processors.clone()),
+				None => warn!("Ingestor of {} is shared: Keep its default processors", file_type),
+			}
+		}
+		ingestor
+	}
+
+	/// Ingestor of `file_type` configured with these options, for the file types they concern.
+	fn ingestor_for(&self, file_type: &str, source_id: &str) -> Option<Arc<dyn BaseIngestor>> {
+		if file_type == "pdf" {
+			let mut ingestor = PdfIngestor::new()
+				.with_passwords(self.pdf_passwords.get(source_id).cloned().unwrap_or_default());
+			if let Some(recognizer) = &self.recognizer {
+				ingestor = ingestor.with_recognizer(recognizer.clone());
+			}
+			return Some(Arc::new(ingestor));
+		}
+		match &self.recognizer {
+			Some(recognizer) if SUPPORTED_EXTENSIONS.contains(&file_type) =>
+				Some(Arc::new(ImageIngestor::new().with_recognizer(recognizer.clone()))),
+			_ => None,
+		}
+	}
+}
+
+/// Extensions of the source code read by the code ingestor.
+pub const PROGRAMMING_LANGUAGE_EXTENSIONS: &[&str] = &[
+	"py", "pyw", "pyp", "js", "mjs", "java", "cpp", "h", "hpp", "c", "h", "cs", "rb", "swift",
+	"php", "php3", "php4", "php5", "phtml", "html", "htm", "css", "go", "rs", "kt", "ts", "pl",
+	"sql", "r", "m", "sh", "bash", "zsh", "dart", "scala", "groovy", "lua", "m", "vb",
+];
+
 pub async fn resolve_ingestor_with_extension(
 	extension: &str,
 ) -> IngestorResult<Arc<dyn BaseIngestor>> {
-	let programming_languages = vec![
-		"py", "pyw", "pyp", "js", "mjs", "java", "cpp", "h", "hpp", "c", "h", "cs", "rb", "swift",
-		"php", "php3", "php4", "php5", "phtml", "html", "htm", "css", "go", "rs", "kt", "ts", "pl",
-		"sql", "r", "m", "sh", "bash", "zsh", "dart", "scala", "groovy", "lua", "m", "vb",
-	];
-	if programming_languages.contains(&extension) {
+	if PROGRAMMING_LANGUAGE_EXTENSIONS.contains(&extension) {
 		return Ok(Arc::new(CodeIngestor::new()));
 	}
 	if extension.contains("osdu_") {
@@ -222,7 +374,7 @@ pub async fn resolve_ingestor_with_extension(
 	}
 	match extension {
 		"pdf" => Ok(Arc::new(PdfIngestor::new())),
-		"html" => Ok(Arc::new(HtmlIngestor::new())),
+		"html" | "confluence" | "webpage" => Ok(Arc::new(HtmlIngestor::new())),
 		"csv" => Ok(Arc::new(CsvIngestor::new())),
 		"xml" => Ok(Arc::new(XmlIngestor::new())),
 		"docx" => Ok(Arc::new(DocxIngestor::new())),
@@ -245,8 +397,18 @@ pub async fn resolve_ingestor_with_extension(
 		"webp" => Ok(Arc::new(ImageIngestor::new())),
 		"ff" => Ok(Arc::new(ImageIngestor::new())),
 		"dds" => Ok(Arc::new(ImageIngestor::new())),
-		"news" | "email" | "notion" | "txt" | "" | "md" | "slack" | "jira" | "text" =>
-			Ok(Arc::new(TxtIngestor::new())),
+		"eml" => Ok(Arc::new(EmlIngestor::new())),
+		"mbox" => Ok(Arc::new(MboxIngestor::new())),
+		"msg" => Ok(Arc::new(MsgIngestor::new())),
+		"md" | "markdown" => Ok(Arc::new(MarkdownIngestor::new())),
+		"ipynb" => Ok(Arc::new(NotebookIngestor::new())),
+		"epub" => Ok(Arc::new(EpubIngestor::new())),
+		"rtf" => Ok(Arc::new(RtfIngestor::new())),
+		"odt" => Ok(Arc::new(OdtIngestor::new())),
+		"ods" => Ok(Arc::new(OdsIngestor::new())),
+		"tex" | "latex" => Ok(Arc::new(TexIngestor::new())),
+		"news" | "email" | "notion" | "txt" | "" | "slack" | "jira" | "text" | "sharepoint" |
+		"sqlrow" | "message" => Ok(Arc::new(TxtIngestor::new())),
 		_ => Ok(Arc::new(UnsupportedIngestor::new())),
 	}
 }
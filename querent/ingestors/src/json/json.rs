@@ -87,6 +87,9 @@ impl BaseIngestor for JsonIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				})
 			}
 			let json: serde_json::Value;
@@ -103,6 +106,9 @@ impl BaseIngestor for JsonIngestor {
 						is_token_stream: false,
 						source_id: source_id.clone(),
 						image_id: None,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
 					});
 					return;
 				}
@@ -115,6 +121,9 @@ impl BaseIngestor for JsonIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 			yield Ok(ingested_tokens);
 
@@ -126,6 +135,9 @@ impl BaseIngestor for JsonIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			})
 		};
 
@@ -156,6 +168,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -193,6 +206,7 @@ mod tests {
 			size: Some(10),
 			source_id: "json_osdu_record".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -230,6 +244,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -23,14 +23,27 @@ pub mod code;
 pub mod csv;
 pub mod doc;
 pub mod docx;
+pub mod email;
+pub mod epub;
+pub mod file_type;
 pub mod html;
 pub mod image;
 pub mod json;
+pub mod markdown;
+pub mod notebook;
+pub mod ocr;
+pub mod odf;
 pub mod odp;
+pub mod ods;
+pub mod odt;
 pub mod osdu;
 pub mod pdf;
 pub mod pptx;
 pub mod processors;
+pub mod rtf;
+pub mod sections;
+pub mod tables;
+pub mod tex;
 pub mod txt;
 pub mod xlsx;
 pub mod xml;
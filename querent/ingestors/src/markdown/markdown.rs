@@ -0,0 +1,239 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use once_cell::sync::Lazy;
+use proto::semantics::IngestedTokens;
+use regex::Regex;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes,
+	sections::{section_tokens, Section, SectionBuilder},
+	AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+static ATX_HEADING: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^ {0,3}(#{1,6})(?:[ \t]+(.*?))?(?:[ \t]+#+)?[ \t]*$").unwrap());
+static SETEXT_UNDERLINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^ {0,3}(=+|-+)[ \t]*$").unwrap());
+static FENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^ {0,3}(`{3,}|~{3,})").unwrap());
+static REFERENCE_DEFINITION: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^ {0,3}\[[^\]]+\]:\s").unwrap());
+static TABLE_DELIMITER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^\s*\|?\s*:?-+:?\s*(\|\s*:?-+:?\s*)*\|?\s*$").unwrap());
+static BLOCK_MARKER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^\s*(?:>\s?)*(?:[-*+]\s+(?:\[[ xX]\]\s+)?|\d+[.)]\s+)?").unwrap());
+static IMAGE: Lazy<Regex> = Lazy::new(|| Regex::new(r"!\[([^\]]*)\]\([^)]*\)").unwrap());
+static LINK: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"\[([^\]]*)\](?:\([^)]*\)|\[[^\]]*\])").unwrap());
+static HTML_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[a-zA-Z][^>]*>").unwrap());
+static EMPHASIS: Lazy<Regex> = Lazy::new(|| Regex::new(r"\*\*|__|~~|`|\*").unwrap());
+
+/// Reduces inline markup to its text: links and images keep their label.
+fn inline_text(text: &str) -> String {
+	let text = IMAGE.replace_all(text, "$1");
+	let text = LINK.replace_all(&text, "$1");
+	let text = HTML_TAG.replace_all(&text, "");
+	EMPHASIS.replace_all(&text, "").trim().to_string()
+}
+
+/// Splits Markdown into sections along its ATX (`#`) and setext (underlined) headings.
+/// Code blocks are kept as text and headings inside them are ignored.
+pub fn markdown_sections(markdown: &str) -> Vec<Section> {
+	let mut builder = SectionBuilder::new();
+	let mut lines = markdown.lines().peekable();
+	// YAML front matter
+	if markdown.starts_with("---") {
+		lines.next();
+		for line in lines.by_ref() {
+			if line.trim_end() == "---" || line.trim_end() == "..." {
+				break;
+			}
+		}
+	}
+
+	let mut fence: Option<String> = None;
+	while let Some(line) = lines.next() {
+		if let Some(marker) = &fence {
+			if line.trim_start().starts_with(marker.as_str()) {
+				fence = None;
+			} else {
+				builder.line(line);
+			}
+			continue;
+		}
+		if let Some(captures) = FENCE.captures(line) {
+			fence = Some(captures[1].to_string());
+			continue;
+		}
+		if let Some(captures) = ATX_HEADING.captures(line) {
+			let title = captures.get(2).map(|title| title.as_str()).unwrap_or_default();
+			builder.heading(captures[1].len(), &inline_text(title));
+			continue;
+		}
+		if !line.trim().is_empty() && !TABLE_DELIMITER.is_match(line) {
+			let underline = lines.peek().and_then(|next| SETEXT_UNDERLINE.captures(next));
+			if let Some(underline) = underline {
+				let level = if underline[1].starts_with('=') { 1 } else { 2 };
+				builder.heading(level, &inline_text(line));
+				lines.next();
+				continue;
+			}
+		}
+		if REFERENCE_DEFINITION.is_match(line) || TABLE_DELIMITER.is_match(line) {
+			continue;
+		}
+		builder.line(&inline_text(&BLOCK_MARKER.replace(line, "")));
+	}
+	builder.finish()
+}
+
+// Define the MarkdownIngestor
+pub struct MarkdownIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl MarkdownIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for MarkdownIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let sections = markdown_sections(&String::from_utf8_lossy(&buffer));
+			for tokens in section_tokens(sections, &file, &doc_source, &source_id) {
+				yield Ok(tokens);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{io::Cursor, path::Path};
+
+	const README: &str = "---
+title: Field guide
+---
+Intro with a [link](https://querent.xyz) and ![a map](map.png).
+
+# Wells #
+
+## Drilling
+
+- Rig **A** moved on site
+- Spud date: `2024-05-01`
+
+```bash
+# not a heading
+./start.sh
+```
+
+Completion
+----------
+
+| Stage | Days |
+|-------|-----:|
+| Frac  | 12   |
+
+Production
+==========
+
+> Output rose in June.
+";
+
+	#[test]
+	fn test_markdown_sections() {
+		let sections = markdown_sections(README);
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![
+				None,
+				Some("Wells > Drilling".to_string()),
+				Some("Wells > Completion".to_string()),
+				Some("Production".to_string()),
+			]
+		);
+		assert_eq!(sections[0].text, "Intro with a link and a map.");
+		assert_eq!(
+			sections[1].text,
+			"Rig A moved on site\nSpud date: 2024-05-01\n\n# not a heading\n./start.sh"
+		);
+		assert_eq!(sections[2].text, "| Stage | Days |\n| Frac  | 12   |");
+		assert_eq!(sections[3].text, "Output rose in June.");
+	}
+
+	#[tokio::test]
+	async fn test_markdown_ingestor() {
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(README.as_bytes().to_vec()))),
+			file: Some(Path::new("guide.md").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("md".to_string()),
+			size: Some(README.len()),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = MarkdownIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut section_paths = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				section_paths.push(tokens.section_path);
+			}
+		}
+		assert_eq!(section_paths.len(), 4);
+		assert_eq!(section_paths[1].as_deref(), Some("Wells > Drilling"));
+	}
+}
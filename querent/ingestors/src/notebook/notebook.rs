@@ -0,0 +1,189 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use proto::semantics::IngestedTokens;
+use serde_json::Value;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	markdown::markdown::markdown_sections, process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor, read_collected_bytes,
+	sections::section_tokens, AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+/// Cell sources are either a string or a list of lines.
+fn cell_source(cell: &Value, key: &str) -> String {
+	match cell.get(key) {
+		Some(Value::String(source)) => source.clone(),
+		Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+		_ => String::new(),
+	}
+}
+
+/// Renders a Jupyter notebook as one Markdown document: markdown cells as they are and
+/// code cells as fenced blocks, so headings in markdown cells scope the code below them.
+pub fn notebook_markdown(notebook: &Value) -> String {
+	// nbformat 4 keeps cells at the top level, nbformat 3 inside worksheets
+	let cells: Vec<&Value> = match notebook.get("cells").and_then(Value::as_array) {
+		Some(cells) => cells.iter().collect(),
+		None => notebook
+			.get("worksheets")
+			.and_then(Value::as_array)
+			.into_iter()
+			.flatten()
+			.filter_map(|worksheet| worksheet.get("cells").and_then(Value::as_array))
+			.flatten()
+			.collect(),
+	};
+	let mut markdown = String::new();
+	for cell in cells {
+		match cell.get("cell_type").and_then(Value::as_str) {
+			Some("markdown") | Some("heading") => {
+				let source = cell_source(cell, "source");
+				// nbformat 3 heading cells carry their level separately
+				if let Some(level) = cell.get("level").and_then(Value::as_u64) {
+					markdown.push_str(&"#".repeat(level as usize));
+					markdown.push(' ');
+				}
+				markdown.push_str(source.trim_end());
+			},
+			Some("code") => {
+				let source = match cell.get("source") {
+					Some(_) => cell_source(cell, "source"),
+					None => cell_source(cell, "input"),
+				};
+				if source.trim().is_empty() {
+					continue;
+				}
+				markdown.push_str("```\n");
+				markdown.push_str(source.trim_end());
+				markdown.push_str("\n```");
+			},
+			_ => continue,
+		}
+		markdown.push_str("\n\n");
+	}
+	markdown
+}
+
+// Define the NotebookIngestor
+pub struct NotebookIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl NotebookIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for NotebookIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let notebook: Value = match serde_json::from_slice(&buffer) {
+				Ok(notebook) => notebook,
+				Err(e) => {
+					yield Err(e.into());
+					return;
+				},
+			};
+			let sections = markdown_sections(&notebook_markdown(&notebook));
+			for tokens in section_tokens(sections, &file, &doc_source, &source_id) {
+				yield Ok(tokens);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{io::Cursor, path::Path};
+
+	#[tokio::test]
+	async fn test_notebook_ingestor() {
+		let notebook = serde_json::json!({
+			"nbformat": 4,
+			"cells": [
+				{"cell_type": "markdown", "source": ["# Decline curves\n", "Fitting Arps curves."]},
+				{"cell_type": "code", "source": "import numpy as np\n# fit the curve", "outputs": []},
+				{"cell_type": "markdown", "source": "## Results\nHyperbolic fits best."},
+				{"cell_type": "raw", "source": "ignored"}
+			]
+		});
+		let bytes = serde_json::to_vec(&notebook).unwrap();
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(bytes))),
+			file: Some(Path::new("decline.ipynb").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("ipynb".to_string()),
+			size: Some(10),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = NotebookIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut sections = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				sections.push((tokens.section_path.unwrap_or_default(), tokens.data[0].clone()));
+			}
+		}
+		assert_eq!(
+			sections,
+			vec![
+				(
+					"Decline curves".to_string(),
+					"Fitting Arps curves import numpy as np fit the curve".to_string()
+				),
+				("Decline curves > Results".to_string(), "Hyperbolic fits best ".to_string()),
+			]
+		);
+	}
+}
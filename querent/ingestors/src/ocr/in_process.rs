@@ -0,0 +1,76 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use image::DynamicImage;
+use ocrs::{ImageSource, OcrEngine, OcrEngineParams};
+use rten::Model;
+use std::{path::Path, sync::Arc};
+
+use super::TextRecognizer;
+use crate::{IngestorError, IngestorErrorKind, IngestorResult};
+
+/// File name of the text detection model in the model directory.
+pub const DETECTION_MODEL: &str = "text-detection.rten";
+/// File name of the text recognition model in the model directory.
+pub const RECOGNITION_MODEL: &str = "text-recognition.rten";
+
+/// OCR running the ocrs detection and recognition models in process, for hosts where
+/// tesseract cannot be installed.
+pub struct InProcessRecognizer {
+	engine: OcrEngine,
+}
+
+impl InProcessRecognizer {
+	/// Loads the models from `model_dir`.
+	pub fn load(model_dir: &Path) -> IngestorResult<Self> {
+		let load_model = |file_name: &str| {
+			let path = model_dir.join(file_name);
+			Model::load_file(&path).map_err(|e| {
+				IngestorError::new(
+					IngestorErrorKind::NotFound,
+					Arc::new(anyhow::anyhow!("Failed to load OCR model {}: {}", path.display(), e)),
+				)
+			})
+		};
+		let engine = OcrEngine::new(OcrEngineParams {
+			detection_model: Some(load_model(DETECTION_MODEL)?),
+			recognition_model: Some(load_model(RECOGNITION_MODEL)?),
+			..Default::default()
+		})
+		.map_err(internal)?;
+		Ok(Self { engine })
+	}
+}
+
+impl TextRecognizer for InProcessRecognizer {
+	fn name(&self) -> &'static str {
+		"in-process"
+	}
+
+	fn recognize(&self, image: &DynamicImage) -> IngestorResult<String> {
+		let image = image.to_rgb8();
+		let source = ImageSource::from_bytes(image.as_raw(), image.dimensions()).map_err(|e| {
+			IngestorError::new(IngestorErrorKind::Internal, Arc::new(anyhow::anyhow!("{}", e)))
+		})?;
+		let input = self.engine.prepare_input(source).map_err(internal)?;
+		self.engine.get_text(&input).map_err(internal)
+	}
+}
+
+fn internal(error: anyhow::Error) -> IngestorError {
+	IngestorError::new(IngestorErrorKind::Internal, Arc::new(error))
+}
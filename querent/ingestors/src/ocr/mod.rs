@@ -0,0 +1,62 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+pub mod in_process;
+pub mod tesseract;
+
+use image::DynamicImage;
+use proto::semantics::{OcrBackend, OcrConfig};
+use std::{path::PathBuf, sync::Arc};
+
+use crate::IngestorResult;
+use in_process::InProcessRecognizer;
+use tesseract::TesseractRecognizer;
+
+/// Reads the text in images, for image files and scanned pages.
+pub trait TextRecognizer: Send + Sync {
+	/// Name of the backend, for logs.
+	fn name(&self) -> &'static str;
+
+	/// Whether the backend can run on this host.
+	fn is_available(&self) -> bool {
+		true
+	}
+
+	/// Text found in `image`. Recognition is CPU bound: call it from a blocking task.
+	fn recognize(&self, image: &DynamicImage) -> IngestorResult<String>;
+}
+
+/// Recognizer used when a pipeline sets none: tesseract, when it is installed.
+pub fn default_recognizer() -> Arc<dyn TextRecognizer> {
+	Arc::new(TesseractRecognizer)
+}
+
+/// Creates the recognizer of an OCR configuration, loading the models of the in-process
+/// backend.
+pub fn create_recognizer(config: &OcrConfig) -> IngestorResult<Arc<dyn TextRecognizer>> {
+	match config.backend() {
+		OcrBackend::Tesseract => Ok(default_recognizer()),
+		OcrBackend::InProcess => {
+			let model_dir = config
+				.model_dir
+				.as_ref()
+				.map(PathBuf::from)
+				.unwrap_or_else(|| common::get_querent_data_path().join("ocr"));
+			Ok(Arc::new(InProcessRecognizer::load(&model_dir)?))
+		},
+	}
+}
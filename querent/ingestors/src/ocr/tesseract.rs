@@ -0,0 +1,50 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use image::DynamicImage;
+use std::sync::Arc;
+
+use super::TextRecognizer;
+use crate::{IngestorError, IngestorErrorKind, IngestorResult};
+
+/// OCR with the tesseract binary found in the path.
+pub struct TesseractRecognizer;
+
+impl TextRecognizer for TesseractRecognizer {
+	fn name(&self) -> &'static str {
+		"tesseract"
+	}
+
+	fn is_available(&self) -> bool {
+		rusty_tesseract::tesseract::find_tesseract_path().is_some()
+	}
+
+	fn recognize(&self, image: &DynamicImage) -> IngestorResult<String> {
+		let image = rusty_tesseract::Image::from_dynamic_image(image).map_err(|e| {
+			IngestorError::new(
+				IngestorErrorKind::Internal,
+				Arc::new(anyhow::anyhow!("Failed to convert image to tesseract image: {:?}", e)),
+			)
+		})?;
+		rusty_tesseract::image_to_string(&image, &rusty_tesseract::Args::default()).map_err(|e| {
+			IngestorError::new(
+				IngestorErrorKind::Internal,
+				Arc::new(anyhow::anyhow!("Failed to get text from image: {:?}", e)),
+			)
+		})
+	}
+}
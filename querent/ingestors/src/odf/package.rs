@@ -0,0 +1,114 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use common::CollectedBytes;
+use futures::{Stream, StreamExt};
+use proto::semantics::IngestedTokens;
+use std::{
+	io::{Cursor, Read},
+	sync::Arc,
+};
+use xml::attribute::OwnedAttribute;
+use zip::ZipArchive;
+
+use crate::{
+	image::image::ImageIngestor, BaseIngestor, IngestorError, IngestorErrorKind, IngestorResult,
+};
+
+/// The parts of an OpenDocument file (odt, ods, odp) the ingestors read: the
+/// `content.xml` body and the pictures stored alongside it.
+pub struct OdfPackage {
+	pub content: String,
+	pub images: Vec<(String, Vec<u8>)>,
+}
+
+impl OdfPackage {
+	pub fn read(data: Vec<u8>) -> IngestorResult<Self> {
+		let mut archive = ZipArchive::new(Cursor::new(data))?;
+		let mut content = String::new();
+		let mut images = Vec::new();
+		for i in 0..archive.len() {
+			let mut file = archive.by_index(i)?;
+			if file.name() == "content.xml" {
+				file.read_to_string(&mut content)?;
+			} else if file.name().starts_with("Pictures/") {
+				let mut image = Vec::new();
+				file.read_to_end(&mut image)?;
+				images.push((file.name().to_string(), image));
+			}
+		}
+		if content.is_empty() {
+			return Err(IngestorError::new(
+				IngestorErrorKind::NotFound,
+				Arc::new(anyhow::anyhow!("No content.xml found in the archive or it is empty")),
+			));
+		}
+		Ok(OdfPackage { content, images })
+	}
+}
+
+/// Value of an attribute, matched on its local name.
+pub fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+	attributes
+		.iter()
+		.find(|attribute| attribute.name.local_name == name)
+		.map(|attribute| attribute.value.as_str())
+}
+
+/// Runs the pictures of a package through the image ingestor, reporting their text under
+/// the file of the document.
+pub fn image_tokens(
+	images: Vec<(String, Vec<u8>)>,
+	file: String,
+	doc_source: String,
+	source_id: String,
+) -> impl Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static {
+	stream! {
+		for (image_name, image) in images {
+			let collected_bytes = CollectedBytes {
+				size: Some(image.len()),
+				data: Some(Box::pin(Cursor::new(image))),
+				file: Some(file.clone().into()),
+				doc_source: Some(doc_source.clone()),
+				eof: false,
+				extension: Some(image_name.split('.').last().unwrap_or("png").to_string()),
+				source_id: source_id.clone(),
+				_owned_permit: None,
+				content_type: None,
+				image_id: Some(image_name),
+			};
+			let mut image_stream = match ImageIngestor::new().ingest(vec![collected_bytes]).await {
+				Ok(image_stream) => image_stream,
+				Err(e) => {
+					tracing::error!("Failed to ingest image of {}: {:?}", file, e);
+					continue;
+				},
+			};
+			while let Some(tokens) = image_stream.next().await {
+				match tokens {
+					Ok(tokens) => if !tokens.data.is_empty() {
+						// only yield good tokens
+						yield Ok(tokens);
+					},
+					Err(e) => tracing::error!("Failed to get tokens from images: {:?}", e),
+				}
+			}
+		}
+	}
+}
@@ -19,22 +19,18 @@
 use async_stream::stream;
 use async_trait::async_trait;
 use common::CollectedBytes;
-use futures::{Stream, StreamExt};
+use futures::{pin_mut, Stream, StreamExt};
 use proto::semantics::IngestedTokens;
-use std::{
-	io::{Cursor, Read},
-	pin::Pin,
-	sync::Arc,
-};
+use std::{pin::Pin, sync::Arc};
 use tokio::io::AsyncReadExt;
 use tracing::{error, info};
 use xml::reader::{EventReader, XmlEvent};
-use zip::ZipArchive;
 
 use crate::{
-	image::image::ImageIngestor, process_ingested_tokens_stream,
-	processors::text_processing::TextCleanupProcessor, AsyncProcessor, BaseIngestor,
-	IngestorResult,
+	odf::package::{image_tokens, OdfPackage},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	AsyncProcessor, BaseIngestor, IngestorResult,
 };
 
 // Define the OdpIngestor
@@ -87,53 +83,16 @@ impl BaseIngestor for OdpIngestor {
 				source_id = collected_bytes.source_id.clone();
 			}
 
-			let cursor = Cursor::new(buffer);
-			let mut archive = match ZipArchive::new(cursor) {
-				Ok(archive) => archive,
+			let package = match OdfPackage::read(buffer) {
+				Ok(package) => package,
 				Err(e) => {
-					error!("Failed to read zip archive: {:?}", e);
+					error!("Failed to read odp archive: {:?}", e);
 					return;
 				}
 			};
+			let slide_images = package.images;
 
-			let mut xml_data = String::new();
-			let mut slide_images = Vec::new();
-
-			// Extract content.xml and images
-			for i in 0..archive.len() {
-				let mut file = match archive.by_index(i) {
-					Ok(file) => file,
-					Err(e) => {
-						error!("Failed to access file in archive: {:?}", e);
-						return;
-					}
-				};
-
-				// Read the XML content
-				if file.name() == "content.xml" {
-					if let Err(e) = file.read_to_string(&mut xml_data) {
-						error!("Failed to read XML data: {:?}", e);
-						return;
-					}
-				}
-
-				// Extract images
-				if file.name().starts_with("Pictures/") {
-					let mut img_data = Vec::new();
-					if let Err(e) = file.read_to_end(&mut img_data) {
-						error!("Failed to read image data: {:?}", e);
-						return;
-					}
-					slide_images.push((file.name().to_string(), img_data));
-				}
-			}
-
-			if xml_data.is_empty() {
-				error!("No content.xml found in the archive or the file is empty");
-				return;
-			}
-
-			let reader = EventReader::from_str(&xml_data);
+			let reader = EventReader::from_str(&package.content);
 			let mut text = String::new();
 			let mut to_read = false;
 
@@ -176,6 +135,9 @@ impl BaseIngestor for OdpIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 			yield Ok(ingested_tokens);
 
@@ -183,36 +145,11 @@ impl BaseIngestor for OdpIngestor {
 				return;
 			}
 			// Process and yield images
-			for (image_name, img_data) in slide_images {
-				let collected_bytes = CollectedBytes {
-					data: Some(Box::pin(Cursor::new(img_data.clone()))),
-					file: Some(file.clone().into()),
-					doc_source: Some(doc_source.clone()),
-					eof: false,
-					extension: Some(image_name.split('.').last().unwrap_or("png").to_string()),
-					size: Some(img_data.len()),
-					source_id: source_id.clone(),
-					_owned_permit: None,
-					image_id: Some(image_name),
-				};
-				let image_ingestor = ImageIngestor::new();
-				let (tx, mut rx) = tokio::sync::mpsc::channel(100);
-				tokio::spawn(async move {
-					let image_stream = image_ingestor.ingest(vec![collected_bytes]).await.unwrap();
-					let mut image_stream = Box::pin(image_stream);
-					while let Some(tokens) = image_stream.next().await {
-						match tokens {
-							Ok(tokens) => if !tokens.data.is_empty() {
-								// only yield good tokens
-								tx.send(Ok(tokens)).await.unwrap();
-							},
-							Err(e) => tracing::error!("Failed to get tokens from images: {:?}", e),
-						}
-					}
-				});
-				while let Some(tokens) = rx.recv().await {
-					yield tokens;
-				}
+			let images =
+				image_tokens(slide_images, file.clone(), doc_source.clone(), source_id.clone());
+			pin_mut!(images);
+			while let Some(tokens) = images.next().await {
+				yield tokens;
 			}
 
 			// Final yield for empty token to signal end
@@ -223,6 +160,9 @@ impl BaseIngestor for OdpIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			});
 		};
 
@@ -253,6 +193,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -294,6 +235,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
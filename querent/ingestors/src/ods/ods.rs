@@ -0,0 +1,303 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use proto::semantics::IngestedTokens;
+use std::{pin::Pin, sync::Arc};
+use xml::{
+	attribute::OwnedAttribute,
+	reader::{EventReader, XmlEvent},
+};
+
+use crate::{
+	odf::package::{attribute, OdfPackage},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes,
+	sections::{section_tokens, Section},
+	AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+/// Spreadsheets repeat rows and cells instead of storing copies; repeats of a non-empty
+/// row or cell are expanded up to this many times.
+const MAX_REPEATED: usize = 1000;
+
+/// A sheet of a spreadsheet as the text of its rows' cells.
+#[derive(Debug, Default, PartialEq)]
+pub struct Sheet {
+	pub name: String,
+	pub rows: Vec<Vec<String>>,
+}
+
+#[derive(Default)]
+struct Row {
+	cells: Vec<String>,
+	// empty cells seen since the last non-empty one, only added if another value follows
+	pending_empty: usize,
+	repeated: usize,
+}
+
+/// Reads the sheets of the `content.xml` of a spreadsheet, dropping empty rows and
+/// trailing empty cells.
+pub fn ods_sheets(content: &str) -> IngestorResult<Vec<Sheet>> {
+	let mut sheets: Vec<Sheet> = Vec::new();
+	let mut row: Option<Row> = None;
+	let mut cell: Option<(String, usize)> = None;
+	let mut skipped = 0;
+	for event in EventReader::from_str(content) {
+		match event? {
+			XmlEvent::StartElement { name, attributes, .. } => match name.local_name.as_str() {
+				"annotation" => skipped += 1,
+				_ if skipped > 0 => {},
+				"table" => sheets.push(Sheet {
+					name: attribute(&attributes, "name")
+						.map(str::to_string)
+						.unwrap_or_else(|| format!("Sheet{}", sheets.len() + 1)),
+					rows: Vec::new(),
+				}),
+				"table-row" =>
+					row = Some(Row {
+						repeated: repeated(&attributes, "number-rows-repeated"),
+						..Row::default()
+					}),
+				"table-cell" | "covered-table-cell" =>
+					cell = Some((String::new(), repeated(&attributes, "number-columns-repeated"))),
+				element @ ("p" | "s" | "tab" | "line-break") =>
+					if let Some((text, _)) = cell.as_mut() {
+						match element {
+							"p" if !text.is_empty() => text.push('\n'),
+							"p" => {},
+							"s" => {
+								let count = attribute(&attributes, "c")
+									.and_then(|count| count.parse().ok())
+									.unwrap_or(1);
+								text.push_str(&" ".repeat(count));
+							},
+							"tab" => text.push('\t'),
+							_ => text.push('\n'),
+						}
+					},
+				_ => {},
+			},
+			XmlEvent::Characters(data) | XmlEvent::Whitespace(data) if skipped == 0 =>
+				if let Some((text, _)) = cell.as_mut() {
+					text.push_str(&data);
+				},
+			XmlEvent::EndElement { name } => match name.local_name.as_str() {
+				"annotation" => skipped -= 1,
+				_ if skipped > 0 => {},
+				"table-cell" | "covered-table-cell" => {
+					if let (Some((text, repeat)), Some(row)) = (cell.take(), row.as_mut()) {
+						let text = text.trim();
+						if text.is_empty() {
+							row.pending_empty += repeat;
+						} else {
+							row.cells.resize(row.cells.len() + row.pending_empty, String::new());
+							row.pending_empty = 0;
+							let len = row.cells.len() + repeat.min(MAX_REPEATED);
+							row.cells.resize(len, text.to_string());
+						}
+					}
+				},
+				"table-row" =>
+					if let (Some(row), Some(sheet)) = (row.take(), sheets.last_mut()) {
+						if !row.cells.is_empty() {
+							for _ in 1..row.repeated.min(MAX_REPEATED) {
+								sheet.rows.push(row.cells.clone());
+							}
+							sheet.rows.push(row.cells);
+						}
+					},
+				_ => {},
+			},
+			_ => {},
+		}
+	}
+	Ok(sheets)
+}
+
+fn repeated(attributes: &[OwnedAttribute], name: &str) -> usize {
+	attribute(attributes, name)
+		.and_then(|count| count.parse().ok())
+		.unwrap_or(1)
+		.max(1)
+}
+
+/// One section per data row, under the sheet name, with the row written as
+/// `header: value` lines taken from the first row of the sheet.
+pub fn sheet_sections(sheets: Vec<Sheet>) -> Vec<Section> {
+	let mut sections = Vec::new();
+	for sheet in sheets {
+		let mut rows = sheet.rows.into_iter();
+		let Some(headers) = rows.next() else { continue };
+		let mut data_rows = rows.peekable();
+		if data_rows.peek().is_none() {
+			// a lone row has nothing to describe it, keep its values as they are
+			sections.push(Section { path: vec![sheet.name], text: headers.join("\n") });
+			continue;
+		}
+		for row in data_rows {
+			let text = row
+				.iter()
+				.enumerate()
+				.filter(|(_, value)| !value.is_empty())
+				.map(|(i, value)| match headers.get(i).filter(|header| !header.is_empty()) {
+					Some(header) => format!("{}: {}", header, value),
+					None => format!("Column {}: {}", i + 1, value),
+				})
+				.collect::<Vec<_>>()
+				.join("\n");
+			sections.push(Section { path: vec![sheet.name.clone()], text });
+		}
+	}
+	sections
+}
+
+// Define the OdsIngestor
+pub struct OdsIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl OdsIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for OdsIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let sheets = match OdfPackage::read(buffer)
+				.and_then(|package| ods_sheets(&package.content))
+			{
+				Ok(sheets) => sheets,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to read ods {}", file)));
+					return;
+				},
+			};
+			let sections = sheet_sections(sheets);
+			for tokens in section_tokens(sections, &file, &doc_source, &source_id) {
+				yield Ok(tokens);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{
+		io::{Cursor, Write},
+		path::Path,
+	};
+	use zip::{write::SimpleFileOptions, ZipWriter};
+
+	const CONTENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:table="urn:oasis:names:tc:opendocument:xmlns:table:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0">
+<office:body><office:spreadsheet>
+<table:table table:name="Wells">
+<table:table-row><table:table-cell><text:p>Well</text:p></table:table-cell><table:table-cell><text:p>Depth</text:p></table:table-cell><table:table-cell table:number-columns-repeated="1020"/></table:table-row>
+<table:table-row><table:table-cell><text:p>A-7</text:p></table:table-cell><table:table-cell><text:p>3200</text:p></table:table-cell></table:table-row>
+<table:table-row table:number-rows-repeated="2"><table:table-cell table:number-columns-repeated="2"/><table:table-cell><text:p>note</text:p></table:table-cell></table:table-row>
+<table:table-row table:number-rows-repeated="1048000"><table:table-cell table:number-columns-repeated="1024"/></table:table-row>
+</table:table>
+<table:table table:name="Empty"/>
+</office:spreadsheet></office:body>
+</office:document-content>"#;
+
+	#[test]
+	fn test_ods_sheets() {
+		let sheets = ods_sheets(CONTENT).unwrap();
+		assert_eq!(sheets.len(), 2);
+		assert_eq!(sheets[0].name, "Wells");
+		assert_eq!(
+			sheets[0].rows,
+			vec![
+				vec!["Well".to_string(), "Depth".to_string()],
+				vec!["A-7".to_string(), "3200".to_string()],
+				vec![String::new(), String::new(), "note".to_string()],
+				vec![String::new(), String::new(), "note".to_string()],
+			]
+		);
+
+		let sections = sheet_sections(sheets);
+		assert_eq!(sections.len(), 3);
+		assert_eq!(sections[0].path, vec!["Wells".to_string()]);
+		assert_eq!(sections[0].text, "Well: A-7\nDepth: 3200");
+		assert_eq!(sections[1].text, "Column 3: note");
+	}
+
+	#[tokio::test]
+	async fn test_ods_ingestor() {
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		writer.start_file("content.xml", SimpleFileOptions::default()).unwrap();
+		writer.write_all(CONTENT.as_bytes()).unwrap();
+		let bytes = writer.finish().unwrap().into_inner();
+
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(bytes))),
+			file: Some(Path::new("wells.ods").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("ods".to_string()),
+			size: Some(10),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = OdsIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut rows = 0;
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				assert_eq!(tokens.section_path.as_deref(), Some("Wells"));
+				rows += 1;
+			}
+		}
+		assert_eq!(rows, 3);
+	}
+}
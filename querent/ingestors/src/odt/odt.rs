@@ -0,0 +1,240 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::{pin_mut, Stream, StreamExt};
+use proto::semantics::IngestedTokens;
+use std::{pin::Pin, sync::Arc};
+use xml::reader::{EventReader, XmlEvent};
+
+use crate::{
+	odf::package::{attribute, image_tokens, OdfPackage},
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes,
+	sections::{section_tokens, Section, SectionBuilder},
+	AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+/// Splits the `content.xml` of a text document into sections along its outline headings.
+/// Comments and tracked deletions are left out.
+pub fn odt_sections(content: &str) -> IngestorResult<Vec<Section>> {
+	let mut builder = SectionBuilder::new();
+	// open headings and paragraphs, innermost last, with the outline level of headings
+	let mut open: Vec<(Option<usize>, String)> = Vec::new();
+	let mut skipped = 0;
+	for event in EventReader::from_str(content) {
+		match event? {
+			XmlEvent::StartElement { name, attributes, .. } => match name.local_name.as_str() {
+				"annotation" | "tracked-changes" => skipped += 1,
+				_ if skipped > 0 => {},
+				"h" => {
+					let level = attribute(&attributes, "outline-level")
+						.and_then(|level| level.parse().ok())
+						.unwrap_or(1);
+					open.push((Some(level), String::new()));
+				},
+				"p" => open.push((None, String::new())),
+				element @ ("s" | "tab" | "line-break") =>
+					if let Some((_, text)) = open.last_mut() {
+						match element {
+							"s" => {
+								let count = attribute(&attributes, "c")
+									.and_then(|count| count.parse().ok())
+									.unwrap_or(1);
+								text.push_str(&" ".repeat(count));
+							},
+							"tab" => text.push('\t'),
+							_ => text.push('\n'),
+						}
+					},
+				_ => {},
+			},
+			XmlEvent::Characters(data) | XmlEvent::Whitespace(data) if skipped == 0 =>
+				if let Some((_, text)) = open.last_mut() {
+					text.push_str(&data);
+				},
+			XmlEvent::EndElement { name } => match name.local_name.as_str() {
+				"annotation" | "tracked-changes" => skipped -= 1,
+				_ if skipped > 0 => {},
+				"h" | "p" => match open.pop() {
+					Some((Some(level), title)) => builder.heading(level, &title),
+					Some((None, text)) => builder.line(&text),
+					None => {},
+				},
+				_ => {},
+			},
+			_ => {},
+		}
+	}
+	Ok(builder.finish())
+}
+
+// Define the OdtIngestor
+pub struct OdtIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl OdtIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for OdtIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let package = match OdfPackage::read(buffer) {
+				Ok(package) => package,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to read odt {}", file)));
+					return;
+				},
+			};
+			let sections = match odt_sections(&package.content) {
+				Ok(sections) => sections,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to read odt {}", file)));
+					return;
+				},
+			};
+			let mut tokens = section_tokens(sections, &file, &doc_source, &source_id);
+			// images go before the message closing the document
+			let end_of_document = tokens.pop();
+			for tokens in tokens {
+				yield Ok(tokens);
+			}
+			let images = image_tokens(package.images, file, doc_source, source_id);
+			pin_mut!(images);
+			while let Some(tokens) = images.next().await {
+				yield tokens;
+			}
+			if let Some(end_of_document) = end_of_document {
+				yield Ok(end_of_document);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::{
+		io::{Cursor, Write},
+		path::Path,
+	};
+	use zip::{write::SimpleFileOptions, ZipWriter};
+
+	const CONTENT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:dc="http://purl.org/dc/elements/1.1/">
+<office:body><office:text>
+<text:p>Field notes</text:p>
+<text:h text:outline-level="1">Well <text:span>A-7</text:span></text:h>
+<text:h text:outline-level="2">Drilling</text:h>
+<text:p>Spud<text:s text:c="2"/>in May.<office:annotation><dc:creator>Ann</dc:creator><text:p>check date</text:p></office:annotation></text:p>
+<text:list><text:list-item><text:p>Mud weight raised</text:p></text:list-item></text:list>
+<text:h text:outline-level="1">Production</text:h>
+<text:p>Output rose.</text:p>
+</office:text></office:body>
+</office:document-content>"#;
+
+	#[test]
+	fn test_odt_sections() {
+		let sections = odt_sections(CONTENT).unwrap();
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![None, Some("Well A-7 > Drilling".to_string()), Some("Production".to_string())]
+		);
+		assert_eq!(sections[1].text, "Spud  in May.\nMud weight raised");
+	}
+
+	#[tokio::test]
+	async fn test_odt_ingestor() {
+		let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+		writer.start_file("mimetype", SimpleFileOptions::default()).unwrap();
+		writer.write_all(b"application/vnd.oasis.opendocument.text").unwrap();
+		writer.start_file("content.xml", SimpleFileOptions::default()).unwrap();
+		writer.write_all(CONTENT.as_bytes()).unwrap();
+		let bytes = writer.finish().unwrap().into_inner();
+
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(bytes))),
+			file: Some(Path::new("notes.odt").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("odt".to_string()),
+			size: Some(10),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = OdtIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut section_paths = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				section_paths.push(tokens.section_path);
+			}
+		}
+		assert_eq!(section_paths.len(), 3);
+		assert_eq!(section_paths[2].as_deref(), Some("Production"));
+
+		let ingestor = OdtIngestor::new();
+		let corrupt = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(b"not a zip".to_vec()))),
+			file: Some(Path::new("broken.odt").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("odt".to_string()),
+			size: Some(9),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+		let mut stream = ingestor.ingest(vec![corrupt]).await.unwrap();
+		assert!(stream.next().await.unwrap().is_err());
+	}
+}
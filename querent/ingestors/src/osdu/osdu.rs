@@ -87,6 +87,9 @@ impl BaseIngestor for OSDURecordIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 				return;
 			}
@@ -100,6 +103,9 @@ impl BaseIngestor for OSDURecordIngestor {
 						is_token_stream: false,
 						source_id: source_id.clone(),
 						image_id: None,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
 					};
 					yield Ok(ingested_tokens);
 				},
@@ -114,6 +120,9 @@ impl BaseIngestor for OSDURecordIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			})
 		};
 
@@ -17,3 +17,4 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 pub mod pdfv1;
+pub mod raster;
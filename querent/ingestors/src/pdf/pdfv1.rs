@@ -17,17 +17,21 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use crate::{
-	image::image::ImageIngestor, process_ingested_tokens_stream,
-	processors::text_processing::TextCleanupProcessor, AsyncProcessor, BaseIngestor, IngestorError,
-	IngestorErrorKind, IngestorResult,
+	image::image::ImageIngestor,
+	ocr::{default_recognizer, TextRecognizer},
+	pdf::raster::rasterize_page,
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	tables::{detect_tables, remove_table_lines, table_text, DetectedTable, PositionedWord},
+	AsyncProcessor, BaseIngestor, IngestorError, IngestorErrorKind, IngestorResult,
 };
 use async_stream::stream;
 use async_trait::async_trait;
 use common::CollectedBytes;
 use futures::{Stream, StreamExt};
+use image::guess_format;
 use pdf_extract::{output_doc, ConvertToFmt, OutputDev, OutputError, PlainTextOutput};
 use proto::semantics::IngestedTokens;
-use rusty_tesseract::image::guess_format;
 use std::{
 	collections::{BTreeMap, HashMap},
 	fmt,
@@ -37,14 +41,35 @@ use std::{
 };
 use tokio::io::AsyncReadExt;
 
+/// Pages with fewer characters than this in their text layer are taken for scans.
+const MIN_PAGE_TEXT: usize = 16;
+
 // Define the PdfIngestor
 pub struct PdfIngestor {
 	processors: Vec<Arc<dyn AsyncProcessor>>,
+	passwords: Vec<String>,
+	recognizer: Arc<dyn TextRecognizer>,
 }
 
 impl PdfIngestor {
 	pub fn new() -> Self {
-		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+		Self {
+			processors: vec![Arc::new(TextCleanupProcessor::new())],
+			passwords: Vec::new(),
+			recognizer: default_recognizer(),
+		}
+	}
+
+	/// Passwords tried, in order, on encrypted documents.
+	pub fn with_passwords(mut self, passwords: Vec<String>) -> Self {
+		self.passwords = passwords;
+		self
+	}
+
+	/// Recognizer reading scanned pages and embedded images.
+	pub fn with_recognizer(mut self, recognizer: Arc<dyn TextRecognizer>) -> Self {
+		self.recognizer = recognizer;
+		self
 	}
 }
 
@@ -59,6 +84,8 @@ impl BaseIngestor for PdfIngestor {
 		all_collected_bytes: Vec<CollectedBytes>,
 	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
 	{
+		let passwords = self.passwords.clone();
+		let recognizer = self.recognizer.clone();
 		// collect all the bytes into a single buffer
 		let stream = stream! {
 			let mut buffer = Vec::new();
@@ -88,22 +115,61 @@ impl BaseIngestor for PdfIngestor {
 				yield Err(IngestorError::new(IngestorErrorKind::Internal, Arc::new(e.into())));
 				return;
 			}
-			let doc = doc.unwrap();
+			let mut doc = doc.unwrap();
 			if doc.is_encrypted() {
-				yield Ok(IngestedTokens {
-					data: vec![],
-					file: file.clone(),
-					doc_source: doc_source.clone(),
-					is_token_stream: false,
-					source_id: source_id.clone(),
-					image_id: None,
-				});
-				return;
+				// documents only protected against editing open with an empty password
+				let unlocked = std::iter::once("")
+					.chain(passwords.iter().map(String::as_str))
+					.any(|password| doc.decrypt(password).is_ok());
+				if !unlocked {
+					yield Err(IngestorError::new(
+						IngestorErrorKind::Unauthorized,
+						Arc::new(anyhow::anyhow!(
+							"Encrypted PDF {} did not open with the collector passwords",
+							file
+						)),
+					));
+					return;
+				}
 			}
-			let mut output = PagePlainTextOutput::new(Arc::new(doc.clone()));
+			let doc = Arc::new(doc);
+			let page_ids = doc.get_pages();
+			let mut output = PagePlainTextOutput::new(doc.clone());
 			let _ = output_doc(&doc, &mut output);
 			let page_images = output.images;
-			for (_, (text, has_image)) in output.pages {
+			let mut page_tables = output.tables;
+			for (page_num, (text, has_image)) in output.pages {
+				let tables = page_tables.remove(&page_num).unwrap_or_default();
+				let text =
+					if tables.is_empty() { text } else { remove_table_lines(&text, &tables) };
+				let scanned = has_image &&
+					recognizer.is_available() &&
+					text.chars().filter(|c| !c.is_whitespace()).count() < MIN_PAGE_TEXT;
+				let recognized = match page_ids.get(&page_num).copied() {
+					Some(page_id) if scanned => {
+						let (doc, recognizer) = (doc.clone(), recognizer.clone());
+						let recognized = tokio::task::spawn_blocking(move || {
+							rasterize_page(&doc, page_id).map(|page| recognizer.recognize(&page))
+						})
+						.await;
+						match recognized {
+							Ok(Some(Ok(recognized))) => Some(recognized),
+							Ok(None) => None,
+							failed => {
+								tracing::error!(
+									"Failed to OCR page {} of {}: {:?}",
+									page_num,
+									file,
+									failed
+								);
+								None
+							},
+						}
+					},
+					_ => None,
+				};
+				let page_recognized = recognized.is_some();
+				let text = recognized.unwrap_or(text);
 				let ingested_tokens = IngestedTokens {
 					data: vec![text],
 					file: file.clone(),
@@ -111,9 +177,26 @@ impl BaseIngestor for PdfIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				};
 				yield Ok(ingested_tokens);
-				if has_image {
+				for DetectedTable { table, .. } in tables {
+					yield Ok(IngestedTokens {
+						data: vec![table_text(&table)],
+						file: file.clone(),
+						doc_source: doc_source.clone(),
+						is_token_stream: false,
+						source_id: source_id.clone(),
+						image_id: None,
+						section_path: None,
+						table: Some(table),
+						duplicate_of: None,
+					});
+				}
+				// the images of a scanned page were read along with the page
+				if has_image && !page_recognized {
 					for image in &page_images {
 						let img_data = image.1;
 						let image_id = image.0.clone();
@@ -133,6 +216,9 @@ impl BaseIngestor for PdfIngestor {
 								is_token_stream: false,
 								source_id: source_id.clone(),
 								image_id: None,
+								section_path: None,
+								table: None,
+								duplicate_of: None,
 							});
 							continue;
 						}
@@ -145,9 +231,11 @@ impl BaseIngestor for PdfIngestor {
 							size: Some(img_data.len()),
 							source_id: source_id.clone(),
 							_owned_permit: None,
+							content_type: None,
 							image_id: Some(image_id.to_string()),
 						};
-						let image_ingestor = ImageIngestor::new();
+						let image_ingestor =
+							ImageIngestor::new().with_recognizer(recognizer.clone());
 						let (tx, mut rx) = tokio::sync::mpsc::channel(100);
 						tokio::spawn(async move {
 							let image_stream = image_ingestor.ingest(vec![collected_bytes]).await.unwrap();
@@ -177,6 +265,9 @@ impl BaseIngestor for PdfIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			})
 		};
 
@@ -197,6 +288,9 @@ struct PagePlainTextOutput {
 	reader: Arc<Mutex<String>>,
 	document: Arc<lopdf::Document>,
 	inner_pages: BTreeMap<u32, (u32, u16)>,
+	// words of the current page, laid out, to find the tables on it
+	words: Vec<PositionedWord>,
+	tables: HashMap<u32, Vec<DetectedTable>>,
 }
 
 struct OutputWrapper(Arc<Mutex<String>>);
@@ -229,6 +323,38 @@ impl PagePlainTextOutput {
 			reader: s,
 			inner: PlainTextOutput::new(OutputWrapper(writer)),
 			images: HashMap::new(),
+			words: Vec::new(),
+			tables: HashMap::new(),
+		}
+	}
+
+	/// Adds a glyph to the word being laid out, or starts a new word after a space or a gap.
+	fn record_glyph(
+		&mut self,
+		trm: &pdf_extract::Transform,
+		width: f64,
+		font_size: f64,
+		char: &str,
+	) {
+		let scaled =
+			((trm.m11 + trm.m21) * font_size * (trm.m12 + trm.m22) * font_size).abs().sqrt();
+		let size = if scaled > 0.0 { scaled } else { font_size.abs() };
+		let (x, y) = (trm.m31, trm.m32);
+		let end = x + width * size;
+		if char.trim().is_empty() {
+			self.words.push(PositionedWord { text: String::new(), x: end, end, y, size });
+			return;
+		}
+		match self.words.last_mut() {
+			Some(word)
+				if (word.y - y).abs() <= size * 0.5 &&
+					x >= word.end - size * 0.1 &&
+					x - word.end <= size * 0.2 =>
+			{
+				word.text.push_str(char);
+				word.end = end;
+			},
+			_ => self.words.push(PositionedWord { text: char.to_string(), x, end, y, size }),
 		}
 	}
 }
@@ -242,6 +368,7 @@ impl OutputDev for PagePlainTextOutput {
 	) -> Result<(), OutputError> {
 		self.current_page = page_num;
 		self.current_page_has_image = false;
+		self.words.clear();
 		self.reader.lock().unwrap().clear(); // Ensure the buffer is clear at the start of each page
 		let page = self.inner_pages.get(&page_num).unwrap_or(&(0, 0));
 		let page_images = self.document.get_page_images(page.clone());
@@ -259,6 +386,12 @@ impl OutputDev for PagePlainTextOutput {
 		let buf = self.reader.lock().unwrap().clone();
 		self.pages.insert(self.current_page, (buf, self.current_page_has_image));
 		self.reader.lock().unwrap().clear();
+		let words: Vec<PositionedWord> =
+			self.words.drain(..).filter(|word| !word.text.is_empty()).collect();
+		let tables = detect_tables(&words);
+		if !tables.is_empty() {
+			self.tables.insert(self.current_page, tables);
+		}
 
 		Ok(())
 	}
@@ -271,6 +404,7 @@ impl OutputDev for PagePlainTextOutput {
 		font_size: f64,
 		char: &str,
 	) -> Result<(), OutputError> {
+		self.record_glyph(trm, width, font_size, char);
 		self.inner.output_character(trm, width, spacing, font_size, char)
 	}
 
@@ -308,6 +442,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -345,6 +480,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -389,6 +525,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
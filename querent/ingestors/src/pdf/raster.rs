@@ -0,0 +1,585 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use image::{DynamicImage, GrayImage, ImageFormat, Luma};
+use lopdf::{content::Content, Dictionary, Document, Object, ObjectId, Stream};
+
+/// Resolution pages are rasterized at for OCR.
+const DPI: f64 = 200.0;
+/// Longest side of a rasterized page, in pixels, whatever the size of the page.
+const MAX_SIDE: f64 = 4000.0;
+/// Images larger than this many pixels are not decoded.
+const MAX_IMAGE_PIXELS: usize = 100_000_000;
+/// Depth of nested form XObjects followed.
+const MAX_FORM_DEPTH: usize = 8;
+/// Letter size, for pages without a media box.
+const DEFAULT_MEDIA_BOX: [f64; 4] = [0.0, 0.0, 612.0, 792.0];
+
+type Matrix = [f64; 6];
+
+const IDENTITY: Matrix = [1.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+
+/// Renders the images drawn on a page onto a white canvas the size of the page, in grayscale.
+///
+/// Text and vector paths are not drawn: this is meant for scanned pages, whose content is
+/// images. Images with JPEG 2000 or JBIG2 compression are skipped. `None` when nothing could
+/// be drawn.
+pub fn rasterize_page(doc: &Document, page_id: ObjectId) -> Option<DynamicImage> {
+	let page = doc.get_object(page_id).and_then(Object::as_dict).ok()?;
+	let media_box = inherited(doc, page, b"MediaBox")
+		.and_then(|media_box| numbers(doc, media_box))
+		.filter(|media_box| media_box.len() == 4)
+		.map(|media_box| [media_box[0], media_box[1], media_box[2], media_box[3]])
+		.unwrap_or(DEFAULT_MEDIA_BOX);
+	let (x0, x1) = (media_box[0].min(media_box[2]), media_box[0].max(media_box[2]));
+	let (y0, y1) = (media_box[1].min(media_box[3]), media_box[1].max(media_box[3]));
+	let (page_width, page_height) = (x1 - x0, y1 - y0);
+	if page_width <= 0.0 || page_height <= 0.0 {
+		return None;
+	}
+	let scale = (DPI / 72.0).min(MAX_SIDE / page_width.max(page_height));
+	// maps page space to canvas pixels, whose origin is the top left corner
+	let page_to_canvas = [scale, 0.0, 0.0, -scale, -x0 * scale, y1 * scale];
+
+	let mut canvas = Canvas {
+		image: GrayImage::from_pixel(
+			(page_width * scale).ceil() as u32,
+			(page_height * scale).ceil() as u32,
+			Luma([255]),
+		),
+		drawn: false,
+	};
+	let content = doc.get_page_content(page_id).ok()?;
+	let resources = inherited(doc, page, b"Resources").and_then(|resources| dict(doc, resources));
+	draw_content(doc, &content, resources, page_to_canvas, &mut canvas, 0);
+	canvas.drawn.then(|| DynamicImage::ImageLuma8(canvas.image))
+}
+
+struct Canvas {
+	image: GrayImage,
+	drawn: bool,
+}
+
+/// Follows the graphics state of a content stream and draws the images it paints.
+fn draw_content(
+	doc: &Document,
+	content: &[u8],
+	resources: Option<&Dictionary>,
+	base: Matrix,
+	canvas: &mut Canvas,
+	depth: usize,
+) {
+	let Ok(content) = Content::decode(content) else {
+		return;
+	};
+	let xobjects = resources
+		.and_then(|resources| resources.get(b"XObject").ok())
+		.and_then(|xobjects| dict(doc, xobjects));
+	let mut ctm = IDENTITY;
+	let mut saved = Vec::new();
+	for operation in content.operations {
+		match operation.operator.as_str() {
+			"q" => saved.push(ctm),
+			"Q" => ctm = saved.pop().unwrap_or(IDENTITY),
+			"cm" => {
+				let Some(operands) = numbers_of(&operation.operands) else {
+					continue;
+				};
+				if let [a, b, c, d, e, f] = operands[..] {
+					ctm = multiply(&[a, b, c, d, e, f], &ctm);
+				}
+			},
+			"Do" => {
+				let Some(Ok(name)) = operation.operands.first().map(Object::as_name) else {
+					continue;
+				};
+				let Some(stream) = xobjects
+					.and_then(|xobjects| xobjects.get(name).ok())
+					.and_then(|xobject| resolve(doc, xobject).as_stream().ok())
+				else {
+					continue;
+				};
+				let transform = multiply(&ctm, &base);
+				match stream.dict.get(b"Subtype").and_then(Object::as_name) {
+					Ok(b"Image") =>
+						if let Some(image) = decode_image(doc, stream) {
+							canvas.draw(&image, &transform);
+						},
+					Ok(b"Form") if depth < MAX_FORM_DEPTH => {
+						let matrix = stream
+							.dict
+							.get(b"Matrix")
+							.ok()
+							.and_then(|matrix| numbers(doc, matrix))
+							.and_then(|matrix| <[f64; 6]>::try_from(matrix).ok())
+							.unwrap_or(IDENTITY);
+						let form_resources = stream
+							.dict
+							.get(b"Resources")
+							.ok()
+							.and_then(|resources| dict(doc, resources))
+							.or(resources);
+						let content = stream_data(stream);
+						draw_content(
+							doc,
+							&content,
+							form_resources,
+							multiply(&matrix, &transform),
+							canvas,
+							depth + 1,
+						);
+					},
+					_ => {},
+				}
+			},
+			_ => {},
+		}
+	}
+}
+
+impl Canvas {
+	/// Paints `image` over the unit square mapped to the canvas by `transform`.
+	fn draw(&mut self, image: &GrayImage, transform: &Matrix) {
+		let [a, b, c, d, e, f] = *transform;
+		let det = a * d - b * c;
+		if det.abs() < f64::EPSILON {
+			return;
+		}
+		let corners = [(e, f), (a + e, b + f), (c + e, d + f), (a + c + e, b + d + f)];
+		let (width, height) = (self.image.width(), self.image.height());
+		let min_x = corners.iter().map(|p| p.0).fold(f64::INFINITY, f64::min).max(0.0) as u32;
+		let min_y = corners.iter().map(|p| p.1).fold(f64::INFINITY, f64::min).max(0.0) as u32;
+		let max_x = corners.iter().map(|p| p.0).fold(0.0, f64::max).ceil().min(width as f64) as u32;
+		let max_y =
+			corners.iter().map(|p| p.1).fold(0.0, f64::max).ceil().min(height as f64) as u32;
+		let (image_width, image_height) = (image.width(), image.height());
+		for y in min_y..max_y {
+			for x in min_x..max_x {
+				let (px, py) = (x as f64 + 0.5 - e, y as f64 + 0.5 - f);
+				let u = (d * px - c * py) / det;
+				let v = (a * py - b * px) / det;
+				if !(0.0..1.0).contains(&u) || !(0.0..1.0).contains(&v) {
+					continue;
+				}
+				// image rows run from the top, where v is 1
+				let column = ((u * image_width as f64) as u32).min(image_width - 1);
+				let row = (((1.0 - v) * image_height as f64) as u32).min(image_height - 1);
+				self.image.put_pixel(x, y, *image.get_pixel(column, row));
+				self.drawn = true;
+			}
+		}
+	}
+}
+
+/// Decodes an image XObject to grayscale.
+fn decode_image(doc: &Document, stream: &Stream) -> Option<GrayImage> {
+	let dict = &stream.dict;
+	let width = integer(doc, dict.get(b"Width").ok()?)?;
+	let height = integer(doc, dict.get(b"Height").ok()?)?;
+	// the sizes come from the file, so their product may not even fit in a usize
+	if width == 0 ||
+		height == 0 ||
+		width.checked_mul(height).is_none_or(|pixels| pixels > MAX_IMAGE_PIXELS)
+	{
+		return None;
+	}
+	let filters: Vec<&[u8]> = match dict.get(b"Filter").map(|filter| resolve(doc, filter)) {
+		Ok(Object::Name(name)) => vec![name],
+		Ok(Object::Array(names)) => names.iter().filter_map(|name| name.as_name().ok()).collect(),
+		_ => Vec::new(),
+	};
+	let image_mask = matches!(dict.get(b"ImageMask"), Ok(Object::Boolean(true)));
+	// a decode array such as [1 0] inverts the samples
+	let inverted = dict
+		.get(b"Decode")
+		.ok()
+		.and_then(|decode| numbers(doc, decode))
+		.is_some_and(|decode| decode.len() >= 2 && decode[0] > decode[1]);
+
+	let image = match filters[..] {
+		[b"DCTDecode"] => image::load_from_memory_with_format(&stream.content, ImageFormat::Jpeg)
+			.ok()?
+			.to_luma8(),
+		[b"CCITTFaxDecode"] => decode_ccitt(doc, stream, width, height)?,
+		[.., b"DCTDecode" | b"CCITTFaxDecode" | b"JPXDecode" | b"JBIG2Decode"] => return None,
+		_ => {
+			let data = stream_data(stream);
+			let (components, palette) = if image_mask {
+				(ColorSpace::Gray, None)
+			} else {
+				color_space(doc, dict.get(b"ColorSpace").ok()?)?
+			};
+			let bits = if image_mask {
+				1
+			} else {
+				dict.get(b"BitsPerComponent")
+					.ok()
+					.and_then(|bits| integer(doc, bits))
+					.unwrap_or(8)
+			};
+			decode_samples(&data, width, height, bits, components, palette.as_deref())?
+		},
+	};
+	let mut image = image;
+	if inverted {
+		image::imageops::invert(&mut image);
+	}
+	Some(image)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColorSpace {
+	Gray,
+	Rgb,
+	Cmyk,
+	// palette indices, looked up in a palette of the given base color space
+	Indexed(Base),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Base {
+	Gray,
+	Rgb,
+	Cmyk,
+}
+
+impl Base {
+	fn components(self) -> usize {
+		match self {
+			Base::Gray => 1,
+			Base::Rgb => 3,
+			Base::Cmyk => 4,
+		}
+	}
+}
+
+/// Color space of an image along with its palette, for indexed images.
+fn color_space(doc: &Document, object: &Object) -> Option<(ColorSpace, Option<Vec<u8>>)> {
+	let color_space = match base_color_space(doc, object) {
+		Some(Base::Gray) => ColorSpace::Gray,
+		Some(Base::Rgb) => ColorSpace::Rgb,
+		Some(Base::Cmyk) => ColorSpace::Cmyk,
+		None => {
+			let array = resolve(doc, object).as_array().ok()?;
+			if array.first()?.as_name().ok()? != b"Indexed" {
+				return None;
+			}
+			let base = base_color_space(doc, array.get(1)?)?;
+			let palette = match resolve(doc, array.get(3)?) {
+				Object::String(bytes, _) => bytes.clone(),
+				Object::Stream(stream) => stream_data(stream),
+				_ => return None,
+			};
+			return Some((ColorSpace::Indexed(base), Some(palette)));
+		},
+	};
+	Some((color_space, None))
+}
+
+fn base_color_space(doc: &Document, object: &Object) -> Option<Base> {
+	match resolve(doc, object) {
+		Object::Name(name) => match name.as_slice() {
+			b"DeviceGray" | b"CalGray" | b"G" => Some(Base::Gray),
+			b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(Base::Rgb),
+			b"DeviceCMYK" | b"CMYK" => Some(Base::Cmyk),
+			_ => None,
+		},
+		Object::Array(array) => match array.first()?.as_name().ok()? {
+			b"CalGray" => Some(Base::Gray),
+			b"CalRGB" | b"Lab" => Some(Base::Rgb),
+			b"ICCBased" => {
+				let profile = resolve(doc, array.get(1)?).as_stream().ok()?;
+				match profile.dict.get(b"N").ok().and_then(|n| integer(doc, n))? {
+					1 => Some(Base::Gray),
+					3 => Some(Base::Rgb),
+					4 => Some(Base::Cmyk),
+					_ => None,
+				}
+			},
+			_ => None,
+		},
+		_ => None,
+	}
+}
+
+/// Converts packed samples of `bits` bits per component to grayscale.
+fn decode_samples(
+	data: &[u8],
+	width: usize,
+	height: usize,
+	bits: usize,
+	color_space: ColorSpace,
+	palette: Option<&[u8]>,
+) -> Option<GrayImage> {
+	if !matches!(bits, 1 | 2 | 4 | 8 | 16) {
+		return None;
+	}
+	let components = match color_space {
+		ColorSpace::Gray | ColorSpace::Indexed(_) => 1,
+		ColorSpace::Rgb => 3,
+		ColorSpace::Cmyk => 4,
+	};
+	let row_len = (width * components * bits).div_ceil(8);
+	if data.len() < row_len * height {
+		return None;
+	}
+	let max = (1u32 << bits.min(8)) - 1;
+	let sample = |row: &[u8], index: usize| -> u32 {
+		match bits {
+			16 => row[index * 2] as u32,
+			8 => row[index] as u32,
+			_ => {
+				let bit = index * bits;
+				(row[bit / 8] as u32 >> (8 - bits - bit % 8)) & max
+			},
+		}
+	};
+	let mut image = GrayImage::new(width as u32, height as u32);
+	for (y, row) in data.chunks(row_len).take(height).enumerate() {
+		for x in 0..width {
+			let value = |component: usize| sample(row, x * components + component);
+			let luma = match color_space {
+				ColorSpace::Gray => value(0) * 255 / max,
+				ColorSpace::Rgb =>
+					rgb_luma(value(0) * 255 / max, value(1) * 255 / max, value(2) * 255 / max),
+				ColorSpace::Cmyk => cmyk_luma([0, 1, 2, 3].map(|c| value(c) * 255 / max)),
+				ColorSpace::Indexed(base) => {
+					let palette = palette?;
+					let start = value(0) as usize * base.components();
+					let entry = palette.get(start..start + base.components())?;
+					match base {
+						Base::Gray => entry[0] as u32,
+						Base::Rgb => rgb_luma(entry[0] as u32, entry[1] as u32, entry[2] as u32),
+						Base::Cmyk => cmyk_luma([0, 1, 2, 3].map(|c| entry[c] as u32)),
+					}
+				},
+			};
+			image.put_pixel(x as u32, y as u32, Luma([luma as u8]));
+		}
+	}
+	Some(image)
+}
+
+fn rgb_luma(r: u32, g: u32, b: u32) -> u32 {
+	(r * 299 + g * 587 + b * 114) / 1000
+}
+
+fn cmyk_luma([c, m, y, k]: [u32; 4]) -> u32 {
+	let channel = |value: u32| 255 - (value + k).min(255);
+	rgb_luma(channel(c), channel(m), channel(y))
+}
+
+/// Decodes CCITT group 4 data, the usual compression of black and white scans.
+fn decode_ccitt(doc: &Document, stream: &Stream, width: usize, height: usize) -> Option<GrayImage> {
+	let params = stream
+		.dict
+		.get(b"DecodeParms")
+		.ok()
+		.map(|params| match resolve(doc, params) {
+			Object::Array(params) => params.first().map(|params| resolve(doc, params)),
+			params => Some(params),
+		})
+		.and_then(|params| params?.as_dict().ok());
+	let param = |key: &[u8]| params.and_then(|params| params.get(key).ok());
+	let k = param(b"K").and_then(|k| k.as_i64().ok()).unwrap_or(0);
+	if k >= 0 {
+		// group 3 is rare in PDF files
+		return None;
+	}
+	let black_is_1 = matches!(param(b"BlackIs1"), Some(Object::Boolean(true)));
+	let columns = param(b"Columns").and_then(|columns| integer(doc, columns)).unwrap_or(width);
+	let columns = u16::try_from(columns).ok()?;
+	let rows = u16::try_from(height).ok();
+
+	let mut image = GrayImage::from_pixel(width as u32, height as u32, Luma([255]));
+	let mut y = 0;
+	fax::decoder::decode_g4(stream.content.iter().copied(), columns, rows, |transitions| {
+		if y >= height {
+			return;
+		}
+		for (x, color) in fax::decoder::pels(transitions, columns).take(width).enumerate() {
+			// samples are 0 for black unless BlackIs1, and 0 paints black
+			let bit = (color == fax::Color::Black) == black_is_1;
+			if !bit {
+				image.put_pixel(x as u32, y as u32, Luma([0]));
+			}
+		}
+		y += 1;
+	})?;
+	Some(image)
+}
+
+/// Content of a stream, decompressed when it has a filter lopdf knows.
+fn stream_data(stream: &Stream) -> Vec<u8> {
+	stream.decompressed_content().unwrap_or_else(|_| stream.content.clone())
+}
+
+/// A key of a page, looked up through the page tree when the page does not set it.
+fn inherited<'a>(doc: &'a Document, page: &'a Dictionary, key: &[u8]) -> Option<&'a Object> {
+	let mut node = page;
+	for _ in 0..MAX_FORM_DEPTH * 4 {
+		if let Ok(value) = node.get(key) {
+			return Some(value);
+		}
+		node = dict(doc, node.get(b"Parent").ok()?)?;
+	}
+	None
+}
+
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+	match object {
+		Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+		_ => object,
+	}
+}
+
+fn dict<'a>(doc: &'a Document, object: &'a Object) -> Option<&'a Dictionary> {
+	resolve(doc, object).as_dict().ok()
+}
+
+fn number(doc: &Document, object: &Object) -> Option<f64> {
+	match resolve(doc, object) {
+		Object::Integer(value) => Some(*value as f64),
+		Object::Real(value) => Some(*value as f64),
+		_ => None,
+	}
+}
+
+fn integer(doc: &Document, object: &Object) -> Option<usize> {
+	number(doc, object).filter(|value| *value >= 0.0).map(|value| value as usize)
+}
+
+fn numbers(doc: &Document, object: &Object) -> Option<Vec<f64>> {
+	resolve(doc, object)
+		.as_array()
+		.ok()?
+		.iter()
+		.map(|value| number(doc, value))
+		.collect()
+}
+
+/// Operands of an operator, which are never references.
+fn numbers_of(operands: &[Object]) -> Option<Vec<f64>> {
+	operands
+		.iter()
+		.map(|operand| match operand {
+			Object::Integer(value) => Some(*value as f64),
+			Object::Real(value) => Some(*value as f64),
+			_ => None,
+		})
+		.collect()
+}
+
+/// Product of two transformation matrices, `m` applied first.
+fn multiply(m: &Matrix, n: &Matrix) -> Matrix {
+	[
+		m[0] * n[0] + m[1] * n[2],
+		m[0] * n[1] + m[1] * n[3],
+		m[2] * n[0] + m[3] * n[2],
+		m[2] * n[1] + m[3] * n[3],
+		m[4] * n[0] + m[5] * n[2] + n[4],
+		m[4] * n[1] + m[5] * n[3] + n[5],
+	]
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use lopdf::{
+		content::{Content, Operation},
+		dictionary,
+	};
+
+	#[test]
+	fn draws_images_where_the_page_places_them() {
+		let mut doc = Document::with_version("1.5");
+		let pages_id = doc.new_object_id();
+		let image_id = doc.add_object(Stream::new(
+			dictionary! {
+				"Type" => "XObject",
+				"Subtype" => "Image",
+				"Width" => 2,
+				"Height" => 2,
+				"ColorSpace" => "DeviceGray",
+				"BitsPerComponent" => 8,
+			},
+			vec![0, 255, 255, 0],
+		));
+		let content = Content {
+			operations: vec![
+				Operation::new("q", vec![]),
+				Operation::new(
+					"cm",
+					vec![36.into(), 0.into(), 0.into(), 36.into(), 0.into(), 0.into()],
+				),
+				Operation::new("Do", vec!["Im0".into()]),
+				Operation::new("Q", vec![]),
+			],
+		};
+		let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+		let page_id = doc.add_object(dictionary! {
+			"Type" => "Page",
+			"Parent" => pages_id,
+			"Contents" => content_id,
+			"Resources" => dictionary! { "XObject" => dictionary! { "Im0" => image_id } },
+		});
+		doc.objects.insert(
+			pages_id,
+			Object::Dictionary(dictionary! {
+				"Type" => "Pages",
+				"Kids" => vec![page_id.into()],
+				"Count" => 1,
+				"MediaBox" => vec![0.into(), 0.into(), 72.into(), 72.into()],
+			}),
+		);
+
+		let page = rasterize_page(&doc, page_id).unwrap().to_luma8();
+		assert_eq!(page.dimensions(), (200, 200));
+		// the image fills the bottom left quarter, its first row on top
+		assert_eq!(page.get_pixel(25, 125)[0], 0);
+		assert_eq!(page.get_pixel(75, 125)[0], 255);
+		assert_eq!(page.get_pixel(25, 175)[0], 255);
+		assert_eq!(page.get_pixel(75, 175)[0], 0);
+		assert_eq!(page.get_pixel(150, 50)[0], 255);
+	}
+
+	#[test]
+	fn skips_images_too_large_to_size() {
+		let doc = Document::with_version("1.5");
+		let stream = Stream::new(
+			dictionary! {
+				"Type" => "XObject",
+				"Subtype" => "Image",
+				"Width" => 1i64 << 32,
+				"Height" => 1i64 << 32,
+				"ColorSpace" => "DeviceGray",
+				"BitsPerComponent" => 8,
+			},
+			vec![0],
+		);
+		assert!(decode_image(&doc, &stream).is_none());
+	}
+
+	#[test]
+	fn decodes_packed_samples() {
+		let image = decode_samples(&[0b1010_0000], 3, 1, 1, ColorSpace::Gray, None).unwrap();
+		let pixels: Vec<u8> = image.pixels().map(|pixel| pixel[0]).collect();
+		assert_eq!(pixels, vec![255, 0, 255]);
+		let image = decode_samples(&[255, 0, 0], 1, 1, 8, ColorSpace::Rgb, None).unwrap();
+		assert_eq!(image.get_pixel(0, 0)[0], 76);
+	}
+}
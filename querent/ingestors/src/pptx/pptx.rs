@@ -86,6 +86,9 @@ impl BaseIngestor for PptxIngestor {
 						is_token_stream: false,
 						source_id: source_id.clone(),
 						image_id: None,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
 					};
 					yield Ok(ingested_tokens);
 				}
@@ -110,6 +113,7 @@ impl BaseIngestor for PptxIngestor {
 							size: Some(img_data.len()),
 							source_id: source_id.clone(),
 							_owned_permit: None,
+							content_type: None,
 							image_id: Some(image_id.to_string()),
 						};
 						let image_ingestor = ImageIngestor::new();
@@ -142,6 +146,9 @@ impl BaseIngestor for PptxIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				});
 			}
 		}
@@ -152,6 +159,9 @@ impl BaseIngestor for PptxIngestor {
 			is_token_stream: false,
 			source_id: source_id.clone(),
 			image_id: None,
+			section_path: None,
+			table: None,
+			duplicate_of: None,
 		})
 		};
 
@@ -183,6 +193,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -228,6 +239,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
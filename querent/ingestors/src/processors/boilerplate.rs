@@ -0,0 +1,140 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use proto::semantics::IngestedTokens;
+use regex::Regex;
+use std::{collections::HashMap, sync::Mutex};
+
+use crate::{
+	processors::registry::{param, ProcessorParams},
+	AsyncProcessor, IngestorResult,
+};
+
+/// Lines longer than this are body text, never headers or footers.
+const MAX_BOILERPLATE_LINE: usize = 120;
+/// Documents whose lines are counted at once; the counts start over past it.
+const MAX_TRACKED_DOCUMENTS: usize = 256;
+
+static PAGE_NUMBER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?i)^[-–\s]*(page\s*)?\d+(\s*(of|/)\s*\d+)?[-–\s]*$").unwrap());
+
+/// Removes headers and footers: lines repeated across the chunks of a document, such as a
+/// running title or a confidentiality notice, and page numbers.
+///
+/// A line counts as repeated once it was seen in `min_repeats` chunks of the same document
+/// (defaults to 3), numbers aside, so that `Page 3` and `Page 4` match. Its first occurrences
+/// are already gone downstream by then. Set `page_numbers` to false to keep page numbers.
+/// Runs on lines, so list it before `cleanup`, which joins them.
+pub struct BoilerplateProcessor {
+	min_repeats: usize,
+	page_numbers: bool,
+	// occurrences of the lines of each document, by file
+	line_counts: Mutex<HashMap<String, HashMap<String, usize>>>,
+}
+
+impl BoilerplateProcessor {
+	pub fn new(params: &ProcessorParams) -> IngestorResult<Self> {
+		Ok(Self {
+			min_repeats: param(params, "min_repeats", 3)?,
+			page_numbers: param(params, "page_numbers", true)?,
+			line_counts: Mutex::new(HashMap::new()),
+		})
+	}
+
+	fn remove_boilerplate(&self, file: &str, text: &str) -> String {
+		let mut documents = self.line_counts.lock().unwrap();
+		if documents.len() >= MAX_TRACKED_DOCUMENTS && !documents.contains_key(file) {
+			documents.clear();
+		}
+		let line_counts = documents.entry(file.to_string()).or_default();
+		let mut seen = Vec::new();
+		let mut kept = Vec::new();
+		for line in text.lines() {
+			let trimmed = line.trim();
+			if trimmed.is_empty() || trimmed.chars().count() > MAX_BOILERPLATE_LINE {
+				kept.push(line);
+				continue;
+			}
+			if self.page_numbers && PAGE_NUMBER.is_match(trimmed) {
+				continue;
+			}
+			let key: String =
+				trimmed.chars().map(|c| if c.is_ascii_digit() { '#' } else { c }).collect();
+			// count a line once per chunk
+			if !seen.contains(&key) {
+				*line_counts.entry(key.clone()).or_default() += 1;
+				seen.push(key.clone());
+			}
+			if line_counts[&key] < self.min_repeats {
+				kept.push(line);
+			}
+		}
+		kept.join("\n")
+	}
+}
+
+#[async_trait]
+impl AsyncProcessor for BoilerplateProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		// tables carry their cells separately, their text is left alone
+		if data.table.is_some() {
+			return Ok(data);
+		}
+		let file = data.file.clone();
+		for text in data.data.iter_mut() {
+			*text = self.remove_boilerplate(&file, text);
+		}
+		Ok(data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn removes_repeated_headers_and_page_numbers() {
+		let processor = BoilerplateProcessor::new(&HashMap::from([(
+			"min_repeats".to_string(),
+			"2".to_string(),
+		)]))
+		.unwrap();
+		let mut pages = Vec::new();
+		for (page, section) in ["Introduction", "Methods", "Results"].iter().enumerate() {
+			let page = page + 1;
+			let tokens = IngestedTokens {
+				data: vec![format!("ACME Corp - Confidential {page}\n{section}\nPage {page} of 3")],
+				file: "report.pdf".to_string(),
+				..Default::default()
+			};
+			pages.push(processor.process_text(tokens).await.unwrap().data.remove(0));
+		}
+		assert_eq!(pages[0], "ACME Corp - Confidential 1\nIntroduction");
+		assert_eq!(pages[2], "Results");
+		let other = IngestedTokens {
+			data: vec!["ACME Corp - Confidential 1".to_string()],
+			file: "other.pdf".to_string(),
+			..Default::default()
+		};
+		assert_eq!(
+			processor.process_text(other).await.unwrap().data[0],
+			"ACME Corp - Confidential 1"
+		);
+	}
+}
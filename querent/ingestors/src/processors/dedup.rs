@@ -0,0 +1,112 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_trait::async_trait;
+use proto::semantics::IngestedTokens;
+use std::{
+	collections::{hash_map::DefaultHasher, HashSet},
+	hash::{Hash, Hasher},
+	sync::Mutex,
+};
+
+use crate::{
+	processors::registry::{invalid_config, param, ProcessorParams},
+	AsyncProcessor, IngestorResult,
+};
+
+/// Drops texts already seen, compared case and whitespace aside.
+///
+/// `scope` is `pipeline` (the default) to drop repeats across all documents of the pipeline,
+/// or `document` to drop them within a document only. At most `max_entries` texts are
+/// remembered (defaults to 1,000,000); past it the processor starts over.
+pub struct DedupProcessor {
+	per_document: bool,
+	max_entries: usize,
+	seen: Mutex<HashSet<u64>>,
+}
+
+impl DedupProcessor {
+	pub fn new(params: &ProcessorParams) -> IngestorResult<Self> {
+		let per_document = match params.get("scope").map(String::as_str) {
+			None | Some("pipeline") => false,
+			Some("document") => true,
+			Some(scope) => return Err(invalid_config(format!("Unknown scope `{}`", scope))),
+		};
+		Ok(Self {
+			per_document,
+			max_entries: param(params, "max_entries", 1_000_000)?,
+			seen: Mutex::new(HashSet::new()),
+		})
+	}
+
+	fn fingerprint(&self, file: &str, text: &str) -> u64 {
+		let mut hasher = DefaultHasher::new();
+		if self.per_document {
+			file.hash(&mut hasher);
+		}
+		for word in text.split_whitespace() {
+			word.to_lowercase().hash(&mut hasher);
+		}
+		hasher.finish()
+	}
+}
+
+#[async_trait]
+impl AsyncProcessor for DedupProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		let mut seen = self.seen.lock().unwrap();
+		let file = data.file.clone();
+		data.data.retain(|text| {
+			if text.trim().is_empty() {
+				return true;
+			}
+			if seen.len() >= self.max_entries {
+				seen.clear();
+			}
+			seen.insert(self.fingerprint(&file, text))
+		});
+		Ok(data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::collections::HashMap;
+
+	fn tokens(file: &str, text: &str) -> IngestedTokens {
+		IngestedTokens {
+			data: vec![text.to_string()],
+			file: file.to_string(),
+			..Default::default()
+		}
+	}
+
+	#[tokio::test]
+	async fn drops_repeated_texts_within_their_scope() {
+		let pipeline = DedupProcessor::new(&HashMap::new()).unwrap();
+		assert_eq!(pipeline.process_text(tokens("a", "Same  text")).await.unwrap().data.len(), 1);
+		assert!(pipeline.process_text(tokens("b", "same text")).await.unwrap().data.is_empty());
+
+		let document =
+			DedupProcessor::new(&HashMap::from([("scope".to_string(), "document".to_string())]))
+				.unwrap();
+		assert_eq!(document.process_text(tokens("a", "Same text")).await.unwrap().data.len(), 1);
+		assert_eq!(document.process_text(tokens("b", "Same text")).await.unwrap().data.len(), 1);
+		assert!(document.process_text(tokens("b", "Same text")).await.unwrap().data.is_empty());
+	}
+}
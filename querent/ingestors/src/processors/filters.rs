@@ -0,0 +1,157 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_trait::async_trait;
+use proto::semantics::IngestedTokens;
+use regex::Regex;
+use whatlang::Lang;
+
+use crate::{
+	processors::registry::{invalid_config, param, ProcessorParams},
+	AsyncProcessor, IngestorResult,
+};
+
+/// Drops texts shorter than `min_chars` characters, whitespace aside. Defaults to 20.
+pub struct MinLengthProcessor {
+	min_chars: usize,
+}
+
+impl MinLengthProcessor {
+	pub fn new(params: &ProcessorParams) -> IngestorResult<Self> {
+		Ok(Self { min_chars: param(params, "min_chars", 20)? })
+	}
+}
+
+#[async_trait]
+impl AsyncProcessor for MinLengthProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		data.data
+			.retain(|text| text.chars().filter(|c| !c.is_whitespace()).count() >= self.min_chars);
+		Ok(data)
+	}
+}
+
+/// Keeps the texts written in one of `languages`, ISO 639-3 codes separated by commas such as
+/// `eng,deu`. Texts whose language is detected with less than `min_confidence` (0-1, defaults
+/// to 0.5) are kept, short texts mostly.
+pub struct LanguageFilterProcessor {
+	languages: Vec<Lang>,
+	min_confidence: f64,
+}
+
+impl LanguageFilterProcessor {
+	pub fn new(params: &ProcessorParams) -> IngestorResult<Self> {
+		let languages = params
+			.get("languages")
+			.ok_or_else(|| invalid_config("`languages` is required".to_string()))?
+			.split(',')
+			.map(str::trim)
+			.filter(|code| !code.is_empty())
+			.map(|code| {
+				Lang::from_code(code.to_lowercase())
+					.ok_or_else(|| invalid_config(format!("Unknown language code `{}`", code)))
+			})
+			.collect::<IngestorResult<Vec<_>>>()?;
+		if languages.is_empty() {
+			return Err(invalid_config("`languages` is empty".to_string()));
+		}
+		Ok(Self { languages, min_confidence: param(params, "min_confidence", 0.5)? })
+	}
+
+	fn keeps(&self, text: &str) -> bool {
+		match whatlang::detect(text) {
+			Some(info) if info.confidence() >= self.min_confidence =>
+				self.languages.contains(&info.lang()),
+			_ => true,
+		}
+	}
+}
+
+#[async_trait]
+impl AsyncProcessor for LanguageFilterProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		data.data.retain(|text| self.keeps(text));
+		Ok(data)
+	}
+}
+
+/// Replaces the matches of `pattern` with `replacement`, which may refer to groups as `$1`.
+/// Removes the matches when `replacement` is not set.
+pub struct RegexReplaceProcessor {
+	pattern: Regex,
+	replacement: String,
+}
+
+impl RegexReplaceProcessor {
+	pub fn new(params: &ProcessorParams) -> IngestorResult<Self> {
+		let pattern = params
+			.get("pattern")
+			.ok_or_else(|| invalid_config("`pattern` is required".to_string()))?;
+		let pattern = Regex::new(pattern)
+			.map_err(|e| invalid_config(format!("Invalid pattern `{}`: {}", pattern, e)))?;
+		Ok(Self { pattern, replacement: params.get("replacement").cloned().unwrap_or_default() })
+	}
+}
+
+#[async_trait]
+impl AsyncProcessor for RegexReplaceProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		for text in data.data.iter_mut() {
+			if let std::borrow::Cow::Owned(replaced) =
+				self.pattern.replace_all(text, self.replacement.as_str())
+			{
+				*text = replaced;
+			}
+		}
+		Ok(data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn params(params: &[(&str, &str)]) -> ProcessorParams {
+		params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+	}
+
+	fn tokens(texts: &[&str]) -> IngestedTokens {
+		IngestedTokens {
+			data: texts.iter().map(|text| text.to_string()).collect(),
+			..Default::default()
+		}
+	}
+
+	#[tokio::test]
+	async fn filters_by_length_and_language() {
+		let min_length = MinLengthProcessor::new(&params(&[("min_chars", "5")])).unwrap();
+		let filtered = min_length.process_text(tokens(&["a b c", "long enough"])).await.unwrap();
+		assert_eq!(filtered.data, vec!["long enough".to_string()]);
+
+		let language = LanguageFilterProcessor::new(&params(&[("languages", "eng")])).unwrap();
+		let filtered = language
+			.process_text(tokens(&[
+				"The drilling report describes the formation pressure measured in the well.",
+				"Der Bohrbericht beschreibt den im Bohrloch gemessenen Formationsdruck sehr genau.",
+			]))
+			.await
+			.unwrap();
+		assert_eq!(filtered.data.len(), 1);
+		assert!(filtered.data[0].starts_with("The drilling report"));
+		assert!(LanguageFilterProcessor::new(&params(&[("languages", "klingon")])).is_err());
+	}
+}
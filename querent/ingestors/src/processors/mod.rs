@@ -16,4 +16,9 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
+pub mod boilerplate;
+pub mod dedup;
+pub mod filters;
+pub mod pii;
+pub mod registry;
 pub mod text_processing;
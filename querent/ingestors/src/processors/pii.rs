@@ -0,0 +1,468 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_trait::async_trait;
+use llms::LLM;
+use once_cell::sync::Lazy;
+use proto::semantics::IngestedTokens;
+use regex::{Captures, Regex};
+use sha2::{Digest, Sha256};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+
+use crate::{
+	processors::registry::{invalid_config, param, ProcessorContext, ProcessorParams},
+	AsyncProcessor, IngestorResult,
+};
+
+/// Words given to the NER model at once, well within the token limit of BERT models.
+const NER_WINDOW_WORDS: usize = 128;
+
+static EMAIL: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b").unwrap()
+});
+static IBAN: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,3})?\b").unwrap()
+});
+// US social security numbers and UK national insurance numbers
+static NATIONAL_ID: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"\b(?:\d{3}-\d{2}-\d{4}|[A-CEGHJ-PR-TW-Z]{2} ?\d{2} ?\d{2} ?\d{2} ?[A-D])\b")
+		.unwrap()
+});
+static PHONE: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r"(?:\+\d{1,3}[ .-]?)?(?:\(\d{1,4}\)[ .-]?)?\d{2,4}(?:[ .-]\d{2,4}){1,4}\b").unwrap()
+});
+
+/// Kind of personal data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PiiKind {
+	Email,
+	Iban,
+	NationalId,
+	Phone,
+	Person,
+}
+
+impl PiiKind {
+	const ALL: [PiiKind; 5] =
+		[PiiKind::Email, PiiKind::Iban, PiiKind::NationalId, PiiKind::Phone, PiiKind::Person];
+
+	pub fn name(self) -> &'static str {
+		match self {
+			PiiKind::Email => "email",
+			PiiKind::Iban => "iban",
+			PiiKind::NationalId => "national_id",
+			PiiKind::Phone => "phone",
+			PiiKind::Person => "person",
+		}
+	}
+
+	fn label(self) -> &'static str {
+		match self {
+			PiiKind::Email => "Email",
+			PiiKind::Iban => "Iban",
+			PiiKind::NationalId => "NationalId",
+			PiiKind::Phone => "Phone",
+			PiiKind::Person => "Person",
+		}
+	}
+
+	/// Form of a value compared when hashing, so that formatting does not change its token.
+	fn normalize(self, value: &str) -> String {
+		match self {
+			PiiKind::Email => value.to_lowercase(),
+			PiiKind::Person =>
+				value.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase(),
+			_ => value
+				.chars()
+				.filter(|c| c.is_ascii_alphanumeric())
+				.collect::<String>()
+				.to_uppercase(),
+		}
+	}
+}
+
+/// What personal data is replaced with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RedactionMode {
+	/// `[EMAIL]`
+	Redact,
+	/// `[EMAIL:3fa9c2d1e0ab]`, a salted hash of the value
+	Hash,
+	/// `Email_QKZTRM`, a salted hash spelled as a name, which the engine still takes for an
+	/// entity
+	Pseudonymize,
+}
+
+/// Counts of the personal data redacted from each document, by kind, until the pipeline
+/// reports them with the state of the document.
+#[derive(Clone, Default)]
+pub struct RedactionAudit {
+	counts: Arc<Mutex<HashMap<String, HashMap<String, u32>>>>,
+}
+
+impl RedactionAudit {
+	fn record(&self, document: &str, counts: &HashMap<PiiKind, u32>) {
+		if counts.is_empty() {
+			return;
+		}
+		let mut documents = self.counts.lock().unwrap();
+		let document = documents.entry(document.to_string()).or_default();
+		for (kind, count) in counts {
+			*document.entry(kind.name().to_string()).or_default() += count;
+		}
+	}
+
+	/// Takes the counts recorded for `document`.
+	pub fn take(&self, document: &str) -> HashMap<String, u32> {
+		self.counts.lock().unwrap().remove(document).unwrap_or_default()
+	}
+}
+
+/// Detects personal data, with regular expressions and the NER model of the pipeline for
+/// names, and replaces it according to `mode`: `redact` (the default), `hash` or
+/// `pseudonymize`.
+///
+/// Hashes and pseudonyms are derived from `salt` and the value only, so a person maps to the
+/// same token in every document and pipeline sharing the salt. Both modes require a salt, which
+/// should be kept secret: phone numbers and IDs are few enough to be recovered from unsalted
+/// hashes. `kinds` restricts detection to some of email, iban, national_id, phone and person,
+/// separated by commas. `ner` set to false leaves names alone when the NER model is too slow.
+pub struct PiiProcessor {
+	kinds: Vec<PiiKind>,
+	mode: RedactionMode,
+	salt: String,
+	ner_llm: Option<Arc<dyn LLM>>,
+	audit: RedactionAudit,
+}
+
+impl PiiProcessor {
+	pub fn new(params: &ProcessorParams, context: &ProcessorContext) -> IngestorResult<Self> {
+		let mode = match params.get("mode").map(String::as_str) {
+			None | Some("redact") => RedactionMode::Redact,
+			Some("hash") => RedactionMode::Hash,
+			Some("pseudonymize") => RedactionMode::Pseudonymize,
+			Some(mode) => return Err(invalid_config(format!("Unknown mode `{}`", mode))),
+		};
+		let kinds = match params.get("kinds") {
+			Some(kinds) => kinds
+				.split(',')
+				.map(str::trim)
+				.filter(|kind| !kind.is_empty())
+				.map(|kind| {
+					PiiKind::ALL
+						.into_iter()
+						.find(|known| known.name() == kind)
+						.ok_or_else(|| invalid_config(format!("Unknown kind `{}`", kind)))
+				})
+				.collect::<IngestorResult<Vec<_>>>()?,
+			None => PiiKind::ALL.to_vec(),
+		};
+		let salt = params.get("salt").cloned().unwrap_or_default();
+		if mode != RedactionMode::Redact && salt.is_empty() {
+			return Err(invalid_config(
+				"The hash and pseudonymize modes require a salt".to_string(),
+			));
+		}
+		let ner_llm = if param(params, "ner", true)? { context.ner_llm.clone() } else { None };
+		if kinds.contains(&PiiKind::Person) && ner_llm.is_none() {
+			tracing::warn!("No NER model for the PII processor: Names are not redacted");
+		}
+		Ok(Self { kinds, mode, salt, ner_llm, audit: context.redaction_audit.clone() })
+	}
+
+	fn replacement(&self, kind: PiiKind, value: &str) -> String {
+		let digest = || {
+			let mut hasher = Sha256::new();
+			hasher.update(self.salt.as_bytes());
+			hasher.update([0]);
+			hasher.update(kind.name().as_bytes());
+			hasher.update([0]);
+			hasher.update(kind.normalize(value).as_bytes());
+			hasher.finalize()
+		};
+		match self.mode {
+			RedactionMode::Redact => format!("[{}]", kind.name().to_uppercase()),
+			RedactionMode::Hash => {
+				let hex: String =
+					digest()[..6].iter().map(|byte| format!("{:02x}", byte)).collect();
+				format!("[{}:{}]", kind.name().to_uppercase(), hex)
+			},
+			RedactionMode::Pseudonymize => {
+				// letters only, so that no pattern matches a pseudonym again
+				let letters: String =
+					digest()[..6].iter().map(|byte| (b'A' + byte % 26) as char).collect();
+				format!("{}_{}", kind.label(), letters)
+			},
+		}
+	}
+
+	/// Replaces the personal data found by regular expressions, the most specific patterns
+	/// first so that IBANs and IDs are not taken for phone numbers.
+	fn redact_patterns(&self, text: &str, counts: &mut HashMap<PiiKind, u32>) -> String {
+		let mut text = text.to_string();
+		for kind in &self.kinds {
+			let (pattern, valid): (&Regex, fn(&str) -> bool) = match kind {
+				PiiKind::Email => (&*EMAIL, |_| true),
+				PiiKind::Iban => (&*IBAN, valid_iban),
+				PiiKind::NationalId => (&*NATIONAL_ID, valid_national_id),
+				PiiKind::Phone => (&*PHONE, valid_phone),
+				PiiKind::Person => continue,
+			};
+			text = pattern
+				.replace_all(&text, |captures: &Captures| {
+					let value = &captures[0];
+					if !valid(value) {
+						return value.to_string();
+					}
+					*counts.entry(*kind).or_default() += 1;
+					self.replacement(*kind, value)
+				})
+				.into_owned();
+		}
+		text
+	}
+
+	/// Names of people the NER model finds in `text`.
+	async fn find_people(&self, text: &str) -> Vec<String> {
+		let Some(ner_llm) = &self.ner_llm else {
+			return Vec::new();
+		};
+		if !self.kinds.contains(&PiiKind::Person) {
+			return Vec::new();
+		}
+		let words: Vec<&str> = text.split_whitespace().collect();
+		let mut people = Vec::new();
+		for window in words.chunks(NER_WINDOW_WORDS) {
+			let classified = match classify(ner_llm.as_ref(), &window.join(" ")).await {
+				Ok(classified) => classified,
+				Err(e) => {
+					tracing::error!("Failed to find names with the NER model: {:?}", e);
+					continue;
+				},
+			};
+			for person in people_in(&classified) {
+				if !people.contains(&person) {
+					people.push(person);
+				}
+			}
+		}
+		people
+	}
+
+	fn redact_people(
+		&self,
+		text: &str,
+		people: &[String],
+		counts: &mut HashMap<PiiKind, u32>,
+	) -> String {
+		if people.is_empty() {
+			return text.to_string();
+		}
+		let mut names: Vec<&String> = people.iter().collect();
+		// longer names first, so that a full name is not cut by a first name
+		names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+		let alternatives: Vec<String> = names.iter().map(|name| regex::escape(name)).collect();
+		let Ok(pattern) = Regex::new(&format!(r"\b(?:{})\b", alternatives.join("|"))) else {
+			return text.to_string();
+		};
+		pattern
+			.replace_all(text, |captures: &Captures| {
+				*counts.entry(PiiKind::Person).or_default() += 1;
+				self.replacement(PiiKind::Person, &captures[0])
+			})
+			.into_owned()
+	}
+}
+
+async fn classify(ner_llm: &dyn LLM, text: &str) -> llms::LLMResult<Vec<(String, String)>> {
+	let tokens = ner_llm.tokenize(text).await?;
+	let model_input = ner_llm.model_input(tokens).await?;
+	ner_llm.token_classification(model_input, None).await
+}
+
+/// Joins the words the NER model labels as people (`B-PER`, `I-PER`) into names.
+fn people_in(classified: &[(String, String)]) -> Vec<String> {
+	let mut people = Vec::new();
+	let mut current: Option<String> = None;
+	for (word, label) in classified {
+		if !label.ends_with("PER") {
+			people.extend(current.take());
+			continue;
+		}
+		let piece = word.strip_prefix("##");
+		match current.as_mut() {
+			Some(name) if !label.starts_with("B-") => match piece {
+				Some(piece) => name.push_str(piece),
+				None => {
+					name.push(' ');
+					name.push_str(word);
+				},
+			},
+			_ => {
+				people.extend(current.take());
+				current = Some(piece.unwrap_or(word).to_string());
+			},
+		}
+	}
+	people.extend(current);
+	people.retain(|name| name.chars().filter(|c| c.is_alphabetic()).count() >= 2);
+	people
+}
+
+fn valid_iban(value: &str) -> bool {
+	let iban: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+	if !(15..=34).contains(&iban.len()) {
+		return false;
+	}
+	// the mod 97 check of ISO 13616, on the country and check digits moved to the end
+	let (head, tail) = iban.split_at(4);
+	let mut remainder = 0u32;
+	for c in tail.chars().chain(head.chars()) {
+		let Some(digit) = c.to_digit(36) else {
+			return false;
+		};
+		remainder = if digit < 10 { remainder * 10 + digit } else { remainder * 100 + digit };
+		remainder %= 97;
+	}
+	remainder == 1
+}
+
+fn valid_national_id(value: &str) -> bool {
+	match value.split('-').collect::<Vec<_>>()[..] {
+		// social security numbers never start with 000, 666 or 9
+		[area, group, serial] =>
+			area != "000" &&
+				area != "666" &&
+				!area.starts_with('9') &&
+				group != "00" &&
+				serial != "0000",
+		_ => true,
+	}
+}
+
+fn valid_phone(value: &str) -> bool {
+	let digits = value.chars().filter(char::is_ascii_digit).count();
+	// 8 digits are more often dates, such as 2024-03-01, than local numbers
+	(9..=15).contains(&digits) || (value.starts_with('+') && digits >= 7)
+}
+
+#[async_trait]
+impl AsyncProcessor for PiiProcessor {
+	async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+		let mut counts = HashMap::new();
+		let mut all_people = Vec::new();
+		for text in data.data.iter_mut() {
+			let people = self.find_people(text).await;
+			let redacted = self.redact_patterns(text, &mut counts);
+			*text = self.redact_people(&redacted, &people, &mut counts);
+			all_people.extend(people);
+		}
+		// cells repeat the text of the table, their redactions are not counted twice
+		if let Some(table) = data.table.as_mut() {
+			let mut cell_counts = HashMap::new();
+			let cells = table
+				.headers
+				.iter_mut()
+				.chain(table.rows.iter_mut().flat_map(|row| row.cells.iter_mut()));
+			for cell in cells {
+				let redacted = self.redact_patterns(cell, &mut cell_counts);
+				*cell = self.redact_people(&redacted, &all_people, &mut cell_counts);
+			}
+		}
+		self.audit.record(&data.file, &counts);
+		Ok(data)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn processor(params: &[(&str, &str)], audit: &RedactionAudit) -> PiiProcessor {
+		let params = params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+		let context = ProcessorContext { ner_llm: None, redaction_audit: audit.clone() };
+		PiiProcessor::new(&params, &context).unwrap()
+	}
+
+	fn tokens(file: &str, text: &str) -> IngestedTokens {
+		IngestedTokens {
+			data: vec![text.to_string()],
+			file: file.to_string(),
+			..Default::default()
+		}
+	}
+
+	#[tokio::test]
+	async fn redacts_patterns_and_audits_documents() {
+		let audit = RedactionAudit::default();
+		let processor = processor(&[], &audit);
+		let text = "Mail jane.doe@example.com or call +1 415 555 2671 before 2024-03-01. \
+			Pay DE89 3704 0044 0532 0130 00, not DE00 1234 5678 9012 3456 78. SSN 123-45-6789.";
+		let redacted = processor.process_text(tokens("ticket.eml", text)).await.unwrap().data;
+		assert_eq!(
+			redacted[0],
+			"Mail [EMAIL] or call [PHONE] before 2024-03-01. \
+			Pay [IBAN], not DE00 1234 5678 9012 3456 78. SSN [NATIONAL_ID]."
+		);
+		let counts = audit.take("ticket.eml");
+		assert_eq!(counts.len(), 4);
+		assert!(counts.values().all(|count| *count == 1));
+		assert!(audit.take("ticket.eml").is_empty());
+	}
+
+	#[tokio::test]
+	async fn maps_values_to_the_same_token_across_documents() {
+		let audit = RedactionAudit::default();
+		let processor = processor(&[("mode", "pseudonymize"), ("salt", "secret")], &audit);
+		let first = processor.process_text(tokens("a.eml", "From Jane.Doe@example.com")).await;
+		let second = processor.process_text(tokens("b.eml", "To jane.doe@example.com")).await;
+		let first = first.unwrap().data.remove(0);
+		let second = second.unwrap().data.remove(0);
+		assert!(first.starts_with("From Email_"));
+		assert_eq!(first.trim_start_matches("From "), second.trim_start_matches("To "));
+	}
+
+	#[test]
+	fn requires_a_salt_to_hash() {
+		let context =
+			ProcessorContext { ner_llm: None, redaction_audit: RedactionAudit::default() };
+		for mode in ["hash", "pseudonymize"] {
+			let params = HashMap::from([("mode".to_string(), mode.to_string())]);
+			assert!(PiiProcessor::new(&params, &context).is_err());
+		}
+	}
+
+	#[test]
+	fn joins_person_labels_into_names() {
+		let classified: Vec<(String, String)> = [
+			("Joel", "B-PER"),
+			("Smith", "I-PER"),
+			("met", "O"),
+			("Ann", "B-PER"),
+			("##a", "I-PER"),
+			("at", "O"),
+			("IBM", "B-ORG"),
+		]
+		.iter()
+		.map(|(word, label)| (word.to_string(), label.to_string()))
+		.collect();
+		assert_eq!(people_in(&classified), vec!["Joel Smith".to_string(), "Anna".to_string()]);
+	}
+}
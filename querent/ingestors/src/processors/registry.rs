@@ -0,0 +1,200 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use llms::LLM;
+use once_cell::sync::Lazy;
+use proto::semantics::ProcessorConfig;
+use std::{
+	collections::HashMap,
+	str::FromStr,
+	sync::{Arc, RwLock},
+};
+
+use crate::{
+	processors::{
+		boilerplate::BoilerplateProcessor,
+		dedup::DedupProcessor,
+		filters::{LanguageFilterProcessor, MinLengthProcessor, RegexReplaceProcessor},
+		pii::{PiiProcessor, RedactionAudit},
+		text_processing::TextCleanupProcessor,
+	},
+	AsyncProcessor, IngestorError, IngestorErrorKind, IngestorResult,
+};
+
+/// Parameters of a processor, as declared in the pipeline request.
+pub type ProcessorParams = HashMap<String, String>;
+
+/// Resources of the pipeline processors may use.
+#[derive(Clone, Default)]
+pub struct ProcessorContext {
+	/// Token classification model, for processors looking for named entities.
+	pub ner_llm: Option<Arc<dyn LLM>>,
+	/// Counts of the PII redacted from each document.
+	pub redaction_audit: RedactionAudit,
+}
+
+/// Creates a processor from its parameters.
+pub type ProcessorFactory = Arc<
+	dyn Fn(&ProcessorParams, &ProcessorContext) -> IngestorResult<Arc<dyn AsyncProcessor>>
+		+ Send
+		+ Sync,
+>;
+
+static PROCESSOR_REGISTRY: Lazy<RwLock<HashMap<String, ProcessorFactory>>> = Lazy::new(|| {
+	let mut factories: HashMap<String, ProcessorFactory> = HashMap::new();
+	factories.insert(
+		"cleanup".to_string(),
+		Arc::new(|_: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(TextCleanupProcessor::new()) as _)
+		}),
+	);
+	factories.insert(
+		"boilerplate".to_string(),
+		Arc::new(|params: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(BoilerplateProcessor::new(params)?) as _)
+		}),
+	);
+	factories.insert(
+		"language".to_string(),
+		Arc::new(|params: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(LanguageFilterProcessor::new(params)?) as _)
+		}),
+	);
+	factories.insert(
+		"min_length".to_string(),
+		Arc::new(|params: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(MinLengthProcessor::new(params)?) as _)
+		}),
+	);
+	factories.insert(
+		"regex_replace".to_string(),
+		Arc::new(|params: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(RegexReplaceProcessor::new(params)?) as _)
+		}),
+	);
+	factories.insert(
+		"dedup".to_string(),
+		Arc::new(|params: &ProcessorParams, _: &ProcessorContext| {
+			Ok(Arc::new(DedupProcessor::new(params)?) as _)
+		}),
+	);
+	factories.insert(
+		"pii".to_string(),
+		Arc::new(|params: &ProcessorParams, context: &ProcessorContext| {
+			Ok(Arc::new(PiiProcessor::new(params, context)?) as _)
+		}),
+	);
+	RwLock::new(factories)
+});
+
+/// Makes a processor available to pipeline requests under `name`, replacing any processor
+/// registered under it.
+pub fn register_processor(name: &str, factory: ProcessorFactory) {
+	PROCESSOR_REGISTRY.write().unwrap().insert(name.to_string(), factory);
+}
+
+/// Names of the registered processors.
+pub fn registered_processors() -> Vec<String> {
+	let mut names: Vec<String> = PROCESSOR_REGISTRY.read().unwrap().keys().cloned().collect();
+	names.sort();
+	names
+}
+
+/// Creates the processor chain of a pipeline request, in order.
+pub fn create_processors(
+	configs: &[ProcessorConfig],
+	context: &ProcessorContext,
+) -> IngestorResult<Vec<Arc<dyn AsyncProcessor>>> {
+	let registry = PROCESSOR_REGISTRY.read().unwrap();
+	configs
+		.iter()
+		.map(|config| {
+			let factory = registry.get(&config.name).ok_or_else(|| {
+				invalid_config(format!("No processor registered as `{}`", config.name))
+			})?;
+			factory(&config.params, context)
+				.map_err(|e| e.add_context(format!("Processor `{}`", config.name)))
+		})
+		.collect()
+}
+
+/// Parameter `key` parsed, or `default` when it is not set.
+pub fn param<T: FromStr>(params: &ProcessorParams, key: &str, default: T) -> IngestorResult<T> {
+	match params.get(key) {
+		Some(value) => value
+			.trim()
+			.parse()
+			.map_err(|_| invalid_config(format!("Invalid value `{}` for `{}`", value, key))),
+		None => Ok(default),
+	}
+}
+
+pub(crate) fn invalid_config(message: String) -> IngestorError {
+	IngestorError::new(IngestorErrorKind::InvalidConfig, Arc::new(anyhow::anyhow!(message)))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use async_trait::async_trait;
+	use proto::semantics::IngestedTokens;
+
+	struct UppercaseProcessor;
+
+	#[async_trait]
+	impl AsyncProcessor for UppercaseProcessor {
+		async fn process_text(&self, mut data: IngestedTokens) -> IngestorResult<IngestedTokens> {
+			data.data = data.data.iter().map(|text| text.to_uppercase()).collect();
+			Ok(data)
+		}
+	}
+
+	fn config(name: &str, params: &[(&str, &str)]) -> ProcessorConfig {
+		ProcessorConfig {
+			name: name.to_string(),
+			params: params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+		}
+	}
+
+	#[tokio::test]
+	async fn creates_chains_of_registered_processors() {
+		register_processor(
+			"uppercase",
+			Arc::new(|_: &ProcessorParams, _: &ProcessorContext| {
+				Ok(Arc::new(UppercaseProcessor) as _)
+			}),
+		);
+		let context = ProcessorContext::default();
+		let chain = create_processors(
+			&[
+				config("regex_replace", &[("pattern", r"\d+"), ("replacement", "#")]),
+				config("uppercase", &[]),
+			],
+			&context,
+		)
+		.unwrap();
+		let mut tokens = IngestedTokens { data: vec!["page 12".to_string()], ..Default::default() };
+		for processor in &chain {
+			tokens = processor.process_text(tokens).await.unwrap();
+		}
+		assert_eq!(tokens.data, vec!["PAGE #".to_string()]);
+
+		assert!(create_processors(&[config("unknown", &[])], &context).is_err());
+		let invalid = config("min_length", &[("min_chars", "many")]);
+		assert!(create_processors(&[invalid], &context).is_err());
+	}
+}
@@ -0,0 +1,286 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use proto::semantics::IngestedTokens;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	process_ingested_tokens_stream, processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes, AsyncProcessor, BaseIngestor, IngestorError, IngestorErrorKind,
+	IngestorResult,
+};
+
+/// Destinations holding formatting, metadata or binary data rather than document text.
+const SKIPPED_DESTINATIONS: &[&str] = &[
+	"colortbl",
+	"datastore",
+	"fldinst",
+	"fonttbl",
+	"footer",
+	"footerf",
+	"footerl",
+	"footerr",
+	"header",
+	"headerf",
+	"headerl",
+	"headerr",
+	"info",
+	"latentstyles",
+	"listoverridetable",
+	"listtable",
+	"object",
+	"pict",
+	"rsidtbl",
+	"stylesheet",
+	"themedata",
+	"xmlnstbl",
+];
+
+#[derive(Clone, Copy)]
+struct GroupState {
+	skip: bool,
+	/// Number of fallback characters following a `\u` escape.
+	unicode_skip: usize,
+}
+
+/// Reads the text of an RTF document, dropping formatting and embedded objects.
+pub fn rtf_text(rtf: &[u8]) -> IngestorResult<String> {
+	if !rtf.starts_with(b"{\\rtf") {
+		return Err(IngestorError::new(
+			IngestorErrorKind::NotSupported,
+			Arc::new(anyhow::anyhow!("Not an RTF document")),
+		));
+	}
+	let mut text = String::new();
+	let mut stack: Vec<GroupState> = Vec::new();
+	let mut state = GroupState { skip: false, unicode_skip: 1 };
+	let mut pending_skip = 0;
+
+	let mut i = 0;
+	while i < rtf.len() {
+		match rtf[i] {
+			b'{' => {
+				stack.push(state);
+				i += 1;
+			},
+			b'}' => {
+				state = stack.pop().unwrap_or(state);
+				i += 1;
+			},
+			b'\r' | b'\n' => i += 1,
+			b'\\' => {
+				i += 1;
+				let Some(&c) = rtf.get(i) else {
+					break;
+				};
+				if c.is_ascii_alphabetic() {
+					let start = i;
+					while i < rtf.len() && rtf[i].is_ascii_alphabetic() {
+						i += 1;
+					}
+					let word = std::str::from_utf8(&rtf[start..i]).unwrap_or_default();
+					let number_start = i;
+					if i < rtf.len() && rtf[i] == b'-' {
+						i += 1;
+					}
+					while i < rtf.len() && rtf[i].is_ascii_digit() {
+						i += 1;
+					}
+					let parameter = std::str::from_utf8(&rtf[number_start..i])
+						.ok()
+						.and_then(|number| number.parse::<i32>().ok());
+					// a space ends the control word and is part of it
+					if i < rtf.len() && rtf[i] == b' ' {
+						i += 1;
+					}
+					match word {
+						"par" | "line" | "sect" | "page" | "row" =>
+							emit(&mut text, &mut pending_skip, state.skip, '\n'),
+						"tab" | "cell" => emit(&mut text, &mut pending_skip, state.skip, '\t'),
+						"emdash" => emit(&mut text, &mut pending_skip, state.skip, '—'),
+						"endash" => emit(&mut text, &mut pending_skip, state.skip, '–'),
+						"bullet" => emit(&mut text, &mut pending_skip, state.skip, '•'),
+						"lquote" | "rquote" => emit(&mut text, &mut pending_skip, state.skip, '\''),
+						"ldblquote" | "rdblquote" =>
+							emit(&mut text, &mut pending_skip, state.skip, '"'),
+						"uc" => state.unicode_skip = parameter.unwrap_or(1).max(0) as usize,
+						"u" => {
+							let code = parameter.unwrap_or_default();
+							// code points above 32767 are written as negative numbers
+							let code = if code < 0 { code + 65536 } else { code } as u32;
+							if !state.skip {
+								text.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+							}
+							pending_skip = state.unicode_skip;
+						},
+						word if SKIPPED_DESTINATIONS.contains(&word) => state.skip = true,
+						_ => {},
+					}
+				} else {
+					i += 1;
+					match c {
+						b'*' => state.skip = true,
+						b'\'' => {
+							let hex =
+								rtf.get(i..i + 2).and_then(|hex| std::str::from_utf8(hex).ok());
+							if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+							{
+								emit(&mut text, &mut pending_skip, state.skip, windows_1252(byte));
+							}
+							i += 2;
+						},
+						b'\\' | b'{' | b'}' =>
+							emit(&mut text, &mut pending_skip, state.skip, c as char),
+						b'~' => emit(&mut text, &mut pending_skip, state.skip, ' '),
+						b'_' => emit(&mut text, &mut pending_skip, state.skip, '-'),
+						b'\r' | b'\n' => emit(&mut text, &mut pending_skip, state.skip, '\n'),
+						_ => {},
+					}
+				}
+			},
+			byte => {
+				emit(&mut text, &mut pending_skip, state.skip, windows_1252(byte));
+				i += 1;
+			},
+		}
+	}
+	Ok(text
+		.lines()
+		.map(str::trim_end)
+		.collect::<Vec<_>>()
+		.join("\n")
+		.trim()
+		.to_string())
+}
+
+/// Appends a character of the current group, unless it is the fallback of a `\u` escape.
+fn emit(text: &mut String, pending_skip: &mut usize, skip: bool, c: char) {
+	if *pending_skip > 0 {
+		*pending_skip -= 1;
+	} else if !skip {
+		text.push(c);
+	}
+}
+
+/// Maps a byte of the default ANSI code page to its character.
+fn windows_1252(byte: u8) -> char {
+	const HIGH: [char; 32] = [
+		'€', '\u{81}', '‚', 'ƒ', '„', '…', '†', '‡', 'ˆ', '‰', 'Š', '‹', 'Œ', '\u{8D}', 'Ž',
+		'\u{8F}', '\u{90}', '‘', '’', '“', '”', '•', '–', '—', '˜', '™', 'š', '›', 'œ', '\u{9D}',
+		'ž', 'Ÿ',
+	];
+	match byte {
+		0x80..=0x9F => HIGH[(byte - 0x80) as usize],
+		_ => byte as char,
+	}
+}
+
+// Define the RtfIngestor
+pub struct RtfIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl RtfIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for RtfIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let text = match rtf_text(&buffer) {
+				Ok(text) => text,
+				Err(e) => {
+					yield Err(e.add_context(format!("Failed to read rtf {}", file)));
+					return;
+				},
+			};
+			if !text.is_empty() {
+				yield Ok(IngestedTokens {
+					data: vec![text],
+					file: file.clone(),
+					doc_source: doc_source.clone(),
+					is_token_stream: false,
+					source_id: source_id.clone(),
+					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
+				});
+			}
+			yield Ok(IngestedTokens {
+				data: vec![],
+				file,
+				doc_source,
+				is_token_stream: false,
+				source_id,
+				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
+			});
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rtf_text() {
+		let rtf =
+			br"{\rtf1\ansi\deff0{\fonttbl{\f0 Times New Roman;}}{\colortbl;\red0\green0\blue0;}
+{\info{\title Hidden title}}{\*\generator Writer;}
+\pard\b Core report\b0\par
+Porosity is 12\'b0 higher in well A\u8209?7, see \{notes\}.\par
+{\pict\pngblip 89504e47}Caf\'e9\tab done\line
+}";
+		assert_eq!(
+			rtf_text(rtf).unwrap(),
+			"Core report\nPorosity is 12° higher in well A\u{2011}7, see {notes}.\nCafé\tdone"
+		);
+		assert!(rtf_text(b"plain text").is_err());
+	}
+}
@@ -0,0 +1,148 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use proto::semantics::IngestedTokens;
+
+/// Text found under a heading, along with the titles of the headings leading to it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Section {
+	pub path: Vec<String>,
+	pub text: String,
+}
+
+impl Section {
+	/// The heading titles joined by " > ", or `None` before the first heading.
+	pub fn path_string(&self) -> Option<String> {
+		(!self.path.is_empty()).then(|| self.path.join(" > "))
+	}
+}
+
+/// Splits a document into sections as its headings and lines are fed in reading order.
+#[derive(Debug, Default)]
+pub struct SectionBuilder {
+	headings: Vec<(usize, String)>,
+	text: String,
+	sections: Vec<Section>,
+}
+
+impl SectionBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Starts a section under a heading of the given level, 1 being the outermost. The
+	/// heading replaces any open heading of the same or a deeper level.
+	pub fn heading(&mut self, level: usize, title: &str) {
+		let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+		if title.is_empty() {
+			return;
+		}
+		self.flush();
+		self.headings.retain(|(open_level, _)| *open_level < level);
+		self.headings.push((level, title));
+	}
+
+	pub fn line(&mut self, line: &str) {
+		self.text.push_str(line.trim_end());
+		self.text.push('\n');
+	}
+
+	pub fn finish(mut self) -> Vec<Section> {
+		self.flush();
+		self.sections
+	}
+
+	fn flush(&mut self) {
+		let text = std::mem::take(&mut self.text);
+		let text = text.trim();
+		if text.is_empty() {
+			return;
+		}
+		self.sections.push(Section {
+			path: self.headings.iter().map(|(_, title)| title.clone()).collect(),
+			text: text.to_string(),
+		});
+	}
+}
+
+/// One token message per section, tagged with its section path, followed by the empty
+/// message that marks the end of the document.
+pub fn section_tokens(
+	sections: Vec<Section>,
+	file: &str,
+	doc_source: &str,
+	source_id: &str,
+) -> Vec<IngestedTokens> {
+	let tokens = |data: Vec<String>, section_path: Option<String>| IngestedTokens {
+		data,
+		file: file.to_string(),
+		doc_source: doc_source.to_string(),
+		is_token_stream: false,
+		source_id: source_id.to_string(),
+		image_id: None,
+		section_path,
+		table: None,
+		duplicate_of: None,
+	};
+	sections
+		.into_iter()
+		.map(|section| {
+			let section_path = section.path_string();
+			tokens(vec![section.text], section_path)
+		})
+		.chain(std::iter::once(tokens(vec![], None)))
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_section_paths() {
+		let mut builder = SectionBuilder::new();
+		builder.line("Preface text");
+		builder.heading(1, "Wells");
+		builder.heading(2, "Drilling");
+		builder.line("Drilling started in May.");
+		builder.heading(3, "  Mud   logs ");
+		builder.line("Logs were taken daily.");
+		builder.heading(2, "Completion");
+		builder.line("");
+		builder.heading(1, "Production");
+		builder.line("Output rose.");
+		let sections = builder.finish();
+
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![
+				None,
+				Some("Wells > Drilling".to_string()),
+				Some("Wells > Drilling > Mud logs".to_string()),
+				Some("Production".to_string()),
+			]
+		);
+		assert_eq!(sections[2].text, "Logs were taken daily.");
+
+		let tokens = section_tokens(sections, "wells.md", "test_source", "Filesystem");
+		assert_eq!(tokens.len(), 5);
+		assert!(tokens[4].data.is_empty());
+		assert_eq!(tokens[1].section_path.as_deref(), Some("Wells > Drilling"));
+	}
+}
@@ -0,0 +1,247 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::cmp::Ordering;
+
+use proto::semantics::{ExtractedTable, TableRow};
+
+/// Lines of a page that must line up in columns before they are read as a table.
+const MIN_TABLE_LINES: usize = 3;
+/// Gap between two words, relative to the font size, that separates two cells of a line.
+const COLUMN_GAP: f64 = 1.0;
+
+/// Builds a table out of rows of cells, the first non-empty row holding the headers. Returns
+/// `None` when no data row follows the headers.
+pub fn table_from_rows(rows: Vec<Vec<String>>) -> Option<ExtractedTable> {
+	let mut rows = rows
+		.into_iter()
+		.map(|row| row.iter().map(|cell| cell.trim().to_string()).collect::<Vec<_>>())
+		.filter(|row| row.iter().any(|cell| !cell.is_empty()));
+	let headers = rows.next()?;
+	let rows: Vec<TableRow> = rows.map(|cells| TableRow { cells }).collect();
+	(!rows.is_empty()).then_some(ExtractedTable { headers, rows })
+}
+
+/// The text of a table as `header: value` lines, one block per row.
+pub fn table_text(table: &ExtractedTable) -> String {
+	table
+		.rows
+		.iter()
+		.map(|row| {
+			row.cells
+				.iter()
+				.enumerate()
+				.filter(|(_, cell)| !cell.is_empty())
+				.map(|(i, cell)| match table.headers.get(i).filter(|header| !header.is_empty()) {
+					Some(header) => format!("{}: {}", header, cell),
+					None => cell.to_string(),
+				})
+				.collect::<Vec<_>>()
+				.join("\n")
+		})
+		.collect::<Vec<_>>()
+		.join("\n\n")
+}
+
+/// A word laid out on a page: its horizontal extent, baseline and font size, in page units.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionedWord {
+	pub text: String,
+	pub x: f64,
+	pub end: f64,
+	pub y: f64,
+	pub size: f64,
+}
+
+/// A table found among the words of a page, along with the lines of text it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DetectedTable {
+	pub table: ExtractedTable,
+	pub lines: Vec<String>,
+}
+
+struct Cell {
+	text: String,
+	x: f64,
+	end: f64,
+}
+
+/// Finds tables among the words of a page: runs of consecutive lines split by wide gaps into
+/// the same number of cells, with the cells of each column overlapping horizontally.
+pub fn detect_tables(words: &[PositionedWord]) -> Vec<DetectedTable> {
+	let mut tables = Vec::new();
+	let mut run: Vec<(String, Vec<Cell>)> = Vec::new();
+	let mut columns: Vec<(f64, f64)> = Vec::new();
+	for line in page_lines(words) {
+		let text = line.iter().map(|word| word.text.as_str()).collect::<Vec<_>>().join(" ");
+		let cells = line_cells(&line);
+		let continues_run = !run.is_empty() &&
+			cells.len() == columns.len() &&
+			cells
+				.iter()
+				.zip(&columns)
+				.all(|(cell, (x, end))| cell.x <= *end && cell.end >= *x);
+		if !continues_run {
+			tables.extend(close_run(std::mem::take(&mut run)));
+			columns.clear();
+			if cells.len() < 2 {
+				continue;
+			}
+		}
+		for (i, cell) in cells.iter().enumerate() {
+			match columns.get_mut(i) {
+				Some((x, end)) => {
+					*x = x.min(cell.x);
+					*end = end.max(cell.end);
+				},
+				None => columns.push((cell.x, cell.end)),
+			}
+		}
+		run.push((text, cells));
+	}
+	tables.extend(close_run(run));
+	tables
+}
+
+fn close_run(run: Vec<(String, Vec<Cell>)>) -> Option<DetectedTable> {
+	if run.len() < MIN_TABLE_LINES {
+		return None;
+	}
+	let (lines, rows): (Vec<String>, Vec<Vec<String>>) = run
+		.into_iter()
+		.map(|(text, cells)| (text, cells.into_iter().map(|cell| cell.text).collect()))
+		.unzip();
+	table_from_rows(rows).map(|table| DetectedTable { table, lines })
+}
+
+/// Groups words into lines from the top of the page down, each line ordered left to right.
+fn page_lines(words: &[PositionedWord]) -> Vec<Vec<&PositionedWord>> {
+	let mut sorted: Vec<&PositionedWord> = words.iter().collect();
+	sorted.sort_by(|a, b| b.y.partial_cmp(&a.y).unwrap_or(Ordering::Equal));
+	let mut lines: Vec<Vec<&PositionedWord>> = Vec::new();
+	let mut line_y = f64::NAN;
+	for word in sorted {
+		match lines.last_mut() {
+			Some(line) if (line_y - word.y).abs() <= word.size * 0.5 => line.push(word),
+			_ => {
+				line_y = word.y;
+				lines.push(vec![word]);
+			},
+		}
+	}
+	for line in &mut lines {
+		line.sort_by(|a, b| a.x.partial_cmp(&b.x).unwrap_or(Ordering::Equal));
+	}
+	lines
+}
+
+fn line_cells(line: &[&PositionedWord]) -> Vec<Cell> {
+	let mut cells: Vec<Cell> = Vec::new();
+	for word in line {
+		match cells.last_mut() {
+			Some(cell) if word.x - cell.end <= word.size * COLUMN_GAP => {
+				cell.text.push(' ');
+				cell.text.push_str(&word.text);
+				cell.end = cell.end.max(word.end);
+			},
+			_ => cells.push(Cell { text: word.text.clone(), x: word.x, end: word.end }),
+		}
+	}
+	cells
+}
+
+/// Drops from `text` the lines read into tables, so their content is not repeated as prose.
+/// Lines are compared with their whitespace collapsed.
+pub fn remove_table_lines(text: &str, tables: &[DetectedTable]) -> String {
+	let normalize = |line: &str| line.split_whitespace().collect::<Vec<_>>().join(" ");
+	let mut table_lines: Vec<String> = tables
+		.iter()
+		.flat_map(|table| table.lines.iter().map(|line| normalize(line)))
+		.collect();
+	text.lines()
+		.filter(|line| {
+			let line = normalize(line);
+			match table_lines.iter().position(|table_line| *table_line == line) {
+				Some(i) if !line.is_empty() => {
+					table_lines.remove(i);
+					false
+				},
+				_ => true,
+			}
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn words(lines: &[(f64, &[(f64, &str)])]) -> Vec<PositionedWord> {
+		lines
+			.iter()
+			.flat_map(|(y, words)| {
+				words.iter().map(|(x, text)| PositionedWord {
+					text: text.to_string(),
+					x: *x,
+					end: x + 5.0 * text.len() as f64,
+					y: *y,
+					size: 10.0,
+				})
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_detect_tables() {
+		let words = words(&[
+			(700.0, &[(50.0, "Drilling"), (92.0, "summary")]),
+			(680.0, &[(50.0, "Well"), (150.0, "Depth"), (250.0, "Operator")]),
+			(
+				665.0,
+				&[(50.0, "A-7"), (155.0, "3200"), (250.0, "North"), (278.0, "Sea"), (296.0, "Ltd")],
+			),
+			(650.5, &[(50.0, "B-2"), (160.0, "950"), (250.0, "Acme")]),
+			(635.0, &[(50.0, "The"), (68.0, "wells"), (96.0, "were"), (120.0, "logged.")]),
+		]);
+		let tables = detect_tables(&words);
+		assert_eq!(tables.len(), 1);
+		let table = &tables[0].table;
+		assert_eq!(table.headers, vec!["Well", "Depth", "Operator"]);
+		assert_eq!(table.rows[0].cells, vec!["A-7", "3200", "North Sea Ltd"]);
+		assert_eq!(table.rows[1].cells, vec!["B-2", "950", "Acme"]);
+		assert_eq!(
+			table_text(table),
+			"Well: A-7\nDepth: 3200\nOperator: North Sea Ltd\n\nWell: B-2\nDepth: 950\nOperator: Acme"
+		);
+
+		let text = "Drilling summary\nWell Depth Operator\nA-7  3200 North Sea Ltd\nB-2 950 Acme\nThe wells were logged.";
+		assert_eq!(remove_table_lines(text, &tables), "Drilling summary\nThe wells were logged.");
+	}
+
+	#[test]
+	fn test_short_runs_are_not_tables() {
+		let words = words(&[
+			(700.0, &[(50.0, "Name"), (150.0, "Value")]),
+			(680.0, &[(50.0, "Porosity"), (150.0, "0.21")]),
+			(660.0, &[(50.0, "Just"), (72.0, "prose"), (100.0, "here")]),
+		]);
+		assert!(detect_tables(&words).is_empty());
+		assert_eq!(table_from_rows(vec![vec!["a".to_string()], vec![String::new()]]), None);
+	}
+}
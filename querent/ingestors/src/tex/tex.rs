@@ -0,0 +1,304 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use once_cell::sync::Lazy;
+use proto::semantics::IngestedTokens;
+use regex::Regex;
+use std::{pin::Pin, sync::Arc};
+
+use crate::{
+	process_ingested_tokens_stream,
+	processors::text_processing::TextCleanupProcessor,
+	read_collected_bytes,
+	sections::{section_tokens, Section, SectionBuilder},
+	AsyncProcessor, BaseIngestor, IngestorResult,
+};
+
+static SECTIONING: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(
+		r"\\(part|chapter|section|subsection|subsubsection|paragraph)\*?\s*(?:\[[^\]]*\])?\s*\{",
+	)
+	.unwrap()
+});
+static SKIPPED_ENVIRONMENT: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(
+		r"\\begin\{(equation|align|gather|multline|eqnarray|displaymath|math|tikzpicture|lstlisting)(\*?)\}",
+	)
+	.unwrap()
+});
+static DISPLAY_MATH: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?s)\$\$.*?\$\$|\\\[.*?\\\]|\\\(.*?\\\)|\$[^$]*\$").unwrap());
+static DROPPED_COMMAND: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(
+		r"\\(?:label|ref|eqref|pageref|cite[a-zA-Z]*|includegraphics|bibliography[a-zA-Z]*|input|include|usepackage|documentclass|vspace|hspace|url)\*?(?:\[[^\]]*\])?\{[^}]*\}",
+	)
+	.unwrap()
+});
+static ITEM: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\item\s*(?:\[([^\]]*)\])?").unwrap());
+static ENVIRONMENT_MARKER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"\\(?:begin|end)\{[^}]*\}(?:\[[^\]]*\])?").unwrap());
+static LINE_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\\\(?:\[[^\]]*\])?|\\par\b").unwrap());
+static COMMAND: Lazy<Regex> = Lazy::new(|| Regex::new(r"\\[a-zA-Z]+\*?\s*").unwrap());
+static PARAGRAPH_BREAK: Lazy<Regex> = Lazy::new(|| Regex::new(r"\n\s*\n").unwrap());
+
+/// Splits the body of a LaTeX document into sections along its sectioning commands, from
+/// `\part` down to `\paragraph`, keeping the prose and dropping markup and math.
+pub fn latex_sections(source: &str) -> Vec<Section> {
+	let source = strip_comments(source);
+	let body = match source.split_once(r"\begin{document}") {
+		Some((_, body)) => body.split(r"\end{document}").next().unwrap_or_default(),
+		None => source.as_str(),
+	};
+
+	let mut builder = SectionBuilder::new();
+	let mut position = 0;
+	while let Some(captures) = SECTIONING.captures_at(body, position) {
+		let command = captures.get(0).expect("the whole match is always captured");
+		let Some((title, end)) = braced(body, command.end()) else { break };
+		paragraphs(&body[position..command.start()], &mut builder);
+		let level = match &captures[1] {
+			"part" => 1,
+			"chapter" => 2,
+			"section" => 3,
+			"subsection" => 4,
+			"subsubsection" => 5,
+			_ => 6,
+		};
+		builder.heading(level, &latex_text(title));
+		position = end;
+	}
+	paragraphs(&body[position..], &mut builder);
+	builder.finish()
+}
+
+/// Drops `%` comments up to the end of their line, leaving escaped `\%` in place.
+fn strip_comments(source: &str) -> String {
+	source
+		.lines()
+		.map(|line| {
+			let mut escaped = false;
+			for (i, character) in line.char_indices() {
+				match character {
+					'%' if !escaped => return &line[..i],
+					'\\' => escaped = !escaped,
+					_ => escaped = false,
+				}
+			}
+			line
+		})
+		.collect::<Vec<_>>()
+		.join("\n")
+}
+
+/// The content of the brace group opened just before `start`, and the position after
+/// its closing brace.
+fn braced(text: &str, start: usize) -> Option<(&str, usize)> {
+	let mut depth = 1;
+	let mut escaped = false;
+	for (i, character) in text[start..].char_indices() {
+		match character {
+			'\\' => {
+				escaped = !escaped;
+				continue;
+			},
+			'{' if !escaped => depth += 1,
+			'}' if !escaped => {
+				depth -= 1;
+				if depth == 0 {
+					return Some((&text[start..start + i], start + i + 1));
+				}
+			},
+			_ => {},
+		}
+		escaped = false;
+	}
+	None
+}
+
+fn paragraphs(text: &str, builder: &mut SectionBuilder) {
+	for paragraph in PARAGRAPH_BREAK.split(&latex_text(text)) {
+		let paragraph = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+		if !paragraph.is_empty() {
+			builder.line(&paragraph);
+		}
+	}
+}
+
+/// Plain text of a LaTeX fragment, with paragraphs, list items and explicit line breaks
+/// separated by blank lines.
+fn latex_text(text: &str) -> String {
+	// escaped dollars must not open inline math
+	let mut text = text.replace(r"\$", "\u{0}");
+	while let Some(captures) = SKIPPED_ENVIRONMENT.captures(&text) {
+		let begin = captures.get(0).expect("the whole match is always captured");
+		let end_marker = format!(r"\end{{{}{}}}", &captures[1], &captures[2]);
+		let end = text[begin.end()..]
+			.find(&end_marker)
+			.map(|end| begin.end() + end + end_marker.len())
+			.unwrap_or(text.len());
+		text.replace_range(begin.start()..end, "\n\n");
+	}
+	let text = DISPLAY_MATH.replace_all(&text, " ");
+	let text = DROPPED_COMMAND.replace_all(&text, "");
+	let text = ITEM.replace_all(&text, "\n\n$1 ");
+	let text = ENVIRONMENT_MARKER.replace_all(&text, "\n\n");
+	let text = LINE_BREAK.replace_all(&text, "\n\n");
+	let text = COMMAND.replace_all(&text, "");
+
+	let mut plain = String::with_capacity(text.len());
+	let mut characters = text.chars();
+	while let Some(character) = characters.next() {
+		match character {
+			'\u{0}' => plain.push('$'),
+			'\\' =>
+				if let Some(escaped) = characters.next() {
+					plain.push(escaped);
+				},
+			'{' | '}' => {},
+			'~' => plain.push(' '),
+			character => plain.push(character),
+		}
+	}
+	plain
+}
+
+// Define the TexIngestor
+pub struct TexIngestor {
+	processors: Vec<Arc<dyn AsyncProcessor>>,
+}
+
+impl TexIngestor {
+	pub fn new() -> Self {
+		Self { processors: vec![Arc::new(TextCleanupProcessor::new())] }
+	}
+}
+
+#[async_trait]
+impl BaseIngestor for TexIngestor {
+	fn set_processors(&mut self, processors: Vec<Arc<dyn AsyncProcessor>>) {
+		self.processors = processors;
+	}
+
+	async fn ingest(
+		&self,
+		all_collected_bytes: Vec<CollectedBytes>,
+	) -> IngestorResult<Pin<Box<dyn Stream<Item = IngestorResult<IngestedTokens>> + Send + 'static>>>
+	{
+		let stream = stream! {
+			let collected = read_collected_bytes(all_collected_bytes).await;
+			let (buffer, file, doc_source, source_id) = match collected {
+				Ok(collected) => collected,
+				Err(e) => {
+					yield Err(e);
+					return;
+				},
+			};
+			let sections = latex_sections(&String::from_utf8_lossy(&buffer));
+			for tokens in section_tokens(sections, &file, &doc_source, &source_id) {
+				yield Ok(tokens);
+			}
+		};
+
+		let processed_stream =
+			process_ingested_tokens_stream(Box::pin(stream), self.processors.clone()).await;
+		Ok(Box::pin(processed_stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::StreamExt;
+	use std::{io::Cursor, path::Path};
+
+	const SOURCE: &str = r#"\documentclass{article}
+\usepackage{amsmath}
+\title{Ignored preamble}
+\begin{document}
+\maketitle
+\section{Introduction}\label{sec:intro}
+Reservoir pressure drops 5\% per year % TODO check the rate
+as shown by \textbf{Smith} \cite{smith2020}. It costs \$3 per barrel.
+
+Flow follows $q = kA/\mu$ closely:
+\begin{equation}
+  q = -\frac{k A}{\mu} \frac{dp}{dx}
+\end{equation}
+\subsection*{Field {\em data}}
+\begin{itemize}
+  \item Well A-7
+  \item[b)] Well B-2
+\end{itemize}
+\section{Conclusion}
+Done.\\ Really.
+\end{document}
+"#;
+
+	#[test]
+	fn test_latex_sections() {
+		let sections = latex_sections(SOURCE);
+		let paths: Vec<Option<String>> = sections.iter().map(Section::path_string).collect();
+		assert_eq!(
+			paths,
+			vec![
+				Some("Introduction".to_string()),
+				Some("Introduction > Field data".to_string()),
+				Some("Conclusion".to_string())
+			]
+		);
+		assert_eq!(
+			sections[0].text,
+			"Reservoir pressure drops 5% per year as shown by Smith . It costs $3 per barrel.\n\
+			 Flow follows closely:"
+		);
+		assert_eq!(sections[1].text, "Well A-7\nb) Well B-2");
+		assert_eq!(sections[2].text, "Done.\nReally.");
+	}
+
+	#[tokio::test]
+	async fn test_tex_ingestor() {
+		let collected_bytes = CollectedBytes {
+			data: Some(Box::pin(Cursor::new(SOURCE.as_bytes().to_vec()))),
+			file: Some(Path::new("paper.tex").to_path_buf()),
+			doc_source: Some("test_source".to_string()),
+			eof: false,
+			extension: Some("tex".to_string()),
+			size: Some(10),
+			source_id: "Filesystem".to_string(),
+			_owned_permit: None,
+			content_type: None,
+			image_id: None,
+		};
+
+		let ingestor = TexIngestor::new();
+		let mut stream = ingestor.ingest(vec![collected_bytes]).await.unwrap();
+		let mut section_paths = Vec::new();
+		while let Some(tokens) = stream.next().await {
+			let tokens = tokens.unwrap();
+			if !tokens.data.is_empty() {
+				section_paths.push(tokens.section_path);
+			}
+		}
+		assert_eq!(section_paths.len(), 3);
+		assert_eq!(section_paths[1].as_deref(), Some("Introduction > Field data"));
+	}
+}
@@ -78,6 +78,9 @@ impl BaseIngestor for TxtIngestor {
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
 						})
 					}
 					buffer.extend_from_slice(&buf);
@@ -98,6 +101,9 @@ impl BaseIngestor for TxtIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				})
 			}else {
 				let ingested_tokens = IngestedTokens {
@@ -107,6 +113,9 @@ impl BaseIngestor for TxtIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				};
 				yield Ok(ingested_tokens);
 				yield Ok(IngestedTokens {
@@ -116,6 +125,9 @@ impl BaseIngestor for TxtIngestor {
 					is_token_stream: false,
 					source_id: source_id.clone(),
 					image_id: None,
+					section_path: None,
+					table: None,
+					duplicate_of: None,
 				})
 			}
 		};
@@ -157,6 +169,7 @@ mod tests {
 			size: Some(10),
 			source_id: "Filesystem".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -192,6 +205,7 @@ mod tests {
 			size: Some(10),
 			source_id: "Filesystem".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -227,6 +241,7 @@ mod tests {
 			size: Some(10),
 			source_id: "Filesystem".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -21,7 +21,7 @@ use async_trait::async_trait;
 use common::CollectedBytes;
 use futures::{Stream, StreamExt};
 use image::guess_format;
-use proto::semantics::IngestedTokens;
+use proto::semantics::{ExtractedTable, IngestedTokens, TableRow};
 use std::{
 	collections::HashMap,
 	io::{Cursor, Read},
@@ -35,8 +35,8 @@ use zip::ZipArchive;
 
 use crate::{
 	image::image::ImageIngestor, process_ingested_tokens_stream,
-	processors::text_processing::TextCleanupProcessor, AsyncProcessor, BaseIngestor,
-	IngestorResult,
+	processors::text_processing::TextCleanupProcessor, tables::table_text, AsyncProcessor,
+	BaseIngestor, IngestorResult,
 };
 // Define the XlsxIngestor
 pub struct XlsxIngestor {
@@ -91,28 +91,62 @@ impl BaseIngestor for XlsxIngestor {
 
 			match xlsx_reader::parse_xlsx(&buffer, None) {
 				Ok(parsed_xlsx) => {
+					// the first non-empty row holds the headers, matched to cells by column
+					let mut headers: Option<Vec<(String, String)>> = None;
+					let mut has_rows = false;
 					for (_, row_map) in &parsed_xlsx {
-						let mut res = String::new();
-						let mut first = true;
-						for (inner_size, value) in row_map {
-							if first {
-								first = false;
-							} else {
-								res.push_str(", ");
-							}
-							res.push_str(&format!("{},{}", inner_size, value));
+						let cells: Vec<(String, String)> = row_map
+							.iter()
+							.map(|(column, value)| (column.to_string(), value.to_string()))
+							.collect();
+						if cells.iter().all(|(_, value)| value.trim().is_empty()) {
+							continue;
 						}
-
-						let ingested_tokens = IngestedTokens {
-							data: vec![res],
+						let Some(headers) = headers.as_ref() else {
+							headers = Some(cells);
+							continue;
+						};
+						let row = headers
+							.iter()
+							.map(|(column, _)| {
+								cells
+									.iter()
+									.find(|(cell_column, _)| cell_column == column)
+									.map(|(_, value)| value.clone())
+									.unwrap_or_default()
+							})
+							.collect();
+						let table = ExtractedTable {
+							headers: headers.iter().map(|(_, header)| header.clone()).collect(),
+							rows: vec![TableRow { cells: row }],
+						};
+						has_rows = true;
+						yield Ok(IngestedTokens {
+							data: vec![table_text(&table)],
 							file: file.clone(),
 							doc_source: doc_source.clone(),
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
-						};
-
-						yield Ok(ingested_tokens);
+							section_path: None,
+							table: Some(table),
+							duplicate_of: None,
+						});
+					}
+					if let Some(headers) = headers.filter(|_| !has_rows) {
+						let text: Vec<String> =
+							headers.into_iter().map(|(_, header)| header).collect();
+						yield Ok(IngestedTokens {
+							data: vec![text.join("\n")],
+							file: file.clone(),
+							doc_source: doc_source.clone(),
+							is_token_stream: false,
+							source_id: source_id.clone(),
+							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
+						});
 					}
 				},
 				Err(e) => {
@@ -124,6 +158,9 @@ impl BaseIngestor for XlsxIngestor {
 						is_token_stream: false,
 						source_id: source_id.clone(),
 						image_id: None,
+						section_path: None,
+						table: None,
+						duplicate_of: None,
 					});
 				}
 			}
@@ -170,6 +207,7 @@ impl BaseIngestor for XlsxIngestor {
 						size: Some(img_data.len()),
 						source_id: source_id.clone(),
 						_owned_permit: None,
+						content_type: None,
 						image_id: Some(name.to_string()),
 					};
 					let image_ingestor = ImageIngestor::new();
@@ -199,6 +237,9 @@ impl BaseIngestor for XlsxIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			})
 		};
 		let processed_stream =
@@ -228,6 +269,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -270,6 +312,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
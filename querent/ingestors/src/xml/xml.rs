@@ -99,6 +99,9 @@ impl BaseIngestor for XmlIngestor {
 							is_token_stream: false,
 							source_id: source_id.clone(),
 							image_id: None,
+							section_path: None,
+							table: None,
+							duplicate_of: None,
 						});
 						return;
 					}
@@ -112,6 +115,9 @@ impl BaseIngestor for XmlIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			};
 			yield Ok(ingested_tokens);
 
@@ -122,6 +128,9 @@ impl BaseIngestor for XmlIngestor {
 				is_token_stream: false,
 				source_id: source_id.clone(),
 				image_id: None,
+				section_path: None,
+				table: None,
+				duplicate_of: None,
 			});
 		};
 
@@ -152,6 +161,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
@@ -189,6 +199,7 @@ mod tests {
 			size: Some(10),
 			source_id: "FileSystem1".to_string(),
 			_owned_permit: None,
+			content_type: None,
 			image_id: None,
 		};
 
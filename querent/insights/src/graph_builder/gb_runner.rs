@@ -21,7 +21,7 @@ use crate::{
 	InsightOutput, InsightResult, InsightRunner,
 };
 use async_trait::async_trait;
-use common::{EventType, SemanticKnowledgePayload};
+use common::{EventType, KnowledgeOrigin, SemanticKnowledgePayload};
 use fastembed::TextEmbedding;
 use futures::Stream;
 use lazy_static::lazy_static;
@@ -210,6 +210,15 @@ impl InsightRunner for GraphBuilderRunner {
 										image_id: Some("".to_string()),
 										event_id: "".to_string(),
 										source_id: "".to_string(),
+										origin: KnowledgeOrigin::Extracted,
+										subject_id: None,
+										object_id: None,
+										section_path: None,
+										chunk_start: None,
+										chunk_end: None,
+										subject_coreference: None,
+										object_coreference: None,
+										duplicate_of: None,
 									};
 
 									neo4j_payload.push((
@@ -252,6 +261,21 @@ impl InsightRunner for GraphBuilderRunner {
 										),
 										event_id: knowledge.event_id,
 										source_id: knowledge.source_id.to_string(),
+										origin: match knowledge.origin.as_deref() {
+											Some("imported") => KnowledgeOrigin::Imported,
+											Some("table") => KnowledgeOrigin::Table,
+											_ => KnowledgeOrigin::Extracted,
+										},
+										subject_id: knowledge.subject_id,
+										object_id: knowledge.object_id,
+										section_path: knowledge.section_path,
+										chunk_start: knowledge
+											.chunk_start
+											.map(|offset| offset as u32),
+										chunk_end: knowledge.chunk_end.map(|offset| offset as u32),
+										subject_coreference: knowledge.subject_coreference,
+										object_coreference: knowledge.object_coreference,
+										duplicate_of: knowledge.duplicate_of,
 									};
 
 									neo4j_payload.push((
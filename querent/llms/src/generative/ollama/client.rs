@@ -124,6 +124,10 @@ impl LLM for Ollama {
 		0
 	}
 
+	fn model_name(&self) -> String {
+		self.model.clone()
+	}
+
 	async fn tokens_to_words(&self, _tokens: &[i32]) -> Vec<String> {
 		Vec::new()
 	}
@@ -127,6 +127,10 @@ impl<C: Config + Send + Sync + 'static> LLM for OpenAI<C> {
 		0
 	}
 
+	fn model_name(&self) -> String {
+		self.model.clone()
+	}
+
 	async fn tokens_to_words(&self, _tokens: &[i32]) -> Vec<String> {
 		Vec::new()
 	}
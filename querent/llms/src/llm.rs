@@ -139,6 +139,11 @@ pub trait LLM: Send + Sync {
 	/// Returns the maximum number of tokens allowed.
 	async fn maximum_tokens(&self) -> usize;
 
+	/// Returns the name of the underlying model, used to label metrics.
+	fn model_name(&self) -> String {
+		"unknown".to_string()
+	}
+
 	/// Converts a sequence of tokens to their corresponding words.
 	async fn tokens_to_words(&self, tokens: &[i32]) -> Vec<String>;
 
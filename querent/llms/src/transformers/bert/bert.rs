@@ -72,10 +72,12 @@ pub struct BertLLM {
 	token_classification_model: Option<BertForTokenClassification>,
 	tokenizer: Tokenizer,
 	device: candle_core::Device,
+	model_name: String,
 }
 
 impl BertLLM {
 	pub fn new(options: EmbedderOptions) -> LLMResult<Self> {
+		let model_name = options.model.clone();
 		let device = match candle_core::Device::cuda_if_available(0) {
 			Ok(device) => device,
 			Err(error) => {
@@ -224,7 +226,7 @@ impl BertLLM {
 			tokenizer.with_padding(Some(pp));
 		}
 
-		let this = Self { model, token_classification_model, tokenizer, device };
+		let this = Self { model, token_classification_model, tokenizer, device, model_name };
 
 		Ok(this)
 	}
@@ -339,6 +341,10 @@ impl LLM for BertLLM {
 		255
 	}
 
+	fn model_name(&self) -> String {
+		self.model_name.clone()
+	}
+
 	async fn tokens_to_words(&self, tokens: &[i32]) -> Vec<String> {
 		let words = tokens
 			.iter()
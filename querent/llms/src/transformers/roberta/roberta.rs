@@ -53,10 +53,12 @@ pub struct RobertaLLM {
 	token_classification_model: Option<RobertaForTokenClassification>,
 	tokenizer: Tokenizer,
 	device: candle_core::Device,
+	model_name: String,
 }
 
 impl RobertaLLM {
 	pub fn new(options: EmbedderOptions) -> LLMResult<Self> {
+		let model_name = options.model.clone();
 		let device = match candle_core::Device::cuda_if_available(0) {
 			Ok(device) => device,
 			Err(error) => {
@@ -203,7 +205,7 @@ impl RobertaLLM {
 			tokenizer.with_padding(Some(pp));
 		}
 
-		let this = Self { model, token_classification_model, tokenizer, device };
+		let this = Self { model, token_classification_model, tokenizer, device, model_name };
 
 		Ok(this)
 	}
@@ -319,6 +321,10 @@ impl LLM for RobertaLLM {
 		255
 	}
 
+	fn model_name(&self) -> String {
+		self.model_name.clone()
+	}
+
 	async fn tokens_to_words(&self, tokens: &[i32]) -> Vec<String> {
 		let words = tokens
 			.iter()
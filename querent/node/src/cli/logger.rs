@@ -16,20 +16,83 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
+use proto::config::OtlpConfig;
+
 #[cfg(feature = "console")]
 pub fn setup_logging_and_tracing() {
 	println!("Console Subscriber is enabled!");
 	console_subscriber::init();
 }
 
+#[cfg(feature = "console")]
+pub fn setup_otlp_exporter(_config: &OtlpConfig) {}
+
+#[cfg(not(feature = "console"))]
+type OtlpLayer = Box<dyn tracing_subscriber::Layer<tracing_subscriber::Registry> + Send + Sync>;
+
+/// Slot of the OTLP exporter in the subscriber, filled once the node config is loaded.
+#[cfg(not(feature = "console"))]
+static OTLP_LAYER: once_cell::sync::OnceCell<
+	tracing_subscriber::reload::Handle<Option<OtlpLayer>, tracing_subscriber::Registry>,
+> = once_cell::sync::OnceCell::new();
+
 #[cfg(not(feature = "console"))]
 pub fn setup_logging_and_tracing() {
-	use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+	use opentelemetry::{global, sdk::propagation::TraceContextPropagator};
+	use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt};
+
+	// Trace context travels inside pipeline messages and REST headers in the W3C format.
+	global::set_text_map_propagator(TraceContextPropagator::new());
+
+	let (otlp_layer, otlp_handle) = reload::Layer::new(None::<OtlpLayer>);
 	tracing_subscriber::registry()
+		.with(otlp_layer)
 		.with(
 			tracing_subscriber::EnvFilter::try_from_default_env()
 				.unwrap_or_else(|_| "info,tower_http=debug".into()),
 		)
 		.with(tracing_subscriber::fmt::layer().with_thread_ids(true))
 		.init();
+	let _ = OTLP_LAYER.set(otlp_handle);
+}
+
+/// Exports spans to an OpenTelemetry collector over OTLP/gRPC when the `tracing.otlp` section of
+/// the node config enables it.
+#[cfg(not(feature = "console"))]
+pub fn setup_otlp_exporter(config: &OtlpConfig) {
+	use opentelemetry::{
+		sdk::{trace, Resource},
+		KeyValue,
+	};
+	use opentelemetry_otlp::WithExportConfig;
+	use tracing_subscriber::Layer;
+
+	if !config.enabled {
+		return;
+	}
+	let Some(otlp_handle) = OTLP_LAYER.get() else {
+		return;
+	};
+	let mut exporter = opentelemetry_otlp::new_exporter().tonic().with_env();
+	if let Some(endpoint) = &config.endpoint {
+		exporter = exporter.with_endpoint(endpoint);
+	}
+	let trace_config = trace::config().with_resource(Resource::new([
+		KeyValue::new("service.name", "querent"),
+		KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+	]));
+	let tracer_res = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(exporter)
+		.with_trace_config(trace_config)
+		.install_batch(opentelemetry::runtime::Tokio);
+	let setup_res = match tracer_res {
+		Ok(tracer) => otlp_handle
+			.reload(Some(tracing_opentelemetry::layer().with_tracer(tracer).boxed()))
+			.map_err(anyhow::Error::from),
+		Err(err) => Err(anyhow::Error::from(err)),
+	};
+	if let Err(err) = setup_res {
+		tracing::error!(error = ?err, "failed to initialize the OpenTelemetry OTLP exporter");
+	}
 }
@@ -22,7 +22,10 @@ use rian_core::MAX_DATA_SIZE_IN_MEMORY;
 use tokio::signal;
 use tracing::{debug, info};
 
-use crate::{cli::load_node_config, config_cli_arg, serve_quester};
+use crate::{
+	cli::{load_node_config, setup_otlp_exporter},
+	config_cli_arg, serve_quester,
+};
 
 pub const MB: u32 = 1_000_000;
 
@@ -120,6 +123,7 @@ impl Serve {
 		debug!(args = ?self, "run-querent-service");
 		busy_detector::set_enabled(true);
 		let node_config = load_node_config(&self.node_config_uri).await.unwrap_or_default();
+		setup_otlp_exporter(&node_config.tracing.otlp);
 		let res = MAX_DATA_SIZE_IN_MEMORY.set((node_config.memory_capacity * MB) as usize);
 		if res.is_err() {
 			info!("MAX_DATA_SIZE_IN_MEMORY is already set");
@@ -16,14 +16,27 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use std::{fmt, net::SocketAddr, sync::Arc};
+use std::{fmt, net::SocketAddr, sync::Arc, time::Duration};
 
-use proto::SpanContextInterceptor;
+use async_trait::async_trait;
+use common::tower::make_channel;
+use proto::{
+	discovery::{
+		discovery_client::DiscoveryClient, DiscoveryRequest, DiscoveryResponse,
+		DiscoverySessionRequest, DiscoverySessionRequestInfoList, DiscoverySessionResponse, Empty,
+		StopDiscoverySessionRequest, StopDiscoverySessionResponse,
+	},
+	error::grpc_status_to_service_error,
+	forwarded_request, SpanContextInterceptor,
+};
 use tonic::{codegen::InterceptedService, transport::Channel};
 use tower::timeout::Timeout;
 
 use super::service::DiscoveryService;
 
+/// Timeout of the requests forwarded to another node.
+const GRPC_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 enum DiscoveryServiceClientImpl {
 	Rest(Arc<dyn DiscoveryService>),
@@ -64,6 +77,13 @@ impl DiscoveryServiceClient {
 		DiscoveryServiceClient { client_impl: DiscoveryServiceClientImpl::Grpc(client), grpc_addr }
 	}
 
+	/// Create a discovery service client instance connecting lazily to the node at `grpc_addr`.
+	pub async fn from_grpc_addr(grpc_addr: SocketAddr) -> Self {
+		let channel = Timeout::new(make_channel(grpc_addr).await, GRPC_TIMEOUT);
+		let client = DiscoveryClient::with_interceptor(channel, SpanContextInterceptor);
+		Self::from_grpc_client(client, grpc_addr)
+	}
+
 	/// Create a search service client instance given a search service and gRPC address.
 	pub fn from_service(service: Arc<dyn DiscoveryService>, grpc_addr: SocketAddr) -> Self {
 		DiscoveryServiceClient { client_impl: DiscoveryServiceClientImpl::Rest(service), grpc_addr }
@@ -80,3 +100,65 @@ impl DiscoveryServiceClient {
 		matches!(self.client_impl, DiscoveryServiceClientImpl::Rest(_))
 	}
 }
+
+#[async_trait]
+impl DiscoveryService for DiscoveryServiceClient {
+	async fn discover_insights(
+		&self,
+		request: DiscoveryRequest,
+	) -> super::Result<DiscoveryResponse> {
+		match &self.client_impl {
+			DiscoveryServiceClientImpl::Rest(service) => service.discover_insights(request).await,
+			DiscoveryServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.discover_insights(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn start_discovery_session(
+		&self,
+		request: DiscoverySessionRequest,
+	) -> super::Result<DiscoverySessionResponse> {
+		match &self.client_impl {
+			DiscoveryServiceClientImpl::Rest(service) =>
+				service.start_discovery_session(request).await,
+			DiscoveryServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.start_discovery_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn stop_discovery_session(
+		&self,
+		request: StopDiscoverySessionRequest,
+	) -> super::Result<StopDiscoverySessionResponse> {
+		match &self.client_impl {
+			DiscoveryServiceClientImpl::Rest(service) =>
+				service.stop_discovery_session(request).await,
+			DiscoveryServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.stop_discovery_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn get_discovery_session_list(&self) -> super::Result<DiscoverySessionRequestInfoList> {
+		match &self.client_impl {
+			DiscoveryServiceClientImpl::Rest(service) => service.get_discovery_session_list().await,
+			DiscoveryServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.list_discovery_sessions(forwarded_request(Empty {}))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+}
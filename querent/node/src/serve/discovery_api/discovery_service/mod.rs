@@ -29,7 +29,9 @@ use tracing::info;
 
 mod client;
 pub mod error;
+pub mod router;
 pub mod service;
+pub use router::DiscoveryRouter;
 pub use service::DiscoveryService;
 
 pub type InsightsPool = Pool<SocketAddr, DiscoveryServiceClient>;
@@ -63,5 +65,5 @@ pub async fn start_discovery_service(
 		metadata_store,
 		discovery_service_mailbox,
 	));
-	Ok(discovery_service)
+	Ok(Arc::new(DiscoveryRouter::new(cluster.clone(), discovery_service)))
 }
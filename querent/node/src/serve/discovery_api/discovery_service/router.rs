@@ -0,0 +1,120 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{net::SocketAddr, sync::Arc};
+
+use async_trait::async_trait;
+use cluster::{Cluster, SessionKind, SessionOwner};
+use proto::discovery::{
+	DiscoveryRequest, DiscoveryResponse, DiscoverySessionRequest, DiscoverySessionRequestInfoList,
+	DiscoverySessionResponse, StopDiscoverySessionRequest, StopDiscoverySessionResponse,
+};
+
+use super::{
+	client::DiscoveryServiceClient, error::DiscoveryError, service::DiscoveryImpl,
+	DiscoveryService, InsightsPool,
+};
+
+/// Serves the discovery sessions of the cluster from any node. Sessions are started on this
+/// node, the requests of sessions run by another node are forwarded to it over gRPC, and the
+/// sessions no live node runs are re-created here from the metadata store. The session requests
+/// are replicated to the metadata store of every node, so any node can re-create a session once
+/// its owner has left. The requests forwarded here by another node are served by
+/// [`DiscoveryImpl`], never forwarded.
+#[derive(Clone)]
+pub struct DiscoveryRouter {
+	cluster: Cluster,
+	local: Arc<DiscoveryImpl>,
+	clients: InsightsPool,
+}
+
+impl DiscoveryRouter {
+	pub fn new(cluster: Cluster, local: Arc<DiscoveryImpl>) -> Self {
+		DiscoveryRouter { cluster, local, clients: InsightsPool::default() }
+	}
+
+	/// Returns the client of the node running the session, or `None` when this node runs it.
+	async fn owner_client(
+		&self,
+		session_id: &str,
+	) -> super::Result<Option<DiscoveryServiceClient>> {
+		let owner = match self.cluster.session_owner(SessionKind::Discovery, session_id).await {
+			SessionOwner::Unknown => {
+				if self.local.restore_discovery_session(session_id).await? {
+					return Ok(None);
+				}
+				self.cluster.wait_for_session_owner(SessionKind::Discovery, session_id).await
+			},
+			owner => owner,
+		};
+		match owner {
+			SessionOwner::Node { grpc_advertise_addr, .. } =>
+				Ok(Some(self.client(grpc_advertise_addr).await)),
+			SessionOwner::SelfNode | SessionOwner::Stopped => Ok(None),
+			SessionOwner::Unknown => Err(DiscoveryError::InvalidArgument(format!(
+				"discovery session `{session_id}` not found"
+			))),
+		}
+	}
+
+	async fn client(&self, grpc_addr: SocketAddr) -> DiscoveryServiceClient {
+		if let Some(client) = self.clients.get(&grpc_addr) {
+			return client;
+		}
+		let client = DiscoveryServiceClient::from_grpc_addr(grpc_addr).await;
+		self.clients.insert(grpc_addr, client.clone());
+		client
+	}
+}
+
+#[async_trait]
+impl DiscoveryService for DiscoveryRouter {
+	async fn discover_insights(
+		&self,
+		request: DiscoveryRequest,
+	) -> super::Result<DiscoveryResponse> {
+		match self.owner_client(&request.session_id).await? {
+			Some(client) => client.discover_insights(request).await,
+			None => self.local.discover_insights(request).await,
+		}
+	}
+
+	async fn start_discovery_session(
+		&self,
+		request: DiscoverySessionRequest,
+	) -> super::Result<DiscoverySessionResponse> {
+		self.local.start_discovery_session(request).await
+	}
+
+	async fn stop_discovery_session(
+		&self,
+		request: StopDiscoverySessionRequest,
+	) -> super::Result<StopDiscoverySessionResponse> {
+		match self.owner_client(&request.session_id).await? {
+			Some(client) => client.stop_discovery_session(request).await,
+			None => self.local.stop_discovery_session(request).await,
+		}
+	}
+
+	async fn get_discovery_session_list(&self) -> super::Result<DiscoverySessionRequestInfoList> {
+		self.local.get_discovery_session_list().await
+	}
+
+	fn local_service(&self) -> Option<Arc<dyn DiscoveryService>> {
+		Some(self.local.clone())
+	}
+}
@@ -24,7 +24,7 @@ use proto::discovery::{
 	DiscoverySessionRequestInfoList, DiscoverySessionResponse, StopDiscoverySessionRequest,
 	StopDiscoverySessionResponse,
 };
-use rian_core::discovery_service::DiscoveryAgentService;
+use rian_core::discovery_service::{DiscoveryAgentService, RestoreDiscoverySession};
 use std::{collections::HashMap, sync::Arc};
 use storage::{MetaStorage, Storage};
 
@@ -52,6 +52,12 @@ pub trait DiscoveryService: 'static + Send + Sync {
 
 	/// Get list of all sessions
 	async fn get_discovery_session_list(&self) -> super::Result<DiscoverySessionRequestInfoList>;
+
+	/// The service of this node serving the requests another node forwarded here, which must not
+	/// be forwarded again. `None` when this service never forwards requests.
+	fn local_service(&self) -> Option<Arc<dyn DiscoveryService>> {
+		None
+	}
 }
 
 #[derive(Clone)]
@@ -76,6 +82,33 @@ impl DiscoveryImpl {
 			metadata_store,
 		}
 	}
+
+	/// Re-creates a session which no live node runs from the request stored when it started, on
+	/// this node or on another one. Returns `false` when the metadata store of this node has no
+	/// such session.
+	pub async fn restore_discovery_session(&self, session_id: &str) -> super::Result<bool> {
+		let request = self
+			.metadata_store
+			.get_discovery_session(&session_id.to_string())
+			.await
+			.map_err(|e| {
+				log::error!("Failed to get discovery session: {}", e);
+				DiscoveryError::Internal("Failed to get discovery session".to_string())
+			})?;
+		let Some(request) = request else {
+			return Ok(false);
+		};
+		let restore = RestoreDiscoverySession { session_id: session_id.to_string(), request };
+		let response =
+			self.discovery_agent_service_message_bus.ask(restore).await.map_err(|e| {
+				log::error!("Failed to restore discovery session: {}", e);
+				DiscoveryError::Internal("Failed to restore discovery session".to_string())
+			})?;
+		match response {
+			Ok(_) => Ok(true),
+			_ => Err(DiscoveryError::Internal("Failed to restore discovery session".to_string())),
+		}
+	}
 }
 
 #[async_trait]
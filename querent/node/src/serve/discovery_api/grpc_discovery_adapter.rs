@@ -20,7 +20,7 @@ use std::sync::Arc;
 
 use crate::discovery_api::discovery_service::{error::DiscoveryError, DiscoveryService};
 use async_trait::async_trait;
-use proto::{discovery::discovery_server as grpc, error::convert_to_grpc_result};
+use proto::{discovery::discovery_server as grpc, error::convert_to_grpc_result, is_forwarded};
 use tracing::instrument;
 
 #[derive(Clone)]
@@ -32,6 +32,19 @@ impl From<Arc<dyn DiscoveryService>> for DiscoveryAdapter {
 	}
 }
 
+impl DiscoveryAdapter {
+	/// The service serving `request`. The requests forwarded by another node are served by this
+	/// node, so that a request is never forwarded twice.
+	fn service<T>(&self, request: &tonic::Request<T>) -> Arc<dyn DiscoveryService> {
+		if is_forwarded(request) {
+			if let Some(local_service) = self.0.local_service() {
+				return local_service;
+			}
+		}
+		self.0.clone()
+	}
+}
+
 #[async_trait]
 impl grpc::Discovery for DiscoveryAdapter {
 	#[instrument(skip(self, request))]
@@ -39,9 +52,10 @@ impl grpc::Discovery for DiscoveryAdapter {
 		&self,
 		request: tonic::Request<proto::discovery::DiscoverySessionRequest>,
 	) -> Result<tonic::Response<proto::discovery::DiscoverySessionResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::discovery::DiscoverySessionResponse, DiscoveryError> =
-			self.0.start_discovery_session(req).await;
+			service.start_discovery_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -50,9 +64,10 @@ impl grpc::Discovery for DiscoveryAdapter {
 		&self,
 		request: tonic::Request<proto::discovery::DiscoveryRequest>,
 	) -> Result<tonic::Response<proto::discovery::DiscoveryResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::discovery::DiscoveryResponse, DiscoveryError> =
-			self.0.discover_insights(req).await;
+			service.discover_insights(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -61,9 +76,10 @@ impl grpc::Discovery for DiscoveryAdapter {
 		&self,
 		request: tonic::Request<proto::discovery::StopDiscoverySessionRequest>,
 	) -> Result<tonic::Response<proto::discovery::StopDiscoverySessionResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::discovery::StopDiscoverySessionResponse, DiscoveryError> =
-			self.0.stop_discovery_session(req).await;
+			service.stop_discovery_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -73,9 +89,10 @@ impl grpc::Discovery for DiscoveryAdapter {
 		empty: tonic::Request<proto::discovery::Empty>,
 	) -> Result<tonic::Response<proto::discovery::DiscoverySessionRequestInfoList>, tonic::Status>
 	{
+		let service = self.service(&empty);
 		let _req = empty.into_inner();
 		let res: Result<proto::discovery::DiscoverySessionRequestInfoList, DiscoveryError> =
-			self.0.get_discovery_session_list().await;
+			service.get_discovery_session_list().await;
 		convert_to_grpc_result(res)
 	}
 }
@@ -27,12 +27,13 @@ use proto::{
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use tracing::{Instrument, Span};
 use warp::{reject::Rejection, Filter};
 
 use crate::{
 	discovery_api::discovery_service::{error::DiscoveryError, DiscoveryService},
 	extract_format_from_qs, make_json_api_response,
-	serve::require,
+	serve::{request_span, require},
 };
 
 #[derive(utoipa::OpenApi)]
@@ -131,7 +132,12 @@ pub fn discover_post_filter(
 		.and(warp::body::json())
 		.and(warp::post())
 		.and(require(Some(discovery_service)))
-		.then(discovery_post_handler)
+		.and(request_span("discovery_search"))
+		.then(
+			|request: DiscoveryRequest,
+			 discovery_service: Option<Arc<dyn DiscoveryService>>,
+			 span: Span| discovery_post_handler(request, discovery_service).instrument(span),
+		)
 		.and(extract_format_from_qs())
 		.map(make_json_api_response)
 		.boxed()
@@ -173,7 +179,12 @@ pub fn discover_get_filter(
 		.and(warp::query::<DiscoveryRequestParam>())
 		.and(warp::get())
 		.and(require(Some(discovery_service)))
-		.then(discovery_get_handler)
+		.and(request_span("discovery_search"))
+		.then(
+			|request: DiscoveryRequestParam,
+			 discovery_service: Option<Arc<dyn DiscoveryService>>,
+			 span: Span| discovery_get_handler(request, discovery_service).instrument(span),
+		)
 		.and(extract_format_from_qs())
 		.map(make_json_api_response)
 		.boxed()
@@ -18,7 +18,9 @@
 
 use async_trait::async_trait;
 use insights::InsightError;
-use proto::{error::convert_to_grpc_result, insights::insight_service_server as grpc};
+use proto::{
+	error::convert_to_grpc_result, insights::insight_service_server as grpc, is_forwarded,
+};
 use std::sync::Arc;
 use tracing::instrument;
 
@@ -33,6 +35,19 @@ impl From<Arc<dyn InsightService>> for InsightAdapter {
 	}
 }
 
+impl InsightAdapter {
+	/// The service serving `request`. The requests forwarded by another node are served by this
+	/// node, so that a request is never forwarded twice.
+	fn service<T>(&self, request: &tonic::Request<T>) -> Arc<dyn InsightService> {
+		if is_forwarded(request) {
+			if let Some(local_service) = self.0.local_service() {
+				return local_service;
+			}
+		}
+		self.0.clone()
+	}
+}
+
 #[async_trait]
 impl grpc::InsightService for InsightAdapter {
 	#[instrument(skip(self, request))]
@@ -40,9 +55,10 @@ impl grpc::InsightService for InsightAdapter {
 		&self,
 		request: tonic::Request<proto::insights::InsightAnalystRequest>,
 	) -> Result<tonic::Response<proto::insights::InsightAnalystResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::InsightAnalystResponse, InsightError> =
-			self.0.create_insight_session(req).await;
+			service.create_insight_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -51,9 +67,10 @@ impl grpc::InsightService for InsightAdapter {
 		&self,
 		request: tonic::Request<proto::insights::InsightQuery>,
 	) -> Result<tonic::Response<proto::insights::InsightQueryResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::InsightQueryResponse, InsightError> =
-			self.0.provide_insight_input(req).await;
+			service.provide_insight_input(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -62,9 +79,10 @@ impl grpc::InsightService for InsightAdapter {
 		&self,
 		request: tonic::Request<proto::insights::StopInsightSessionRequest>,
 	) -> Result<tonic::Response<proto::insights::StopInsightSessionResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::StopInsightSessionResponse, InsightError> =
-			self.0.stop_insight_session(req).await;
+			service.stop_insight_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -73,9 +91,10 @@ impl grpc::InsightService for InsightAdapter {
 		&self,
 		request: tonic::Request<proto::insights::EmptyInput>,
 	) -> Result<tonic::Response<proto::insights::InsightRequestInfoList>, tonic::Status> {
+		let service = self.service(&request);
 		let _req = request.into_inner();
 		let res: Result<proto::InsightRequestInfoList, InsightError> =
-			self.0.get_insight_request_list().await;
+			service.get_insight_request_list().await;
 		convert_to_grpc_result(res)
 	}
 }
@@ -16,13 +16,28 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use proto::SpanContextInterceptor;
-use std::{fmt, net::SocketAddr, sync::Arc};
+use async_trait::async_trait;
+use common::tower::make_channel;
+use insights::InsightResult;
+use proto::{
+	error::grpc_status_to_service_error,
+	forwarded_request,
+	insights::{
+		insight_service_client::InsightServiceClient as InsightServiceGrpcClient, EmptyInput,
+		InsightAnalystRequest, InsightAnalystResponse, InsightQuery, InsightQueryResponse,
+		StopInsightSessionRequest, StopInsightSessionResponse,
+	},
+	InsightRequestInfoList, SpanContextInterceptor,
+};
+use std::{fmt, net::SocketAddr, sync::Arc, time::Duration};
 use tonic::{codegen::InterceptedService, transport::Channel};
 use tower::timeout::Timeout;
 
 use crate::insight_api::insights_service::InsightService;
 
+/// Timeout of the requests forwarded to another node.
+const GRPC_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 enum InsightServiceClientImpl {
 	Rest(Arc<dyn InsightService>),
@@ -63,6 +78,13 @@ impl InsightServiceClient {
 		InsightServiceClient { client_impl: InsightServiceClientImpl::Grpc(client), grpc_addr }
 	}
 
+	/// Create an insight service client instance connecting lazily to the node at `grpc_addr`.
+	pub async fn from_grpc_addr(grpc_addr: SocketAddr) -> Self {
+		let channel = Timeout::new(make_channel(grpc_addr).await, GRPC_TIMEOUT);
+		let client = InsightServiceGrpcClient::with_interceptor(channel, SpanContextInterceptor);
+		Self::from_grpc_client(client, grpc_addr)
+	}
+
 	/// Create a search service client instance given a search service and gRPC address.
 	pub fn from_service(service: Arc<dyn InsightService>, grpc_addr: SocketAddr) -> Self {
 		InsightServiceClient { client_impl: InsightServiceClientImpl::Rest(service), grpc_addr }
@@ -79,3 +101,64 @@ impl InsightServiceClient {
 		matches!(self.client_impl, InsightServiceClientImpl::Rest(_))
 	}
 }
+
+#[async_trait]
+impl InsightService for InsightServiceClient {
+	async fn provide_insight_input(
+		&self,
+		request: InsightQuery,
+	) -> InsightResult<InsightQueryResponse> {
+		match &self.client_impl {
+			InsightServiceClientImpl::Rest(service) => service.provide_insight_input(request).await,
+			InsightServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.provide_insight_input(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn create_insight_session(
+		&self,
+		request: InsightAnalystRequest,
+	) -> InsightResult<InsightAnalystResponse> {
+		match &self.client_impl {
+			InsightServiceClientImpl::Rest(service) =>
+				service.create_insight_session(request).await,
+			InsightServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.create_insight_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn stop_insight_session(
+		&self,
+		request: StopInsightSessionRequest,
+	) -> InsightResult<StopInsightSessionResponse> {
+		match &self.client_impl {
+			InsightServiceClientImpl::Rest(service) => service.stop_insight_session(request).await,
+			InsightServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.stop_insight_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn get_insight_request_list(&self) -> InsightResult<InsightRequestInfoList> {
+		match &self.client_impl {
+			InsightServiceClientImpl::Rest(service) => service.get_insight_request_list().await,
+			InsightServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.list_insight_requests(forwarded_request(EmptyInput {}))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+}
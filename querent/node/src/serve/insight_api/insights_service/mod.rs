@@ -29,6 +29,8 @@ use storage::{MetaStorage, SecretStorage, Storage};
 use tracing::info;
 pub mod client;
 pub use client::*;
+pub mod router;
+pub use router::*;
 pub mod service;
 pub use service::*;
 
@@ -61,5 +63,5 @@ pub async fn start_insight_service(
 		metadata_store,
 		insight_service_mailbox,
 	));
-	Ok(insight_service)
+	Ok(Arc::new(InsightRouter::new(cluster.clone(), insight_service)))
 }
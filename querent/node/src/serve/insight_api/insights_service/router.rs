@@ -0,0 +1,118 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{net::SocketAddr, sync::Arc};
+
+use async_trait::async_trait;
+use cluster::{Cluster, SessionKind, SessionOwner};
+use common::Pool;
+use insights::{InsightError, InsightErrorKind, InsightResult};
+use proto::{
+	insights::{
+		InsightAnalystRequest, InsightAnalystResponse, InsightQuery, InsightQueryResponse,
+		StopInsightSessionRequest, StopInsightSessionResponse,
+	},
+	InsightRequestInfoList,
+};
+
+use super::{InsightImpl, InsightService, InsightServiceClient};
+
+/// Serves the insight sessions of the cluster from any node, forwarding the requests of sessions
+/// run by another node and re-creating the sessions no live node runs. Sessions are re-created
+/// from the metadata store of this node, to which the requests of the sessions started on any
+/// node are replicated. Forwarded requests are served locally.
+#[derive(Clone)]
+pub struct InsightRouter {
+	cluster: Cluster,
+	local: Arc<InsightImpl>,
+	clients: Pool<SocketAddr, InsightServiceClient>,
+}
+
+impl InsightRouter {
+	pub fn new(cluster: Cluster, local: Arc<InsightImpl>) -> Self {
+		InsightRouter { cluster, local, clients: Pool::default() }
+	}
+
+	/// Returns the client of the node running the session, or `None` when this node runs it.
+	async fn owner_client(&self, session_id: &str) -> InsightResult<Option<InsightServiceClient>> {
+		let owner = match self.cluster.session_owner(SessionKind::Insight, session_id).await {
+			SessionOwner::Unknown => {
+				if self.local.restore_insight_session(session_id).await? {
+					return Ok(None);
+				}
+				self.cluster.wait_for_session_owner(SessionKind::Insight, session_id).await
+			},
+			owner => owner,
+		};
+		match owner {
+			SessionOwner::Node { grpc_advertise_addr, .. } =>
+				Ok(Some(self.client(grpc_advertise_addr).await)),
+			SessionOwner::SelfNode | SessionOwner::Stopped => Ok(None),
+			SessionOwner::Unknown => Err(InsightError::new(
+				InsightErrorKind::NotFound,
+				Arc::new(anyhow::anyhow!("Insight session `{}` not found", session_id)),
+			)),
+		}
+	}
+
+	async fn client(&self, grpc_addr: SocketAddr) -> InsightServiceClient {
+		if let Some(client) = self.clients.get(&grpc_addr) {
+			return client;
+		}
+		let client = InsightServiceClient::from_grpc_addr(grpc_addr).await;
+		self.clients.insert(grpc_addr, client.clone());
+		client
+	}
+}
+
+#[async_trait]
+impl InsightService for InsightRouter {
+	async fn provide_insight_input(
+		&self,
+		request: InsightQuery,
+	) -> InsightResult<InsightQueryResponse> {
+		match self.owner_client(&request.session_id).await? {
+			Some(client) => client.provide_insight_input(request).await,
+			None => self.local.provide_insight_input(request).await,
+		}
+	}
+
+	async fn create_insight_session(
+		&self,
+		request: InsightAnalystRequest,
+	) -> InsightResult<InsightAnalystResponse> {
+		self.local.create_insight_session(request).await
+	}
+
+	async fn stop_insight_session(
+		&self,
+		request: StopInsightSessionRequest,
+	) -> InsightResult<StopInsightSessionResponse> {
+		match self.owner_client(&request.session_id).await? {
+			Some(client) => client.stop_insight_session(request).await,
+			None => self.local.stop_insight_session(request).await,
+		}
+	}
+
+	async fn get_insight_request_list(&self) -> InsightResult<InsightRequestInfoList> {
+		self.local.get_insight_request_list().await
+	}
+
+	fn local_service(&self) -> Option<Arc<dyn InsightService>> {
+		Some(self.local.clone())
+	}
+}
@@ -29,7 +29,7 @@ use proto::{
 	},
 	InsightRequestInfo, InsightRequestInfoList,
 };
-use rian_core::InsightAgentService;
+use rian_core::{InsightAgentService, RestoreInsightSession};
 use storage::{MetaStorage, Storage};
 
 #[async_trait]
@@ -54,6 +54,12 @@ pub trait InsightService: 'static + Send + Sync {
 
 	/// List all sessions
 	async fn get_insight_request_list(&self) -> InsightResult<InsightRequestInfoList>;
+
+	/// The service of this node serving the requests another node forwarded here, which must not
+	/// be forwarded again. `None` when this service never forwards requests.
+	fn local_service(&self) -> Option<Arc<dyn InsightService>> {
+		None
+	}
 }
 
 #[derive(Clone)]
@@ -78,6 +84,34 @@ impl InsightImpl {
 			metadata_store,
 		}
 	}
+
+	/// Re-creates a session which no live node runs from the request stored when it started, on
+	/// this node or on another one. Returns `false` when the metadata store of this node has no
+	/// such session.
+	pub async fn restore_insight_session(&self, session_id: &str) -> InsightResult<bool> {
+		let request =
+			self.metadata_store.get_insight_session(&session_id.to_string()).await.map_err(
+				|e| {
+					log::error!("Failed to get insight session: {}", e);
+					InsightError::new(
+						InsightErrorKind::Internal,
+						Arc::new(anyhow::anyhow!("Failed to get insight session: {}", e)),
+					)
+				},
+			)?;
+		let Some(request) = request else {
+			return Ok(false);
+		};
+		let restore = RestoreInsightSession { session_id: session_id.to_string(), request };
+		let response = self.insight_agent_service_message_bus.ask(restore).await.map_err(|e| {
+			log::error!("Failed to restore insight session: {}", e);
+			InsightError::new(
+				InsightErrorKind::Internal,
+				Arc::new(anyhow::anyhow!("Failed to restore insight session: {}", e)),
+			)
+		})?;
+		response.map(|_| true)
+	}
 }
 
 #[async_trait]
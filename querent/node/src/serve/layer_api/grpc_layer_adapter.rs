@@ -20,7 +20,7 @@ use std::sync::Arc;
 
 use crate::layer_api::layer_service::{error::LayerError, LayerService};
 use async_trait::async_trait;
-use proto::{error::convert_to_grpc_result, layer::layer_server as grpc};
+use proto::{error::convert_to_grpc_result, is_forwarded, layer::layer_server as grpc};
 use tracing::instrument;
 
 #[derive(Clone)]
@@ -32,6 +32,19 @@ impl From<Arc<dyn LayerService>> for LayerAdapter {
 	}
 }
 
+impl LayerAdapter {
+	/// The service serving `request`. The requests forwarded by another node are served by this
+	/// node, so that a request is never forwarded twice.
+	fn service<T>(&self, request: &tonic::Request<T>) -> Arc<dyn LayerService> {
+		if is_forwarded(request) {
+			if let Some(local_service) = self.0.local_service() {
+				return local_service;
+			}
+		}
+		self.0.clone()
+	}
+}
+
 #[async_trait]
 impl grpc::Layer for LayerAdapter {
 	#[instrument(skip(self, request))]
@@ -39,9 +52,10 @@ impl grpc::Layer for LayerAdapter {
 		&self,
 		request: tonic::Request<proto::layer::LayerSessionRequest>,
 	) -> Result<tonic::Response<proto::layer::LayerSessionResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::layer::LayerSessionResponse, LayerError> =
-			self.0.start_layer_session(req).await;
+			service.start_layer_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -50,8 +64,10 @@ impl grpc::Layer for LayerAdapter {
 		&self,
 		request: tonic::Request<proto::layer::LayerRequest>,
 	) -> Result<tonic::Response<proto::layer::LayerResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
-		let res: Result<proto::layer::LayerResponse, LayerError> = self.0.layer_insights(req).await;
+		let res: Result<proto::layer::LayerResponse, LayerError> =
+			service.layer_insights(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -60,9 +76,10 @@ impl grpc::Layer for LayerAdapter {
 		&self,
 		request: tonic::Request<proto::layer::StopLayerSessionRequest>,
 	) -> Result<tonic::Response<proto::layer::StopLayerSessionResponse>, tonic::Status> {
+		let service = self.service(&request);
 		let req = request.into_inner();
 		let res: Result<proto::layer::StopLayerSessionResponse, LayerError> =
-			self.0.stop_layer_session(req).await;
+			service.stop_layer_session(req).await;
 		convert_to_grpc_result(res)
 	}
 
@@ -71,9 +88,10 @@ impl grpc::Layer for LayerAdapter {
 		&self,
 		empty: tonic::Request<proto::layer::Empty>,
 	) -> Result<tonic::Response<proto::layer::LayerSessionRequestInfoList>, tonic::Status> {
+		let service = self.service(&empty);
 		let _req = empty.into_inner();
 		let res: Result<proto::layer::LayerSessionRequestInfoList, LayerError> =
-			self.0.get_layer_session_list().await;
+			service.get_layer_session_list().await;
 		convert_to_grpc_result(res)
 	}
 }
@@ -16,14 +16,28 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use std::{fmt, net::SocketAddr, sync::Arc};
+use std::{fmt, net::SocketAddr, sync::Arc, time::Duration};
 
-use proto::SpanContextInterceptor;
+use async_trait::async_trait;
+use common::tower::make_channel;
+use proto::{
+	error::grpc_status_to_service_error,
+	forwarded_request,
+	layer::{
+		layer_client::LayerClient, Empty, LayerRequest, LayerResponse, LayerSessionRequest,
+		LayerSessionRequestInfoList, LayerSessionResponse, StopLayerSessionRequest,
+		StopLayerSessionResponse,
+	},
+	SpanContextInterceptor,
+};
 use tonic::{codegen::InterceptedService, transport::Channel};
 use tower::timeout::Timeout;
 
 use super::service::LayerService;
 
+/// Timeout of the requests forwarded to another node.
+const GRPC_TIMEOUT: Duration = Duration::from_secs(30);
+
 #[derive(Clone)]
 enum LayerServiceClientImpl {
 	Rest(Arc<dyn LayerService>),
@@ -64,6 +78,13 @@ impl LayerServiceClient {
 		LayerServiceClient { client_impl: LayerServiceClientImpl::Grpc(client), grpc_addr }
 	}
 
+	/// Create a layer service client instance connecting lazily to the node at `grpc_addr`.
+	pub async fn from_grpc_addr(grpc_addr: SocketAddr) -> Self {
+		let channel = Timeout::new(make_channel(grpc_addr).await, GRPC_TIMEOUT);
+		let client = LayerClient::with_interceptor(channel, SpanContextInterceptor);
+		Self::from_grpc_client(client, grpc_addr)
+	}
+
 	/// Create a search service client instance given a search service and gRPC address.
 	pub fn from_service(service: Arc<dyn LayerService>, grpc_addr: SocketAddr) -> Self {
 		LayerServiceClient { client_impl: LayerServiceClientImpl::Rest(service), grpc_addr }
@@ -80,3 +101,60 @@ impl LayerServiceClient {
 		matches!(self.client_impl, LayerServiceClientImpl::Rest(_))
 	}
 }
+
+#[async_trait]
+impl LayerService for LayerServiceClient {
+	async fn layer_insights(&self, request: LayerRequest) -> super::Result<LayerResponse> {
+		match &self.client_impl {
+			LayerServiceClientImpl::Rest(service) => service.layer_insights(request).await,
+			LayerServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.layer_insights(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn start_layer_session(
+		&self,
+		request: LayerSessionRequest,
+	) -> super::Result<LayerSessionResponse> {
+		match &self.client_impl {
+			LayerServiceClientImpl::Rest(service) => service.start_layer_session(request).await,
+			LayerServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.start_layer_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn stop_layer_session(
+		&self,
+		request: StopLayerSessionRequest,
+	) -> super::Result<StopLayerSessionResponse> {
+		match &self.client_impl {
+			LayerServiceClientImpl::Rest(service) => service.stop_layer_session(request).await,
+			LayerServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.stop_layer_session(forwarded_request(request))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+
+	async fn get_layer_session_list(&self) -> super::Result<LayerSessionRequestInfoList> {
+		match &self.client_impl {
+			LayerServiceClientImpl::Rest(service) => service.get_layer_session_list().await,
+			LayerServiceClientImpl::Grpc(grpc_client) => grpc_client
+				.clone()
+				.list_layer_sessions(forwarded_request(Empty {}))
+				.await
+				.map(|response| response.into_inner())
+				.map_err(grpc_status_to_service_error),
+		}
+	}
+}
@@ -29,7 +29,9 @@ use tracing::info;
 
 mod client;
 pub mod error;
+pub mod router;
 pub mod service;
+pub use router::LayerRouter;
 pub use service::LayerService;
 
 pub type InsightsPool = Pool<SocketAddr, LayerServiceClient>;
@@ -63,5 +65,5 @@ pub async fn start_layer_service(
 		metadata_store,
 		layer_service_mailbox,
 	));
-	Ok(layer_service)
+	Ok(Arc::new(LayerRouter::new(cluster.clone(), layer_service)))
 }
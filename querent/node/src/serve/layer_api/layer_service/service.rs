@@ -24,7 +24,7 @@ use proto::layer::{
 	LayerSessionRequestInfoList, LayerSessionResponse, StopLayerSessionRequest,
 	StopLayerSessionResponse,
 };
-use rian_core::layer_service::LayerAgentService;
+use rian_core::layer_service::{LayerAgentService, RestoreLayerSession};
 use std::{collections::HashMap, sync::Arc};
 use storage::{MetaStorage, Storage};
 
@@ -49,6 +49,12 @@ pub trait LayerService: 'static + Send + Sync {
 
 	/// Get list of all sessions
 	async fn get_layer_session_list(&self) -> super::Result<LayerSessionRequestInfoList>;
+
+	/// The service of this node serving the requests another node forwarded here, which must not
+	/// be forwarded again. `None` when this service never forwards requests.
+	fn local_service(&self) -> Option<Arc<dyn LayerService>> {
+		None
+	}
 }
 
 #[derive(Clone)]
@@ -73,6 +79,32 @@ impl LayerImpl {
 			metadata_store,
 		}
 	}
+
+	/// Re-creates a session which no live node runs from the request stored when it started, on
+	/// this node or on another one. Returns `false` when the metadata store of this node has no
+	/// such session.
+	pub async fn restore_layer_session(&self, session_id: &str) -> super::Result<bool> {
+		let request = self
+			.metadata_store
+			.get_layer_session(&session_id.to_string())
+			.await
+			.map_err(|e| {
+				log::error!("Failed to get layer session: {}", e);
+				LayerError::Internal("Failed to get layer session".to_string())
+			})?;
+		let Some(request) = request else {
+			return Ok(false);
+		};
+		let restore = RestoreLayerSession { session_id: session_id.to_string(), request };
+		let response = self.layer_agent_service_message_bus.ask(restore).await.map_err(|e| {
+			log::error!("Failed to restore layer session: {}", e);
+			LayerError::Internal("Failed to restore layer session".to_string())
+		})?;
+		match response {
+			Ok(_) => Ok(true),
+			_ => Err(LayerError::Internal("Failed to restore layer session".to_string())),
+		}
+	}
 }
 
 #[async_trait]
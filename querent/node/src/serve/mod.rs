@@ -22,6 +22,7 @@ use std::{convert::Infallible, time::Duration};
 pub use build_info::*;
 pub mod service;
 use cluster::Cluster;
+use common::TraceContext;
 pub use service::*;
 pub mod cluster_api;
 pub use cluster_api::*;
@@ -35,6 +36,8 @@ pub mod discovery_api;
 pub mod insight_api;
 pub use insight_api::*;
 pub mod layer_api;
+pub mod session_replication;
+pub use session_replication::*;
 
 pub mod health_check_api;
 pub use health_check_api::*;
@@ -79,6 +82,22 @@ fn with_arg<T: Clone + Send>(arg: T) -> impl Filter<Extract = (T,), Error = Infa
 	warp::any().map(move || arg.clone())
 }
 
+/// Opens a server span for the request. When the caller sends W3C `traceparent` headers, the span
+/// continues the caller's trace.
+fn request_span(
+	name: &'static str,
+) -> impl Filter<Extract = (tracing::Span,), Error = Infallible> + Clone {
+	warp::header::headers_cloned().map(move |headers: warp::http::HeaderMap| {
+		let span = tracing::info_span!("rest_request", otel.name = name, otel.kind = "server");
+		let trace_context: TraceContext = headers
+			.iter()
+			.filter_map(|(key, value)| Some((key.as_str(), value.to_str().ok()?)))
+			.collect();
+		trace_context.attach_to(&span);
+		span
+	})
+}
+
 async fn node_readiness(
 	cluster: Cluster,
 	grpc_readiness_signal_rx: oneshot::Receiver<()>,
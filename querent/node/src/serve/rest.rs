@@ -43,9 +43,9 @@ use crate::{
 		discover_get_filter, discover_post_filter, get_discovery_history_handler,
 		start_discovery_session_filter, stop_discovery_session_filter,
 	},
-	get_pipelines_history_handler, get_pipelines_metadata_handler,
+	get_document_states_handler, get_pipelines_history_handler, get_pipelines_metadata_handler,
 	health_check_api::health_check_handlers,
-	ingest_token_handler, ingest_tokens_put_handler,
+	import_knowledge_post_handler, ingest_token_handler, ingest_tokens_put_handler,
 	insight_api::rest::{
 		get_insights_history_handler, insights_prompt_filter, list_insights_handler,
 		start_insights_session_filter, stop_insight_session_filter,
@@ -56,9 +56,9 @@ use crate::{
 		stop_layer_session_filter,
 	},
 	list_collectors_list_handler, metrics_handler, node_info_handler, observe_pipeline_get_handler,
-	pipelines_get_all_handler, restart_pipeline_post_handler, set_collectors_post_handler,
-	start_pipeline_post_handler, stop_pipeline_delete_handler, BodyFormat, BuildInfo,
-	QuerentServices, RuntimeInfo,
+	pipelines_get_all_handler, restart_pipeline_post_handler, retry_documents_post_handler,
+	set_collectors_post_handler, start_pipeline_post_handler, stop_pipeline_delete_handler,
+	BodyFormat, BuildInfo, QuerentServices, RuntimeInfo,
 };
 
 /// The minimum size a response body must be in order to
@@ -221,6 +221,10 @@ fn api_v1_routes(
 				.or(ingest_token_handler(Some(services.semantic_service_bus.clone())))
 				.or(ingest_tokens_put_handler(Some(services.semantic_service_bus.clone())))
 				.or(restart_pipeline_post_handler(Some(services.semantic_service_bus.clone())))
+				.or(import_knowledge_post_handler(
+					services.event_storages.clone(),
+					services.index_storages.clone(),
+				))
 				.or(start_discovery_session_filter(services.discovery_service.clone()))
 				.or(discover_get_filter(services.discovery_service.clone()))
 				.or(discover_post_filter(services.discovery_service.clone()))
@@ -233,6 +237,8 @@ fn api_v1_routes(
 				.or(insights_prompt_filter(services.insight_service.clone()))
 				.or(list_insights_handler())
 				.or(get_pipelines_history_handler(services.metadata_store.clone()))
+				.or(get_document_states_handler(services.metadata_store.clone()))
+				.or(retry_documents_post_handler(Some(services.semantic_service_bus.clone())))
 				.or(get_insights_history_handler(services.insight_service.clone()))
 				.or(get_discovery_history_handler(services.discovery_service.clone()))
 				.or(layer_get_filter(services.layer_service.clone()))
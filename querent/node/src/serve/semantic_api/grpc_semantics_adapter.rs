@@ -164,6 +164,9 @@ impl grpc::SemanticsServiceGrpc for SemanticsGrpcAdapter {
 				doc_source: token.doc_source.into(),
 				source_id: token.source_id.into(),
 				image_id: None,
+				section_path: token.section_path,
+				table: token.table,
+				duplicate_of: None,
 			};
 			synapse_tokens.push(synapse_token);
 		});
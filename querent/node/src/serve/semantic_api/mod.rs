@@ -22,3 +22,5 @@ pub mod grpc_semantics_adapter;
 pub use grpc_semantics_adapter::*;
 pub mod ner_model_directory;
 pub use ner_model_directory::*;
+pub mod placement;
+pub use placement::*;
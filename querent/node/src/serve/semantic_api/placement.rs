@@ -0,0 +1,114 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Arc,
+};
+
+use actors::MessageBus;
+use async_trait::async_trait;
+use cluster::{
+	PipelineCatalog, PipelineLauncher, PlacementCost, PlacementScheduler, PlacementTask,
+};
+use common::EventType;
+use proto::semantics::SemanticPipelineRequest;
+use rian_core::SemanticService;
+use storage::{MetaStorage, SecretStorage, Storage};
+use tokio::task::JoinHandle;
+
+use crate::{spawn_pipeline, QuerentServices};
+
+/// The pipelines stored in the metadata store of this node. The scheduler replicates them through
+/// the cluster state, so every node stores the pipelines started on any other one and a survivor
+/// can run the pipelines of a dead node. The definitions carry the collector credentials, so the
+/// gossip network must be as trusted as the gRPC one.
+struct StoredPipelines {
+	semantic_service_mailbox: MessageBus<SemanticService>,
+	event_storages: HashMap<EventType, Vec<Arc<dyn Storage>>>,
+	index_storages: Vec<Arc<dyn Storage>>,
+	secret_store: Arc<dyn SecretStorage>,
+	metadata_store: Arc<dyn MetaStorage>,
+}
+
+#[async_trait]
+impl PipelineCatalog for StoredPipelines {
+	async fn list_tasks(&self) -> anyhow::Result<Vec<PlacementTask>> {
+		let pipelines = self.metadata_store.get_all_pipelines().await?;
+		Ok(pipelines
+			.into_iter()
+			.flat_map(|(pipeline_id, request)| {
+				PlacementTask::for_pipeline(&pipeline_id, request.shards.unwrap_or_default())
+			})
+			.collect())
+	}
+
+	async fn definitions(&self) -> anyhow::Result<BTreeMap<String, String>> {
+		let pipelines = self.metadata_store.get_all_pipelines().await?;
+		pipelines
+			.into_iter()
+			.map(|(pipeline_id, request)| Ok((pipeline_id, serde_json::to_string(&request)?)))
+			.collect()
+	}
+
+	async fn store_definition(&self, pipeline_id: &str, definition: &str) -> anyhow::Result<()> {
+		let request: SemanticPipelineRequest = serde_json::from_str(definition)?;
+		self.metadata_store.set_pipeline(&pipeline_id.to_string(), request).await?;
+		Ok(())
+	}
+}
+
+#[async_trait]
+impl PipelineLauncher for StoredPipelines {
+	async fn launch(&self, task: &PlacementTask) -> anyhow::Result<()> {
+		let request =
+			self.metadata_store.get_pipeline(&task.pipeline_id).await?.ok_or_else(|| {
+				anyhow::anyhow!("Semantic pipeline `{}` is not stored.", task.pipeline_id)
+			})?;
+		spawn_pipeline(
+			task.task_id(),
+			&request,
+			task.key_range(),
+			self.semantic_service_mailbox.clone(),
+			self.event_storages.clone(),
+			self.index_storages.clone(),
+			self.secret_store.clone(),
+			self.metadata_store.clone(),
+		)
+		.await?;
+		Ok(())
+	}
+}
+
+/// Starts the scheduler placing the pipelines on the nodes of the cluster and moving the
+/// pipelines of dead nodes to the surviving ones.
+pub fn start_placement_scheduler(services: &QuerentServices) -> JoinHandle<()> {
+	let stored_pipelines = Arc::new(StoredPipelines {
+		semantic_service_mailbox: services.semantic_service_bus.clone(),
+		event_storages: services.event_storages.clone(),
+		index_storages: services.index_storages.clone(),
+		secret_store: services.secret_store.clone(),
+		metadata_store: services.metadata_store.clone(),
+	});
+	PlacementScheduler::new(
+		services.cluster.clone(),
+		stored_pipelines.clone(),
+		stored_pipelines,
+		PlacementCost::default(),
+	)
+	.spawn()
+}
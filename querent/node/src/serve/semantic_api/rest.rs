@@ -17,39 +17,64 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use actors::{AskError, MessageBus, Observe};
+use cluster::{KeyRange, PlacementTask};
 use common::{get_querent_data_path, EventType};
-use engines::agn::AttentionTensorsEngine;
+use engines::{
+	agn::{AttentionTensorsEngine, CoreferenceResolver, PredicateNormalizer},
+	chunking::create_chunker,
+	extraction::LlmExtractionEngine,
+	table::TableModeEngine,
+	Engine,
+};
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use futures_util::StreamExt;
+use ingestors::{
+	ocr::create_recognizer,
+	processors::{
+		pii::RedactionAudit,
+		registry::{create_processors, ProcessorContext},
+	},
+	IngestorOptions,
+};
 use llms::{
+	client::{Ollama, OllamaClient},
 	transformers::{
 		bert::{BertLLM, EmbedderOptions},
 		roberta::roberta::RobertaLLM,
 	},
-	LLM,
+	CallOptions, Claude, OpenAI, OpenAIConfig, LLM,
 };
+use once_cell::sync::OnceCell;
 use proto::{
 	config::StorageConfigs,
 	semantics::{
-		AzureCollectorConfig, Backend, CollectorConfig, CollectorConfigResponse,
-		DeleteCollectorRequest, DeleteCollectorResponse, DropBoxCollectorConfig,
-		EmailCollectorConfig, EmptyGetPipelinesMetadata, FileCollectorConfig, FixedEntities,
-		GcsCollectorConfig, GithubCollectorConfig, GoogleDriveCollectorConfig, IndexingStatistics,
-		JiraCollectorConfig, ListCollectorConfig, ListCollectorRequest, Neo4jConfig,
-		NewsCollectorConfig, NotionConfig, OneDriveConfig, OsduServiceConfig, PipelineMetadata,
-		PipelineRequestInfo, PipelineRequestInfoList, PipelinesMetadata, PostgresConfig,
-		RecordKind, S3CollectorConfig, SalesForceConfig, SampleEntities, SemanticPipelineRequest,
-		SemanticPipelineResponse, SendIngestedTokens, SlackCollectorConfig, StorageConfig,
-		StorageType,
+		AzureCollectorConfig, Backend, CollectorConfig, CollectorConfigResponse, ColumnType,
+		ConfluenceConfig, CoreferenceConfig, CoreferenceStrategy, DeleteCollectorRequest,
+		DeleteCollectorResponse, DocumentState, DocumentStateList, DocumentStatus,
+		DropBoxCollectorConfig, EmailCollectorConfig, EmptyGetPipelinesMetadata, EntityAliases,
+		EntityResolutionConfig, ExtractedTable, ExtractionConfig, ExtractionEngine,
+		FileCollectorConfig, FixedEntities, GcsCollectorConfig, GithubCollectorConfig,
+		GoogleDriveCollectorConfig, IndexingStatistics, JiraCollectorConfig, KafkaCollectorConfig,
+		KnowledgeImportFormat, KnowledgeImportRequest, KnowledgeImportResponse,
+		ListCollectorConfig, ListCollectorRequest, LlmProvider, Neo4jConfig, NewsCollectorConfig,
+		NotionConfig, OneDriveConfig, OsduServiceConfig, PipelineMetadata, PipelineRequestInfo,
+		PipelineRequestInfoList, PipelinesMetadata, PostgresConfig, PredicateSchema, RecordKind,
+		RelationType, RetryDocumentsRequest, RetryDocumentsResponse, S3CollectorConfig,
+		SalesForceConfig, SampleEntities, SemanticPipelineRequest, SemanticPipelineResponse,
+		SendIngestedTokens, SharePointConfig, SlackCollectorConfig, SqlCollectorConfig,
+		StorageConfig, StorageType, TableModeConfig, TableRow, UnmatchedRelationPolicy,
+		WebCrawlerConfig,
 	},
 };
 use serde_json::from_str;
 
 use proto::semantics::IngestedTokens;
 use rian_core::{
-	create_dynamic_sources, ObservePipeline, PipelineErrors, PipelineSettings, RestartPipeline,
+	create_dynamic_sources, import_knowledge, ingest::dedup::Deduplicator, EntityResolver,
+	ObservePipeline, PipelineErrors, PipelineSettings, RestartPipeline, RetryDocuments,
 	SemanticService, SemanticServiceCounters, ShutdownPipeline, SpawnPipeline,
 };
+use serde::Deserialize;
 use std::{collections::HashMap, convert::Infallible, sync::Arc};
 use tracing::{error, warn};
 use warp::{filters::ws::WebSocket, reject::Rejection, Filter};
@@ -70,6 +95,9 @@ use crate::{extract_format_from_qs, make_json_api_response, serve::require, Mode
 		delete_collectors,
 		list_collectors,
 		get_pipelines_history,
+		import_knowledge_into_collection,
+		get_document_states,
+		retry_documents,
 	),
 	components(schemas(
 		SemanticPipelineRequest,
@@ -79,6 +107,8 @@ use crate::{extract_format_from_qs, make_json_api_response, serve::require, Mode
 		IndexingStatistics,
 		CollectorConfig,
 		IngestedTokens,
+		ExtractedTable,
+		TableRow,
 		PipelinesMetadata,
 		PipelineMetadata,
 		GcsCollectorConfig,
@@ -97,6 +127,18 @@ use crate::{extract_format_from_qs, make_json_api_response, serve::require, Mode
 		Neo4jConfig,
 		FixedEntities,
 		SampleEntities,
+		EntityResolutionConfig,
+		EntityAliases,
+		PredicateSchema,
+		RelationType,
+		UnmatchedRelationPolicy,
+		TableModeConfig,
+		ColumnType,
+		ExtractionConfig,
+		ExtractionEngine,
+		LlmProvider,
+		CoreferenceConfig,
+		CoreferenceStrategy,
 		AzureCollectorConfig,
 		EmailCollectorConfig,
 		SlackCollectorConfig,
@@ -111,6 +153,19 @@ use crate::{extract_format_from_qs, make_json_api_response, serve::require, Mode
 		OsduServiceConfig,
 		RecordKind,
 		SalesForceConfig,
+		ConfluenceConfig,
+		SharePointConfig,
+		SqlCollectorConfig,
+		WebCrawlerConfig,
+		KafkaCollectorConfig,
+		KnowledgeImportRequest,
+		KnowledgeImportResponse,
+		KnowledgeImportFormat,
+		DocumentState,
+		DocumentStateList,
+		DocumentStatus,
+		RetryDocumentsRequest,
+		RetryDocumentsResponse,
 	))
 )]
 pub struct SemanticApi;
@@ -259,6 +314,41 @@ pub async fn start_pipeline(
 	metadata_store: Arc<dyn storage::MetaStorage>,
 ) -> Result<SemanticPipelineResponse, PipelineErrors> {
 	let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+	// A pipeline split by key range starts with its first shard on this node, the placement
+	// scheduler places the others on the nodes of the cluster.
+	let task = PlacementTask::for_pipeline(&new_uuid, request.shards.unwrap_or_default()).remove(0);
+	spawn_pipeline(
+		task.task_id(),
+		&request,
+		task.key_range(),
+		semantic_service_mailbox,
+		event_storages,
+		index_storages,
+		secret_store,
+		metadata_store.clone(),
+	)
+	.await?;
+	metadata_store
+		.set_pipeline(&new_uuid, request)
+		.await
+		.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+	//call secret store and get the credentials
+	Ok(SemanticPipelineResponse { pipeline_id: new_uuid })
+}
+
+/// Builds the pipeline of a request and spawns it on this node, collecting only the documents
+/// of `key_range` when set.
+#[allow(clippy::too_many_arguments)]
+pub async fn spawn_pipeline(
+	pipeline_id: String,
+	request: &SemanticPipelineRequest,
+	key_range: Option<KeyRange>,
+	semantic_service_mailbox: MessageBus<SemanticService>,
+	event_storages: HashMap<EventType, Vec<Arc<dyn storage::Storage>>>,
+	index_storages: Vec<Arc<dyn storage::Storage>>,
+	secret_store: Arc<dyn storage::SecretStorage>,
+	metadata_store: Arc<dyn storage::MetaStorage>,
+) -> Result<(), PipelineErrors> {
 	// Extract entities from request.fixed_entities or use a default
 	let entities = match &request.fixed_entities {
 		Some(fixed_entities) => fixed_entities.entities.clone(),
@@ -279,8 +369,11 @@ pub async fn start_pipeline(
 		_ => ("Davlan/xlm-roberta-base-wikiann-ner".to_string(), "Roberta".to_string()), // Default to option 1
 	};
 
+	let extraction = request.extraction.clone().unwrap_or_default();
+	let llm_extraction = extraction.engine == ExtractionEngine::Llm as i32;
+
 	// Initialize NER model only if fixed_entities is not defined or empty
-	let ner_llm: Option<Arc<dyn LLM>> = if entities.is_empty() {
+	let ner_llm: Option<Arc<dyn LLM>> = if entities.is_empty() && !llm_extraction {
 		let ner_options = EmbedderOptions {
 			model: model_string.to_string(),
 			local_dir: None,
@@ -305,6 +398,7 @@ pub async fn start_pipeline(
 	};
 
 	let mut collectors_configs = Vec::new();
+	let mut pdf_passwords = HashMap::new();
 	for collector_id in request.clone().collectors {
 		let config_value = secret_store.get_secret(&collector_id).await.map_err(|e| {
 			PipelineErrors::InvalidParams(anyhow::anyhow!("Failed to create sources: {:?}", e))
@@ -317,9 +411,46 @@ pub async fn start_pipeline(
 						e
 					))
 				})?;
+			if !collector_config_value.pdf_passwords.is_empty() {
+				pdf_passwords
+					.insert(collector_id.clone(), collector_config_value.pdf_passwords.clone());
+			}
 			collectors_configs.push(collector_config_value);
 		}
 	}
+	let recognizer = match &request.ocr {
+		Some(ocr) => Some(create_recognizer(ocr).map_err(|e| {
+			PipelineErrors::InvalidParams(anyhow::anyhow!("Invalid OCR settings: {:?}", e))
+		})?),
+		None => None,
+	};
+	let redaction_audit = RedactionAudit::default();
+	let processors = if request.processors.is_empty() {
+		None
+	} else {
+		// the PII processor finds names with the NER model, loaded for it with fixed entities
+		let needs_ner = request.processors.iter().any(|processor| processor.name == "pii");
+		let processor_ner_llm = match &ner_llm {
+			Some(ner_llm) => Some(ner_llm.clone()),
+			None if needs_ner => Some(Arc::new(
+				RobertaLLM::new(EmbedderOptions {
+					model: "Davlan/xlm-roberta-base-wikiann-ner".to_string(),
+					local_dir: None,
+					revision: None,
+					distribution: None,
+				})
+				.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?,
+			) as Arc<dyn LLM>),
+			None => None,
+		};
+		let context = ProcessorContext {
+			ner_llm: processor_ner_llm,
+			redaction_audit: redaction_audit.clone(),
+		};
+		Some(create_processors(&request.processors, &context).map_err(|e| {
+			PipelineErrors::InvalidParams(anyhow::anyhow!("Invalid processors: {:?}", e))
+		})?)
+	};
 	let mut _license_key = None;
 	#[cfg(feature = "license-check")]
 	{
@@ -327,36 +458,92 @@ pub async fn start_pipeline(
 			PipelineErrors::InvalidParams(anyhow::anyhow!("Failed to create sources: {:?}", e))
 		})?;
 	};
-	let data_sources = create_dynamic_sources(_license_key, collectors_configs).await?;
+	let data_sources =
+		create_dynamic_sources(_license_key, collectors_configs, Some(metadata_store.clone()))
+			.await?;
 
-	let options = EmbedderOptions {
-		model: "sentence-transformers/all-MiniLM-L6-v2".to_string(),
-		local_dir: None,
-		revision: None,
-		distribution: None,
+	let embedding_model = shared_embedding_model()?;
+
+	let predicate_normalizer = match &request.predicate_schema {
+		Some(schema) => PredicateNormalizer::new(schema, &embedding_model)
+			.map_err(|e| {
+				PipelineErrors::InvalidParams(anyhow::anyhow!("Invalid predicate schema: {:?}", e))
+			})?
+			.map(Arc::new),
+		None => None,
 	};
-	let embedder =
-		Arc::new(BertLLM::new(options).map_err(|e| PipelineErrors::UnknownError(e.to_string()))?);
 
-	let model_details: InitOptions = InitOptions::new(EmbeddingModel::AllMiniLML6V2)
-		.with_cache_dir(get_querent_data_path())
-		.with_show_download_progress(true);
-	let embedding_model = TextEmbedding::try_new(model_details)
-		.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+	let chunker = create_chunker(
+		&request.chunking.clone().unwrap_or_default(),
+		Some(embedding_model.clone()),
+	);
+	let mut engine: Arc<dyn Engine> = if llm_extraction {
+		Arc::new(LlmExtractionEngine::new(
+			create_extraction_llm(&extraction)?,
+			&extraction,
+			entities,
+			sample_entities,
+			embedding_model.clone(),
+			predicate_normalizer,
+			chunker,
+		))
+	} else {
+		let options = EmbedderOptions {
+			model: "sentence-transformers/all-MiniLM-L6-v2".to_string(),
+			local_dir: None,
+			revision: None,
+			distribution: None,
+		};
+		let embedder = Arc::new(
+			BertLLM::new(options).map_err(|e| PipelineErrors::UnknownError(e.to_string()))?,
+		);
+		Arc::new(AttentionTensorsEngine::new(
+			embedder,
+			entities,
+			sample_entities,
+			Some(embedding_model.clone()),
+			ner_llm,
+			predicate_normalizer,
+			chunker,
+			CoreferenceResolver::new(&request.coreference.clone().unwrap_or_default())
+				.map(Arc::new),
+		))
+	};
+	if let Some(table_mode) = &request.table_mode {
+		engine = Arc::new(TableModeEngine::new(engine, table_mode, embedding_model.clone()));
+	}
 
-	let engine = Arc::new(AttentionTensorsEngine::new(
-		embedder,
-		entities,
-		sample_entities,
-		Some(embedding_model),
-		ner_llm,
-	));
+	// opt-in: the graph nodes of resolved entities are merged on their canonical id
+	let entity_resolver = request
+		.entity_resolution
+		.as_ref()
+		.and_then(|config| EntityResolver::new(config, Some(embedding_model)))
+		.map(|resolver| Arc::new(resolver.with_metadata_store(metadata_store.clone())));
+	// opt-in: pipelines without a deduplication config extract every document
+	let deduplicator = request.deduplication.as_ref().and_then(Deduplicator::new).map(|dedup| {
+		Arc::new(dedup.with_metadata_store(pipeline_id.clone(), metadata_store.clone()))
+	});
 
-	let pipeline_settings =
-		PipelineSettings { engine, event_storages, index_storages, secret_store, data_sources };
+	let pipeline_settings = PipelineSettings {
+		engine,
+		event_storages,
+		index_storages,
+		secret_store,
+		data_sources,
+		entity_resolver,
+		metadata_store: metadata_store.clone(),
+		ingestor_options: IngestorOptions {
+			pdf_passwords,
+			recognizer,
+			processors,
+			redaction_audit,
+		},
+		deduplicator,
+		key_range,
+	};
 
 	let pipeline_rest = semantic_service_mailbox
-		.ask(SpawnPipeline { settings: pipeline_settings, pipeline_id: new_uuid.clone() })
+		.ask(SpawnPipeline { settings: pipeline_settings, pipeline_id })
 		.await;
 	let pipeline_id = pipeline_rest.unwrap_or(Ok("".to_string()));
 	if pipeline_id.is_err() {
@@ -370,12 +557,64 @@ pub async fn start_pipeline(
 			result_pipe_obs.unwrap_err()
 		)));
 	}
-	metadata_store
-		.set_pipeline(&id.clone(), request)
-		.await
-		.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
-	//call secret store and get the credentials
-	Ok(SemanticPipelineResponse { pipeline_id: id })
+	Ok(())
+}
+
+/// Creates the generative model prompted by the LLM extraction engine. API keys are read from
+/// the environment by the OpenAI and Claude clients.
+fn create_extraction_llm(config: &ExtractionConfig) -> Result<Arc<dyn LLM>, PipelineErrors> {
+	// extraction should not vary between runs over the same documents
+	let options = CallOptions::new().with_temperature(0.0);
+	let llm: Arc<dyn LLM> = match LlmProvider::from_i32(config.provider)
+		.unwrap_or(LlmProvider::Openai)
+	{
+		LlmProvider::Openai => {
+			let mut openai_config = OpenAIConfig::default();
+			if let Some(base_url) = &config.base_url {
+				openai_config = openai_config.with_api_base(base_url);
+			}
+			let mut llm = OpenAI::new(openai_config).with_options(options);
+			if let Some(model) = &config.model {
+				llm = llm.with_model(model);
+			}
+			Arc::new(llm)
+		},
+		LlmProvider::Claude => {
+			let mut llm = Claude::new().with_options(options);
+			if let Some(model) = &config.model {
+				llm = llm.with_model(model);
+			}
+			Arc::new(llm)
+		},
+		LlmProvider::Ollama => {
+			let client = match &config.base_url {
+				Some(base_url) => OllamaClient::try_new(base_url.as_str()).map_err(|e| {
+					PipelineErrors::InvalidParams(anyhow::anyhow!("Invalid Ollama URL: {:?}", e))
+				})?,
+				None => OllamaClient::default(),
+			};
+			let model = config.model.clone().unwrap_or_else(|| "llama3".to_string());
+			Arc::new(Ollama::new(Arc::new(client), model, None))
+		},
+	};
+	Ok(llm)
+}
+
+/// Returns the sentence embedding model shared by semantic pipelines and knowledge imports.
+///
+/// The model is loaded on first use and kept for the lifetime of the node.
+fn shared_embedding_model() -> Result<Arc<TextEmbedding>, PipelineErrors> {
+	static EMBEDDING_MODEL: OnceCell<Arc<TextEmbedding>> = OnceCell::new();
+	EMBEDDING_MODEL
+		.get_or_try_init(|| {
+			let model_details: InitOptions = InitOptions::new(EmbeddingModel::AllMiniLML6V2)
+				.with_cache_dir(get_querent_data_path())
+				.with_show_download_progress(true);
+			TextEmbedding::try_new(model_details)
+				.map(Arc::new)
+				.map_err(|e| PipelineErrors::UnknownError(e.to_string()))
+		})
+		.cloned()
 }
 
 pub fn start_pipeline_post_handler(
@@ -599,6 +838,133 @@ pub fn restart_pipeline_post_handler(
 		.boxed()
 }
 
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DocumentStatesParam {
+	/// Only return documents in this state, e.g. `parse_failed`.
+	pub status: Option<DocumentStatus>,
+}
+
+#[utoipa::path(
+	get,
+	tag = "Semantic Service",
+	path = "/semantics/{pipeline_id}/documents",
+	responses(
+		(status = 200, description = "Outcome of every document processed by the pipeline.", body = DocumentStateList)
+	),
+	params(
+		("pipeline_id" = String, Path, description = "The pipeline id whose documents to list."),
+		DocumentStatesParam,
+	)
+)]
+
+/// List the outcome of every document processed by a pipeline.
+pub async fn get_document_states(
+	pipeline_id: String,
+	params: DocumentStatesParam,
+	metadata_store: Arc<dyn storage::MetaStorage>,
+) -> Result<DocumentStateList, PipelineErrors> {
+	let mut documents = metadata_store
+		.get_document_states(&pipeline_id)
+		.await
+		.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+	if let Some(status) = params.status {
+		documents.retain(|document| document.status() == status);
+	}
+	Ok(DocumentStateList { documents })
+}
+
+pub fn get_document_states_handler(
+	metadata_store: Arc<dyn storage::MetaStorage>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+	warp::path!("semantics" / String / "documents")
+		.and(warp::get())
+		.and(warp::query::<DocumentStatesParam>())
+		.and(require(Some(metadata_store)))
+		.then(get_document_states)
+		.and(extract_format_from_qs())
+		.map(make_json_api_response)
+		.boxed()
+}
+
+#[utoipa::path(
+	post,
+	tag = "Semantic Service",
+	path = "/semantics/{pipeline_id}/documents/retry",
+	request_body = RetryDocumentsRequest,
+	responses(
+		(status = 200, description = "Documents queued for another attempt and the ones that cannot be retried.", body = RetryDocumentsResponse)
+	),
+	params(
+		("pipeline_id" = String, Path, description = "The running pipeline to retry documents in.")
+	)
+)]
+
+/// Retry failed documents of a running pipeline.
+pub async fn retry_documents(
+	pipeline_id: String,
+	request: RetryDocumentsRequest,
+	semantic_service_mailbox: MessageBus<SemanticService>,
+) -> Result<RetryDocumentsResponse, PipelineErrors> {
+	semantic_service_mailbox
+		.ask(RetryDocuments { pipeline_id, documents: request.documents })
+		.await
+		.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?
+}
+
+pub fn retry_documents_post_handler(
+	semantic_service_bus: Option<MessageBus<SemanticService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+	warp::path!("semantics" / String / "documents" / "retry")
+		.and(warp::post())
+		.and(warp::body::json())
+		.and(require(semantic_service_bus))
+		.then(retry_documents)
+		.and(extract_format_from_qs())
+		.map(make_json_api_response)
+		.boxed()
+}
+
+#[utoipa::path(
+	post,
+	tag = "Semantic Service",
+	path = "/semantics/{collection_id}/import",
+	request_body = KnowledgeImportRequest,
+	responses(
+		(status = 200, description = "Successfully imported knowledge into the collection.", body = KnowledgeImportResponse)
+	),
+	params(
+		("collection_id" = String, Path, description = "The collection (pipeline id) to import knowledge into.")
+	)
+)]
+
+/// Import curated knowledge from RDF (Turtle / N-Triples) or CSV into a collection.
+pub async fn import_knowledge_into_collection(
+	collection_id: String,
+	request: KnowledgeImportRequest,
+	event_storages: HashMap<EventType, Vec<Arc<dyn storage::Storage>>>,
+	index_storages: Vec<Arc<dyn storage::Storage>>,
+) -> Result<KnowledgeImportResponse, PipelineErrors> {
+	let embedding_model = shared_embedding_model()?;
+	import_knowledge(collection_id, request, &embedding_model, &event_storages, &index_storages)
+		.await
+}
+
+pub fn import_knowledge_post_handler(
+	event_storages: HashMap<EventType, Vec<Arc<dyn storage::Storage>>>,
+	index_storages: Vec<Arc<dyn storage::Storage>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+	warp::path!("semantics" / String / "import")
+		.and(warp::post())
+		.and(warp::body::json())
+		.and(require(Some(event_storages)))
+		.and(require(Some(index_storages)))
+		.then(import_knowledge_into_collection)
+		.and(extract_format_from_qs())
+		.map(make_json_api_response)
+		.boxed()
+}
+
 pub fn set_collectors_post_handler(
 	secret_store: Arc<dyn storage::SecretStorage>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
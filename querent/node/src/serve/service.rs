@@ -24,7 +24,7 @@ use crate::{
 	insight_api::insights_service::InsightService,
 	insights_service::start_insight_service,
 	layer_api::layer_service::{start_layer_service, LayerService},
-	rest,
+	rest, start_placement_scheduler, start_session_replicator,
 };
 use actors::{ActorExitStatus, MessageBus, Querent};
 use cluster::{start_cluster_service, Cluster};
@@ -164,6 +164,8 @@ pub async fn serve_quester(
 		secret_store,
 		metadata_store,
 	});
+	start_placement_scheduler(&services);
+	start_session_replicator(&services);
 	info!("Starting REST server 📡: check /api-doc.json for available APIs");
 	info!("Rest server listening on {}", rest_listen_addr);
 	let rest_server = rest::start_rest_server(
@@ -316,6 +318,8 @@ pub async fn serve_quester_without_servers(
 		secret_store,
 		metadata_store,
 	});
+	start_placement_scheduler(&services);
+	start_session_replicator(&services);
 	// set the QuerentServices in the global static variable
 	let set_res = QUERENT_SERVICES_ONCE.set(services.clone());
 	if set_res.is_err() {
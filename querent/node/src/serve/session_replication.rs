@@ -0,0 +1,89 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use std::{collections::BTreeMap, sync::Arc};
+
+use async_trait::async_trait;
+use cluster::{SessionCatalog, SessionKind, SessionReplicator};
+use proto::{
+	discovery::DiscoverySessionRequest, layer::LayerSessionRequest, InsightAnalystRequest,
+};
+use serde::Serialize;
+use storage::MetaStorage;
+use tokio::task::JoinHandle;
+
+use crate::QuerentServices;
+
+/// The session requests stored in the metadata store of this node. The replicator copies them
+/// to every node of the cluster, so any node can re-create a session once its owner has left.
+struct StoredSessions {
+	metadata_store: Arc<dyn MetaStorage>,
+}
+
+fn serialize_requests<T: Serialize>(
+	requests: Vec<(String, T)>,
+) -> anyhow::Result<BTreeMap<String, String>> {
+	requests
+		.into_iter()
+		.map(|(session_id, request)| Ok((session_id, serde_json::to_string(&request)?)))
+		.collect()
+}
+
+#[async_trait]
+impl SessionCatalog for StoredSessions {
+	async fn requests(&self, kind: SessionKind) -> anyhow::Result<BTreeMap<String, String>> {
+		match kind {
+			SessionKind::Discovery =>
+				serialize_requests(self.metadata_store.get_all_discovery_sessions().await?),
+			SessionKind::Insight =>
+				serialize_requests(self.metadata_store.get_all_insight_sessions().await?),
+			SessionKind::Layer =>
+				serialize_requests(self.metadata_store.get_all_layer_sessions().await?),
+		}
+	}
+
+	async fn store_request(
+		&self,
+		kind: SessionKind,
+		session_id: &str,
+		request: &str,
+	) -> anyhow::Result<()> {
+		let session_id = session_id.to_string();
+		match kind {
+			SessionKind::Discovery => {
+				let request: DiscoverySessionRequest = serde_json::from_str(request)?;
+				self.metadata_store.set_discovery_session(&session_id, request).await?;
+			},
+			SessionKind::Insight => {
+				let request: InsightAnalystRequest = serde_json::from_str(request)?;
+				self.metadata_store.set_insight_session(&session_id, request).await?;
+			},
+			SessionKind::Layer => {
+				let request: LayerSessionRequest = serde_json::from_str(request)?;
+				self.metadata_store.set_layer_session(&session_id, request).await?;
+			},
+		}
+		Ok(())
+	}
+}
+
+/// Starts replicating the session requests of this node through the cluster state.
+pub fn start_session_replicator(services: &QuerentServices) -> JoinHandle<()> {
+	let stored_sessions =
+		Arc::new(StoredSessions { metadata_store: services.metadata_store.clone() });
+	SessionReplicator::new(services.cluster.clone(), stored_sessions).spawn()
+}
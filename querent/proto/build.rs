@@ -34,6 +34,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 	prost_config
 		.type_attribute(".", "#[derive(Serialize, Deserialize, utoipa::ToSchema, specta::Type)]");
 	prost_config.extern_path(".querent.semantics.StorageType", "StorageType");
+	// Collector configurations saved before the field existed lack it.
+	prost_config.field_attribute("CollectorConfig.pdf_passwords", "#[serde(default)]");
+	// Pipeline requests saved before the field existed lack it.
+	prost_config.field_attribute("SemanticPipelineRequest.processors", "#[serde(default)]");
+	prost_config.field_attribute("ProcessorConfig.params", "#[serde(default)]");
+	// Document states persisted before the field existed lack it.
+	prost_config.field_attribute("DocumentState.redactions", "#[serde(default)]");
+	prost_config.field_attribute("DocumentState.duplicated_from", "#[serde(default)]");
 	ProtoGenerator::builder()
 		.with_prost_config(prost_config)
 		.with_protos(&["protos/querent/semantics.proto"])
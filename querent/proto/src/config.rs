@@ -99,10 +99,24 @@ impl Default for JaegerConfig {
 #[derive(Debug, Clone, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct StorageConfigs(pub Vec<StorageConfig>);
 
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpConfig {
+	/// Exports the spans of the node to an OpenTelemetry collector over OTLP/gRPC.
+	#[serde(default)]
+	pub enabled: bool,
+	/// Address of the collector. Defaults to `OTEL_EXPORTER_OTLP_ENDPOINT`, or
+	/// `http://localhost:4317` when that is unset.
+	#[serde(default)]
+	pub endpoint: Option<String>,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct Tracing {
 	pub jaeger: JaegerConfig,
+	#[serde(default)]
+	pub otlp: OtlpConfig,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -142,7 +156,7 @@ impl Default for NodeConfig {
 			cpu_capacity: 5,
 			memory_capacity: 1000,
 			storage_configs: StorageConfigs(Vec::new()),
-			tracing: Tracing { jaeger: JaegerConfig::default() },
+			tracing: Tracing { jaeger: JaegerConfig::default(), otlp: OtlpConfig::default() },
 		}
 	}
 }
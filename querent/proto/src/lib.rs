@@ -37,6 +37,24 @@ pub mod insights;
 pub use insights::*;
 pub mod layer;
 
+/// Metadata key marking a request forwarded by another node to the node running its session.
+/// The node receiving such a request serves it itself and never forwards it again.
+pub const FORWARDED_HEADER: &str = "x-querent-forwarded";
+
+/// Wraps a request sent to the node running its session, marking it as forwarded.
+pub fn forwarded_request<T>(message: T) -> tonic::Request<T> {
+	let mut request = tonic::Request::new(message);
+	request
+		.metadata_mut()
+		.insert(FORWARDED_HEADER, tonic::metadata::MetadataValue::from_static("1"));
+	request
+}
+
+/// Returns whether the request was forwarded by another node.
+pub fn is_forwarded<T>(request: &tonic::Request<T>) -> bool {
+	request.metadata().contains_key(FORWARDED_HEADER)
+}
+
 #[derive(Clone, Debug)]
 pub struct SpanContextInterceptor;
 pub struct MutMetadataMap<'a>(&'a mut tonic::metadata::MetadataMap);
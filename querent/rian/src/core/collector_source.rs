@@ -18,16 +18,19 @@
 
 use actors::{ActorExitStatus, MessageBus};
 use async_trait::async_trait;
+use cluster::KeyRange;
 use common::{CollectedBytes, CollectionBatch, CollectionCounter, TerimateSignal};
 use futures::StreamExt;
-use sources::{zip::zip::ZipSource, DataSource, SourceError, SourceErrorKind};
+use proto::semantics::DocumentStatus;
+use sources::{zip::zip::ZipSource, DataSource, DocumentFilter, SourceError, SourceErrorKind};
 use std::{sync::Arc, time::Duration};
 use tokio::{io::AsyncReadExt, sync::mpsc, task::JoinHandle, time};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, info_span, Span};
 
 use crate::{
-	ingest::ingestor_service::IngestorService, EventLock, EventStreamer, Source, SourceContext,
-	BATCH_NUM_EVENTS_LIMIT, EMIT_BATCHES_TIMEOUT, NUMBER_FILES_IN_MEMORY,
+	ingest::ingestor_service::IngestorService, metrics::PIPELINE_METRICS, DocumentTracker,
+	EventLock, EventStreamer, Source, SourceContext, BATCH_NUM_EVENTS_LIMIT, EMIT_BATCHES_TIMEOUT,
+	NUMBER_FILES_IN_MEMORY,
 };
 
 pub struct Collector {
@@ -41,6 +44,9 @@ pub struct Collector {
 	leftover_collection_batches: Vec<CollectionBatch>,
 	semaphore: Arc<tokio::sync::Semaphore>,
 	source_counter_semaphore: Arc<tokio::sync::Semaphore>,
+	document_tracker: DocumentTracker,
+	// Keys of the documents collected when the pipeline is a shard, all of them otherwise.
+	key_range: Option<KeyRange>,
 }
 
 impl Collector {
@@ -48,6 +54,8 @@ impl Collector {
 		id: String,
 		data_pollers: Vec<Arc<dyn sources::DataSource>>,
 		terminate_sig: TerimateSignal,
+		document_tracker: DocumentTracker,
+		key_range: Option<KeyRange>,
 	) -> Self {
 		let total_pollers = data_pollers.len();
 		Self {
@@ -61,6 +69,8 @@ impl Collector {
 			leftover_collection_batches: Vec::new(),
 			source_counter_semaphore: Arc::new(tokio::sync::Semaphore::new(total_pollers)),
 			semaphore: Arc::new(tokio::sync::Semaphore::new(NUMBER_FILES_IN_MEMORY)),
+			document_tracker,
+			key_range,
 		}
 	}
 }
@@ -87,19 +97,66 @@ impl Source for Collector {
 			let data_poller = data_poller.clone();
 			let event_sender = event_sender.clone();
 			let terminate_sig = self.terminate_sig.clone();
+			let document_tracker = self.document_tracker.clone();
+			let key_range = self.key_range;
 			let handle = tokio::spawn(async move {
 				let _permit = permit.unwrap();
-				let result = data_poller.poll_data().await;
+				// a shard lists the documents of its key range only, without fetching the others
+				let result = match key_range {
+					Some(key_range) => {
+						let filter: DocumentFilter =
+							Arc::new(move |key: &str| key_range.contains(key));
+						data_poller.poll_filtered_data(filter).await
+					},
+					None => data_poller.poll_data().await,
+				};
 				match result {
 					Ok(mut stream) => {
 						let mut buffer_data: Vec<CollectedBytes> = Vec::new();
 						let mut file_data = Vec::new();
-						while let Some(Ok(mut data)) = stream.next().await {
+						// Root span of each document's trace, open until its last chunk arrives.
+						let mut document_span: Option<Span> = None;
+						while let Some(data) = stream.next().await {
 							if terminate_sig.is_dead() {
 								break;
 							}
+							// A failure on a single document does not stop the source, a failure
+							// of the source itself does.
+							let mut data = match data {
+								Ok(data) => data,
+								Err(e) => match &e.document {
+									Some(document) => {
+										error!("Failed to fetch {}: {:?}", document, e);
+										document_tracker
+											.record(
+												document,
+												"",
+												DocumentStatus::FetchFailed,
+												Some(e.to_string()),
+											)
+											.await;
+										continue;
+									},
+									None => {
+										error!("Failed to poll data: {:?}", e);
+										break;
+									},
+								},
+							};
+							let file =
+								data.file.clone().unwrap_or_default().to_string_lossy().to_string();
 							let extension = data.extension.clone().unwrap_or_default();
 							let source_id = data.source_id.clone();
+							PIPELINE_METRICS
+								.collector_bytes_total
+								.with_label_values([&source_id])
+								.inc_by(data.size.unwrap_or(0) as u64);
+							if data.eof {
+								PIPELINE_METRICS
+									.collector_documents_total
+									.with_label_values([&source_id])
+									.inc();
+							}
 
 							let zip_source_extensions =
 								vec!["zip", "zipx", "jar", "war", "ear", "tar", "gz"];
@@ -111,9 +168,10 @@ impl Source for Collector {
 								}
 							}
 
-							let file =
-								data.file.clone().unwrap_or_default().to_string_lossy().to_string();
 							let eof = data.eof;
+							let span = document_span.get_or_insert_with(
+								|| info_span!("collect_document", collector = %source_id, file = %file),
+							);
 							buffer_data.push(data);
 
 							if is_zipped {
@@ -142,12 +200,19 @@ impl Source for Collector {
 														Vec::new();
 													zip_buffer_data.push(zip_data);
 
-													let zip_batch = CollectionBatch::new(
-														&zip_file,
-														&zip_extension,
-														zip_buffer_data,
-														None,
-													);
+													let zip_batch = info_span!(
+														parent: &*span,
+														"collect_document",
+														file = %zip_file
+													)
+													.in_scope(|| {
+														CollectionBatch::new(
+															&zip_file,
+															&zip_extension,
+															zip_buffer_data,
+															None,
+														)
+													});
 													if let Err(e) =
 														event_sender.send(zip_batch).await
 													{
@@ -168,11 +233,13 @@ impl Source for Collector {
 							}
 
 							if eof {
+								let span = document_span.take().unwrap_or_else(Span::none);
 								if is_zipped {
 									continue;
 								}
-								let batch =
-									CollectionBatch::new(&file, &extension, buffer_data, None);
+								let batch = span.in_scope(|| {
+									CollectionBatch::new(&file, &extension, buffer_data, None)
+								});
 								if let Err(e) = event_sender.send(batch).await {
 									error!("Failed to send data to event sender: {:?}", e);
 								}
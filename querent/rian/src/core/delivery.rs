@@ -0,0 +1,311 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+use proto::semantics::IngestedTokens;
+use sources::DataSource;
+use std::{
+	collections::{HashMap, VecDeque},
+	fmt,
+	sync::{
+		atomic::{AtomicBool, Ordering},
+		Arc, Mutex,
+	},
+};
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Tells the sources that acknowledge documents when the knowledge extracted from the
+/// documents they collected is stored, so that they move their position only then.
+///
+/// A document is pending while the engine has tokens of it left to process, or storages have
+/// events of it left to write. The engines process tokens one at a time and emit the events of
+/// a token before taking the next one, so a document whose tokens are all processed has all
+/// of its events counted. Once the ingestor is done with it and nothing is pending, it is
+/// acknowledged to its source, unless a storage failed to write some of its events: the
+/// source then collects it again when the pipeline restarts.
+#[derive(Clone, Default)]
+pub struct DeliveryTracker {
+	// `None` when no source of the pipeline acknowledges documents.
+	inner: Option<Arc<DeliveryState>>,
+}
+
+struct DeliveryState {
+	sources: HashMap<String, Arc<dyn DataSource>>,
+	documents: Mutex<HashMap<String, PendingDocument>>,
+	// Documents of the tokens handed to the engine and not known to be processed yet.
+	in_engine: Mutex<VecDeque<String>>,
+}
+
+#[derive(Default)]
+struct PendingDocument {
+	source_id: String,
+	pending: usize,
+	read: bool,
+	failed: bool,
+}
+
+impl DeliveryTracker {
+	pub fn new(data_sources: &[Arc<dyn DataSource>]) -> Self {
+		let sources: HashMap<String, Arc<dyn DataSource>> = data_sources
+			.iter()
+			.filter(|source| source.acknowledges_documents())
+			.map(|source| (source.source_id().to_string(), source.clone()))
+			.collect();
+		if sources.is_empty() {
+			return Self::default();
+		}
+		Self {
+			inner: Some(Arc::new(DeliveryState {
+				sources,
+				documents: Mutex::default(),
+				in_engine: Mutex::default(),
+			})),
+		}
+	}
+
+	/// Hands the tokens to the engine through a channel of a single token: sending one returns
+	/// once the engine took the token before it, and is therefore done with the one before that.
+	pub fn track_engine(
+		&self,
+		mut token_receiver: mpsc::Receiver<IngestedTokens>,
+	) -> mpsc::Receiver<IngestedTokens> {
+		let Some(state) = self.inner.clone() else {
+			return token_receiver;
+		};
+		let (engine_sender, engine_receiver) = mpsc::channel(1);
+		tokio::spawn(async move {
+			while let Some(tokens) = token_receiver.recv().await {
+				let document = tokens.file.clone();
+				if engine_sender.send(tokens).await.is_err() {
+					break;
+				}
+				let processed: Vec<String> = {
+					let mut in_engine = state.in_engine.lock().unwrap();
+					in_engine.push_back(document);
+					let count = in_engine.len().saturating_sub(2);
+					in_engine.drain(..count).collect()
+				};
+				for document in processed {
+					state.release(&document, true);
+				}
+			}
+		});
+		engine_receiver
+	}
+
+	/// The engine processed all the tokens it was handed.
+	pub fn engine_finished(&self) {
+		let Some(state) = &self.inner else {
+			return;
+		};
+		let processed: Vec<String> = state.in_engine.lock().unwrap().drain(..).collect();
+		for document in processed {
+			state.release(&document, true);
+		}
+	}
+
+	/// Tokens of `document` are about to be handed to the engine.
+	pub fn sent(&self, document: &str, source_id: &str) {
+		let Some(state) = &self.inner else {
+			return;
+		};
+		if !state.sources.contains_key(source_id) {
+			return;
+		}
+		let mut documents = state.documents.lock().unwrap();
+		let tracked = documents.entry(document.to_string()).or_default();
+		tracked.source_id = source_id.to_string();
+		tracked.pending += 1;
+	}
+
+	/// The engine emitted an event of `document`, to be written by the storages.
+	pub fn extracted(&self, document: &str) {
+		let Some(state) = &self.inner else {
+			return;
+		};
+		if let Some(tracked) = state.documents.lock().unwrap().get_mut(document) {
+			tracked.pending += 1;
+		}
+	}
+
+	/// The ingestor is done with `document`: no more of its tokens are sent.
+	pub fn read(&self, document: &str, source_id: &str) {
+		let Some(state) = &self.inner else {
+			return;
+		};
+		if !state.sources.contains_key(source_id) {
+			return;
+		}
+		let settled = {
+			let mut documents = state.documents.lock().unwrap();
+			let tracked = documents.entry(document.to_string()).or_default();
+			tracked.source_id = source_id.to_string();
+			tracked.read = true;
+			if tracked.pending > 0 {
+				return;
+			}
+			documents.remove(document)
+		};
+		if let Some(tracked) = settled {
+			state.settle(document.to_string(), tracked);
+		}
+	}
+
+	/// Follows the writes of a batch of events, released once every storage is done with it.
+	pub fn delivery<'a>(&self, documents: impl IntoIterator<Item = &'a str>) -> Delivery {
+		let Some(state) = self.inner.clone() else {
+			return Delivery::default();
+		};
+		Delivery {
+			batch: Some(Arc::new(DeliveryBatch {
+				state,
+				documents: documents.into_iter().map(str::to_string).collect(),
+				failed: AtomicBool::new(false),
+			})),
+		}
+	}
+}
+
+impl DeliveryState {
+	fn release(&self, document: &str, stored: bool) {
+		let settled = {
+			let mut documents = self.documents.lock().unwrap();
+			let Some(tracked) = documents.get_mut(document) else {
+				return;
+			};
+			tracked.pending = tracked.pending.saturating_sub(1);
+			tracked.failed |= !stored;
+			if !tracked.read || tracked.pending > 0 {
+				return;
+			}
+			documents.remove(document)
+		};
+		if let Some(tracked) = settled {
+			self.settle(document.to_string(), tracked);
+		}
+	}
+
+	fn settle(&self, document: String, tracked: PendingDocument) {
+		if tracked.failed {
+			warn!("Not acknowledging {}, which storages failed to write", document);
+			return;
+		}
+		let Some(source) = self.sources.get(&tracked.source_id).cloned() else {
+			return;
+		};
+		tokio::spawn(async move { source.acknowledge(&[document]).await });
+	}
+}
+
+/// Events of a batch on their way to the storages. Each storage write holds a clone, and the
+/// events count as stored once the last clone is dropped.
+#[derive(Clone, Default)]
+pub struct Delivery {
+	batch: Option<Arc<DeliveryBatch>>,
+}
+
+impl Delivery {
+	/// Some of the events could not be written.
+	pub fn fail(&self) {
+		if let Some(batch) = &self.batch {
+			batch.failed.store(true, Ordering::Relaxed);
+		}
+	}
+}
+
+impl fmt::Debug for Delivery {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let documents = self.batch.as_ref().map_or(0, |batch| batch.documents.len());
+		f.debug_struct("Delivery").field("events", &documents).finish()
+	}
+}
+
+struct DeliveryBatch {
+	state: Arc<DeliveryState>,
+	// one entry per event
+	documents: Vec<String>,
+	failed: AtomicBool,
+}
+
+impl Drop for DeliveryBatch {
+	fn drop(&mut self) {
+		let stored = !self.failed.load(Ordering::Relaxed);
+		for document in &self.documents {
+			self.state.release(document, stored);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::StreamExt;
+	use proto::semantics::KafkaCollectorConfig;
+	use sources::kafka::{broker::MemoryBroker, kafka::KafkaSource};
+	use std::time::Duration;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn test_documents_are_acknowledged_once_stored() {
+		let broker = Arc::new(MemoryBroker::new());
+		broker.publish("alerts", 0, None, "Pump 4 pressure high").await;
+		broker.publish("alerts", 0, None, "Gas detected").await;
+		let config = KafkaCollectorConfig {
+			topics: vec!["alerts".to_string()],
+			group_id: "querent".to_string(),
+			idle_timeout_ms: Some(100),
+			id: "kafka".to_string(),
+			..Default::default()
+		};
+		let source = Arc::new(KafkaSource::with_broker(config, broker.clone()).unwrap());
+		let documents: Vec<String> = source
+			.poll_data()
+			.await
+			.unwrap()
+			.map(|collected| collected.unwrap().file.unwrap().to_string_lossy().to_string())
+			.collect()
+			.await;
+		let delivery = DeliveryTracker::new(&[source.clone() as Arc<dyn DataSource>]);
+
+		let (token_sender, token_receiver) = mpsc::channel(10);
+		let mut engine = delivery.track_engine(token_receiver);
+		for document in &documents {
+			let tokens = IngestedTokens {
+				file: document.clone(),
+				source_id: "kafka".to_string(),
+				..Default::default()
+			};
+			delivery.sent(document, "kafka");
+			token_sender.send(tokens).await.unwrap();
+			delivery.read(document, "kafka");
+		}
+		drop(token_sender);
+		while let Some(tokens) = engine.recv().await {
+			delivery.extracted(&tokens.file);
+		}
+		delivery.engine_finished();
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		assert_eq!(broker.committed("alerts", 0).await, None);
+
+		drop(delivery.delivery([documents[0].as_str()]));
+		let failed = delivery.delivery([documents[1].as_str()]);
+		failed.fail();
+		drop(failed);
+		tokio::time::sleep(Duration::from_millis(50)).await;
+		// the second message is read again once the pipeline restarts
+		assert_eq!(broker.committed("alerts", 0).await, Some(1));
+	}
+}
@@ -0,0 +1,325 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use proto::semantics::{DocumentState, DocumentStatus};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex},
+};
+use storage::MetaStorage;
+use tracing::error;
+
+/// Records the outcome of every document a pipeline processes. The latest state of each
+/// document is kept in memory and persisted to the metadata store, when there is one, from
+/// which [`DocumentTracker::load`] restores it when the pipeline starts again.
+///
+/// The in-memory map holds one entry per document the pipeline has seen, so it grows with the
+/// size of the corpus the pipeline collects.
+#[derive(Clone)]
+pub struct DocumentTracker {
+	pipeline_id: String,
+	metadata_store: Option<Arc<dyn MetaStorage>>,
+	documents: Arc<Mutex<HashMap<String, TrackedDocument>>>,
+	// Detected file types waiting to be reported with the next state of their document.
+	file_types: Arc<Mutex<HashMap<String, String>>>,
+	// What reading documents found, waiting to be reported the same way.
+	read_details: Arc<Mutex<HashMap<String, ReadDetails>>>,
+}
+
+/// Found while reading a document, only known once it is read.
+#[derive(Default)]
+struct ReadDetails {
+	redactions: HashMap<String, u32>,
+	duplicate_of: Option<String>,
+	duplicated_from: HashMap<String, u32>,
+}
+
+impl ReadDetails {
+	fn apply(self, state: &mut DocumentState) {
+		state.redactions = self.redactions;
+		state.duplicate_of = self.duplicate_of;
+		state.duplicated_from = self.duplicated_from;
+	}
+}
+
+struct TrackedDocument {
+	state: DocumentState,
+	// A retry is underway and has not reported back yet.
+	retrying: bool,
+}
+
+impl DocumentTracker {
+	pub fn new(pipeline_id: String, metadata_store: Option<Arc<dyn MetaStorage>>) -> Self {
+		Self {
+			pipeline_id,
+			metadata_store,
+			documents: Arc::default(),
+			file_types: Arc::default(),
+			read_details: Arc::default(),
+		}
+	}
+
+	/// Restores the document states persisted by earlier runs of the pipeline, so documents that
+	/// failed before a restart can still be listed and retried. States recorded since the
+	/// pipeline started take precedence.
+	pub async fn load(&self) {
+		let Some(metadata_store) = &self.metadata_store else {
+			return;
+		};
+		let states = match metadata_store.get_document_states(&self.pipeline_id).await {
+			Ok(states) => states,
+			Err(e) => {
+				error!("Failed to load document states of pipeline {}: {:?}", self.pipeline_id, e);
+				return;
+			},
+		};
+		let mut documents = self.documents.lock().unwrap();
+		for state in states {
+			documents
+				.entry(state.document.clone())
+				.or_insert(TrackedDocument { state, retrying: false });
+		}
+	}
+
+	/// Remembers the type `document` was detected as, reported along with its next state.
+	pub fn set_file_type(&self, document: &str, file_type: &str) {
+		self.file_types
+			.lock()
+			.unwrap()
+			.insert(document.to_string(), file_type.to_string());
+	}
+
+	/// Remembers the personal data redacted from `document`, by kind, reported along with its
+	/// next state.
+	pub fn set_redactions(&self, document: &str, redactions: HashMap<String, u32>) {
+		if redactions.is_empty() {
+			return;
+		}
+		self.read_details
+			.lock()
+			.unwrap()
+			.entry(document.to_string())
+			.or_default()
+			.redactions = redactions;
+	}
+
+	/// Remembers the document `document` nearly duplicates, and the documents whose chunks it
+	/// repeats, reported along with its next state.
+	pub fn set_duplicates(
+		&self,
+		document: &str,
+		duplicate_of: Option<String>,
+		duplicated_from: HashMap<String, u32>,
+	) {
+		if duplicate_of.is_none() && duplicated_from.is_empty() {
+			return;
+		}
+		let mut read_details = self.read_details.lock().unwrap();
+		let details = read_details.entry(document.to_string()).or_default();
+		details.duplicate_of = duplicate_of;
+		details.duplicated_from = duplicated_from;
+	}
+
+	/// Records the outcome of the current attempt on `document`.
+	///
+	/// The ingestor reports `NoTriples` once it is done with a document, which can be after the
+	/// engine already extracted triples from it: such a document stays `Succeeded`.
+	pub async fn record(
+		&self,
+		document: &str,
+		source_id: &str,
+		status: DocumentStatus,
+		reason: Option<String>,
+	) {
+		let file_type = self.file_types.lock().unwrap().remove(document);
+		let read_details = self.read_details.lock().unwrap().remove(document);
+		let state = {
+			let mut documents = self.documents.lock().unwrap();
+			let is_new = !documents.contains_key(document);
+			let tracked =
+				documents.entry(document.to_string()).or_insert_with(|| TrackedDocument {
+					state: DocumentState {
+						document: document.to_string(),
+						attempts: 1,
+						..Default::default()
+					},
+					retrying: false,
+				});
+			let keeps_success = !is_new &&
+				!tracked.retrying &&
+				tracked.state.status() == DocumentStatus::Succeeded &&
+				matches!(status, DocumentStatus::Succeeded | DocumentStatus::NoTriples);
+			if keeps_success {
+				// the details of a document are known once it is read, after its triples
+				match read_details {
+					Some(details) => details.apply(&mut tracked.state),
+					None => return,
+				}
+				tracked.state.clone()
+			} else {
+				tracked.retrying = false;
+				let state = &mut tracked.state;
+				if !source_id.is_empty() {
+					state.source_id = source_id.to_string();
+				}
+				state.set_status(status);
+				state.reason = reason;
+				if file_type.is_some() {
+					state.file_type = file_type;
+				}
+				if let Some(details) = read_details {
+					details.apply(state);
+				}
+				state.updated_at = chrono::Utc::now().timestamp_millis() as u64;
+				state.clone()
+			}
+		};
+		if let Some(metadata_store) = &self.metadata_store {
+			if let Err(e) = metadata_store.set_document_state(&self.pipeline_id, state).await {
+				error!("Failed to persist state of document {}: {:?}", document, e);
+			}
+		}
+	}
+
+	/// Starts a new attempt on a document that failed. Returns its state, or `None` when the
+	/// document is unknown, succeeded or is already being retried.
+	pub fn begin_retry(&self, document: &str) -> Option<DocumentState> {
+		let mut documents = self.documents.lock().unwrap();
+		let tracked = documents.get_mut(document)?;
+		if tracked.retrying || tracked.state.status() == DocumentStatus::Succeeded {
+			return None;
+		}
+		tracked.retrying = true;
+		tracked.state.attempts += 1;
+		Some(tracked.state.clone())
+	}
+
+	/// Documents whose last attempt failed.
+	pub fn failed_documents(&self) -> Vec<String> {
+		let documents = self.documents.lock().unwrap();
+		let mut failed = documents
+			.values()
+			.filter(|tracked| tracked.state.status() != DocumentStatus::Succeeded)
+			.map(|tracked| tracked.state.document.clone())
+			.collect::<Vec<_>>();
+		failed.sort_unstable();
+		failed
+	}
+
+	pub fn get(&self, document: &str) -> Option<DocumentState> {
+		self.documents
+			.lock()
+			.unwrap()
+			.get(document)
+			.map(|tracked| tracked.state.clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_late_no_triples_keeps_success() {
+		let tracker = DocumentTracker::new("pipeline".to_string(), None);
+		tracker.record("a.pdf", "", DocumentStatus::Succeeded, None).await;
+		tracker
+			.record("a.pdf", "files", DocumentStatus::NoTriples, Some("nothing".to_string()))
+			.await;
+		let state = tracker.get("a.pdf").unwrap();
+		assert_eq!(state.status(), DocumentStatus::Succeeded);
+		assert_eq!(state.attempts, 1);
+		assert!(tracker.failed_documents().is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_file_type_is_reported_with_the_state() {
+		let tracker = DocumentTracker::new("pipeline".to_string(), None);
+		tracker.set_file_type("report.dat", "pdf");
+		assert!(tracker.get("report.dat").is_none());
+		tracker.record("report.dat", "s3", DocumentStatus::Succeeded, None).await;
+		tracker.record("report.dat", "s3", DocumentStatus::NoTriples, None).await;
+		assert_eq!(tracker.get("report.dat").unwrap().file_type.as_deref(), Some("pdf"));
+	}
+
+	#[tokio::test]
+	async fn test_redactions_are_reported_after_success() {
+		let tracker = DocumentTracker::new("pipeline".to_string(), None);
+		tracker.record("ticket.eml", "email", DocumentStatus::Succeeded, None).await;
+		tracker.set_redactions("ticket.eml", HashMap::from([("email".to_string(), 2)]));
+		tracker.record("ticket.eml", "email", DocumentStatus::NoTriples, None).await;
+		let state = tracker.get("ticket.eml").unwrap();
+		assert_eq!(state.status(), DocumentStatus::Succeeded);
+		assert_eq!(state.redactions.get("email"), Some(&2));
+	}
+
+	#[tokio::test]
+	async fn test_duplicates_are_reported_with_redactions() {
+		let tracker = DocumentTracker::new("pipeline".to_string(), None);
+		tracker.set_redactions("fwd.eml", HashMap::from([("phone".to_string(), 1)]));
+		tracker.set_duplicates(
+			"fwd.eml",
+			Some("thread.eml".to_string()),
+			HashMap::from([("thread.eml".to_string(), 3)]),
+		);
+		tracker.record("fwd.eml", "email", DocumentStatus::NoTriples, None).await;
+		let state = tracker.get("fwd.eml").unwrap();
+		assert_eq!(state.duplicate_of.as_deref(), Some("thread.eml"));
+		assert_eq!(state.duplicated_from.get("thread.eml"), Some(&3));
+		assert_eq!(state.redactions.get("phone"), Some(&1));
+	}
+
+	#[tokio::test]
+	async fn test_states_are_reloaded_from_the_metadata_store() {
+		let dir = tempfile::tempdir().unwrap();
+		let metadata_store =
+			storage::create_metadata_store(dir.path().to_path_buf()).await.unwrap();
+		let tracker = DocumentTracker::new("pipeline".to_string(), Some(metadata_store.clone()));
+		tracker
+			.record("a.csv", "files", DocumentStatus::ParseFailed, Some("bad".to_string()))
+			.await;
+		tracker.record("b.csv", "files", DocumentStatus::Succeeded, None).await;
+
+		let restarted = DocumentTracker::new("pipeline".to_string(), Some(metadata_store));
+		restarted.load().await;
+		assert_eq!(restarted.failed_documents(), vec!["a.csv".to_string()]);
+		assert_eq!(restarted.begin_retry("a.csv").unwrap().source_id, "files");
+	}
+
+	#[tokio::test]
+	async fn test_retry_failed_document() {
+		let tracker = DocumentTracker::new("pipeline".to_string(), None);
+		tracker
+			.record("a.csv", "files", DocumentStatus::ParseFailed, Some("bad".to_string()))
+			.await;
+		tracker.record("b.csv", "files", DocumentStatus::Succeeded, None).await;
+		assert_eq!(tracker.failed_documents(), vec!["a.csv".to_string()]);
+		assert!(tracker.begin_retry("b.csv").is_none());
+
+		let state = tracker.begin_retry("a.csv").unwrap();
+		assert_eq!(state.attempts, 2);
+		assert!(tracker.begin_retry("a.csv").is_none());
+
+		tracker.record("a.csv", "", DocumentStatus::Succeeded, None).await;
+		let state = tracker.get("a.csv").unwrap();
+		assert_eq!(state.status(), DocumentStatus::Succeeded);
+		assert_eq!(state.source_id, "files");
+		assert_eq!(state.reason, None);
+		assert!(tracker.failed_documents().is_empty());
+	}
+}
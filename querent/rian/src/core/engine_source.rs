@@ -18,21 +18,22 @@
 
 use actors::{ActorExitStatus, MessageBus, TrySendError};
 use async_trait::async_trait;
-use common::{EventState, EventType, EventsBatch, EventsCounter, TerimateSignal};
+use common::{DocumentTraces, EventState, EventType, EventsBatch, EventsCounter, TerimateSignal};
 use engines::{Engine, EngineError, EngineErrorKind};
 use futures::StreamExt;
-use proto::semantics::IngestedTokens;
+use proto::semantics::{DocumentStatus, IngestedTokens};
 use std::{collections::HashMap, sync::Arc, time::Duration};
 use tokio::{
 	sync::mpsc,
 	task::JoinHandle,
 	time::{self},
 };
-use tracing::{error, info};
+use tracing::{error, info, info_span, Span};
 
 use crate::{
-	ingest::ingestor_service::IngestorService, EventLock, EventStreamer, NewEventLock, Source,
-	SourceContext, BATCH_NUM_EVENTS_LIMIT, EMIT_BATCHES_TIMEOUT,
+	ingest::ingestor_service::IngestorService, DeliveryTracker, DocumentTracker, EventLock,
+	EventStreamer, NewEventLock, Source, SourceContext, BATCH_NUM_EVENTS_LIMIT,
+	EMIT_BATCHES_TIMEOUT,
 };
 
 pub struct EngineRunner {
@@ -45,6 +46,8 @@ pub struct EngineRunner {
 	// terimatesignal to kill actors in the pipeline.
 	pub terminate_sig: TerimateSignal,
 	left_over_batches: Vec<EventsBatch>,
+	document_traces: DocumentTraces,
+	document_tracker: DocumentTracker,
 }
 
 impl EngineRunner {
@@ -53,8 +56,12 @@ impl EngineRunner {
 		engine: Arc<dyn Engine>,
 		token_receiver: mpsc::Receiver<IngestedTokens>,
 		terminate_sig: TerimateSignal,
+		document_traces: DocumentTraces,
+		document_tracker: DocumentTracker,
+		delivery: DeliveryTracker,
 	) -> Self {
 		let (event_sender, event_receiver) = mpsc::channel(1000);
+		let token_receiver = delivery.track_engine(token_receiver);
 		let event_runner = engine.clone();
 		let term_sig = terminate_sig.clone();
 		info!("Starting the engine 🚀");
@@ -74,6 +81,12 @@ impl EngineRunner {
 				}
 				match data {
 					Ok(event) => {
+						// counted before the engine moves on to the next tokens
+						if matches!(event.event_type, EventType::Graph | EventType::Vector) &&
+							!event.payload.is_empty()
+						{
+							delivery.extracted(&event.file);
+						}
 						if let Err(e) = event_sender.send((event.clone().event_type, event)).await {
 							return Err(EngineError::new(
 								EngineErrorKind::EventStream,
@@ -114,6 +127,10 @@ impl EngineRunner {
 					},
 				}
 			}
+			// the engine stopped early when the pipeline is killed, leaving tokens unprocessed
+			if !term_sig.is_dead() {
+				delivery.engine_finished();
+			}
 
 			let success_event = EventState {
 				event_type: EventType::Success,
@@ -144,12 +161,35 @@ impl EngineRunner {
 			workflow_handle,
 			terminate_sig,
 			left_over_batches: Vec::new(),
+			document_traces,
+			document_tracker,
 		}
 	}
 
 	pub fn get_engine(&self) -> Arc<dyn Engine> {
 		self.engine.clone()
 	}
+
+	/// A batch mixes events of several documents: its span continues the trace of one of them
+	/// and links to the others.
+	fn attach_document_traces(&self, span: &Span, events: &HashMap<EventType, Vec<EventState>>) {
+		let mut files =
+			events.values().flatten().map(|event| event.file.as_str()).collect::<Vec<_>>();
+		files.sort_unstable();
+		files.dedup();
+		let mut has_parent = false;
+		for file in files {
+			let Some(trace_context) = self.document_traces.get(file) else {
+				continue;
+			};
+			if has_parent {
+				trace_context.link_to(span);
+			} else {
+				trace_context.attach_to(span);
+				has_parent = true;
+			}
+		}
+	}
 }
 
 #[async_trait]
@@ -233,11 +273,20 @@ impl Source for EngineRunner {
 		}
 		if !events_collected.is_empty() || !self.left_over_batches.is_empty() {
 			if !events_collected.is_empty() {
-				let events_batch = EventsBatch::new(
-					self.id.clone(),
-					events_collected,
-					chrono::Utc::now().timestamp_millis() as u64,
-				);
+				for event in events_collected.get(&EventType::Graph).into_iter().flatten() {
+					self.document_tracker
+						.record(&event.file, "", DocumentStatus::Succeeded, None)
+						.await;
+				}
+				let batch_span = info_span!("engine_batch", engine = %self.id, events = counter);
+				self.attach_document_traces(&batch_span, &events_collected);
+				let events_batch = batch_span.in_scope(|| {
+					EventsBatch::new(
+						self.id.clone(),
+						events_collected,
+						chrono::Utc::now().timestamp_millis() as u64,
+					)
+				});
 				self.left_over_batches.push(events_batch);
 			}
 			let mut left_batches = Vec::new();
@@ -31,12 +31,16 @@ pub mod engine_source;
 pub use engine_source::*;
 pub mod collector_source;
 pub use collector_source::*;
+pub mod document_tracker;
+pub use document_tracker::*;
+pub mod delivery;
+pub use delivery::*;
 
 pub type SourceContext = ActorContext<SourceActor>;
 
 pub const NUMBER_FILES_IN_MEMORY: usize = 100;
 
-pub const MAX_DATA_SIZE_IN_MEMORY: OnceCell<usize> = OnceCell::new();
+pub static MAX_DATA_SIZE_IN_MEMORY: OnceCell<usize> = OnceCell::new();
 
 pub const BATCH_NUM_EVENTS_LIMIT: usize = 10;
 
@@ -30,8 +30,9 @@ use std::{
 };
 use storage::Storage;
 use tokio::runtime::Handle;
+use tracing::Instrument;
 
-use super::insert_discovered_knowledge_async;
+use super::{insert_discovered_knowledge_async, storage_query_span};
 
 pub struct DiscoverySearch {
 	agent_id: String,
@@ -188,7 +189,11 @@ impl Handler<DiscoveryRequest> for DiscoverySearch {
 			if *event_type == EventType::Vector {
 				for storage in storage.iter() {
 					if self.current_query.is_empty() && self.current_top_pairs.is_empty() {
-						let auto_suggestions = match storage.autogenerate_queries(3).await {
+						let auto_suggestions = match storage
+							.autogenerate_queries(3)
+							.instrument(storage_query_span(storage, "autogenerate_queries"))
+							.await
+						{
 							Ok(suggestions) => suggestions,
 							Err(e) => {
 								tracing::info!("Failed to auto-generate queries: {:?}", e);
@@ -211,7 +216,10 @@ impl Handler<DiscoveryRequest> for DiscoverySearch {
 					let mut total_fetched = 0;
 
 					if top_pairs_incoming.len() < 10 {
-						let auto_top_pairs_suggestions = match storage.autogenerate_queries(1).await
+						let auto_top_pairs_suggestions = match storage
+							.autogenerate_queries(1)
+							.instrument(storage_query_span(storage, "autogenerate_queries"))
+							.await
 						{
 							Ok(suggestions) => suggestions,
 							Err(e) => {
@@ -249,6 +257,7 @@ impl Handler<DiscoveryRequest> for DiscoverySearch {
 									10,
 									self.current_offset + total_fetched,
 								)
+								.instrument(storage_query_span(storage, "filter_and_query"))
 								.await;
 						} else {
 							search_results = storage
@@ -261,6 +270,7 @@ impl Handler<DiscoveryRequest> for DiscoverySearch {
 									self.current_offset + total_fetched,
 									&top_pair_embeddings,
 								)
+								.instrument(storage_query_span(storage, "similarity_search_l2"))
 								.await;
 						}
 						match search_results {
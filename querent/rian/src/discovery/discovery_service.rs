@@ -18,10 +18,13 @@
 
 #[cfg(feature = "license-check")]
 use crate::is_discovery_agent_type_allowed;
-use crate::{discovery_searcher::DiscoverySearch, discovery_traverser::DiscoveryTraverse};
+use crate::{
+	discovery_searcher::DiscoverySearch, discovery_traverser::DiscoveryTraverse,
+	metrics::PIPELINE_METRICS,
+};
 use actors::{Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Healthz, MessageBus};
 use async_trait::async_trait;
-use cluster::Cluster;
+use cluster::{Cluster, SessionKind, SESSION_STATE_RUNNING, SESSION_STATE_STOPPED};
 use common::EventType;
 use proto::discovery::{
 	DiscoveryAgentType, DiscoveryError, DiscoveryRequest, DiscoveryResponse,
@@ -92,15 +95,22 @@ impl Actor for DiscoveryAgentService {
 		()
 	}
 }
-#[async_trait]
-impl Handler<DiscoverySessionRequest> for DiscoveryAgentService {
-	type Reply = Result<DiscoverySessionResponse, DiscoveryError>;
-	async fn handle(
+
+/// Re-creates a discovery session from the request that started it, keeping its id. Sent to take
+/// over the sessions of a node which left the cluster; a session already running is kept.
+#[derive(Debug, Clone)]
+pub struct RestoreDiscoverySession {
+	pub session_id: String,
+	pub request: DiscoverySessionRequest,
+}
+
+impl DiscoveryAgentService {
+	async fn start_session(
 		&mut self,
+		session_id: String,
 		request: DiscoverySessionRequest,
 		ctx: &ActorContext<Self>,
-	) -> Result<Self::Reply, ActorExitStatus> {
-		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+	) -> Result<Result<DiscoverySessionResponse, DiscoveryError>, ActorExitStatus> {
 		let current_timestamp = chrono::Utc::now().timestamp();
 		let event_storages = self.event_storages.clone();
 		#[cfg(feature = "license-check")]
@@ -121,7 +131,7 @@ impl Handler<DiscoverySessionRequest> for DiscoveryAgentService {
 		match request.session_type.clone().unwrap_or(DiscoveryAgentType::Retriever) {
 			DiscoveryAgentType::Retriever => {
 				let search = DiscoverySearch::new(
-					new_uuid.clone(),
+					session_id.clone(),
 					current_timestamp as u64,
 					event_storages.clone(),
 					request.clone(),
@@ -130,11 +140,11 @@ impl Handler<DiscoverySessionRequest> for DiscoveryAgentService {
 				let (search_messagebus, search) = ctx.spawn_actor().spawn(search);
 				let search_handle =
 					DiscoverSearchHandle { mailbox: search_messagebus, handle: search };
-				self.searcher_pipelines.insert(new_uuid.clone(), search_handle);
+				self.searcher_pipelines.insert(session_id.clone(), search_handle);
 			},
 			DiscoveryAgentType::Traverser => {
 				let search = DiscoveryTraverse::new(
-					new_uuid.clone(),
+					session_id.clone(),
 					current_timestamp as u64,
 					event_storages.clone(),
 					request.clone(),
@@ -143,11 +153,44 @@ impl Handler<DiscoverySessionRequest> for DiscoveryAgentService {
 				let (traverse_messagebus, traverse) = ctx.spawn_actor().spawn(search);
 				let traverse_handle =
 					DiscoveryTraverseHandle { mailbox: traverse_messagebus, handle: traverse };
-				self.traverse_pipelines.insert(new_uuid.clone(), traverse_handle);
+				self.traverse_pipelines.insert(session_id.clone(), traverse_handle);
 			},
 		}
+		self.cluster
+			.update_session_state(SessionKind::Discovery, &session_id, SESSION_STATE_RUNNING)
+			.await;
+
+		Ok(Ok(DiscoverySessionResponse { session_id }))
+	}
+}
+
+#[async_trait]
+impl Handler<DiscoverySessionRequest> for DiscoveryAgentService {
+	type Reply = Result<DiscoverySessionResponse, DiscoveryError>;
+	async fn handle(
+		&mut self,
+		request: DiscoverySessionRequest,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+		self.start_session(new_uuid, request, ctx).await
+	}
+}
 
-		Ok(Ok(DiscoverySessionResponse { session_id: new_uuid }))
+#[async_trait]
+impl Handler<RestoreDiscoverySession> for DiscoveryAgentService {
+	type Reply = Result<DiscoverySessionResponse, DiscoveryError>;
+	async fn handle(
+		&mut self,
+		restore: RestoreDiscoverySession,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		if self.traverse_pipelines.contains_key(&restore.session_id) ||
+			self.searcher_pipelines.contains_key(&restore.session_id)
+		{
+			return Ok(Ok(DiscoverySessionResponse { session_id: restore.session_id }));
+		}
+		self.start_session(restore.session_id, restore.request, ctx).await
 	}
 }
 
@@ -176,12 +219,26 @@ impl Handler<StopDiscoverySessionRequest> for DiscoveryAgentService {
 		let agent_handle = self.traverse_pipelines.remove(&request.session_id);
 		if let Some(agent_handle) = agent_handle {
 			let _ = agent_handle.handle.kill().await;
+			self.cluster
+				.update_session_state(
+					SessionKind::Discovery,
+					&request.session_id,
+					SESSION_STATE_STOPPED,
+				)
+				.await;
 			return Ok(Ok(StopDiscoverySessionResponse { session_id: request.session_id }));
 		}
 
 		let search_handle = self.searcher_pipelines.remove(&request.session_id);
 		if let Some(search_handle) = search_handle {
 			let _ = search_handle.handle.kill().await;
+			self.cluster
+				.update_session_state(
+					SessionKind::Discovery,
+					&request.session_id,
+					SESSION_STATE_STOPPED,
+				)
+				.await;
 			return Ok(Ok(StopDiscoverySessionResponse { session_id: request.session_id }));
 		}
 
@@ -201,6 +258,10 @@ impl Handler<DiscoveryRequest> for DiscoveryAgentService {
 		let agent_handle = self.traverse_pipelines.get(&request.session_id);
 
 		if let Some(agent_handle) = agent_handle {
+			let _query_timer = PIPELINE_METRICS
+				.discovery_query_duration_seconds
+				.with_label_values(["traverse"])
+				.start_timer();
 			let response = agent_handle
 				.mailbox
 				.ask(request)
@@ -219,6 +280,10 @@ impl Handler<DiscoveryRequest> for DiscoveryAgentService {
 
 		let search_handle = self.searcher_pipelines.get(&request.session_id);
 		if let Some(search_handle) = search_handle {
+			let _query_timer = PIPELINE_METRICS
+				.discovery_query_duration_seconds
+				.with_label_values(["search"])
+				.start_timer();
 			let response = search_handle
 				.mailbox
 				.ask(request)
@@ -32,8 +32,9 @@ use storage::{
 	QuerySuggestion, Storage,
 };
 use tokio::runtime::Handle;
+use tracing::Instrument;
 
-use super::insert_discovered_knowledge_async;
+use super::{insert_discovered_knowledge_async, storage_query_span};
 
 pub struct DiscoveryTraverse {
 	agent_id: String,
@@ -169,7 +170,11 @@ impl Handler<DiscoveryRequest> for DiscoveryTraverse {
 			if *event_type == EventType::Vector {
 				for storage in storages.iter() {
 					if message.query.is_empty() {
-						let auto_suggestions = match storage.autogenerate_queries(3).await {
+						let auto_suggestions = match storage
+							.autogenerate_queries(3)
+							.instrument(storage_query_span(storage, "autogenerate_queries"))
+							.await
+						{
 							Ok(suggestions) => suggestions,
 							Err(e) => {
 								tracing::info!("Failed to auto-generate queries: {:?}", e);
@@ -198,14 +203,17 @@ impl Handler<DiscoveryRequest> for DiscoveryTraverse {
 							self.current_offset,
 							&vec![],
 						)
+						.instrument(storage_query_span(storage, "similarity_search_l2"))
 						.await;
 					match search_results {
 						Ok(results) => {
 							self.current_offset += results.len() as i64;
 
 							let filtered_results = get_top_k_pairs(results, 3);
-							let traverser_results_1 =
-								storage.traverse_metadata_table(&filtered_results).await;
+							let traverser_results_1 = storage
+								.traverse_metadata_table(&filtered_results)
+								.instrument(storage_query_span(storage, "traverse_metadata_table"))
+								.await;
 
 							if self.previous_query_results.is_empty() ||
 								message.session_id != self.previous_session_id
@@ -292,10 +300,22 @@ impl Handler<DiscoveryRequest> for DiscoveryTraverse {
 
 								let final_traverser_results = if results_intersection.is_empty() {
 									self.previous_filtered_results = formatted_output_1.clone();
-									storage.traverse_metadata_table(&formatted_output_1).await
+									storage
+										.traverse_metadata_table(&formatted_output_1)
+										.instrument(storage_query_span(
+											storage,
+											"traverse_metadata_table",
+										))
+										.await
 								} else {
 									self.previous_filtered_results = results_intersection.clone();
-									storage.traverse_metadata_table(&results_intersection).await
+									storage
+										.traverse_metadata_table(&results_intersection)
+										.instrument(storage_query_span(
+											storage,
+											"traverse_metadata_table",
+										))
+										.await
 								};
 								match &final_traverser_results {
 									Ok(results) => {
@@ -20,11 +20,17 @@ use actors::ActorExitStatus;
 use common::DocumentPayload;
 use std::sync::Arc;
 use storage::Storage;
+use tracing::{info_span, Span};
 
 pub mod discovery_searcher;
 pub mod discovery_service;
 pub mod discovery_traverser;
 
+/// Span around a storage call made while answering a discovery request.
+fn storage_query_span(storage: &Arc<dyn Storage>, query: &'static str) -> Span {
+	info_span!("storage_query", backend = storage.backend_name(), query)
+}
+
 async fn insert_discovered_knowledge_async(
 	storage: Arc<dyn Storage>,
 	storage_items: Vec<DocumentPayload>,
@@ -21,13 +21,13 @@ use async_trait::async_trait;
 use common::{CollectionBatch, EventStreamerCounters, EventType, EventsBatch, RuntimeType};
 use std::sync::Arc;
 use tokio::runtime::Handle;
-use tracing::error;
+use tracing::{error, info_span, Instrument};
 
 use crate::{
 	indexer::Indexer,
 	ingest::ingestor_service::IngestorService,
 	storage::{ContextualEmbeddings, ContextualTriples, StorageMapper},
-	EventLock, IndexerKnowledge, NewEventLock,
+	DeliveryTracker, EntityResolver, EventLock, IndexerKnowledge, NewEventLock,
 };
 
 pub struct EventStreamer {
@@ -35,6 +35,8 @@ pub struct EventStreamer {
 	storage_mapper_messagebus: MessageBus<StorageMapper>,
 	indexer_messagebus: MessageBus<Indexer>,
 	ingestor_messagebus: MessageBus<IngestorService>,
+	entity_resolver: Option<Arc<EntityResolver>>,
+	delivery: DeliveryTracker,
 	timestamp: u64,
 	counters: Arc<EventStreamerCounters>,
 	publish_event_lock: EventLock,
@@ -46,6 +48,8 @@ impl EventStreamer {
 		storage_mapper_messagebus: MessageBus<StorageMapper>,
 		indexer_messagebus: MessageBus<Indexer>,
 		ingestor_messagebus: MessageBus<IngestorService>,
+		entity_resolver: Option<Arc<EntityResolver>>,
+		delivery: DeliveryTracker,
 		timestamp: u64,
 	) -> Self {
 		Self {
@@ -56,6 +60,8 @@ impl EventStreamer {
 			counters: Arc::new(EventStreamerCounters::new()),
 			publish_event_lock: EventLock::default(),
 			ingestor_messagebus,
+			entity_resolver,
+			delivery,
 		}
 	}
 
@@ -136,54 +142,78 @@ impl Handler<EventsBatch> for EventStreamer {
 		self.counters.increment_events_received(group_event_count.clone() as u64);
 		self.timestamp = message.timestamp;
 
-		// Send grouped events to StorageMapper
-		for (event_type, event_states) in grouped_events {
-			match event_type {
-				EventType::Graph => {
-					let contextual_triples: ContextualTriples =
-						ContextualTriples::new(self.qflow_id.clone(), event_states, self.timestamp);
-					let mapper_res = ctx
-						.send_message(&self.storage_mapper_messagebus, contextual_triples.clone())
-						.await;
-					match mapper_res {
-						Ok(_) => {},
-						Err(e) => {
-							error!("Error sending message to StorageMapper: {:?}", e);
-						},
-					}
-					let indexer_knowledge = IndexerKnowledge::new(
-						self.qflow_id.clone(),
-						self.timestamp,
-						contextual_triples.event_payload(),
-					);
-					let indexer_res =
-						ctx.send_message(&self.indexer_messagebus, indexer_knowledge).await;
-					match indexer_res {
-						Ok(_) => {},
-						Err(e) => {
-							error!("Error sending message to Indexer: {:?}", e);
-						},
-					}
-				},
-				EventType::Vector => {
-					let contextual_embeddings = ContextualEmbeddings::new(
-						self.qflow_id.clone(),
-						event_states,
-						self.timestamp,
-					);
-					let vec_res = ctx
-						.send_message(&self.storage_mapper_messagebus, contextual_embeddings)
-						.await;
-					match vec_res {
-						Ok(_) => {},
-						Err(e) => {
-							error!("Error sending message to StorageMapper: {:?}", e);
-						},
-					}
-				},
-				_ => {},
+		let stream_span = info_span!("stream_events", qflow_id = %self.qflow_id);
+		message.trace_context.attach_to(&stream_span);
+		async {
+			// Send grouped events to StorageMapper
+			for (event_type, event_states) in grouped_events {
+				let delivery =
+					self.delivery.delivery(event_states.iter().map(|event| event.file.as_str()));
+				match event_type {
+					EventType::Graph => {
+						let event_states = match &self.entity_resolver {
+							Some(resolver) => resolver.resolve_events(event_states).await,
+							None => event_states,
+						};
+						let contextual_triples: ContextualTriples = ContextualTriples::new(
+							self.qflow_id.clone(),
+							event_states,
+							self.timestamp,
+						)
+						.with_delivery(delivery.clone());
+						let mapper_res = ctx
+							.send_message(
+								&self.storage_mapper_messagebus,
+								contextual_triples.clone(),
+							)
+							.await;
+						match mapper_res {
+							Ok(_) => {},
+							Err(e) => {
+								error!("Error sending message to StorageMapper: {:?}", e);
+								delivery.fail();
+							},
+						}
+						let indexer_knowledge = IndexerKnowledge::new(
+							self.qflow_id.clone(),
+							self.timestamp,
+							contextual_triples.event_payload(),
+						)
+						.with_delivery(delivery.clone());
+						let indexer_res =
+							ctx.send_message(&self.indexer_messagebus, indexer_knowledge).await;
+						match indexer_res {
+							Ok(_) => {},
+							Err(e) => {
+								error!("Error sending message to Indexer: {:?}", e);
+								delivery.fail();
+							},
+						}
+					},
+					EventType::Vector => {
+						let contextual_embeddings = ContextualEmbeddings::new(
+							self.qflow_id.clone(),
+							event_states,
+							self.timestamp,
+						)
+						.with_delivery(delivery.clone());
+						let vec_res = ctx
+							.send_message(&self.storage_mapper_messagebus, contextual_embeddings)
+							.await;
+						match vec_res {
+							Ok(_) => {},
+							Err(e) => {
+								error!("Error sending message to StorageMapper: {:?}", e);
+								delivery.fail();
+							},
+						}
+					},
+					_ => {},
+				}
 			}
 		}
+		.instrument(stream_span)
+		.await;
 		self.counters.increment_events_processed(group_event_count as u64);
 		ctx.record_progress();
 		Ok(())
@@ -0,0 +1,101 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use super::ImportedStatement;
+
+/// Parses CSV triples. The header row must contain `subject`, `predicate` and `object` columns;
+/// `subject_type`, `object_type`, `predicate_type` and `sentence` are optional. Column names are
+/// matched case-insensitively. Rows missing a subject, predicate or object are skipped.
+///
+/// Returns the statements and the number of skipped rows.
+pub fn parse_csv_triples(
+	content: &str,
+	default_entity_type: &str,
+) -> anyhow::Result<(Vec<ImportedStatement>, u64)> {
+	let mut reader = csv::ReaderBuilder::new()
+		.flexible(true)
+		.trim(csv::Trim::All)
+		.from_reader(content.as_bytes());
+	let headers: Vec<String> =
+		reader.headers()?.iter().map(|header| header.to_lowercase()).collect();
+	let column = |name: &str| headers.iter().position(|header| header == name);
+	let (subject, predicate, object) =
+		match (column("subject"), column("predicate"), column("object")) {
+			(Some(s), Some(p), Some(o)) => (s, p, o),
+			_ => {
+				anyhow::bail!("CSV header must contain `subject`, `predicate` and `object` columns")
+			},
+		};
+	let subject_type = column("subject_type");
+	let object_type = column("object_type");
+	let predicate_type = column("predicate_type");
+	let sentence = column("sentence");
+
+	let mut statements = Vec::new();
+	let mut skipped = 0;
+	for record in reader.records() {
+		let record = record?;
+		let field = |index: Option<usize>| {
+			index.and_then(|i| record.get(i)).filter(|value| !value.is_empty())
+		};
+		let (Some(s), Some(p), Some(o)) =
+			(field(Some(subject)), field(Some(predicate)), field(Some(object)))
+		else {
+			skipped += 1;
+			continue;
+		};
+		statements.push(ImportedStatement {
+			subject: s.to_string(),
+			subject_type: field(subject_type).unwrap_or(default_entity_type).to_string(),
+			subject_id: None,
+			predicate: p.to_string(),
+			predicate_type: field(predicate_type).unwrap_or("relation").to_string(),
+			object: o.to_string(),
+			object_type: field(object_type).unwrap_or(default_entity_type).to_string(),
+			object_id: None,
+			sentence: field(sentence).map(str::to_string),
+		});
+	}
+	Ok((statements, skipped))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_csv_with_optional_columns() {
+		let content = "Subject,Predicate,Object,Subject_Type\n\
+			Pump 42,located in,Plant A,Pump\n\
+			Pump 43,,Plant B,Pump\n\
+			Plant A,operated by,Acme,\n";
+		let (statements, skipped) = parse_csv_triples(content, "Entity").unwrap();
+		assert_eq!(skipped, 1);
+		assert_eq!(statements.len(), 2);
+		assert_eq!(statements[0].subject_type, "Pump");
+		assert_eq!(statements[0].object_type, "Entity");
+		assert_eq!(statements[1].subject_type, "Entity");
+		assert_eq!(statements[1].predicate_type, "relation");
+		assert_eq!(statements[1].sentence, None);
+	}
+
+	#[test]
+	fn rejects_missing_columns() {
+		assert!(parse_csv_triples("subject,object\na,b\n", "Entity").is_err());
+	}
+}
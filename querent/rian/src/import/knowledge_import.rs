@@ -0,0 +1,259 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{collections::HashMap, sync::Arc};
+
+use common::{EventType, KnowledgeOrigin, SemanticKnowledgePayload, VectorPayload};
+use engines::utils::calculate_triple_embeddings;
+use fastembed::TextEmbedding;
+use proto::semantics::{KnowledgeImportFormat, KnowledgeImportRequest, KnowledgeImportResponse};
+use storage::Storage;
+use tracing::info;
+
+use super::{parse_csv_triples, parse_turtle, rdf_to_statements};
+use crate::PipelineErrors;
+
+/// Number of statements embedded and written to storage per batch.
+const IMPORT_BATCH_SIZE: usize = 256;
+const DEFAULT_ENTITY_TYPE: &str = "Entity";
+
+/// A statement read from a curated source, ready to be turned into semantic knowledge.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedStatement {
+	pub subject: String,
+	pub subject_type: String,
+	/// Canonical id of the subject when the source provides one (e.g. an RDF IRI).
+	pub subject_id: Option<String>,
+	pub predicate: String,
+	pub predicate_type: String,
+	pub object: String,
+	pub object_type: String,
+	pub object_id: Option<String>,
+	pub sentence: Option<String>,
+}
+
+impl ImportedStatement {
+	/// Sentence used for embedding and display. Curated sources rarely carry one, so a
+	/// sentence is synthesized from the statement itself.
+	pub fn sentence(&self) -> String {
+		self.sentence.clone().unwrap_or_else(|| {
+			format!("{} {} {}", self.subject, humanize_predicate(&self.predicate), self.object)
+		})
+	}
+
+	pub fn into_payload(self, source_id: &str) -> SemanticKnowledgePayload {
+		SemanticKnowledgePayload {
+			sentence: self.sentence(),
+			subject: self.subject,
+			subject_type: self.subject_type,
+			object: self.object,
+			object_type: self.object_type,
+			predicate: self.predicate,
+			predicate_type: self.predicate_type,
+			image_id: None,
+			blob: None,
+			event_id: uuid::Uuid::new_v4().simple().to_string(),
+			source_id: source_id.to_string(),
+			origin: KnowledgeOrigin::Imported,
+			subject_id: self.subject_id,
+			object_id: self.object_id,
+			section_path: None,
+			chunk_start: None,
+			chunk_end: None,
+			subject_coreference: None,
+			object_coreference: None,
+			duplicate_of: None,
+		}
+	}
+}
+
+/// Turns `locatedIn`, `located_in` or `located-in` into `located in`.
+pub fn humanize_predicate(predicate: &str) -> String {
+	let mut words = String::with_capacity(predicate.len() + 4);
+	let mut previous_lowercase = false;
+	for c in predicate.chars() {
+		if c == '_' || c == '-' {
+			words.push(' ');
+			previous_lowercase = false;
+		} else if c.is_uppercase() {
+			if previous_lowercase {
+				words.push(' ');
+			}
+			words.extend(c.to_lowercase());
+			previous_lowercase = false;
+		} else {
+			words.push(c);
+			previous_lowercase = c.is_lowercase() || c.is_ascii_digit();
+		}
+	}
+	words
+}
+
+/// Parses the content of an import request into statements, returning the statements and the
+/// number of source records that did not produce one.
+pub fn parse_import_request(
+	request: &KnowledgeImportRequest,
+) -> anyhow::Result<(Vec<ImportedStatement>, u64)> {
+	let default_entity_type = request
+		.default_entity_type
+		.as_deref()
+		.filter(|entity_type| !entity_type.is_empty())
+		.unwrap_or(DEFAULT_ENTITY_TYPE);
+	match KnowledgeImportFormat::from_i32(request.format) {
+		Some(KnowledgeImportFormat::Turtle) | Some(KnowledgeImportFormat::Ntriples) => {
+			let triples = parse_turtle(&request.content)?;
+			Ok(rdf_to_statements(&triples, default_entity_type))
+		},
+		Some(KnowledgeImportFormat::Csv) =>
+			parse_csv_triples(&request.content, default_entity_type),
+		None => anyhow::bail!("unknown import format {}", request.format),
+	}
+}
+
+/// Imports curated knowledge into a collection.
+///
+/// Imported triples are embedded with the same model the semantic pipeline uses and written to
+/// the graph, vector and index storages exactly like extracted knowledge, flagged with
+/// [`KnowledgeOrigin::Imported`].
+pub async fn import_knowledge(
+	collection_id: String,
+	request: KnowledgeImportRequest,
+	embedder: &TextEmbedding,
+	event_storages: &HashMap<EventType, Vec<Arc<dyn Storage>>>,
+	index_storages: &[Arc<dyn Storage>],
+) -> Result<KnowledgeImportResponse, PipelineErrors> {
+	if request.source_name.is_empty() {
+		return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+			"source_name is required to import knowledge"
+		)));
+	}
+	let (statements, skipped_statements) =
+		parse_import_request(&request).map_err(PipelineErrors::InvalidParams)?;
+	let document_id = request.source_name.clone();
+	let document_source = format!("import://{}", request.source_name);
+
+	let mut imported_triples = 0;
+	for batch in statements.chunks(IMPORT_BATCH_SIZE) {
+		let payloads: Vec<SemanticKnowledgePayload> = batch
+			.iter()
+			.cloned()
+			.map(|statement| statement.into_payload(&request.source_name))
+			.collect();
+		let embedding_inputs: Vec<(String, String, String, String)> = payloads
+			.iter()
+			.map(|payload| {
+				(
+					payload.sentence.clone(),
+					payload.subject.clone(),
+					payload.object.clone(),
+					humanize_predicate(&payload.predicate),
+				)
+			})
+			.collect();
+		let embeddings = calculate_triple_embeddings(embedder, &embedding_inputs)
+			.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+
+		let vectors: Vec<(String, String, Option<String>, VectorPayload)> = payloads
+			.iter()
+			.zip(embeddings)
+			.map(|(payload, embeddings)| {
+				(
+					document_id.clone(),
+					document_source.clone(),
+					None,
+					VectorPayload { event_id: payload.event_id.clone(), embeddings, score: 1.0 },
+				)
+			})
+			.collect();
+		let triples: Vec<(String, String, Option<String>, SemanticKnowledgePayload)> = payloads
+			.into_iter()
+			.map(|payload| (document_id.clone(), document_source.clone(), None, payload))
+			.collect();
+
+		for storage in event_storages.get(&EventType::Graph).into_iter().flatten() {
+			storage
+				.insert_graph(collection_id.clone(), &triples)
+				.await
+				.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+		}
+		for storage in event_storages.get(&EventType::Vector).into_iter().flatten() {
+			storage
+				.insert_vector(collection_id.clone(), &vectors)
+				.await
+				.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+		}
+		for storage in index_storages {
+			storage
+				.index_knowledge(collection_id.clone(), &triples)
+				.await
+				.map_err(|e| PipelineErrors::UnknownError(e.to_string()))?;
+		}
+		imported_triples += triples.len() as u64;
+	}
+	info!(
+		"Imported {} triples from {} into collection {}",
+		imported_triples, request.source_name, collection_id
+	);
+	Ok(KnowledgeImportResponse { collection_id, imported_triples, skipped_statements })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn humanizes_predicates() {
+		assert_eq!(humanize_predicate("locatedIn"), "located in");
+		assert_eq!(humanize_predicate("part_of"), "part of");
+		assert_eq!(humanize_predicate("subClassOf"), "sub class of");
+		assert_eq!(humanize_predicate("hasISO9001"), "has iso9001");
+	}
+
+	#[test]
+	fn imported_payloads_are_flagged() {
+		let statement = ImportedStatement {
+			subject: "Pump 42".to_string(),
+			subject_type: "Pump".to_string(),
+			subject_id: None,
+			predicate: "locatedIn".to_string(),
+			predicate_type: "relation".to_string(),
+			object: "Plant A".to_string(),
+			object_type: "Plant".to_string(),
+			object_id: None,
+			sentence: None,
+		};
+		let payload = statement.into_payload("assets.ttl");
+		assert_eq!(payload.origin, KnowledgeOrigin::Imported);
+		assert_eq!(payload.sentence, "Pump 42 located in Plant A");
+		assert_eq!(payload.source_id, "assets.ttl");
+		assert!(!payload.event_id.is_empty());
+	}
+
+	#[test]
+	fn parses_requests_by_format() {
+		let request = KnowledgeImportRequest {
+			source_name: "assets.csv".to_string(),
+			format: KnowledgeImportFormat::Csv as i32,
+			content: "subject,predicate,object\nPump 42,located in,Plant A\n".to_string(),
+			default_entity_type: Some("Asset".to_string()),
+		};
+		let (statements, skipped) = parse_import_request(&request).unwrap();
+		assert_eq!(skipped, 0);
+		assert_eq!(statements[0].subject_type, "Asset");
+	}
+}
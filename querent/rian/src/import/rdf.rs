@@ -0,0 +1,295 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::collections::HashMap;
+
+use rio_api::{
+	model::{Literal, Subject, Term},
+	parser::TriplesParser,
+};
+use rio_turtle::TurtleParser;
+
+use super::ImportedStatement;
+
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const LABEL_PREDICATES: [&str; 2] =
+	["http://www.w3.org/2000/01/rdf-schema#label", "http://www.w3.org/2004/02/skos/core#prefLabel"];
+
+/// A single RDF term.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RdfTerm {
+	Iri(String),
+	BlankNode(String),
+	Literal { value: String, datatype: Option<String>, language: Option<String> },
+}
+
+/// A parsed RDF statement.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RdfTriple {
+	pub subject: RdfTerm,
+	pub predicate: String,
+	pub object: RdfTerm,
+}
+
+/// Parses Turtle content. N-Triples is a subset of Turtle and is handled by the same parser.
+///
+/// Blank node property lists (`[ ... ]`) and collections (`( ... )`) are expanded into blank
+/// nodes and `rdf:first` / `rdf:rest` statements as the Turtle grammar prescribes. RDF-star
+/// quoted triples are rejected.
+pub fn parse_turtle(content: &str) -> anyhow::Result<Vec<RdfTriple>> {
+	let mut triples = Vec::new();
+	TurtleParser::new(content.as_bytes(), None).parse_all(&mut |triple| -> anyhow::Result<()> {
+		let subject = match triple.subject {
+			Subject::NamedNode(node) => RdfTerm::Iri(node.iri.to_string()),
+			Subject::BlankNode(node) => RdfTerm::BlankNode(node.id.to_string()),
+			Subject::Triple(_) => anyhow::bail!("quoted triples are not supported"),
+		};
+		let object = match triple.object {
+			Term::NamedNode(node) => RdfTerm::Iri(node.iri.to_string()),
+			Term::BlankNode(node) => RdfTerm::BlankNode(node.id.to_string()),
+			Term::Literal(Literal::Simple { value }) =>
+				RdfTerm::Literal { value: value.to_string(), datatype: None, language: None },
+			Term::Literal(Literal::LanguageTaggedString { value, language }) => RdfTerm::Literal {
+				value: value.to_string(),
+				datatype: None,
+				language: Some(language.to_string()),
+			},
+			Term::Literal(Literal::Typed { value, datatype }) => RdfTerm::Literal {
+				value: value.to_string(),
+				datatype: Some(datatype.iri.to_string()),
+				language: None,
+			},
+			Term::Triple(_) => anyhow::bail!("quoted triples are not supported"),
+		};
+		triples.push(RdfTriple { subject, predicate: triple.predicate.iri.to_string(), object });
+		Ok(())
+	})?;
+	Ok(triples)
+}
+
+/// Converts RDF triples into importable statements.
+///
+/// `rdf:type` statements become entity types and `rdfs:label` / `skos:prefLabel` statements
+/// become entity names; neither is emitted as a statement on its own. Returns the statements and
+/// the number of RDF triples that were folded into types or labels.
+pub fn rdf_to_statements(
+	triples: &[RdfTriple],
+	default_entity_type: &str,
+) -> (Vec<ImportedStatement>, u64) {
+	let mut types: HashMap<&RdfTerm, String> = HashMap::new();
+	let mut labels: HashMap<&RdfTerm, String> = HashMap::new();
+	let mut folded = 0;
+	for triple in triples {
+		if triple.predicate == RDF_TYPE {
+			if let RdfTerm::Iri(class) = &triple.object {
+				types.entry(&triple.subject).or_insert_with(|| local_name(class));
+			}
+		} else if LABEL_PREDICATES.contains(&triple.predicate.as_str()) {
+			if let RdfTerm::Literal { value, language, .. } = &triple.object {
+				let preferred = matches!(language.as_deref(), None | Some("en"));
+				if preferred || !labels.contains_key(&triple.subject) {
+					labels.insert(&triple.subject, value.clone());
+				}
+			}
+		}
+	}
+
+	let name_of = |term: &RdfTerm| -> String {
+		labels.get(term).cloned().unwrap_or_else(|| match term {
+			RdfTerm::Iri(iri) => local_name(iri),
+			RdfTerm::BlankNode(id) => format!("_:{}", id),
+			RdfTerm::Literal { value, .. } => value.clone(),
+		})
+	};
+	let type_of = |term: &RdfTerm| -> String {
+		types.get(term).cloned().unwrap_or_else(|| default_entity_type.to_string())
+	};
+	// IRIs already identify a resource globally, so they double as canonical entity ids.
+	let id_of = |term: &RdfTerm| -> Option<String> {
+		match term {
+			RdfTerm::Iri(iri) => Some(iri.clone()),
+			_ => None,
+		}
+	};
+
+	let mut statements = Vec::new();
+	for triple in triples {
+		if triple.predicate == RDF_TYPE || LABEL_PREDICATES.contains(&triple.predicate.as_str()) {
+			folded += 1;
+			continue;
+		}
+		let (object, object_type, object_id, predicate_type) = match &triple.object {
+			RdfTerm::Literal { value, datatype, .. } => (
+				value.clone(),
+				datatype.as_deref().map(local_name).unwrap_or_else(|| "literal".to_string()),
+				None,
+				"attribute",
+			),
+			other => (name_of(other), type_of(other), id_of(other), "relation"),
+		};
+		statements.push(ImportedStatement {
+			subject: name_of(&triple.subject),
+			subject_type: type_of(&triple.subject),
+			subject_id: id_of(&triple.subject),
+			predicate: local_name(&triple.predicate),
+			predicate_type: predicate_type.to_string(),
+			object,
+			object_type,
+			object_id,
+			sentence: None,
+		});
+	}
+	(statements, folded)
+}
+
+/// Returns the fragment or last path segment of an IRI.
+pub fn local_name(iri: &str) -> String {
+	let trimmed = iri.trim_end_matches(['/', '#']);
+	trimmed
+		.rsplit(['#', '/', ':'])
+		.next()
+		.filter(|name| !name.is_empty())
+		.unwrap_or(trimmed)
+		.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const XSD: &str = "http://www.w3.org/2001/XMLSchema#";
+	const RDF: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#";
+
+	const TAXONOMY: &str = r#"
+@prefix ex: <http://example.org/assets#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+# Curated asset register
+ex:pump42 a ex:Pump ;
+	rdfs:label "Pump 42"@en ;
+	ex:locatedIn ex:plantA , ex:plantB ;
+	ex:ratedPower 7.5 .
+ex:plantA a ex:Plant ; rdfs:label "Plant A" ;
+	ex:lines ( ex:line1 ex:line2 ) .
+"#;
+
+	#[test]
+	fn parses_turtle_with_prefixes_and_lists() {
+		let triples = parse_turtle(TAXONOMY).unwrap();
+		assert_eq!(triples.len(), 12);
+		assert_eq!(triples[0].predicate, RDF_TYPE);
+		assert_eq!(triples[0].object, RdfTerm::Iri("http://example.org/assets#Pump".to_string()));
+		assert_eq!(
+			triples[4].object,
+			RdfTerm::Literal {
+				value: "7.5".to_string(),
+				datatype: Some(format!("{}decimal", XSD)),
+				language: None
+			}
+		);
+		let lines = triples.iter().find(|triple| local_name(&triple.predicate) == "lines").unwrap();
+		let mut node = lines.object.clone();
+		let mut members = Vec::new();
+		while node != RdfTerm::Iri(format!("{}nil", RDF)) {
+			let property = |local: &str| {
+				let predicate = format!("{}{}", RDF, local);
+				triples
+					.iter()
+					.find(|triple| triple.subject == node && triple.predicate == predicate)
+					.map(|triple| triple.object.clone())
+					.unwrap()
+			};
+			members.push(property("first"));
+			node = property("rest");
+		}
+		assert_eq!(
+			members,
+			[
+				RdfTerm::Iri("http://example.org/assets#line1".to_string()),
+				RdfTerm::Iri("http://example.org/assets#line2".to_string())
+			]
+		);
+	}
+
+	#[test]
+	fn parses_ntriples() {
+		let content = "<http://e.org/a> <http://e.org/knows> <http://e.org/b> .\n\
+			_:x <http://e.org/name> \"Bob \\\"B\\\"\"^^<http://www.w3.org/2001/XMLSchema#string> .";
+		let triples = parse_turtle(content).unwrap();
+		assert_eq!(triples.len(), 2);
+		assert_eq!(triples[1].subject, RdfTerm::BlankNode("x".to_string()));
+		assert_eq!(
+			triples[1].object,
+			RdfTerm::Literal {
+				value: "Bob \"B\"".to_string(),
+				datatype: Some(format!("{}string", XSD)),
+				language: None
+			}
+		);
+	}
+
+	#[test]
+	fn expands_property_lists_and_collections() {
+		let content = r#"
+@prefix ex: <http://example.org/> .
+ex:plant ex:operator [ ex:name "Acme" ] ;
+	ex:lines ( ex:line1 ex:line2 ) .
+"#;
+		let triples = parse_turtle(content).unwrap();
+		assert_eq!(triples.len(), 7);
+		let with_predicate = |local: &'static str| {
+			triples.iter().filter(move |triple| local_name(&triple.predicate) == local)
+		};
+		let operator = with_predicate("operator").next().unwrap();
+		assert!(matches!(operator.object, RdfTerm::BlankNode(_)));
+		assert_eq!(with_predicate("name").next().unwrap().subject, operator.object);
+		let members: Vec<&RdfTerm> = with_predicate("first").map(|triple| &triple.object).collect();
+		assert_eq!(
+			members,
+			[
+				&RdfTerm::Iri("http://example.org/line1".to_string()),
+				&RdfTerm::Iri("http://example.org/line2".to_string())
+			]
+		);
+	}
+
+	#[test]
+	fn folds_types_and_labels_into_statements() {
+		let triples = parse_turtle(TAXONOMY).unwrap();
+		let (statements, folded) = rdf_to_statements(&triples, "Entity");
+		assert_eq!(folded, 4);
+		assert_eq!(statements.len(), 8);
+		let located = &statements[0];
+		assert_eq!(located.subject, "Pump 42");
+		assert_eq!(located.subject_type, "Pump");
+		assert_eq!(located.predicate, "locatedIn");
+		assert_eq!(located.object, "Plant A");
+		assert_eq!(located.object_type, "Plant");
+		assert_eq!(located.subject_id.as_deref(), Some("http://example.org/assets#pump42"));
+		assert_eq!(statements[2].object_id, None);
+		assert_eq!(statements[1].object, "plantB");
+		assert_eq!(statements[1].object_type, "Entity");
+		assert_eq!(statements[2].predicate_type, "attribute");
+		assert_eq!(statements[2].object_type, "decimal");
+	}
+
+	#[test]
+	fn rejects_undeclared_prefix() {
+		assert!(parse_turtle("ex:a ex:b ex:c .").is_err());
+	}
+}
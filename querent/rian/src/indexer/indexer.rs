@@ -23,8 +23,9 @@ use async_trait::async_trait;
 use common::{IndexerCounters, RuntimeType, SemanticKnowledgePayload};
 use storage::Storage;
 use tokio::runtime::Handle;
+use tracing::{info_span, Instrument};
 
-use crate::{EventLock, IndexerKnowledge, NewEventLock};
+use crate::{metrics::PIPELINE_METRICS, Delivery, EventLock, IndexerKnowledge, NewEventLock};
 
 pub struct Indexer {
 	pub qflow_id: String,
@@ -133,6 +134,9 @@ impl Handler<IndexerKnowledge> for Indexer {
 		_message: IndexerKnowledge,
 		_ctx: &ActorContext<Self>,
 	) -> Result<(), ActorExitStatus> {
+		let index_span = info_span!("index_knowledge", qflow_id = %self.qflow_id);
+		_message.trace_context.attach_to(&index_span);
+		let _entered = index_span.enter();
 		let knowledge = _message.triples;
 		let mut doc_map: HashMap<String, Vec<SemanticKnowledgePayload>> = HashMap::new();
 		for (doc, _source, _image_id, payload) in &knowledge {
@@ -154,7 +158,12 @@ impl Handler<IndexerKnowledge> for Indexer {
 			self.counters.increment_total_objects_indexed(o as u64);
 		});
 		for storage in &self.index_storages {
-			insert_index_async(self.qflow_id.clone(), storage.clone(), knowledge.clone())?;
+			insert_index_async(
+				self.qflow_id.clone(),
+				storage.clone(),
+				knowledge.clone(),
+				_message.delivery.clone(),
+			)?;
 		}
 		Ok(())
 	}
@@ -164,12 +173,25 @@ pub fn insert_index_async(
 	collection_id: String,
 	storage: Arc<dyn Storage>,
 	storage_items: Vec<(String, String, Option<String>, SemanticKnowledgePayload)>,
+	delivery: Delivery,
 ) -> Result<(), ActorExitStatus> {
-	tokio::spawn(async move {
-		let upsert_result = storage.index_knowledge(collection_id, &storage_items).await;
+	let index_task = async move {
+		let labels = [storage.backend_name(), "index"];
+		let write_timer = PIPELINE_METRICS
+			.storage_write_duration_seconds
+			.with_label_values(labels)
+			.start_timer();
+		let upsert_result = storage
+			.index_knowledge(collection_id, &storage_items)
+			.instrument(info_span!("storage_write", backend = labels[0], kind = labels[1]))
+			.await;
+		write_timer.observe_duration();
 		if let Err(e) = upsert_result {
+			PIPELINE_METRICS.storage_write_errors_total.with_label_values(labels).inc();
 			log::error!("Error inserting knowledge: {:?}", e);
+			delivery.fail();
 		}
-	});
+	};
+	tokio::spawn(index_task.in_current_span());
 	Ok(())
 }
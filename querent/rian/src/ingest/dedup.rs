@@ -0,0 +1,575 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use proto::semantics::{
+	DeduplicationConfig, DocumentFingerprints, DuplicateAction, IngestedTokens,
+};
+use std::{
+	collections::{hash_map::DefaultHasher, BTreeMap, HashMap},
+	fmt,
+	hash::{Hash, Hasher},
+	sync::{
+		atomic::{AtomicU64, Ordering},
+		Arc, Mutex,
+	},
+};
+use storage::MetaStorage;
+use tracing::error;
+
+/// Number of hash functions of document MinHash signatures.
+const MINHASH_PERMUTATIONS: usize = 128;
+/// Rows of a band of the MinHash index: signatures sharing any of the 16 bands are compared,
+/// which finds documents whose similarity is above 0.7 most of the time.
+const MINHASH_ROWS: usize = 8;
+/// Words of the shingles documents are compared by.
+const DOCUMENT_SHINGLE: usize = 5;
+/// Words of the shingles chunk fingerprints are made of.
+const CHUNK_SHINGLE: usize = 3;
+/// Chunks indexed before the index starts over, to bound its memory.
+const MAX_INDEXED_CHUNKS: usize = 1_000_000;
+/// Documents indexed before the index starts over.
+const MAX_INDEXED_DOCUMENTS: usize = 100_000;
+
+const DEFAULT_MAX_CHUNK_DISTANCE: u32 = 3;
+const DEFAULT_DOCUMENT_SIMILARITY: f32 = 0.9;
+const DEFAULT_MIN_CHUNK_WORDS: u32 = 8;
+
+/// Finds the documents and chunks of a pipeline that repeat ones read before, such as the
+/// quoted messages of a mail thread or the versions of a file.
+///
+/// Chunks are compared by SimHash fingerprints as they are read: a chunk whose fingerprint is
+/// within `max_chunk_distance` bits of one seen in another document is a duplicate, skipped or
+/// extracted with a link to that document. Documents are compared once read by the Jaccard
+/// similarity of their shingles, estimated from MinHash signatures. Re-reading a document, on
+/// retry or in a later run, does not make it a duplicate of itself.
+///
+/// The fingerprints of every document are persisted to the metadata store, when there is one,
+/// from which [`Deduplicator::load`] restores the indexes when the pipeline starts again. The
+/// indexes start over once they hold `MAX_INDEXED_CHUNKS` chunks or `MAX_INDEXED_DOCUMENTS`
+/// documents.
+pub struct Deduplicator {
+	pipeline_id: String,
+	metadata_store: Option<Arc<dyn MetaStorage>>,
+	action: DuplicateAction,
+	max_chunk_distance: u32,
+	document_similarity: f32,
+	min_chunk_words: usize,
+	chunks: Mutex<ChunkIndex>,
+	documents: Mutex<DocumentIndex>,
+	next_reading: AtomicU64,
+}
+
+/// Fingerprint of a document being read, and the duplicates found in it so far.
+pub struct DocumentFingerprint {
+	document: Arc<str>,
+	// tells the readings of a document apart, so that a retry is not a duplicate of itself
+	reading: u64,
+	signature: Vec<u64>,
+	shingles: usize,
+	// fingerprints of the chunks of the document which repeat no other document
+	chunks: Vec<u64>,
+	duplicated_from: HashMap<String, u32>,
+}
+
+/// Duplicates found in a document.
+#[derive(Debug, Default, PartialEq)]
+pub struct DuplicateReport {
+	/// Document read before that this one nearly duplicates as a whole.
+	pub duplicate_of: Option<String>,
+	/// Documents read before whose chunks this one repeats, with the number of chunks.
+	pub duplicated_from: HashMap<String, u32>,
+}
+
+struct ChunkEntry {
+	fingerprint: u64,
+	document: Arc<str>,
+	reading: u64,
+}
+
+#[derive(Default)]
+struct ChunkIndex {
+	entries: Vec<ChunkEntry>,
+	// entries by band of their fingerprint: fingerprints within n bits share one of n + 1 bands
+	bands: HashMap<(usize, u64), Vec<usize>>,
+}
+
+#[derive(Default)]
+struct DocumentIndex {
+	documents: Vec<(Arc<str>, Vec<u64>)>,
+	bands: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl Deduplicator {
+	/// `None` unless deduplication is enabled.
+	pub fn new(config: &DeduplicationConfig) -> Option<Self> {
+		if config.enabled != Some(true) {
+			return None;
+		}
+		Some(Self {
+			pipeline_id: String::new(),
+			metadata_store: None,
+			action: config.action(),
+			// the fingerprint has 64 bits, split in one band more than the distance
+			max_chunk_distance: config
+				.max_chunk_distance
+				.unwrap_or(DEFAULT_MAX_CHUNK_DISTANCE)
+				.min(31),
+			document_similarity: config.document_similarity.unwrap_or(DEFAULT_DOCUMENT_SIMILARITY),
+			min_chunk_words: config.min_chunk_words.unwrap_or(DEFAULT_MIN_CHUNK_WORDS) as usize,
+			chunks: Mutex::default(),
+			documents: Mutex::default(),
+			next_reading: AtomicU64::new(0),
+		})
+	}
+
+	/// Persists the fingerprints of the documents `pipeline_id` reads to `metadata_store`.
+	pub fn with_metadata_store(
+		mut self,
+		pipeline_id: String,
+		metadata_store: Arc<dyn MetaStorage>,
+	) -> Self {
+		self.pipeline_id = pipeline_id;
+		self.metadata_store = Some(metadata_store);
+		self
+	}
+
+	/// Restores the fingerprints persisted by earlier runs of the pipeline, so that documents
+	/// duplicating ones read before a restart are recognized.
+	pub async fn load(&self) {
+		let Some(metadata_store) = &self.metadata_store else {
+			return;
+		};
+		let persisted = match metadata_store.get_document_fingerprints(&self.pipeline_id).await {
+			Ok(persisted) => persisted,
+			Err(e) => {
+				error!("Failed to load fingerprints of pipeline {}: {:?}", self.pipeline_id, e);
+				return;
+			},
+		};
+		let mut chunks = self.chunks.lock().unwrap();
+		let mut documents = self.documents.lock().unwrap();
+		for fingerprints in persisted {
+			if chunks.entries.len() + fingerprints.chunks.len() > MAX_INDEXED_CHUNKS ||
+				documents.documents.len() >= MAX_INDEXED_DOCUMENTS
+			{
+				break;
+			}
+			let document: Arc<str> = Arc::from(fingerprints.document);
+			let reading = self.next_reading.fetch_add(1, Ordering::Relaxed);
+			for fingerprint in fingerprints.chunks {
+				chunks.insert(fingerprint, document.clone(), reading, self.max_chunk_distance);
+			}
+			if fingerprints.signature.len() == MINHASH_PERMUTATIONS {
+				documents.insert(document, fingerprints.signature);
+			}
+		}
+	}
+
+	/// Whether duplicates are dropped rather than linked, in which case the tokens of a document
+	/// are held back until `finish_document` tells whether it duplicates one as a whole.
+	pub fn skips_duplicates(&self) -> bool {
+		self.action == DuplicateAction::Skip
+	}
+
+	/// Starts reading `document`.
+	pub fn start_document(&self, document: &str) -> DocumentFingerprint {
+		DocumentFingerprint {
+			document: Arc::from(document),
+			reading: self.next_reading.fetch_add(1, Ordering::Relaxed),
+			signature: vec![u64::MAX; MINHASH_PERMUTATIONS],
+			shingles: 0,
+			chunks: Vec::new(),
+			duplicated_from: HashMap::new(),
+		}
+	}
+
+	/// Adds the chunks of `tokens` to the fingerprint of their document and finds the ones
+	/// seen before, which are removed. When duplicates are only linked, they are returned in
+	/// tokens of their own instead, one per document they repeat, named by `duplicate_of`. The
+	/// rows of a table stay with their table.
+	pub fn filter_chunks(
+		&self,
+		fingerprint: &mut DocumentFingerprint,
+		tokens: &mut IngestedTokens,
+	) -> Vec<IngestedTokens> {
+		let mut chunks = self.chunks.lock().unwrap();
+		if chunks.entries.len() >= MAX_INDEXED_CHUNKS {
+			*chunks = ChunkIndex::default();
+		}
+		let mut duplicates = HashMap::new();
+		for (position, text) in tokens.data.iter().enumerate() {
+			let words = normalized_words(text);
+			fingerprint.add_shingles(&words);
+			if words.len() < self.min_chunk_words {
+				continue;
+			}
+			let chunk_fingerprint = simhash(&words);
+			match chunks.find(chunk_fingerprint, self.max_chunk_distance) {
+				// another document, or this one earlier in the same reading
+				Some(entry)
+					if *entry.document != *fingerprint.document ||
+						entry.reading == fingerprint.reading =>
+				{
+					*fingerprint.duplicated_from.entry(entry.document.to_string()).or_default() +=
+						1;
+					duplicates.insert(position, entry.document.clone());
+				},
+				// read by an earlier reading of the document
+				Some(_) => fingerprint.chunks.push(chunk_fingerprint),
+				None => {
+					chunks.insert(
+						chunk_fingerprint,
+						fingerprint.document.clone(),
+						fingerprint.reading,
+						self.max_chunk_distance,
+					);
+					fingerprint.chunks.push(chunk_fingerprint);
+				},
+			}
+		}
+		if duplicates.is_empty() || (self.action == DuplicateAction::Link && tokens.table.is_some())
+		{
+			return Vec::new();
+		}
+		let mut linked: BTreeMap<Arc<str>, Vec<String>> = BTreeMap::new();
+		for (position, text) in std::mem::take(&mut tokens.data).into_iter().enumerate() {
+			match duplicates.get(&position) {
+				Some(document) if self.action == DuplicateAction::Link =>
+					linked.entry(document.clone()).or_default().push(text),
+				Some(_) => {},
+				None => tokens.data.push(text),
+			}
+		}
+		// the table of duplicate tokens is a duplicate too
+		if tokens.data.is_empty() {
+			tokens.table = None;
+		}
+		linked
+			.into_iter()
+			.map(|(document, data)| IngestedTokens {
+				file: tokens.file.clone(),
+				data,
+				is_token_stream: tokens.is_token_stream,
+				doc_source: tokens.doc_source.clone(),
+				source_id: tokens.source_id.clone(),
+				image_id: tokens.image_id.clone(),
+				section_path: tokens.section_path.clone(),
+				table: None,
+				duplicate_of: Some(document.to_string()),
+			})
+			.collect()
+	}
+
+	/// Ends the reading of a document: compares it with the documents read before, indexes it
+	/// for the ones read next and persists its fingerprints.
+	pub async fn finish_document(&self, fingerprint: DocumentFingerprint) -> DuplicateReport {
+		let DocumentFingerprint {
+			document, signature, shingles, chunks, mut duplicated_from, ..
+		} = fingerprint;
+		// chunks repeated within the document are not duplicates of another one
+		duplicated_from.remove(&*document);
+		let (duplicate_of, indexed_signature) = if shingles == 0 {
+			(None, Vec::new())
+		} else {
+			self.index_document(document.clone(), signature)
+		};
+		if let Some(metadata_store) = &self.metadata_store {
+			// a document read again without new chunks keeps the fingerprints persisted before
+			if !chunks.is_empty() || !indexed_signature.is_empty() {
+				let fingerprints = DocumentFingerprints {
+					document: document.to_string(),
+					signature: indexed_signature,
+					chunks,
+				};
+				if let Err(e) =
+					metadata_store.set_document_fingerprints(&self.pipeline_id, fingerprints).await
+				{
+					error!("Failed to persist fingerprints of {}: {:?}", document, e);
+				}
+			}
+		}
+		DuplicateReport { duplicate_of, duplicated_from }
+	}
+
+	/// Finds the document `document` nearly duplicates, and indexes its signature when there is
+	/// none, returning the signature indexed.
+	fn index_document(
+		&self,
+		document: Arc<str>,
+		signature: Vec<u64>,
+	) -> (Option<String>, Vec<u64>) {
+		let mut documents = self.documents.lock().unwrap();
+		if documents.documents.len() >= MAX_INDEXED_DOCUMENTS {
+			*documents = DocumentIndex::default();
+		}
+		let duplicate_of = documents
+			.candidates(&signature)
+			.into_iter()
+			.filter(|(candidate, _)| ***candidate != *document)
+			.map(|(candidate, other)| (candidate, similarity(&signature, other)))
+			.filter(|(_, similarity)| *similarity >= self.document_similarity)
+			.max_by(|a, b| a.1.total_cmp(&b.1))
+			.map(|(candidate, _)| candidate.to_string());
+		if duplicate_of.is_some() {
+			return (duplicate_of, Vec::new());
+		}
+		documents.insert(document, signature.clone());
+		(None, signature)
+	}
+}
+
+impl fmt::Debug for Deduplicator {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("Deduplicator")
+			.field("action", &self.action)
+			.field("max_chunk_distance", &self.max_chunk_distance)
+			.field("document_similarity", &self.document_similarity)
+			.field("min_chunk_words", &self.min_chunk_words)
+			.finish()
+	}
+}
+
+impl DocumentFingerprint {
+	fn add_shingles(&mut self, words: &[String]) {
+		let shingle_len = DOCUMENT_SHINGLE.min(words.len());
+		if shingle_len == 0 {
+			return;
+		}
+		for shingle in words.windows(shingle_len) {
+			let hash = hash_of(shingle);
+			for (permutation, min) in self.signature.iter_mut().enumerate() {
+				*min = (*min)
+					.min(mix(hash ^ (permutation as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)));
+			}
+			self.shingles += 1;
+		}
+	}
+}
+
+impl ChunkIndex {
+	fn find(&self, fingerprint: u64, max_distance: u32) -> Option<&ChunkEntry> {
+		band_keys(fingerprint, max_distance as usize + 1)
+			.filter_map(|key| self.bands.get(&key))
+			.flatten()
+			.map(|index| &self.entries[*index])
+			.find(|entry| (entry.fingerprint ^ fingerprint).count_ones() <= max_distance)
+	}
+
+	fn insert(&mut self, fingerprint: u64, document: Arc<str>, reading: u64, max_distance: u32) {
+		let index = self.entries.len();
+		self.entries.push(ChunkEntry { fingerprint, document, reading });
+		for key in band_keys(fingerprint, max_distance as usize + 1) {
+			self.bands.entry(key).or_default().push(index);
+		}
+	}
+}
+
+impl DocumentIndex {
+	fn candidates(&self, signature: &[u64]) -> Vec<(&Arc<str>, &Vec<u64>)> {
+		let mut indexes: Vec<usize> = signature
+			.chunks(MINHASH_ROWS)
+			.enumerate()
+			.filter_map(|(band, rows)| self.bands.get(&(band, hash_of(rows))))
+			.flatten()
+			.copied()
+			.collect();
+		indexes.sort_unstable();
+		indexes.dedup();
+		indexes
+			.into_iter()
+			.map(|index| (&self.documents[index].0, &self.documents[index].1))
+			.collect()
+	}
+
+	fn insert(&mut self, document: Arc<str>, signature: Vec<u64>) {
+		let index = self.documents.len();
+		for (band, rows) in signature.chunks(MINHASH_ROWS).enumerate() {
+			self.bands.entry((band, hash_of(rows))).or_default().push(index);
+		}
+		self.documents.push((document, signature));
+	}
+}
+
+/// Lowercased words, without punctuation, so that formatting does not tell copies apart.
+fn normalized_words(text: &str) -> Vec<String> {
+	text.split(|c: char| !c.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.map(str::to_lowercase)
+		.collect()
+}
+
+/// SimHash of the word shingles of a chunk: near duplicates differ in few bits.
+fn simhash(words: &[String]) -> u64 {
+	let shingle_len = CHUNK_SHINGLE.min(words.len());
+	let mut weights = [0i32; 64];
+	for shingle in words.windows(shingle_len) {
+		let hash = hash_of(shingle);
+		for (bit, weight) in weights.iter_mut().enumerate() {
+			*weight += if hash >> bit & 1 == 1 { 1 } else { -1 };
+		}
+	}
+	weights
+		.iter()
+		.enumerate()
+		.filter(|(_, weight)| **weight > 0)
+		.fold(0, |fingerprint, (bit, _)| fingerprint | 1 << bit)
+}
+
+/// Splits the 64 bits of a fingerprint in `bands` ranges, keyed by their position.
+fn band_keys(fingerprint: u64, bands: usize) -> impl Iterator<Item = (usize, u64)> {
+	let bands = bands.clamp(1, 64);
+	(0..bands).map(move |band| {
+		let start = band * 64 / bands;
+		let end = (band + 1) * 64 / bands;
+		let mask = if end - start == 64 { u64::MAX } else { (1u64 << (end - start)) - 1 };
+		(band, fingerprint >> start & mask)
+	})
+}
+
+/// Share of equal MinHash values, an estimate of the Jaccard similarity of the shingles.
+fn similarity(a: &[u64], b: &[u64]) -> f32 {
+	let equal = a.iter().zip(b).filter(|(a, b)| a == b).count();
+	equal as f32 / a.len() as f32
+}
+
+fn hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+	let mut hasher = DefaultHasher::new();
+	value.hash(&mut hasher);
+	hasher.finish()
+}
+
+/// splitmix64 finalizer, turning the shingle hash into one independent hash per permutation.
+fn mix(mut value: u64) -> u64 {
+	value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	value ^ (value >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const THREAD: &str = "The pressure tests on the northern well came back within the expected \
+		range and the crew will move the rig to the second site next week";
+
+	fn tokens(texts: &[&str]) -> IngestedTokens {
+		IngestedTokens {
+			data: texts.iter().map(|text| text.to_string()).collect(),
+			..Default::default()
+		}
+	}
+
+	async fn read(
+		deduplicator: &Deduplicator,
+		document: &str,
+		texts: &[&str],
+	) -> (Vec<String>, Vec<IngestedTokens>, DuplicateReport) {
+		let mut fingerprint = deduplicator.start_document(document);
+		let mut tokens = tokens(texts);
+		let linked = deduplicator.filter_chunks(&mut fingerprint, &mut tokens);
+		(tokens.data, linked, deduplicator.finish_document(fingerprint).await)
+	}
+
+	#[tokio::test]
+	async fn skips_chunks_and_links_documents_already_read() {
+		let deduplicator =
+			Deduplicator::new(&DeduplicationConfig { enabled: Some(true), ..Default::default() })
+				.unwrap();
+		let reply = "Thanks, could you also send the logs of the second site once the rig is there";
+		let (kept, _, report) = read(&deduplicator, "first.eml", &[THREAD]).await;
+		assert_eq!(kept.len(), 1);
+		assert_eq!(report, DuplicateReport::default());
+
+		// a reply quoting the first message, with other punctuation and case
+		let quoted = THREAD.to_uppercase().replace(' ', "  ");
+		let (kept, linked, report) = read(&deduplicator, "reply.eml", &[reply, &quoted]).await;
+		assert_eq!(kept, vec![reply.to_string()]);
+		assert!(linked.is_empty());
+		assert_eq!(report.duplicated_from.get("first.eml"), Some(&1));
+		assert_eq!(report.duplicate_of, None);
+
+		// a forwarded copy of the first message
+		let (kept, _, report) = read(&deduplicator, "forward.eml", &[&format!("{THREAD}.")]).await;
+		assert!(kept.is_empty());
+		assert_eq!(report.duplicate_of.as_deref(), Some("first.eml"));
+
+		// reading the first message again, on retry
+		let (kept, _, report) = read(&deduplicator, "first.eml", &[THREAD]).await;
+		assert_eq!(kept.len(), 1);
+		assert_eq!(report, DuplicateReport::default());
+	}
+
+	#[tokio::test]
+	async fn finds_near_duplicate_chunks() {
+		let deduplicator = Deduplicator::new(&DeduplicationConfig {
+			enabled: Some(true),
+			action: DuplicateAction::Link as i32,
+			// a word changed in a short chunk flips a few bits of its fingerprint
+			max_chunk_distance: Some(8),
+			..Default::default()
+		})
+		.unwrap();
+		read(&deduplicator, "v1.docx", &[THREAD]).await;
+		let edited = THREAD.replace("next week", "next month");
+		let (kept, linked, report) = read(&deduplicator, "v2.docx", &[&edited]).await;
+		// linked, not skipped
+		assert!(kept.is_empty());
+		assert_eq!(linked.len(), 1);
+		assert_eq!(linked[0].data, vec![edited.clone()]);
+		assert_eq!(linked[0].duplicate_of.as_deref(), Some("v1.docx"));
+		assert_eq!(report.duplicated_from.get("v1.docx"), Some(&1));
+		// opt-in
+		assert!(Deduplicator::new(&DeduplicationConfig::default()).is_none());
+		assert!(Deduplicator::new(&DeduplicationConfig {
+			enabled: Some(false),
+			..Default::default()
+		})
+		.is_none());
+	}
+
+	#[tokio::test]
+	async fn recognizes_documents_read_before_a_restart() {
+		let dir = tempfile::tempdir().unwrap();
+		let metadata_store =
+			storage::create_metadata_store(dir.path().to_path_buf()).await.unwrap();
+		let config = DeduplicationConfig { enabled: Some(true), ..Default::default() };
+		let deduplicator = Deduplicator::new(&config)
+			.unwrap()
+			.with_metadata_store("pipeline".to_string(), metadata_store.clone());
+		read(&deduplicator, "first.eml", &[THREAD]).await;
+
+		let restarted = Deduplicator::new(&config)
+			.unwrap()
+			.with_metadata_store("pipeline".to_string(), metadata_store.clone());
+		restarted.load().await;
+		let (kept, _, report) = read(&restarted, "forward.eml", &[&format!("{THREAD}.")]).await;
+		assert!(kept.is_empty());
+		assert_eq!(report.duplicate_of.as_deref(), Some("first.eml"));
+		// not a duplicate of its reading before the restart
+		let (kept, _, report) = read(&restarted, "first.eml", &[THREAD]).await;
+		assert_eq!(kept.len(), 1);
+		assert_eq!(report, DuplicateReport::default());
+
+		// the fingerprints belong to their pipeline
+		let other = Deduplicator::new(&config)
+			.unwrap()
+			.with_metadata_store("other".to_string(), metadata_store);
+		other.load().await;
+		let (kept, ..) = read(&other, "forward.eml", &[THREAD]).await;
+		assert_eq!(kept.len(), 1);
+	}
+}
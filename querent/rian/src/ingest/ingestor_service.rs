@@ -16,18 +16,32 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use std::sync::Arc;
+use std::{
+	sync::Arc,
+	time::{Duration, Instant},
+};
 
 use actors::{Actor, ActorContext, ActorExitStatus, Handler, QueueCapacity};
 use async_trait::async_trait;
-use common::{CollectionBatch, IngestorCounters, RuntimeType, TerimateSignal};
+use common::{
+	CollectionBatch, DocumentTraces, IngestorCounters, RuntimeType, TerimateSignal, TraceContext,
+};
 use futures::StreamExt;
-use ingestors::resolve_ingestor_with_extension;
-use proto::semantics::IngestedTokens;
+use ingestors::{file_type::peek_head, resolve_ingestor, IngestorOptions};
+use proto::semantics::{DocumentStatus, IngestedTokens};
 use tokio::{runtime::Handle, sync::mpsc::Sender, task::JoinHandle};
-use tracing::{error, info};
+use tracing::{error, info, info_span, Instrument};
 
-use crate::{MAX_DATA_SIZE_IN_MEMORY, NUMBER_FILES_IN_MEMORY};
+use super::dedup::Deduplicator;
+use crate::{
+	metrics::{file_type_label, PIPELINE_METRICS},
+	DeliveryTracker, DocumentTracker, MAX_DATA_SIZE_IN_MEMORY, NUMBER_FILES_IN_MEMORY,
+};
+
+/// Bytes of text of a document held back until deduplication tells whether it duplicates one
+/// read before. The tokens of larger documents are sent as they come, skipping their duplicate
+/// chunks only, so the document is extracted even if it turns out to duplicate one as a whole.
+const MAX_HELD_BACK_BYTES: usize = 16 * 1024 * 1024;
 
 pub struct IngestorService {
 	pub collector_id: String,
@@ -37,6 +51,11 @@ pub struct IngestorService {
 	workflow_handles: Vec<JoinHandle<()>>,
 	workflow_semaphore: Arc<tokio::sync::Semaphore>,
 	terminate_signal: TerimateSignal,
+	document_traces: DocumentTraces,
+	document_tracker: DocumentTracker,
+	delivery: DeliveryTracker,
+	ingestor_options: IngestorOptions,
+	deduplicator: Option<Arc<Deduplicator>>,
 }
 
 impl IngestorService {
@@ -45,6 +64,11 @@ impl IngestorService {
 		token_sender: Sender<IngestedTokens>,
 		timestamp: u64,
 		terminate_signal: TerimateSignal,
+		document_traces: DocumentTraces,
+		document_tracker: DocumentTracker,
+		delivery: DeliveryTracker,
+		ingestor_options: IngestorOptions,
+		deduplicator: Option<Arc<Deduplicator>>,
 	) -> Self {
 		Self {
 			collector_id,
@@ -54,6 +78,11 @@ impl IngestorService {
 			workflow_handles: Vec::new(),
 			workflow_semaphore: Arc::new(tokio::sync::Semaphore::new(NUMBER_FILES_IN_MEMORY)),
 			terminate_signal,
+			document_traces,
+			document_tracker,
+			delivery,
+			ingestor_options,
+			deduplicator,
 		}
 	}
 
@@ -132,7 +161,7 @@ impl Handler<CollectionBatch> for IngestorService {
 
 	async fn handle(
 		&mut self,
-		message: CollectionBatch,
+		mut message: CollectionBatch,
 		_ctx: &ActorContext<Self>,
 	) -> Result<Self::Reply, ActorExitStatus> {
 		if MAX_DATA_SIZE_IN_MEMORY.get().is_some() &&
@@ -153,9 +182,36 @@ impl Handler<CollectionBatch> for IngestorService {
 		};
 
 		self.workflow_handles.retain(|handle| !handle.is_finished());
-		let file_ingestor = resolve_ingestor_with_extension(&message.ext).await.map_err(|e| {
-			ActorExitStatus::Failure(anyhow::anyhow!("Failed to resolve ingestor: {}", e).into())
-		})?;
+		let content_type = message.bytes.first().and_then(|bytes| bytes.content_type.clone());
+		let head = match message.bytes.first_mut() {
+			Some(bytes) => peek_head(bytes).await.unwrap_or_else(|e| {
+				error!("Failed to read the start of {}: {}", message.file, e);
+				Vec::new()
+			}),
+			None => Vec::new(),
+		};
+		let (file_type, file_ingestor) =
+			resolve_ingestor(&head, content_type.as_deref(), &message.ext).await.map_err(|e| {
+				ActorExitStatus::Failure(
+					anyhow::anyhow!("Failed to resolve ingestor: {}", e).into(),
+				)
+			})?;
+		let source_id =
+			message.bytes.first().map(|bytes| bytes.source_id.clone()).unwrap_or_default();
+		let file_ingestor = self.ingestor_options.configure(&file_type, &source_id, file_ingestor);
+		self.document_tracker.set_file_type(&message.file, &file_type);
+		if !file_ingestor.is_supported() {
+			self.document_tracker
+				.record(
+					&message.file,
+					&source_id,
+					DocumentStatus::Unsupported,
+					Some(format!("No ingestor for file type `{}`", file_type)),
+				)
+				.await;
+			self.delivery.read(&message.file, &source_id);
+			return Ok(Ok(None));
+		}
 
 		let token_sender = self.get_token_sender();
 		if token_sender.is_closed() {
@@ -173,7 +229,17 @@ impl Handler<CollectionBatch> for IngestorService {
 			self.counters.get_current_memory_usage() + total_bytes as u64,
 		);
 		let term_sig = self.terminate_signal.clone();
-		let handle = tokio::spawn(async move {
+		let document_tracker = self.document_tracker.clone();
+		let delivery = self.delivery.clone();
+		let redaction_audit = self.ingestor_options.redaction_audit.clone();
+		let deduplicator = self.deduplicator.clone();
+		let ingest_span =
+			info_span!("ingest_document", file = %message.file, file_type = %file_type);
+		message.trace_context.attach_to(&ingest_span);
+		self.document_traces.insert(&message.file, TraceContext::from_span(&ingest_span));
+		let ingest_task = async move {
+			// Only time spent inside the ingestor counts as parsing, not waiting on the engine.
+			let mut parse_started = Instant::now();
 			let ingested_token_stream = file_ingestor.ingest(message.bytes).await;
 			match ingested_token_stream {
 				Ok(mut ingested_tokens_stream) => {
@@ -183,37 +249,185 @@ impl Handler<CollectionBatch> for IngestorService {
 					let _permit = message._permit.unwrap();
 					let _permit_workflow = local_workflow_permit;
 
+					let mut parse_duration = Duration::ZERO;
+					let mut has_content = false;
+					let mut last_error = None;
+					let mut fingerprint =
+						deduplicator.as_ref().map(|dedup| dedup.start_document(&message.file));
+					// Sent once the document is known not to duplicate one read before.
+					let mut hold_back =
+						deduplicator.as_ref().is_some_and(|dedup| dedup.skips_duplicates());
+					let mut held_back = Vec::new();
+					let mut held_back_bytes = 0;
 					while let Some(ingested_tokens_result) = ingested_tokens_stream.next().await {
+						parse_duration += parse_started.elapsed();
+						parse_started = Instant::now();
 						if term_sig.is_dead() {
 							break;
 						}
 						match ingested_tokens_result {
-							Ok(ingested_tokens) => {
-								if let Err(e) = token_sender.send(ingested_tokens).await {
-									error!("Failed to send IngestedTokens to token_sender with error: {}", e);
+							Ok(mut ingested_tokens) => {
+								has_content |= !ingested_tokens.data.is_empty();
+								let mut linked = Vec::new();
+								if let (Some(dedup), Some(fingerprint)) =
+									(&deduplicator, fingerprint.as_mut())
+								{
+									linked = dedup.filter_chunks(fingerprint, &mut ingested_tokens);
+								}
+								if hold_back {
+									let bytes = text_size(&ingested_tokens);
+									held_back_bytes += bytes;
+									held_back.push(ingested_tokens);
+									counters.set_current_memory_usage(
+										counters.get_current_memory_usage() + bytes as u64,
+									);
+									// too large to wait for: only its duplicate chunks are skipped
+									if held_back_bytes > MAX_HELD_BACK_BYTES {
+										hold_back = false;
+										counters.set_current_memory_usage(
+											counters.get_current_memory_usage() -
+												held_back_bytes as u64,
+										);
+										held_back_bytes = 0;
+										for ingested_tokens in held_back.drain(..) {
+											if !send_tokens(
+												&token_sender,
+												&counters,
+												&delivery,
+												ingested_tokens,
+											)
+											.await
+											{
+												return;
+											}
+										}
+									}
+								} else if !send_tokens(
+									&token_sender,
+									&counters,
+									&delivery,
+									ingested_tokens,
+								)
+								.await
+								{
 									return;
 								}
-								counters.increment_total_ingested_tokens(1);
+								for linked_tokens in linked {
+									if !send_tokens(
+										&token_sender,
+										&counters,
+										&delivery,
+										linked_tokens,
+									)
+									.await
+									{
+										return;
+									}
+								}
 							},
 							Err(e) => {
-								error!("Failed to ingest file for collector_id:{} and file extension: {} with error: {}", collector_id, message.ext, e);
+								PIPELINE_METRICS
+									.ingestor_failures_total
+									.with_label_values([file_type_label(&file_type)])
+									.inc();
+								error!("Failed to ingest file for collector_id:{} and file type: {} with error: {}", collector_id, file_type, e);
+								last_error = Some(e.to_string());
 							},
 						}
+						parse_started = Instant::now();
+					}
+					parse_duration += parse_started.elapsed();
+					PIPELINE_METRICS
+						.ingestor_parse_duration_seconds
+						.with_label_values([file_type_label(&file_type)])
+						.observe(parse_duration.as_secs_f64());
+					// Triples are extracted asynchronously: the engine upgrades the document to
+					// succeeded once it sees one.
+					if !term_sig.is_dead() {
+						document_tracker
+							.set_redactions(&message.file, redaction_audit.take(&message.file));
+						if let (Some(dedup), Some(fingerprint)) = (&deduplicator, fingerprint) {
+							let report = dedup.finish_document(fingerprint).await;
+							if report.duplicate_of.is_none() {
+								for ingested_tokens in held_back {
+									if !send_tokens(
+										&token_sender,
+										&counters,
+										&delivery,
+										ingested_tokens,
+									)
+									.await
+									{
+										return;
+									}
+								}
+							}
+							document_tracker.set_duplicates(
+								&message.file,
+								report.duplicate_of,
+								report.duplicated_from,
+							);
+						}
+						let (status, reason) = match last_error {
+							Some(e) if !has_content => (DocumentStatus::ParseFailed, e),
+							_ => (
+								DocumentStatus::NoTriples,
+								"No triples were extracted from the document".to_string(),
+							),
+						};
+						document_tracker
+							.record(&message.file, &source_id, status, Some(reason))
+							.await;
+						delivery.read(&message.file, &source_id);
 					}
 					// Drop the permits here to release them
 					drop(_permit);
 					drop(_permit_workflow);
 					counters.set_current_memory_usage(
-						counters.get_current_memory_usage() - total_bytes as u64,
+						counters.get_current_memory_usage() - (total_bytes + held_back_bytes) as u64,
 					);
 				},
 				Err(e) => {
-					error!("Failed to ingest file for collector_id:{} and file extension: {} with error: {}", collector_id, message.ext, e);
+					PIPELINE_METRICS
+						.ingestor_failures_total
+						.with_label_values([file_type_label(&file_type)])
+						.inc();
+					error!("Failed to ingest file for collector_id:{} and file type: {} with error: {}", collector_id, file_type, e);
+					document_tracker
+						.record(
+							&message.file,
+							&source_id,
+							DocumentStatus::ParseFailed,
+							Some(e.to_string()),
+						)
+						.await;
+					delivery.read(&message.file, &source_id);
 				},
 			}
-		});
+		};
+		let handle = tokio::spawn(ingest_task.instrument(ingest_span));
 		self.workflow_handles.push(handle);
 		_ctx.record_progress();
 		Ok(Ok(None))
 	}
 }
+
+/// Sends tokens to the engine, returning `false` once it is gone.
+async fn send_tokens(
+	token_sender: &Sender<IngestedTokens>,
+	counters: &IngestorCounters,
+	delivery: &DeliveryTracker,
+	ingested_tokens: IngestedTokens,
+) -> bool {
+	delivery.sent(&ingested_tokens.file, &ingested_tokens.source_id);
+	if let Err(e) = token_sender.send(ingested_tokens).await {
+		error!("Failed to send IngestedTokens to token_sender with error: {}", e);
+		return false;
+	}
+	counters.increment_total_ingested_tokens(1);
+	true
+}
+
+fn text_size(ingested_tokens: &IngestedTokens) -> usize {
+	ingested_tokens.data.iter().map(String::len).sum()
+}
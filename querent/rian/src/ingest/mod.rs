@@ -16,4 +16,5 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
+pub mod dedup;
 pub mod ingestor_service;
@@ -18,7 +18,7 @@
 
 use actors::{Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Healthz, MessageBus};
 use async_trait::async_trait;
-use cluster::Cluster;
+use cluster::{Cluster, SessionKind, SESSION_STATE_RUNNING, SESSION_STATE_STOPPED};
 use common::EventType;
 use insights::{
 	get_insight_info_by_id, get_insight_runner_by_id, CustomInsightOption, InsightConfig,
@@ -35,13 +35,14 @@ use std::{
 };
 use storage::Storage;
 
-use crate::InsightAgent;
+use crate::{metrics::PIPELINE_METRICS, InsightAgent};
 
 #[cfg(feature = "license-check")]
 use crate::is_insight_allowed_by_product;
 
 // TODO Insight Agents rethinking needed
 struct InsightAgentHandles {
+	insight_id: String,
 	mailbox: MessageBus<InsightAgent>,
 	handle: ActorHandle<InsightAgent>,
 }
@@ -91,14 +92,22 @@ impl Actor for InsightAgentService {
 		()
 	}
 }
-#[async_trait]
-impl Handler<InsightAnalystRequest> for InsightAgentService {
-	type Reply = Result<InsightAnalystResponse, InsightError>;
-	async fn handle(
+
+/// Re-creates an insight session from the request that started it, keeping its id. Sent to take
+/// over the sessions of a node which left the cluster; a session already running is kept.
+#[derive(Debug, Clone)]
+pub struct RestoreInsightSession {
+	pub session_id: String,
+	pub request: InsightAnalystRequest,
+}
+
+impl InsightAgentService {
+	async fn start_session(
 		&mut self,
+		session_id: String,
 		request: InsightAnalystRequest,
 		ctx: &ActorContext<Self>,
-	) -> Result<Self::Reply, ActorExitStatus> {
+	) -> Result<Result<InsightAnalystResponse, InsightError>, ActorExitStatus> {
 		#[cfg(feature = "license-check")]
 		{
 			if self._license_key.is_some() {
@@ -112,7 +121,6 @@ impl Handler<InsightAnalystRequest> for InsightAgentService {
 				return Err(anyhow::anyhow!("License Key not provided").into());
 			}
 		};
-		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
 		let current_timestamp = chrono::Utc::now().timestamp();
 		let event_storages = self.event_storages.clone();
 		let index_storages = self.index_storages.clone();
@@ -176,7 +184,7 @@ impl Handler<InsightAnalystRequest> for InsightAgentService {
 			}
 		}
 		let insight_config: InsightConfig = InsightConfig::new(
-			new_uuid.clone(),
+			session_id.clone(),
 			discovery_session_id,
 			semantic_pipeline_id,
 			event_storages.clone(),
@@ -189,16 +197,48 @@ impl Handler<InsightAnalystRequest> for InsightAgentService {
 		}
 		let insight_agent = InsightAgent::new(
 			insight.unwrap(),
-			new_uuid.clone(),
+			session_id.clone(),
 			current_timestamp as u64,
 			insight_config,
 		);
 
 		let (insight_messagebus, insight) = ctx.spawn_actor().spawn(insight_agent);
-		let insight_handle = InsightAgentHandles { mailbox: insight_messagebus, handle: insight };
-		self.agent_pipelines.insert(new_uuid.clone(), insight_handle);
+		let insight_handle =
+			InsightAgentHandles { insight_id, mailbox: insight_messagebus, handle: insight };
+		self.agent_pipelines.insert(session_id.clone(), insight_handle);
+		self.cluster
+			.update_session_state(SessionKind::Insight, &session_id, SESSION_STATE_RUNNING)
+			.await;
+
+		Ok(Ok(InsightAnalystResponse { session_id }))
+	}
+}
+
+#[async_trait]
+impl Handler<InsightAnalystRequest> for InsightAgentService {
+	type Reply = Result<InsightAnalystResponse, InsightError>;
+	async fn handle(
+		&mut self,
+		request: InsightAnalystRequest,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+		self.start_session(new_uuid, request, ctx).await
+	}
+}
 
-		Ok(Ok(InsightAnalystResponse { session_id: new_uuid }))
+#[async_trait]
+impl Handler<RestoreInsightSession> for InsightAgentService {
+	type Reply = Result<InsightAnalystResponse, InsightError>;
+	async fn handle(
+		&mut self,
+		restore: RestoreInsightSession,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		if self.agent_pipelines.contains_key(&restore.session_id) {
+			return Ok(Ok(InsightAnalystResponse { session_id: restore.session_id }));
+		}
+		self.start_session(restore.session_id, restore.request, ctx).await
 	}
 }
 
@@ -227,6 +267,13 @@ impl Handler<StopInsightSessionRequest> for InsightAgentService {
 		let agent_handle = self.agent_pipelines.remove(&request.session_id);
 		if let Some(agent_handle) = agent_handle {
 			let _ = agent_handle.handle.kill().await;
+			self.cluster
+				.update_session_state(
+					SessionKind::Insight,
+					&request.session_id,
+					SESSION_STATE_STOPPED,
+				)
+				.await;
 			return Ok(Ok(StopInsightSessionResponse { session_id: request.session_id }));
 		}
 
@@ -245,6 +292,10 @@ impl Handler<InsightQuery> for InsightAgentService {
 	) -> Result<Result<InsightQueryResponse, InsightError>, ActorExitStatus> {
 		let agent_handle = self.agent_pipelines.get(&request.session_id);
 		if let Some(agent_handle) = agent_handle {
+			let _query_timer = PIPELINE_METRICS
+				.insight_query_duration_seconds
+				.with_label_values([&agent_handle.insight_id])
+				.start_timer();
 			let response = agent_handle
 				.mailbox
 				.ask(request)
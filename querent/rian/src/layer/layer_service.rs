@@ -21,7 +21,7 @@ use crate::is_layer_agent_type_allowed;
 use crate::layer_link_prediction::LayerLink;
 use actors::{Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Healthz, MessageBus};
 use async_trait::async_trait;
-use cluster::Cluster;
+use cluster::{Cluster, SessionKind, SESSION_STATE_RUNNING, SESSION_STATE_STOPPED};
 use common::EventType;
 use proto::layer::{
 	LayerAgentType, LayerError, LayerRequest, LayerResponse, LayerSessionRequest,
@@ -77,15 +77,22 @@ impl Actor for LayerAgentService {
 		()
 	}
 }
-#[async_trait]
-impl Handler<LayerSessionRequest> for LayerAgentService {
-	type Reply = Result<LayerSessionResponse, LayerError>;
-	async fn handle(
+
+/// Re-creates a layer session from the request that started it, keeping its id. Sent to take over
+/// the sessions of a node which left the cluster; a session already running is kept.
+#[derive(Debug, Clone)]
+pub struct RestoreLayerSession {
+	pub session_id: String,
+	pub request: LayerSessionRequest,
+}
+
+impl LayerAgentService {
+	async fn start_session(
 		&mut self,
+		session_id: String,
 		request: LayerSessionRequest,
 		ctx: &ActorContext<Self>,
-	) -> Result<Self::Reply, ActorExitStatus> {
-		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+	) -> Result<Result<LayerSessionResponse, LayerError>, ActorExitStatus> {
 		let current_timestamp = chrono::Utc::now().timestamp();
 		let event_storages = self.event_storages.clone();
 		#[cfg(feature = "license-check")]
@@ -106,7 +113,7 @@ impl Handler<LayerSessionRequest> for LayerAgentService {
 		match request.session_type.clone().unwrap_or(LayerAgentType::Link) {
 			LayerAgentType::Link => {
 				let search = LayerLink::new(
-					new_uuid.clone(),
+					session_id.clone(),
 					current_timestamp as u64,
 					event_storages.clone(),
 					request.clone(),
@@ -114,11 +121,42 @@ impl Handler<LayerSessionRequest> for LayerAgentService {
 
 				let (search_messagebus, search) = ctx.spawn_actor().spawn(search);
 				let search_handle = LayerLinkHandle { mailbox: search_messagebus, handle: search };
-				self.searcher_pipelines.insert(new_uuid.clone(), search_handle);
+				self.searcher_pipelines.insert(session_id.clone(), search_handle);
 			},
 		}
+		self.cluster
+			.update_session_state(SessionKind::Layer, &session_id, SESSION_STATE_RUNNING)
+			.await;
+
+		Ok(Ok(LayerSessionResponse { session_id }))
+	}
+}
+
+#[async_trait]
+impl Handler<LayerSessionRequest> for LayerAgentService {
+	type Reply = Result<LayerSessionResponse, LayerError>;
+	async fn handle(
+		&mut self,
+		request: LayerSessionRequest,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		let new_uuid = uuid::Uuid::new_v4().to_string().replace("-", "");
+		self.start_session(new_uuid, request, ctx).await
+	}
+}
 
-		Ok(Ok(LayerSessionResponse { session_id: new_uuid }))
+#[async_trait]
+impl Handler<RestoreLayerSession> for LayerAgentService {
+	type Reply = Result<LayerSessionResponse, LayerError>;
+	async fn handle(
+		&mut self,
+		restore: RestoreLayerSession,
+		ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		if self.searcher_pipelines.contains_key(&restore.session_id) {
+			return Ok(Ok(LayerSessionResponse { session_id: restore.session_id }));
+		}
+		self.start_session(restore.session_id, restore.request, ctx).await
 	}
 }
 
@@ -147,6 +185,13 @@ impl Handler<StopLayerSessionRequest> for LayerAgentService {
 		let search_handle = self.searcher_pipelines.remove(&request.session_id);
 		if let Some(search_handle) = search_handle {
 			let _ = search_handle.handle.kill().await;
+			self.cluster
+				.update_session_state(
+					SessionKind::Layer,
+					&request.session_id,
+					SESSION_STATE_STOPPED,
+				)
+				.await;
 			return Ok(Ok(StopLayerSessionResponse { session_id: request.session_id }));
 		}
 
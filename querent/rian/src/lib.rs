@@ -39,7 +39,12 @@ pub mod insights;
 pub use insights::*;
 pub mod layer;
 pub use layer::*;
+pub mod import;
 pub mod ingest;
+pub mod metrics;
+pub use import::*;
+pub mod resolution;
+pub use resolution::*;
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "license-check")]
 use sp_runtime::MultiSignature;
@@ -70,8 +75,13 @@ pub async fn start_semantic_service(
 pub async fn create_dynamic_sources(
 	licence_key: Option<String>,
 	collectors_configs: Vec<CollectorConfig>,
+	metadata_store: Option<Arc<dyn storage::MetaStorage>>,
 ) -> Result<Vec<Arc<dyn sources::DataSource>>, PipelineErrors> {
 	let mut sources: Vec<Arc<dyn sources::DataSource>> = vec![];
+	// Incremental sources resume from the checkpoint their previous run saved.
+	let checkpoint_store = metadata_store.map(|metadata_store| {
+		Arc::new(MetaCheckpointStore::new(metadata_store)) as Arc<dyn sources::CheckpointStore>
+	});
 	let licence_key = licence_key.unwrap_or_default();
 	if licence_key.is_empty() {
 		log::warn!("Missing License Key");
@@ -197,6 +207,10 @@ pub async fn create_dynamic_sources(
 
 				match sources::notion::notion::NotionSource::new(config.clone()).await {
 					Ok(notion_source) => {
+						let notion_source = match &checkpoint_store {
+							Some(store) => notion_source.with_checkpoint_store(store.clone()),
+							None => notion_source,
+						};
 						sources.push(Arc::new(notion_source) as Arc<dyn sources::DataSource>);
 					},
 					Err(e) => {
@@ -219,6 +233,10 @@ pub async fn create_dynamic_sources(
 
 				match sources::slack::slack::SlackApiClient::new(config.clone()).await {
 					Ok(slack_source) => {
+						let slack_source = match &checkpoint_store {
+							Some(store) => slack_source.with_checkpoint_store(store.clone()),
+							None => slack_source,
+						};
 						sources.push(Arc::new(slack_source) as Arc<dyn sources::DataSource>);
 					},
 					Err(e) => {
@@ -294,6 +312,119 @@ pub async fn create_dynamic_sources(
 					},
 				}
 			},
+			Some(proto::semantics::Backend::Confluence(config)) => {
+				#[cfg(feature = "license-check")]
+				if !is_data_source_allowed_by_product(licence_key.clone(), &collector).unwrap() {
+					return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+						"Data source not allowed by product: {}",
+						collector.name.clone(),
+					)));
+				}
+
+				match sources::confluence::confluence::ConfluenceSource::new(config.clone()).await {
+					Ok(confluence_source) => {
+						sources.push(Arc::new(confluence_source) as Arc<dyn sources::DataSource>);
+					},
+					Err(e) => {
+						return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+							"Failed to initialize confluence source: {:?} ",
+							e
+						)));
+					},
+				}
+			},
+			Some(proto::semantics::Backend::Sharepoint(config)) => {
+				#[cfg(feature = "license-check")]
+				if !is_data_source_allowed_by_product(licence_key.clone(), &collector).unwrap() {
+					return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+						"Data source not allowed by product: {}",
+						collector.name.clone(),
+					)));
+				}
+
+				match sources::sharepoint::sharepoint::SharePointSource::new(config.clone()).await {
+					Ok(sharepoint_source) => {
+						sources.push(Arc::new(sharepoint_source) as Arc<dyn sources::DataSource>);
+					},
+					Err(e) => {
+						return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+							"Failed to initialize sharepoint source: {:?} ",
+							e
+						)));
+					},
+				}
+			},
+			Some(proto::semantics::Backend::Sql(config)) => {
+				#[cfg(feature = "license-check")]
+				if !is_data_source_allowed_by_product(licence_key.clone(), &collector).unwrap() {
+					return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+						"Data source not allowed by product: {}",
+						collector.name.clone(),
+					)));
+				}
+
+				match sources::sql::sql::SqlSource::new(config.clone()).await {
+					Ok(sql_source) => {
+						let sql_source = match &checkpoint_store {
+							Some(store) => sql_source.with_checkpoint_store(store.clone()),
+							None => sql_source,
+						};
+						sources.push(Arc::new(sql_source) as Arc<dyn sources::DataSource>);
+					},
+					Err(e) => {
+						return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+							"Failed to initialize sql source: {:?} ",
+							e
+						)));
+					},
+				}
+			},
+			Some(proto::semantics::Backend::Web(config)) => {
+				#[cfg(feature = "license-check")]
+				if !is_data_source_allowed_by_product(licence_key.clone(), &collector).unwrap() {
+					return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+						"Data source not allowed by product: {}",
+						collector.name.clone(),
+					)));
+				}
+
+				match sources::web::crawler::WebCrawlerSource::new(config.clone()).await {
+					Ok(web_source) => {
+						sources.push(Arc::new(web_source) as Arc<dyn sources::DataSource>);
+					},
+					Err(e) => {
+						return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+							"Failed to initialize web crawler source: {:?} ",
+							e
+						)));
+					},
+				}
+			},
+			Some(proto::semantics::Backend::Kafka(config)) => {
+				#[cfg(feature = "license-check")]
+				if !is_data_source_allowed_by_product(licence_key.clone(), &collector).unwrap() {
+					return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+						"Data source not allowed by product: {}",
+						collector.name.clone(),
+					)));
+				}
+
+				match sources::kafka::kafka::KafkaSource::new(config.clone()).await {
+					Ok(kafka_source) => {
+						let kafka_source = match &checkpoint_store {
+							Some(store) => kafka_source.with_checkpoint_store(store.clone()),
+							None => kafka_source,
+						};
+						sources.push(Arc::new(kafka_source) as Arc<dyn sources::DataSource>);
+					},
+					Err(e) => {
+						return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
+							"Failed to initialize kafka source: {:?} ",
+							e
+						)));
+					},
+				}
+			},
 			_ =>
 				return Err(PipelineErrors::InvalidParams(anyhow::anyhow!(
 					"Invalid source type: {}",
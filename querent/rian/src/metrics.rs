@@ -0,0 +1,128 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use common::metrics::{counter_vec, histogram_vec, HistogramVec, IntCounterVec};
+use ingestors::PROGRAMMING_LANGUAGE_EXTENSIONS;
+use once_cell::sync::Lazy;
+
+pub struct PipelineMetrics {
+	pub collector_bytes_total: IntCounterVec<1>,
+	pub collector_documents_total: IntCounterVec<1>,
+	pub ingestor_parse_duration_seconds: HistogramVec<1>,
+	pub ingestor_failures_total: IntCounterVec<1>,
+	pub storage_write_duration_seconds: HistogramVec<2>,
+	pub storage_write_errors_total: IntCounterVec<2>,
+	pub discovery_query_duration_seconds: HistogramVec<1>,
+	pub insight_query_duration_seconds: HistogramVec<1>,
+}
+
+impl Default for PipelineMetrics {
+	fn default() -> Self {
+		PipelineMetrics {
+			collector_bytes_total: counter_vec(
+				"collector_bytes_total",
+				"Number of bytes polled from a data source.",
+				"pipeline",
+				&[],
+				["collector"],
+			),
+			collector_documents_total: counter_vec(
+				"collector_documents_total",
+				"Number of documents polled from a data source.",
+				"pipeline",
+				&[],
+				["collector"],
+			),
+			ingestor_parse_duration_seconds: histogram_vec(
+				"ingestor_parse_duration_seconds",
+				"Time taken to parse a document into tokens.",
+				"pipeline",
+				&[],
+				["extension"],
+			),
+			ingestor_failures_total: counter_vec(
+				"ingestor_failures_total",
+				"Number of documents or document parts that failed to parse.",
+				"pipeline",
+				&[],
+				["extension"],
+			),
+			storage_write_duration_seconds: histogram_vec(
+				"storage_write_duration_seconds",
+				"Time taken to write a batch of events to a storage backend.",
+				"pipeline",
+				&[],
+				["backend", "kind"],
+			),
+			storage_write_errors_total: counter_vec(
+				"storage_write_errors_total",
+				"Number of failed writes to a storage backend.",
+				"pipeline",
+				&[],
+				["backend", "kind"],
+			),
+			discovery_query_duration_seconds: histogram_vec(
+				"query_duration_seconds",
+				"Time taken to answer a discovery query.",
+				"discovery",
+				&[],
+				["agent"],
+			),
+			insight_query_duration_seconds: histogram_vec(
+				"query_duration_seconds",
+				"Time taken to answer an insight query.",
+				"insights",
+				&[],
+				["insight"],
+			),
+		}
+	}
+}
+
+pub static PIPELINE_METRICS: Lazy<PipelineMetrics> = Lazy::new(PipelineMetrics::default);
+
+/// Label of the ingestor metrics for a file type. Source code and OSDU records are grouped and
+/// types without an ingestor of their own count as `other`, so that the extensions of the
+/// documents read cannot add series without bound.
+pub fn file_type_label(file_type: &str) -> &str {
+	match file_type {
+		"pdf" | "html" | "confluence" | "webpage" | "csv" | "xml" | "docx" | "doc" | "jpeg" |
+		"jpg" | "png" | "json" | "pptx" | "odp" | "xlsx" | "bmp" | "gif" | "hdr" | "ico" |
+		"exr" | "pnm" | "qoi" | "tiff" | "webp" | "ff" | "dds" | "eml" | "mbox" | "msg" |
+		"md" | "markdown" | "ipynb" | "epub" | "rtf" | "odt" | "ods" | "tex" | "latex" |
+		"news" | "email" | "notion" | "txt" | "slack" | "jira" | "text" | "sharepoint" |
+		"sqlrow" | "message" => file_type,
+		_ if PROGRAMMING_LANGUAGE_EXTENSIONS.contains(&file_type) => "code",
+		_ if file_type.contains("osdu_") => "osdu",
+		_ => "other",
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::file_type_label;
+
+	#[test]
+	fn test_file_type_label() {
+		assert_eq!(file_type_label("pdf"), "pdf");
+		assert_eq!(file_type_label("rs"), "code");
+		assert_eq!(file_type_label("osdu_wellbore"), "osdu");
+		assert_eq!(file_type_label("dat-7f3a"), "other");
+		assert_eq!(file_type_label(""), "other");
+	}
+}
@@ -17,20 +17,34 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use crate::{
-	indexer::Indexer, ingest::ingestor_service::IngestorService, Collector, EngineRunner,
-	EventStreamer, SourceActor, StorageMapper,
+	indexer::Indexer,
+	ingest::{dedup::Deduplicator, ingestor_service::IngestorService},
+	Collector, DeliveryTracker, DocumentTracker, EngineRunner, EntityResolver, EventStreamer,
+	SourceActor, StorageMapper, EMIT_BATCHES_TIMEOUT, NUMBER_FILES_IN_MEMORY,
 };
 use actors::{
-	Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, QueueCapacity,
+	Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, MessageBus, QueueCapacity,
 	Supervisable, HEARTBEAT,
 };
 use async_trait::async_trait;
-use common::{EventType, PubSubBroker, TerimateSignal};
+use cluster::KeyRange;
+use common::{
+	CollectedBytes, CollectionBatch, DocumentTraces, EventType, PubSubBroker, TerimateSignal,
+};
 use engines::Engine;
-use proto::semantics::{IndexingStatistics, IngestedTokens};
+use ingestors::IngestorOptions;
+use proto::semantics::{
+	DocumentStatus, IndexingStatistics, IngestedTokens, RetryDocumentsResponse,
+};
 use sources::DataSource;
-use std::{collections::HashMap, sync::Arc, time::Duration};
-use storage::{SecretStorage, Storage};
+use std::{
+	collections::HashMap,
+	io::Cursor,
+	path::{Path, PathBuf},
+	sync::Arc,
+	time::Duration,
+};
+use storage::{MetaStorage, SecretStorage, Storage};
 use tokio::{
 	sync::{mpsc, Semaphore},
 	time::Instant,
@@ -62,6 +76,11 @@ pub struct PipelineSettings {
 	pub index_storages: Vec<Arc<dyn Storage>>,
 	pub secret_store: Arc<dyn SecretStorage>,
 	pub engine: Arc<dyn Engine>,
+	pub entity_resolver: Option<Arc<EntityResolver>>,
+	pub metadata_store: Arc<dyn MetaStorage>,
+	pub ingestor_options: IngestorOptions,
+	pub deduplicator: Option<Arc<Deduplicator>>,
+	pub key_range: Option<KeyRange>,
 }
 
 struct PipelineHandlers {
@@ -71,6 +90,7 @@ struct PipelineHandlers {
 	pub storage_mapper_handler: ActorHandle<StorageMapper>,
 	pub collection_handlers: Vec<ActorHandle<SourceActor>>,
 	pub ingestor_handler: ActorHandle<IngestorService>,
+	pub ingestor_mailbox: MessageBus<IngestorService>,
 	pub next_progress_check: Instant,
 }
 
@@ -97,6 +117,15 @@ pub struct SemanticPipeline {
 	pub event_storages: HashMap<EventType, Vec<Arc<dyn Storage>>>,
 	// Index storages
 	pub index_storages: Vec<Arc<dyn Storage>>,
+	// Resolves extracted entity mentions to canonical ids.
+	pub entity_resolver: Option<Arc<EntityResolver>>,
+	// PDF passwords and OCR backend of the ingestors.
+	pub ingestor_options: IngestorOptions,
+	// Skips or links the chunks and documents read before.
+	pub deduplicator: Option<Arc<Deduplicator>>,
+	// Keys of the documents collected, when the pipeline is a shard of a pipeline split across
+	// nodes.
+	pub key_range: Option<KeyRange>,
 	// terimatesignal to kill actors in the pipeline.
 	pub terminate_sig: TerimateSignal,
 	// Statistics about the event processing system.
@@ -105,6 +134,10 @@ pub struct SemanticPipeline {
 	handlers: Option<PipelineHandlers>,
 	// pubsub broker
 	pub pubsub_broker: PubSubBroker,
+	// Outcome of every document the pipeline processed.
+	pub document_tracker: DocumentTracker,
+	// Memory permits of the documents being retried.
+	retry_semaphore: Arc<Semaphore>,
 	retry_count: usize,
 }
 
@@ -115,14 +148,25 @@ impl SemanticPipeline {
 		data_sources: Vec<Arc<dyn DataSource>>,
 		event_storages: HashMap<EventType, Vec<Arc<dyn Storage>>>,
 		index_storages: Vec<Arc<dyn Storage>>,
+		entity_resolver: Option<Arc<EntityResolver>>,
 		pubsub_broker: PubSubBroker,
+		metadata_store: Option<Arc<dyn MetaStorage>>,
+		ingestor_options: IngestorOptions,
+		deduplicator: Option<Arc<Deduplicator>>,
+		key_range: Option<KeyRange>,
 	) -> Self {
 		Self {
+			document_tracker: DocumentTracker::new(id.clone(), metadata_store),
+			retry_semaphore: Arc::new(Semaphore::new(NUMBER_FILES_IN_MEMORY)),
 			id,
 			engine,
 			data_sources,
 			event_storages,
 			index_storages,
+			entity_resolver,
+			ingestor_options,
+			deduplicator,
+			key_range,
 			terminate_sig: TerimateSignal::default(),
 			statistics: IndexingStatistics::default(),
 			handlers: None,
@@ -242,6 +286,8 @@ impl SemanticPipeline {
 		);
 
 		let current_timestamp = chrono::Utc::now().timestamp_millis() as u64;
+		// Acknowledges the documents of the sources that wait for their knowledge to be stored.
+		let delivery = DeliveryTracker::new(&self.data_sources);
 
 		// Storage mapper actor
 		let storage_mapper =
@@ -258,11 +304,17 @@ impl SemanticPipeline {
 			ctx.spawn_actor().set_terminate_sig(self.terminate_sig.clone()).spawn(indexer);
 
 		// Ingestor actor
+		let document_traces = DocumentTraces::default();
 		let ingestor_service = IngestorService::new(
 			engine_id.clone(),
 			token_sender.clone(),
 			current_timestamp,
 			self.terminate_sig.clone(),
+			document_traces.clone(),
+			self.document_tracker.clone(),
+			delivery.clone(),
+			self.ingestor_options.clone(),
+			self.deduplicator.clone(),
 		);
 
 		let (ingestor_mailbox, ingestor_inbox) = ctx
@@ -275,6 +327,8 @@ impl SemanticPipeline {
 			storage_mapper_mailbox,
 			indexer_messagebus,
 			ingestor_mailbox.clone(),
+			self.entity_resolver.clone(),
+			delivery.clone(),
 			current_timestamp,
 		);
 		let (event_streamer_messagebus, event_streamer_inbox) = ctx
@@ -288,6 +342,8 @@ impl SemanticPipeline {
 			engine_id.clone(),
 			self.data_sources.clone(),
 			self.terminate_sig.clone(),
+			self.document_tracker.clone(),
+			self.key_range,
 		);
 
 		let (collector_message_bus, collector_inbox) = ctx
@@ -319,6 +375,9 @@ impl SemanticPipeline {
 			self.engine.clone(),
 			token_receiver,
 			self.terminate_sig.clone(),
+			document_traces,
+			self.document_tracker.clone(),
+			delivery,
 		);
 		let (engine_message_bus, engine_inbox) = ctx
 			.spawn_ctx()
@@ -326,7 +385,7 @@ impl SemanticPipeline {
 		let engine_source_actor = SourceActor {
 			source: Box::new(engine_source),
 			event_streamer_messagebus,
-			ingestor_messagebus: ingestor_mailbox,
+			ingestor_messagebus: ingestor_mailbox.clone(),
 		};
 		let (_, engine_handle) = ctx
 			.spawn_actor()
@@ -340,6 +399,7 @@ impl SemanticPipeline {
 			storage_mapper_handler: storage_mapper_inbox,
 			next_progress_check: Instant::now() + *HEARTBEAT,
 			ingestor_handler: ingestor_inbox,
+			ingestor_mailbox,
 			collection_handlers,
 		});
 		Ok(())
@@ -407,6 +467,13 @@ impl Actor for SemanticPipeline {
 	}
 
 	async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+		self.document_tracker.load().await;
+		if let Some(deduplicator) = &self.deduplicator {
+			deduplicator.load().await;
+		}
+		if let Some(entity_resolver) = &self.entity_resolver {
+			entity_resolver.load().await;
+		}
 		self.handle(Trigger::default(), ctx).await?;
 		self.handle(ControlLoop, ctx).await?;
 		Ok(())
@@ -500,3 +567,128 @@ impl Handler<IngestedTokens> for SemanticPipeline {
 		Ok(())
 	}
 }
+
+#[derive(Clone, Debug)]
+pub struct RetryDocuments {
+	pub pipeline_id: String,
+	// Retries every failed document when empty.
+	pub documents: Vec<String>,
+}
+
+#[async_trait]
+impl Handler<RetryDocuments> for SemanticPipeline {
+	type Reply = RetryDocumentsResponse;
+	async fn handle(
+		&mut self,
+		message: RetryDocuments,
+		_ctx: &ActorContext<Self>,
+	) -> Result<RetryDocumentsResponse, ActorExitStatus> {
+		let mut response = RetryDocumentsResponse::default();
+		let Some(handles) = &self.handlers else {
+			return Ok(response);
+		};
+		let documents = if message.documents.is_empty() {
+			self.document_tracker.failed_documents()
+		} else {
+			message.documents
+		};
+		for document in documents {
+			let Some(state) = self.document_tracker.get(&document) else {
+				continue;
+			};
+			if state.status() == DocumentStatus::Succeeded {
+				continue;
+			}
+			// Only the source that collected the document can fetch it again.
+			let data_source = self
+				.data_sources
+				.iter()
+				.find(|data_source| data_source.source_id() == state.source_id);
+			let data_source = match data_source {
+				Some(data_source) if data_source.can_fetch_document() => data_source.clone(),
+				Some(_) => {
+					let reason = format!(
+						"Source {} cannot fetch a single document again, it is not retryable",
+						state.source_id
+					);
+					response.not_retried.insert(document, reason);
+					continue;
+				},
+				None => {
+					let reason = format!("Source {} is not part of the pipeline", state.source_id);
+					response.not_retried.insert(document, reason);
+					continue;
+				},
+			};
+			let Some(state) = self.document_tracker.begin_retry(&document) else {
+				continue;
+			};
+			let retry = retry_document(
+				state.document,
+				state.source_id,
+				data_source,
+				handles.ingestor_mailbox.clone(),
+				self.document_tracker.clone(),
+				self.retry_semaphore.clone(),
+			);
+			tokio::spawn(retry);
+			response.retried.push(document);
+		}
+		Ok(response)
+	}
+}
+
+/// Fetches a document again from the source that collected it and hands it to the ingestor,
+/// as the collector would have.
+async fn retry_document(
+	document: String,
+	source_id: String,
+	data_source: Arc<dyn DataSource>,
+	ingestor_mailbox: MessageBus<IngestorService>,
+	document_tracker: DocumentTracker,
+	retry_semaphore: Arc<Semaphore>,
+) {
+	let content = match data_source.get_all(Path::new(&document)).await {
+		Ok(content) => content,
+		Err(e) => {
+			document_tracker
+				.record(&document, &source_id, DocumentStatus::FetchFailed, Some(e.to_string()))
+				.await;
+			return;
+		},
+	};
+	let Ok(permit) = retry_semaphore.acquire_owned().await else {
+		return;
+	};
+	let size = content.len();
+	let collected_bytes = CollectedBytes::new(
+		Some(PathBuf::from(&document)),
+		Some(Box::pin(Cursor::new(content))),
+		true,
+		Some(document.clone()),
+		Some(size),
+		source_id,
+		None,
+	);
+	let extension = collected_bytes.extension.clone().unwrap_or_default();
+	let mut batch =
+		CollectionBatch::new(&document, &extension, vec![collected_bytes], Some(permit));
+	// The ingestor hands the batch back while it is at capacity.
+	loop {
+		match ingestor_mailbox.ask(batch).await {
+			Ok(Ok(Some(returned_batch))) => {
+				batch = returned_batch;
+				tokio::time::sleep(EMIT_BATCHES_TIMEOUT).await;
+			},
+			Ok(Ok(None)) => return,
+			Ok(Err(e)) => {
+				error!("Failed to retry document {}: {:?}", document, e);
+				return;
+			},
+			Err(e) => {
+				error!("Failed to retry document {}: {:?}", document, e);
+				return;
+			},
+		}
+	}
+}
@@ -16,16 +16,20 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use crate::{PipelineErrors, PipelineSettings, SemanticPipeline, ShutdownPipe};
+use crate::{PipelineErrors, PipelineSettings, RetryDocuments, SemanticPipeline, ShutdownPipe};
 use actors::{
 	Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Healthz, MessageBus,
 	Observation, HEARTBEAT,
 };
 use async_trait::async_trait;
-use cluster::Cluster;
+use cluster::{
+	placement::{PIPELINE_STATE_FINISHED, PIPELINE_STATE_RUNNING},
+	Cluster,
+};
 use common::PubSubBroker;
 use proto::semantics::{
-	EmptyGetPipelinesMetadata, IndexingStatistics, PipelineMetadata, SendIngestedTokens,
+	EmptyGetPipelinesMetadata, IndexingStatistics, PipelineMetadata, RetryDocumentsResponse,
+	SendIngestedTokens,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -90,6 +94,7 @@ impl SemanticService {
 	}
 
 	async fn self_supervise(&mut self) -> Result<(), ActorExitStatus> {
+		let mut exited_pipelines = Vec::new();
 		self.semantic_pipelines.retain(|qflow_id, pipeline_handle| {
 			match pipeline_handle.handle.state() {
 				ActorState::Idle | ActorState::Paused | ActorState::Processing => true,
@@ -100,6 +105,7 @@ impl SemanticService {
 					);
 					self.counters.num_successful_pipelines += 1;
 					self.counters.num_running_pipelines -= 1;
+					exited_pipelines.push(qflow_id.clone());
 					false
 				},
 				ActorState::Failure => {
@@ -111,10 +117,15 @@ impl SemanticService {
 					);
 					self.counters.num_failed_pipelines += 1;
 					self.counters.num_running_pipelines -= 1;
+					exited_pipelines.push(qflow_id.clone());
 					false
 				},
 			}
 		});
+		// Pipelines that ran to their end are not placed again on another node.
+		for pipeline_id in exited_pipelines {
+			self.gossip_pipeline_state(&pipeline_id, PIPELINE_STATE_FINISHED).await;
+		}
 		let pipeline_metrics: HashMap<&String, IndexingStatistics> = self
 			.semantic_pipelines
 			.values()
@@ -128,6 +139,12 @@ impl SemanticService {
 		Ok(())
 	}
 
+	async fn gossip_pipeline_state(&self, pipeline_id: &str, state: &str) {
+		if let Err(error) = self.cluster.update_semantic_pipeline_state(pipeline_id, state).await {
+			error!(pipeline_id=%pipeline_id, error=?error, "Failed to gossip pipeline state.");
+		}
+	}
+
 	async fn observe_pipeline(
 		&mut self,
 		pipeline_id: String,
@@ -197,7 +214,12 @@ impl SemanticService {
 			settings.data_sources.clone(),
 			settings.event_storages.clone(),
 			settings.index_storages.clone(),
+			settings.entity_resolver.clone(),
 			self.pubsub_broker.clone(),
+			Some(settings.metadata_store.clone()),
+			settings.ingestor_options.clone(),
+			settings.deduplicator.clone(),
+			settings.key_range,
 		);
 
 		let (pipeline_mailbox, pipeline_handle) = ctx.spawn_actor().spawn(semantic_pipe);
@@ -207,6 +229,7 @@ impl SemanticService {
 			settings,
 			pipeline_id: pipeline_id.clone(),
 		};
+		self.gossip_pipeline_state(&pipeline_id, PIPELINE_STATE_RUNNING).await;
 		self.semantic_pipelines.insert(pipeline_id, pipeline_handle);
 		self.counters.num_running_pipelines += 1;
 		Ok(())
@@ -355,6 +378,25 @@ impl Handler<SendIngestedTokens> for SemanticService {
 	}
 }
 
+#[async_trait]
+impl Handler<RetryDocuments> for SemanticService {
+	type Reply = Result<RetryDocumentsResponse, PipelineErrors>;
+
+	async fn handle(
+		&mut self,
+		message: RetryDocuments,
+		_ctx: &ActorContext<Self>,
+	) -> Result<Self::Reply, ActorExitStatus> {
+		let Some(pipeline_handle) = self.semantic_pipelines.get(&message.pipeline_id) else {
+			return Ok(Err(PipelineErrors::PipelineNotFound { pipeline_id: message.pipeline_id }));
+		};
+		match pipeline_handle.mailbox.ask(message).await {
+			Ok(response) => Ok(Ok(response)),
+			Err(e) => Ok(Err(PipelineErrors::UnknownError(e.to_string()))),
+		}
+	}
+}
+
 #[async_trait]
 impl Handler<EmptyGetPipelinesMetadata> for SemanticService {
 	type Reply = Vec<PipelineMetadata>;
@@ -0,0 +1,651 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::HashMap,
+	fmt,
+	sync::{Arc, Mutex},
+};
+
+use common::{EventState, EventType, SemanticKnowledgePayload};
+use fastembed::TextEmbedding;
+use proto::semantics::EntityResolutionConfig;
+use storage::MetaStorage;
+use tracing::error;
+
+const DEFAULT_STRING_SIMILARITY_THRESHOLD: f64 = 0.93;
+const DEFAULT_EMBEDDING_SIMILARITY_THRESHOLD: f32 = 0.9;
+/// Fuzzy string matching on very short keys merges unrelated entities ("ion" / "iot").
+const MIN_FUZZY_KEY_LEN: usize = 4;
+const LEGAL_SUFFIXES: &[&str] = &[
+	"inc",
+	"corp",
+	"corporation",
+	"co",
+	"company",
+	"ltd",
+	"limited",
+	"llc",
+	"plc",
+	"gmbh",
+	"ag",
+	"sa",
+];
+
+/// A group of surface forms that refer to the same entity.
+struct EntityCluster {
+	id: String,
+	key: String,
+	embedding: Option<Vec<f32>>,
+}
+
+#[derive(Default)]
+struct ResolverState {
+	clusters: Vec<EntityCluster>,
+	/// Every key resolved so far, including keys that joined an existing cluster.
+	by_key: HashMap<String, usize>,
+	/// Acronyms of multi-word cluster keys ("ibm" for "international business machines").
+	by_acronym: HashMap<String, usize>,
+	/// Clusters grouped by the numbers in their key; entities never match across groups.
+	by_digits: HashMap<String, Vec<usize>>,
+	/// Fuzzy matching candidates, blocked by the numbers and the first character of the key.
+	blocks: HashMap<(String, Option<char>), Vec<usize>>,
+}
+
+/// Clusters entity mentions produced by the engines and assigns each cluster a canonical id.
+///
+/// Mentions are normalized (case, punctuation, leading articles and legal suffixes) and looked
+/// up in the user supplied alias tables first. Unknown mentions are then matched against the
+/// known clusters by exact key, acronym, Jaro-Winkler similarity and finally embedding
+/// similarity; a mention that matches nothing starts a new cluster.
+///
+/// Canonical ids are derived from the normalized label of a cluster: the alias table canonical
+/// name, or else the most descriptive key of the batch that created it (expanded names before
+/// acronyms, then the most frequent form). Ids therefore do not depend on the order of mentions
+/// within a batch. A cluster keeps its id when later batches add better forms, so ids already
+/// written to storage stay valid.
+///
+/// With a metadata store, the id of every key is recorded the first time it is resolved and
+/// reused from then on, so ids survive restarts and agree across pipelines even though the
+/// clusters themselves live in memory.
+pub struct EntityResolver {
+	string_similarity_threshold: f64,
+	embedding_similarity_threshold: f32,
+	aliases: HashMap<String, String>,
+	embedder: Option<Arc<TextEmbedding>>,
+	metadata_store: Option<Arc<dyn MetaStorage>>,
+	state: Mutex<ResolverState>,
+}
+
+impl fmt::Debug for EntityResolver {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("EntityResolver")
+			.field("string_similarity_threshold", &self.string_similarity_threshold)
+			.field("embedding_similarity_threshold", &self.embedding_similarity_threshold)
+			.field("aliases", &self.aliases.len())
+			.field("uses_embeddings", &self.embedder.is_some())
+			.finish()
+	}
+}
+
+impl EntityResolver {
+	/// Returns `None` unless resolution is enabled in the config.
+	pub fn new(
+		config: &EntityResolutionConfig,
+		embedder: Option<Arc<TextEmbedding>>,
+	) -> Option<Self> {
+		if config.enabled != Some(true) {
+			return None;
+		}
+		let mut aliases = HashMap::new();
+		for table in &config.aliases {
+			let canonical = normalize_mention(&table.canonical);
+			if canonical.is_empty() {
+				continue;
+			}
+			for alias in &table.aliases {
+				let alias = normalize_mention(alias);
+				if !alias.is_empty() {
+					aliases.insert(alias, canonical.clone());
+				}
+			}
+			aliases.insert(canonical.clone(), canonical);
+		}
+		Some(Self {
+			string_similarity_threshold: config
+				.string_similarity_threshold
+				.map(|threshold| threshold as f64)
+				.unwrap_or(DEFAULT_STRING_SIMILARITY_THRESHOLD),
+			embedding_similarity_threshold: config
+				.embedding_similarity_threshold
+				.unwrap_or(DEFAULT_EMBEDDING_SIMILARITY_THRESHOLD),
+			aliases,
+			embedder,
+			metadata_store: None,
+			state: Mutex::new(ResolverState::default()),
+		})
+	}
+
+	/// Records the canonical ids of resolved keys in `metadata_store`.
+	pub fn with_metadata_store(mut self, metadata_store: Arc<dyn MetaStorage>) -> Self {
+		self.metadata_store = Some(metadata_store);
+		self
+	}
+
+	/// Restores the keys resolved by earlier runs, so that their mentions keep their ids.
+	pub async fn load(&self) {
+		let Some(metadata_store) = &self.metadata_store else {
+			return;
+		};
+		let mut entity_ids = match metadata_store.get_entity_ids().await {
+			Ok(entity_ids) => entity_ids,
+			Err(e) => {
+				error!("Failed to load entity ids: {:?}", e);
+				return;
+			},
+		};
+		// the key an id was derived from becomes the key of its cluster
+		entity_ids.sort_by_key(|(key, id)| canonical_entity_id(key) != *id);
+		let mut state = self.lock_state();
+		let mut by_id: HashMap<String, usize> = HashMap::new();
+		for (key, id) in entity_ids {
+			if state.by_key.contains_key(&key) {
+				continue;
+			}
+			let index = match by_id.get(&id) {
+				Some(&index) => index,
+				None => {
+					let index = add_cluster(&mut state, key.clone(), id.clone(), None);
+					by_id.insert(id, index);
+					index
+				},
+			};
+			state.by_key.insert(key, index);
+		}
+	}
+
+	/// Resolves a batch of mentions to canonical entity ids, in input order.
+	pub async fn resolve(&self, mentions: &[&str]) -> Vec<String> {
+		let keys: Vec<String> = mentions.iter().map(|mention| self.key_of(mention)).collect();
+		let ranked = rank_keys(&keys);
+
+		// Embeddings are only computed for keys the string rules cannot place, and outside
+		// the lock on the blocking pool since embedding is by far the slowest step.
+		let unmatched: Vec<String> = {
+			let state = self.lock_state();
+			ranked
+				.iter()
+				.filter(|key| self.match_by_string(&state, key).is_none())
+				.map(|key| key.to_string())
+				.collect()
+		};
+		let mut embeddings: HashMap<String, Vec<f32>> = HashMap::new();
+		if let Some(embedder) = self.embedder.clone().filter(|_| !unmatched.is_empty()) {
+			let texts = unmatched.clone();
+			match tokio::task::spawn_blocking(move || embedder.embed(texts, None)).await {
+				Ok(Ok(vectors)) => embeddings.extend(unmatched.into_iter().zip(vectors)),
+				Ok(Err(e)) => error!("Failed to embed entity mentions: {:?}", e),
+				Err(e) => error!("Entity mention embedding task failed: {:?}", e),
+			}
+		}
+
+		// keys that started a cluster, and keys that joined one
+		let (mut founded, mut joined) = (Vec::new(), Vec::new());
+		{
+			let mut state = self.lock_state();
+			for key in ranked {
+				if state.by_key.contains_key(key) {
+					continue;
+				}
+				let embedding = embeddings.remove(key);
+				let matched = self.match_by_string(&state, key).or_else(|| {
+					embedding.as_deref().and_then(|e| self.match_by_embedding(&state, key, e))
+				});
+				let index = match matched {
+					Some(index) => {
+						joined.push(key.to_string());
+						index
+					},
+					None => {
+						founded.push(key.to_string());
+						add_cluster(
+							&mut state,
+							key.to_string(),
+							canonical_entity_id(key),
+							embedding,
+						)
+					},
+				};
+				state.by_key.insert(key.to_string(), index);
+			}
+		}
+		// founders first: the clusters they adopt an id for pass it on to the keys joining them
+		self.record(founded).await;
+		self.record(joined).await;
+
+		let state = self.lock_state();
+		keys.iter().map(|key| state.clusters[state.by_key[key]].id.clone()).collect()
+	}
+
+	/// Records the ids of newly resolved keys, adopting the ids already recorded for them by
+	/// an earlier run or another pipeline.
+	async fn record(&self, keys: Vec<String>) {
+		let Some(metadata_store) = self.metadata_store.as_ref().filter(|_| !keys.is_empty()) else {
+			return;
+		};
+		let resolved: Vec<(String, String)> = {
+			let state = self.lock_state();
+			keys.into_iter()
+				.map(|key| {
+					let id = state.clusters[state.by_key[&key]].id.clone();
+					(key, id)
+				})
+				.collect()
+		};
+		let stored = match metadata_store.set_entity_ids(resolved.clone()).await {
+			Ok(stored) => stored,
+			Err(e) => {
+				error!("Failed to record entity ids: {:?}", e);
+				return;
+			},
+		};
+		let mut state = self.lock_state();
+		for ((key, id), (_, stored_id)) in resolved.into_iter().zip(stored) {
+			if id == stored_id {
+				continue;
+			}
+			let index = state.by_key[&key];
+			if state.clusters[index].key == key {
+				state.clusters[index].id = stored_id;
+			} else {
+				let index = add_cluster(&mut state, key.clone(), stored_id, None);
+				state.by_key.insert(key, index);
+			}
+		}
+	}
+
+	/// Fills in `subject_id` / `object_id` on graph events that do not carry them yet.
+	pub async fn resolve_events(&self, events: Vec<EventState>) -> Vec<EventState> {
+		let mut parsed: Vec<(EventState, Option<SemanticKnowledgePayload>)> = events
+			.into_iter()
+			.map(|event| {
+				let payload = if event.event_type == EventType::Graph {
+					serde_json::from_str::<SemanticKnowledgePayload>(&event.payload)
+						.map_err(|e| error!("Failed to deserialize payload: {:?}", e))
+						.ok()
+				} else {
+					None
+				};
+				(event, payload)
+			})
+			.collect();
+
+		let mentions: Vec<&str> = parsed
+			.iter()
+			.filter_map(|(_, payload)| payload.as_ref())
+			.flat_map(|payload| [payload.subject.as_str(), payload.object.as_str()])
+			.collect();
+		let mut ids = self.resolve(&mentions).await.into_iter();
+
+		for (event, payload) in parsed.iter_mut() {
+			let Some(payload) = payload else { continue };
+			let (subject_id, object_id) = (ids.next(), ids.next());
+			if payload.subject_id.is_some() && payload.object_id.is_some() {
+				continue;
+			}
+			payload.subject_id = payload.subject_id.take().or(subject_id);
+			payload.object_id = payload.object_id.take().or(object_id);
+			match serde_json::to_string(payload) {
+				Ok(serialized) => event.payload = serialized,
+				Err(e) => error!("Failed to serialize payload: {:?}", e),
+			}
+		}
+		parsed.into_iter().map(|(event, _)| event).collect()
+	}
+
+	fn lock_state(&self) -> std::sync::MutexGuard<'_, ResolverState> {
+		self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	fn key_of(&self, mention: &str) -> String {
+		let mut key = normalize_mention(mention);
+		if key.is_empty() {
+			key = mention.trim().to_lowercase();
+		}
+		self.aliases.get(&key).cloned().unwrap_or(key)
+	}
+
+	fn match_by_string(&self, state: &ResolverState, key: &str) -> Option<usize> {
+		if let Some(&index) = state.by_key.get(key) {
+			return Some(index);
+		}
+		let digits = digits_of(key);
+		let same_digits = |index: &&usize| digits_of(&state.clusters[**index].key) == digits;
+		if is_acronym(key) {
+			if let Some(&index) = state.by_acronym.get(key).filter(same_digits) {
+				return Some(index);
+			}
+		}
+		if let Some(acronym) = acronym_of(key).filter(|acronym| is_acronym(acronym)) {
+			if let Some(&index) = state.by_key.get(&acronym).filter(same_digits) {
+				return Some(index);
+			}
+		}
+		if key.len() < MIN_FUZZY_KEY_LEN {
+			return None;
+		}
+		// Blocking on the first character keeps lookups cheap; a typo in the very first letter
+		// rarely clears the similarity threshold anyway.
+		let mut best: Option<(usize, f64)> = None;
+		for &index in state.blocks.get(&block_of(key)).into_iter().flatten() {
+			let cluster_key = &state.clusters[index].key;
+			if cluster_key.len() < MIN_FUZZY_KEY_LEN {
+				continue;
+			}
+			let similarity = jaro_winkler(key, cluster_key);
+			let improves = match best {
+				Some((_, score)) => similarity > score,
+				None => true,
+			};
+			if similarity >= self.string_similarity_threshold && improves {
+				best = Some((index, similarity));
+			}
+		}
+		best.map(|(index, _)| index)
+	}
+
+	fn match_by_embedding(
+		&self,
+		state: &ResolverState,
+		key: &str,
+		embedding: &[f32],
+	) -> Option<usize> {
+		state
+			.by_digits
+			.get(&digits_of(key).join(" "))
+			.into_iter()
+			.flatten()
+			.filter_map(|&index| {
+				let cluster = &state.clusters[index];
+				let similarity = cosine_similarity(embedding, cluster.embedding.as_deref()?);
+				(similarity >= self.embedding_similarity_threshold).then_some((index, similarity))
+			})
+			.max_by(|a, b| a.1.total_cmp(&b.1))
+			.map(|(index, _)| index)
+	}
+}
+
+fn add_cluster(
+	state: &mut ResolverState,
+	key: String,
+	id: String,
+	embedding: Option<Vec<f32>>,
+) -> usize {
+	let index = state.clusters.len();
+	if let Some(acronym) = acronym_of(&key) {
+		state.by_acronym.entry(acronym).or_insert(index);
+	}
+	state.by_digits.entry(digits_of(&key).join(" ")).or_default().push(index);
+	state.blocks.entry(block_of(&key)).or_default().push(index);
+	state.clusters.push(EntityCluster { id, key, embedding });
+	index
+}
+
+/// Distinct keys of a batch, most descriptive first: more words, then more mentions, then
+/// longer keys, with ties broken alphabetically.
+fn rank_keys(keys: &[String]) -> Vec<&str> {
+	let mut counts: HashMap<&str, usize> = HashMap::new();
+	for key in keys {
+		*counts.entry(key.as_str()).or_default() += 1;
+	}
+	let mut ranked: Vec<&str> = counts.keys().copied().collect();
+	ranked.sort_by(|a, b| {
+		let words = |key: &str| key.split_whitespace().count();
+		words(b)
+			.cmp(&words(a))
+			.then(counts[b].cmp(&counts[a]))
+			.then(b.len().cmp(&a.len()))
+			.then(a.cmp(b))
+	});
+	ranked
+}
+
+fn block_of(key: &str) -> (String, Option<char>) {
+	(digits_of(key).join(" "), key.chars().next())
+}
+
+/// Canonical id for a normalized entity key.
+pub fn canonical_entity_id(key: &str) -> String {
+	// FNV-1a keeps ids stable across processes, unlike the std hasher.
+	let hash = key.bytes().fold(0xcbf29ce484222325_u64, |hash, byte| {
+		(hash ^ byte as u64).wrapping_mul(0x100000001b3)
+	});
+	format!("ent_{:016x}", hash)
+}
+
+/// Lowercases a mention, strips punctuation, a leading article and trailing legal suffixes.
+pub fn normalize_mention(mention: &str) -> String {
+	let cleaned: String = mention
+		.to_lowercase()
+		.chars()
+		.filter(|c| !matches!(c, '.' | '\'' | '’'))
+		.map(|c| if c.is_alphanumeric() { c } else { ' ' })
+		.collect();
+	let mut tokens: Vec<&str> = cleaned.split_whitespace().collect();
+	if tokens.len() > 1 && tokens[0] == "the" {
+		tokens.remove(0);
+	}
+	while tokens.len() > 1 && LEGAL_SUFFIXES.contains(tokens.last().unwrap_or(&"")) {
+		tokens.pop();
+	}
+	tokens.join(" ")
+}
+
+fn acronym_of(key: &str) -> Option<String> {
+	let tokens: Vec<&str> =
+		key.split_whitespace().filter(|t| !matches!(*t, "of" | "and")).collect();
+	if tokens.len() < 2 || tokens.iter().any(|t| t.chars().all(|c| c.is_ascii_digit())) {
+		return None;
+	}
+	Some(tokens.iter().filter_map(|t| t.chars().next()).collect())
+}
+
+fn is_acronym(key: &str) -> bool {
+	(2..=6).contains(&key.len()) && key.chars().all(|c| c.is_alphabetic())
+}
+
+fn digits_of(key: &str) -> Vec<&str> {
+	key.split(|c: char| !c.is_ascii_digit()).filter(|s| !s.is_empty()).collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+	let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+	let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+	let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+	if norm_a == 0.0 || norm_b == 0.0 {
+		0.0
+	} else {
+		dot / (norm_a * norm_b)
+	}
+}
+
+/// Jaro-Winkler similarity in `[0, 1]`.
+pub fn jaro_winkler(a: &str, b: &str) -> f64 {
+	let a: Vec<char> = a.chars().collect();
+	let b: Vec<char> = b.chars().collect();
+	if a.is_empty() && b.is_empty() {
+		return 1.0;
+	}
+	if a.is_empty() || b.is_empty() {
+		return 0.0;
+	}
+	let window = (a.len().max(b.len()) / 2).saturating_sub(1);
+	let mut a_matched = vec![false; a.len()];
+	let mut b_matched = vec![false; b.len()];
+	let mut matches = 0usize;
+	for (i, ca) in a.iter().enumerate() {
+		let start = i.saturating_sub(window);
+		let end = (i + window + 1).min(b.len());
+		for j in start..end {
+			if !b_matched[j] && b[j] == *ca {
+				a_matched[i] = true;
+				b_matched[j] = true;
+				matches += 1;
+				break;
+			}
+		}
+	}
+	if matches == 0 {
+		return 0.0;
+	}
+	let a_seq = a.iter().zip(&a_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+	let b_seq = b.iter().zip(&b_matched).filter(|(_, m)| **m).map(|(c, _)| c);
+	let transpositions = a_seq.zip(b_seq).filter(|(x, y)| x != y).count() / 2;
+	let m = matches as f64;
+	let jaro = (m / a.len() as f64 + m / b.len() as f64 + (m - transpositions as f64) / m) / 3.0;
+	let prefix = a.iter().zip(&b).take(4).take_while(|(x, y)| x == y).count();
+	jaro + prefix as f64 * 0.1 * (1.0 - jaro)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use proto::semantics::EntityAliases;
+
+	fn resolver(config: EntityResolutionConfig) -> EntityResolver {
+		EntityResolver::new(&EntityResolutionConfig { enabled: Some(true), ..config }, None)
+			.unwrap()
+	}
+
+	#[test]
+	fn normalizes_mentions() {
+		assert_eq!(normalize_mention("I.B.M."), "ibm");
+		assert_eq!(normalize_mention("The Acme Corp."), "acme");
+		assert_eq!(normalize_mention("O'Reilly Media, Inc"), "oreilly media");
+		assert_eq!(normalize_mention("Inc"), "inc");
+	}
+
+	#[tokio::test]
+	async fn clusters_surface_forms() {
+		let resolver = resolver(EntityResolutionConfig::default());
+		let ids = resolver
+			.resolve(&[
+				"international business machines",
+				"IBM",
+				"i.b.m.",
+				"International Business Machines Corp",
+				"microsoft",
+				"microsfot",
+			])
+			.await;
+		assert_eq!(ids[0], ids[1]);
+		assert_eq!(ids[0], ids[2]);
+		assert_eq!(ids[0], ids[3]);
+		assert_ne!(ids[0], ids[4]);
+		assert_eq!(ids[4], ids[5]);
+		assert_eq!(ids[0], canonical_entity_id("international business machines"));
+	}
+
+	#[tokio::test]
+	async fn ids_do_not_depend_on_mention_order() {
+		let mentions =
+			["IBM", "microsfot", "International Business Machines", "microsoft", "Microsoft"];
+		let forward = resolver(EntityResolutionConfig::default()).resolve(&mentions).await;
+		let reversed: Vec<&str> = mentions.iter().rev().copied().collect();
+		let mut backward = resolver(EntityResolutionConfig::default()).resolve(&reversed).await;
+		backward.reverse();
+		assert_eq!(forward, backward);
+		assert_eq!(forward[0], canonical_entity_id("international business machines"));
+		assert_eq!(forward[1], canonical_entity_id("microsoft"));
+	}
+
+	#[tokio::test]
+	async fn ids_survive_restarts_and_agree_across_pipelines() {
+		let dir = tempfile::tempdir().unwrap();
+		let metadata_store =
+			storage::create_metadata_store(dir.path().to_path_buf()).await.unwrap();
+		let persisted = || {
+			resolver(EntityResolutionConfig::default()).with_metadata_store(metadata_store.clone())
+		};
+		let first = persisted();
+		let ibm = first.resolve(&["IBM"]).await;
+		first.resolve(&["International Business Machines"]).await;
+		assert_eq!(ibm, vec![canonical_entity_id("ibm")]);
+
+		// without the recorded ids the expanded name would found the cluster
+		let restarted = persisted();
+		restarted.load().await;
+		let ids = restarted.resolve(&["International Business Machines", "i.b.m."]).await;
+		assert_eq!(ids, vec![ibm[0].clone(); 2]);
+
+		let other_pipeline = persisted();
+		let ids = other_pipeline.resolve(&["International Business Machines Corp", "IBM"]).await;
+		assert_eq!(ids, vec![ibm[0].clone(); 2]);
+	}
+
+	#[tokio::test]
+	async fn keeps_numbered_entities_apart() {
+		let resolver = resolver(EntityResolutionConfig::default());
+		let ids = resolver.resolve(&["pump 42", "pump 43", "pump-42"]).await;
+		assert_ne!(ids[0], ids[1]);
+		assert_eq!(ids[0], ids[2]);
+	}
+
+	#[tokio::test]
+	async fn applies_alias_tables_and_can_be_disabled() {
+		let config = EntityResolutionConfig {
+			aliases: vec![EntityAliases {
+				canonical: "Big Blue".to_string(),
+				aliases: vec!["IBM".to_string()],
+			}],
+			..Default::default()
+		};
+		let ids = resolver(config).resolve(&["ibm", "big blue"]).await;
+		assert_eq!(ids, vec![canonical_entity_id("big blue"); 2]);
+
+		let disabled = EntityResolutionConfig { enabled: Some(false), ..Default::default() };
+		assert!(EntityResolver::new(&disabled, None).is_none());
+		// opt-in
+		assert!(EntityResolver::new(&EntityResolutionConfig::default(), None).is_none());
+	}
+
+	#[tokio::test]
+	async fn resolves_graph_events() {
+		let payload = SemanticKnowledgePayload {
+			subject: "ibm".to_string(),
+			object: "international business machines".to_string(),
+			object_id: Some("urn:ibm".to_string()),
+			..Default::default()
+		};
+		let event = EventState {
+			event_type: EventType::Graph,
+			timestamp: 0.0,
+			payload: serde_json::to_string(&payload).unwrap(),
+			file: "doc.txt".to_string(),
+			doc_source: "test".to_string(),
+			image_id: None,
+		};
+		let events = resolver(EntityResolutionConfig::default()).resolve_events(vec![event]).await;
+		let resolved: SemanticKnowledgePayload = serde_json::from_str(&events[0].payload).unwrap();
+		assert_eq!(
+			resolved.subject_id,
+			Some(canonical_entity_id("international business machines"))
+		);
+		assert_eq!(resolved.object_id.as_deref(), Some("urn:ibm"));
+	}
+}
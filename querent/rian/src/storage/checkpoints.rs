@@ -0,0 +1,46 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+use async_trait::async_trait;
+use sources::CheckpointStore;
+use std::sync::Arc;
+use storage::MetaStorage;
+
+/// Keeps source checkpoints in the node's metadata store so they survive restarts.
+pub struct MetaCheckpointStore {
+	metadata_store: Arc<dyn MetaStorage>,
+}
+
+impl MetaCheckpointStore {
+	pub fn new(metadata_store: Arc<dyn MetaStorage>) -> Self {
+		Self { metadata_store }
+	}
+}
+
+#[async_trait]
+impl CheckpointStore for MetaCheckpointStore {
+	async fn get_checkpoint(&self, source_id: &str) -> anyhow::Result<Option<String>> {
+		Ok(self.metadata_store.get_source_checkpoint(&source_id.to_string()).await?)
+	}
+
+	async fn set_checkpoint(&self, source_id: &str, checkpoint: String) -> anyhow::Result<()> {
+		Ok(self
+			.metadata_store
+			.set_source_checkpoint(&source_id.to_string(), &checkpoint)
+			.await?)
+	}
+}
@@ -16,8 +16,10 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
+pub mod checkpoints;
+pub use checkpoints::*;
 pub mod storage_mapper;
 pub use storage_mapper::*;
 pub mod types;
-pub use storage::{SecretStorage, Storage};
+pub use storage::{MetaStorage, SecretStorage, Storage};
 pub use types::*;
@@ -17,7 +17,7 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use super::{ContextualEmbeddings, ContextualTriples};
-use crate::{EventLock, NewEventLock};
+use crate::{metrics::PIPELINE_METRICS, Delivery, EventLock, NewEventLock};
 use actors::{Actor, ActorContext, ActorExitStatus, Handler, QueueCapacity};
 use async_trait::async_trait;
 use common::{
@@ -26,7 +26,7 @@ use common::{
 use std::{collections::HashMap, sync::Arc};
 use storage::Storage;
 use tokio::runtime::Handle;
-use tracing::error;
+use tracing::{error, info_span, Instrument};
 
 pub struct StorageMapper {
 	qflow_id: String,
@@ -122,6 +122,9 @@ impl Handler<ContextualTriples> for StorageMapper {
 		self.counters.increment_total(message.len() as u64);
 		self.counters.increment_event_count(message.event_type(), message.len() as u64);
 		let event_type = message.event_type();
+		let store_span =
+			info_span!("store_graph", qflow_id = %self.qflow_id, triples = message.len());
+		message.trace_context.attach_to(&store_span);
 
 		// Iterate over all storages in self.event_storages
 		for (stored_event_type, storage) in &self.event_storages {
@@ -130,11 +133,15 @@ impl Handler<ContextualTriples> for StorageMapper {
 					let storage_clone = storage.clone();
 					let storage_items = message.event_payload();
 					// Spawn a task for each storage insertion
-					tokio::spawn(insert_graph_async(
-						self.qflow_id.clone(),
-						storage_clone,
-						storage_items,
-					));
+					tokio::spawn(
+						insert_graph_async(
+							self.qflow_id.clone(),
+							storage_clone,
+							storage_items,
+							message.delivery.clone(),
+						)
+						.instrument(store_span.clone()),
+					);
 				}
 			}
 		}
@@ -156,6 +163,8 @@ impl Handler<ContextualEmbeddings> for StorageMapper {
 		self.counters.increment_event_count(message.event_type(), message.len() as u64);
 		let event_type = message.event_type();
 		let qflow_id = message.qflow_id();
+		let store_span = info_span!("store_vectors", qflow_id = %qflow_id, vectors = message.len());
+		message.trace_context.attach_to(&store_span);
 
 		// Iterate over all storages in self.event_storages
 		for (stored_event_type, storage) in &self.event_storages {
@@ -165,7 +174,15 @@ impl Handler<ContextualEmbeddings> for StorageMapper {
 					let qflow_id_clone = qflow_id.clone();
 					let storage_items = message.event_payload();
 
-					tokio::spawn(insert_vector_async(storage_clone, qflow_id_clone, storage_items));
+					tokio::spawn(
+						insert_vector_async(
+							storage_clone,
+							qflow_id_clone,
+							storage_items,
+							message.delivery.clone(),
+						)
+						.instrument(store_span.clone()),
+					);
 				}
 			}
 		}
@@ -178,8 +195,18 @@ async fn insert_graph_async(
 	collection_id: String,
 	storage: Arc<dyn Storage>,
 	storage_items: Vec<(String, String, Option<String>, SemanticKnowledgePayload)>,
+	delivery: Delivery,
 ) -> Result<(), ActorExitStatus> {
-	let upsert_result = storage.insert_graph(collection_id, &storage_items).await;
+	let labels = [storage.backend_name(), "graph"];
+	let write_timer = PIPELINE_METRICS
+		.storage_write_duration_seconds
+		.with_label_values(labels)
+		.start_timer();
+	let upsert_result = storage
+		.insert_graph(collection_id, &storage_items)
+		.instrument(info_span!("storage_write", backend = labels[0], kind = labels[1]))
+		.await;
+	write_timer.observe_duration();
 	match upsert_result {
 		Ok(()) => {
 			// Increment counters if insertion is successful
@@ -188,7 +215,9 @@ async fn insert_graph_async(
 		},
 		Err(e) => {
 			// Handle error if insertion fails
+			PIPELINE_METRICS.storage_write_errors_total.with_label_values(labels).inc();
 			error!("Error while inserting graphs: {:?}", e);
+			delivery.fail();
 			// Depending on your error handling strategy, you might want to propagate the error
 			// back to the caller or handle it differently
 			Err(ActorExitStatus::Failure(e.source))
@@ -200,8 +229,18 @@ async fn insert_vector_async(
 	storage: Arc<dyn Storage>,
 	qflow_id: String,
 	storage_items: Vec<(String, String, Option<String>, VectorPayload)>,
+	delivery: Delivery,
 ) -> Result<(), ActorExitStatus> {
-	let upsert_result = storage.insert_vector(qflow_id, &storage_items).await;
+	let labels = [storage.backend_name(), "vector"];
+	let write_timer = PIPELINE_METRICS
+		.storage_write_duration_seconds
+		.with_label_values(labels)
+		.start_timer();
+	let upsert_result = storage
+		.insert_vector(qflow_id, &storage_items)
+		.instrument(info_span!("storage_write", backend = labels[0], kind = labels[1]))
+		.await;
+	write_timer.observe_duration();
 	match upsert_result {
 		Ok(()) => {
 			// Increment counters if insertion is successful
@@ -210,7 +249,9 @@ async fn insert_vector_async(
 		},
 		Err(e) => {
 			// Handle error if insertion fails
+			PIPELINE_METRICS.storage_write_errors_total.with_label_values(labels).inc();
 			error!("Error while inserting vector: {:?}", e);
+			delivery.fail();
 			// Depending on your error handling strategy, you might want to propagate the error
 			// back to the caller or handle it differently
 			Err(ActorExitStatus::Failure(e.source))
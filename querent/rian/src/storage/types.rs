@@ -16,21 +16,41 @@
 
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
-use common::{EventState, EventType, SemanticKnowledgePayload, VectorPayload};
+use common::{EventState, EventType, SemanticKnowledgePayload, TraceContext, VectorPayload};
 use serde::Serialize;
 use tracing::error;
 
+use crate::Delivery;
+
 #[derive(Debug, Serialize, Clone)]
 pub struct ContextualTriples {
 	event_type: EventType,
 	pub qflow_id: String,
 	pub triple_states: Vec<EventState>,
 	pub timestamp: u64,
+	#[serde(skip)]
+	pub trace_context: TraceContext,
+	#[serde(skip)]
+	pub delivery: Delivery,
 }
 
 impl ContextualTriples {
+	/// Creates a message carrying the trace context of the current span.
 	pub fn new(qflow_id: String, triple_states: Vec<EventState>, timestamp: u64) -> Self {
-		Self { event_type: EventType::Graph, qflow_id, triple_states, timestamp }
+		Self {
+			event_type: EventType::Graph,
+			qflow_id,
+			triple_states,
+			timestamp,
+			trace_context: TraceContext::current(),
+			delivery: Delivery::default(),
+		}
+	}
+
+	/// Follows the writes of the events, to acknowledge their documents once stored.
+	pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+		self.delivery = delivery;
+		self
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -82,11 +102,29 @@ pub struct ContextualEmbeddings {
 	pub qflow_id: String,
 	pub vector_states: Vec<EventState>,
 	pub timestamp: u64,
+	#[serde(skip)]
+	pub trace_context: TraceContext,
+	#[serde(skip)]
+	pub delivery: Delivery,
 }
 
 impl ContextualEmbeddings {
+	/// Creates a message carrying the trace context of the current span.
 	pub fn new(qflow_id: String, vector_states: Vec<EventState>, timestamp: u64) -> Self {
-		Self { event_type: EventType::Vector, qflow_id, vector_states, timestamp }
+		Self {
+			event_type: EventType::Vector,
+			qflow_id,
+			vector_states,
+			timestamp,
+			trace_context: TraceContext::current(),
+			delivery: Delivery::default(),
+		}
+	}
+
+	/// Follows the writes of the events, to acknowledge their documents once stored.
+	pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+		self.delivery = delivery;
+		self
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -133,15 +171,32 @@ pub struct IndexerKnowledge {
 	pub qflow_id: String,
 	pub timestamp: u64,
 	pub triples: Vec<(String, String, Option<String>, SemanticKnowledgePayload)>,
+	#[serde(skip)]
+	pub trace_context: TraceContext,
+	#[serde(skip)]
+	pub delivery: Delivery,
 }
 
 impl IndexerKnowledge {
+	/// Creates a message carrying the trace context of the current span.
 	pub fn new(
 		qflow_id: String,
 		timestamp: u64,
 		triples: Vec<(String, String, Option<String>, SemanticKnowledgePayload)>,
 	) -> Self {
-		Self { qflow_id, timestamp, triples }
+		Self {
+			qflow_id,
+			timestamp,
+			triples,
+			trace_context: TraceContext::current(),
+			delivery: Delivery::default(),
+		}
+	}
+
+	/// Follows the writes of the triples, to acknowledge their documents once stored.
+	pub fn with_delivery(mut self, delivery: Delivery) -> Self {
+		self.delivery = delivery;
+		self
 	}
 
 	pub fn is_empty(&self) -> bool {
@@ -44,7 +44,8 @@ use tokio_util::{compat::FuturesAsyncReadCompatExt, io::StreamReader};
 use tracing::instrument;
 
 use crate::{
-	DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult, REQUEST_SEMAPHORE,
+	DataSource, DocumentFilter, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+	REQUEST_SEMAPHORE,
 };
 
 /// Azure object storage implementation
@@ -124,6 +125,69 @@ impl AzureBlobStorage {
 
 		Ok(buf)
 	}
+
+	/// Lists the blobs and downloads the ones `filter` accepts, all of them without one.
+	fn poll_blobs(
+		&self,
+		filter: Option<DocumentFilter>,
+	) -> Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + '_>> {
+		let mut blob_stream = self.container_client.list_blobs().into_stream();
+		let container_client = self.container_client.clone();
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			while let Some(blob_result) = blob_stream.next().await {
+				let blob = match blob_result {
+					Ok(blob) => blob,
+					Err(err) => {
+						yield Err(SourceError::from(AzureErrorWrapper::from(err)));
+						continue;
+					},
+				};
+				let blobs_list = blob.blobs;
+
+				for blob_info in blobs_list.blobs() {
+					let blob_name = blob_info.name.clone();
+					// the rejected blobs are not downloaded
+					if filter.as_ref().is_some_and(|filter| !filter(&blob_name)) {
+						continue;
+					}
+					let blob_path = Path::new(&blob_name);
+					let file_size = blob_info.properties.content_length;
+					let boxed_reader = retry(&self.retry_params, || async {
+						let output_stream = container_client.blob_client(&blob_name).get().into_stream();
+						let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
+						let async_read_stream = output_stream
+						.map(|page_res| {
+							page_res
+								.map(|page| page.data)
+								.map_err(|err| FutureError::new(FutureErrorKind::Other, err))
+						})
+						.try_flatten()
+						.map(|e| e.map_err(|err| FutureError::new(FutureErrorKind::Other, err)));
+						let stream_reader = StreamReader::new(async_read_stream);
+						let boxed_pinned_stream  = Box::pin(stream_reader) as Pin<Box<dyn AsyncRead + Send + Unpin>>;
+						Ok::<_, AzureErrorWrapper>(boxed_pinned_stream)
+					}).await.map_err(AzureErrorWrapper::from)?;
+
+					// Only process and serialize if bytes were read
+					let collected_bytes = CollectedBytes::new(
+						Some(blob_path.to_path_buf()),
+						Some(boxed_reader),
+						true,
+						Some(container_client.container_name().to_string()),
+						Some(file_size as usize),
+						source_id.clone(),
+						None,
+					);
+					yield Ok(collected_bytes);
+
+				}
+			}
+		};
+
+		Box::pin(stream)
+	}
 }
 
 /// Collect a download stream into an output buffer.
@@ -182,6 +246,14 @@ impl DataSource for AzureBlobStorage {
 		}
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	fn can_fetch_document(&self) -> bool {
+		true
+	}
+
 	#[instrument(level = "debug", skip(self, range), fields(range.start = range.start, range.end = range.end))]
 	async fn get_slice(&self, path: &Path, range: Range<usize>) -> SourceResult<Vec<u8>> {
 		self.get_to_vec(path, Some(range.clone())).await
@@ -237,58 +309,14 @@ impl DataSource for AzureBlobStorage {
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
-		let mut blob_stream = self.container_client.list_blobs().into_stream();
-		let container_client = self.container_client.clone();
-		let source_id = self.source_id.clone();
-
-		let stream = stream! {
-			while let Some(blob_result) = blob_stream.next().await {
-				let blob = match blob_result {
-					Ok(blob) => blob,
-					Err(err) => {
-						yield Err(SourceError::from(AzureErrorWrapper::from(err)));
-						continue;
-					},
-				};
-				let blobs_list = blob.blobs;
-
-				for blob_info in blobs_list.blobs() {
-					let blob_name = blob_info.name.clone();
-					let blob_path = Path::new(&blob_name);
-					let file_size = blob_info.properties.content_length;
-					let boxed_reader = retry(&self.retry_params, || async {
-						let output_stream = container_client.blob_client(&blob_name).get().into_stream();
-						let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
-						let async_read_stream = output_stream
-						.map(|page_res| {
-							page_res
-								.map(|page| page.data)
-								.map_err(|err| FutureError::new(FutureErrorKind::Other, err))
-						})
-						.try_flatten()
-						.map(|e| e.map_err(|err| FutureError::new(FutureErrorKind::Other, err)));
-						let stream_reader = StreamReader::new(async_read_stream);
-						let boxed_pinned_stream  = Box::pin(stream_reader) as Pin<Box<dyn AsyncRead + Send + Unpin>>;
-						Ok::<_, AzureErrorWrapper>(boxed_pinned_stream)
-					}).await.map_err(AzureErrorWrapper::from)?;
-
-					// Only process and serialize if bytes were read
-					let collected_bytes = CollectedBytes::new(
-						Some(blob_path.to_path_buf()),
-						Some(boxed_reader),
-						true,
-						Some(container_client.container_name().to_string()),
-						Some(file_size as usize),
-						source_id.clone(),
-						None,
-					);
-					yield Ok(collected_bytes);
-
-				}
-			}
-		};
+		Ok(self.poll_blobs(None))
+	}
 
-		Ok(Box::pin(stream))
+	async fn poll_filtered_data(
+		&self,
+		filter: DocumentFilter,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		Ok(self.poll_blobs(Some(filter)))
 	}
 }
 
@@ -0,0 +1,511 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	fmt,
+	io::Cursor,
+	ops::Range,
+	path::{Path, PathBuf},
+	pin::Pin,
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use bytes::Bytes;
+use common::{CollectedBytes, RetryParams};
+use futures::Stream;
+use proto::semantics::ConfluenceConfig;
+use reqwest::{Client, StatusCode};
+use serde_json::Value;
+use tokio::io::AsyncRead;
+
+use crate::{
+	send_throttled, string_to_async_read, DataSource, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult,
+};
+
+/// Number of spaces, pages or attachments requested per call.
+const PAGE_LIMIT: usize = 25;
+
+/// A Confluence page with the context needed to render it on its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfluencePage {
+	pub id: String,
+	pub title: String,
+	pub space_key: String,
+	/// Titles of the parent pages, from the space root down.
+	pub ancestors: Vec<String>,
+	pub labels: Vec<String>,
+	/// Body in the storage format, which is XHTML.
+	pub body: String,
+}
+
+impl ConfluencePage {
+	pub fn from_json(space_key: &str, value: &Value) -> Option<Self> {
+		let titles = |items: &Value, field: &str| -> Vec<String> {
+			items
+				.as_array()
+				.map(|items| {
+					items
+						.iter()
+						.filter_map(|item| item[field].as_str().map(str::to_string))
+						.collect()
+				})
+				.unwrap_or_default()
+		};
+		Some(ConfluencePage {
+			id: value["id"].as_str()?.to_string(),
+			title: value["title"].as_str().unwrap_or_default().to_string(),
+			space_key: value["space"]["key"].as_str().unwrap_or(space_key).to_string(),
+			ancestors: titles(&value["ancestors"], "title"),
+			labels: titles(&value["metadata"]["labels"]["results"], "name"),
+			body: value["body"]["storage"]["value"].as_str().unwrap_or_default().to_string(),
+		})
+	}
+
+	/// Wraps the body in a document that also carries the title, space, position in the
+	/// page tree and labels, so they reach the HTML ingestor with the content.
+	pub fn to_html(&self) -> String {
+		let mut path = self.ancestors.clone();
+		path.push(self.title.clone());
+		let mut html = format!(
+			"<html><head><title>{title}</title></head><body>\n<h1>{title}</h1>\n\
+			 <p>Space: {space}</p>\n<p>Path: {path}</p>\n",
+			title = escape_html(&self.title),
+			space = escape_html(&self.space_key),
+			path = escape_html(&path.join(" / ")),
+		);
+		if !self.labels.is_empty() {
+			html.push_str(&format!("<p>Labels: {}</p>\n", escape_html(&self.labels.join(", "))));
+		}
+		html.push_str(&self.body);
+		html.push_str("\n</body></html>");
+		html
+	}
+
+	/// Folder the page and its attachments are reported under.
+	fn folder(&self) -> PathBuf {
+		PathBuf::from(&self.space_key).join(file_name(&self.title))
+	}
+}
+
+#[derive(Clone)]
+pub struct ConfluenceSource {
+	client: Client,
+	base_url: String,
+	email: String,
+	api_token: String,
+	space_keys: Vec<String>,
+	include_attachments: bool,
+	source_id: String,
+	pub retry_params: RetryParams,
+}
+
+impl fmt::Debug for ConfluenceSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ConfluenceSource")
+			.field("base_url", &self.base_url)
+			.field("space_keys", &self.space_keys)
+			.field("source_id", &self.source_id)
+			.finish()
+	}
+}
+
+impl ConfluenceSource {
+	pub async fn new(config: ConfluenceConfig) -> anyhow::Result<Self> {
+		if config.base_url.is_empty() {
+			return Err(anyhow::anyhow!("Confluence base url is required"));
+		}
+		Ok(ConfluenceSource {
+			client: Client::new(),
+			base_url: config.base_url.trim_end_matches('/').to_string(),
+			email: config.email,
+			api_token: config.api_token,
+			space_keys: config.space_keys,
+			include_attachments: config.include_attachments.unwrap_or(true),
+			source_id: config.id,
+			retry_params: RetryParams::aggressive(),
+		})
+	}
+
+	async fn send(&self, url: &str, query: &[(&str, String)]) -> SourceResult<reqwest::Response> {
+		let response = send_throttled(&self.retry_params, || async {
+			self.client
+				.get(url)
+				.basic_auth(&self.email, Some(&self.api_token))
+				.query(query)
+				.send()
+				.await
+				.map_err(|err| {
+					SourceError::new(
+						SourceErrorKind::Connection,
+						anyhow::anyhow!("Error making the Confluence request: {:?}", err).into(),
+					)
+				})
+		})
+		.await?;
+
+		let status = response.status();
+		if status.is_success() {
+			return Ok(response);
+		}
+		let kind = match status {
+			StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => SourceErrorKind::Unauthorized,
+			StatusCode::NOT_FOUND => SourceErrorKind::NotFound,
+			_ => SourceErrorKind::Io,
+		};
+		Err(SourceError::new(
+			kind,
+			anyhow::anyhow!("Confluence request to {} failed with status {}", url, status).into(),
+		))
+	}
+
+	async fn get_json(&self, path: &str, query: &[(&str, String)]) -> SourceResult<Value> {
+		let url = format!("{}{}", self.base_url, path);
+		self.send(&url, query).await?.json::<Value>().await.map_err(|err| {
+			SourceError::new(
+				SourceErrorKind::Io,
+				anyhow::anyhow!("Error parsing the Confluence response: {:?}", err).into(),
+			)
+		})
+	}
+
+	/// Fetches every result of a paginated listing.
+	async fn get_all_results(
+		&self,
+		path: &str,
+		query: &[(&str, String)],
+	) -> SourceResult<Vec<Value>> {
+		let mut results = Vec::new();
+		let mut start = 0;
+		loop {
+			let mut page_query = query.to_vec();
+			page_query.push(("start", start.to_string()));
+			page_query.push(("limit", PAGE_LIMIT.to_string()));
+			let response = self.get_json(path, &page_query).await?;
+			let batch = response["results"].as_array().cloned().unwrap_or_default();
+			let count = batch.len();
+			results.extend(batch);
+			if count < PAGE_LIMIT {
+				return Ok(results);
+			}
+			start += count;
+		}
+	}
+
+	/// The configured spaces, or every space readable by the user.
+	pub async fn spaces(&self) -> SourceResult<Vec<String>> {
+		if !self.space_keys.is_empty() {
+			return Ok(self.space_keys.clone());
+		}
+		let spaces = self.get_all_results("/rest/api/space", &[]).await?;
+		Ok(spaces
+			.iter()
+			.filter_map(|space| space["key"].as_str().map(str::to_string))
+			.collect())
+	}
+
+	pub async fn pages(&self, space_key: &str) -> SourceResult<Vec<ConfluencePage>> {
+		let query = [
+			("spaceKey", space_key.to_string()),
+			("type", "page".to_string()),
+			("status", "current".to_string()),
+			("expand", "body.storage,ancestors,metadata.labels,space".to_string()),
+		];
+		let pages = self.get_all_results("/rest/api/content", &query).await?;
+		Ok(pages
+			.iter()
+			.filter_map(|page| ConfluencePage::from_json(space_key, page))
+			.collect())
+	}
+
+	/// Names and download links of the files attached to a page.
+	async fn attachments(&self, page_id: &str) -> SourceResult<Vec<(String, String)>> {
+		let path = format!("/rest/api/content/{}/child/attachment", page_id);
+		let attachments = self.get_all_results(&path, &[]).await?;
+		Ok(attachments
+			.iter()
+			.filter_map(|attachment| {
+				let title = attachment["title"].as_str()?;
+				let download = attachment["_links"]["download"].as_str()?;
+				Some((title.to_string(), format!("{}{}", self.base_url, download)))
+			})
+			.collect())
+	}
+
+	async fn download(&self, url: &str) -> SourceResult<Bytes> {
+		self.send(url, &[]).await?.bytes().await.map_err(|err| {
+			SourceError::new(
+				SourceErrorKind::Io,
+				anyhow::anyhow!("Error reading Confluence attachment: {:?}", err).into(),
+			)
+		})
+	}
+}
+
+fn escape_html(text: &str) -> String {
+	text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Page titles may contain separators, which would otherwise nest the reported path.
+fn file_name(title: &str) -> String {
+	title.replace(['/', '\\'], "-")
+}
+
+#[async_trait]
+impl DataSource for ConfluenceSource {
+	async fn check_connectivity(&self) -> anyhow::Result<()> {
+		match self.space_keys.first() {
+			Some(space_key) => {
+				self.get_json(&format!("/rest/api/space/{}", space_key), &[]).await?;
+			},
+			None => {
+				self.get_json("/rest/api/space", &[("limit", "1".to_string())]).await?;
+			},
+		}
+		Ok(())
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn get_slice_stream(
+		&self,
+		_path: &Path,
+		_range: Range<usize>,
+	) -> SourceResult<Box<dyn AsyncRead + Send + Unpin>> {
+		Ok(Box::new(string_to_async_read("".to_string())))
+	}
+
+	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn file_num_bytes(&self, _path: &Path) -> SourceResult<u64> {
+		Ok(0)
+	}
+
+	async fn copy_to(&self, _path: &Path, _output: &mut dyn SendableAsync) -> SourceResult<()> {
+		Ok(())
+	}
+
+	/// Emits every current page of the crawled spaces as HTML, followed by its attachments.
+	async fn poll_data(
+		&self,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			let spaces = match self.spaces().await {
+				Ok(spaces) => spaces,
+				Err(err) => {
+					yield Err(err);
+					return;
+				}
+			};
+			for space_key in spaces {
+				let pages = match self.pages(&space_key).await {
+					Ok(pages) => pages,
+					Err(err) => {
+						yield Err(err);
+						continue;
+					}
+				};
+				let doc_source = format!("confluence://{}", space_key);
+				for page in pages {
+					let folder = page.folder();
+					let html = page.to_html();
+					let size = html.len();
+					yield Ok(CollectedBytes::new(
+						Some(PathBuf::from(format!("{}.confluence", folder.display()))),
+						Some(Box::pin(string_to_async_read(html))),
+						true,
+						Some(doc_source.clone()),
+						Some(size),
+						source_id.clone(),
+						None,
+					));
+
+					if !self.include_attachments {
+						continue;
+					}
+					let attachments = match self.attachments(&page.id).await {
+						Ok(attachments) => attachments,
+						Err(err) => {
+							yield Err(err.with_document(folder.to_string_lossy()));
+							continue;
+						}
+					};
+					for (name, url) in attachments {
+						let file = folder.join(file_name(&name));
+						match self.download(&url).await {
+							Ok(data) => {
+								let size = data.len();
+								yield Ok(CollectedBytes::new(
+									Some(file),
+									Some(Box::pin(Cursor::new(data))),
+									true,
+									Some(doc_source.clone()),
+									Some(size),
+									source_id.clone(),
+									None,
+								));
+							},
+							Err(err) => yield Err(err.with_document(file.to_string_lossy())),
+						}
+					}
+				}
+			}
+		};
+
+		Ok(Box::pin(stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use futures::StreamExt;
+	use serde_json::json;
+	use tokio::io::AsyncReadExt;
+	use wiremock::{
+		matchers::{method, path, query_param},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+
+	fn config(base_url: String, space_keys: Vec<String>) -> ConfluenceConfig {
+		ConfluenceConfig {
+			base_url,
+			email: "user@querent.xyz".to_string(),
+			api_token: "token".to_string(),
+			space_keys,
+			include_attachments: None,
+			id: "confluence".to_string(),
+		}
+	}
+
+	#[tokio::test]
+	async fn test_confluence_pages_and_attachments() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/space"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_json(json!({ "results": [{ "key": "DOC" }] })),
+			)
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/content"))
+			.and(query_param("spaceKey", "DOC"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"results": [{
+					"id": "42",
+					"title": "Install guide",
+					"space": { "key": "DOC" },
+					"ancestors": [{ "id": "1", "title": "Home" }],
+					"metadata": { "labels": { "results": [{ "name": "setup" }] } },
+					"body": { "storage": { "value": "<p>Run the installer.</p>" } }
+				}]
+			})))
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/content/42/child/attachment"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({
+				"results": [{
+					"title": "setup.pdf",
+					"_links": { "download": "/download/attachments/42/setup.pdf" }
+				}]
+			})))
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/download/attachments/42/setup.pdf"))
+			.respond_with(ResponseTemplate::new(200).set_body_bytes(b"%PDF-1.4".to_vec()))
+			.mount(&server)
+			.await;
+
+		let source = ConfluenceSource::new(config(server.uri(), vec![])).await.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+
+		let mut stream = source.poll_data().await.unwrap();
+		let mut collected = HashMap::new();
+		while let Some(item) = stream.next().await {
+			let mut item = item.unwrap();
+			let mut data = Vec::new();
+			item.data.take().unwrap().read_to_end(&mut data).await.unwrap();
+			collected.insert(item.file.unwrap(), (item.extension, data));
+		}
+
+		let (extension, page) = &collected[&PathBuf::from("DOC/Install guide.confluence")];
+		assert_eq!(extension.as_deref(), Some("confluence"));
+		let page = String::from_utf8(page.clone()).unwrap();
+		assert!(page.contains("<p>Path: Home / Install guide</p>"));
+		assert!(page.contains("<p>Labels: setup</p>"));
+		assert!(page.contains("<p>Run the installer.</p>"));
+
+		let (extension, attachment) = &collected[&PathBuf::from("DOC/Install guide/setup.pdf")];
+		assert_eq!(extension.as_deref(), Some("pdf"));
+		assert_eq!(attachment, b"%PDF-1.4");
+	}
+
+	#[tokio::test]
+	async fn test_confluence_waits_out_throttling() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/space/DOC"))
+			.respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "0"))
+			.up_to_n_times(2)
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/space/DOC"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "key": "DOC" })))
+			.mount(&server)
+			.await;
+
+		let source = ConfluenceSource::new(config(server.uri(), vec!["DOC".to_string()]))
+			.await
+			.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+		assert_eq!(server.received_requests().await.unwrap().len(), 3);
+	}
+
+	#[tokio::test]
+	async fn test_confluence_invalid_token() {
+		let server = MockServer::start().await;
+		Mock::given(method("GET"))
+			.and(path("/rest/api/space/DOC"))
+			.respond_with(ResponseTemplate::new(401))
+			.mount(&server)
+			.await;
+
+		let source = ConfluenceSource::new(config(server.uri(), vec!["DOC".to_string()]))
+			.await
+			.unwrap();
+		assert!(source.check_connectivity().await.is_err());
+	}
+}
@@ -113,18 +113,9 @@ impl GoogleDriveSource {
 		}
 
 		if mime_type.starts_with("application/vnd.google-apps.") {
-			match mime_type.as_str() {
-				"application/vnd.google-apps.document" => "application/pdf",
-				"application/vnd.google-apps.spreadsheet" =>
-					"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-				"application/vnd.google-apps.presentation" =>
-					"application/vnd.openxmlformats-officedocument.presentationml.presentation",
-				_ => {
-					return Err(google_drive3::Error::FieldClash(
-						"Unsupported Google Apps file type",
-					));
-				},
-			};
+			if export_mime_type(&mime_type).is_none() {
+				return Err(google_drive3::Error::FieldClash("Unsupported Google Apps file type"));
+			}
 			Ok(resp_obj.into_body())
 		} else {
 			Ok(resp_obj.into_body())
@@ -188,6 +179,14 @@ impl DataSource for GoogleDriveSource {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	fn can_fetch_document(&self) -> bool {
+		true
+	}
+
 	#[instrument(level = "debug", skip(self, range), fields(range.start = range.start, range.end = range.end))]
 	async fn get_slice(&self, path: &Path, range: Range<usize>) -> SourceResult<Vec<u8>> {
 		let file_id = self.get_file_id_by_path(path).await?;
@@ -338,6 +337,9 @@ impl DataSource for GoogleDriveSource {
 										.into(),
 									)
 								})?;
+							let content_type = file.mime_type.as_deref().map(|mime_type| {
+								export_mime_type(mime_type).unwrap_or(mime_type).to_string()
+							});
 							let collected_bytes = CollectedBytes::new(
 								Some(file.name.clone().map(PathBuf::from).unwrap_or_default()),
 								Some(Box::pin(body_to_async_read(content_body))),
@@ -346,7 +348,8 @@ impl DataSource for GoogleDriveSource {
 								Some(file.size.unwrap_or(0) as usize),
 								source_id.clone(),
 								None,
-							);
+							)
+							.with_content_type(content_type);
 							yield Ok(collected_bytes);
 						}
 					}
@@ -363,6 +366,18 @@ impl DataSource for GoogleDriveSource {
 	}
 }
 
+/// Format Google Docs, Sheets and Slides files are exported to.
+fn export_mime_type(mime_type: &str) -> Option<&'static str> {
+	match mime_type {
+		"application/vnd.google-apps.document" => Some("application/pdf"),
+		"application/vnd.google-apps.spreadsheet" =>
+			Some("application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+		"application/vnd.google-apps.presentation" =>
+			Some("application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+		_ => None,
+	}
+}
+
 // Convert hyper::Body to AsyncRead
 fn body_to_async_read(body: Body) -> impl AsyncRead + Send + Unpin {
 	// Create a StreamReader that wraps the Body stream
@@ -399,14 +414,9 @@ async fn download_file(
 	}
 
 	if mime_type.starts_with("application/vnd.google-apps.") {
-		let _export_mime_type = match mime_type.as_str() {
-			"application/vnd.google-apps.document" => "application/pdf",
-			"application/vnd.google-apps.spreadsheet" =>
-				"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
-			"application/vnd.google-apps.presentation" =>
-				"application/vnd.openxmlformats-officedocument.presentationml.presentation",
-			_ => return Err(google_drive3::Error::FieldClash("Unsupported Google Apps file type")),
-		};
+		if export_mime_type(&mime_type).is_none() {
+			return Err(google_drive3::Error::FieldClash("Unsupported Google Apps file type"));
+		}
 
 		Ok(resp_obj.into_body())
 	} else {
@@ -30,10 +30,10 @@ use crate::{
 };
 use async_trait::async_trait;
 
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
 use common::{retry, CollectedBytes};
 use futures::stream::{self, Stream, StreamExt};
 use imap::Session;
+use mailparse::{parse_headers, MailHeaderMap};
 use native_tls::TlsStream;
 use proto::semantics::EmailCollectorConfig;
 use tokio::{
@@ -78,65 +78,6 @@ impl EmailSource {
 			retry_params: common::RetryParams::aggressive(),
 		})
 	}
-
-	fn extract_attachments(&self, email_content: &str) -> Vec<(String, Vec<u8>)> {
-		let mut attachments = Vec::new();
-
-		// Extract the boundary string
-		let boundary = email_content
-			.lines()
-			.find(|line| line.starts_with("Content-Type: multipart"))
-			.and_then(|line| line.split("boundary=").nth(1))
-			.map(|b| b.trim_matches(|c: char| c == '"' || c.is_whitespace()));
-
-		if let Some(boundary) = boundary {
-			// Split the email content into MIME parts using the extracted boundary
-			let parts: Vec<&str> = email_content.split(&format!("--{}", boundary)).collect();
-
-			for part in parts {
-				// Check if this part contains an attachment
-				if part.contains("Content-Disposition: attachment") {
-					let mut filename = String::new();
-					let mut base64_content = String::new();
-
-					// Extract filename and content type
-					for line in part.lines() {
-						if line.contains("filename=") {
-							filename = line
-								.split("filename=")
-								.nth(1)
-								.unwrap_or("")
-								.trim_matches(|c: char| c == '"' || c.is_whitespace())
-								.to_string();
-						}
-					}
-
-					// Extract base64 content
-					if let Some(base64_section) = part.split("\r\n\r\n").nth(1) {
-						base64_content = base64_section
-							.lines()
-							.filter(|line| !line.starts_with("--"))
-							.collect::<Vec<&str>>()
-							.join("")
-							.chars()
-							.filter(|c| {
-								c.is_ascii_alphanumeric() || *c == '+' || *c == '/' || *c == '='
-							})
-							.collect();
-					}
-					if let Ok(decoded) = BASE64.decode(&base64_content) {
-						attachments.push((filename, decoded));
-					} else {
-						eprintln!("Base64 decoding error for file: {}", filename);
-					}
-				}
-			}
-		} else {
-			eprintln!("Could not find multipart boundary in email content");
-		}
-
-		attachments
-	}
 }
 
 #[async_trait]
@@ -157,6 +98,10 @@ impl DataSource for EmailSource {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn copy_to(&self, _path: &Path, output: &mut dyn SendableAsync) -> SourceResult<()> {
 		let session_lock = self.imap_session.clone();
 		let mut session = session_lock.lock().await;
@@ -260,59 +205,36 @@ impl DataSource for EmailSource {
 		let mut collected_messages = Vec::new();
 		for fetch in fetches.into_iter() {
 			if let Some(body) = fetch.body() {
-				let email_content = String::from_utf8(body.to_vec()).expect("Invalid UTF-8");
-				let message_id = email_content
-					.split("Message-ID: <")
-					.nth(1)
-					.and_then(|s| s.split('>').next())
-					.and_then(|s| s.split('@').next())
-					.unwrap_or("");
-
-				let subject = email_content
-					.lines()
-					.find(|line| line.starts_with("Subject:"))
-					.map(|line| line.trim_start_matches("Subject:").trim())
-					.unwrap_or("");
-				let main_text = email_content
-					.split("Content-Type: text/plain; charset=\"UTF-8\"\r\n\r\n")
-					.nth(1)
-					.and_then(|s| s.split("\r\n\r\n--").next())
-					.unwrap_or("");
-				let combined_text = format!("{}, {}", subject, main_text);
-
-				for attachment in self.extract_attachments(&email_content) {
-					let (filename, content) = attachment;
-					let file_extension = Path::new(&filename)
-						.extension()
-						.and_then(|ext| ext.to_str())
-						.map(|ext| ext.to_string())
-						.unwrap_or_else(|| String::new());
-
-					let cursor = Cursor::new(content.to_vec());
-					collected_messages.push(CollectedBytes {
-						data: Some(Box::pin(cursor)),
-						file: Some(PathBuf::from(filename.clone())),
-						source_id: self.source_id.clone(),
-						eof: true,
-						doc_source: Some(format!("email://{}", filename)),
-						extension: Some(file_extension),
-						size: Some(content.len()),
-						_owned_permit: None,
-						image_id: None,
-					});
-				}
+				// The raw message is handed to the eml ingestor, which decodes the MIME tree
+				// and routes each attachment to the ingestor for its type. Malformed headers
+				// are reported by the ingestor rather than failing the whole poll.
+				let headers = parse_headers(body).map(|(headers, _)| headers).unwrap_or_default();
+				let message_id = headers
+					.get_first_value("Message-ID")
+					.map(|id| id.trim().trim_start_matches('<').trim_end_matches('>').to_string())
+					.filter(|id| !id.is_empty())
+					.unwrap_or_else(|| format!("message-{}", fetch.message));
+				let sender = headers
+					.get_first_value("From")
+					.and_then(|from| mailparse::addrparse(&from).ok())
+					.and_then(|addrs| match addrs.first() {
+						Some(mailparse::MailAddr::Single(info)) => Some(info.addr.clone()),
+						_ => None,
+					})
+					.unwrap_or_else(|| "unknown_sender".to_string());
 
 				let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
-				let cursor = Cursor::new(combined_text.as_bytes().to_vec());
+				let cursor = Cursor::new(body.to_vec());
 				collected_messages.push(CollectedBytes {
 					source_id: self.source_id.clone(),
 					data: Some(Box::pin(cursor)),
-					file: Some(PathBuf::from(format!("{}.email", message_id))),
+					file: Some(PathBuf::from(format!("{}.eml", message_id))),
 					eof: true,
-					doc_source: Some("email://unknown_sender".to_string()),
-					extension: Some("txt".to_string()),
+					doc_source: Some(format!("email://{}", sender)),
+					extension: Some("eml".to_string()),
 					size: Some(body.len() as usize),
 					_owned_permit: None,
+					content_type: None,
 					image_id: None,
 				});
 			}
@@ -17,7 +17,8 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use crate::{
-	DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult, REQUEST_SEMAPHORE,
+	DataSource, DocumentFilter, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+	REQUEST_SEMAPHORE,
 };
 use async_trait::async_trait;
 use common::CollectedBytes;
@@ -51,6 +52,7 @@ impl LocalFolderSource {
 	async fn poll_data_recursive<'life0>(
 		&self,
 		folder_path: PathBuf,
+		filter: Option<DocumentFilter>,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
 		let mut streams: Vec<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send>>> =
 			Vec::new();
@@ -74,6 +76,9 @@ impl LocalFolderSource {
 
 				if metadata.is_file() {
 					let file_path = entry.path();
+					if filter.as_ref().is_some_and(|filter| !filter(&file_path.to_string_lossy())) {
+						continue;
+					}
 					let file_stream = self.read_file_and_stream_output(file_path.clone()).await?;
 					streams.push(file_stream);
 				} else if metadata.is_dir() {
@@ -93,10 +98,14 @@ impl LocalFolderSource {
 
 		let stream = async_stream::stream! {
 			let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
-			let file_metadata = fs::metadata(&file_path).await.map_err(SourceError::from)?;
-			let file_size = file_metadata.len() as usize;
 			let file_name = file_path.to_string_lossy().to_string();
-			let file = fs::File::open(&file_path).await.map_err(SourceError::from)?;
+			let file_metadata = fs::metadata(&file_path)
+				.await
+				.map_err(|e| SourceError::from(e).with_document(file_name.clone()))?;
+			let file_size = file_metadata.len() as usize;
+			let file = fs::File::open(&file_path)
+				.await
+				.map_err(|e| SourceError::from(e).with_document(file_name.clone()))?;
 			let reader = BufReader::new(file);
 
 			let collected_bytes = CollectedBytes::new(
@@ -132,6 +141,14 @@ impl DataSource for LocalFolderSource {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	fn can_fetch_document(&self) -> bool {
+		true
+	}
+
 	async fn get_slice(&self, path: &Path, range: Range<usize>) -> SourceResult<Vec<u8>> {
 		let full_path = self.full_path(path);
 		let mut file = fs::File::open(&full_path).await.map_err(SourceError::from)?;
@@ -209,16 +226,23 @@ impl DataSource for LocalFolderSource {
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
-		let result = self.poll_data_recursive(self.folder_path.clone()).await;
+		let result = self.poll_data_recursive(self.folder_path.clone(), None).await;
 
 		result
 	}
+
+	async fn poll_filtered_data(
+		&self,
+		filter: DocumentFilter,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		self.poll_data_recursive(self.folder_path.clone(), Some(filter)).await
+	}
 }
 
 #[cfg(test)]
 mod tests {
 
-	use std::{collections::HashSet, path::PathBuf};
+	use std::{collections::HashSet, path::PathBuf, sync::Arc};
 
 	use futures::StreamExt;
 	use tempfile::TempDir;
@@ -298,4 +322,22 @@ mod tests {
 			"Expected no files to be collected in an empty directory"
 		);
 	}
+	#[tokio::test]
+	async fn test_local_file_collector_filters_before_reading() {
+		let temp_dir = TempDir::new().expect("Failed to create temp directory");
+		for name in ["a.txt", "b.txt", "c.txt"] {
+			std::fs::write(temp_dir.path().join(name), name).unwrap();
+		}
+		let local_storage =
+			LocalFolderSource::new(temp_dir.path().to_path_buf(), "LocalFolderSource".to_string());
+
+		let filter: crate::DocumentFilter = Arc::new(|path: &str| !path.ends_with("b.txt"));
+		let mut stream = local_storage.poll_filtered_data(filter).await.unwrap();
+		let mut files = HashSet::new();
+		while let Some(collected_bytes) = stream.next().await {
+			let file = collected_bytes.unwrap().file.unwrap();
+			files.insert(file.file_name().unwrap().to_string_lossy().to_string());
+		}
+		assert_eq!(files, HashSet::from(["a.txt".to_string(), "c.txt".to_string()]));
+	}
 }
@@ -28,7 +28,8 @@ use proto::semantics::GcsCollectorConfig;
 use tokio::io::{AsyncRead, AsyncWriteExt};
 
 use crate::{
-	DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult, REQUEST_SEMAPHORE,
+	DataSource, DocumentFilter, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+	REQUEST_SEMAPHORE,
 };
 
 #[derive(Debug, Clone, PartialEq)]
@@ -67,6 +68,59 @@ impl OpendalStorage {
 		let op = Operator::new(cfg)?.finish();
 		Ok(Self { op, _bucket: bucket, source_id, retry_params: common::RetryParams::aggressive() })
 	}
+
+	/// Lists the objects and downloads the ones `filter` accepts, all of them without one.
+	fn poll_objects(
+		&self,
+		filter: Option<DocumentFilter>,
+	) -> Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + '_>> {
+		let op = self.op.clone();
+		let source_id = self.source_id.clone();
+		let stream = stream! {
+			let mut object_lister = retry(&self.retry_params, || async {
+				op.lister_with("")
+				.recursive(true)
+				.metakey(Metakey::ContentLength)
+				.await
+				.map_err(
+					|e| SourceError::new(SourceErrorKind::Connection, anyhow::anyhow!("Error listing objects: {:?}", e).into())
+				)
+			}).await?;
+
+			while let Some(object) = object_lister.next().await {
+				let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
+				match object {
+					Ok(object) => {
+						let key = object.path().to_string();
+						// the rejected objects are not downloaded
+						if filter.as_ref().is_some_and(|filter| !filter(&key)) {
+							continue;
+						}
+						let meta = op.stat(&key).await?;
+
+						let file_size = meta.content_length() as usize;
+						let storage_reader: opendal::Reader = op.reader_with(&key).await?;
+						yield Ok(CollectedBytes::new(
+							Some(Path::new(&key).to_path_buf()),
+							Some(Box::pin(storage_reader)),
+							true,
+							Some(key.clone()),
+							Some(file_size),
+							source_id.clone(),
+							None,
+						));
+					}
+					Err(e) => {
+						yield Err(SourceError::new(
+							SourceErrorKind::Io,
+							anyhow::anyhow!("Error listing object: {:?}", e).into()
+						));
+					}
+				}
+			}
+		};
+		Box::pin(stream)
+	}
 }
 
 #[async_trait]
@@ -76,6 +130,14 @@ impl DataSource for OpendalStorage {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	fn can_fetch_document(&self) -> bool {
+		true
+	}
+
 	async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> SourceResult<()> {
 		let path = path.as_os_str().to_string_lossy();
 		let mut storage_reader = self.op.reader(&path).await?;
@@ -121,48 +183,14 @@ impl DataSource for OpendalStorage {
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
-		let op = self.op.clone();
-		let source_id = self.source_id.clone();
-		let stream = stream! {
-			let mut object_lister = retry(&self.retry_params, || async {
-				op.lister_with("")
-				.recursive(true)
-				.metakey(Metakey::ContentLength)
-				.await
-				.map_err(
-					|e| SourceError::new(SourceErrorKind::Connection, anyhow::anyhow!("Error listing objects: {:?}", e).into())
-				)
-			}).await?;
-
-			while let Some(object) = object_lister.next().await {
-				let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
-				match object {
-					Ok(object) => {
-						let key = object.path().to_string();
-						let meta = op.stat(&key).await?;
+		Ok(self.poll_objects(None))
+	}
 
-						let file_size = meta.content_length() as usize;
-						let storage_reader: opendal::Reader = op.reader_with(&key).await?;
-						yield Ok(CollectedBytes::new(
-							Some(Path::new(&key).to_path_buf()),
-							Some(Box::pin(storage_reader)),
-							true,
-							Some(key.clone()),
-							Some(file_size),
-							source_id.clone(),
-							None,
-						));
-					}
-					Err(e) => {
-						yield Err(SourceError::new(
-							SourceErrorKind::Io,
-							anyhow::anyhow!("Error listing object: {:?}", e).into()
-						));
-					}
-				}
-			}
-		};
-		Ok(Box::pin(stream))
+	async fn poll_filtered_data(
+		&self,
+		filter: DocumentFilter,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		Ok(self.poll_objects(Some(filter)))
 	}
 }
 
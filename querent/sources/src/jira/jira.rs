@@ -248,6 +248,10 @@ impl DataSource for JiraSource {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
@@ -0,0 +1,165 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+use tokio::sync::{Mutex, Notify};
+
+/// A topic partition, the unit offsets are tracked for.
+pub type Partition = (String, i32);
+
+/// A message read from a topic partition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StreamMessage {
+	pub topic: String,
+	pub partition: i32,
+	pub offset: i64,
+	pub key: Option<String>,
+	pub payload: Vec<u8>,
+}
+
+/// A consumer group member of a message broker, so the collector does not depend on a
+/// particular broker and can be exercised against the in-process [`MemoryBroker`].
+#[async_trait]
+pub trait MessageBroker: Send + Sync {
+	/// Checks the broker is reachable and serves the topics.
+	async fn check_connectivity(&self, topics: &[String]) -> anyhow::Result<()>;
+
+	/// Joins the group on the topics, resuming from the offsets it committed.
+	async fn subscribe(&self, topics: &[String]) -> anyhow::Result<()>;
+
+	/// Returns up to `max` messages, or none when nothing arrived within `timeout`.
+	async fn receive(&self, max: usize, timeout: Duration) -> anyhow::Result<Vec<StreamMessage>>;
+
+	/// Commits the offset of the next message to read on each partition.
+	async fn commit(&self, offsets: &HashMap<Partition, i64>) -> anyhow::Result<()>;
+}
+
+#[derive(Default)]
+struct MemoryState {
+	partitions: HashMap<Partition, Vec<StreamMessage>>,
+	subscribed: Vec<String>,
+	positions: HashMap<Partition, i64>,
+	committed: HashMap<Partition, i64>,
+}
+
+impl MemoryState {
+	fn take(&mut self, max: usize) -> Vec<StreamMessage> {
+		let mut partitions: Vec<&Partition> = self
+			.partitions
+			.keys()
+			.filter(|(topic, _)| self.subscribed.contains(topic))
+			.collect();
+		partitions.sort();
+		let mut messages = Vec::new();
+		for partition in partitions {
+			let position = self.positions.entry(partition.clone()).or_insert(0);
+			let available = &self.partitions[partition][*position as usize..];
+			let count = available.len().min(max - messages.len());
+			messages.extend_from_slice(&available[..count]);
+			*position += count as i64;
+			if messages.len() == max {
+				break;
+			}
+		}
+		messages
+	}
+}
+
+/// An in-process broker with a single consumer group, standing in for Kafka in tests and
+/// local runs.
+#[derive(Default)]
+pub struct MemoryBroker {
+	state: Mutex<MemoryState>,
+	published: Notify,
+}
+
+impl MemoryBroker {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Appends a message to the partition and returns its offset.
+	pub async fn publish(
+		&self,
+		topic: &str,
+		partition: i32,
+		key: Option<&str>,
+		payload: impl Into<Vec<u8>>,
+	) -> i64 {
+		let mut state = self.state.lock().await;
+		let messages = state.partitions.entry((topic.to_string(), partition)).or_default();
+		let offset = messages.len() as i64;
+		messages.push(StreamMessage {
+			topic: topic.to_string(),
+			partition,
+			offset,
+			key: key.map(str::to_string),
+			payload: payload.into(),
+		});
+		self.published.notify_one();
+		offset
+	}
+
+	/// The offset the group committed on the partition.
+	pub async fn committed(&self, topic: &str, partition: i32) -> Option<i64> {
+		self.state.lock().await.committed.get(&(topic.to_string(), partition)).copied()
+	}
+}
+
+#[async_trait]
+impl MessageBroker for MemoryBroker {
+	async fn check_connectivity(&self, _topics: &[String]) -> anyhow::Result<()> {
+		Ok(())
+	}
+
+	async fn subscribe(&self, topics: &[String]) -> anyhow::Result<()> {
+		let mut state = self.state.lock().await;
+		state.subscribed = topics.to_vec();
+		state.positions = state.committed.clone();
+		Ok(())
+	}
+
+	async fn receive(&self, max: usize, timeout: Duration) -> anyhow::Result<Vec<StreamMessage>> {
+		let deadline = Instant::now() + timeout;
+		loop {
+			let messages = self.state.lock().await.take(max);
+			if !messages.is_empty() {
+				return Ok(messages);
+			}
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			if remaining.is_zero() ||
+				tokio::time::timeout(remaining, self.published.notified()).await.is_err()
+			{
+				return Ok(vec![]);
+			}
+		}
+	}
+
+	async fn commit(&self, offsets: &HashMap<Partition, i64>) -> anyhow::Result<()> {
+		let mut state = self.state.lock().await;
+		for (partition, offset) in offsets {
+			state.committed.insert(partition.clone(), *offset);
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,626 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::{BTreeSet, HashMap},
+	fmt,
+	ops::Range,
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::Arc,
+	time::{Duration, Instant},
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::Stream;
+use proto::semantics::KafkaCollectorConfig;
+use rdkafka::{
+	consumer::{CommitMode, Consumer, StreamConsumer},
+	ClientConfig, Message, Offset, TopicPartitionList,
+};
+use tokio::{io::AsyncRead, sync::Mutex};
+use tracing::warn;
+
+use super::{
+	broker::{MessageBroker, Partition, StreamMessage},
+	payload::PayloadDecoder,
+};
+use crate::{
+	string_to_async_read, CheckpointStore, DataSource, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult,
+};
+
+/// Messages read from the broker at once.
+const BATCH_SIZE: usize = 100;
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a started batch waits for more messages.
+const BATCH_LINGER: Duration = Duration::from_millis(50);
+const METADATA_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A consumer group member of a Kafka compatible cluster, committing offsets manually.
+pub struct KafkaBroker {
+	consumer: Arc<StreamConsumer>,
+}
+
+impl KafkaBroker {
+	pub fn new(config: &KafkaCollectorConfig) -> anyhow::Result<Self> {
+		if config.bootstrap_servers.is_empty() {
+			return Err(anyhow::anyhow!("Bootstrap servers are required"));
+		}
+		if config.group_id.is_empty() {
+			return Err(anyhow::anyhow!("A consumer group id is required"));
+		}
+		let mut client = ClientConfig::new();
+		client
+			.set("bootstrap.servers", &config.bootstrap_servers)
+			.set("group.id", &config.group_id)
+			.set("enable.auto.commit", "false")
+			.set("auto.offset.reset", "earliest");
+		let optional_settings = [
+			("security.protocol", &config.security_protocol),
+			("sasl.mechanism", &config.sasl_mechanism),
+			("sasl.username", &config.sasl_username),
+			("sasl.password", &config.sasl_password),
+		];
+		for (key, value) in optional_settings {
+			if let Some(value) = value.as_ref().filter(|value| !value.is_empty()) {
+				client.set(key, value);
+			}
+		}
+		let consumer: StreamConsumer = client.create()?;
+		Ok(KafkaBroker { consumer: Arc::new(consumer) })
+	}
+}
+
+fn to_stream_message(message: &impl Message) -> StreamMessage {
+	StreamMessage {
+		topic: message.topic().to_string(),
+		partition: message.partition(),
+		offset: message.offset(),
+		key: message.key().map(|key| String::from_utf8_lossy(key).to_string()),
+		payload: message.payload().unwrap_or_default().to_vec(),
+	}
+}
+
+#[async_trait]
+impl MessageBroker for KafkaBroker {
+	async fn check_connectivity(&self, topics: &[String]) -> anyhow::Result<()> {
+		let consumer = self.consumer.clone();
+		let metadata =
+			tokio::task::spawn_blocking(move || consumer.fetch_metadata(None, METADATA_TIMEOUT))
+				.await??;
+		for topic in topics {
+			if !metadata.topics().iter().any(|metadata| metadata.name() == topic) {
+				return Err(anyhow::anyhow!("Topic {} does not exist", topic));
+			}
+		}
+		Ok(())
+	}
+
+	async fn subscribe(&self, topics: &[String]) -> anyhow::Result<()> {
+		let topics: Vec<&str> = topics.iter().map(String::as_str).collect();
+		self.consumer.subscribe(&topics)?;
+		Ok(())
+	}
+
+	async fn receive(&self, max: usize, timeout: Duration) -> anyhow::Result<Vec<StreamMessage>> {
+		let mut messages = Vec::new();
+		let mut deadline = Instant::now() + timeout;
+		while messages.len() < max {
+			let remaining = deadline.saturating_duration_since(Instant::now());
+			match tokio::time::timeout(remaining, self.consumer.recv()).await {
+				Ok(Ok(message)) => messages.push(to_stream_message(&message)),
+				Ok(Err(e)) if messages.is_empty() =>
+					return Err(anyhow::anyhow!("Failed to consume messages: {:?}", e)),
+				Ok(Err(_)) | Err(_) => break,
+			}
+			deadline = deadline.min(Instant::now() + BATCH_LINGER);
+		}
+		Ok(messages)
+	}
+
+	async fn commit(&self, offsets: &HashMap<Partition, i64>) -> anyhow::Result<()> {
+		let mut list = TopicPartitionList::new();
+		for ((topic, partition), offset) in offsets {
+			list.add_partition_offset(topic, *partition, Offset::Offset(*offset))?;
+		}
+		self.consumer.commit(&list, CommitMode::Async)?;
+		Ok(())
+	}
+}
+
+/// Consumes topics as a consumer group member, one document per message, until the
+/// pipeline stops or the configured idle timeout passes without messages. Delivery is at
+/// least once: offsets are committed once the pipeline acknowledges that the knowledge of
+/// the messages is stored, so messages in flight when the node stops are read again. A
+/// replayed message keeps its document name, made of its topic, partition and offset, so
+/// storages keyed by document take it for the same document.
+#[derive(Clone)]
+pub struct KafkaSource {
+	broker: Arc<dyn MessageBroker>,
+	topics: Vec<String>,
+	decoder: PayloadDecoder,
+	idle_timeout: Option<Duration>,
+	source_id: String,
+	checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+	deliveries: Arc<Mutex<Deliveries>>,
+}
+
+/// Messages handed over to the pipeline, and the offsets committed for them.
+#[derive(Default)]
+struct Deliveries {
+	/// Offsets handed over and not acknowledged yet, per partition.
+	pending: HashMap<Partition, BTreeSet<i64>>,
+	/// Partition and offset of the messages handed over, by document.
+	documents: HashMap<String, (Partition, i64)>,
+	/// Offset after the last message read, per partition.
+	read: HashMap<Partition, i64>,
+	/// Offset the group stands at, per partition.
+	committed: HashMap<Partition, i64>,
+	/// Next offset to ingest per `topic/partition`.
+	checkpoints: HashMap<String, i64>,
+}
+
+impl Deliveries {
+	/// Offsets to commit: up to the first message not acknowledged yet, or past the last
+	/// message read.
+	fn committable(&self) -> HashMap<Partition, i64> {
+		self.read
+			.iter()
+			.filter_map(|(partition, read)| {
+				let pending = self.pending.get(partition).and_then(|offsets| offsets.first());
+				let next = *pending.unwrap_or(read);
+				let advances =
+					self.committed.get(partition).is_none_or(|committed| next > *committed);
+				advances.then(|| (partition.clone(), next))
+			})
+			.collect()
+	}
+}
+
+impl fmt::Debug for KafkaSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("KafkaSource")
+			.field("topics", &self.topics)
+			.field("idle_timeout", &self.idle_timeout)
+			.field("source_id", &self.source_id)
+			.finish()
+	}
+}
+
+impl KafkaSource {
+	pub async fn new(config: KafkaCollectorConfig) -> anyhow::Result<Self> {
+		let broker = KafkaBroker::new(&config)?;
+		Self::with_broker(config, Arc::new(broker))
+	}
+
+	/// Consumes through the given broker rather than connecting to the configured servers.
+	pub fn with_broker(
+		config: KafkaCollectorConfig,
+		broker: Arc<dyn MessageBroker>,
+	) -> anyhow::Result<Self> {
+		let topics: Vec<String> =
+			config.topics.into_iter().filter(|topic| !topic.is_empty()).collect();
+		if topics.is_empty() {
+			return Err(anyhow::anyhow!("At least one topic is required"));
+		}
+		let decoder = PayloadDecoder::new(
+			config.payload_format.as_deref(),
+			config.avro_schema.as_deref(),
+			config.text_fields,
+		)?;
+		Ok(KafkaSource {
+			broker,
+			topics,
+			decoder,
+			idle_timeout: config
+				.idle_timeout_ms
+				.filter(|timeout| *timeout > 0)
+				.map(|timeout| Duration::from_millis(timeout as u64)),
+			source_id: config.id,
+			checkpoint_store: None,
+			deliveries: Arc::default(),
+		})
+	}
+
+	pub fn with_checkpoint_store(mut self, checkpoint_store: Arc<dyn CheckpointStore>) -> Self {
+		self.checkpoint_store = Some(checkpoint_store);
+		self
+	}
+
+	/// Next offset to ingest per `topic/partition`.
+	async fn load_checkpoints(&self) -> HashMap<String, i64> {
+		let Some(store) = &self.checkpoint_store else {
+			return HashMap::new();
+		};
+		match store.get_checkpoint(&self.source_id).await {
+			Ok(Some(checkpoint)) => serde_json::from_str(&checkpoint).unwrap_or_else(|e| {
+				warn!("Ignoring unreadable kafka checkpoint for {}: {:?}", self.source_id, e);
+				HashMap::new()
+			}),
+			Ok(None) => HashMap::new(),
+			Err(e) => {
+				warn!("Failed to load kafka checkpoint for {}: {:?}", self.source_id, e);
+				HashMap::new()
+			},
+		}
+	}
+
+	/// Commits, to the group and the checkpoint, the offsets of the messages acknowledged
+	/// without gaps.
+	async fn commit_acknowledged(&self) {
+		let mut deliveries = self.deliveries.lock().await;
+		let offsets = deliveries.committable();
+		if offsets.is_empty() {
+			return;
+		}
+		if let Err(e) = self.broker.commit(&offsets).await {
+			warn!("Failed to commit offsets for {}: {:?}", self.source_id, e);
+			return;
+		}
+		for ((topic, partition), next) in offsets {
+			let next_offset =
+				deliveries.checkpoints.entry(checkpoint_key(&topic, partition)).or_default();
+			*next_offset = (*next_offset).max(next);
+			deliveries.committed.insert((topic, partition), next);
+		}
+		self.save_checkpoints(&deliveries.checkpoints).await;
+	}
+
+	async fn save_checkpoints(&self, checkpoints: &HashMap<String, i64>) {
+		let Some(store) = &self.checkpoint_store else {
+			return;
+		};
+		let checkpoint = match serde_json::to_string(checkpoints) {
+			Ok(checkpoint) => checkpoint,
+			Err(e) => {
+				warn!("Failed to serialize kafka checkpoint: {:?}", e);
+				return;
+			},
+		};
+		if let Err(e) = store.set_checkpoint(&self.source_id, checkpoint).await {
+			warn!("Failed to save kafka checkpoint for {}: {:?}", self.source_id, e);
+		}
+	}
+}
+
+fn checkpoint_key(topic: &str, partition: i32) -> String {
+	format!("{}/{}", topic, partition)
+}
+
+#[async_trait]
+impl DataSource for KafkaSource {
+	async fn check_connectivity(&self) -> anyhow::Result<()> {
+		self.broker.check_connectivity(&self.topics).await
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn get_slice_stream(
+		&self,
+		_path: &Path,
+		_range: Range<usize>,
+	) -> SourceResult<Box<dyn AsyncRead + Send + Unpin>> {
+		Ok(Box::new(string_to_async_read("".to_string())))
+	}
+
+	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn file_num_bytes(&self, _path: &Path) -> SourceResult<u64> {
+		Ok(0)
+	}
+
+	async fn copy_to(&self, _path: &Path, _output: &mut dyn SendableAsync) -> SourceResult<()> {
+		Ok(())
+	}
+
+	fn acknowledges_documents(&self) -> bool {
+		true
+	}
+
+	async fn acknowledge(&self, documents: &[String]) {
+		{
+			let mut deliveries = self.deliveries.lock().await;
+			for document in documents {
+				let Some((partition, offset)) = deliveries.documents.remove(document) else {
+					continue;
+				};
+				if let Some(offsets) = deliveries.pending.get_mut(&partition) {
+					offsets.remove(&offset);
+				}
+			}
+		}
+		self.commit_acknowledged().await;
+	}
+
+	/// Emits every message as a document named after its topic, partition and offset. Offsets
+	/// are committed, to the group and the checkpoint, up to the first message whose document
+	/// the pipeline has not acknowledged yet; messages that yield no document count as
+	/// acknowledged. Messages below the checkpoint are skipped so a group that lost its
+	/// offsets does not ingest them twice.
+	async fn poll_data(
+		&self,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let checkpoints = self.load_checkpoints().await;
+		*self.deliveries.lock().await = Deliveries { checkpoints, ..Default::default() };
+		self.broker.subscribe(&self.topics).await.map_err(|e| {
+			SourceError::new(
+				SourceErrorKind::Connection,
+				anyhow::anyhow!("Failed to subscribe to {:?}: {:?}", self.topics, e).into(),
+			)
+		})?;
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			let mut last_message = Instant::now();
+			loop {
+				let messages = match self.broker.receive(BATCH_SIZE, RECEIVE_TIMEOUT).await {
+					Ok(messages) => messages,
+					Err(e) => {
+						yield Err(SourceError::new(SourceErrorKind::Connection, e.into()));
+						break;
+					}
+				};
+				if messages.is_empty() {
+					if self.idle_timeout.is_some_and(|timeout| last_message.elapsed() >= timeout) {
+						break;
+					}
+					continue;
+				}
+				last_message = Instant::now();
+
+				for message in messages {
+					let key = checkpoint_key(&message.topic, message.partition);
+					let partition = (message.topic.clone(), message.partition);
+					let mut deliveries = self.deliveries.lock().await;
+					// the group already stands at the first message it reads
+					deliveries.committed.entry(partition.clone()).or_insert(message.offset);
+					let read = deliveries.read.entry(partition.clone()).or_default();
+					*read = (*read).max(message.offset + 1);
+					if deliveries.checkpoints.get(&key).is_some_and(|next| message.offset < *next) {
+						continue;
+					}
+					let file_name = format!("{}-{}.message", message.partition, message.offset);
+					let file = PathBuf::from(&message.topic).join(file_name);
+					let text = match self.decoder.text(&message.payload) {
+						Ok(text) => text,
+						Err(e) => {
+							drop(deliveries);
+							yield Err(SourceError::new(SourceErrorKind::Io, e.into())
+								.with_document(file.to_string_lossy()));
+							continue;
+						}
+					};
+					if text.trim().is_empty() {
+						continue;
+					}
+					// registered before the message is handed over, as it may be acknowledged
+					// right away
+					deliveries.pending.entry(partition.clone()).or_default().insert(message.offset);
+					deliveries
+						.documents
+						.insert(file.to_string_lossy().to_string(), (partition, message.offset));
+					drop(deliveries);
+					let size = text.len();
+					yield Ok(CollectedBytes::new(
+						Some(file),
+						Some(Box::pin(string_to_async_read(text))),
+						true,
+						Some(format!("kafka://{}", message.topic)),
+						Some(size),
+						source_id.clone(),
+						None,
+					));
+				}
+
+				self.commit_acknowledged().await;
+			}
+		};
+
+		Ok(Box::pin(stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use futures::StreamExt;
+	use tokio::{io::AsyncReadExt, sync::Mutex};
+
+	use super::*;
+	use crate::kafka::broker::MemoryBroker;
+
+	#[derive(Default)]
+	struct MemoryCheckpoints(Mutex<HashMap<String, String>>);
+
+	#[async_trait]
+	impl CheckpointStore for MemoryCheckpoints {
+		async fn get_checkpoint(&self, source_id: &str) -> anyhow::Result<Option<String>> {
+			Ok(self.0.lock().await.get(source_id).cloned())
+		}
+
+		async fn set_checkpoint(&self, source_id: &str, checkpoint: String) -> anyhow::Result<()> {
+			self.0.lock().await.insert(source_id.to_string(), checkpoint);
+			Ok(())
+		}
+	}
+
+	fn config(topics: &[&str]) -> KafkaCollectorConfig {
+		KafkaCollectorConfig {
+			bootstrap_servers: "localhost:9092".to_string(),
+			topics: topics.iter().map(|topic| topic.to_string()).collect(),
+			group_id: "querent".to_string(),
+			payload_format: None,
+			avro_schema: None,
+			text_fields: vec![],
+			security_protocol: None,
+			sasl_mechanism: None,
+			sasl_username: None,
+			sasl_password: None,
+			idle_timeout_ms: Some(100),
+			id: "kafka".to_string(),
+		}
+	}
+
+	/// Collects the documents of a run, acknowledging them as a pipeline storing them would.
+	async fn collect(source: &KafkaSource) -> Vec<(PathBuf, String)> {
+		let collected = collect_unacknowledged(source).await;
+		let documents: Vec<String> =
+			collected.iter().map(|(file, _)| file.to_string_lossy().to_string()).collect();
+		source.acknowledge(&documents).await;
+		collected
+	}
+
+	async fn collect_unacknowledged(source: &KafkaSource) -> Vec<(PathBuf, String)> {
+		let mut stream = source.poll_data().await.unwrap();
+		let mut collected = Vec::new();
+		while let Some(item) = stream.next().await {
+			let mut item = match item {
+				Ok(item) => item,
+				Err(e) => {
+					collected.push((PathBuf::from(e.document.unwrap()), "error".to_string()));
+					continue;
+				},
+			};
+			let mut text = String::new();
+			item.data.take().unwrap().read_to_string(&mut text).await.unwrap();
+			collected.push((item.file.unwrap(), text));
+		}
+		collected
+	}
+
+	#[tokio::test]
+	async fn test_kafka_messages_as_documents() {
+		let broker = Arc::new(MemoryBroker::new());
+		broker.publish("alerts", 0, None, r#"{"text": "Pump 4 pressure high"}"#).await;
+		broker.publish("alerts", 1, Some("well-7"), r#"{"text": "Gas detected"}"#).await;
+		broker.publish("alerts", 1, None, "not json").await;
+		broker.publish("ignored", 0, None, r#"{"text": "Not subscribed"}"#).await;
+
+		let mut json_config = config(&["alerts"]);
+		json_config.payload_format = Some("json".to_string());
+		json_config.text_fields = vec!["text".to_string()];
+		let checkpoints = Arc::new(MemoryCheckpoints::default());
+		let source = KafkaSource::with_broker(json_config, broker.clone())
+			.unwrap()
+			.with_checkpoint_store(checkpoints.clone());
+
+		let collected = collect(&source).await;
+		assert_eq!(
+			collected,
+			vec![
+				(PathBuf::from("alerts/0-0.message"), "Pump 4 pressure high".to_string()),
+				(PathBuf::from("alerts/1-0.message"), "Gas detected".to_string()),
+				(PathBuf::from("alerts/1-1.message"), "error".to_string()),
+			]
+		);
+		assert_eq!(broker.committed("alerts", 0).await, Some(1));
+		assert_eq!(broker.committed("alerts", 1).await, Some(2));
+		assert_eq!(broker.committed("ignored", 0).await, None);
+
+		// The next run resumes after the committed offsets.
+		broker.publish("alerts", 0, None, r#"{"text": "Pump 4 back to normal"}"#).await;
+		let collected = collect(&source).await;
+		assert_eq!(
+			collected,
+			vec![(PathBuf::from("alerts/0-1.message"), "Pump 4 back to normal".to_string())]
+		);
+		let checkpoint: HashMap<String, i64> =
+			serde_json::from_str(&checkpoints.get_checkpoint("kafka").await.unwrap().unwrap())
+				.unwrap();
+		assert_eq!(
+			checkpoint,
+			HashMap::from([("alerts/0".to_string(), 2), ("alerts/1".to_string(), 2)])
+		);
+	}
+
+	#[tokio::test]
+	async fn test_kafka_checkpoint_skips_ingested_messages() {
+		// A fresh group, as after its offsets expired, starts from the beginning of the topic.
+		let broker = Arc::new(MemoryBroker::new());
+		for text in ["first", "second", "third"] {
+			broker.publish("logs", 0, None, text).await;
+		}
+		let checkpoints = Arc::new(MemoryCheckpoints::default());
+		checkpoints
+			.set_checkpoint("kafka", r#"{"logs/0": 2}"#.to_string())
+			.await
+			.unwrap();
+		let source = KafkaSource::with_broker(config(&["logs"]), broker.clone())
+			.unwrap()
+			.with_checkpoint_store(checkpoints);
+
+		let collected = collect(&source).await;
+		assert_eq!(collected, vec![(PathBuf::from("logs/0-2.message"), "third".to_string())]);
+		assert_eq!(broker.committed("logs", 0).await, Some(3));
+	}
+
+	#[tokio::test]
+	async fn test_kafka_commits_acknowledged_messages_only() {
+		let broker = Arc::new(MemoryBroker::new());
+		for text in ["first", "second", "third"] {
+			broker.publish("logs", 0, None, text).await;
+		}
+		let checkpoints = Arc::new(MemoryCheckpoints::default());
+		let source = KafkaSource::with_broker(config(&["logs"]), broker.clone())
+			.unwrap()
+			.with_checkpoint_store(checkpoints.clone());
+
+		let collected = collect_unacknowledged(&source).await;
+		assert_eq!(collected.len(), 3);
+		assert_eq!(broker.committed("logs", 0).await, None);
+		source
+			.acknowledge(&["logs/0-0.message".to_string(), "logs/0-2.message".to_string()])
+			.await;
+		assert_eq!(broker.committed("logs", 0).await, Some(1));
+
+		// The node stops before the second message is stored: it is read again, and so is
+		// the third one behind it.
+		let collected = collect(&source).await;
+		assert_eq!(
+			collected,
+			vec![
+				(PathBuf::from("logs/0-1.message"), "second".to_string()),
+				(PathBuf::from("logs/0-2.message"), "third".to_string()),
+			]
+		);
+		assert_eq!(broker.committed("logs", 0).await, Some(3));
+		assert_eq!(checkpoints.get_checkpoint("kafka").await.unwrap().unwrap(), r#"{"logs/0":3}"#);
+	}
+
+	#[tokio::test]
+	async fn test_kafka_config_validation() {
+		let broker = Arc::new(MemoryBroker::new());
+		assert!(KafkaSource::with_broker(config(&[]), broker.clone()).is_err());
+		let mut avro_config = config(&["logs"]);
+		avro_config.payload_format = Some("avro".to_string());
+		assert!(KafkaSource::with_broker(avro_config, broker).is_err());
+		let mut no_group = config(&["logs"]);
+		no_group.group_id = String::new();
+		assert!(KafkaBroker::new(&no_group).is_err());
+	}
+}
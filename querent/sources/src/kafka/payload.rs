@@ -0,0 +1,196 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use apache_avro::{from_avro_datum, types::Value as AvroValue, Schema};
+use serde_json::Value;
+
+/// How message payloads are encoded.
+#[derive(Clone, Debug)]
+pub enum PayloadFormat {
+	Text,
+	Json,
+	Avro(Schema),
+}
+
+/// Turns message payloads into the text handed to the ingestors.
+#[derive(Clone, Debug)]
+pub struct PayloadDecoder {
+	format: PayloadFormat,
+	text_fields: Vec<String>,
+}
+
+impl PayloadDecoder {
+	pub fn new(
+		format: Option<&str>,
+		avro_schema: Option<&str>,
+		text_fields: Vec<String>,
+	) -> anyhow::Result<Self> {
+		let format = match format.unwrap_or("text").to_lowercase().as_str() {
+			"" | "text" => PayloadFormat::Text,
+			"json" => PayloadFormat::Json,
+			"avro" => {
+				let schema = avro_schema
+					.ok_or_else(|| anyhow::anyhow!("Avro payloads require an avro schema"))?;
+				PayloadFormat::Avro(Schema::parse_str(schema)?)
+			},
+			other => return Err(anyhow::anyhow!("Unsupported payload format: {}", other)),
+		};
+		let text_fields = text_fields.into_iter().filter(|field| !field.is_empty()).collect();
+		Ok(PayloadDecoder { format, text_fields })
+	}
+
+	pub fn text(&self, payload: &[u8]) -> anyhow::Result<String> {
+		let value = match &self.format {
+			PayloadFormat::Text => return Ok(String::from_utf8_lossy(payload).to_string()),
+			PayloadFormat::Json => serde_json::from_slice(payload)?,
+			PayloadFormat::Avro(schema) => decode_avro(schema, payload)?,
+		};
+		if self.text_fields.is_empty() {
+			return Ok(render_value(&value));
+		}
+		Ok(self
+			.text_fields
+			.iter()
+			.filter_map(|field| lookup(&value, field))
+			.map(render_value)
+			.filter(|text| !text.is_empty())
+			.collect::<Vec<_>>()
+			.join("\n"))
+	}
+}
+
+/// Decodes a single avro datum, with or without the zero byte and four byte schema id
+/// Confluent serializers put in front of it.
+fn decode_avro(schema: &Schema, payload: &[u8]) -> anyhow::Result<Value> {
+	let framed = match payload {
+		[0, _, _, _, _, datum @ ..] => read_datum(schema, datum),
+		_ => None,
+	};
+	let value = match framed.or_else(|| read_datum(schema, payload)) {
+		Some(value) => value,
+		None => return Err(anyhow::anyhow!("Payload does not match the avro schema")),
+	};
+	Ok(Value::try_from(value)?)
+}
+
+/// Only a datum that spans the whole input counts as read.
+fn read_datum(schema: &Schema, mut datum: &[u8]) -> Option<AvroValue> {
+	let value = from_avro_datum(schema, &mut datum, None).ok()?;
+	datum.is_empty().then_some(value)
+}
+
+/// Follows a dotted path through objects and arrays.
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+	path.split('.').try_fold(value, |value, segment| match value {
+		Value::Object(map) => map.get(segment),
+		Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+		_ => None,
+	})
+}
+
+/// Strings as they are, anything nested as `path: value` lines.
+fn render_value(value: &Value) -> String {
+	let mut lines = Vec::new();
+	flatten("", value, &mut lines);
+	lines.join("\n")
+}
+
+fn flatten(prefix: &str, value: &Value, lines: &mut Vec<String>) {
+	let path = |segment: &str| {
+		if prefix.is_empty() {
+			segment.to_string()
+		} else {
+			format!("{}.{}", prefix, segment)
+		}
+	};
+	match value {
+		Value::Object(map) =>
+			for (key, value) in map {
+				flatten(&path(key), value, lines);
+			},
+		Value::Array(items) =>
+			for (index, item) in items.iter().enumerate() {
+				flatten(&path(&index.to_string()), item, lines);
+			},
+		Value::Null => {},
+		scalar => {
+			let text = match scalar {
+				Value::String(text) => text.clone(),
+				other => other.to_string(),
+			};
+			if prefix.is_empty() {
+				lines.push(text);
+			} else {
+				lines.push(format!("{}: {}", prefix, text));
+			}
+		},
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use apache_avro::{to_avro_datum, types::Record};
+
+	use super::*;
+
+	const SCHEMA: &str = r#"{
+		"type": "record",
+		"name": "Reading",
+		"fields": [
+			{"name": "well", "type": "string"},
+			{"name": "depth", "type": "long"},
+			{"name": "note", "type": ["null", "string"]}
+		]
+	}"#;
+
+	#[test]
+	fn test_json_payloads() {
+		let payload =
+			br#"{"id": 7, "body": {"text": "Pressure dropped"}, "tags": ["alert", "well"]}"#;
+		let all = PayloadDecoder::new(Some("json"), None, vec![]).unwrap();
+		assert_eq!(
+			all.text(payload).unwrap(),
+			"body.text: Pressure dropped\nid: 7\ntags.0: alert\ntags.1: well"
+		);
+
+		let fields = vec!["body.text".to_string(), "tags.1".to_string(), "missing".to_string()];
+		let selected = PayloadDecoder::new(Some("json"), None, fields).unwrap();
+		assert_eq!(selected.text(payload).unwrap(), "Pressure dropped\nwell");
+		assert!(selected.text(b"not json").is_err());
+	}
+
+	#[test]
+	fn test_avro_payloads() {
+		let schema = Schema::parse_str(SCHEMA).unwrap();
+		let mut record = Record::new(&schema).unwrap();
+		record.put("well", "A-12");
+		record.put("depth", 3150i64);
+		record.put("note", AvroValue::Union(1, Box::new(AvroValue::String("Gas show".into()))));
+		let datum = to_avro_datum(&schema, record).unwrap();
+		let mut framed = vec![0, 0, 0, 0, 42];
+		framed.extend_from_slice(&datum);
+
+		let decoder =
+			PayloadDecoder::new(Some("avro"), Some(SCHEMA), vec!["note".to_string()]).unwrap();
+		assert_eq!(decoder.text(&datum).unwrap(), "Gas show");
+		assert_eq!(decoder.text(&framed).unwrap(), "Gas show");
+		assert!(decoder.text(b"\x01").is_err());
+		assert!(PayloadDecoder::new(Some("avro"), None, vec![]).is_err());
+		assert!(PayloadDecoder::new(Some("protobuf"), None, vec![]).is_err());
+	}
+}
@@ -17,9 +17,13 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 pub mod source;
+use common::{retry, RetryParams};
+use reqwest::{header::RETRY_AFTER, StatusCode};
 use std::{
+	future::Future,
 	io::{self, Cursor, ErrorKind},
 	path::{Path, PathBuf},
+	time::Duration,
 };
 use tempfile::TempPath;
 use tokio::io::AsyncRead;
@@ -28,18 +32,23 @@ use tracing::error;
 pub use source::*;
 use tokio::fs::File;
 pub mod azure;
+pub mod confluence;
 pub mod drive;
 pub mod email;
 pub mod filesystem;
 pub mod gcs;
 pub mod jira;
+pub mod kafka;
 pub mod news;
 pub mod notion;
 pub mod onedrive;
 pub mod osdu;
 pub mod s3;
 pub mod sales_force;
+pub mod sharepoint;
 pub mod slack;
+pub mod sql;
+pub mod web;
 pub mod zip;
 use once_cell::sync::Lazy;
 use tokio::sync::Semaphore;
@@ -116,6 +125,37 @@ pub fn string_to_async_read(description: String) -> impl AsyncRead + Send + Unpi
 	Cursor::new(description.into_bytes())
 }
 
+/// Sends a request, sending it again while the service answers 429 Too Many Requests after
+/// waiting as long as its `Retry-After` header asks, or the backoff of `retry_params` when
+/// the header is missing. The last throttled response is returned once attempts run out.
+pub async fn send_throttled<F, Fut>(
+	retry_params: &RetryParams,
+	send: F,
+) -> SourceResult<reqwest::Response>
+where
+	F: Fn() -> Fut,
+	Fut: Future<Output = SourceResult<reqwest::Response>>,
+{
+	let mut num_attempts = 0;
+	loop {
+		let response = retry(retry_params, &send).await?;
+		num_attempts += 1;
+		if response.status() != StatusCode::TOO_MANY_REQUESTS ||
+			num_attempts >= retry_params.max_attempts
+		{
+			return Ok(response);
+		}
+		let delay = response
+			.headers()
+			.get(RETRY_AFTER)
+			.and_then(|value| value.to_str().ok())
+			.and_then(|value| value.trim().parse().ok())
+			.map(Duration::from_secs)
+			.unwrap_or_else(|| retry_params.compute_delay(num_attempts));
+		tokio::time::sleep(delay).await;
+	}
+}
+
 pub fn resolve_ingestor_with_extension(extension: &str) -> SourceResult<String> {
 	let programming_languages = vec![
 		"py", "pyw", "pyp", "js", "mjs", "java", "cpp", "h", "hpp", "c", "cs", "rb", "swift",
@@ -281,6 +281,10 @@ impl DataSource for NewsApiClient {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
@@ -358,6 +362,7 @@ impl DataSource for NewsApiClient {
 													size: Some(image_bytes.len()),
 													source_id: source_id.clone(),
 													_owned_permit: None,
+													content_type: None,
 													image_id: Some(image_name),
 													extension: Some(extension),
 												};
@@ -17,50 +17,202 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use std::{
+	collections::{HashSet, VecDeque},
+	fmt,
 	ops::Range,
 	path::{Path, PathBuf},
 	pin::Pin,
+	sync::Arc,
 };
 
 use async_stream::stream;
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
 use common::CollectedBytes;
 use futures::Stream;
 use proto::semantics::NotionConfig;
 use tokio::io::AsyncRead;
+use tracing::warn;
 
 use crate::{
-	string_to_async_read, DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+	string_to_async_read, CheckpointStore, DataSource, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult,
 };
 
 use super::utils::{
-	extract_file_extension, fetch_all_page_ids, fetch_page, format_page, get_images_from_page,
+	extract_file_extension, fetch_all_page_ids, fetch_database_title, fetch_page,
+	fetch_page_content, format_page, format_record, get_images_from_page, plain_text,
+	query_database, search_workspace, Page, PageContent,
 };
 
-#[derive(Clone, Debug)]
+/// Something left to crawl: a page, or a database with its title.
+#[derive(Debug, Clone)]
+enum NotionTarget {
+	Page(String),
+	Database(String, Option<String>),
+}
+
+#[derive(Clone)]
 pub struct NotionSource {
 	api_token: String,
 	page_ids: Vec<String>,
 	source_id: String,
+	crawl_workspace: bool,
+	recursive: bool,
+	checkpoint_store: Option<Arc<dyn CheckpointStore>>,
 	pub retry_params: common::RetryParams,
 }
 
+impl fmt::Debug for NotionSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("NotionSource")
+			.field("page_ids", &self.page_ids)
+			.field("source_id", &self.source_id)
+			.field("crawl_workspace", &self.crawl_workspace)
+			.field("recursive", &self.recursive)
+			.finish()
+	}
+}
+
 impl NotionSource {
 	pub async fn new(config: NotionConfig) -> anyhow::Result<Self> {
 		Ok(NotionSource {
 			api_token: config.api_key.clone(),
+			crawl_workspace: config.crawl_workspace.unwrap_or(false) || config.page_ids.is_empty(),
+			recursive: config.recursive.unwrap_or(true),
 			page_ids: config.page_ids.clone(),
 			source_id: config.id,
+			checkpoint_store: None,
 			retry_params: common::RetryParams::aggressive(),
 		})
 	}
+
+	/// Only pages and rows edited since the previous run are collected once a checkpoint
+	/// store is set.
+	pub fn with_checkpoint_store(mut self, checkpoint_store: Arc<dyn CheckpointStore>) -> Self {
+		self.checkpoint_store = Some(checkpoint_store);
+		self
+	}
+
+	/// `last_edited_time` of the newest page collected by the previous run.
+	async fn load_checkpoint(&self) -> Option<DateTime<Utc>> {
+		let store = self.checkpoint_store.as_ref()?;
+		match store.get_checkpoint(&self.source_id).await {
+			Ok(checkpoint) => checkpoint.and_then(|checkpoint| checkpoint.parse().ok()),
+			Err(e) => {
+				warn!("Failed to load notion checkpoint for {}: {:?}", self.source_id, e);
+				None
+			},
+		}
+	}
+
+	async fn save_checkpoint(&self, checkpoint: DateTime<Utc>) {
+		let Some(store) = &self.checkpoint_store else {
+			return;
+		};
+		if let Err(e) = store.set_checkpoint(&self.source_id, checkpoint.to_rfc3339()).await {
+			warn!("Failed to save notion checkpoint for {}: {:?}", self.source_id, e);
+		}
+	}
+
+	/// Where the crawl starts: everything shared with the integration, or the listed ids.
+	/// Search results already include nested pages, so rows are left to their databases.
+	async fn initial_targets(
+		&self,
+		edited_after: Option<DateTime<Utc>>,
+	) -> SourceResult<Vec<NotionTarget>> {
+		if !self.crawl_workspace {
+			return Ok(self.page_ids.iter().cloned().map(NotionTarget::Page).collect());
+		}
+		let mut targets = Vec::new();
+		for database in
+			search_workspace(&self.api_token, "database", None, &self.retry_params).await?
+		{
+			if let Some(id) = database["id"].as_str() {
+				targets.push(NotionTarget::Database(
+					id.to_string(),
+					Some(plain_text(&database["title"])),
+				));
+			}
+		}
+		for page in
+			search_workspace(&self.api_token, "page", edited_after, &self.retry_params).await?
+		{
+			if page["parent"]["type"].as_str() == Some("database_id") {
+				continue;
+			}
+			if let Some(id) = page["id"].as_str() {
+				targets.push(NotionTarget::Page(id.to_string()));
+			}
+		}
+		Ok(targets)
+	}
+
+	async fn page_content(&self, page_id: &str) -> SourceResult<PageContent> {
+		fetch_page_content(&self.api_token, page_id, &self.retry_params).await
+	}
+}
+
+/// Pages and databases nested in a page, to be crawled after it.
+fn child_targets(content: PageContent) -> impl Iterator<Item = NotionTarget> {
+	let databases = content
+		.child_databases
+		.into_iter()
+		.map(|(id, title)| NotionTarget::Database(id, Some(title)));
+	content.child_pages.into_iter().map(NotionTarget::Page).chain(databases)
+}
+
+/// Page properties followed by the text of its blocks.
+fn render_page(properties: String, content: &PageContent) -> String {
+	if content.lines.is_empty() {
+		properties
+	} else {
+		format!("{}\n\n{}", properties, content.lines.join("\n"))
+	}
+}
+
+fn page_bytes(page_id: &str, data: String, doc_source: String, source_id: &str) -> CollectedBytes {
+	let size = data.len();
+	CollectedBytes::new(
+		Some(PathBuf::from(format!("{}.notion", page_id))),
+		Some(Box::pin(string_to_async_read(data))),
+		true,
+		Some(doc_source),
+		Some(size),
+		source_id.to_string(),
+		None,
+	)
+}
+
+async fn page_images(page: &Page, source_id: &str) -> Vec<CollectedBytes> {
+	let Ok(images) = get_images_from_page(page.properties.properties.clone()).await else {
+		return Vec::new();
+	};
+	images
+		.into_iter()
+		.map(|(image_name, image_bytes)| {
+			let extension = extract_file_extension(&image_name).unwrap_or("").to_string();
+			CollectedBytes {
+				file: Some(PathBuf::from(image_name.clone())),
+				size: Some(image_bytes.len()),
+				data: Some(Box::pin(std::io::Cursor::new(image_bytes))),
+				eof: true,
+				doc_source: Some("notion://image".to_string()),
+				source_id: source_id.to_string(),
+				_owned_permit: None,
+				content_type: None,
+				image_id: Some(image_name),
+				extension: Some(extension),
+			}
+		})
+		.collect()
 }
 
 #[async_trait]
 impl DataSource for NotionSource {
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
-		let page_ids = if self.page_ids.is_empty() {
-			match fetch_all_page_ids(&self.api_token).await {
+		if self.crawl_workspace {
+			let pages = match fetch_all_page_ids(&self.api_token).await {
 				Ok(ids) => ids,
 				Err(err) =>
 					return Err(SourceError::new(
@@ -68,20 +220,33 @@ impl DataSource for NotionSource {
 						anyhow::anyhow!("Failed to fetch page IDs: {:?}", err).into(),
 					)
 					.into()),
+			};
+			if pages.is_empty() {
+				search_workspace(&self.api_token, "database", None, &self.retry_params)
+					.await
+					.map_err(|err| {
+						anyhow::anyhow!("Failed to verify API token, error: {:?}", err)
+					})?;
 			}
-		} else {
-			self.page_ids.clone()
-		};
-		let response = fetch_page(&self.api_token, &page_ids[0], &self.retry_params).await;
+			return Ok(());
+		}
 
+		let response = fetch_page(&self.api_token, &self.page_ids[0], &self.retry_params).await;
 		if response.is_ok() {
-			Ok(())
-		} else {
-			Err(anyhow::anyhow!(
-				"Failed to verify API token or page ID, error: {:?}",
-				response.err()
-			))
+			return Ok(());
+		}
+		// The listed id may be a database rather than a page.
+		if fetch_database_title(&self.api_token, &self.page_ids[0], &self.retry_params)
+			.await
+			.is_ok()
+		{
+			return Ok(());
 		}
+		Err(anyhow::anyhow!("Failed to verify API token or page ID, error: {:?}", response.err()))
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
 	}
 
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
@@ -108,74 +273,150 @@ impl DataSource for NotionSource {
 		Ok(())
 	}
 
+	/// Crawls the listed pages, or the whole workspace, rendering pages as their properties
+	/// followed by their block text and database rows as records. With a checkpoint only
+	/// what was edited since the previous run is emitted, although listed pages are still
+	/// walked to reach their children. Pages edited within the minute before the checkpoint
+	/// are emitted again, as edit times only have minute precision.
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
-		let page_ids = self.page_ids.clone();
-
-		let api_token = self.api_token.clone();
 		let source_id = self.source_id.clone();
+		// Search results already cover nested pages, so only listed pages are followed.
+		let follow_children = self.recursive && !self.crawl_workspace;
 
 		let stream = stream! {
-			let page_ids = if page_ids.is_empty() {
-				match fetch_all_page_ids(&api_token).await {
-					Ok(ids) => ids,
-					Err(err) => {
-						yield Err(SourceError::new(
-							SourceErrorKind::Io,
-							anyhow::anyhow!("Failed to fetch page IDs: {:?}", err).into(),
-						));
-						return;
-					}
+			let checkpoint = self.load_checkpoint().await;
+			// Notion rounds `last_edited_time` down to the minute, so an edit made later in the
+			// minute of the checkpoint carries the checkpoint time itself. Looking a minute
+			// further back collects it, at the cost of emitting that minute's pages again.
+			let edited_after = checkpoint.map(|checkpoint| checkpoint - Duration::minutes(1));
+			let mut queue: VecDeque<NotionTarget> = match self.initial_targets(edited_after).await {
+				Ok(targets) => targets.into(),
+				Err(err) => {
+					yield Err(SourceError::new(
+						err.kind(),
+						anyhow::anyhow!("Failed to fetch page IDs: {:?}", err).into(),
+					));
+					return;
 				}
-			} else {
-				self.page_ids.clone()
 			};
-
-			for page_id in page_ids {
-				match fetch_page(&api_token, &page_id, &self.retry_params).await {
-					Ok(page) => {
-						let page_data = format_page(&page.properties);
-						let page_file_name = format!("{}.notion", page_id);
-
-						yield Ok(CollectedBytes::new(
-							Some(PathBuf::from(page_file_name)),
-							Some(Box::pin(string_to_async_read(page_data.clone()))),
-							true,
-							Some("notion://page".to_string()),
-							Some(page_data.len()),
-							source_id.clone(),
-							None,
-						));
-
-						let res = get_images_from_page(page.properties.properties).await;
-						if let Ok(images) = res {
-							for (image_name, image_bytes) in images {
-								let image_name_path = Some(PathBuf::from(image_name.clone()));
-								let extension = extract_file_extension(&image_name).unwrap_or("").to_string();
-
-								let collected_bytes = CollectedBytes {
-									file: image_name_path,
-									data: Some(Box::pin(std::io::Cursor::new(image_bytes.clone()))),
-									eof: true,
-									doc_source: Some("notion://image".to_string()),
-									size: Some(image_bytes.len()),
-									source_id: source_id.clone(),
-									_owned_permit: None,
-									image_id: Some(image_name),
-									extension: Some(extension),
-								};
-
-								yield Ok(collected_bytes);
+			let mut visited_pages: HashSet<String> = HashSet::new();
+			let mut visited_databases: HashSet<String> = HashSet::new();
+			let mut newest = checkpoint;
+			let mut failed = false;
+
+			while let Some(target) = queue.pop_front() {
+				match target {
+					NotionTarget::Page(page_id) => {
+						if !visited_pages.insert(page_id.replace('-', "")) {
+							continue;
+						}
+						let page = fetch_page(&self.api_token, &page_id, &self.retry_params).await;
+						let page = match page {
+							Ok(page) => page,
+							Err(err) => {
+								// Listed ids may name a database rather than a page.
+								if self.page_ids.contains(&page_id) {
+									queue.push_back(NotionTarget::Database(page_id.clone(), None));
+									continue;
+								}
+								failed = true;
+								yield Err(SourceError::new(
+									SourceErrorKind::Io,
+									anyhow::anyhow!("Error while fetching page: {:?}", err).into(),
+								).with_document(format!("{}.notion", page_id)));
+								continue;
 							}
+						};
+						let changed =
+							edited_after.is_none_or(|after| page.last_edited_time > after);
+						let content = if changed || follow_children {
+							match self.page_content(&page_id).await {
+								Ok(content) => content,
+								Err(err) => {
+									failed = true;
+									yield Err(err.with_document(format!("{}.notion", page_id)));
+									continue;
+								}
+							}
+						} else {
+							PageContent::default()
+						};
+						if changed {
+							newest = newest.max(Some(page.last_edited_time));
+							let page_data = render_page(format_page(&page.properties), &content);
+							let doc_source = "notion://page".to_string();
+							yield Ok(page_bytes(&page_id, page_data, doc_source, &source_id));
+							for image in page_images(&page, &source_id).await {
+								yield Ok(image);
+							}
+						}
+						if follow_children {
+							queue.extend(child_targets(content));
 						}
 					},
-					Err(err) => {
-						yield Err(SourceError::new(
-							SourceErrorKind::Io,
-							anyhow::anyhow!("Error while fetching page: {:?}", err).into(),
-						));
-					}
+					NotionTarget::Database(database_id, title) => {
+						if !visited_databases.insert(database_id.replace('-', "")) {
+							continue;
+						}
+						let title = match title.filter(|title| !title.is_empty()) {
+							Some(title) => title,
+							None => {
+								let api_token = &self.api_token;
+								fetch_database_title(api_token, &database_id, &self.retry_params)
+									.await
+									.unwrap_or_else(|_| database_id.clone())
+							},
+						};
+						let rows = query_database(
+							&self.api_token,
+							&database_id,
+							edited_after,
+							&self.retry_params,
+						)
+						.await;
+						let rows = match rows {
+							Ok(rows) => rows,
+							Err(err) => {
+								failed = true;
+								yield Err(err.with_document(format!("{}.notion", database_id)));
+								continue;
+							}
+						};
+						for row in rows {
+							let row_id = row.id.to_string();
+							if !visited_pages.insert(row_id.replace('-', "")) {
+								continue;
+							}
+							let content = if self.recursive {
+								self.page_content(&row_id).await.unwrap_or_else(|err| {
+									warn!("Failed to fetch content of row {}: {:?}", row_id, err);
+									PageContent::default()
+								})
+							} else {
+								PageContent::default()
+							};
+							newest = newest.max(Some(row.last_edited_time));
+							let record =
+								render_page(format_record(&title, &row.properties), &content);
+							let doc_source = format!("notion://database/{}", database_id);
+							yield Ok(page_bytes(&row_id, record, doc_source, &source_id));
+							for image in page_images(&row, &source_id).await {
+								yield Ok(image);
+							}
+							if follow_children {
+								queue.extend(child_targets(content));
+							}
+						}
+					},
+				}
+			}
+
+			// Pages that failed would be skipped by the next run if the checkpoint moved past them.
+			if !failed {
+				if let Some(newest) = newest.filter(|newest| Some(*newest) != checkpoint) {
+					self.save_checkpoint(newest).await;
 				}
 			}
 		};
@@ -203,6 +444,8 @@ mod tests {
 				.unwrap_or("".to_string()),
 			page_ids: vec![],
 			id: "NS1".to_string(),
+			crawl_workspace: None,
+			recursive: None,
 		};
 
 		let notion_api_client = NotionSource::new(notion_config).await.unwrap();
@@ -249,6 +492,8 @@ mod tests {
 			api_key: "INVALID_API_KEY".to_string(),
 			page_ids: vec![env::var("NOTION_PAGE_ID").unwrap_or_default()],
 			id: "NS1".to_string(),
+			crawl_workspace: None,
+			recursive: None,
 		};
 
 		let notion_api_client = NotionSource::new(notion_config).await.unwrap();
@@ -267,6 +512,8 @@ mod tests {
 			api_key: env::var("NOTION_API_KEY").unwrap_or_default(),
 			page_ids: vec!["INVALID_PAGE_ID".to_string()],
 			id: "NS1".to_string(),
+			crawl_workspace: None,
+			recursive: None,
 		};
 
 		let notion_api_client = NotionSource::new(notion_config).await.unwrap();
@@ -106,6 +106,19 @@ pub fn format_properties(properties: &HashMap<String, PropertyConfiguration>) ->
 }
 
 pub fn format_page(properties: &Properties) -> String {
+	property_entries(properties).join(", ")
+}
+
+/// Renders a database row as a record, one `property: value` line per property under
+/// the name of its database.
+pub fn format_record(database_title: &str, properties: &Properties) -> String {
+	let mut entries = property_entries(properties);
+	entries.sort();
+	format!("Database: {}\n{}", database_title, entries.join("\n"))
+}
+
+/// `property: value` entries of a page, one per value.
+fn property_entries(properties: &Properties) -> Vec<String> {
 	let mut values: Vec<String> = Vec::new();
 
 	for (key, value) in &properties.properties {
@@ -178,7 +191,7 @@ pub fn format_page(properties: &Properties) -> String {
 		}
 	}
 
-	values.join(", ")
+	values
 }
 
 pub async fn fetch_page(
@@ -377,51 +390,302 @@ pub fn extract_file_extension(file_name: &str) -> Option<&str> {
 }
 
 pub async fn fetch_all_page_ids(api_token: &str) -> Result<Vec<String>, SourceError> {
-	let client = Client::new();
-	let url = "https://api.notion.com/v1/search";
+	let pages =
+		search_workspace(api_token, "page", None, &common::RetryParams::aggressive()).await?;
+	Ok(pages
+		.iter()
+		.filter_map(|page| page["id"].as_str().map(|s| s.to_string()))
+		.collect())
+}
 
-	let body = json!({
-		"filter": {
-			"property": "object",
-			"value": "page"
+/// Sends a request to the Notion API and returns the JSON body of a successful response.
+async fn notion_request(
+	client: &Client,
+	api_token: &str,
+	method: reqwest::Method,
+	url: &str,
+	body: Option<&serde_json::Value>,
+	retry_params: &common::RetryParams,
+) -> Result<serde_json::Value, SourceError> {
+	let response = retry(retry_params, || async {
+		let mut request = client
+			.request(method.clone(), url)
+			.bearer_auth(api_token)
+			.header("Notion-Version", "2022-06-28");
+		if let Some(body) = body {
+			request = request.json(body);
 		}
-	});
-
-	let response = client
-		.post(url)
-		.bearer_auth(api_token)
-		.header("Notion-Version", "2022-06-28")
-		.json(&body)
-		.send()
-		.await
-		.map_err(|err| {
+		request.send().await.map_err(|err| {
 			SourceError::new(
 				SourceErrorKind::Io,
 				anyhow::anyhow!("Error while making request to Notion API: {:?}", err).into(),
 			)
-		})?;
+		})
+	})
+	.await?;
 
-	if response.status().is_success() {
-		let list_response: serde_json::Value =
-			response.json::<serde_json::Value>().await.map_err(|err| {
+	let status = response.status();
+	if !status.is_success() {
+		let kind = if status == reqwest::StatusCode::NOT_FOUND {
+			SourceErrorKind::NotFound
+		} else if status == reqwest::StatusCode::UNAUTHORIZED {
+			SourceErrorKind::Unauthorized
+		} else {
+			SourceErrorKind::Io
+		};
+		return Err(SourceError::new(
+			kind,
+			anyhow::anyhow!("Notion API request to {} failed with status {}", url, status).into(),
+		));
+	}
+	response.json::<serde_json::Value>().await.map_err(|err| {
+		SourceError::new(
+			SourceErrorKind::Io,
+			anyhow::anyhow!("Error parsing Notion response: {:?}", err).into(),
+		)
+	})
+}
+
+/// Lists every page or database (`object`) shared with the integration, most recently
+/// edited first, stopping at the first one not edited after `edited_after`.
+pub async fn search_workspace(
+	api_token: &str,
+	object: &str,
+	edited_after: Option<DateTime<Utc>>,
+	retry_params: &common::RetryParams,
+) -> Result<Vec<serde_json::Value>, SourceError> {
+	let client = Client::new();
+	let mut results = Vec::new();
+	let mut cursor: Option<String> = None;
+	loop {
+		let mut body = json!({
+			"filter": { "property": "object", "value": object },
+			"sort": { "direction": "descending", "timestamp": "last_edited_time" },
+			"page_size": 100,
+		});
+		if let Some(cursor) = &cursor {
+			body["start_cursor"] = json!(cursor);
+		}
+		let response = notion_request(
+			&client,
+			api_token,
+			reqwest::Method::POST,
+			"https://api.notion.com/v1/search",
+			Some(&body),
+			retry_params,
+		)
+		.await?;
+		for result in response["results"].as_array().cloned().unwrap_or_default() {
+			if let Some(edited_after) = edited_after {
+				if last_edited_time(&result).is_some_and(|edited| edited <= edited_after) {
+					return Ok(results);
+				}
+			}
+			results.push(result);
+		}
+		cursor = next_cursor(&response);
+		if cursor.is_none() {
+			break;
+		}
+	}
+	Ok(results)
+}
+
+/// Rows of a database, optionally only those edited after `edited_after`.
+pub async fn query_database(
+	api_token: &str,
+	database_id: &str,
+	edited_after: Option<DateTime<Utc>>,
+	retry_params: &common::RetryParams,
+) -> Result<Vec<Page>, SourceError> {
+	let client = Client::new();
+	let url = format!("https://api.notion.com/v1/databases/{}/query", database_id);
+	let mut rows = Vec::new();
+	let mut cursor: Option<String> = None;
+	loop {
+		let mut body = json!({ "page_size": 100 });
+		if let Some(edited_after) = edited_after {
+			body["filter"] = json!({
+				"timestamp": "last_edited_time",
+				"last_edited_time": { "after": edited_after.to_rfc3339() },
+			});
+		}
+		if let Some(cursor) = &cursor {
+			body["start_cursor"] = json!(cursor);
+		}
+		let response = notion_request(
+			&client,
+			api_token,
+			reqwest::Method::POST,
+			&url,
+			Some(&body),
+			retry_params,
+		)
+		.await?;
+		for row in response["results"].as_array().cloned().unwrap_or_default() {
+			let row = serde_json::from_value::<Page>(row).map_err(|err| {
 				SourceError::new(
 					SourceErrorKind::Io,
-					anyhow::anyhow!("Error parsing Notion response: {:?}", err).into(),
+					anyhow::anyhow!("Error parsing database row: {:?}", err).into(),
 				)
 			})?;
+			rows.push(row);
+		}
+		cursor = next_cursor(&response);
+		if cursor.is_none() {
+			break;
+		}
+	}
+	Ok(rows)
+}
 
-		let page_ids = list_response["results"]
-			.as_array()
-			.unwrap_or(&vec![])
-			.iter()
-			.filter_map(|page| page["id"].as_str().map(|s| s.to_string()))
-			.collect();
+/// Title of a database.
+pub async fn fetch_database_title(
+	api_token: &str,
+	database_id: &str,
+	retry_params: &common::RetryParams,
+) -> Result<String, SourceError> {
+	let url = format!("https://api.notion.com/v1/databases/{}", database_id);
+	let database =
+		notion_request(&Client::new(), api_token, reqwest::Method::GET, &url, None, retry_params)
+			.await?;
+	Ok(plain_text(&database["title"]))
+}
 
-		Ok(page_ids)
-	} else {
-		Err(SourceError::new(
-			SourceErrorKind::Io,
-			anyhow::anyhow!("Failed to fetch pages from Notion API").into(),
-		))
+/// Text of a page body along with the pages and databases nested in it.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PageContent {
+	pub lines: Vec<String>,
+	pub child_pages: Vec<String>,
+	/// Id and title of each child database.
+	pub child_databases: Vec<(String, String)>,
+}
+
+/// Walks the blocks of a page, descending into nested blocks but not into child pages
+/// or child databases, which are returned so the caller can crawl them as documents.
+pub async fn fetch_page_content(
+	api_token: &str,
+	page_id: &str,
+	retry_params: &common::RetryParams,
+) -> Result<PageContent, SourceError> {
+	let client = Client::new();
+	let mut content = PageContent::default();
+	// Depth first, so nested blocks follow their parent in the rendered text.
+	let mut pending = vec![(page_id.to_string(), None::<String>, 0usize)];
+	while let Some((block_id, mut cursor, depth)) = pending.pop() {
+		let mut children = Vec::new();
+		loop {
+			let mut url =
+				format!("https://api.notion.com/v1/blocks/{}/children?page_size=100", block_id);
+			if let Some(cursor) = &cursor {
+				url.push_str(&format!("&start_cursor={}", cursor));
+			}
+			let response =
+				notion_request(&client, api_token, reqwest::Method::GET, &url, None, retry_params)
+					.await?;
+			children.extend(response["results"].as_array().cloned().unwrap_or_default());
+			cursor = next_cursor(&response);
+			if cursor.is_none() {
+				break;
+			}
+		}
+
+		let mut nested = Vec::new();
+		for block in &children {
+			let id = block["id"].as_str().unwrap_or_default().to_string();
+			match block["type"].as_str() {
+				Some("child_page") => content.child_pages.push(id),
+				Some("child_database") => content.child_databases.push((
+					id,
+					block["child_database"]["title"].as_str().unwrap_or("").to_string(),
+				)),
+				_ => {
+					if let Some(text) = block_text(block) {
+						content.lines.push(format!("{}{}", "  ".repeat(depth), text));
+					}
+					if block["has_children"].as_bool().unwrap_or(false) {
+						nested.push((id, None, depth + 1));
+					}
+				},
+			}
+		}
+		// Reversed so the first nested block is walked first.
+		pending.extend(nested.into_iter().rev());
+	}
+	Ok(content)
+}
+
+/// Plain text of a block, prefixed the way it would read in Markdown.
+pub fn block_text(block: &serde_json::Value) -> Option<String> {
+	let block_type = block["type"].as_str()?;
+	let data = &block[block_type];
+	let text = match block_type {
+		"table_row" =>
+			data["cells"].as_array()?.iter().map(plain_text).collect::<Vec<_>>().join(" | "),
+		"child_page" | "child_database" => data["title"].as_str()?.to_string(),
+		_ => plain_text(&data["rich_text"]),
+	};
+	if text.trim().is_empty() {
+		return None;
+	}
+	let prefix = match block_type {
+		"heading_1" => "# ",
+		"heading_2" => "## ",
+		"heading_3" => "### ",
+		"bulleted_list_item" => "- ",
+		"numbered_list_item" => "1. ",
+		"quote" => "> ",
+		"to_do" if data["checked"].as_bool().unwrap_or(false) => "[x] ",
+		"to_do" => "[ ] ",
+		_ => "",
+	};
+	Some(format!("{}{}", prefix, text))
+}
+
+/// Concatenated `plain_text` of a rich text array.
+pub fn plain_text(rich_text: &serde_json::Value) -> String {
+	rich_text
+		.as_array()
+		.map(|parts| parts.iter().filter_map(|part| part["plain_text"].as_str()).collect())
+		.unwrap_or_default()
+}
+
+pub fn last_edited_time(object: &serde_json::Value) -> Option<DateTime<Utc>> {
+	object["last_edited_time"].as_str()?.parse().ok()
+}
+
+fn next_cursor(response: &serde_json::Value) -> Option<String> {
+	if !response["has_more"].as_bool().unwrap_or(false) {
+		return None;
+	}
+	response["next_cursor"].as_str().map(|cursor| cursor.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_block_text() {
+		let heading = json!({
+			"type": "heading_2",
+			"heading_2": { "rich_text": [{ "plain_text": "Field " }, { "plain_text": "notes" }] }
+		});
+		assert_eq!(block_text(&heading).as_deref(), Some("## Field notes"));
+
+		let todo = json!({
+			"type": "to_do",
+			"to_do": { "rich_text": [{ "plain_text": "Log core" }], "checked": true }
+		});
+		assert_eq!(block_text(&todo).as_deref(), Some("[x] Log core"));
+
+		let row = json!({
+			"type": "table_row",
+			"table_row": { "cells": [[{ "plain_text": "Well A" }], [{ "plain_text": "3000 m" }]] }
+		});
+		assert_eq!(block_text(&row).as_deref(), Some("Well A | 3000 m"));
+
+		let divider = json!({ "type": "divider", "divider": {} });
+		assert_eq!(block_text(&divider), None);
 	}
 }
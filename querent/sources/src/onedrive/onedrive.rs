@@ -103,6 +103,7 @@ impl OneDriveSource {
 			get(url).await.map_err(|e| SourceError {
 				kind: SourceErrorKind::Connection,
 				source: Arc::new(anyhow!("Got error while downloading file: {}", e)),
+				document: None,
 			})
 		})
 		.await?;
@@ -145,6 +146,10 @@ impl DataSource for OneDriveSource {
 		}
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn copy_to_file(&self, path: &Path, output_path: &Path) -> SourceResult<u64> {
 		default_copy_to_file(self, path, output_path).await
 	}
@@ -155,7 +160,11 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		for drive_item in drive_items {
 			if let Some(_file) = &drive_item.file {
 				if let Some(download_url) = &drive_item.download_url {
@@ -165,19 +174,25 @@ impl DataSource for OneDriveSource {
 					bytes_stream.read_to_end(&mut bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Failed to read file: {}", e)),
+						document: None,
 					})?;
 					let slice = bytes
 						.get(range)
 						.ok_or_else(|| SourceError {
 							kind: SourceErrorKind::Io,
 							source: Arc::new(anyhow!("Failed to get slice from bytes")),
+							document: None,
 						})?
 						.to_vec();
 					return Ok(slice);
 				}
 			}
 		}
-		Err(SourceError { kind: SourceErrorKind::Io, source: Arc::new(anyhow!("No files found")) })
+		Err(SourceError {
+			kind: SourceErrorKind::Io,
+			source: Arc::new(anyhow!("No files found")),
+			document: None,
+		})
 	}
 
 	#[instrument(level = "debug", skip(self, range), fields(range.start = range.start, range.end = range.end))]
@@ -190,7 +205,11 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		for drive_item in drive_items {
 			if let Some(_file) = &drive_item.file {
 				if let Some(download_url) = &drive_item.download_url {
@@ -200,19 +219,25 @@ impl DataSource for OneDriveSource {
 					bytes_stream.read_to_end(&mut bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Failed to read file: {}", e)),
+						document: None,
 					})?;
 					let slice = bytes
 						.get(range)
 						.ok_or_else(|| SourceError {
 							kind: SourceErrorKind::Io,
 							source: Arc::new(anyhow!("Failed to get slice from bytes")),
+							document: None,
 						})?
 						.to_vec();
 					return Ok(Box::new(Cursor::new(slice)));
 				}
 			}
 		}
-		Err(SourceError { kind: SourceErrorKind::Io, source: Arc::new(anyhow!("No files found")) })
+		Err(SourceError {
+			kind: SourceErrorKind::Io,
+			source: Arc::new(anyhow!("No files found")),
+			document: None,
+		})
 	}
 
 	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
@@ -220,7 +245,11 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		for drive_item in drive_items {
 			if let Some(_file) = &drive_item.file {
 				if let Some(download_url) = &drive_item.download_url {
@@ -230,12 +259,17 @@ impl DataSource for OneDriveSource {
 					bytes_stream.read_to_end(&mut bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Failed to read file: {}", e)),
+						document: None,
 					})?;
 					return Ok(bytes);
 				}
 			}
 		}
-		Err(SourceError { kind: SourceErrorKind::Io, source: Arc::new(anyhow!("No files found")) })
+		Err(SourceError {
+			kind: SourceErrorKind::Io,
+			source: Arc::new(anyhow!("No files found")),
+			document: None,
+		})
 	}
 
 	async fn poll_data(
@@ -245,13 +279,17 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		let source_id = self.source_id.clone();
 
 		let stream = stream! {
 			for drive_item in drive_items {
 				let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
-				if let Some(_file) = &drive_item.file {
+				if let Some(file) = &drive_item.file {
 					let name = drive_item.name.clone().unwrap_or_default();
 					let extension = Self::get_file_extension(&name);
 					let size = drive_item.size.unwrap_or(0);
@@ -266,6 +304,10 @@ impl DataSource for OneDriveSource {
 							size: Some(size as usize),
 							source_id: source_id.clone(),
 							_owned_permit: None,
+							content_type: file
+								.get("mimeType")
+								.and_then(|mime_type| mime_type.as_str())
+								.map(str::to_string),
 							image_id: None,
 						});
 					}
@@ -280,7 +322,11 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		for drive_item in drive_items {
 			if let Some(_file) = &drive_item.file {
 				if let Some(download_url) = &drive_item.download_url {
@@ -290,12 +336,17 @@ impl DataSource for OneDriveSource {
 					bytes_stream.read_to_end(&mut bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Failed to read file: {}", e)),
+						document: None,
 					})?;
 					return Ok(bytes.len() as u64);
 				}
 			}
 		}
-		Err(SourceError { kind: SourceErrorKind::Io, source: Arc::new(anyhow!("No files found")) })
+		Err(SourceError {
+			kind: SourceErrorKind::Io,
+			source: Arc::new(anyhow!("No files found")),
+			document: None,
+		})
 	}
 
 	async fn copy_to(&self, _path: &Path, output: &mut dyn SendableAsync) -> SourceResult<()> {
@@ -303,7 +354,11 @@ impl DataSource for OneDriveSource {
 			.onedrive
 			.list_children(ItemLocation::from_path(&self.folder_path).unwrap())
 			.await
-			.map_err(|e| SourceError { kind: SourceErrorKind::Io, source: Arc::new(e.into()) })?;
+			.map_err(|e| SourceError {
+				kind: SourceErrorKind::Io,
+				source: Arc::new(e.into()),
+				document: None,
+			})?;
 		for drive_item in drive_items {
 			if let Some(_file) = &drive_item.file {
 				if let Some(download_url) = &drive_item.download_url {
@@ -313,10 +368,12 @@ impl DataSource for OneDriveSource {
 					bytes_stream.read_to_end(&mut bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Failed to read file: {}", e)),
+						document: None,
 					})?;
 					output.write_all(&bytes).await.map_err(|e| SourceError {
 						kind: SourceErrorKind::Io,
 						source: Arc::new(anyhow!("Got error while writing to output: {}", e)),
+						document: None,
 					})?;
 				}
 			}
@@ -119,6 +119,10 @@ impl DataSource for OSDUStorageService {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.config.id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
@@ -175,6 +179,7 @@ impl DataSource for OSDUStorageService {
 								size: Some(size),
 								source_id: record_id.clone(),
 								_owned_permit: None,
+								content_type: None,
 								image_id: None,
 							};
 
@@ -216,6 +221,7 @@ impl DataSource for OSDUStorageService {
 													size: file_size,
 													source_id: format!("{}", record_id.clone()),
 													_owned_permit: None,
+													content_type: None,
 													image_id: None,
 												};
 												yield Ok(file_collected_bytes);
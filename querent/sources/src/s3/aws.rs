@@ -19,8 +19,8 @@
 use std::{io, ops::Range, path::Path, pin::Pin};
 
 use crate::{
-	s3::retry::aws_retry, DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult,
-	REQUEST_SEMAPHORE,
+	s3::retry::aws_retry, DataSource, DocumentFilter, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult, REQUEST_SEMAPHORE,
 };
 use async_stream::stream;
 use async_trait::async_trait;
@@ -146,6 +146,14 @@ impl DataSource for S3Source {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	fn can_fetch_document(&self) -> bool {
+		true
+	}
+
 	async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> SourceResult<()> {
 		let get_object_output =
 			self.create_get_object_request(path, None).await.map_err(|err| {
@@ -223,6 +231,24 @@ impl DataSource for S3Source {
 			continuation_token_start,
 			source_id,
 			self.retry_params,
+			None,
+		)
+		.await;
+
+		Ok(Box::pin(stream))
+	}
+
+	async fn poll_filtered_data(
+		&self,
+		filter: DocumentFilter,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let stream = create_poll_data_stream(
+			self.s3_client.clone().unwrap(),
+			self.bucket_name.clone(),
+			self.continuation_token.clone(),
+			self.source_id.clone(),
+			self.retry_params,
+			Some(filter),
 		)
 		.await;
 
@@ -236,6 +262,7 @@ async fn create_poll_data_stream(
 	continuation_token_start: Option<String>,
 	source_id: String,
 	retry_params: RetryParams,
+	filter: Option<DocumentFilter>,
 ) -> impl Stream<Item = SourceResult<CollectedBytes>> + Send + 'static {
 	stream! {
 		let mut continuation_token = continuation_token_start;
@@ -255,6 +282,10 @@ async fn create_poll_data_stream(
 				for object in contents {
 					let _permit = REQUEST_SEMAPHORE.acquire().await.unwrap();
 					if let Some(key) = object.key {
+						// the rejected objects are not downloaded
+						if filter.as_ref().is_some_and(|filter| !filter(&key)) {
+							continue;
+						}
 						let get_object_output = s3_client
 							.get_object()
 							.bucket(&bucket_name)
@@ -269,6 +300,7 @@ async fn create_poll_data_stream(
 								)
 							})?;
 						let file_size = get_object_output.content_length();
+						let content_type = get_object_output.content_type().map(str::to_string);
 						let collected_bytes = CollectedBytes::new(
 							Some(Path::new(&key).to_path_buf()),
 							Some(Box::pin(get_object_output.body.into_async_read())),
@@ -277,7 +309,8 @@ async fn create_poll_data_stream(
 							Some(file_size.unwrap_or(0) as usize),
 							source_id.clone(),
 							None,
-						);
+						)
+						.with_content_type(content_type);
 						yield Ok(collected_bytes);
 					}
 				}
@@ -102,6 +102,10 @@ impl DataSource for SalesForceApiClient {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
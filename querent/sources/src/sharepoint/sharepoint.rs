@@ -0,0 +1,725 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::VecDeque,
+	fmt,
+	io::Cursor,
+	ops::Range,
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::Arc,
+};
+
+use anyhow::anyhow;
+use async_stream::stream;
+use async_trait::async_trait;
+use bytes::Bytes;
+use common::{CollectedBytes, RetryParams};
+use futures::Stream;
+use proto::semantics::SharePointConfig;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::{io::AsyncRead, sync::Mutex};
+
+use crate::{
+	send_throttled, string_to_async_read, DataSource, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult,
+};
+
+const LOGIN_URL: &str = "https://login.microsoftonline.com";
+const GRAPH_URL: &str = "https://graph.microsoft.com/v1.0";
+const GRAPH_SCOPE: &str = "https://graph.microsoft.com/.default offline_access";
+
+#[derive(Deserialize)]
+struct TokenResponse {
+	access_token: String,
+	refresh_token: Option<String>,
+}
+
+/// A site and the name its documents are reported under.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SharePointSite {
+	pub id: String,
+	pub name: String,
+}
+
+/// A file of a document library, with its path inside the library.
+#[derive(Clone, Debug, PartialEq)]
+struct DriveFile {
+	path: PathBuf,
+	size: Option<usize>,
+	/// Pre-authenticated link when Graph returned one, the content endpoint otherwise.
+	download_url: Option<String>,
+	content_path: String,
+	content_type: Option<String>,
+}
+
+/// One page of a folder listing: its files, its subfolders as (listing, path) and the link
+/// to the next page.
+#[derive(Debug, Default)]
+struct DrivePage {
+	files: Vec<DriveFile>,
+	folders: Vec<(String, PathBuf)>,
+	next: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct SharePointSource {
+	client: Client,
+	login_url: String,
+	graph_url: String,
+	client_id: String,
+	client_secret: String,
+	redirect_uri: String,
+	tenant_id: String,
+	refresh_token: Arc<Mutex<String>>,
+	access_token: Arc<Mutex<Option<String>>>,
+	sites: Vec<String>,
+	drives: Vec<String>,
+	include_lists: bool,
+	source_id: String,
+	pub retry_params: RetryParams,
+}
+
+impl fmt::Debug for SharePointSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SharePointSource")
+			.field("tenant_id", &self.tenant_id)
+			.field("sites", &self.sites)
+			.field("drives", &self.drives)
+			.field("source_id", &self.source_id)
+			.finish()
+	}
+}
+
+impl SharePointSource {
+	pub async fn new(config: SharePointConfig) -> anyhow::Result<Self> {
+		Self::with_endpoints(config, LOGIN_URL, GRAPH_URL).await
+	}
+
+	/// Talks to other login and Graph endpoints, such as a national cloud or a mock server.
+	pub async fn with_endpoints(
+		config: SharePointConfig,
+		login_url: &str,
+		graph_url: &str,
+	) -> anyhow::Result<Self> {
+		let tenant_id = if config.tenant_id.is_empty() {
+			"organizations".to_string()
+		} else {
+			config.tenant_id
+		};
+		let source = SharePointSource {
+			client: Client::new(),
+			login_url: login_url.trim_end_matches('/').to_string(),
+			graph_url: graph_url.trim_end_matches('/').to_string(),
+			client_id: config.client_id,
+			client_secret: config.client_secret,
+			redirect_uri: config.redirect_uri,
+			tenant_id,
+			refresh_token: Arc::new(Mutex::new(config.refresh_token)),
+			access_token: Arc::new(Mutex::new(None)),
+			sites: config.sites,
+			drives: config.drives,
+			include_lists: config.include_lists.unwrap_or(false),
+			source_id: config.id,
+			retry_params: RetryParams::aggressive(),
+		};
+		// Like OneDrive, a bad refresh token fails the collector creation.
+		source.refresh_access_token().await?;
+		Ok(source)
+	}
+
+	/// Exchanges the refresh token for an access token, keeping the new refresh token
+	/// Microsoft may hand back.
+	async fn refresh_access_token(&self) -> anyhow::Result<String> {
+		let mut refresh_token = self.refresh_token.lock().await;
+		let url = format!("{}/{}/oauth2/v2.0/token", self.login_url, self.tenant_id);
+		let response = self
+			.client
+			.post(url)
+			.form(&[
+				("client_id", self.client_id.as_str()),
+				("client_secret", self.client_secret.as_str()),
+				("redirect_uri", self.redirect_uri.as_str()),
+				("refresh_token", refresh_token.as_str()),
+				("grant_type", "refresh_token"),
+				("scope", GRAPH_SCOPE),
+			])
+			.send()
+			.await?;
+		if !response.status().is_success() {
+			let error = response.text().await.unwrap_or_default();
+			return Err(anyhow!("Login failed: {}", error));
+		}
+		let token = response.json::<TokenResponse>().await?;
+		if let Some(new_refresh_token) = token.refresh_token {
+			*refresh_token = new_refresh_token;
+		}
+		*self.access_token.lock().await = Some(token.access_token.clone());
+		Ok(token.access_token)
+	}
+
+	async fn access_token(&self) -> anyhow::Result<String> {
+		if let Some(token) = self.access_token.lock().await.clone() {
+			return Ok(token);
+		}
+		self.refresh_access_token().await
+	}
+
+	fn url(&self, path: &str) -> String {
+		if path.starts_with("http") {
+			path.to_string()
+		} else {
+			format!("{}{}", self.graph_url, path)
+		}
+	}
+
+	/// Sends a Graph request, logging in again once if the access token has expired.
+	async fn graph_get(&self, path: &str) -> SourceResult<reqwest::Response> {
+		let url = self.url(path);
+		let mut refreshed = false;
+		loop {
+			let token = self.access_token().await.map_err(|err| {
+				SourceError::new(SourceErrorKind::Unauthorized, anyhow!("{:?}", err).into())
+			})?;
+			let response = send_throttled(&self.retry_params, || async {
+				self.client.get(&url).bearer_auth(&token).send().await.map_err(|err| {
+					SourceError::new(
+						SourceErrorKind::Connection,
+						anyhow!("Error making the Graph request: {:?}", err).into(),
+					)
+				})
+			})
+			.await?;
+
+			let status = response.status();
+			if status == StatusCode::UNAUTHORIZED && !refreshed {
+				refreshed = true;
+				*self.access_token.lock().await = None;
+				continue;
+			}
+			return check_status(&url, response);
+		}
+	}
+
+	async fn graph_json(&self, path: &str) -> SourceResult<Value> {
+		self.graph_get(path).await?.json::<Value>().await.map_err(|err| {
+			SourceError::new(
+				SourceErrorKind::Io,
+				anyhow!("Error parsing the Graph response: {:?}", err).into(),
+			)
+		})
+	}
+
+	/// Follows `@odata.nextLink` to collect every value of a listing.
+	async fn graph_list(&self, path: &str) -> SourceResult<Vec<Value>> {
+		let mut values = Vec::new();
+		let mut next = Some(path.to_string());
+		while let Some(path) = next {
+			let response = self.graph_json(&path).await?;
+			values.extend(response["value"].as_array().cloned().unwrap_or_default());
+			next = response["@odata.nextLink"].as_str().map(str::to_string);
+		}
+		Ok(values)
+	}
+
+	/// The configured sites, or every site the user can search.
+	pub async fn sites(&self) -> SourceResult<Vec<SharePointSite>> {
+		let sites = if self.sites.is_empty() {
+			self.graph_list("/sites?search=*").await?
+		} else {
+			let mut sites = Vec::new();
+			for site in &self.sites {
+				sites.push(self.graph_json(&format!("/sites/{}", site)).await?);
+			}
+			sites
+		};
+		Ok(sites
+			.iter()
+			.filter_map(|site| {
+				let id = site["id"].as_str()?.to_string();
+				let name = site["displayName"].as_str().or(site["name"].as_str()).unwrap_or(&id);
+				Some(SharePointSite { name: name.to_string(), id })
+			})
+			.collect())
+	}
+
+	/// Document libraries of a site as (id, name), limited to the configured names.
+	async fn drives(&self, site: &SharePointSite) -> SourceResult<Vec<(String, String)>> {
+		let drives = self.graph_list(&format!("/sites/{}/drives", site.id)).await?;
+		Ok(drives
+			.iter()
+			.filter_map(|drive| {
+				Some((drive["id"].as_str()?.to_string(), drive["name"].as_str()?.to_string()))
+			})
+			.filter(|(_, name)| {
+				self.drives.is_empty() ||
+					self.drives.iter().any(|wanted| wanted.eq_ignore_ascii_case(name))
+			})
+			.collect())
+	}
+
+	/// Lists one page of a document library folder.
+	async fn drive_page(
+		&self,
+		drive_id: &str,
+		listing: &str,
+		folder: &Path,
+	) -> SourceResult<DrivePage> {
+		let response = self.graph_json(listing).await?;
+		let mut page = DrivePage {
+			next: response["@odata.nextLink"].as_str().map(str::to_string),
+			..DrivePage::default()
+		};
+		for item in response["value"].as_array().into_iter().flatten() {
+			let (Some(id), Some(name)) = (item["id"].as_str(), item["name"].as_str()) else {
+				continue;
+			};
+			if item["folder"].is_object() {
+				let children = format!("/drives/{}/items/{}/children", drive_id, id);
+				page.folders.push((children, folder.join(name)));
+			} else if item["file"].is_object() {
+				page.files.push(DriveFile {
+					path: folder.join(name),
+					size: item["size"].as_u64().map(|size| size as usize),
+					download_url: item["@microsoft.graph.downloadUrl"].as_str().map(str::to_string),
+					content_path: format!("/drives/{}/items/{}/content", drive_id, id),
+					content_type: item["file"]["mimeType"].as_str().map(str::to_string),
+				});
+			}
+		}
+		Ok(page)
+	}
+
+	async fn download(&self, file: &DriveFile) -> SourceResult<Bytes> {
+		let response = match &file.download_url {
+			// Download links carry their own authorization.
+			Some(url) => {
+				let response = send_throttled(&self.retry_params, || async {
+					self.client.get(url).send().await.map_err(|err| {
+						SourceError::new(
+							SourceErrorKind::Connection,
+							anyhow!("Got error while downloading file: {:?}", err).into(),
+						)
+					})
+				})
+				.await?;
+				check_status(url, response)?
+			},
+			None => self.graph_get(&file.content_path).await?,
+		};
+		response.bytes().await.map_err(|err| {
+			SourceError::new(SourceErrorKind::Io, anyhow!("Failed to read file: {:?}", err).into())
+		})
+	}
+
+	/// Items of the site lists as (list name, item), document libraries excluded since
+	/// their files are collected through the drives.
+	async fn list_items(&self, site: &SharePointSite) -> SourceResult<Vec<(String, Value)>> {
+		let mut items = Vec::new();
+		for list in self.graph_list(&format!("/sites/{}/lists", site.id)).await? {
+			let hidden = list["list"]["hidden"].as_bool().unwrap_or(false);
+			if hidden || list["list"]["template"].as_str() == Some("documentLibrary") {
+				continue;
+			}
+			let Some(list_id) = list["id"].as_str() else {
+				continue;
+			};
+			let name = list["displayName"].as_str().unwrap_or(list_id).to_string();
+			let path = format!("/sites/{}/lists/{}/items?expand=fields", site.id, list_id);
+			for item in self.graph_list(&path).await? {
+				items.push((name.clone(), item));
+			}
+		}
+		Ok(items)
+	}
+}
+
+fn check_status(url: &str, response: reqwest::Response) -> SourceResult<reqwest::Response> {
+	let status = response.status();
+	if status.is_success() {
+		return Ok(response);
+	}
+	let kind = match status {
+		StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => SourceErrorKind::Unauthorized,
+		StatusCode::NOT_FOUND => SourceErrorKind::NotFound,
+		_ => SourceErrorKind::Io,
+	};
+	Err(SourceError::new(kind, anyhow!("Request to {} failed with status {}", url, status).into()))
+}
+
+/// Renders a list item as `field: value` lines, leaving out the OData and hidden
+/// system fields.
+pub fn format_list_item(list_name: &str, fields: &Value) -> String {
+	let mut lines: Vec<String> = fields
+		.as_object()
+		.map(|fields| {
+			fields
+				.iter()
+				.filter(|(key, _)| !key.starts_with('@') && !key.starts_with('_'))
+				.filter_map(|(key, value)| {
+					let value = match value {
+						Value::String(value) if !value.is_empty() => value.clone(),
+						Value::Number(value) => value.to_string(),
+						Value::Bool(value) => value.to_string(),
+						_ => return None,
+					};
+					Some(format!("{}: {}", key, value))
+				})
+				.collect()
+		})
+		.unwrap_or_default();
+	lines.sort();
+	format!("List: {}\n{}", list_name, lines.join("\n"))
+}
+
+#[async_trait]
+impl DataSource for SharePointSource {
+	async fn check_connectivity(&self) -> anyhow::Result<()> {
+		let sites = self.sites().await?;
+		if sites.is_empty() {
+			return Err(anyhow!("No SharePoint sites found"));
+		}
+		Ok(())
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn get_slice_stream(
+		&self,
+		_path: &Path,
+		_range: Range<usize>,
+	) -> SourceResult<Box<dyn AsyncRead + Send + Unpin>> {
+		Ok(Box::new(string_to_async_read("".to_string())))
+	}
+
+	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn file_num_bytes(&self, _path: &Path) -> SourceResult<u64> {
+		Ok(0)
+	}
+
+	async fn copy_to(&self, _path: &Path, _output: &mut dyn SendableAsync) -> SourceResult<()> {
+		Ok(())
+	}
+
+	/// Emits the files of each site's document libraries under `site/library/path`, then
+	/// the list items when enabled. Files are downloaded right after the listing page that
+	/// names them, as the download links Graph hands out expire within the hour.
+	async fn poll_data(
+		&self,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			let sites = match self.sites().await {
+				Ok(sites) => sites,
+				Err(err) => {
+					yield Err(err);
+					return;
+				}
+			};
+			for site in sites {
+				let doc_source = format!("sharepoint://{}", site.name);
+				let site_folder = PathBuf::from(&site.name);
+				let drives = match self.drives(&site).await {
+					Ok(drives) => drives,
+					Err(err) => {
+						yield Err(err);
+						continue;
+					}
+				};
+				for (drive_id, drive_name) in drives {
+					let library = site_folder.join(&drive_name);
+					let root = format!("/drives/{}/root/children", drive_id);
+					let mut listings = VecDeque::from([(root, PathBuf::new())]);
+					while let Some((listing, folder)) = listings.pop_front() {
+						let page = match self.drive_page(&drive_id, &listing, &folder).await {
+							Ok(page) => page,
+							Err(err) => {
+								let folder = library.join(&folder);
+								yield Err(err.with_document(folder.to_string_lossy()));
+								continue;
+							}
+						};
+						if let Some(next) = page.next {
+							listings.push_front((next, folder));
+						}
+						listings.extend(page.folders);
+						for file in page.files {
+							let path = library.join(&file.path);
+							match self.download(&file).await {
+								Ok(data) => {
+									let size = file.size.unwrap_or(data.len());
+									yield Ok(CollectedBytes::new(
+										Some(path),
+										Some(Box::pin(Cursor::new(data))),
+										true,
+										Some(doc_source.clone()),
+										Some(size),
+										source_id.clone(),
+										None,
+									)
+									.with_content_type(file.content_type.clone()));
+								},
+								Err(err) => yield Err(err.with_document(path.to_string_lossy())),
+							}
+						}
+					}
+				}
+
+				if !self.include_lists {
+					continue;
+				}
+				let items = match self.list_items(&site).await {
+					Ok(items) => items,
+					Err(err) => {
+						yield Err(err);
+						continue;
+					}
+				};
+				for (list_name, item) in items {
+					let item_id = item["id"].as_str().unwrap_or_default();
+					let text = format_list_item(&list_name, &item["fields"]);
+					let size = text.len();
+					let file = site_folder.join(&list_name).join(format!("{}.sharepoint", item_id));
+					yield Ok(CollectedBytes::new(
+						Some(file),
+						Some(Box::pin(string_to_async_read(text))),
+						true,
+						Some(doc_source.clone()),
+						Some(size),
+						source_id.clone(),
+						None,
+					));
+				}
+			}
+		};
+
+		Ok(Box::pin(stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::collections::HashMap;
+
+	use futures::StreamExt;
+	use serde_json::json;
+	use tokio::io::AsyncReadExt;
+	use wiremock::{
+		matchers::{header, method, path, query_param},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+
+	fn config(sites: Vec<String>, drives: Vec<String>) -> SharePointConfig {
+		SharePointConfig {
+			client_id: "client".to_string(),
+			client_secret: "secret".to_string(),
+			redirect_uri: "http://localhost:8000/callback".to_string(),
+			refresh_token: "refresh".to_string(),
+			tenant_id: "contoso".to_string(),
+			sites,
+			drives,
+			include_lists: Some(true),
+			id: "sharepoint".to_string(),
+		}
+	}
+
+	async fn mock_token(server: &MockServer, access_token: &str, times: Option<u64>) {
+		let mock = Mock::given(method("POST"))
+			.and(path("/contoso/oauth2/v2.0/token"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(
+				json!({ "access_token": access_token, "refresh_token": "rotated" }),
+			));
+		match times {
+			Some(times) => mock.up_to_n_times(times).mount(server).await,
+			None => mock.mount(server).await,
+		}
+	}
+
+	async fn mock_json(server: &MockServer, route: &str, body: Value) {
+		Mock::given(method("GET"))
+			.and(path(route))
+			.respond_with(ResponseTemplate::new(200).set_body_json(body))
+			.mount(server)
+			.await;
+	}
+
+	#[tokio::test]
+	async fn test_sharepoint_drives_and_lists() {
+		let server = MockServer::start().await;
+		mock_token(&server, "token", None).await;
+		mock_json(
+			&server,
+			"/sites/contoso.sharepoint.com:/sites/legal",
+			json!({ "id": "site1", "displayName": "Legal" }),
+		)
+		.await;
+		mock_json(
+			&server,
+			"/sites/site1/drives",
+			json!({ "value": [
+				{ "id": "d1", "name": "Contracts" },
+				{ "id": "d2", "name": "Archive" }
+			] }),
+		)
+		.await;
+		mock_json(
+			&server,
+			"/drives/d1/root/children",
+			json!({ "value": [
+				{ "id": "f1", "name": "2024", "folder": {} },
+				{
+					"id": "i1",
+					"name": "nda.docx",
+					"file": {},
+					"size": 3,
+					"@microsoft.graph.downloadUrl": format!("{}/download/i1", server.uri())
+				}
+			] }),
+		)
+		.await;
+		mock_json(
+			&server,
+			"/drives/d1/items/f1/children",
+			json!({ "value": [{ "id": "i2", "name": "msa.pdf", "file": {} }] }),
+		)
+		.await;
+		Mock::given(method("GET"))
+			.and(path("/download/i1"))
+			.respond_with(ResponseTemplate::new(200).set_body_bytes(b"nda".to_vec()))
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/drives/d1/items/i2/content"))
+			.and(header("Authorization", "Bearer token"))
+			.respond_with(ResponseTemplate::new(200).set_body_bytes(b"msa".to_vec()))
+			.mount(&server)
+			.await;
+		mock_json(
+			&server,
+			"/sites/site1/lists",
+			json!({ "value": [
+				{ "id": "l1", "displayName": "Vendors", "list": { "template": "genericList" } },
+				{
+					"id": "l2",
+					"displayName": "Documents",
+					"list": { "template": "documentLibrary" }
+				}
+			] }),
+		)
+		.await;
+		Mock::given(method("GET"))
+			.and(path("/sites/site1/lists/l1/items"))
+			.and(query_param("expand", "fields"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "value": [{
+				"id": "7",
+				"fields": { "Title": "Acme", "Status": "Active", "@odata.etag": "1" }
+			}] })))
+			.mount(&server)
+			.await;
+
+		let source = SharePointSource::with_endpoints(
+			config(
+				vec!["contoso.sharepoint.com:/sites/legal".to_string()],
+				vec!["contracts".to_string()],
+			),
+			&server.uri(),
+			&server.uri(),
+		)
+		.await
+		.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+
+		let mut stream = source.poll_data().await.unwrap();
+		let mut collected = HashMap::new();
+		while let Some(item) = stream.next().await {
+			let mut item = item.unwrap();
+			let mut data = Vec::new();
+			item.data.take().unwrap().read_to_end(&mut data).await.unwrap();
+			collected.insert(item.file.unwrap(), String::from_utf8(data).unwrap());
+		}
+
+		assert_eq!(collected.len(), 3);
+		assert_eq!(collected[&PathBuf::from("Legal/Contracts/nda.docx")], "nda");
+		assert_eq!(collected[&PathBuf::from("Legal/Contracts/2024/msa.pdf")], "msa");
+		assert_eq!(
+			collected[&PathBuf::from("Legal/Vendors/7.sharepoint")],
+			"List: Vendors\nStatus: Active\nTitle: Acme"
+		);
+	}
+
+	#[tokio::test]
+	async fn test_sharepoint_refreshes_expired_token() {
+		let server = MockServer::start().await;
+		mock_token(&server, "expired", Some(1)).await;
+		mock_token(&server, "fresh", None).await;
+		Mock::given(method("GET"))
+			.and(path("/sites/site1"))
+			.and(header("Authorization", "Bearer expired"))
+			.respond_with(ResponseTemplate::new(401))
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/sites/site1"))
+			.and(header("Authorization", "Bearer fresh"))
+			.respond_with(ResponseTemplate::new(200).set_body_json(json!({ "id": "site1" })))
+			.mount(&server)
+			.await;
+
+		let source = SharePointSource::with_endpoints(
+			config(vec!["site1".to_string()], vec![]),
+			&server.uri(),
+			&server.uri(),
+		)
+		.await
+		.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+		assert_eq!(*source.refresh_token.lock().await, "rotated");
+	}
+
+	#[tokio::test]
+	async fn test_sharepoint_invalid_refresh_token() {
+		let server = MockServer::start().await;
+		Mock::given(method("POST"))
+			.and(path("/contoso/oauth2/v2.0/token"))
+			.respond_with(
+				ResponseTemplate::new(400).set_body_json(json!({ "error": "invalid_grant" })),
+			)
+			.mount(&server)
+			.await;
+
+		let source =
+			SharePointSource::with_endpoints(config(vec![], vec![]), &server.uri(), &server.uri())
+				.await;
+		assert!(source.is_err());
+	}
+}
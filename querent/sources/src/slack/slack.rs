@@ -17,37 +17,84 @@
 // This software includes code developed by QuerentAI LLC (https://querent.xyz).
 
 use std::{
+	collections::HashMap,
+	fmt,
 	io::Cursor,
 	ops::Range,
 	path::{Path, PathBuf},
 	pin::Pin,
+	sync::Arc,
+	time::{SystemTime, UNIX_EPOCH},
 };
 
 use async_stream::stream;
 use async_trait::async_trait;
 use common::{retry, CollectedBytes};
 use futures::Stream;
+use once_cell::sync::Lazy;
 use proto::semantics::SlackCollectorConfig;
+use regex::Regex;
 use slack_morphism::{
-	api::{SlackApiConversationsHistoryRequest, SlackApiConversationsHistoryResponse},
+	api::{
+		SlackApiConversationsHistoryRequest, SlackApiConversationsHistoryResponse,
+		SlackApiConversationsListRequest, SlackApiConversationsRepliesRequest,
+		SlackApiUsersInfoRequest,
+	},
 	prelude::SlackClientHyperConnector,
-	SlackApiToken, SlackApiTokenValue, SlackChannelId, SlackClient, SlackCursorId, SlackTs,
+	SlackApiToken, SlackApiTokenValue, SlackChannelId, SlackClient, SlackConversationType,
+	SlackCursorId, SlackFile, SlackHistoryMessage, SlackTs, SlackUserId,
 };
-use tokio::io::AsyncRead;
+use tokio::{io::AsyncRead, sync::Mutex};
+use tracing::warn;
 
-use crate::{DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult};
+use crate::{
+	CheckpointStore, DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+};
+
+static USER_MENTION: Lazy<Regex> = Lazy::new(|| Regex::new(r"<@([A-Z0-9]+)(\|[^>]*)?>").unwrap());
+static CHANNEL_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[CGD][A-Z0-9]{8,}$").unwrap());
+
+/// A channel to collect, identified by id and labelled by name when known.
+#[derive(Clone, Debug, PartialEq)]
+struct SlackChannel {
+	id: String,
+	label: String,
+	/// Channels named in the configuration fail the poll when they can't be read,
+	/// channels discovered through `all_public_channels` are skipped instead.
+	required: bool,
+}
 
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 pub struct SlackApiClient {
 	source_id: String,
 	token: SlackApiToken,
+	access_token: String,
 	pub channel: String,
+	channels: Vec<String>,
+	all_public_channels: bool,
+	include_threads: bool,
+	include_files: bool,
 	latest: Option<String>,
 	inclusive: Option<bool>,
 	oldest: Option<String>,
+	checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+	http: reqwest::Client,
+	user_names: Arc<Mutex<HashMap<String, String>>>,
 	pub retry_params: common::RetryParams,
 }
 
+impl fmt::Debug for SlackApiClient {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SlackApiClient")
+			.field("source_id", &self.source_id)
+			.field("channels", &self.channels)
+			.field("all_public_channels", &self.all_public_channels)
+			.field("latest", &self.latest)
+			.field("oldest", &self.oldest)
+			.finish()
+	}
+}
+
 impl SlackApiClient {
 	pub async fn new(config: SlackCollectorConfig) -> anyhow::Result<Self> {
 		let token_value: SlackApiTokenValue = config.access_token.clone().into();
@@ -56,13 +103,224 @@ impl SlackApiClient {
 		Ok(SlackApiClient {
 			source_id: config.id,
 			token,
+			access_token: config.access_token,
+			channels: requested_channels(&config.channel_name, config.channels.as_deref()),
 			channel: config.channel_name,
+			all_public_channels: config.all_public_channels.unwrap_or(false),
+			include_threads: config.include_threads.unwrap_or(true),
+			include_files: config.include_files.unwrap_or(true),
 			latest: config.latest,
 			inclusive: config.inclusive,
 			oldest: config.oldest,
+			checkpoint_store: None,
+			http: reqwest::Client::new(),
+			user_names: Arc::new(Mutex::new(HashMap::new())),
 			retry_params: common::RetryParams::aggressive(),
 		})
 	}
+
+	/// Saves the newest collected position after each run and resumes from it on the next
+	/// one. Has no effect when the configuration pins `latest`.
+	pub fn with_checkpoint_store(mut self, checkpoint_store: Arc<dyn CheckpointStore>) -> Self {
+		self.checkpoint_store = Some(checkpoint_store);
+		self
+	}
+
+	/// Resolves configured channel names to ids and, in `all_public_channels` mode, adds
+	/// every public channel of the workspace.
+	async fn resolve_channels(&self) -> SourceResult<Vec<SlackChannel>> {
+		let needs_listing =
+			self.all_public_channels || self.channels.iter().any(|c| !CHANNEL_ID.is_match(c));
+		let public_channels = if needs_listing {
+			retry(&self.retry_params, || async { list_public_channels(&self.token).await }).await?
+		} else {
+			Vec::new()
+		};
+
+		let mut channels: Vec<SlackChannel> = Vec::new();
+		for requested in &self.channels {
+			let name = requested.trim_start_matches('#');
+			let channel = match public_channels.iter().find(|c| c.id == name || c.label == name) {
+				Some(channel) => SlackChannel { required: true, ..channel.clone() },
+				None if CHANNEL_ID.is_match(name) =>
+					SlackChannel { id: name.to_string(), label: name.to_string(), required: true },
+				None =>
+					return Err(SourceError::new(
+						SourceErrorKind::NotFound,
+						anyhow::anyhow!("Slack channel {} not found", requested).into(),
+					)),
+			};
+			if !channels.iter().any(|c| c.id == channel.id) {
+				channels.push(channel);
+			}
+		}
+		if self.all_public_channels {
+			for channel in public_channels {
+				if !channels.iter().any(|c| c.id == channel.id) {
+					channels.push(channel);
+				}
+			}
+		}
+		Ok(channels)
+	}
+
+	/// Per channel `latest` of the last successful run.
+	async fn load_checkpoints(&self) -> HashMap<String, String> {
+		let Some(store) = &self.checkpoint_store else {
+			return HashMap::new();
+		};
+		match store.get_checkpoint(&self.source_id).await {
+			Ok(Some(checkpoint)) => serde_json::from_str(&checkpoint).unwrap_or_else(|e| {
+				warn!("Ignoring unreadable slack checkpoint for {}: {:?}", self.source_id, e);
+				HashMap::new()
+			}),
+			Ok(None) => HashMap::new(),
+			Err(e) => {
+				warn!("Failed to load slack checkpoint for {}: {:?}", self.source_id, e);
+				HashMap::new()
+			},
+		}
+	}
+
+	async fn save_checkpoints(&self, checkpoints: &HashMap<String, String>) {
+		let Some(store) = &self.checkpoint_store else {
+			return;
+		};
+		let checkpoint = match serde_json::to_string(checkpoints) {
+			Ok(checkpoint) => checkpoint,
+			Err(e) => {
+				warn!("Failed to serialize slack checkpoint: {:?}", e);
+				return;
+			},
+		};
+		if let Err(e) = store.set_checkpoint(&self.source_id, checkpoint).await {
+			warn!("Failed to save slack checkpoint for {}: {:?}", self.source_id, e);
+		}
+	}
+
+	/// Display name of a user, looked up once per user and cached for the client lifetime.
+	async fn user_name(&self, user_id: &str) -> String {
+		if let Some(name) = self.user_names.lock().await.get(user_id) {
+			return name.clone();
+		}
+		let name = match get_user_name(user_id, &self.token).await {
+			Ok(Some(name)) => name,
+			Ok(None) => user_id.to_string(),
+			Err(e) => {
+				warn!("Failed to look up slack user {}: {:?}", user_id, e);
+				return user_id.to_string();
+			},
+		};
+		self.user_names.lock().await.insert(user_id.to_string(), name.clone());
+		name
+	}
+
+	/// Renders a thread as one `author: text` line per message, with user mentions
+	/// replaced by display names.
+	async fn render_thread(
+		&self,
+		channel: &SlackChannel,
+		thread: &[SlackHistoryMessage],
+	) -> String {
+		let mut lines = Vec::new();
+		for message in thread {
+			let mut text = message.content.text.clone().unwrap_or_default();
+			if let Some(attachments) = &message.content.attachments {
+				for attachment in attachments {
+					if let Some(fallback) = &attachment.fallback {
+						text.push(' ');
+						text.push_str(&remove_special_characters(fallback));
+					}
+					if let Some(attachment_text) = &attachment.text {
+						text.push(' ');
+						text.push_str(attachment_text);
+					}
+				}
+			}
+			let mentioned: Vec<String> =
+				USER_MENTION.captures_iter(&text).map(|c| c[1].to_string()).collect();
+			let mut names = HashMap::new();
+			for user_id in mentioned {
+				let name = self.user_name(&user_id).await;
+				names.insert(user_id, name);
+			}
+			let text = replace_mentions(&text, &names);
+			if text.trim().is_empty() {
+				continue;
+			}
+			let author = match (&message.sender.user, &message.sender.username) {
+				(Some(user_id), _) => self.user_name(&user_id.to_string()).await,
+				(None, Some(username)) => username.clone(),
+				(None, None) => "unknown".to_string(),
+			};
+			lines.push(format!("{}: {}", author, text.trim()));
+		}
+		if lines.is_empty() {
+			return String::new();
+		}
+		format!("Channel: {}\n{}", channel.label, lines.join("\n"))
+	}
+
+	/// Downloads a file shared in a channel, returning its name and content.
+	async fn download_file(&self, file: &SlackFile) -> SourceResult<Option<(String, Vec<u8>)>> {
+		let Some(url) = file.url_private_download.as_ref().or(file.url_private.as_ref()) else {
+			return Ok(None);
+		};
+		let mut name = file
+			.name
+			.clone()
+			.or_else(|| file.title.clone())
+			.unwrap_or_else(|| file.id.to_string());
+		if Path::new(&name).extension().is_none() {
+			if let Some(filetype) = &file.filetype {
+				name = format!("{}.{}", name, filetype);
+			}
+		}
+		let response = self
+			.http
+			.get(url.as_str())
+			.bearer_auth(&self.access_token)
+			.send()
+			.await?
+			.error_for_status()?;
+		let data = response.bytes().await?;
+		Ok(Some((name, data.to_vec())))
+	}
+
+	async fn thread_replies(
+		&self,
+		channel_id: &str,
+		ts: &SlackTs,
+	) -> SourceResult<Vec<SlackHistoryMessage>> {
+		let mut replies = Vec::new();
+		let mut cursor: Option<SlackCursorId> = None;
+		loop {
+			let req = SlackApiConversationsRepliesRequest::new(
+				SlackChannelId::new(channel_id.to_string()),
+				ts.clone(),
+			)
+			.with_limit(200)
+			.opt_cursor(cursor.clone());
+			let response = retry(&self.retry_params, || async {
+				let client = SlackClient::new(SlackClientHyperConnector::new()?);
+				let session = client.open_session(&self.token);
+				session.conversations_replies(&req).await.map_err(|err| {
+					SourceError::new(
+						SourceErrorKind::Io,
+						anyhow::anyhow!("Error while getting the thread replies: {:?}", err).into(),
+					)
+				})
+			})
+			.await?;
+			// The parent message is repeated at the top of every page.
+			replies.extend(response.messages.into_iter().filter(|m| &m.origin.ts != ts));
+			cursor = response.response_metadata.and_then(|metadata| metadata.next_cursor);
+			if cursor.is_none() || !response.has_more.unwrap_or(false) {
+				break;
+			}
+		}
+		Ok(replies)
+	}
 }
 
 fn string_to_async_read(description: String) -> impl AsyncRead + Send + Unpin {
@@ -73,6 +331,63 @@ fn remove_special_characters(input: &str) -> String {
 	input.chars().filter(|c| c.is_alphanumeric() || c.is_whitespace()).collect()
 }
 
+/// `channel_name` followed by the comma separated `channels`, without duplicates.
+fn requested_channels(channel_name: &str, channels: Option<&str>) -> Vec<String> {
+	let mut requested: Vec<String> = Vec::new();
+	for channel in std::iter::once(channel_name).chain(channels.unwrap_or("").split(',')) {
+		let channel = channel.trim();
+		if !channel.is_empty() && !requested.iter().any(|c| c == channel) {
+			requested.push(channel.to_string());
+		}
+	}
+	requested
+}
+
+fn replace_mentions(text: &str, names: &HashMap<String, String>) -> String {
+	USER_MENTION
+		.replace_all(text, |captures: &regex::Captures| match names.get(&captures[1]) {
+			Some(name) => format!("@{}", name),
+			None => captures[0].to_string(),
+		})
+		.to_string()
+}
+
+/// Tracks which messages of a channel were fully collected, newest first as the history
+/// returns them, to tell where the next run has to resume.
+#[derive(Debug, Default)]
+struct ChannelProgress {
+	failed: bool,
+	/// Newest fully collected message older than every failed one.
+	resume_after: Option<String>,
+}
+
+impl ChannelProgress {
+	fn record(&mut self, ts: &str, collected: bool) {
+		if !collected {
+			self.failed = true;
+			self.resume_after = None;
+		} else if self.failed && self.resume_after.is_none() {
+			self.resume_after = Some(ts.to_string());
+		}
+	}
+
+	/// Checkpoint to save once the history was read up to `run_latest`, `None` to keep the
+	/// previous one because the oldest message of the run failed.
+	fn checkpoint(self, run_latest: &str) -> Option<String> {
+		if self.failed {
+			self.resume_after
+		} else {
+			Some(run_latest.to_string())
+		}
+	}
+}
+
+/// Current time in Slack's `seconds.micros` timestamp format.
+fn slack_now() -> String {
+	let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default();
+	format!("{}.{:06}", now.as_secs(), now.subsec_micros())
+}
+
 pub async fn get_message(
 	req: SlackApiConversationsHistoryRequest,
 	token: &SlackApiToken,
@@ -90,6 +405,63 @@ pub async fn get_message(
 	Ok(message_response)
 }
 
+async fn list_public_channels(token: &SlackApiToken) -> Result<Vec<SlackChannel>, SourceError> {
+	let client = SlackClient::new(SlackClientHyperConnector::new()?);
+	let session = client.open_session(token);
+
+	let mut channels = Vec::new();
+	let mut cursor: Option<SlackCursorId> = None;
+	loop {
+		let req = SlackApiConversationsListRequest::new()
+			.with_limit(200)
+			.with_exclude_archived(true)
+			.with_types(vec![SlackConversationType::Public])
+			.opt_cursor(cursor.clone());
+		let response = session.conversations_list(&req).await.map_err(|err| {
+			SourceError::new(
+				SourceErrorKind::Io,
+				anyhow::anyhow!("Error while listing the channels: {:?}", err).into(),
+			)
+		})?;
+		for channel in response.channels {
+			let id = channel.id.to_string();
+			let label = channel.name.clone().unwrap_or_else(|| id.clone());
+			channels.push(SlackChannel { id, label, required: false });
+		}
+		cursor = response.response_metadata.and_then(|metadata| metadata.next_cursor);
+		if cursor.is_none() {
+			break;
+		}
+	}
+	Ok(channels)
+}
+
+async fn get_user_name(
+	user_id: &str,
+	token: &SlackApiToken,
+) -> Result<Option<String>, SourceError> {
+	let client = SlackClient::new(SlackClientHyperConnector::new()?);
+	let session = client.open_session(token);
+
+	let response = session
+		.users_info(&SlackApiUsersInfoRequest::new(SlackUserId::new(user_id.to_string())))
+		.await
+		.map_err(|err| {
+			SourceError::new(
+				SourceErrorKind::Io,
+				anyhow::anyhow!("Error while getting the user: {:?}", err).into(),
+			)
+		})?;
+	let user = response.user;
+	let profile_name = user.profile.and_then(|profile| {
+		profile
+			.display_name
+			.filter(|name| !name.is_empty())
+			.or(profile.real_name.filter(|name| !name.is_empty()))
+	});
+	Ok(profile_name.or(user.name))
+}
+
 #[async_trait]
 impl DataSource for SlackApiClient {
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
@@ -110,6 +482,10 @@ impl DataSource for SlackApiClient {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
@@ -134,78 +510,148 @@ impl DataSource for SlackApiClient {
 		Ok(())
 	}
 
+	/// Collects each configured channel between its checkpoint (or `oldest`) and the start
+	/// of this run. Every top level message becomes one document together with its thread
+	/// replies, and shared files are emitted as their own documents. The checkpoint only
+	/// moves past messages whose replies and files were all fetched, so a failed message is
+	/// collected again by the next run. Replies posted later to threads older than the
+	/// checkpoint are not picked up.
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
-		let channel_id: SlackChannelId = SlackChannelId::new(self.channel.clone());
-		let latest: SlackTs = SlackTs::new(self.latest.clone().unwrap_or("".to_string()));
-		let oldest: SlackTs = SlackTs::new(self.oldest.clone().unwrap_or("".to_string()));
+		let channels = self.resolve_channels().await?;
+		// A pinned `latest` means a fixed window, so there is nothing to advance.
+		let advance_checkpoints = self.latest.is_none() && self.checkpoint_store.is_some();
+		let run_latest = self.latest.clone().unwrap_or_else(slack_now);
+		let mut checkpoints = self.load_checkpoints().await;
 		let token = self.token.clone();
 		let limit = 100;
 
 		let source_id = self.source_id.clone();
-		let mut cursor: Option<SlackCursorId> = None;
 		let stream = stream! {
-			loop {
-				let req: SlackApiConversationsHistoryRequest = SlackApiConversationsHistoryRequest {
-					channel: Some(channel_id.clone()),
-					cursor: cursor.clone(),
-					latest: Some(latest.clone()),
-					limit: Some(limit.clone()),
-					oldest: Some(oldest.clone()),
-					inclusive: Some(self.inclusive.unwrap_or(true)),
+			for channel in channels {
+				let channel_id = SlackChannelId::new(channel.id.clone());
+				// The checkpoint is the last collected message, so it is excluded from the
+				// window while a configured `oldest` follows `inclusive`.
+				let (oldest, inclusive) = match checkpoints.get(&channel.id) {
+					Some(checkpoint) => (checkpoint.clone(), false),
+					None => (
+						self.oldest.clone().unwrap_or_default(),
+						self.inclusive.unwrap_or(true),
+					),
 				};
-				let message_response = retry(&self.retry_params, || async {
-					get_message(req.clone(), &token)
-						.await
-				})
-				.await?;
-				for messages in message_response.messages {
-					let file_name = format!("{}.slack", channel_id.to_string());
-					let file_name_path = Some(PathBuf::from(file_name));
-					let mut message_text = messages.content.text.unwrap_or("".to_string());
-					if let Some(attachments) = messages.content.attachments {
-						if !attachments.is_empty() {
-							// finding links in the attachments
-							for attachment in attachments {
-								if let Some(mut fallback) = attachment.fallback {
-									fallback = remove_special_characters(&fallback);
-									message_text.push_str(" ");
-									message_text.push_str(&fallback);
-								}
-								if let Some(text) = attachment.text {
-									message_text.push_str(" ");
-									message_text.push_str(&text);
-								}
+				let mut cursor: Option<SlackCursorId> = None;
+				let mut completed = true;
+				let mut progress = ChannelProgress::default();
+				loop {
+					let req = SlackApiConversationsHistoryRequest {
+						channel: Some(channel_id.clone()),
+						cursor: cursor.clone(),
+						latest: Some(SlackTs::new(run_latest.clone())),
+						limit: Some(limit),
+						oldest: Some(SlackTs::new(oldest.clone())),
+						inclusive: Some(inclusive),
+					};
+					let message_response = match retry(&self.retry_params, || async {
+						get_message(req.clone(), &token).await
+					})
+					.await {
+						Ok(message_response) => message_response,
+						Err(e) => {
+							completed = false;
+							if channel.required {
+								yield Err(e);
+							} else {
+								warn!("Skipping slack channel {}: {:?}", channel.label, e);
 							}
+							break;
+						},
+					};
+					for message in message_response.messages {
+						let ts = message.origin.ts.clone();
+						let mut thread = vec![message];
+						let mut collected = true;
+						if self.include_threads && thread[0].parent.reply_count.unwrap_or(0) > 0 {
+							match self.thread_replies(&channel.id, &ts).await {
+								Ok(replies) => thread.extend(replies),
+								Err(e) => {
+									collected = false;
+									warn!(
+										"Failed to get replies of {} in {}: {:?}",
+										ts,
+										channel.label,
+										e
+									)
+								},
+							}
+						}
+
+						let thread_text = self.render_thread(&channel, &thread).await;
+						if !thread_text.is_empty() {
+							let file_name = format!("{}/{}.slack", channel.label, ts);
+							let data_len = Some(thread_text.len());
+							yield Ok(CollectedBytes::new(
+								Some(PathBuf::from(file_name)),
+								Some(Box::pin(string_to_async_read(thread_text))),
+								true,
+								Some(format!("slack://{}", channel.id)),
+								data_len,
+								source_id.clone(),
+								None
+							))
 						}
-					} else {
-						continue;
+
+						let files: Vec<SlackFile> = if self.include_files {
+							thread
+								.iter()
+								.flat_map(|m| m.content.files.clone().unwrap_or_default())
+								.collect()
+						} else {
+							Vec::new()
+						};
+						for file in files {
+							let document = format!("{}/{}", channel.label, file.id);
+							match self.download_file(&file).await {
+								Ok(Some((name, data))) => {
+									let data_len = Some(data.len());
+									yield Ok(CollectedBytes::new(
+										Some(PathBuf::from(format!("{}/{}", channel.label, name))),
+										Some(Box::pin(Cursor::new(data))),
+										true,
+										Some(format!("slack://{}/{}", channel.id, file.id)),
+										data_len,
+										source_id.clone(),
+										None
+									))
+								},
+								Ok(None) => {},
+								Err(e) => {
+									collected = false;
+									yield Err(e.with_document(document));
+								},
+							}
+						}
+						progress.record(&ts.to_string(), collected);
+					}
+					cursor = message_response
+						.response_metadata
+						.and_then(|metadata| metadata.next_cursor);
+					if cursor.is_none() {
+						break;
+					}
+					if !message_response.has_more.unwrap_or(false) {
+						break;
 					}
-					let doc_source = Some("slack://".to_string());
-					let data_len = Some(message_text.len());
-					yield Ok(CollectedBytes::new(
-						file_name_path,
-						Some(Box::pin(string_to_async_read(message_text))),
-						true,
-						doc_source,
-						data_len,
-						source_id.clone(),
-						None
-					))
-				}
-				if let Some(metadata) = message_response.response_metadata {
-					cursor = metadata.next_cursor;
-				} else {
-					cursor = None;
-				}
-				if cursor.is_none() {
-					break;
 				}
-				if !message_response.has_more.unwrap_or(false) {
-					break;
+				if completed {
+					if let Some(checkpoint) = progress.checkpoint(&run_latest) {
+						checkpoints.insert(channel.id.clone(), checkpoint);
+					}
 				}
 			}
+			if advance_checkpoints {
+				self.save_checkpoints(&checkpoints).await;
+			}
 		};
 		Ok(Box::pin(stream))
 	}
@@ -221,6 +667,42 @@ mod tests {
 
 	use super::*;
 
+	#[test]
+	fn test_requested_channels_and_mentions() {
+		assert_eq!(
+			requested_channels("C05TA5R7D88", Some(" general, C05TA5R7D88,,random ")),
+			vec!["C05TA5R7D88".to_string(), "general".to_string(), "random".to_string()]
+		);
+		assert!(requested_channels("", None).is_empty());
+
+		let names = HashMap::from([("U024BE7LH".to_string(), "ana".to_string())]);
+		assert_eq!(
+			replace_mentions("ping <@U024BE7LH> and <@U999999|bob>", &names),
+			"ping @ana and <@U999999|bob>"
+		);
+	}
+
+	#[test]
+	fn test_checkpoint_stops_before_failed_messages() {
+		let mut progress = ChannelProgress::default();
+		progress.record("30.0", true);
+		progress.record("20.0", true);
+		assert_eq!(progress.checkpoint("40.0"), Some("40.0".to_string()));
+
+		let mut progress = ChannelProgress::default();
+		for (ts, collected) in [("30.0", true), ("25.0", false), ("20.0", true), ("15.0", false)] {
+			progress.record(ts, collected);
+		}
+		progress.record("10.0", true);
+		progress.record("5.0", true);
+		assert_eq!(progress.checkpoint("40.0"), Some("10.0".to_string()));
+
+		let mut progress = ChannelProgress::default();
+		progress.record("30.0", true);
+		progress.record("20.0", false);
+		assert_eq!(progress.checkpoint("40.0"), None);
+	}
+
 	#[tokio::test]
 	async fn test_slack_collector() {
 		dotenv().ok();
@@ -238,6 +720,10 @@ mod tests {
 			latest: None,
 			inclusive: Some(true),
 			id: "Slack-source".to_string(),
+			channels: None,
+			all_public_channels: None,
+			include_threads: None,
+			include_files: None,
 		};
 
 		let slack_api_client = SlackApiClient::new(slack_config).await.unwrap();
@@ -288,6 +774,10 @@ mod tests {
 			latest: None,
 			inclusive: Some(true),
 			id: "Slack-source".to_string(),
+			channels: None,
+			all_public_channels: None,
+			include_threads: None,
+			include_files: None,
 		};
 
 		let slack_api_client = SlackApiClient::new(slack_config)
@@ -319,6 +809,10 @@ mod tests {
 			latest: None,
 			inclusive: Some(true),
 			id: "Slack-source".to_string(),
+			channels: None,
+			all_public_channels: None,
+			include_threads: None,
+			include_files: None,
 		};
 
 		let slack_api_client = SlackApiClient::new(slack_config)
@@ -368,6 +862,10 @@ mod tests {
 			latest: None,
 			inclusive: Some(true),
 			id: "Slack-source".to_string(),
+			channels: None,
+			all_public_channels: None,
+			include_threads: None,
+			include_files: None,
 		};
 
 		let slack_api_client = SlackApiClient::new(slack_config)
@@ -18,7 +18,7 @@
 
 use async_trait::async_trait;
 use common::{CollectedBytes, Retryable};
-use futures::Stream;
+use futures::{future, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::{
 	fmt::{self, Debug},
@@ -65,6 +65,8 @@ pub struct SourceError {
 	pub kind: SourceErrorKind,
 	#[source]
 	pub source: Arc<anyhow::Error>,
+	/// Document the error happened on, when the source knows it.
+	pub document: Option<String>,
 }
 
 /// Generic Result type for source type operations.
@@ -72,7 +74,13 @@ pub type SourceResult<T> = Result<T, SourceError>;
 
 impl SourceError {
 	pub fn new(kind: SourceErrorKind, source: Arc<anyhow::Error>) -> Self {
-		SourceError { kind, source }
+		SourceError { kind, source, document: None }
+	}
+
+	/// Attributes the error to a document of the source.
+	pub fn with_document(mut self, document: impl Into<String>) -> Self {
+		self.document = Some(document.into());
+		self
 	}
 
 	/// Add some context to the wrapper error.
@@ -83,6 +91,7 @@ impl SourceError {
 		SourceError {
 			kind: self.kind,
 			source: Arc::new(anyhow::anyhow!("{ctx}").context(self.source)),
+			document: self.document,
 		}
 	}
 
@@ -147,6 +156,16 @@ pub trait DataSource: fmt::Debug + Send + Sync {
 	/// Establishes a connection to the source.
 	async fn check_connectivity(&self) -> anyhow::Result<()>;
 
+	/// Id of the collector the source was configured from, carried by the documents it collects.
+	fn source_id(&self) -> &str;
+
+	/// Whether `get_all` fetches the document collected under a path on its own, so a failed
+	/// document can be fetched again. Sources collecting records, pages or messages rather than
+	/// addressable files cannot.
+	fn can_fetch_document(&self) -> bool {
+		false
+	}
+
 	/// Pulls data from the source and copies it to a file.
 	async fn copy_to_file(&self, path: &Path, output_path: &Path) -> SourceResult<u64> {
 		default_copy_to_file(self, path, output_path).await
@@ -199,4 +218,45 @@ pub trait DataSource: fmt::Debug + Send + Sync {
 	async fn poll_data(
 		&self,
 	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>>;
+
+	/// Polls the documents whose path `filter` accepts. Sources listing their documents before
+	/// downloading them override it to skip the others unfetched; by default every document is
+	/// fetched and the others are dropped.
+	async fn poll_filtered_data(
+		&self,
+		filter: DocumentFilter,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let stream = self.poll_data().await?;
+		Ok(Box::pin(stream.filter(move |data| {
+			let accepted = match data {
+				Ok(data) => filter(&data.file.clone().unwrap_or_default().to_string_lossy()),
+				// the errors of the source itself reach every poller
+				Err(error) => error.document.as_deref().is_none_or(|document| filter(document)),
+			};
+			future::ready(accepted)
+		})))
+	}
+
+	/// Whether the source waits for [`DataSource::acknowledge`] before it considers the
+	/// documents it collected done with, so that the ones lost to a crash are collected again.
+	fn acknowledges_documents(&self) -> bool {
+		false
+	}
+
+	/// Called once the knowledge extracted from `documents` is stored.
+	async fn acknowledge(&self, _documents: &[String]) {}
+}
+
+/// Accepts the paths of the documents a source should collect.
+pub type DocumentFilter = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Remembers how far an incremental source got, so the next run resumes from there
+/// instead of collecting everything again.
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+	/// Returns the checkpoint saved by the last successful run of the source.
+	async fn get_checkpoint(&self, source_id: &str) -> anyhow::Result<Option<String>>;
+
+	/// Saves the checkpoint the next run of the source should resume from.
+	async fn set_checkpoint(&self, source_id: &str, checkpoint: String) -> anyhow::Result<()>;
 }
@@ -0,0 +1,656 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::{HashMap, HashSet},
+	fmt,
+	ops::Range,
+	path::{Path, PathBuf},
+	pin::Pin,
+	sync::Arc,
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::CollectedBytes;
+use futures::{Stream, StreamExt};
+use once_cell::sync::Lazy;
+use proto::semantics::SqlCollectorConfig;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+	any::{install_default_drivers, AnyPoolOptions, AnyRow},
+	AnyPool, Column, Row, ValueRef,
+};
+use tokio::io::AsyncRead;
+use tracing::warn;
+
+use crate::{
+	string_to_async_read, CheckpointStore, DataSource, SendableAsync, SourceError, SourceErrorKind,
+	SourceResult,
+};
+
+static IDENTIFIER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"^[A-Za-z_][A-Za-z0-9_]*(\.[A-Za-z_][A-Za-z0-9_]*)?$").unwrap());
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{([^{}]+)\}").unwrap());
+
+/// A statement to run and the name its rows are reported under.
+#[derive(Clone, Debug, PartialEq)]
+struct SqlQuery {
+	label: String,
+	sql: String,
+}
+
+/// Where a query stopped: the last incremental value read and the digests of the rows read with
+/// it. Rows committed later can repeat that value, so the next run reads from the value again
+/// and skips the rows it already read.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct QueryCheckpoint {
+	value: String,
+	#[serde(default)]
+	rows: Vec<String>,
+}
+
+impl QueryCheckpoint {
+	fn has_read(&self, value: &str, digest: &str) -> bool {
+		self.value == value && self.rows.iter().any(|row| row == digest)
+	}
+
+	/// Number of rows read with `value`.
+	fn rows_with(&self, value: &str) -> usize {
+		if self.value == value {
+			self.rows.len()
+		} else {
+			0
+		}
+	}
+}
+
+/// Checkpoints written before rows were recorded hold the last value alone.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCheckpoint {
+	Query(QueryCheckpoint),
+	Value(String),
+}
+
+/// Collects the rows of SELECT queries or whole tables, one document per row.
+#[derive(Clone)]
+pub struct SqlSource {
+	pool: AnyPool,
+	queries: Vec<SqlQuery>,
+	row_template: Option<String>,
+	incremental_column: Option<String>,
+	source_id: String,
+	checkpoint_store: Option<Arc<dyn CheckpointStore>>,
+}
+
+impl fmt::Debug for SqlSource {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SqlSource")
+			.field("queries", &self.queries)
+			.field("incremental_column", &self.incremental_column)
+			.field("source_id", &self.source_id)
+			.finish()
+	}
+}
+
+impl SqlSource {
+	pub async fn new(config: SqlCollectorConfig) -> anyhow::Result<Self> {
+		let mut queries = Vec::new();
+		for table in &config.tables {
+			if !IDENTIFIER.is_match(table) {
+				return Err(anyhow::anyhow!("Invalid table name: {}", table));
+			}
+			queries
+				.push(SqlQuery { label: table.clone(), sql: format!("SELECT * FROM {}", table) });
+		}
+		for (index, query) in config.queries.iter().enumerate() {
+			let sql = query.trim().trim_end_matches(';').to_string();
+			let keyword = sql.split_whitespace().next().unwrap_or_default().to_lowercase();
+			if keyword != "select" && keyword != "with" {
+				return Err(anyhow::anyhow!("Only SELECT queries are supported: {}", query));
+			}
+			queries.push(SqlQuery { label: format!("query_{}", index + 1), sql });
+		}
+		if queries.is_empty() {
+			return Err(anyhow::anyhow!("At least one query or table is required"));
+		}
+		let incremental_column = config.incremental_column.filter(|column| !column.is_empty());
+		if let Some(column) = &incremental_column {
+			if !IDENTIFIER.is_match(column) {
+				return Err(anyhow::anyhow!("Invalid incremental column: {}", column));
+			}
+		}
+
+		install_default_drivers();
+		let pool = AnyPoolOptions::new()
+			.max_connections(2)
+			.after_connect(|conn, _| {
+				Box::pin(async move {
+					let backend = conn.backend_name().to_string();
+					let statement = read_only_statement(&backend).ok_or_else(|| {
+						sqlx::Error::Configuration(
+							format!("Read-only sessions are not supported on {}", backend).into(),
+						)
+					})?;
+					sqlx::query(statement).execute(conn).await?;
+					Ok(())
+				})
+			})
+			.connect_lazy(&config.connection_url)?;
+		Ok(SqlSource {
+			pool,
+			queries,
+			row_template: config.row_template.filter(|template| !template.is_empty()),
+			incremental_column,
+			source_id: config.id,
+			checkpoint_store: None,
+		})
+	}
+
+	pub fn with_checkpoint_store(mut self, checkpoint_store: Arc<dyn CheckpointStore>) -> Self {
+		self.checkpoint_store = Some(checkpoint_store);
+		self
+	}
+
+	/// Where each query stopped, per query label.
+	async fn load_checkpoints(&self) -> HashMap<String, QueryCheckpoint> {
+		let Some(store) = &self.checkpoint_store else {
+			return HashMap::new();
+		};
+		match store.get_checkpoint(&self.source_id).await {
+			Ok(Some(checkpoint)) =>
+				serde_json::from_str::<HashMap<String, StoredCheckpoint>>(&checkpoint)
+					.map(|checkpoints| {
+						checkpoints
+							.into_iter()
+							.map(|(label, checkpoint)| match checkpoint {
+								StoredCheckpoint::Query(checkpoint) => (label, checkpoint),
+								StoredCheckpoint::Value(value) =>
+									(label, QueryCheckpoint { value, rows: Vec::new() }),
+							})
+							.collect()
+					})
+					.unwrap_or_else(|e| {
+						warn!("Ignoring unreadable sql checkpoint for {}: {:?}", self.source_id, e);
+						HashMap::new()
+					}),
+			Ok(None) => HashMap::new(),
+			Err(e) => {
+				warn!("Failed to load sql checkpoint for {}: {:?}", self.source_id, e);
+				HashMap::new()
+			},
+		}
+	}
+
+	async fn save_checkpoints(&self, checkpoints: &HashMap<String, QueryCheckpoint>) {
+		let Some(store) = &self.checkpoint_store else {
+			return;
+		};
+		let checkpoint = match serde_json::to_string(checkpoints) {
+			Ok(checkpoint) => checkpoint,
+			Err(e) => {
+				warn!("Failed to serialize sql checkpoint: {:?}", e);
+				return;
+			},
+		};
+		if let Err(e) = store.set_checkpoint(&self.source_id, checkpoint).await {
+			warn!("Failed to save sql checkpoint for {}: {:?}", self.source_id, e);
+		}
+	}
+
+	/// The query as run: wrapped to skip rows before `from` and to return the rest in
+	/// incremental order when an incremental column is configured.
+	fn statement(&self, query: &SqlQuery, from: Option<&str>) -> String {
+		let Some(column) = &self.incremental_column else {
+			return query.sql.clone();
+		};
+		let filter = from
+			.map(|from| format!(" WHERE {} >= {}", column, sql_literal(from)))
+			.unwrap_or_default();
+		format!("SELECT * FROM ({}) AS querent_rows{} ORDER BY {}", query.sql, filter, column)
+	}
+}
+
+/// Makes every transaction of a session read-only, so the configured queries can't write
+/// even through a data-modifying `WITH` clause or a function with side effects.
+fn read_only_statement(backend: &str) -> Option<&'static str> {
+	match backend {
+		"PostgreSQL" => Some("SET SESSION CHARACTERISTICS AS TRANSACTION READ ONLY"),
+		"MySQL" => Some("SET SESSION TRANSACTION READ ONLY"),
+		"SQLite" => Some("PRAGMA query_only = ON"),
+		_ => None,
+	}
+}
+
+/// Numbers are compared as numbers, anything else as a quoted string.
+fn sql_literal(value: &str) -> String {
+	if value.parse::<f64>().is_ok() {
+		value.to_string()
+	} else {
+		format!("'{}'", value.replace('\'', "''"))
+	}
+}
+
+/// Reads a column as text, whatever its type, with NULL as an empty string.
+fn column_value(row: &AnyRow, index: usize) -> String {
+	match row.try_get_raw(index) {
+		Ok(value) if !value.is_null() => {},
+		_ => return String::new(),
+	}
+	if let Ok(value) = row.try_get::<String, _>(index) {
+		return value;
+	}
+	if let Ok(value) = row.try_get::<i64, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<i32, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<i16, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<f64, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<f32, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<bool, _>(index) {
+		return value.to_string();
+	}
+	if let Ok(value) = row.try_get::<Vec<u8>, _>(index) {
+		return String::from_utf8_lossy(&value).to_string();
+	}
+	String::new()
+}
+
+/// Identifies a row among the rows sharing its incremental value.
+fn row_digest(values: &[(String, String)]) -> String {
+	format!("{:x}", md5::compute(serde_json::to_vec(values).unwrap_or_default()))
+}
+
+fn row_values(row: &AnyRow) -> Vec<(String, String)> {
+	row.columns()
+		.iter()
+		.map(|column| (column.name().to_string(), column_value(row, column.ordinal())))
+		.collect()
+}
+
+/// Fills the `{column}` placeholders of the template, or lists the row as `column: value`
+/// lines like the CSV ingestor does.
+pub fn render_row(template: Option<&str>, values: &[(String, String)]) -> String {
+	let Some(template) = template else {
+		return values
+			.iter()
+			.map(|(column, value)| format!("{}: {}", column, value))
+			.collect::<Vec<_>>()
+			.join("\n");
+	};
+	PLACEHOLDER
+		.replace_all(template, |captures: &regex::Captures| {
+			let name = captures[1].trim();
+			values
+				.iter()
+				.find(|(column, _)| column == name)
+				.or_else(|| values.iter().find(|(column, _)| column.eq_ignore_ascii_case(name)))
+				.map(|(_, value)| value.clone())
+				.unwrap_or_else(|| captures[0].to_string())
+		})
+		.to_string()
+}
+
+#[async_trait]
+impl DataSource for SqlSource {
+	async fn check_connectivity(&self) -> anyhow::Result<()> {
+		sqlx::query("SELECT 1")
+			.fetch_one(&self.pool)
+			.await
+			.map_err(|e| anyhow::anyhow!("Failed to connect to the database: {:?}", e))?;
+		Ok(())
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn get_slice_stream(
+		&self,
+		_path: &Path,
+		_range: Range<usize>,
+	) -> SourceResult<Box<dyn AsyncRead + Send + Unpin>> {
+		Ok(Box::new(string_to_async_read("".to_string())))
+	}
+
+	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn file_num_bytes(&self, _path: &Path) -> SourceResult<u64> {
+		Ok(0)
+	}
+
+	async fn copy_to(&self, _path: &Path, _output: &mut dyn SendableAsync) -> SourceResult<()> {
+		Ok(())
+	}
+
+	/// Runs every query and emits each row as a document named after the query and the
+	/// row's incremental value, or its position when there is no incremental column. Rows
+	/// repeating an incremental value also carry their rank among the rows with that value. A
+	/// query only moves its checkpoint once all of its rows were read.
+	async fn poll_data(
+		&self,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let mut checkpoints = self.load_checkpoints().await;
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			for query in &self.queries {
+				let after = checkpoints.get(&query.label).cloned();
+				let statement =
+					self.statement(query, after.as_ref().map(|after| after.value.as_str()));
+				let doc_source = format!("sql://{}", query.label);
+				let mut rows = sqlx::query(&statement).fetch(&self.pool);
+				let mut checkpoint = after.clone();
+				let mut position = 0;
+				let mut repeats: HashMap<String, usize> = HashMap::new();
+				let mut keys = HashSet::new();
+				let mut completed = true;
+				while let Some(row) = rows.next().await {
+					let row = match row {
+						Ok(row) => row,
+						Err(e) => {
+							completed = false;
+							yield Err(SourceError::new(
+								SourceErrorKind::Io,
+								anyhow::anyhow!("Failed to read rows of {}: {:?}", query.label, e)
+									.into(),
+							)
+							.with_document(query.label.clone()));
+							break;
+						}
+					};
+					position += 1;
+					let values = row_values(&row);
+					let incremental_value = self.incremental_column.as_ref().and_then(|column| {
+						values
+							.iter()
+							.find(|(name, _)| name.eq_ignore_ascii_case(column))
+							.map(|(_, value)| value.clone())
+					});
+					let mut key = match incremental_value {
+						Some(value) => {
+							let digest = row_digest(&values);
+							if after.as_ref().is_some_and(|after| after.has_read(&value, &digest)) {
+								continue;
+							}
+							let rank = repeats.entry(value.clone()).or_insert_with(|| {
+								after.as_ref().map_or(0, |after| after.rows_with(&value))
+							});
+							*rank += 1;
+							let key = if *rank == 1 {
+								value.clone()
+							} else {
+								format!("{}-{}", value, rank)
+							};
+							match &mut checkpoint {
+								Some(checkpoint) if checkpoint.value == value =>
+									checkpoint.rows.push(digest),
+								_ =>
+									checkpoint =
+										Some(QueryCheckpoint { value, rows: vec![digest] }),
+							}
+							key
+						},
+						None => position.to_string(),
+					};
+					if !keys.insert(key.clone()) {
+						key = format!("{}-{}", key, position);
+					}
+
+					let text = render_row(self.row_template.as_deref(), &values);
+					let size = text.len();
+					let file_name = format!("{}.sqlrow", key.replace('/', "-"));
+					let file = PathBuf::from(&query.label).join(file_name);
+					yield Ok(CollectedBytes::new(
+						Some(file),
+						Some(Box::pin(string_to_async_read(text))),
+						true,
+						Some(doc_source.clone()),
+						Some(size),
+						source_id.clone(),
+						None,
+					));
+				}
+				if completed {
+					if let Some(checkpoint) = checkpoint {
+						checkpoints.insert(query.label.clone(), checkpoint);
+					}
+				}
+			}
+			if self.incremental_column.is_some() {
+				self.save_checkpoints(&checkpoints).await;
+			}
+		};
+
+		Ok(Box::pin(stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{io::AsyncReadExt, sync::Mutex};
+
+	use super::*;
+
+	#[derive(Default)]
+	struct MemoryCheckpoints(Mutex<HashMap<String, String>>);
+
+	#[async_trait]
+	impl CheckpointStore for MemoryCheckpoints {
+		async fn get_checkpoint(&self, source_id: &str) -> anyhow::Result<Option<String>> {
+			Ok(self.0.lock().await.get(source_id).cloned())
+		}
+
+		async fn set_checkpoint(&self, source_id: &str, checkpoint: String) -> anyhow::Result<()> {
+			self.0.lock().await.insert(source_id.to_string(), checkpoint);
+			Ok(())
+		}
+	}
+
+	fn config(connection_url: &str) -> SqlCollectorConfig {
+		SqlCollectorConfig {
+			connection_url: connection_url.to_string(),
+			queries: vec![],
+			tables: vec![],
+			row_template: None,
+			incremental_column: None,
+			id: "sql".to_string(),
+		}
+	}
+
+	async fn contracts_database(dir: &tempfile::TempDir) -> (String, AnyPool) {
+		install_default_drivers();
+		let url = format!("sqlite://{}?mode=rwc", dir.path().join("contracts.db").display());
+		let pool = AnyPoolOptions::new().max_connections(1).connect(&url).await.unwrap();
+		sqlx::query("CREATE TABLE contracts (id INTEGER, party TEXT, amount REAL, signed TEXT)")
+			.execute(&pool)
+			.await
+			.unwrap();
+		sqlx::query(
+			"INSERT INTO contracts VALUES (1, 'Acme', 1200.5, '2024-01-02'), \
+			 (2, 'Globex', 80, NULL)",
+		)
+		.execute(&pool)
+		.await
+		.unwrap();
+		(url, pool)
+	}
+
+	async fn collect(source: &SqlSource) -> Vec<(PathBuf, String)> {
+		let mut stream = source.poll_data().await.unwrap();
+		let mut collected = Vec::new();
+		while let Some(item) = stream.next().await {
+			let mut item = item.unwrap();
+			let mut text = String::new();
+			item.data.take().unwrap().read_to_string(&mut text).await.unwrap();
+			collected.push((item.file.unwrap(), text));
+		}
+		collected
+	}
+
+	#[tokio::test]
+	async fn test_sql_rows_as_documents() {
+		let dir = tempfile::tempdir().unwrap();
+		let (url, _pool) = contracts_database(&dir).await;
+
+		let mut table_config = config(&url);
+		table_config.tables = vec!["contracts".to_string()];
+		let source = SqlSource::new(table_config).await.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+		let rows = collect(&source).await;
+		assert_eq!(rows.len(), 2);
+		assert_eq!(rows[0].0, PathBuf::from("contracts/1.sqlrow"));
+		assert_eq!(rows[0].1, "id: 1\nparty: Acme\namount: 1200.5\nsigned: 2024-01-02");
+		assert_eq!(rows[1].1, "id: 2\nparty: Globex\namount: 80\nsigned: ");
+
+		let mut query_config = config(&url);
+		query_config.queries =
+			vec!["SELECT party, amount FROM contracts WHERE amount > 100;".to_string()];
+		query_config.row_template = Some("{party} signed for {amount} {currency}".to_string());
+		let source = SqlSource::new(query_config).await.unwrap();
+		let rows = collect(&source).await;
+		assert_eq!(
+			rows,
+			vec![(
+				PathBuf::from("query_1/1.sqlrow"),
+				"Acme signed for 1200.5 {currency}".to_string()
+			)]
+		);
+
+		let write = sqlx::query("DELETE FROM contracts").execute(&source.pool).await;
+		assert!(write.is_err());
+	}
+
+	#[tokio::test]
+	async fn test_sql_incremental_polling() {
+		let dir = tempfile::tempdir().unwrap();
+		let (url, pool) = contracts_database(&dir).await;
+		let checkpoints = Arc::new(MemoryCheckpoints::default());
+
+		let mut incremental_config = config(&url);
+		incremental_config.tables = vec!["contracts".to_string()];
+		incremental_config.incremental_column = Some("id".to_string());
+		let source = SqlSource::new(incremental_config)
+			.await
+			.unwrap()
+			.with_checkpoint_store(checkpoints.clone());
+
+		assert_eq!(collect(&source).await.len(), 2);
+		assert!(collect(&source).await.is_empty());
+
+		sqlx::query("INSERT INTO contracts VALUES (3, 'Initech', 300, NULL)")
+			.execute(&pool)
+			.await
+			.unwrap();
+		let rows = collect(&source).await;
+		assert_eq!(rows.len(), 1);
+		assert_eq!(rows[0].0, PathBuf::from("contracts/3.sqlrow"));
+	}
+
+	#[tokio::test]
+	async fn test_sql_repeated_incremental_values() {
+		let dir = tempfile::tempdir().unwrap();
+		let (url, pool) = contracts_database(&dir).await;
+		sqlx::query("INSERT INTO contracts VALUES (3, 'Initech', 80, NULL)")
+			.execute(&pool)
+			.await
+			.unwrap();
+
+		let mut incremental_config = config(&url);
+		incremental_config.tables = vec!["contracts".to_string()];
+		incremental_config.incremental_column = Some("amount".to_string());
+		let source = SqlSource::new(incremental_config).await.unwrap();
+		let files: Vec<PathBuf> =
+			collect(&source).await.into_iter().map(|(file, _)| file).collect();
+		assert_eq!(
+			files,
+			vec![
+				PathBuf::from("contracts/80.sqlrow"),
+				PathBuf::from("contracts/80-2.sqlrow"),
+				PathBuf::from("contracts/1200.5.sqlrow"),
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_sql_rows_committed_later_with_the_last_value() {
+		let dir = tempfile::tempdir().unwrap();
+		let (url, pool) = contracts_database(&dir).await;
+		let checkpoints = Arc::new(MemoryCheckpoints::default());
+
+		let mut incremental_config = config(&url);
+		incremental_config.tables = vec!["contracts".to_string()];
+		incremental_config.incremental_column = Some("id".to_string());
+		let source = SqlSource::new(incremental_config)
+			.await
+			.unwrap()
+			.with_checkpoint_store(checkpoints.clone());
+		assert_eq!(collect(&source).await.len(), 2);
+
+		sqlx::query("INSERT INTO contracts VALUES (2, 'Umbrella', 50, NULL)")
+			.execute(&pool)
+			.await
+			.unwrap();
+		let rows = collect(&source).await;
+		assert_eq!(rows.len(), 1);
+		assert_eq!(rows[0].0, PathBuf::from("contracts/2-2.sqlrow"));
+		assert!(rows[0].1.contains("Umbrella"));
+		assert!(collect(&source).await.is_empty());
+
+		// Checkpoints holding the last value alone read the rows with that value again.
+		checkpoints
+			.set_checkpoint("sql", r#"{"contracts": "2"}"#.to_string())
+			.await
+			.unwrap();
+		assert_eq!(collect(&source).await.len(), 2);
+		assert!(collect(&source).await.is_empty());
+	}
+
+	#[tokio::test]
+	async fn test_sql_rejects_statements_other_than_select() {
+		let mut delete_config = config("sqlite::memory:");
+		delete_config.queries = vec!["DELETE FROM contracts".to_string()];
+		assert!(SqlSource::new(delete_config).await.is_err());
+
+		let mut table_config = config("sqlite::memory:");
+		table_config.tables = vec!["contracts; DROP TABLE users".to_string()];
+		assert!(SqlSource::new(table_config).await.is_err());
+	}
+
+	#[test]
+	fn test_sql_literal() {
+		assert_eq!(sql_literal("42"), "42");
+		assert_eq!(sql_literal("O'Brien"), "'O''Brien'");
+	}
+}
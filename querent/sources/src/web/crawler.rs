@@ -0,0 +1,690 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	ops::Range,
+	path::{Path, PathBuf},
+	pin::Pin,
+	task::{Context, Poll},
+	time::Duration,
+};
+
+use async_stream::stream;
+use async_trait::async_trait;
+use common::{
+	tower::{BoxFuture, ConstantRate, Cost, RateLimit},
+	CollectedBytes,
+};
+use futures::Stream;
+use proto::semantics::WebCrawlerConfig;
+use regex::Regex;
+use reqwest::{
+	header::{CONTENT_TYPE, LOCATION},
+	redirect, Client, StatusCode,
+};
+use tokio::io::AsyncRead;
+use tower::{Service, ServiceExt};
+use tracing::warn;
+use url::{Position, Url};
+
+use super::{
+	links::{canonical_url, parse_page, sitemap_locations},
+	robots::RobotsTxt,
+};
+use crate::{
+	string_to_async_read, DataSource, SendableAsync, SourceError, SourceErrorKind, SourceResult,
+};
+
+const DEFAULT_USER_AGENT: &str = "QuerentBot/1.0";
+const DEFAULT_MAX_DEPTH: usize = 2;
+const DEFAULT_MAX_PAGES: usize = 1000;
+const DEFAULT_CRAWL_DELAY: Duration = Duration::from_millis(1000);
+/// Sitemap indexes nested deeper than this are not followed.
+const MAX_SITEMAP_NESTING: usize = 3;
+
+/// A GET request, one unit of work for the rate limiter of its host.
+struct Fetch(Url);
+
+impl Cost for Fetch {
+	fn cost(&self) -> u64 {
+		1
+	}
+}
+
+#[derive(Clone)]
+struct Fetcher {
+	client: Client,
+}
+
+impl Service<Fetch> for Fetcher {
+	type Response = reqwest::Response;
+	type Error = reqwest::Error;
+	type Future = BoxFuture<reqwest::Response, reqwest::Error>;
+
+	fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+		Poll::Ready(Ok(()))
+	}
+
+	fn call(&mut self, Fetch(url): Fetch) -> Self::Future {
+		let client = self.client.clone();
+		Box::pin(async move { client.get(url).send().await })
+	}
+}
+
+/// Per crawl state: the robots.txt and rate limiter of every host met so far.
+#[derive(Default)]
+struct Hosts {
+	robots: HashMap<String, RobotsTxt>,
+	limiters: HashMap<String, RateLimit<Fetcher, ConstantRate>>,
+}
+
+/// Crawls web pages from seed URLs and sitemaps, within the allowed patterns and the
+/// rules of each site's robots.txt.
+#[derive(Clone, Debug)]
+pub struct WebCrawlerSource {
+	/// Client for robots.txt, which follows redirects on its own.
+	client: Client,
+	/// Client for pages and sitemaps, which leaves redirects to the crawl so their targets
+	/// go through the scope, robots.txt and rate limit checks like any other URL.
+	fetch_client: Client,
+	seeds: Vec<Url>,
+	sitemap_url: Option<Url>,
+	allow: Vec<Regex>,
+	deny: Vec<Regex>,
+	/// Hosts of the seeds and sitemap, the crawl scope when there are no allow patterns.
+	hosts: HashSet<String>,
+	max_depth: usize,
+	max_pages: usize,
+	crawl_delay: Duration,
+	user_agent: String,
+	source_id: String,
+}
+
+impl WebCrawlerSource {
+	pub async fn new(config: WebCrawlerConfig) -> anyhow::Result<Self> {
+		let seeds = config
+			.seed_urls
+			.iter()
+			.map(|seed| {
+				Url::parse(seed).map_err(|e| anyhow::anyhow!("Invalid seed url {}: {}", seed, e))
+			})
+			.collect::<anyhow::Result<Vec<_>>>()?;
+		let sitemap_url = config
+			.sitemap_url
+			.filter(|sitemap| !sitemap.is_empty())
+			.map(|sitemap| {
+				Url::parse(&sitemap)
+					.map_err(|e| anyhow::anyhow!("Invalid sitemap url {}: {}", sitemap, e))
+			})
+			.transpose()?;
+		if seeds.is_empty() && sitemap_url.is_none() {
+			return Err(anyhow::anyhow!("At least one seed url or a sitemap url is required"));
+		}
+		let compile = |patterns: &[String]| {
+			patterns
+				.iter()
+				.map(|pattern| {
+					Regex::new(pattern)
+						.map_err(|e| anyhow::anyhow!("Invalid pattern {}: {}", pattern, e))
+				})
+				.collect::<anyhow::Result<Vec<_>>>()
+		};
+		let hosts = seeds
+			.iter()
+			.chain(sitemap_url.iter())
+			.filter_map(|url| url.host_str().map(str::to_string))
+			.collect();
+		let user_agent = config
+			.user_agent
+			.filter(|user_agent| !user_agent.is_empty())
+			.unwrap_or_else(|| DEFAULT_USER_AGENT.to_string());
+		let client = Client::builder().user_agent(user_agent.clone()).build()?;
+		let fetch_client = Client::builder()
+			.user_agent(user_agent.clone())
+			.redirect(redirect::Policy::none())
+			.build()?;
+
+		Ok(WebCrawlerSource {
+			client,
+			fetch_client,
+			seeds,
+			sitemap_url,
+			allow: compile(&config.allow_patterns)?,
+			deny: compile(&config.deny_patterns)?,
+			hosts,
+			max_depth: config.max_depth.map_or(DEFAULT_MAX_DEPTH, |depth| depth.max(0) as usize),
+			max_pages: config.max_pages.map_or(DEFAULT_MAX_PAGES, |pages| pages.max(0) as usize),
+			crawl_delay: config
+				.crawl_delay_ms
+				.map_or(DEFAULT_CRAWL_DELAY, |delay| Duration::from_millis(delay.max(0) as u64)),
+			user_agent,
+			source_id: config.id,
+		})
+	}
+
+	fn in_scope(&self, url: &Url) -> bool {
+		if url.scheme() != "http" && url.scheme() != "https" {
+			return false;
+		}
+		if self.deny.iter().any(|pattern| pattern.is_match(url.as_str())) {
+			return false;
+		}
+		if self.allow.is_empty() {
+			url.host_str().is_some_and(|host| self.hosts.contains(host))
+		} else {
+			self.allow.iter().any(|pattern| pattern.is_match(url.as_str()))
+		}
+	}
+
+	/// The robots.txt of the host of `url`, read on the first visit to the host.
+	async fn robots<'a>(&self, hosts: &'a mut Hosts, url: &Url) -> &'a RobotsTxt {
+		let origin = url.origin().ascii_serialization();
+		if !hosts.robots.contains_key(&origin) {
+			let robots = self.fetch_robots(url).await;
+			hosts.robots.insert(origin.clone(), robots);
+		}
+		&hosts.robots[&origin]
+	}
+
+	async fn allowed(&self, hosts: &mut Hosts, url: &Url) -> bool {
+		self.robots(hosts, url).await.is_allowed(&url[Position::BeforePath..])
+	}
+
+	/// A missing robots.txt allows everything, one that fails with a server error
+	/// disallows everything until the next run.
+	async fn fetch_robots(&self, url: &Url) -> RobotsTxt {
+		let Ok(robots_url) = url.join("/robots.txt") else {
+			return RobotsTxt::allow_all();
+		};
+		match self.client.get(robots_url.clone()).send().await {
+			Ok(response) if response.status().is_success() => match response.text().await {
+				Ok(content) => RobotsTxt::parse(&content, &self.user_agent),
+				Err(_) => RobotsTxt::allow_all(),
+			},
+			Ok(response) if response.status().is_client_error() => RobotsTxt::allow_all(),
+			Ok(response) => {
+				warn!("Fetching {} failed with status {}", robots_url, response.status());
+				RobotsTxt::disallow_all()
+			},
+			Err(e) => {
+				warn!("Failed to fetch {}: {:?}", robots_url, e);
+				RobotsTxt::disallow_all()
+			},
+		}
+	}
+
+	/// Fetches a URL once the host's crawl delay, the larger of the configured one and
+	/// the robots.txt one, has passed since its previous request. Redirects are returned
+	/// as is, see `redirect_target`.
+	async fn fetch(&self, hosts: &mut Hosts, url: &Url) -> SourceResult<reqwest::Response> {
+		let delay = self
+			.robots(hosts, url)
+			.await
+			.crawl_delay
+			.map_or(self.crawl_delay, |delay| delay.max(self.crawl_delay))
+			.max(Duration::from_millis(1));
+		let origin = url.origin().ascii_serialization();
+		let limiter = hosts.limiters.entry(origin).or_insert_with(|| {
+			let fetcher = Fetcher { client: self.fetch_client.clone() };
+			RateLimit::new(fetcher, ConstantRate::new(1, delay))
+		});
+		let fetcher = ServiceExt::<Fetch>::ready(limiter).await.map_err(|e| fetch_error(url, e))?;
+		let response = fetcher.call(Fetch(url.clone())).await.map_err(|e| fetch_error(url, e))?;
+
+		let status = response.status();
+		if status.is_success() || redirect_target(url, &response).is_some() {
+			return Ok(response);
+		}
+		let kind = match status {
+			StatusCode::NOT_FOUND | StatusCode::GONE => SourceErrorKind::NotFound,
+			StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => SourceErrorKind::Unauthorized,
+			_ => SourceErrorKind::Io,
+		};
+		Err(SourceError::new(
+			kind,
+			anyhow::anyhow!("Fetching {} failed with status {}", url, status).into(),
+		))
+	}
+
+	/// Page URLs listed by a sitemap, following sitemap indexes and redirects.
+	async fn sitemap_pages(&self, hosts: &mut Hosts, sitemap: &Url) -> SourceResult<Vec<Url>> {
+		let mut pages = Vec::new();
+		let mut sitemaps = VecDeque::from([(sitemap.clone(), 0)]);
+		while let Some((sitemap, nesting)) = sitemaps.pop_front() {
+			let response = self.fetch(hosts, &sitemap).await?;
+			if let Some(target) = redirect_target(&sitemap, &response) {
+				if nesting < MAX_SITEMAP_NESTING {
+					sitemaps.push_front((target, nesting + 1));
+				}
+				continue;
+			}
+			let xml = response.text().await.map_err(|e| fetch_error(&sitemap, e))?;
+			let (is_index, locations) = sitemap_locations(&xml);
+			let locations = locations.iter().filter_map(|location| Url::parse(location).ok());
+			if !is_index {
+				pages.extend(locations);
+			} else if nesting < MAX_SITEMAP_NESTING {
+				sitemaps.extend(locations.map(|location| (location, nesting + 1)));
+			}
+		}
+		Ok(pages)
+	}
+}
+
+/// Where a redirect response points, resolved against the requested URL.
+fn redirect_target(url: &Url, response: &reqwest::Response) -> Option<Url> {
+	if !response.status().is_redirection() {
+		return None;
+	}
+	let location = response.headers().get(LOCATION)?.to_str().ok()?;
+	url.join(location).ok()
+}
+
+fn fetch_error(url: &Url, error: impl std::fmt::Debug) -> SourceError {
+	SourceError::new(
+		SourceErrorKind::Connection,
+		anyhow::anyhow!("Error while fetching {}: {:?}", url, error).into(),
+	)
+}
+
+/// Path a page is reported under, made of its host and path.
+fn page_file(url: &Url) -> PathBuf {
+	let mut name = format!("{}{}", url.host_str().unwrap_or_default(), url.path());
+	if name.ends_with('/') {
+		name.push_str("index");
+	}
+	if let Some(query) = url.query() {
+		let query: String =
+			query.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+		name.push('_');
+		name.push_str(&query);
+	}
+	PathBuf::from(format!("{}.webpage", name))
+}
+
+#[async_trait]
+impl DataSource for WebCrawlerSource {
+	async fn check_connectivity(&self) -> anyhow::Result<()> {
+		let url = self.seeds.first().or(self.sitemap_url.as_ref()).cloned();
+		let Some(url) = url else {
+			return Err(anyhow::anyhow!("No url to crawl"));
+		};
+		let mut hosts = Hosts::default();
+		if !self.allowed(&mut hosts, &url).await {
+			return Err(anyhow::anyhow!("Crawling {} is disallowed by robots.txt", url));
+		}
+		self.fetch(&mut hosts, &url).await?;
+		Ok(())
+	}
+
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
+	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn get_slice_stream(
+		&self,
+		_path: &Path,
+		_range: Range<usize>,
+	) -> SourceResult<Box<dyn AsyncRead + Send + Unpin>> {
+		Ok(Box::new(string_to_async_read("".to_string())))
+	}
+
+	async fn get_all(&self, _path: &Path) -> SourceResult<Vec<u8>> {
+		Ok(vec![])
+	}
+
+	async fn file_num_bytes(&self, _path: &Path) -> SourceResult<u64> {
+		Ok(0)
+	}
+
+	async fn copy_to(&self, _path: &Path, _output: &mut dyn SendableAsync) -> SourceResult<()> {
+		Ok(())
+	}
+
+	/// Crawls breadth first from the seeds and the sitemap pages, emitting every HTML page
+	/// once under its canonical URL. Pages are skipped when robots.txt disallows them or
+	/// their robots meta tag asks not to index them. A redirect queues its target at the
+	/// depth of the redirecting URL.
+	async fn poll_data(
+		&self,
+	) -> SourceResult<Pin<Box<dyn Stream<Item = SourceResult<CollectedBytes>> + Send + 'life0>>> {
+		let source_id = self.source_id.clone();
+
+		let stream = stream! {
+			let mut hosts = Hosts::default();
+			let mut queue: VecDeque<(Url, usize)> =
+				self.seeds.iter().map(|seed| (seed.clone(), 0)).collect();
+			if let Some(sitemap) = &self.sitemap_url {
+				match self.sitemap_pages(&mut hosts, sitemap).await {
+					Ok(pages) => queue.extend(pages.into_iter().map(|page| (page, 0))),
+					Err(e) => yield Err(e.with_document(sitemap.as_str())),
+				}
+			}
+			let mut seen: HashSet<Url> = HashSet::new();
+			let mut collected = 0;
+
+			while let Some((url, depth)) = queue.pop_front() {
+				if collected >= self.max_pages {
+					break;
+				}
+				let url = canonical_url(&url);
+				if !self.in_scope(&url) || !seen.insert(url.clone()) {
+					continue;
+				}
+				if !self.allowed(&mut hosts, &url).await {
+					continue;
+				}
+				let response = match self.fetch(&mut hosts, &url).await {
+					Ok(response) => response,
+					Err(e) => {
+						yield Err(e.with_document(url.as_str()));
+						continue;
+					}
+				};
+				if let Some(target) = redirect_target(&url, &response) {
+					queue.push_front((target, depth));
+					continue;
+				}
+				let is_html = response
+					.headers()
+					.get(CONTENT_TYPE)
+					.and_then(|content_type| content_type.to_str().ok())
+					.is_none_or(|content_type| content_type.contains("html"));
+				if !is_html {
+					continue;
+				}
+				let html = match response.text().await {
+					Ok(html) => html,
+					Err(e) => {
+						yield Err(fetch_error(&url, e).with_document(url.as_str()));
+						continue;
+					}
+				};
+
+				let page = parse_page(&html, &url);
+				let canonical = page.canonical.as_ref().map(canonical_url);
+				// Pages pointing at another canonical URL are collected once under that URL.
+				let duplicate = canonical.as_ref().is_some_and(|canonical| {
+					*canonical != url && !seen.insert(canonical.clone())
+				});
+				if !page.noindex && !duplicate {
+					collected += 1;
+					let page_url = canonical.unwrap_or_else(|| url.clone());
+					let size = html.len();
+					yield Ok(CollectedBytes::new(
+						Some(page_file(&page_url)),
+						Some(Box::pin(string_to_async_read(html))),
+						true,
+						Some(page_url.to_string()),
+						Some(size),
+						source_id.clone(),
+						None,
+					));
+				}
+				if depth < self.max_depth && !page.nofollow {
+					for link in page.links {
+						let link = canonical_url(&link);
+						if self.in_scope(&link) && !seen.contains(&link) {
+							queue.push_back((link, depth + 1));
+						}
+					}
+				}
+			}
+		};
+
+		Ok(Box::pin(stream))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Instant;
+
+	use futures::StreamExt;
+	use tokio::io::AsyncReadExt;
+	use wiremock::{
+		matchers::{method, path},
+		Mock, MockServer, ResponseTemplate,
+	};
+
+	use super::*;
+
+	fn config(seed_urls: Vec<String>, sitemap_url: Option<String>) -> WebCrawlerConfig {
+		WebCrawlerConfig {
+			seed_urls,
+			sitemap_url,
+			allow_patterns: vec![],
+			deny_patterns: vec![],
+			max_depth: None,
+			max_pages: None,
+			crawl_delay_ms: Some(1),
+			user_agent: None,
+			id: "web".to_string(),
+		}
+	}
+
+	async fn mock_page(server: &MockServer, route: &str, body: &str, expected_calls: Option<u64>) {
+		let mock = Mock::given(method("GET")).and(path(route)).respond_with(
+			ResponseTemplate::new(200).set_body_raw(body.to_string(), "text/html; charset=utf-8"),
+		);
+		match expected_calls {
+			Some(calls) => mock.expect(calls).mount(server).await,
+			None => mock.mount(server).await,
+		}
+	}
+
+	async fn crawl(source: &WebCrawlerSource) -> Vec<(String, PathBuf, String)> {
+		let mut stream = source.poll_data().await.unwrap();
+		let mut pages = Vec::new();
+		while let Some(item) = stream.next().await {
+			let mut item = item.unwrap();
+			let mut html = String::new();
+			item.data.take().unwrap().read_to_string(&mut html).await.unwrap();
+			pages.push((item.doc_source.unwrap(), item.file.unwrap(), html));
+		}
+		pages
+	}
+
+	#[tokio::test]
+	async fn test_crawl_follows_links_within_limits() {
+		let server = MockServer::start().await;
+		let base = server.uri();
+		Mock::given(method("GET"))
+			.and(path("/robots.txt"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+			)
+			.mount(&server)
+			.await;
+		mock_page(
+			&server,
+			"/",
+			r#"<html><body>
+				<a href="/guide">Guide</a>
+				<a href="/guide?utm_source=home#intro">Guide again</a>
+				<a href="/private/keys">Keys</a>
+				<a href="/skip/me">Skipped</a>
+				<a href="https://elsewhere.example.com/">Elsewhere</a>
+				<a href="/hidden">Hidden</a>
+				<a href="/deep">Deep</a>
+			</body></html>"#,
+			None,
+		)
+		.await;
+		mock_page(
+			&server,
+			"/guide",
+			r#"<html><body><a href="/copy">Copy</a></body></html>"#,
+			Some(1),
+		)
+		.await;
+		mock_page(
+			&server,
+			"/copy",
+			r#"<html><head><link rel="canonical" href="/guide"></head><body>Copy</body></html>"#,
+			Some(1),
+		)
+		.await;
+		mock_page(&server, "/private/keys", "<html><body>Keys</body></html>", Some(0)).await;
+		mock_page(&server, "/skip/me", "<html><body>Skipped</body></html>", Some(0)).await;
+		mock_page(
+			&server,
+			"/hidden",
+			r#"<html><head><meta name="robots" content="noindex"></head><body>Hidden</body></html>"#,
+			Some(1),
+		)
+		.await;
+		mock_page(
+			&server,
+			"/deep",
+			r#"<html><body><a href="/deeper">Deeper</a></body></html>"#,
+			None,
+		)
+		.await;
+		mock_page(
+			&server,
+			"/deeper",
+			r#"<html><body><a href="/deepest">Deepest</a></body></html>"#,
+			Some(1),
+		)
+		.await;
+		mock_page(&server, "/deepest", "<html><body>Deepest</body></html>", Some(0)).await;
+
+		let mut crawler_config = config(vec![format!("{}/", base)], None);
+		crawler_config.deny_patterns = vec!["/skip/".to_string()];
+		crawler_config.max_depth = Some(2);
+		let source = WebCrawlerSource::new(crawler_config).await.unwrap();
+		assert!(source.check_connectivity().await.is_ok());
+
+		let pages = crawl(&source).await;
+		let sources: Vec<&str> = pages.iter().map(|(doc_source, ..)| doc_source.as_str()).collect();
+		assert_eq!(
+			sources,
+			vec![
+				format!("{}/", base),
+				format!("{}/guide", base),
+				format!("{}/deep", base),
+				format!("{}/deeper", base),
+			]
+		);
+		assert_eq!(pages[1].1, PathBuf::from("127.0.0.1/guide.webpage"));
+		assert!(pages[1].2.contains("/copy"));
+	}
+
+	#[tokio::test]
+	async fn test_crawl_sitemap_with_robots_crawl_delay() {
+		let server = MockServer::start().await;
+		let base = server.uri();
+		Mock::given(method("GET"))
+			.and(path("/robots.txt"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_string("User-agent: *\nCrawl-delay: 0.1\n"),
+			)
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/sitemap.xml"))
+			.respond_with(ResponseTemplate::new(200).set_body_string(format!(
+				"<sitemapindex><sitemap><loc>{}/pages.xml</loc></sitemap></sitemapindex>",
+				base
+			)))
+			.mount(&server)
+			.await;
+		Mock::given(method("GET"))
+			.and(path("/pages.xml"))
+			.respond_with(ResponseTemplate::new(200).set_body_string(format!(
+				"<urlset><url><loc>{0}/a</loc></url><url><loc>{0}/b?x=1&amp;y=2</loc></url></urlset>",
+				base
+			)))
+			.mount(&server)
+			.await;
+		mock_page(&server, "/a", "<html><body>A</body></html>", Some(1)).await;
+		mock_page(&server, "/b", "<html><body>B</body></html>", Some(1)).await;
+
+		let mut crawler_config = config(vec![], Some(format!("{}/sitemap.xml", base)));
+		crawler_config.max_depth = Some(0);
+		let source = WebCrawlerSource::new(crawler_config).await.unwrap();
+
+		let started = Instant::now();
+		let pages = crawl(&source).await;
+		// Four rate limited requests, of which the limiter lets the first two through at once.
+		assert!(started.elapsed() >= Duration::from_millis(200));
+		let files: Vec<&PathBuf> = pages.iter().map(|(_, file, _)| file).collect();
+		assert_eq!(
+			files,
+			vec![
+				&PathBuf::from("127.0.0.1/a.webpage"),
+				&PathBuf::from("127.0.0.1/b_x_1_y_2.webpage")
+			]
+		);
+	}
+
+	#[tokio::test]
+	async fn test_crawl_checks_redirect_targets() {
+		let server = MockServer::start().await;
+		let base = server.uri();
+		Mock::given(method("GET"))
+			.and(path("/robots.txt"))
+			.respond_with(
+				ResponseTemplate::new(200).set_body_string("User-agent: *\nDisallow: /private\n"),
+			)
+			.mount(&server)
+			.await;
+		let redirects = [
+			("/old", "/new".to_string()),
+			("/sneaky", "/private/keys".to_string()),
+			("/away", "https://elsewhere.example.com/".to_string()),
+			("/loop", format!("{}/loop", base)),
+		];
+		for (route, location) in redirects {
+			Mock::given(method("GET"))
+				.and(path(route))
+				.respond_with(ResponseTemplate::new(301).insert_header("Location", location))
+				.expect(1)
+				.mount(&server)
+				.await;
+		}
+		mock_page(&server, "/new", "<html><body>New</body></html>", Some(1)).await;
+		mock_page(&server, "/private/keys", "<html><body>Keys</body></html>", Some(0)).await;
+
+		let seeds = ["/old", "/sneaky", "/away", "/loop"];
+		let crawler_config =
+			config(seeds.iter().map(|seed| format!("{}{}", base, seed)).collect(), None);
+		let source = WebCrawlerSource::new(crawler_config).await.unwrap();
+
+		let pages = crawl(&source).await;
+		let sources: Vec<&str> = pages.iter().map(|(doc_source, ..)| doc_source.as_str()).collect();
+		assert_eq!(sources, vec![format!("{}/new", base)]);
+	}
+
+	#[tokio::test]
+	async fn test_crawler_config_validation() {
+		assert!(WebCrawlerSource::new(config(vec![], None)).await.is_err());
+		assert!(WebCrawlerSource::new(config(vec!["not a url".to_string()], None))
+			.await
+			.is_err());
+		let mut invalid_pattern = config(vec!["https://docs.querent.xyz".to_string()], None);
+		invalid_pattern.allow_patterns = vec!["(".to_string()];
+		assert!(WebCrawlerSource::new(invalid_pattern).await.is_err());
+	}
+}
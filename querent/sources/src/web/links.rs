@@ -0,0 +1,195 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use url::Url;
+
+static SITEMAP_LOC: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?is)<loc>\s*(.*?)\s*</loc>").unwrap());
+
+/// What the crawler needs to know about a fetched page.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PageLinks {
+	/// Target of `<link rel="canonical">`.
+	pub canonical: Option<Url>,
+	/// Absolute http(s) links of the page, in document order.
+	pub links: Vec<Url>,
+	/// The robots meta tag asks not to index the page.
+	pub noindex: bool,
+	/// The robots meta tag asks not to follow the links of the page.
+	pub nofollow: bool,
+}
+
+fn attribute(tag: &tl::HTMLTag, name: &str) -> Option<String> {
+	tag.attributes()
+		.get(name)
+		.flatten()
+		.map(|value| value.as_utf8_str().trim().to_string())
+}
+
+fn tags<'a>(dom: &'a tl::VDom<'a>, name: &str) -> Vec<&'a tl::HTMLTag<'a>> {
+	let parser = dom.parser();
+	dom.query_selector(name)
+		.map(|handles| {
+			handles
+				.filter_map(|handle| handle.get(parser).and_then(|node| node.as_tag()))
+				.collect()
+		})
+		.unwrap_or_default()
+}
+
+/// Extracts the links, canonical URL and robots directives of a page fetched from `url`.
+pub fn parse_page(html: &str, url: &Url) -> PageLinks {
+	let Ok(dom) = tl::parse(html, tl::ParserOptions::default()) else {
+		return PageLinks::default();
+	};
+	let base = tags(&dom, "base")
+		.into_iter()
+		.find_map(|tag| attribute(tag, "href"))
+		.and_then(|href| url.join(&href).ok())
+		.unwrap_or_else(|| url.clone());
+	let resolve = |href: &str| {
+		base.join(href)
+			.ok()
+			.filter(|link| link.scheme() == "http" || link.scheme() == "https")
+	};
+
+	let mut page = PageLinks::default();
+	for tag in tags(&dom, "meta") {
+		let is_robots =
+			attribute(tag, "name").is_some_and(|name| name.eq_ignore_ascii_case("robots"));
+		if !is_robots {
+			continue;
+		}
+		let content = attribute(tag, "content").unwrap_or_default().to_lowercase();
+		page.noindex |= content.contains("noindex") || content.contains("none");
+		page.nofollow |= content.contains("nofollow") || content.contains("none");
+	}
+	page.canonical = tags(&dom, "link")
+		.into_iter()
+		.filter(|tag| {
+			attribute(tag, "rel").is_some_and(|rel| rel.eq_ignore_ascii_case("canonical"))
+		})
+		.find_map(|tag| attribute(tag, "href"))
+		.and_then(|href| resolve(&href));
+	for tag in tags(&dom, "a") {
+		let nofollow =
+			attribute(tag, "rel").is_some_and(|rel| rel.to_lowercase().contains("nofollow"));
+		if nofollow {
+			continue;
+		}
+		if let Some(link) = attribute(tag, "href").and_then(|href| resolve(&href)) {
+			page.links.push(link);
+		}
+	}
+	page
+}
+
+/// The form of a URL pages are de-duplicated by: no fragment, no `utm_` tracking
+/// parameters and the remaining query parameters sorted.
+pub fn canonical_url(url: &Url) -> Url {
+	let mut canonical = url.clone();
+	canonical.set_fragment(None);
+	let mut pairs: Vec<(String, String)> = url
+		.query_pairs()
+		.filter(|(key, _)| !key.starts_with("utm_"))
+		.map(|(key, value)| (key.to_string(), value.to_string()))
+		.collect();
+	if pairs.is_empty() {
+		canonical.set_query(None);
+	} else {
+		pairs.sort();
+		canonical.query_pairs_mut().clear().extend_pairs(pairs);
+	}
+	canonical
+}
+
+/// Reads the `<loc>` entries of a sitemap, and whether it is a sitemap index listing
+/// further sitemaps rather than pages.
+pub fn sitemap_locations(xml: &str) -> (bool, Vec<String>) {
+	let is_index = xml.contains("<sitemapindex");
+	let locations = SITEMAP_LOC
+		.captures_iter(xml)
+		.map(|captures| {
+			captures[1]
+				.replace("&lt;", "<")
+				.replace("&gt;", ">")
+				.replace("&quot;", "\"")
+				.replace("&apos;", "'")
+				.replace("&amp;", "&")
+		})
+		.collect();
+	(is_index, locations)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_parse_page() {
+		let html = r#"<html><head>
+			<link rel="canonical" href="/guide/install">
+			<meta name="robots" content="index, nofollow">
+			</head><body>
+			<a href="setup#step-2">Setup</a>
+			<a href="https://other.example.com/page">Elsewhere</a>
+			<a href="mailto:docs@querent.xyz">Mail</a>
+			<a href="/login" rel="nofollow">Login</a>
+			</body></html>"#;
+		let url = Url::parse("https://docs.querent.xyz/guide/install?utm_source=mail").unwrap();
+		let page = parse_page(html, &url);
+		assert_eq!(page.canonical, Url::parse("https://docs.querent.xyz/guide/install").ok());
+		assert_eq!(
+			page.links,
+			vec![
+				Url::parse("https://docs.querent.xyz/guide/setup#step-2").unwrap(),
+				Url::parse("https://other.example.com/page").unwrap(),
+			]
+		);
+		assert!(!page.noindex);
+		assert!(page.nofollow);
+	}
+
+	#[test]
+	fn test_canonical_url() {
+		let url = Url::parse("https://docs.querent.xyz/a?b=2&utm_medium=x&a=1#top").unwrap();
+		assert_eq!(canonical_url(&url).as_str(), "https://docs.querent.xyz/a?a=1&b=2");
+		let url = Url::parse("https://docs.querent.xyz/a?utm_source=mail").unwrap();
+		assert_eq!(canonical_url(&url).as_str(), "https://docs.querent.xyz/a");
+	}
+
+	#[test]
+	fn test_sitemap_locations() {
+		let xml = r#"<?xml version="1.0"?>
+			<urlset><url><loc> https://docs.querent.xyz/a?x=1&amp;y=2 </loc></url>
+			<url><loc>https://docs.querent.xyz/b</loc></url></urlset>"#;
+		assert_eq!(
+			sitemap_locations(xml),
+			(
+				false,
+				vec![
+					"https://docs.querent.xyz/a?x=1&y=2".to_string(),
+					"https://docs.querent.xyz/b".to_string()
+				]
+			)
+		);
+		let index = "<sitemapindex><sitemap><loc>https://docs.querent.xyz/s1.xml</loc></sitemap></sitemapindex>";
+		assert!(sitemap_locations(index).0);
+	}
+}
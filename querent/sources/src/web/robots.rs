@@ -0,0 +1,195 @@
+// Copyright (C) 2023 QuerentAI LLC.
+// This file is part of Querent.
+
+// The Licensed Work is licensed under the Business Source License 1.1 (BSL 1.1).
+// You may use this file in compliance with the BSL 1.1, subject to the following restrictions:
+// 1. You may not use the Licensed Work for AI-related services, database services,
+//    or any service or product offering that provides database, big data, or analytics
+//    services to third parties unless explicitly authorized by QuerentAI LLC.
+// 2. For more details, see the LICENSE file or visit https://mariadb.com/bsl11/.
+
+// For inquiries about alternative licensing arrangements, please contact contact@querent.xyz.
+
+// The Licensed Work is provided "AS IS", WITHOUT WARRANTY OF ANY KIND, express or implied,
+// including but not limited to the warranties of merchantability, fitness for a particular purpose,
+// and non-infringement. See the Business Source License for more details.
+
+// This software includes code developed by QuerentAI LLC (https://querent.xyz).
+
+use std::time::Duration;
+
+/// The robots.txt rules that apply to one user agent.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RobotsTxt {
+	/// `(allowed, pattern)` pairs, the longest matching pattern decides.
+	rules: Vec<(bool, String)>,
+	pub crawl_delay: Option<Duration>,
+	pub sitemaps: Vec<String>,
+}
+
+impl RobotsTxt {
+	/// Used when a site has no robots.txt.
+	pub fn allow_all() -> Self {
+		RobotsTxt::default()
+	}
+
+	/// Used when a site's robots.txt could not be read because of a server error.
+	pub fn disallow_all() -> Self {
+		RobotsTxt { rules: vec![(false, "/".to_string())], ..Default::default() }
+	}
+
+	/// Keeps the groups naming the product token of `user_agent`, or the `*` group when
+	/// none does.
+	pub fn parse(content: &str, user_agent: &str) -> Self {
+		let product = user_agent.split('/').next().unwrap_or_default().trim().to_lowercase();
+		let mut groups: Vec<(Vec<String>, Vec<(bool, String)>, Option<Duration>)> = Vec::new();
+		let mut sitemaps = Vec::new();
+		// Consecutive user-agent lines share the rules that follow them.
+		let mut collecting_agents = false;
+
+		for line in content.lines() {
+			let line = line.split('#').next().unwrap_or_default().trim();
+			let Some((key, value)) = line.split_once(':') else {
+				continue;
+			};
+			let value = value.trim();
+			match key.trim().to_lowercase().as_str() {
+				"user-agent" => {
+					if !collecting_agents {
+						groups.push((Vec::new(), Vec::new(), None));
+						collecting_agents = true;
+					}
+					if let Some(group) = groups.last_mut() {
+						group.0.push(value.to_lowercase());
+					}
+				},
+				"allow" | "disallow" => {
+					collecting_agents = false;
+					// An empty disallow allows everything, which is the default anyway.
+					if let (Some(group), false) = (groups.last_mut(), value.is_empty()) {
+						group.1.push((key.trim().eq_ignore_ascii_case("allow"), value.to_string()));
+					}
+				},
+				"crawl-delay" => {
+					collecting_agents = false;
+					if let (Some(group), Ok(seconds)) = (groups.last_mut(), value.parse::<f64>()) {
+						if seconds.is_finite() && seconds >= 0.0 {
+							group.2 = Some(Duration::from_secs_f64(seconds));
+						}
+					}
+				},
+				"sitemap" => sitemaps.push(value.to_string()),
+				_ => {},
+			}
+		}
+
+		let named =
+			|agent: &String| agent != "*" && !agent.is_empty() && product.contains(agent.as_str());
+		let specific = groups.iter().any(|(agents, ..)| agents.iter().any(named));
+		let applies = |agents: &Vec<String>| {
+			if specific {
+				agents.iter().any(named)
+			} else {
+				agents.iter().any(|agent| agent == "*")
+			}
+		};
+
+		let mut robots = RobotsTxt { sitemaps, ..Default::default() };
+		for (_, rules, crawl_delay) in groups.into_iter().filter(|(agents, ..)| applies(agents)) {
+			robots.rules.extend(rules);
+			robots.crawl_delay = robots.crawl_delay.max(crawl_delay);
+		}
+		robots
+	}
+
+	/// Whether `path`, with its query, may be fetched. Allow wins when an allow and a
+	/// disallow pattern of the same length match.
+	pub fn is_allowed(&self, path: &str) -> bool {
+		let mut decision: Option<(usize, bool)> = None;
+		for (allowed, pattern) in &self.rules {
+			if !pattern_matches(pattern, path) {
+				continue;
+			}
+			let better = match decision {
+				None => true,
+				Some((length, previous)) =>
+					pattern.len() > length || (pattern.len() == length && *allowed && !previous),
+			};
+			if better {
+				decision = Some((pattern.len(), *allowed));
+			}
+		}
+		decision.is_none_or(|(_, allowed)| allowed)
+	}
+}
+
+/// Matches a robots.txt path pattern, where `*` stands for any sequence of characters
+/// and a trailing `$` anchors the pattern at the end of the path.
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+	let (pattern, anchored) = match pattern.strip_suffix('$') {
+		Some(pattern) => (pattern, true),
+		None => (pattern, false),
+	};
+	let parts: Vec<&str> = pattern.split('*').collect();
+	let Some(mut rest) = path.strip_prefix(parts[0]) else {
+		return false;
+	};
+	for (index, part) in parts.iter().enumerate().skip(1) {
+		if anchored && index == parts.len() - 1 {
+			return rest.ends_with(part);
+		}
+		match rest.find(part) {
+			Some(position) => rest = &rest[position + part.len()..],
+			None => return false,
+		}
+	}
+	!anchored || rest.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const ROBOTS: &str = "
+		# Everyone
+		User-agent: *
+		Disallow: /private/
+		Allow: /private/handbook
+		Disallow: /*.pdf$
+		Crawl-delay: 2
+
+		User-agent: OtherBot
+		User-agent: QuerentBot
+		Disallow: /drafts
+		Crawl-delay: 0.5
+
+		Sitemap: https://docs.querent.xyz/sitemap.xml
+	";
+
+	#[test]
+	fn test_robots_rules_for_other_agents() {
+		let robots = RobotsTxt::parse(ROBOTS, "SomeBot/2.0");
+		assert!(robots.is_allowed("/guide/install"));
+		assert!(!robots.is_allowed("/private/keys"));
+		assert!(robots.is_allowed("/private/handbook/intro"));
+		assert!(!robots.is_allowed("/files/report.pdf"));
+		assert!(robots.is_allowed("/files/report.pdf?download=1"));
+		assert_eq!(robots.crawl_delay, Some(Duration::from_secs(2)));
+		assert_eq!(robots.sitemaps, vec!["https://docs.querent.xyz/sitemap.xml".to_string()]);
+	}
+
+	#[test]
+	fn test_robots_rules_for_named_agent() {
+		let robots = RobotsTxt::parse(ROBOTS, "QuerentBot/1.0");
+		assert!(robots.is_allowed("/private/keys"));
+		assert!(!robots.is_allowed("/drafts/next-release"));
+		assert_eq!(robots.crawl_delay, Some(Duration::from_millis(500)));
+	}
+
+	#[test]
+	fn test_robots_defaults() {
+		assert!(RobotsTxt::allow_all().is_allowed("/anything"));
+		assert!(!RobotsTxt::disallow_all().is_allowed("/anything"));
+		assert!(RobotsTxt::parse("User-agent: *\nDisallow:\n", "QuerentBot").is_allowed("/"));
+	}
+}
@@ -61,6 +61,10 @@ impl DataSource for ZipSource {
 		Ok(())
 	}
 
+	fn source_id(&self) -> &str {
+		&self.source_id
+	}
+
 	async fn get_slice(&self, _path: &Path, _range: Range<usize>) -> SourceResult<Vec<u8>> {
 		Ok(vec![])
 	}
@@ -151,6 +155,7 @@ impl DataSource for ZipSource {
 						size: Some(file_data.len()),
 						source_id: source_id.clone(),
 						_owned_permit: None,
+						content_type: None,
 						image_id: None,
 						extension: Some(extension),
 					};
@@ -230,6 +235,7 @@ impl DataSource for ZipSource {
 						size: Some(buffer.len()),
 						source_id: source_id.clone(),
 						_owned_permit: None,
+						content_type: None,
 						image_id: None,
 						extension: Some(extension),
 					};
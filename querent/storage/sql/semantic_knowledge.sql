@@ -10,5 +10,14 @@ CREATE TABLE semantic_knowledge (
     collection_id VARCHAR,
     image_id VARCHAR,
     event_id VARCHAR,
-    source_id VARCHAR
+    source_id VARCHAR,
+    origin VARCHAR DEFAULT 'extracted',
+    subject_id VARCHAR,
+    object_id VARCHAR,
+    section_path VARCHAR,
+    chunk_start INTEGER,
+    chunk_end INTEGER,
+    duplicate_of VARCHAR,
+    subject_coreference VARCHAR,
+    object_coreference VARCHAR
 );
@@ -63,6 +63,10 @@ impl Neo4jStorage {
 
 #[async_trait]
 impl FabricStorage for Neo4jStorage {
+	fn backend_name(&self) -> &'static str {
+		"neo4j"
+	}
+
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
 		// You can perform a simple query to check connectivity
 		let cypher_query = "RETURN 1";
@@ -157,9 +161,19 @@ impl FabricStorage for Neo4jStorage {
 				("document_source", source.clone()),
 				("collection_id", _collection_id.clone()),
 				("image_id", image_id_res.clone()),
+				("origin", data.origin.as_str().to_string()),
+				("subject_id", data.subject_id.clone().unwrap_or_default()),
+				("object_id", data.object_id.clone().unwrap_or_default()),
+				("section_path", data.section_path.clone().unwrap_or_default()),
+				("subject_coreference", data.subject_coreference.clone().unwrap_or_default()),
+				("object_coreference", data.object_coreference.clone().unwrap_or_default()),
+				("duplicate_of", data.duplicate_of.clone().unwrap_or_default()),
 			];
 
-			let parameterized_query = Query::new(cypher_query).params(params);
+			let parameterized_query = Query::new(cypher_query)
+				.params(params)
+				.param("chunk_start", data.chunk_start.map(i64::from))
+				.param("chunk_end", data.chunk_end.map(i64::from));
 			let tx_res = txn.execute(parameterized_query).await;
 			match tx_res {
 				Ok(_) => {
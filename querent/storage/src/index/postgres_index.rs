@@ -51,6 +51,15 @@ pub struct SemanticKnowledge {
 	pub image_id: Option<String>,
 	pub event_id: String,
 	pub source_id: String,
+	pub origin: Option<String>,
+	pub subject_id: Option<String>,
+	pub object_id: Option<String>,
+	pub section_path: Option<String>,
+	pub chunk_start: Option<i32>,
+	pub chunk_end: Option<i32>,
+	pub duplicate_of: Option<String>,
+	pub subject_coreference: Option<String>,
+	pub object_coreference: Option<String>,
 }
 
 // #[derive(Debug, Clone, Copy, FromSqlRow, AsExpression, Serialize)]
@@ -107,6 +116,10 @@ impl PostgresStorage {
 
 #[async_trait]
 impl FabricStorage for PostgresStorage {
+	fn backend_name(&self) -> &'static str {
+		"postgres"
+	}
+
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
 		let _ = self.pool.get().await?;
 		Ok(())
@@ -184,6 +197,15 @@ impl FabricStorage for PostgresStorage {
 						image_id: image_id.clone(),
 						event_id: item.event_id.clone(),
 						source_id: item.source_id.clone(),
+						origin: Some(item.origin.as_str().to_string()),
+						subject_id: item.subject_id.clone(),
+						object_id: item.object_id.clone(),
+						section_path: item.section_path.clone(),
+						chunk_start: item.chunk_start.map(|offset| offset as i32),
+						chunk_end: item.chunk_end.map(|offset| offset as i32),
+						duplicate_of: item.duplicate_of.clone(),
+						subject_coreference: item.subject_coreference.clone(),
+						object_coreference: item.object_coreference.clone(),
 					};
 					diesel::insert_into(semantic_knowledge::dsl::semantic_knowledge)
 						.values(form)
@@ -265,7 +287,16 @@ table! {
 		collection_id -> Nullable<Varchar>,
 		image_id -> Nullable<VarChar>,
 		event_id -> Varchar,
-		source_id -> Varchar
+		source_id -> Varchar,
+		origin -> Nullable<Varchar>,
+		subject_id -> Nullable<Varchar>,
+		object_id -> Nullable<Varchar>,
+		section_path -> Nullable<Varchar>,
+		chunk_start -> Nullable<Int4>,
+		chunk_end -> Nullable<Int4>,
+		duplicate_of -> Nullable<Varchar>,
+		subject_coreference -> Nullable<Varchar>,
+		object_coreference -> Nullable<Varchar>,
 	}
 }
 
@@ -24,8 +24,10 @@ use std::{
 use crate::{MetaStorage, StorageError, StorageErrorKind, StorageResult};
 use async_trait::async_trait;
 use proto::{
-	discovery::DiscoverySessionRequest, layer::LayerSessionRequest,
-	semantics::SemanticPipelineRequest, InsightAnalystRequest,
+	discovery::DiscoverySessionRequest,
+	layer::LayerSessionRequest,
+	semantics::{DocumentFingerprints, DocumentState, SemanticPipelineRequest},
+	InsightAnalystRequest,
 };
 use redb::{Database, ReadableTable, TableDefinition};
 use std::path::PathBuf;
@@ -39,6 +41,21 @@ const TABLE_INSIGHT_SESSIONS: TableDefinition<&str, &[u8]> =
 const TABLE_LAYER_SESSIONS: TableDefinition<&str, &[u8]> =
 	TableDefinition::new("querent_layer_sessions");
 
+// Keyed by `{pipeline_id}/{document}`.
+const TABLE_DOCUMENT_STATES: TableDefinition<&str, &[u8]> =
+	TableDefinition::new("querent_document_states");
+
+// Keyed by `{pipeline_id}/{document}`.
+const TABLE_DOCUMENT_FINGERPRINTS: TableDefinition<&str, &[u8]> =
+	TableDefinition::new("querent_document_fingerprints");
+
+// Keyed by collector id.
+const TABLE_SOURCE_CHECKPOINTS: TableDefinition<&str, &str> =
+	TableDefinition::new("querent_source_checkpoints");
+
+// Keyed by normalized entity key, shared by all pipelines.
+const TABLE_ENTITY_IDS: TableDefinition<&str, &str> = TableDefinition::new("querent_entity_ids");
+
 pub struct MetaStore {
 	db: Arc<Database>,
 }
@@ -64,6 +81,10 @@ impl MetaStore {
 		write_txn.open_table(TABLE_DISCOVERY_SESSIONS).unwrap();
 		write_txn.open_table(TABLE_INSIGHT_SESSIONS).unwrap();
 		write_txn.open_table(TABLE_LAYER_SESSIONS).unwrap();
+		write_txn.open_table(TABLE_DOCUMENT_STATES).unwrap();
+		write_txn.open_table(TABLE_DOCUMENT_FINGERPRINTS).unwrap();
+		write_txn.open_table(TABLE_SOURCE_CHECKPOINTS).unwrap();
+		write_txn.open_table(TABLE_ENTITY_IDS).unwrap();
 		write_txn.commit().unwrap();
 
 		Self { db: Arc::new(db) }
@@ -363,6 +384,36 @@ impl MetaStorage for MetaStore {
 		Ok(session)
 	}
 
+	/// Get Layer session by id
+	async fn get_layer_session(
+		&self,
+		session_id: &String,
+	) -> StorageResult<Option<LayerSessionRequest>> {
+		let read_txn = self.db.begin_read().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let session = {
+			let table = read_txn.open_table(TABLE_LAYER_SESSIONS).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+			let value = table.get(session_id.as_str()).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+			match value {
+				Some(value) =>
+					Some(bincode::deserialize(value.value()).map_err(|e| StorageError {
+						kind: StorageErrorKind::Serialization,
+						source: Arc::new(anyhow::Error::from(e)),
+					})?),
+				None => None,
+			}
+		};
+		Ok(session)
+	}
+
 	/// Get all Insight sessions ran by this node
 	async fn get_all_insight_sessions(
 		&self,
@@ -462,4 +513,262 @@ impl MetaStorage for MetaStore {
 		};
 		Ok(session)
 	}
+
+	/// Set the outcome of a document processed by a semantic pipeline
+	async fn set_document_state(
+		&self,
+		pipeline_id: &String,
+		state: DocumentState,
+	) -> StorageResult<()> {
+		let key = format!("{}/{}", pipeline_id, state.document);
+		let encoded_data = bincode::serialize(&state).map_err(|e| StorageError {
+			kind: StorageErrorKind::Serialization,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let write_txn = self.db.begin_write().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		{
+			let mut table =
+				write_txn.open_table(TABLE_DOCUMENT_STATES).map_err(|e| StorageError {
+					kind: StorageErrorKind::Internal,
+					source: Arc::new(anyhow::Error::from(e)),
+				})?;
+			table.insert(key.as_str(), encoded_data.as_slice()).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+		}
+		write_txn.commit().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})
+	}
+
+	/// Get the outcomes of all documents processed by a semantic pipeline
+	async fn get_document_states(&self, pipeline_id: &String) -> StorageResult<Vec<DocumentState>> {
+		let prefix = format!("{}/", pipeline_id);
+		let read_txn = self.db.begin_read().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let mut states = Vec::new();
+		{
+			let table = read_txn.open_table(TABLE_DOCUMENT_STATES).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+			let iter = table.range(prefix.as_str()..).map_err(|err| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(err)),
+			})?;
+			for result in iter {
+				let (key_access_guard, value_access_guard) =
+					result.map_err(|err| StorageError {
+						kind: StorageErrorKind::Internal,
+						source: Arc::new(anyhow::Error::from(err)),
+					})?;
+				if !key_access_guard.value().starts_with(prefix.as_str()) {
+					break;
+				}
+				let state: DocumentState = bincode::deserialize(value_access_guard.value())
+					.map_err(|e| StorageError {
+						kind: StorageErrorKind::Serialization,
+						source: Arc::new(anyhow::Error::from(e)),
+					})?;
+				states.push(state);
+			}
+		}
+		Ok(states)
+	}
+
+	/// Set the fingerprints of a document read by a deduplicating semantic pipeline
+	async fn set_document_fingerprints(
+		&self,
+		pipeline_id: &String,
+		fingerprints: DocumentFingerprints,
+	) -> StorageResult<()> {
+		let key = format!("{}/{}", pipeline_id, fingerprints.document);
+		let encoded_data = bincode::serialize(&fingerprints).map_err(|e| StorageError {
+			kind: StorageErrorKind::Serialization,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let write_txn = self.db.begin_write().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		{
+			let mut table =
+				write_txn.open_table(TABLE_DOCUMENT_FINGERPRINTS).map_err(|e| StorageError {
+					kind: StorageErrorKind::Internal,
+					source: Arc::new(anyhow::Error::from(e)),
+				})?;
+			table.insert(key.as_str(), encoded_data.as_slice()).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+		}
+		write_txn.commit().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})
+	}
+
+	/// Get the fingerprints of all documents read by a deduplicating semantic pipeline
+	async fn get_document_fingerprints(
+		&self,
+		pipeline_id: &String,
+	) -> StorageResult<Vec<DocumentFingerprints>> {
+		let prefix = format!("{}/", pipeline_id);
+		let read_txn = self.db.begin_read().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let mut documents = Vec::new();
+		{
+			let table =
+				read_txn.open_table(TABLE_DOCUMENT_FINGERPRINTS).map_err(|e| StorageError {
+					kind: StorageErrorKind::Internal,
+					source: Arc::new(anyhow::Error::from(e)),
+				})?;
+			let iter = table.range(prefix.as_str()..).map_err(|err| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(err)),
+			})?;
+			for result in iter {
+				let (key_access_guard, value_access_guard) =
+					result.map_err(|err| StorageError {
+						kind: StorageErrorKind::Internal,
+						source: Arc::new(anyhow::Error::from(err)),
+					})?;
+				if !key_access_guard.value().starts_with(prefix.as_str()) {
+					break;
+				}
+				let fingerprints: DocumentFingerprints =
+					bincode::deserialize(value_access_guard.value()).map_err(|e| StorageError {
+						kind: StorageErrorKind::Serialization,
+						source: Arc::new(anyhow::Error::from(e)),
+					})?;
+				documents.push(fingerprints);
+			}
+		}
+		Ok(documents)
+	}
+
+	/// Set the position an incremental source reached in its last run
+	async fn set_source_checkpoint(
+		&self,
+		source_id: &String,
+		checkpoint: &String,
+	) -> StorageResult<()> {
+		let write_txn = self.db.begin_write().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		{
+			let mut table =
+				write_txn.open_table(TABLE_SOURCE_CHECKPOINTS).map_err(|e| StorageError {
+					kind: StorageErrorKind::Internal,
+					source: Arc::new(anyhow::Error::from(e)),
+				})?;
+			table
+				.insert(source_id.as_str(), checkpoint.as_str())
+				.map_err(|e| StorageError {
+					kind: StorageErrorKind::Internal,
+					source: Arc::new(anyhow::Error::from(e)),
+				})?;
+		}
+		write_txn.commit().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})
+	}
+
+	/// Get the position an incremental source reached in its last run
+	async fn get_source_checkpoint(&self, source_id: &String) -> StorageResult<Option<String>> {
+		let read_txn = self.db.begin_read().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let table = read_txn.open_table(TABLE_SOURCE_CHECKPOINTS).map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let value = table.get(source_id.as_str()).map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		Ok(value.map(|value| value.value().to_string()))
+	}
+
+	/// Set the canonical ids of resolved entity keys that have none yet, and return the ids
+	/// stored for all of the given keys
+	async fn set_entity_ids(
+		&self,
+		entity_ids: Vec<(String, String)>,
+	) -> StorageResult<Vec<(String, String)>> {
+		let write_txn = self.db.begin_write().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let mut stored = Vec::with_capacity(entity_ids.len());
+		{
+			let mut table = write_txn.open_table(TABLE_ENTITY_IDS).map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+			for (key, id) in entity_ids {
+				let existing = table
+					.get(key.as_str())
+					.map_err(|e| StorageError {
+						kind: StorageErrorKind::Internal,
+						source: Arc::new(anyhow::Error::from(e)),
+					})?
+					.map(|value| value.value().to_string());
+				// the first id recorded for a key wins, also over concurrently running pipelines
+				let id = match existing {
+					Some(existing) => existing,
+					None => {
+						table.insert(key.as_str(), id.as_str()).map_err(|e| StorageError {
+							kind: StorageErrorKind::Internal,
+							source: Arc::new(anyhow::Error::from(e)),
+						})?;
+						id
+					},
+				};
+				stored.push((key, id));
+			}
+		}
+		write_txn.commit().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		Ok(stored)
+	}
+
+	/// Get the canonical ids of all resolved entity keys
+	async fn get_entity_ids(&self) -> StorageResult<Vec<(String, String)>> {
+		let read_txn = self.db.begin_read().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let table = read_txn.open_table(TABLE_ENTITY_IDS).map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let iter = table.iter().map_err(|e| StorageError {
+			kind: StorageErrorKind::Internal,
+			source: Arc::new(anyhow::Error::from(e)),
+		})?;
+		let mut entity_ids = Vec::new();
+		for result in iter {
+			let (key, id) = result.map_err(|e| StorageError {
+				kind: StorageErrorKind::Internal,
+				source: Arc::new(anyhow::Error::from(e)),
+			})?;
+			entity_ids.push((key.value().to_string(), id.value().to_string()));
+		}
+		Ok(entity_ids)
+	}
 }
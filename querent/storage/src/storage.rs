@@ -22,8 +22,10 @@ use crate::{
 use async_trait::async_trait;
 use common::{DocumentPayload, SemanticKnowledgePayload, VectorPayload};
 use proto::{
-	discovery::DiscoverySessionRequest, layer::LayerSessionRequest,
-	semantics::SemanticPipelineRequest, InsightAnalystRequest,
+	discovery::DiscoverySessionRequest,
+	layer::LayerSessionRequest,
+	semantics::{DocumentFingerprints, DocumentState, SemanticPipelineRequest},
+	InsightAnalystRequest,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -108,6 +110,11 @@ impl StorageError {
 /// Currently we support Graph, Vector and Index storages.
 #[async_trait]
 pub trait FabricStorage: Send + Sync + 'static {
+	/// Name of the storage backend, used to label metrics.
+	fn backend_name(&self) -> &'static str {
+		"unknown"
+	}
+
 	/// Check storage connection if applicable
 	async fn check_connectivity(&self) -> anyhow::Result<()>;
 
@@ -315,6 +322,76 @@ pub trait MetaStorage: Send + Sync {
 	async fn get_all_layer_sessions(&self) -> StorageResult<Vec<(String, LayerSessionRequest)>> {
 		Ok(vec![])
 	}
+
+	/// Get Layer session by id
+	async fn get_layer_session(
+		&self,
+		_session_id: &String,
+	) -> StorageResult<Option<LayerSessionRequest>> {
+		Ok(None)
+	}
+
+	/// Set the outcome of a document processed by a semantic pipeline
+	async fn set_document_state(
+		&self,
+		_pipeline_id: &String,
+		_state: DocumentState,
+	) -> StorageResult<()> {
+		Ok(())
+	}
+
+	/// Get the outcomes of all documents processed by a semantic pipeline
+	async fn get_document_states(
+		&self,
+		_pipeline_id: &String,
+	) -> StorageResult<Vec<DocumentState>> {
+		Ok(vec![])
+	}
+
+	/// Set the fingerprints of a document read by a deduplicating semantic pipeline
+	async fn set_document_fingerprints(
+		&self,
+		_pipeline_id: &String,
+		_fingerprints: DocumentFingerprints,
+	) -> StorageResult<()> {
+		Ok(())
+	}
+
+	/// Get the fingerprints of all documents read by a deduplicating semantic pipeline
+	async fn get_document_fingerprints(
+		&self,
+		_pipeline_id: &String,
+	) -> StorageResult<Vec<DocumentFingerprints>> {
+		Ok(vec![])
+	}
+
+	/// Set the position an incremental source reached in its last run
+	async fn set_source_checkpoint(
+		&self,
+		_source_id: &String,
+		_checkpoint: &String,
+	) -> StorageResult<()> {
+		Ok(())
+	}
+
+	/// Get the position an incremental source reached in its last run
+	async fn get_source_checkpoint(&self, _source_id: &String) -> StorageResult<Option<String>> {
+		Ok(None)
+	}
+
+	/// Set the canonical ids of resolved entity keys that have none yet, and return the ids
+	/// stored for all of the given keys
+	async fn set_entity_ids(
+		&self,
+		entity_ids: Vec<(String, String)>,
+	) -> StorageResult<Vec<(String, String)>> {
+		Ok(entity_ids)
+	}
+
+	/// Get the canonical ids of all resolved entity keys
+	async fn get_entity_ids(&self) -> StorageResult<Vec<(String, String)>> {
+		Ok(vec![])
+	}
 }
 
 impl Debug for dyn MetaStorage {
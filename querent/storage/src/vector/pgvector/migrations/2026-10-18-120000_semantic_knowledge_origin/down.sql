@@ -0,0 +1 @@
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS origin;
@@ -0,0 +1 @@
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS origin VARCHAR DEFAULT 'extracted';
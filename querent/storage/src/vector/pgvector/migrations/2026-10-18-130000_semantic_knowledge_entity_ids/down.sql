@@ -0,0 +1,2 @@
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS object_id;
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS subject_id;
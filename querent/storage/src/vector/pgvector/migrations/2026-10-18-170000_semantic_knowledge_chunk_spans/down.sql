@@ -0,0 +1,3 @@
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS chunk_end;
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS chunk_start;
+ALTER TABLE semantic_knowledge DROP COLUMN IF EXISTS section_path;
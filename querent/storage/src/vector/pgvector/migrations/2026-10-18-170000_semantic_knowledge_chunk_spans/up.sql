@@ -0,0 +1,3 @@
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS section_path VARCHAR;
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS chunk_start INTEGER;
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS chunk_end INTEGER;
@@ -0,0 +1 @@
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS duplicate_of VARCHAR;
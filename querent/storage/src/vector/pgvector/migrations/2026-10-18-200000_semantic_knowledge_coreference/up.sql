@@ -0,0 +1,2 @@
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS subject_coreference VARCHAR;
+ALTER TABLE semantic_knowledge ADD COLUMN IF NOT EXISTS object_coreference VARCHAR;
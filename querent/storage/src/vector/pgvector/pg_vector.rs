@@ -82,6 +82,10 @@ impl PGVector {
 
 #[async_trait]
 impl FabricStorage for PGVector {
+	fn backend_name(&self) -> &'static str {
+		"pgvector"
+	}
+
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
 		let _ = self.pool.get().await?;
 		Ok(())
@@ -668,7 +672,7 @@ impl FabricAccessor for PGVector {
 		collection_id: &str,
 	) -> StorageResult<Vec<FilteredSemanticKnowledge>> {
 		let query = format!(
-			"SELECT subject, subject_type, object, object_type, sentence, image_id, event_id, source_id, document_source, document_id
+			"SELECT subject, subject_type, object, object_type, sentence, image_id, event_id, source_id, document_source, document_id, origin, subject_id, object_id, section_path, chunk_start, chunk_end, duplicate_of, subject_coreference, object_coreference
 			FROM semantic_knowledge 
 			WHERE collection_id = $1"
 		);
@@ -382,7 +382,7 @@ pub async fn fetch_documents_for_embedding(
 
 use diesel::{
 	sql_query,
-	sql_types::{Float4, Float8, Text},
+	sql_types::{Float4, Float8, Integer, Text},
 	QueryableByName,
 };
 
@@ -437,6 +437,24 @@ pub struct FilteredSemanticKnowledge {
 	pub document_source: String,
 	#[diesel(sql_type = Text)]
 	pub document_id: String,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub origin: Option<String>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub subject_id: Option<String>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub object_id: Option<String>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub section_path: Option<String>,
+	#[diesel(sql_type = Nullable<Integer>)]
+	pub chunk_start: Option<i32>,
+	#[diesel(sql_type = Nullable<Integer>)]
+	pub chunk_end: Option<i32>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub duplicate_of: Option<String>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub subject_coreference: Option<String>,
+	#[diesel(sql_type = Nullable<Text>)]
+	pub object_coreference: Option<String>,
 }
 
 pub async fn fetch_documents_for_embedding_pgembed(
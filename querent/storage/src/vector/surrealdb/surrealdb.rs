@@ -194,6 +194,10 @@ impl SurrealDB {
 
 #[async_trait]
 impl FabricStorage for SurrealDB {
+	fn backend_name(&self) -> &'static str {
+		"surrealdb"
+	}
+
 	async fn check_connectivity(&self) -> anyhow::Result<()> {
 		let _query_response = self.db.query("SELECT * FROM non_existing_table LIMIT 1;").await;
 		Ok(())
@@ -376,6 +380,15 @@ impl FabricStorage for SurrealDB {
 				image_id: image_id.clone(),
 				event_id: item.event_id.clone(),
 				source_id: item.source_id.clone(),
+				origin: Some(item.origin.as_str().to_string()),
+				subject_id: item.subject_id.clone(),
+				object_id: item.object_id.clone(),
+				section_path: item.section_path.clone(),
+				chunk_start: item.chunk_start.map(|offset| offset as i32),
+				chunk_end: item.chunk_end.map(|offset| offset as i32),
+				duplicate_of: item.duplicate_of.clone(),
+				subject_coreference: item.subject_coreference.clone(),
+				object_coreference: item.object_coreference.clone(),
 			};
 			let _created: Record = self
 				.db
@@ -478,7 +491,7 @@ impl FabricAccessor for SurrealDB {
 		collection_id: &str,
 	) -> StorageResult<Vec<FilteredSemanticKnowledge>> {
 		let query = format!(
-			"SELECT document_id, subject, subject_type, object, object_type, document_source, sentence, event_id, source_id, image_id FROM semantic_knowledge WHERE collection_id = '{}'",
+			"SELECT document_id, subject, subject_type, object, object_type, document_source, sentence, event_id, source_id, image_id, origin, subject_id, object_id, section_path, chunk_start, chunk_end, duplicate_of, subject_coreference, object_coreference FROM semantic_knowledge WHERE collection_id = '{}'",
 			collection_id
 		);
 		let mut response: Response = self.db.query(query).await.map_err(|e| StorageError {
@@ -504,6 +517,15 @@ impl FabricAccessor for SurrealDB {
 				source_id: item.source_id,
 				image_id: item.image_id,
 				document_id: item.document_id,
+				origin: item.origin,
+				subject_id: item.subject_id,
+				object_id: item.object_id,
+				section_path: item.section_path,
+				chunk_start: item.chunk_start,
+				chunk_end: item.chunk_end,
+				duplicate_of: item.duplicate_of,
+				subject_coreference: item.subject_coreference,
+				object_coreference: item.object_coreference,
 			})
 			.collect())
 	}
@@ -904,6 +926,7 @@ impl Storage for SurrealDB {}
 #[cfg(test)]
 mod tests {
 	use super::*;
+	use common::KnowledgeOrigin;
 	use tempfile::tempdir;
 	use tokio;
 	use uuid::Uuid;
@@ -1047,6 +1070,15 @@ mod tests {
 				predicate_type: "ptype_1".to_string(),
 				image_id: Some("".to_string()),
 				blob: Some("event_1".to_string()),
+				origin: KnowledgeOrigin::Extracted,
+				subject_id: None,
+				object_id: None,
+				section_path: None,
+				chunk_start: None,
+				chunk_end: None,
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: None,
 			},
 		)];
 
@@ -1101,6 +1133,15 @@ mod tests {
 				predicate_type: "ptype_1".to_string(),
 				image_id: Some("".to_string()),
 				blob: Some("event_1".to_string()),
+				origin: KnowledgeOrigin::Extracted,
+				subject_id: None,
+				object_id: None,
+				section_path: None,
+				chunk_start: None,
+				chunk_end: None,
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: None,
 			},
 		)];
 
@@ -1192,6 +1233,15 @@ mod tests {
 				predicate_type: "ptype_1".to_string(),
 				image_id: Some("".to_string()),
 				blob: Some("event_1".to_string()),
+				origin: KnowledgeOrigin::Extracted,
+				subject_id: None,
+				object_id: None,
+				section_path: None,
+				chunk_start: None,
+				chunk_end: None,
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: None,
 			},
 		)];
 
@@ -1230,6 +1280,15 @@ mod tests {
 					predicate_type: format!("ptype_{}", i),
 					image_id: Some(format!("image_{}", i)),
 					blob: Some(format!("blob_{}", i)),
+					origin: KnowledgeOrigin::Extracted,
+					subject_id: None,
+					object_id: None,
+					section_path: None,
+					chunk_start: None,
+					chunk_end: None,
+					subject_coreference: None,
+					object_coreference: None,
+					duplicate_of: None,
 				},
 			));
 		}
@@ -1300,6 +1359,15 @@ mod tests {
 				predicate_type: "ptype_1".to_string(),
 				image_id: Some("".to_string()),
 				blob: Some("event_1".to_string()),
+				origin: KnowledgeOrigin::Extracted,
+				subject_id: None,
+				object_id: None,
+				section_path: None,
+				chunk_start: None,
+				chunk_end: None,
+				subject_coreference: None,
+				object_coreference: None,
+				duplicate_of: None,
 			},
 		)];
 
@@ -12,6 +12,15 @@ DEFINE FIELD collection_id ON TABLE semantic_knowledge TYPE option<string>;
 DEFINE FIELD image_id ON TABLE semantic_knowledge TYPE option<string>;
 DEFINE FIELD event_id ON TABLE semantic_knowledge TYPE string;
 DEFINE FIELD source_id ON TABLE semantic_knowledge TYPE string;
+DEFINE FIELD origin ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD subject_id ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD object_id ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD section_path ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD chunk_start ON TABLE semantic_knowledge TYPE option<int>;
+DEFINE FIELD chunk_end ON TABLE semantic_knowledge TYPE option<int>;
+DEFINE FIELD duplicate_of ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD subject_coreference ON TABLE semantic_knowledge TYPE option<string>;
+DEFINE FIELD object_coreference ON TABLE semantic_knowledge TYPE option<string>;
 
 
 
@@ -244,7 +244,7 @@ export const universalConstant = 42;
  */
 
 /**
- * @typedef { { azure: AzureCollectorConfig } | { gcs: GcsCollectorConfig } | { s3: S3CollectorConfig } | { jira: JiraCollectorConfig } | { drive: GoogleDriveCollectorConfig } | { email: EmailCollectorConfig } | { dropbox: DropBoxCollectorConfig } | { github: GithubCollectorConfig } | { slack: SlackCollectorConfig } | { news: NewsCollectorConfig } | { files: FileCollectorConfig } | { onedrive: OneDriveConfig } | { notion: NotionConfig } | { osdu: OsduServiceConfig } } Backend
+ * @typedef { { azure: AzureCollectorConfig } | { gcs: GcsCollectorConfig } | { s3: S3CollectorConfig } | { jira: JiraCollectorConfig } | { drive: GoogleDriveCollectorConfig } | { email: EmailCollectorConfig } | { dropbox: DropBoxCollectorConfig } | { github: GithubCollectorConfig } | { slack: SlackCollectorConfig } | { news: NewsCollectorConfig } | { files: FileCollectorConfig } | { onedrive: OneDriveConfig } | { notion: NotionConfig } | { osdu: OsduServiceConfig } | { confluence: ConfluenceConfig } | { sharepoint: SharePointConfig } | { sql: SqlCollectorConfig } | { web: WebCrawlerConfig } | { kafka: KafkaCollectorConfig } } Backend
  */
 
 /**
@@ -258,7 +258,12 @@ export const universalConstant = 42;
 /**
  * CollectorConfig is a message to hold configuration for a collector.
  * Defines a collector with a specific configuration.
- * @typedef { { name: string; backend: Backend | null } } CollectorConfig
+ * @typedef { { name: string; backend: Backend | null; pdf_passwords?: string[] } } CollectorConfig
+ */
+
+/**
+ * ConfluenceConfig is a message to hold configuration for a Confluence collector.
+ * @typedef { { base_url: string; email: string; api_token: string; space_keys: string[]; include_attachments: boolean | null; id: string } } ConfluenceConfig
  */
 
 /**
@@ -289,6 +294,11 @@ export const universalConstant = 42;
  * @typedef { { imap_server: string; imap_port: number; imap_username: string; imap_password: string; imap_folder: string; id: string } } EmailCollectorConfig
  */
 
+/**
+ * A table found in a document: its header row and the data rows below it.
+ * @typedef { { headers: string[]; rows: TableRow[] } } ExtractedTable
+ */
+
 /**
  * FileCollectorConfig is a message to hold configuration for a file collector.
  * @typedef { { root_path: string; id: string } } FileCollectorConfig
@@ -318,7 +328,7 @@ export const universalConstant = 42;
  */
 
 /**
- * @typedef { { file: string; data: string[]; is_token_stream: boolean; doc_source: string; source_id: string; image_id: string | null } } IngestedTokens
+ * @typedef { { file: string; data: string[]; is_token_stream: boolean; doc_source: string; source_id: string; image_id: string | null; section_path: string | null; table: ExtractedTable | null } } IngestedTokens
  */
 
 /**
@@ -365,6 +375,11 @@ export const universalConstant = 42;
  * @typedef { { jira_server: string; jira_email: string; jira_api_key: string; jira_project: string; id: string } } JiraCollectorConfig
  */
 
+/**
+ * KafkaCollectorConfig is a message to hold configuration for a Kafka collector.
+ * @typedef { { bootstrap_servers: string; topics: string[]; group_id: string; payload_format: string | null; avro_schema: string | null; text_fields: string[]; security_protocol: string | null; sasl_mechanism: string | null; sasl_username: string | null; sasl_password: string | null; idle_timeout_ms: number | null; id: string } } KafkaCollectorConfig
+ */
+
 /**
  * @typedef { { config: CollectorConfig[] } } ListCollectorConfig
  */
@@ -375,7 +390,7 @@ export const universalConstant = 42;
  */
 
 /**
- * @typedef { { api_key: string; page_ids: string[]; id: string } } NotionConfig
+ * @typedef { { api_key: string; page_ids: string[]; id: string; crawl_workspace: boolean | null; recursive: boolean | null } } NotionConfig
  */
 
 /**
@@ -407,6 +422,11 @@ export const universalConstant = 42;
  * @typedef { { entities: string[] } } SampleEntities
  */
 
+/**
+ * SharePointConfig is a message to hold configuration for a SharePoint collector.
+ * @typedef { { client_id: string; client_secret: string; redirect_uri: string; refresh_token: string; tenant_id: string; sites: string[]; drives: string[]; include_lists: boolean | null; id: string } } SharePointConfig
+ */
+
 /**
  * @typedef { { collectors: string[]; fixed_entities: FixedEntities | null; sample_entities: SampleEntities | null; model: number | null } } SemanticPipelineRequest
  */
@@ -417,13 +437,27 @@ export const universalConstant = 42;
 
 /**
  * SlackCollectorConfig is a message to hold configuration for a Slack collector.
- * @typedef { { access_token: string; channel_name: string; oldest: string | null; inclusive: boolean | null; latest: string | null; id: string } } SlackCollectorConfig
+ * @typedef { { access_token: string; channel_name: string; oldest: string | null; inclusive: boolean | null; latest: string | null; id: string; channels: string | null; all_public_channels: boolean | null; include_threads: boolean | null; include_files: boolean | null } } SlackCollectorConfig
+ */
+
+/**
+ * SqlCollectorConfig is a message to hold configuration for a relational database collector.
+ * @typedef { { connection_url: string; queries: string[]; tables: string[]; row_template: string | null; incremental_column: string | null; id: string } } SqlCollectorConfig
+ */
+
+/**
+ * @typedef { { cells: string[] } } TableRow
  */
 
 /**
  * @typedef { { version: string; currentVersion: string; body: string | null } } UpdateResult
  */
 
+/**
+ * WebCrawlerConfig is a message to hold configuration for a web crawler collector.
+ * @typedef { { seed_urls: string[]; sitemap_url: string | null; allow_patterns: string[]; deny_patterns: string[]; max_depth: number | null; max_pages: number | null; crawl_delay_ms: number | null; user_agent: string | null; id: string } } WebCrawlerConfig
+ */
+
 /** tauri-specta globals **/
 
 import { invoke as TAURI_INVOKE, Channel as TAURI_CHANNEL } from '@tauri-apps/api/core';